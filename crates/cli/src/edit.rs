@@ -8,10 +8,13 @@ use std::{
 use clap::Args;
 
 use votable::{
-  data::TableOrBinOrBin2, error::VOTableError, iter::SimpleVOTableRowIterator,
-  votable::new_xml_writer, CooSys, Description, Field, FieldRef, Group, Info, Link, Max, Min, Opt,
-  Param, ParamRef, Resource, TableDataContent, TableGroup, TimeSys, VOTableElement, Values,
-  VoidTableDataContent,
+  data::TableOrBinOrBin2,
+  error::VOTableError,
+  iter::SimpleVOTableRowIterator,
+  mivot::{Globals, Model, Report, Templates, Vodml},
+  votable::new_xml_writer,
+  CooSys, Description, Field, FieldRef, Group, Info, Link, Max, Min, Opt, Param, ParamRef,
+  Resource, TableDataContent, TableGroup, TimeSys, VOTableElement, Values, VoidTableDataContent,
 };
 
 #[cfg(feature = "vizier")]
@@ -32,14 +35,17 @@ pub struct Edit {
   output: Output,
   /// List of "TAG CONDITION ACTION ARGS", e.g.:
   /// -e 'INFO name=Target rm' -e 'FIELD ID=RA set_attrs ucd=pos.eq.ra;meta.main unit=deg'
+  /// -e 'INSTANCE dmid=_spacesys_icrs rm'
   /// CONDITIONS:
-  ///   name=VAL  name (if any) equals a given value
-  ///     id=VAL  id (if any) equals a given value
-  ///    vid=VAL  virtual id equals a given value
+  ///     name=VAL  name (if any) equals a given value
+  ///       id=VAL  id (if any) equals a given value
+  ///      vid=VAL  virtual id equals a given value
+  ///     dmid=VAL  MIVOT dmid (if any) equals a given value
+  ///   dmrole=VAL  MIVOT dmrole (if any) equals a given value
   /// ACTIONS ARGS:
   ///   rm                                                 Remove the TAG
   ///   set_attrs        KEY=VAL (KEY=VAL) ...               Set TAG attributes
-  ///   set_content      CONTENT                             Set the content for `DESCRIPTION`, `INFO`, `LINK`, `PARAMRef` or `FIELDRef`
+  ///   set_content      CONTENT                             Set the content for `DESCRIPTION`, `INFO`, `LINK`, `PARAMRef`, `FIELDRef` or `REPORT`
   ///   set_desc         DESC                                Set the `DESCRIPTION` for `VOTABLE`, `RESOURCE`, `TABLE`, `FIELD`, `PARAM` or `GROUP`
   ///   push_timesys     KEY=VAL (KEY=VAL) ...               Push a new `TIMESYS` in `VOTABLE` or `RESOURCE`
   ///   set_min          KEY=VAL (KEY=VAL) ...               Set a new `MIN` for `VALUES`.
@@ -58,6 +64,13 @@ pub struct Edit {
   ///   push_field       KEY=VAL (KEY=VAL) ... (SUB_ACTIONS) Push the new `FIELD` in `TABLE`.
   ///   prepend_resource KEY=VAL (KEY=VAL) ... (SUB_ACTIONS) Preprend the new `RESOURCE` in `VOTABLE` or `RESOURCE`.
   ///   push_resource    KEY=VAL (KEY=VAL) ... (SUB_ACTIONS) Push the new `RESOURCE` in `VOTABLE` or `RESOURCE`.
+  ///   push_vodml       KEY=VAL (KEY=VAL) ... (SUB_ACTIONS) Push the new MIVOT `VODML` in a `RESOURCE` not having one.
+  ///   set_report       KEY=VAL (KEY=VAL) ... (SUB_ACTION)  Set the new `REPORT` in `VODML`.
+  ///   push_model       KEY=VAL (KEY=VAL) ...               Push the new `MODEL` in `VODML`.
+  ///   set_globals                            (SUB_ACTIONS) Set the new `GLOBALS` in `VODML`.
+  ///   push_templates   (KEY=VAL) ...         (SUB_ACTIONS) Push the new `TEMPLATES` in `VODML`.
+  ///   push_instance    KEY=VAL (KEY=VAL) ... (SUB_ACTIONS) Push the new `INSTANCE` in `GLOBALS`, `TEMPLATES` or `INSTANCE`.
+  ///   push_attribute   KEY=VAL (KEY=VAL) ...               Push the new `ATTRIBUTE` in `INSTANCE`.
   /// SUB ACTIONS:
   ///   Sub-actions are the same as the ACTIONS (except `rm` which is not allowed).
   ///   A sub-action stars with a `@`. Actually one can see `@` as an action separator with the
@@ -112,6 +125,7 @@ impl Edit {
             .visit(&mut visitor)
             .map_err(|e| VOTableError::Custom(e.to_string()))
             .and_then(|_| {
+              visitor.update_mivot_refs(&mut vot);
               #[cfg(feature = "vizier")]
               if self.vizier_org_names {
                 vot
@@ -191,6 +205,7 @@ impl Edit {
     let mut vot = it.votable.clone();
     let mut visitor = UpdateVisitor::new(self.elems);
     vot.visit(&mut visitor)?; // Modif and write the cloned version to avoid destructive modifications
+    visitor.update_mivot_refs(&mut vot);
     #[cfg(feature = "vizier")]
     if self.vizier_org_names {
       vot
@@ -203,7 +218,10 @@ impl Edit {
         .and_then(|mut out_vot| {
           out_vot
             .visit(&mut visitor) // Re-visit the full VOTable before writting its tail
-            .and_then(|()| out_vot.write_from_data_end(&mut writer, &(), false))
+            .and_then(|()| {
+              visitor.update_mivot_refs(&mut out_vot);
+              out_vot.write_from_data_end(&mut writer, &(), false)
+            })
         })
     } else {
       // No table in the VOTable
@@ -264,6 +282,10 @@ pub enum Condition {
   IdEq(String),
   /// `name=NAME`: element match the given name
   NameEq(String),
+  /// `dmid=DMID`: MIVOT element match the given dmid
+  DmIdEq(String),
+  /// `dmrole=DMROLE`: MIVOT element match the given dmrole
+  DmRoleEq(String),
 }
 impl Condition {
   pub fn is_ok(&self, vid: &str, id: Option<&String>, name: Option<&String>) -> bool {
@@ -273,6 +295,22 @@ impl Condition {
       Self::NameEq(s) => name
         .map(|name| name.as_str() == s.as_str())
         .unwrap_or(false),
+      Self::DmIdEq(_) | Self::DmRoleEq(_) => false,
+    }
+  }
+
+  /// Same as `is_ok` for MIVOT elements, which have no `ID` nor `name` but may have a `dmid`
+  /// and/or a `dmrole`.
+  pub fn is_ok_mivot(&self, vid: &str, dmid: Option<&String>, dmrole: Option<&String>) -> bool {
+    match self {
+      Self::VirtualIdEq(s) => vid == s.as_str(),
+      Self::DmIdEq(s) => dmid
+        .map(|dmid| dmid.as_str() == s.as_str())
+        .unwrap_or(false),
+      Self::DmRoleEq(s) => dmrole
+        .map(|dmrole| dmrole.as_str() == s.as_str())
+        .unwrap_or(false),
+      Self::IdEq(_) | Self::NameEq(_) => false,
     }
   }
 }
@@ -285,8 +323,10 @@ impl FromStr for Condition {
         "vid" => Ok(Self::VirtualIdEq(val.to_string())),
         "id" => Ok(Self::IdEq(val.to_string())),
         "name" => Ok(Self::NameEq(val.to_string())),
+        "dmid" => Ok(Self::DmIdEq(val.to_string())),
+        "dmrole" => Ok(Self::DmRoleEq(val.to_string())),
         _ => Err(VOTableError::Custom(format!(
-          "Unrecognized condition. Actual: {}. Expected: one of [vid, id, name].",
+          "Unrecognized condition. Actual: {}. Expected: one of [vid, id, name, dmid, dmrole].",
           key
         ))),
       },
@@ -346,6 +386,24 @@ pub enum Action {
   PushResource {
     resource: Resource<VoidTableDataContent>,
   },
+  /// `push_vodml KEY=VAL ... @set_report ... @push_model ... @set_globals ... @push_templates ...` push the new `VODML` in `RESOURCE`.
+  PushVodml { vodml: Vodml },
+  /// `set_report status=OK @set_content CONTENT` set the new `REPORT` in `VODML`.
+  SetReport { report: Report },
+  /// `push_model name=NAME url=URL` push the new `MODEL` in `VODML`.
+  PushModel { model: Model },
+  /// `set_globals @push_instance ... @@push_attribute ...` set the new `GLOBALS` in `VODML`.
+  SetGlobals { globals: Globals },
+  /// `push_templates tableref=REF @push_instance ... @@push_attribute ...` push the new `TEMPLATES` in `VODML`.
+  PushTemplates { templates: Templates },
+  /// `push_instance KEY=VAL ... @push_attribute ... @push_instance ...` push the new `INSTANCE` in `GLOBALS`, `TEMPLATES` or `INSTANCE`.
+  /// The actual type of `INSTANCE` depends on its parent, so the sub-actions are applied when pushing it.
+  PushInstance {
+    attributes: Vec<(String, String)>,
+    sub_actions: Vec<Action>,
+  },
+  /// `push_attribute KEY=VAL ...` push the new `ATTRIBUTE` in `INSTANCE`.
+  PushAttribute { attributes: Vec<(String, String)> },
   // FITS, STREAM, ...
 }
 impl Action {
@@ -373,7 +431,10 @@ impl Action {
       Action::Rm => !matches!(tag, Tag::VOTABLE),
       Action::SetAttrs { .. } => !matches!(tag, Tag::DEFINITION),
       Action::SetContent { .. } => {
-        matches!(tag, Tag::INFO | Tag::LINK | Tag::PARAMRef | Tag::FIELDRef)
+        matches!(
+          tag,
+          Tag::INFO | Tag::LINK | Tag::PARAMRef | Tag::FIELDRef | Tag::REPORT
+        )
       }
       Action::SetDesc { .. } => matches!(
         tag,
@@ -400,6 +461,15 @@ impl Action {
       Action::PushField { .. } => matches!(tag, Tag::TABLE),
       Action::PrependResource { .. } => matches!(tag, Tag::VOTABLE | Tag::RESOURCE),
       Action::PushResource { .. } => matches!(tag, Tag::VOTABLE | Tag::RESOURCE),
+      Action::PushVodml { .. } => matches!(tag, Tag::RESOURCE),
+      Action::SetReport { .. } => matches!(tag, Tag::VODML),
+      Action::PushModel { .. } => matches!(tag, Tag::VODML),
+      Action::SetGlobals { .. } => matches!(tag, Tag::VODML),
+      Action::PushTemplates { .. } => matches!(tag, Tag::VODML),
+      Action::PushInstance { .. } => {
+        matches!(tag, Tag::GLOBALS | Tag::TEMPLATES | Tag::INSTANCE)
+      }
+      Action::PushAttribute { .. } => matches!(tag, Tag::INSTANCE),
     }
   }
 
@@ -442,6 +512,20 @@ impl Action {
       Action::PushResource { resource } => {
         elem.push_resource(Resource::from_void_table_data_content(resource))
       }
+      Action::PushVodml { vodml } => elem.push_vodml(vodml),
+      Action::SetReport { report } => elem.set_report(report),
+      Action::PushModel { model } => elem.push_model(model),
+      Action::SetGlobals { globals } => elem.set_globals(globals),
+      Action::PushTemplates { templates } => elem.push_templates(templates),
+      Action::PushInstance {
+        attributes,
+        sub_actions,
+      } => elem.push_instance(attributes.into_iter(), |instance| {
+        sub_actions
+          .into_iter()
+          .try_for_each(|action| action.apply_on_wrapped_elem(instance))
+      }),
+      Action::PushAttribute { attributes } => elem.push_attribute(attributes.into_iter()),
     }
   }
 
@@ -457,117 +541,158 @@ impl Action {
   where
     I: Iterator<Item = &'a str>,
   {
-    match current.split_once(|c: char| c.is_whitespace()) {
-      Some((action, args)) => {
-        match action {
-          "set_attrs" => Ok((
-            Self::SetAttrs {
-              attributes: Action::str2map(args.trim_end()),
-            },
-            it,
-          )),
-          "set_content" => Ok((
-            Self::SetContent {
-              new_content: args.to_string(),
-            },
-            it,
-          )),
-          "set_description" => Ok((
-            Self::SetDesc {
-              new_desc: args.to_string(),
+    // Actions such as `set_globals` may have no argument
+    let (action, args) = current
+      .split_once(|c: char| c.is_whitespace())
+      .unwrap_or((current, ""));
+    match action {
+      "set_attrs" => Ok((
+        Self::SetAttrs {
+          attributes: Action::str2map(args.trim_end()),
+        },
+        it,
+      )),
+      "set_content" => Ok((
+        Self::SetContent {
+          new_content: args.to_string(),
+        },
+        it,
+      )),
+      "set_description" => Ok((
+        Self::SetDesc {
+          new_desc: args.to_string(),
+        },
+        it,
+      )),
+      "push_timesys" => {
+        Self::create_generic_elem::<_, TimeSys>(args, it, first_elem).and_then(|(wrapped, it)| {
+          wrapped
+            .time_sys()
+            .map(|timesys| (Self::PushTimeSys { timesys }, it))
+        })
+      }
+      "set_min" => Self::create_generic_elem::<_, Min>(args, it, first_elem)
+        .and_then(|(wrapped, it)| wrapped.min().map(|min| (Self::SetMin { min }, it))),
+      "set_max" => Self::create_generic_elem::<_, Max>(args, it, first_elem)
+        .and_then(|(wrapped, it)| wrapped.max().map(|max| (Self::SetMax { max }, it))),
+      "push_option" => {
+        Self::create_generic_elem::<_, Opt>(args, it, first_elem).and_then(|(wrapped, it)| {
+          wrapped
+            .option()
+            .map(|option| (Self::PushOption { option }, it))
+        })
+      }
+      "set_values" => {
+        Self::create_generic_elem::<_, Values>(args, it, first_elem).and_then(|(wrapped, it)| {
+          wrapped
+            .values()
+            .map(|values| (Self::SetValues { values }, it))
+        })
+      }
+      "push_info" => Self::create_generic_elem::<_, Info>(args, it, first_elem)
+        .and_then(|(wrapped, it)| wrapped.info().map(|info| (Self::PushInfo { info }, it))),
+      "push_post_info" => Self::create_generic_elem::<_, Info>(args, it, first_elem)
+        .and_then(|(wrapped, it)| wrapped.info().map(|info| (Self::PushPostInfo { info }, it))),
+      "push_link" => Self::create_generic_elem::<_, Link>(args, it, first_elem)
+        .and_then(|(wrapped, it)| wrapped.link().map(|link| (Self::PushLink { link }, it))),
+      "push_fieldref" => {
+        Self::create_generic_elem::<_, FieldRef>(args, it, first_elem).and_then(|(wrapped, it)| {
+          wrapped
+            .field_ref()
+            .map(|fieldref| (Self::PushFielRef { fieldref }, it))
+        })
+      }
+      "push_paramref" => {
+        Self::create_generic_elem::<_, ParamRef>(args, it, first_elem).and_then(|(wrapped, it)| {
+          wrapped
+            .param_ref()
+            .map(|paramref| (Self::PushParamRef { paramref }, it))
+        })
+      }
+      "push_coosys" => {
+        Self::create_generic_elem::<_, CooSys>(args, it, first_elem).and_then(|(wrapped, it)| {
+          wrapped
+            .coo_sys()
+            .map(|coosys| (Self::PushCooSys { coosys }, it))
+        })
+      }
+      "push_group" => Self::create_generic_elem::<_, Group>(args, it, first_elem)
+        .and_then(|(wrapped, it)| wrapped.group().map(|group| (Self::PushGroup { group }, it))),
+      "push_tablegroup" => Self::create_generic_elem::<_, TableGroup>(args, it, first_elem)
+        .and_then(|(wrapped, it)| {
+          wrapped
+            .table_group()
+            .map(|group| (Self::PushTableGroup { group }, it))
+        }),
+      "push_param" => Self::create_generic_elem::<_, Param>(args, it, first_elem)
+        .and_then(|(wrapped, it)| wrapped.param().map(|param| (Self::PushParam { param }, it))),
+      "push_field" => Self::create_generic_elem::<_, Field>(args, it, first_elem)
+        .and_then(|(wrapped, it)| wrapped.field().map(|field| (Self::PushField { field }, it))),
+      "prepend_resource" => {
+        Self::create_generic_elem::<_, Resource<VoidTableDataContent>>(args, it, first_elem)
+          .and_then(|(wrapped, it)| {
+            wrapped
+              .resource()
+              .map(|resource| (Self::PrependResource { resource }, it))
+          })
+      }
+      "push_resource" => {
+        Self::create_generic_elem::<_, Resource<VoidTableDataContent>>(args, it, first_elem)
+          .and_then(|(wrapped, it)| {
+            wrapped
+              .resource()
+              .map(|resource| (Self::PushResource { resource }, it))
+          })
+      }
+      "push_vodml" => Self::create_generic_elem::<_, Vodml>(args, it, first_elem)
+        .and_then(|(wrapped, it)| wrapped.vodml().map(|vodml| (Self::PushVodml { vodml }, it))),
+      "set_report" => {
+        Self::create_generic_elem::<_, Report>(args, it, first_elem).and_then(|(wrapped, it)| {
+          wrapped
+            .report()
+            .map(|report| (Self::SetReport { report }, it))
+        })
+      }
+      "push_model" => Self::create_generic_elem::<_, Model>(args, it, first_elem)
+        .and_then(|(wrapped, it)| wrapped.model().map(|model| (Self::PushModel { model }, it))),
+      "set_globals" => {
+        Self::create_generic_elem::<_, Globals>(args, it, first_elem).and_then(|(wrapped, it)| {
+          wrapped
+            .globals()
+            .map(|globals| (Self::SetGlobals { globals }, it))
+        })
+      }
+      "push_templates" => {
+        Self::create_generic_elem::<_, Templates>(args, it, first_elem).and_then(|(wrapped, it)| {
+          wrapped
+            .templates()
+            .map(|templates| (Self::PushTemplates { templates }, it))
+        })
+      }
+      "push_instance" => {
+        Self::create_deferred_elem(args, it, first_elem).map(|(attributes, sub_actions, it)| {
+          (
+            Self::PushInstance {
+              attributes,
+              sub_actions,
             },
             it,
-          )),
-          "push_timesys" => Self::create_generic_elem::<_, TimeSys>(args, it, first_elem).and_then(
-            |(wrapped, it)| {
-              wrapped
-                .time_sys()
-                .map(|timesys| (Self::PushTimeSys { timesys }, it))
-            },
-          ),
-          "set_min" => Self::create_generic_elem::<_, Min>(args, it, first_elem)
-            .and_then(|(wrapped, it)| wrapped.min().map(|min| (Self::SetMin { min }, it))),
-          "set_max" => Self::create_generic_elem::<_, Max>(args, it, first_elem)
-            .and_then(|(wrapped, it)| wrapped.max().map(|max| (Self::SetMax { max }, it))),
-          "push_option" => {
-            Self::create_generic_elem::<_, Opt>(args, it, first_elem).and_then(|(wrapped, it)| {
-              wrapped
-                .option()
-                .map(|option| (Self::PushOption { option }, it))
-            })
-          }
-          "set_values" => Self::create_generic_elem::<_, Values>(args, it, first_elem).and_then(
-            |(wrapped, it)| {
-              wrapped
-                .values()
-                .map(|values| (Self::SetValues { values }, it))
-            },
-          ),
-          "push_info" => Self::create_generic_elem::<_, Info>(args, it, first_elem)
-            .and_then(|(wrapped, it)| wrapped.info().map(|info| (Self::PushInfo { info }, it))),
-          "push_post_info" => Self::create_generic_elem::<_, Info>(args, it, first_elem)
-            .and_then(|(wrapped, it)| wrapped.info().map(|info| (Self::PushPostInfo { info }, it))),
-          "push_link" => Self::create_generic_elem::<_, Link>(args, it, first_elem)
-            .and_then(|(wrapped, it)| wrapped.link().map(|link| (Self::PushLink { link }, it))),
-          "push_fieldref" => Self::create_generic_elem::<_, FieldRef>(args, it, first_elem)
-            .and_then(|(wrapped, it)| {
-              wrapped
-                .field_ref()
-                .map(|fieldref| (Self::PushFielRef { fieldref }, it))
-            }),
-          "push_paramref" => Self::create_generic_elem::<_, ParamRef>(args, it, first_elem)
-            .and_then(|(wrapped, it)| {
-              wrapped
-                .param_ref()
-                .map(|paramref| (Self::PushParamRef { paramref }, it))
-            }),
-          "push_coosys" => Self::create_generic_elem::<_, CooSys>(args, it, first_elem).and_then(
-            |(wrapped, it)| {
-              wrapped
-                .coo_sys()
-                .map(|coosys| (Self::PushCooSys { coosys }, it))
-            },
-          ),
-          "push_group" => Self::create_generic_elem::<_, Group>(args, it, first_elem)
-            .and_then(|(wrapped, it)| wrapped.group().map(|group| (Self::PushGroup { group }, it))),
-          "push_tablegroup" => Self::create_generic_elem::<_, TableGroup>(args, it, first_elem)
-            .and_then(|(wrapped, it)| {
-              wrapped
-                .table_group()
-                .map(|group| (Self::PushTableGroup { group }, it))
-            }),
-          "push_param" => Self::create_generic_elem::<_, Param>(args, it, first_elem)
-            .and_then(|(wrapped, it)| wrapped.param().map(|param| (Self::PushParam { param }, it))),
-          "push_field" => Self::create_generic_elem::<_, Field>(args, it, first_elem)
-            .and_then(|(wrapped, it)| wrapped.field().map(|field| (Self::PushField { field }, it))),
-          "prepend_resource" => {
-            Self::create_generic_elem::<_, Resource<VoidTableDataContent>>(args, it, first_elem)
-              .and_then(|(wrapped, it)| {
-                wrapped
-                  .resource()
-                  .map(|resource| (Self::PrependResource { resource }, it))
-              })
-          }
-          "push_resource" => {
-            Self::create_generic_elem::<_, Resource<VoidTableDataContent>>(args, it, first_elem)
-              .and_then(|(wrapped, it)| {
-                wrapped
-                  .resource()
-                  .map(|resource| (Self::PushResource { resource }, it))
-              })
-          }
-          _ => Err(VOTableError::Custom(format!(
-            "Unrecognized Action. Actual: {}. Expected: one of [rm, set_attrs, set_content, \
+          )
+        })
+      }
+      "push_attribute" => Ok((
+        Self::PushAttribute {
+          attributes: Action::str2map(args.trim_end()),
+        },
+        it,
+      )),
+      _ => Err(VOTableError::Custom(format!(
+        "Unrecognized Action. Actual: {}. Expected: one of [rm, set_attrs, set_content, \
           set_description, push_timesys, set_min, set_max, push_option, set_values, push_info, \
           push_post_info, push_link, push_fieldref, push_paramref, push_coosys, push_group, \
-           push_table_group, push_param, push_field, prepend_resource, push_resource, ...].",
-            action
-          ))),
-        }
-      }
-      None => Err(VOTableError::Custom(format!(
-        "No whitespace to spit on in '{}'",
-        current
+           push_table_group, push_param, push_field, prepend_resource, push_resource, push_vodml, \
+           set_report, push_model, set_globals, push_templates, push_instance, push_attribute].",
+        action
       ))),
     }
   }
@@ -610,6 +735,42 @@ impl Action {
       }
     }
   }
+
+  /// Same as `create_generic_elem`, but for elements whose actual type is known only when pushed
+  /// in their parent element: the sub-actions are collected instead of being applied.
+  #[allow(clippy::type_complexity)]
+  fn create_deferred_elem<'a, I>(
+    args: &'a str,
+    mut it: Peekable<I>,
+    first_elem: bool,
+  ) -> Result<(Vec<(String, String)>, Vec<Action>, Peekable<I>), VOTableError>
+  where
+    I: Iterator<Item = &'a str>,
+  {
+    let attrs = Action::str2map(args);
+    let mut sub_actions = Vec::new();
+    if first_elem {
+      // Continue collecting actions on this object untill '@<' is found
+      while let Some(curr) = it.next() {
+        let (action, next_it) = Action::from_sub_action(curr, it, false)?;
+        sub_actions.push(action);
+        it = next_it;
+      }
+    } else if it.peek().is_some_and(|e| e.trim().is_empty()) {
+      // '@@' encountered, collect next commands for this item
+      let _ = it.next();
+      // Continue collecting actions on this object untill '@<' is found
+      while let Some(curr) = it.next() {
+        if curr.trim() == "<" {
+          break;
+        }
+        let (action, next_it) = Action::from_sub_action(curr, it, false)?;
+        sub_actions.push(action);
+        it = next_it;
+      }
+    }
+    Ok((attrs, sub_actions, it))
+  }
 }
 impl FromStr for Action {
   type Err = VOTableError;
@@ -633,7 +794,7 @@ impl FromStr for Action {
 
 #[cfg(test)]
 mod test {
-  use crate::edit::Action;
+  use crate::edit::{Action, TagConditionAction};
 
   #[test]
   fn test_parse_action() {
@@ -652,4 +813,23 @@ mod test {
     println!("{:?}", a);
     assert!(a.is_ok())
   }
+
+  #[test]
+  fn test_parse_mivot_action() {
+    let s = r#"push_vodml @set_report status=OK @@set_content Manual annotation @<
+        @push_model name=ivoa url=https://www.ivoa.net/xml/VODML/IVOA-v1.vo-dml.xml
+        @push_templates tableref=gaia @@push_instance dmtype=x:Y
+        @@push_attribute dmrole=x:Y.a dmtype=ivoa:real ref=ra"#;
+    let a = s.parse::<Action>();
+    println!("{:?}", a);
+    assert!(a.is_ok())
+  }
+
+  #[test]
+  fn test_parse_mivot_tag_condition_action() {
+    let s = "INSTANCE dmid=_spacesys_icrs rm";
+    let tca = s.parse::<TagConditionAction>();
+    println!("{:?}", tca);
+    assert!(tca.is_ok())
+  }
 }
@@ -1,15 +1,21 @@
-use std::{fmt::Alignment, io::stdin, slice::Iter};
+use std::{
+  fmt::Alignment,
+  io::{stdin, stdout, BufReader, Write},
+  slice::Iter,
+};
 
 use clap::{Args, Subcommand, ValueEnum};
 
 use votable::{
   error::VOTableError,
+  impls::stats::TableStats,
   iter::{SimpleVOTableRowIterator, VOTableIterator},
-  TableDataContent, VOTable,
+  HasContent, TableDataContent, VOTable,
 };
 
 use super::{
   input::Input,
+  streaming::compute_stats,
   visitors::{
     colnames::ColnamesVisitor, fieldarray::FieldArrayVisitor, votstruct::AsciiStructVisitor,
   },
@@ -29,7 +35,20 @@ pub struct Get {
 impl Get {
   pub fn exec(self) -> Result<(), VOTableError> {
     self.input.is_streamable().and_then(|is_streamable| {
-      if is_streamable {
+      if let GetAction::Stats {
+        separator,
+        parallel,
+        chunk_size,
+      } = &self.action
+      {
+        if is_streamable {
+          self.exec_stats(*separator, *parallel, *chunk_size)
+        } else {
+          Err(VOTableError::Custom(String::from(
+            "Statistics only available for 'xml' input",
+          )))
+        }
+      } else if is_streamable {
         self.exec_streaming()
       } else {
         self.exec_in_mem()
@@ -45,6 +64,24 @@ impl Get {
       .and_then(|votw| self.action.exec(votw.unwrap()))
   }
 
+  /// Compute (in streaming mode) and print the statistics of the columns of the first table.
+  pub fn exec_stats(
+    &self,
+    separator: char,
+    parallel: Option<usize>,
+    chunk_size: usize,
+  ) -> Result<(), VOTableError> {
+    let stats = match &self.input.input {
+      Some(path) => SimpleVOTableRowIterator::from_file(path)
+        .and_then(|it| compute_stats(it, parallel, chunk_size)),
+      None => SimpleVOTableRowIterator::from_reader(BufReader::new(stdin()))
+        .and_then(|it| compute_stats(it, parallel, chunk_size)),
+    }?;
+    let stdout = stdout();
+    let mut handle = stdout.lock();
+    print_stats(&stats, separator, &mut handle).map_err(VOTableError::Io)
+  }
+
   /// Exec in streaming mode if the input is XML.
   pub fn exec_streaming(self) -> Result<(), VOTableError> {
     if self.stop_at_first_data {
@@ -102,6 +139,48 @@ enum GetAction {
     #[arg(short, long)]
     not_aligned: bool,
   },
+  /// Print per-column statistics of the first table ('xml' input only): number of values,
+  /// number of nulls, min, max, mean and estimated number of distinct values.
+  Stats {
+    /// Column separator
+    #[arg(short, long, default_value_t = ',')]
+    separator: char,
+    /// Exec concurrently using N threads
+    #[arg(long, value_name = "N")]
+    parallel: Option<usize>,
+    /// Number of rows process by a same thread in `parallel` mode
+    #[arg(long, default_value_t = 10_000_usize)]
+    chunk_size: usize,
+  },
+  /// Print the query status ('OK', 'ERROR' or 'OVERFLOW', from the last QUERY_STATUS INFO) and
+  /// its message, if any; 'UNKNOWN' if no status is found.
+  /// Trailing INFOs (after the table) are ignored with --early-stop.
+  #[command(verbatim_doc_comment)]
+  Status,
+}
+
+/// Print the statistics, one line per column.
+fn print_stats<W: Write>(stats: &TableStats, sep: char, write: &mut W) -> std::io::Result<()> {
+  writeln!(
+    write,
+    "name{s}count{s}n_null{s}min{s}max{s}mean{s}n_distinct",
+    s = sep
+  )?;
+  for (name, col) in stats.names().iter().zip(stats.columns()) {
+    writeln!(
+      write,
+      "{}{s}{}{s}{}{s}{}{s}{}{s}{}{s}{}",
+      name,
+      col.count(),
+      col.n_null(),
+      col.min().map(|v| v.to_string()).unwrap_or_default(),
+      col.max().map(|v| v.to_string()).unwrap_or_default(),
+      col.mean().map(|v| v.to_string()).unwrap_or_default(),
+      col.distinct_estimate(),
+      s = sep
+    )?;
+  }
+  Ok(())
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -221,6 +300,17 @@ impl GetAction {
           .visit(&mut visitor)
           .map_err(|e| VOTableError::Custom(e.to_string()))
       }
+      Self::Status => {
+        match vot.query_status()? {
+          Some((status, info)) => match info.get_content() {
+            Some(message) => println!("{}\t{}", status, message),
+            None => println!("{}", status),
+          },
+          None => println!("UNKNOWN"),
+        };
+        Ok(())
+      }
+      Self::Stats { .. } => unreachable!(), // Computed in streaming mode before reaching this point.
     }
   }
 }
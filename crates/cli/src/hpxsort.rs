@@ -16,6 +16,7 @@ use votable::{
   datatype::Datatype,
   error::VOTableError,
   iter::SimpleVOTableRowIterator,
+  ucd,
   votable::new_xml_writer,
   Field, TableElem, VOTable, VoidTableDataContent,
 };
@@ -243,14 +244,12 @@ pub(crate) fn look_for_positional_columns(
 ) -> Result<(usize, usize), VOTableError> {
   let ilon = lon_name
     .map(|colname| look_for_float_col_index_having_name(fields, colname.as_str()))
-    .or_else(|| look_for_float_col_index_having_ucd(fields, "pos.eq.ra;meta.main"))
-    .or_else(|| look_for_float_col_index_having_ucd(fields, "pos.eq.ra"))
+    .or_else(|| look_for_float_main_col_index_having_ucd(fields, "pos.eq.ra"))
     .unwrap_or_else(|| look_for_float_col_index_name_startswith(fields, "RA"))
     .map_err(VOTableError::Custom)?;
   let ilat = lat_name
     .map(|colname| look_for_float_col_index_having_name(fields, colname.as_str()))
-    .or_else(|| look_for_float_col_index_having_ucd(fields, "pos.eq.dec;meta.main"))
-    .or_else(|| look_for_float_col_index_having_ucd(fields, "pos.eq.de"))
+    .or_else(|| look_for_float_main_col_index_having_ucd(fields, "pos.eq.dec"))
     .unwrap_or_else(|| look_for_float_col_index_name_startswith(fields, "DE"))
     .map_err(VOTableError::Custom)?;
   Ok((ilon, ilat))
@@ -271,22 +270,20 @@ fn look_for_float_col_index_having_name(fields: &[&Field], colname: &str) -> Res
   Err(format!("Column '{}' not found!", &colname))
 }
 
-fn look_for_float_col_index_having_ucd(
+/// Returns the index in the given field array of the main column having the given UCD primary
+/// word (i.e. preferably also having `meta.main`), see [ucd::find_main_column].
+/// An error is raised if the datatype of the column is not Float or Double.
+fn look_for_float_main_col_index_having_ucd(
   fields: &[&Field],
-  target_ucd: &str,
+  primary: &str,
 ) -> Option<Result<usize, String>> {
-  for (i, field) in fields.iter().enumerate() {
-    if let Some(ucd) = &field.ucd && ucd.as_str() == target_ucd {
-      return Some(match field.datatype {
-        Datatype::Float | Datatype::Double => Ok(i),
-        _ => Err(format!(
-          "Column '{}' is not a float or a double.",
-          &field.name
-        )),
-      });
-    }
-  }
-  None
+  ucd::find_main_column(fields.iter().copied(), primary).map(|i| match fields[i].datatype {
+    Datatype::Float | Datatype::Double => Ok(i),
+    _ => Err(format!(
+      "Column '{}' is not a float or a double.",
+      &fields[i].name
+    )),
+  })
 }
 
 fn look_for_float_col_index_name_startswith(
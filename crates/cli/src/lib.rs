@@ -4,6 +4,8 @@ pub mod get;
 pub mod hcidx;
 pub mod hpxsort;
 pub mod input;
+pub mod mivot;
+pub mod new;
 pub mod output;
 pub mod qhcidx;
 pub mod streaming;
@@ -3,8 +3,8 @@ use clap::Parser;
 use votable::error::VOTableError;
 
 use votable_cli::{
-  convert::Convert, edit::Edit, get::Get, hcidx::HealpixCumulIndex, hpxsort::HpxSort,
-  qhcidx::QueryHealpixCumulIndex, streaming::StreamConvert,
+  convert::Convert, edit::Edit, get::Get, hcidx::HealpixCumulIndex, hpxsort::HpxSort, mivot::Mivot,
+  new::New, qhcidx::QueryHealpixCumulIndex, streaming::StreamConvert,
 };
 
 #[derive(Debug, Parser)]
@@ -15,6 +15,8 @@ pub enum CliArgs {
   Sconvert(StreamConvert),
   Edit(Edit),
   Get(Get),
+  New(New),
+  Mivot(Mivot),
   HSort(HpxSort), // InMem or streaming mode, guess position from pos.eq.ra;meta.main or pos.eq.ra or user provided
   HCIdx(HealpixCumulIndex), //HEALPix Cumulative Index
   QHCIdx(QueryHealpixCumulIndex), // Query using a HEALPix Cumulative Index (name of columns and file taken in the HCI FITS file
@@ -27,6 +29,8 @@ impl CliArgs {
       Self::Sconvert(p) => p.exec(),
       Self::Edit(p) => p.exec(),
       Self::Get(p) => p.exec(),
+      Self::New(p) => p.exec(),
+      Self::Mivot(p) => p.exec(),
       Self::HSort(p) => p.exec(),
       Self::HCIdx(p) => p.exec(),
       Self::QHCIdx(p) => p.exec(),
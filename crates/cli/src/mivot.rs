@@ -0,0 +1,263 @@
+use std::{
+  fs::File,
+  io::{stdin, stdout, BufRead, BufReader, BufWriter, Write},
+  path::PathBuf,
+};
+
+use clap::{Args, Subcommand, ValueEnum};
+
+use votable::{
+  error::VOTableError,
+  impls::mem::InMemTableDataRows,
+  mivot::{Annotator, GlobalsMode, MivotRowStream},
+  resource::{Resource, ResourceOrTable},
+  votable::{new_xml_writer, VOTable},
+  QuickXmlReadWrite, TableDataContent,
+};
+
+use super::{input::Input, output::Output};
+
+/// Work with MIVOT annotations: e.g. generate them from the tables metadata.
+#[derive(Debug, Args)]
+pub struct Mivot {
+  #[command(flatten)]
+  input: Input,
+  #[command(subcommand)]
+  action: MivotAction,
+}
+
+impl Mivot {
+  pub fn exec(self) -> Result<(), VOTableError> {
+    match self.action {
+      MivotAction::Export {
+        globals,
+        buffer,
+        output,
+      } => export(&self.input, globals.into(), buffer, output),
+      action => self
+        .input
+        .load()
+        .and_then(|votw| action.exec(votw.unwrap())),
+    }
+  }
+}
+
+/// How the GLOBALS instances referenced by the exported instances are written.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum GlobalsExport {
+  /// Copy the referenced instance in place of each reference
+  #[default]
+  Inline,
+  /// Write the referenced instances once, before the rows, and replace each reference by its 'dmref'
+  Once,
+}
+
+impl From<GlobalsExport> for GlobalsMode {
+  fn from(globals: GlobalsExport) -> Self {
+    match globals {
+      GlobalsExport::Inline => GlobalsMode::Inline,
+      GlobalsExport::Once => GlobalsMode::Once,
+    }
+  }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MivotAction {
+  /// Annotate a table with the MANGO model (positions, proper motions, parallax, photometry and errors),
+  /// from its FIELDs UCDs and units, its GROUPs and the COOSYS/TIMESYS.
+  Annotate {
+    /// Index of the table to be annotated, starting at 0 (tables taken in the document order).
+    #[arg(long, value_name = "INDEX", default_value_t = 0)]
+    table: usize,
+    /// Only print the generated VODML block (the VOTable is not written).
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+    #[command(flatten)]
+    output: Output,
+  },
+  /// Export in NDJSON the model instances materialized from each row of the first table,
+  /// in streaming mode (the MIVOT annotations may follow the table data in an input file).
+  /// Each line is an instance object made of its 'dmtype' and of its sub-elements keyed by their 'dmrole'.
+  ///
+  /// An input file is read twice: a first pass looks for the annotations in the whole file,
+  /// a second pass streams the rows.
+  /// Stdin is read once: the annotations must precede the table data, unless '--buffer' is used.
+  Export {
+    /// Write the referenced GLOBALS instances inline or once (before the rows)
+    #[arg(long, value_enum, default_value_t = GlobalsExport::Inline)]
+    globals: GlobalsExport,
+    /// When reading from stdin, buffer all the rows in memory if the MIVOT annotations follow the table data
+    #[arg(long)]
+    buffer: bool,
+    /// Path of the output NDJSON file [default: write to stdout]
+    #[arg(short = 'o', long = "out", value_name = "FILE")]
+    output: Option<PathBuf>,
+  },
+}
+
+impl MivotAction {
+  fn exec(self, mut vot: VOTable<InMemTableDataRows>) -> Result<(), VOTableError> {
+    match self {
+      Self::Annotate {
+        table,
+        dry_run,
+        output,
+      } => {
+        let annotator = Annotator::from_votable(&vot);
+        let (resource, i) = table_resource(&mut vot, table)?;
+        let mut vodml = match &mut resource.sub_elems[i].resource_or_table {
+          ResourceOrTable::Table(table) => annotator.annotate(table),
+          ResourceOrTable::Resource(_) => unreachable!(), // index of a table
+        }?;
+        if dry_run {
+          let stdout = stdout();
+          let mut writer = new_xml_writer(stdout.lock(), None, Some(2));
+          vodml.write(&mut writer, &())?;
+          writeln!(writer.into_inner()).map_err(VOTableError::Io)
+        } else if is_annotated(resource) {
+          Err(VOTableError::Custom(String::from(
+            "The RESOURCE containing the table already has a MIVOT annotation",
+          )))
+        } else {
+          resource.prepend_resource_by_ref(Resource::new().set_type("meta").set_vodml(vodml));
+          output.save(vot.wrap())
+        }
+      }
+      Self::Export { .. } => unreachable!(), // streaming, see `Mivot::exec`
+    }
+  }
+}
+
+/// Streams the rows of the first table, writing the materialized instances in NDJSON.
+/// An input file is first scanned for the MIVOT annotations, which may follow the table data.
+fn export(
+  input: &Input,
+  globals_mode: GlobalsMode,
+  buffer: bool,
+  output: Option<PathBuf>,
+) -> Result<(), VOTableError> {
+  if !input.is_streamable()? {
+    return Err(VOTableError::Custom(String::from(
+      "MIVOT export requires an XML input VOTable",
+    )));
+  }
+  match &input.input {
+    Some(path) => export_to(MivotRowStream::from_file(path, globals_mode)?, output),
+    None => export_to(
+      MivotRowStream::from_reader(BufReader::new(stdin()), globals_mode, buffer)?,
+      output,
+    ),
+  }
+}
+
+fn export_to<R: BufRead>(
+  stream: MivotRowStream<R>,
+  output: Option<PathBuf>,
+) -> Result<(), VOTableError> {
+  match output {
+    Some(path) => {
+      let file = File::create(path).map_err(VOTableError::Io)?;
+      let mut write = BufWriter::new(file);
+      export_rows(stream, &mut write)?;
+      write.flush().map_err(VOTableError::Io)
+    }
+    None => {
+      let stdout = stdout();
+      let mut handle = stdout.lock();
+      export_rows(stream, &mut handle)?;
+      handle.flush().map_err(VOTableError::Io)
+    }
+  }
+}
+
+fn export_rows<R: BufRead, W: Write>(
+  mut stream: MivotRowStream<R>,
+  mut write: W,
+) -> Result<(), VOTableError> {
+  // Write the referenced GLOBALS instances (once)
+  for instance in stream.globals() {
+    write_ndjson(&mut write, instance)?;
+  }
+  // Write the instances of each row
+  for instances in stream.rows() {
+    for instance in instances? {
+      write_ndjson(&mut write, &instance)?;
+    }
+  }
+  Ok(())
+}
+
+fn write_ndjson<W: Write, T: serde::Serialize>(
+  mut write: W,
+  value: &T,
+) -> Result<(), VOTableError> {
+  serde_json::to_writer(&mut write, value).map_err(|e| VOTableError::Custom(e.to_string()))?;
+  write.write_all(b"\n").map_err(VOTableError::Io)
+}
+
+/// Returns the `RESOURCE` containing the `index`-th table of the VOTable, together with the
+/// index of the table in the `RESOURCE` sub-elements.
+fn table_resource<C: TableDataContent>(
+  vot: &mut VOTable<C>,
+  index: usize,
+) -> Result<(&mut Resource<C>, usize), VOTableError> {
+  let mut remaining = index;
+  let mut path = Vec::new();
+  let found = vot.resources.iter().enumerate().any(|(i, resource)| {
+    path.push(i);
+    let found = table_path(resource, &mut remaining, &mut path);
+    if !found {
+      path.pop();
+    }
+    found
+  });
+  if !found {
+    return Err(VOTableError::Custom(format!(
+      "Table of index {} not found in the VOTable",
+      index
+    )));
+  }
+  // path = [resource index, sub-resources indices..., table index]
+  let mut resource = &mut vot.resources[path[0]];
+  for i in &path[1..path.len() - 1] {
+    resource = match &mut resource.sub_elems[*i].resource_or_table {
+      ResourceOrTable::Resource(sub_resource) => sub_resource,
+      ResourceOrTable::Table(_) => unreachable!(), // path of resources
+    };
+  }
+  Ok((resource, path[path.len() - 1]))
+}
+
+/// Pushes in `path` the indices of the sub-elements leading to the `index`-th table of the
+/// `RESOURCE`, returning `false` (and decreasing `index` of the number of tables) if not found.
+fn table_path<C: TableDataContent>(
+  resource: &Resource<C>,
+  index: &mut usize,
+  path: &mut Vec<usize>,
+) -> bool {
+  for (i, sub_elem) in resource.sub_elems.iter().enumerate() {
+    path.push(i);
+    match &sub_elem.resource_or_table {
+      ResourceOrTable::Table(_) if *index == 0 => return true,
+      ResourceOrTable::Table(_) => *index -= 1,
+      ResourceOrTable::Resource(sub_resource) => {
+        if table_path(sub_resource, index, path) {
+          return true;
+        }
+      }
+    }
+    path.pop();
+  }
+  false
+}
+
+/// Tells whether the `RESOURCE` (or one of its direct sub-resources) contains a `VODML` block.
+fn is_annotated<C: TableDataContent>(resource: &Resource<C>) -> bool {
+  resource.vodml.is_some()
+    || resource.sub_elems.iter().any(|sub_elem| {
+      matches!(
+        &sub_elem.resource_or_table,
+        ResourceOrTable::Resource(sub_resource) if sub_resource.vodml.is_some()
+      )
+    })
+}
@@ -0,0 +1,40 @@
+use clap::Args;
+
+use votable::{
+  dali::RESULTS_RESOURCE_TYPE,
+  error::VOTableError,
+  impls::mem::InMemTableDataRows,
+  resource::Resource,
+  standards::Standard,
+  votable::{VOTable, Version},
+};
+
+use super::output::Output;
+
+/// Create a new VOTable, with an empty table following a standard
+/// ('obscore', 'ssa', 'siav2' or 'epntap').
+#[derive(Debug, Args)]
+pub struct New {
+  /// Standard the table header follows: 'obscore', 'ssa', 'siav2' or 'epntap'.
+  #[arg(long, value_name = "STANDARD")]
+  template: Standard,
+  /// Only put the columns required by the standard (i.e. not the optional ones).
+  #[arg(long)]
+  required_only: bool,
+  #[command(flatten)]
+  output: Output,
+}
+
+impl New {
+  pub fn exec(self) -> Result<(), VOTableError> {
+    let table = self
+      .template
+      .new_table::<InMemTableDataRows>(!self.required_only);
+    let resource = Resource::new()
+      .set_type(RESULTS_RESOURCE_TYPE)
+      .push_table(table);
+    self
+      .output
+      .save(VOTable::new(Version::V1_4, resource).wrap())
+  }
+}
@@ -1,4 +1,8 @@
-//! Single (large) Table Streaming Converter.
+//! (Large) Tables Streaming Converter.
+//!
+//! Without `--parallel`, the XML output formats convert all the tables of the input VOTable.
+//! The `csv` output format, the `--parallel` mode and the value transformation options
+//! (`--to-frame`, `--to-time`, ...) only support a single table.
 
 use std::{
   fs::File,
@@ -13,7 +17,8 @@ use crossbeam::channel::{Receiver, Sender, bounded};
 use serde::{Deserializer, de::DeserializeSeed};
 
 use votable::{
-  TableElem, VOTable, VoidTableDataContent,
+  TableDataContent, TableElem, VOTable, VoidTableDataContent,
+  coosys::transform::{Frame, TableFrameConverter},
   data::{TableOrBinOrBin2, tabledata::FieldIteratorUnbuffered},
   error::VOTableError,
   impls::{
@@ -23,9 +28,15 @@ use votable::{
       write::{B64Formatter, BinarySerializer, EncoderWriter, general_purpose},
     },
     mem::InMemTableDataRows,
+    stats::TableStats,
     visitors::FixedLengthArrayVisitor,
   },
-  iter::SimpleVOTableRowIterator,
+  iter::{SimpleVOTableRowIterator, convert::convert_all_tables},
+  resource::{Resource, ResourceOrTable},
+  timesys::{
+    TimeScale,
+    convert::{TableTimeConverter, TimeRepr},
+  },
   votable::new_xml_writer,
 };
 
@@ -53,7 +64,7 @@ impl FromStr for OutputFormat {
   }
 }
 
-/// Convert a single table XML VOTable in streaming mode.
+/// Convert an XML VOTable in streaming mode (a single table for 'csv', --parallel or value transformations).
 #[derive(Debug, Args)]
 pub struct StreamConvert {
   /// Path of the input XML VOTable [default: read from stdin]
@@ -74,6 +85,25 @@ pub struct StreamConvert {
   /// Number of rows process by a same thread in `parallel` mode
   #[arg(long, default_value_t = 10_000_usize)]
   chunk_size: usize,
+  /// Transform the positions (and proper motions) into the given frame, updating the COOSYS
+  /// ('icrs', 'fk5', 'fk4', 'ecliptic', 'galactic' or 'supergalactic'); not compatible with --parallel
+  #[arg(long, value_name = "FRAME")]
+  to_frame: Option<Frame>,
+  /// Epoch (Julian year) the positions are propagated to when using --to-frame (requires proper motions)
+  #[arg(long, value_name = "YEAR", requires = "to_frame")]
+  to_epoch: Option<f64>,
+  /// Convert the columns referencing a TIMESYS into the given representation, updating the TIMESYS
+  /// ('jd', 'mjd', 'jyear' or 'iso'); not compatible with --parallel
+  #[arg(long, value_name = "REPR")]
+  to_time: Option<TimeRepr>,
+  /// Convert the columns referencing a TIMESYS into the given timescale, updating the TIMESYS
+  /// ('UTC', 'TAI', 'TT', 'TDB' or 'GPS'); not compatible with --parallel
+  #[arg(long, value_name = "SCALE")]
+  to_timescale: Option<TimeScale>,
+  /// Write the actual min/max of each column in the FIELDs VALUES and the number of rows in an
+  /// INFO (reads the input twice: requires --in); not compatible with value transformations
+  #[arg(long, requires = "input")]
+  set_min_max: bool,
 }
 
 impl StreamConvert {
@@ -82,6 +112,30 @@ impl StreamConvert {
   }
 
   pub fn choose_input_and_exec(self) -> Result<(), VOTableError> {
+    if self.has_row_transform() && self.parallel.is_some() {
+      return Err(VOTableError::Custom(String::from(
+        "Options '--to-frame', '--to-time' and '--to-timescale' not compatible with '--parallel'",
+      )));
+    }
+    if self.set_min_max {
+      if self.has_row_transform() {
+        return Err(VOTableError::Custom(String::from(
+          "Option '--set-min-max' not compatible with '--to-frame', '--to-time' and '--to-timescale'",
+        )));
+      }
+      return self.set_min_max_and_exec();
+    }
+    if self.parallel.is_none() && !self.has_row_transform() {
+      let format = match self.output_fmt {
+        OutputFormat::XmlTabledata => Some(TableOrBinOrBin2::TableData),
+        OutputFormat::XmlBinary => Some(TableOrBinOrBin2::Binary),
+        OutputFormat::XmlBinary2 => Some(TableOrBinOrBin2::Binary2),
+        OutputFormat::CSV => None,
+      };
+      if let Some(format) = format {
+        return self.convert_all_tables(&format);
+      }
+    }
     match &self.input {
       Some(path) => {
         SimpleVOTableRowIterator::from_file(path).and_then(|it| self.choose_output_and_exec(it))
@@ -95,6 +149,97 @@ impl StreamConvert {
     }
   }
 
+  /// Compute the statistics of the table in a first pass, and write them in the output
+  /// header while converting the table in a second pass.
+  /// Only single-table VOTables are supported.
+  fn set_min_max_and_exec(self) -> Result<(), VOTableError> {
+    let path = self.input.as_ref().ok_or_else(|| {
+      VOTableError::Custom(String::from("Option '--set-min-max' requires '--in'"))
+    })?;
+    let (stats, votable) = SimpleVOTableRowIterator::from_file(path)
+      .and_then(|it| compute_stats_and_read_to_end(it, self.parallel, self.chunk_size, true))
+      .map_err(|e| {
+        VOTableError::Custom(format!(
+          "Error computing the statistics (note: '--set-min-max' only supports single-table VOTables): {}",
+          e
+        ))
+      })?;
+    if votable.is_some_and(|votable| votable.resources.iter().map(n_tables).sum::<usize>() > 1) {
+      return Err(VOTableError::Custom(String::from(
+        "Option '--set-min-max' only supports VOTables containing a single table",
+      )));
+    }
+    let mut it = SimpleVOTableRowIterator::from_file(path)?;
+    // MIN/MAX do not modify the table schema: the input metadata can be updated
+    if let Some(table) = it.votable.get_first_table_mut() {
+      stats.inject_into_table(table);
+    }
+    self.choose_output_and_exec(it)
+  }
+
+  /// Convert all the tables of the input VOTable.
+  fn convert_all_tables(&self, format: &TableOrBinOrBin2) -> Result<(), VOTableError> {
+    let reader: Box<dyn BufRead> = match &self.input {
+      Some(path) => Box::new(BufReader::new(File::open(path).map_err(VOTableError::Io)?)),
+      None => Box::new(BufReader::new(stdin())),
+    };
+    match &self.output {
+      Some(path) => {
+        let file = File::create(path).map_err(VOTableError::Io)?;
+        let mut write = BufWriter::new(file);
+        convert_all_tables(reader, &mut write, format)?;
+        write.flush().map_err(VOTableError::Io)
+      }
+      None => {
+        let stdout = stdout();
+        let mut handle = stdout.lock();
+        convert_all_tables(reader, &mut handle, format)?;
+        handle.flush().map_err(VOTableError::Io)
+      }
+    }
+  }
+
+  /// Tells whether the values of the rows have to be transformed.
+  fn has_row_transform(&self) -> bool {
+    self.to_frame.is_some() || self.to_time.is_some() || self.to_timescale.is_some()
+  }
+
+  /// Transform the positions and/or the times of the first table.
+  fn transform_and_exec<R, W>(
+    &self,
+    it: SimpleVOTableRowIterator<R>,
+    write: W,
+  ) -> Result<(), VOTableError>
+  where
+    R: BufRead,
+    W: Write,
+  {
+    // The rows are read according to the input metadata, the output metadata is modified
+    let mut out_vot = it.votable.clone();
+    let frame_converter = self
+      .to_frame
+      .map(|frame| TableFrameConverter::new(&mut out_vot, frame, self.to_epoch))
+      .transpose()?;
+    let time_converter = if self.to_time.is_some() || self.to_timescale.is_some() {
+      Some(TableTimeConverter::new(
+        &mut out_vot,
+        self.to_time,
+        self.to_timescale.clone(),
+      )?)
+    } else {
+      None
+    };
+    transform_rows(it, out_vot, write, self.output_fmt, self.separator, |row| {
+      if let Some(converter) = &frame_converter {
+        converter.transform_row(row)?;
+      }
+      match &time_converter {
+        Some(converter) => converter.convert_row(row),
+        None => Ok(()),
+      }
+    })
+  }
+
   pub fn choose_output_and_exec<R: BufRead + Send>(
     self,
     it: SimpleVOTableRowIterator<R>,
@@ -122,6 +267,9 @@ impl StreamConvert {
     R: BufRead + Send,
     W: Write,
   {
+    if self.has_row_transform() {
+      return self.transform_and_exec(it, write);
+    }
     match it.data_type() {
       TableOrBinOrBin2::TableData => {
         match self.output_fmt {
@@ -511,6 +659,82 @@ fn to_csv<R: BufRead, W: Write>(
   write.write_all(b"\n").map_err(VOTableError::Io)
 }
 
+/// Write the rows of the first table transformed by the given function.
+/// # Params
+/// * `it`: the input rows, read according to the input VOTable metadata
+/// * `out_vot`: the output VOTable metadata
+fn transform_rows<R, W, F>(
+  mut it: SimpleVOTableRowIterator<R>,
+  mut out_vot: VOTable<VoidTableDataContent>,
+  mut write: W,
+  output_fmt: OutputFormat,
+  separator: char,
+  transform: F,
+) -> Result<(), VOTableError>
+where
+  R: BufRead,
+  W: Write,
+  F: Fn(&mut [VOTableValue]) -> Result<(), VOTableError>,
+{
+  match output_fmt {
+    OutputFormat::CSV => {
+      write_csv_header(&out_vot, &mut write, separator)?;
+      // Write data
+      for row in it.to_row_value_iter() {
+        let mut row = row?;
+        transform(&mut row)?;
+        let mut field_it = row.into_iter();
+        if let Some(field) = field_it.next() {
+          write_1st_csv_field_with_newline(&mut write, field.to_string().as_str(), separator)?;
+          for field in field_it {
+            write_csv_field(&mut write, field.to_string().as_str(), separator)?;
+          }
+        }
+      }
+      // End with a newline
+      write.write_all(b"\n").map_err(VOTableError::Io)
+    }
+    _ => {
+      let mut writer = new_xml_writer(write, None, None);
+      // Also convert the input VOTable since its end is written from it
+      for votable in [&mut it.votable, &mut out_vot] {
+        match output_fmt {
+          OutputFormat::XmlTabledata => votable.to_tabledata(),
+          OutputFormat::XmlBinary => votable.to_binary(),
+          _ => votable.to_binary2(),
+        }?;
+      }
+      out_vot.write_to_data_beginning(&mut writer, &(), false)?;
+      let schema = get_schema(&out_vot);
+      let mut error: Option<VOTableError> = None;
+      let rows = it.to_row_value_iter().map_while(|r| {
+        match r.and_then(|mut row| transform(&mut row).map(|()| row)) {
+          Ok(row) => Some(row),
+          Err(e) => {
+            error = Some(e);
+            None
+          }
+        }
+      });
+      match output_fmt {
+        OutputFormat::XmlTabledata => {
+          InMemTableDataRows::write_tabledata_rows(&mut writer, rows, schema)
+        }
+        OutputFormat::XmlBinary => {
+          InMemTableDataRows::write_binary_rows(writer.inner(), rows, schema)
+        }
+        _ => InMemTableDataRows::write_binary2_rows(writer.inner(), rows, schema),
+      }?;
+      match error {
+        Some(e) => Err(e),
+        None => it
+          .read_to_end()
+          .and_then(|mut in_vot| in_vot.write_from_data_end(&mut writer, &(), false)),
+      }
+    }
+  }
+}
+
 fn td_to_binary_par<R: BufRead + Send, W: Write>(
   mut it: SimpleVOTableRowIterator<R>,
   write: W,
@@ -909,3 +1133,166 @@ where
     Err(e) => panic!("Error reading rows: {:?}", e),
   }
 }
+
+/// Number of tables in the given resource and its sub-resources.
+fn n_tables<C: TableDataContent>(resource: &Resource<C>) -> usize {
+  resource
+    .sub_elems
+    .iter()
+    .map(|sub_elem| match &sub_elem.resource_or_table {
+      ResourceOrTable::Table(_) => 1,
+      ResourceOrTable::Resource(sub_resource) => n_tables(sub_resource),
+    })
+    .sum()
+}
+
+/// Compute the statistics of the columns of the first table of the given VOTable.
+/// # Panics
+/// if the given VOTable does not contain a table.
+pub fn compute_stats<R: BufRead + Send>(
+  it: SimpleVOTableRowIterator<R>,
+  parallel: Option<usize>,
+  chunk_size: usize,
+) -> Result<TableStats, VOTableError> {
+  compute_stats_and_read_to_end(it, parallel, chunk_size, false).map(|(stats, _)| stats)
+}
+
+/// Same as [compute_stats], also returning, if `read_to_end` is `true`, the VOTable metadata read
+/// till the end of the document (i.e. including the tables following the first one).
+fn compute_stats_and_read_to_end<R: BufRead + Send>(
+  mut it: SimpleVOTableRowIterator<R>,
+  parallel: Option<usize>,
+  chunk_size: usize,
+  read_to_end: bool,
+) -> Result<(TableStats, Option<VOTable<VoidTableDataContent>>), VOTableError> {
+  let mut stats = TableStats::from_table_elems(
+    it.votable
+      .get_first_table()
+      .expect("No table found!")
+      .elems
+      .as_slice(),
+  );
+  match parallel {
+    None => {
+      for row in it.to_row_value_iter() {
+        stats.push_row(&row?);
+      }
+      let votable = read_to_end.then(|| it.read_to_end()).transpose()?;
+      Ok((stats, votable))
+    }
+    Some(n_threads) => {
+      let schema = get_schema(&it.votable);
+      match it.data_type() {
+        TableOrBinOrBin2::TableData => {
+          fn decode(raw_td_row: &[u8], schema: &TableSchema) -> Vec<VOTableValue> {
+            match FieldIteratorUnbuffered::new(raw_td_row)
+              .zip(schema.iter())
+              .map(|(res, schema)| res.and_then(|field_str| schema.value_from_str(&field_str)))
+              .collect::<Result<Vec<VOTableValue>, VOTableError>>()
+            {
+              Ok(row) => row,
+              Err(e) => panic!("Error reading row: {:?}", e),
+            }
+          }
+          let mut raw_row_it = it.to_owned_tabledata_row_iterator();
+          let stats = stats_par(
+            &mut raw_row_it,
+            schema,
+            stats,
+            decode,
+            n_threads,
+            chunk_size,
+          )?;
+          let votable = read_to_end.then(|| raw_row_it.read_to_end()).transpose()?;
+          Ok((stats, votable))
+        }
+        TableOrBinOrBin2::Binary => {
+          fn decode(raw_bin_row: &[u8], schema: &TableSchema) -> Vec<VOTableValue> {
+            binrow2fieldit(raw_bin_row, schema).collect()
+          }
+          let mut raw_row_it = it.to_owned_binary_row_iterator();
+          let stats = stats_par(
+            &mut raw_row_it,
+            schema,
+            stats,
+            decode,
+            n_threads,
+            chunk_size,
+          )?;
+          let votable = read_to_end.then(|| raw_row_it.read_to_end()).transpose()?;
+          Ok((stats, votable))
+        }
+        TableOrBinOrBin2::Binary2 => {
+          fn decode(raw_bin2_row: &[u8], schema: &TableSchema) -> Vec<VOTableValue> {
+            bin2row2fieldit(raw_bin2_row, schema).collect()
+          }
+          let mut raw_row_it = it.to_owned_binary2_row_iterator();
+          let stats = stats_par(
+            &mut raw_row_it,
+            schema,
+            stats,
+            decode,
+            n_threads,
+            chunk_size,
+          )?;
+          let votable = read_to_end.then(|| raw_row_it.read_to_end()).transpose()?;
+          Ok((stats, votable))
+        }
+        TableOrBinOrBin2::Fits(_) => Err(VOTableError::Custom(String::from(
+          "FITS data not supported",
+        ))),
+      }
+    }
+  }
+}
+
+/// Each thread computes the statistics of the row chunks it receives, the statistics of all
+/// threads are then merged.
+fn stats_par<I>(
+  raw_row_it: &mut I,
+  schema: TableSchema,
+  mut stats: TableStats,
+  decode: fn(&[u8], &TableSchema) -> Vec<VOTableValue>,
+  n_threads: usize,
+  chunk_size: usize,
+) -> Result<TableStats, VOTableError>
+where
+  I: Iterator<Item = Result<Vec<u8>, VOTableError>> + Send,
+{
+  let n_threads = n_threads.max(1);
+  let (sender, receiver) = bounded::<Vec<Vec<u8>>>(n_threads);
+  scope(|s| {
+    let workers = (0..n_threads)
+      .map(|_| {
+        let receiver = receiver.clone();
+        let schema = &schema;
+        let mut thread_stats = stats.clone();
+        s.spawn(move || {
+          for raw_rows_chunk in receiver.iter() {
+            for raw_row in raw_rows_chunk {
+              thread_stats.push_row(&decode(&raw_row, schema));
+            }
+          }
+          thread_stats
+        })
+      })
+      .collect::<Vec<_>>();
+    // Producer in the current thread
+    let mut rows_chunk = load_n(raw_row_it, chunk_size);
+    while !rows_chunk.is_empty() {
+      sender
+        .send(rows_chunk)
+        .expect("Unexpected error sending raw rows");
+      rows_chunk = load_n(raw_row_it, chunk_size);
+    }
+    // Close the channel, otherwise the workers will never exit the for-loop
+    drop(sender);
+    for worker in workers {
+      let thread_stats = worker
+        .join()
+        .expect("Error computing statistics in parallel");
+      stats.merge(&thread_stats)?;
+    }
+    Ok(stats)
+  })
+}
@@ -78,12 +78,18 @@ pub enum Tag {
   STREAM,
   // MIVOT related tags
   VODML,
-  // REPORT
-  // MODEL
-  // GLOBAL
-  // TEMPLATE
-  // ATTRIBUTE / COLLECTION / INSTANCE / REFERENCE
-  // JOIN / WHERE / PRIMARY_KEY / FOREIGN_KEY
+  GLOBALS,
+  TEMPLATES,
+  INSTANCE,
+  COLLECTION,
+  REFERENCE,
+  JOIN,
+  REPORT,
+  MODEL,
+  ATTRIBUTE,
+  WHERE,
+  PRIMARYKey,
+  FOREIGNKey,
 }
 const TAGS: [Tag; Tag::len()] = [
   Tag::VOTABLE,
@@ -107,6 +113,18 @@ const TAGS: [Tag; Tag::len()] = [
   Tag::MAX,
   Tag::STREAM,
   Tag::VODML,
+  Tag::GLOBALS,
+  Tag::TEMPLATES,
+  Tag::INSTANCE,
+  Tag::COLLECTION,
+  Tag::REFERENCE,
+  Tag::JOIN,
+  Tag::REPORT,
+  Tag::MODEL,
+  Tag::ATTRIBUTE,
+  Tag::WHERE,
+  Tag::PRIMARYKey,
+  Tag::FOREIGNKey,
 ];
 
 impl Tag {
@@ -114,7 +132,7 @@ impl Tag {
     *self as usize
   }
   pub const fn len() -> usize {
-    Self::VODML as usize + 1
+    Self::FOREIGNKey as usize + 1
   }
   pub const fn array() -> [Tag; Tag::len()] {
     TAGS
@@ -126,29 +144,7 @@ impl Tag {
     [default; Tag::len()]
   }
   pub const fn new_array_of_vec<T>() -> [Vec<T>; Tag::len()] {
-    [
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-      Vec::new(),
-    ]
+    [const { Vec::new() }; Tag::len()]
   }
   pub fn iterator() -> Iter<'static, Tag> {
     TAGS.iter()
@@ -178,7 +174,19 @@ impl Tag {
       Self::MIN => b'n', // Final letter since both min and max start be 'm'
       Self::MAX => b'x', // Final letter since both min and max start be 'm'
       Self::STREAM => b's',
-      Self::VODML => b'M', // For 'M'ivot of vod'M'l
+      Self::VODML => b'M',     // For 'M'ivot of vod'M'l
+      Self::GLOBALS => b'L',   // 'G' already used by GROUP
+      Self::TEMPLATES => b'S', // 'T' already used by TABLE
+      Self::INSTANCE => b'I',
+      Self::COLLECTION => b'N', // 'C' already used by COOSYS
+      Self::REFERENCE => b'X',  // 'R' already used by RESOURCE
+      Self::JOIN => b'J',
+      Self::REPORT => b'r',
+      Self::MODEL => b'm',
+      Self::ATTRIBUTE => b'a',
+      Self::WHERE => b'w',
+      Self::PRIMARYKey => b'k',
+      Self::FOREIGNKey => b'y',
     }
   }
 }
@@ -209,6 +217,18 @@ impl FromStr for Tag {
       "MAX" => Ok(Self::MAX),
       "STREAM" => Ok(Self::STREAM),
       "VODML" => Ok(Self::VODML),
+      "GLOBALS" => Ok(Self::GLOBALS),
+      "TEMPLATES" => Ok(Self::TEMPLATES),
+      "INSTANCE" => Ok(Self::INSTANCE),
+      "COLLECTION" => Ok(Self::COLLECTION),
+      "REFERENCE" => Ok(Self::REFERENCE),
+      "JOIN" => Ok(Self::JOIN),
+      "REPORT" => Ok(Self::REPORT),
+      "MODEL" => Ok(Self::MODEL),
+      "ATTRIBUTE" => Ok(Self::ATTRIBUTE),
+      "WHERE" => Ok(Self::WHERE),
+      "PRIMARY_KEY" => Ok(Self::PRIMARYKey),
+      "FOREIGN_KEY" => Ok(Self::FOREIGNKey),
       _ => Err(VOTableError::Custom(format!("Tag '{}' not recognized.", s))),
     }
   }
@@ -216,6 +236,10 @@ impl FromStr for Tag {
 
 impl Display for Tag {
   fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-    Debug::fmt(self, f)
+    match self {
+      Self::PRIMARYKey => f.write_str("PRIMARY_KEY"),
+      Self::FOREIGNKey => f.write_str("FOREIGN_KEY"),
+      _ => Debug::fmt(self, f),
+    }
   }
 }
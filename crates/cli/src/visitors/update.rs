@@ -13,7 +13,24 @@ use votable::{
   group::{Group, GroupElem, TableGroup, TableGroupElem},
   info::Info,
   link::Link,
-  mivot::visitors::donothing::DoNothing,
+  mivot::{
+    globals::{
+      collection::{CollectionElems as GCElems, InstanceOrRef as GCInstanceOrRef},
+      instance::{
+        collection::collection::{CollectionElems as GICElems, InstanceOrRef as GICInstanceOrRef},
+        InstanceElem as GIElem,
+      },
+      GlobalsElem,
+    },
+    templates::instance::{
+      collection::collection::{CollectionElems as TICElems, InstanceOrRef as TICInstanceOrRef},
+      InstanceElem as TIElem,
+    },
+    AttributeC, AttributeI, CollectionGC, CollectionGIC, CollectionGICC, CollectionTIC,
+    CollectionTICC, ForeignKey, Globals, InstanceGCI, InstanceGI, InstanceGII, InstanceTI,
+    InstanceTII, Join, Model, PrimaryKeyDyn, PrimaryKeyS, Projection, RefDynTICR, RefDynTIR,
+    RefGCR, RefGIR, Report, Templates, Vodml, VodmlVisitor, WhereJ, WhereT,
+  },
   param::Param,
   paramref::ParamRef,
   resource::{Resource, ResourceElem, ResourceOrTable},
@@ -44,6 +61,13 @@ pub struct UpdateVisitor {
   rm_selector_by_tag: [Vec<Condition>; Tag::len()],
   /// Tag (and vid) of a sub-element to be removed from the current tag, in the order they appear in the sub elem
   tagvid_to_rm_stack: Vec<Vec<(Tag, String)>>,
+  /// FIELDs renamed or removed, to be reported in the MIVOT annotations
+  projection: Projection,
+  /// Key, in the projection, of the TABLE being visited
+  table_key: String,
+  /// Number of removed MIVOT elements already logged (in streaming mode, the VOTable is visited
+  /// twice: the first time to write its head and the second time to write its tail)
+  n_mivot_removed_logged: usize,
 }
 
 impl UpdateVisitor {
@@ -64,9 +88,23 @@ impl UpdateVisitor {
       updates_by_tag,
       rm_selector_by_tag,
       tagvid_to_rm_stack: Vec::with_capacity(8),
+      projection: Projection::new(),
+      table_key: String::new(),
+      n_mivot_removed_logged: 0,
     }
   }
 
+  /// Update the MIVOT annotations of the given VOTable according to the `FIELD`s
+  /// renamed (`ID` or `name`) or removed during the visit.
+  pub fn update_mivot_refs<C: TableDataContent>(&mut self, votable: &mut VOTable<C>) {
+    let report = self.projection.apply_to_votable(votable);
+    let n_removed = report.removed.len();
+    for removed in report.removed.into_iter().skip(self.n_mivot_removed_logged) {
+      warn!("MIVOT element removed after FIELD removal: {}", removed);
+    }
+    self.n_mivot_removed_logged = self.n_mivot_removed_logged.max(n_removed);
+  }
+
   /// Add the current tag to the count map (i.e. number of occurrences of each possible sub-tag)
   /// of the parent tag in the hierarchy, and return its count.
   fn add_to_count(&mut self, tag: &Tag) -> u16 {
@@ -175,6 +213,214 @@ impl UpdateVisitor {
       .parse::<usize>()
       .unwrap()
   }
+
+  /// Creates the visitor of the `VODML` of the current `RESOURCE`.
+  /// The removal of the `VODML` itself is made when ending the visit of the `RESOURCE`.
+  fn new_mivot_visitor(&mut self) -> Self {
+    const TAG: Tag = Tag::VODML;
+    let vid = self.get_sub_elem_vid(TAG, false);
+    self.select_mivot_rm(TAG, vid, None, None);
+    Self {
+      cur_vid: self.cur_vid.clone(),
+      cur_counts: vec![HashMap::with_capacity(20)],
+      updates_by_tag: self.updates_by_tag.clone(),
+      rm_selector_by_tag: self.rm_selector_by_tag.clone(),
+      tagvid_to_rm_stack: vec![Default::default()],
+      projection: Projection::new(),
+      table_key: String::new(),
+      n_mivot_removed_logged: 0,
+    }
+  }
+
+  /// Add the given MIVOT element in the list of elements to be removed from its parent, if selected.
+  fn select_mivot_rm(
+    &mut self,
+    tag: Tag,
+    vid: String,
+    dmid: Option<&String>,
+    dmrole: Option<&String>,
+  ) {
+    if self.rm_selector_by_tag[tag.index()]
+      .iter()
+      .any(|e| e.is_ok_mivot(vid.as_str(), dmid, dmrole))
+    {
+      append_to_rm_list(&mut self.tagvid_to_rm_stack, tag, vid);
+    }
+  }
+
+  /// Apply the modifications selecting the given MIVOT element.
+  fn apply_mivot_updates<T>(
+    &self,
+    tag: Tag,
+    vid: &str,
+    dmid: Option<&String>,
+    dmrole: Option<&String>,
+    elem: &mut T,
+  ) -> Result<(), VOTableError>
+  where
+    for<'b> &'b mut T: Into<VOTableWrappedElemMut<'b, VoidTableDataContent>>,
+  {
+    for e in &self.updates_by_tag[tag.index()] {
+      if e.condition.is_ok_mivot(vid, dmid, dmrole) {
+        e.action
+          .clone()
+          .apply_on_wrapped_elem_mut((&mut *elem).into())?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Visit a MIVOT element having no sub-element.
+  fn visit_mivot_elem<T>(
+    &mut self,
+    tag: Tag,
+    may_be_repeated: bool,
+    dmid: Option<String>,
+    dmrole: Option<String>,
+    elem: &mut T,
+  ) -> Result<(), VOTableError>
+  where
+    for<'b> &'b mut T: Into<VOTableWrappedElemMut<'b, VoidTableDataContent>>,
+  {
+    let vid = self.get_sub_elem_vid(tag, may_be_repeated);
+    self.apply_mivot_updates(tag, vid.as_str(), dmid.as_ref(), dmrole.as_ref(), elem)?;
+    self.select_mivot_rm(tag, vid, dmid.as_ref(), dmrole.as_ref());
+    Ok(())
+  }
+
+  /// Start visiting a MIVOT element possibly having sub-elements.
+  fn visit_mivot_start(
+    &mut self,
+    tag: Tag,
+    may_be_repeated: bool,
+    dmid: Option<&String>,
+    dmrole: Option<&String>,
+  ) {
+    self.go_down(tag, may_be_repeated);
+    let vid = self.get_vid().to_string();
+    self.select_mivot_rm(tag, vid, dmid, dmrole);
+    self.tagvid_to_rm_stack.push(Default::default());
+  }
+
+  /// End visiting a MIVOT element possibly having sub-elements (which have already been removed).
+  fn visit_mivot_ended<T>(
+    &mut self,
+    tag: Tag,
+    dmid: Option<String>,
+    dmrole: Option<String>,
+    elem: &mut T,
+  ) -> Result<(), VOTableError>
+  where
+    for<'b> &'b mut T: Into<VOTableWrappedElemMut<'b, VoidTableDataContent>>,
+  {
+    self.apply_mivot_updates(tag, self.get_vid(), dmid.as_ref(), dmrole.as_ref(), elem)?;
+    self.go_up()
+  }
+
+  /// Returns the (tag, vid) of the sub-elements of the current MIVOT element to be removed,
+  /// starting from the last one (so that removing an element does not change the vid of the
+  /// next elements to be removed).
+  fn pop_rm_list(&mut self, tag: &str) -> Vec<(Tag, String)> {
+    let mut to_rm = self.tagvid_to_rm_stack.pop().unwrap();
+    to_rm.reverse();
+    for (tag_to_rm, vid_to_rm) in &to_rm {
+      trace!("In {}, rm tag {} vid={}", tag, tag_to_rm, vid_to_rm);
+    }
+    to_rm
+  }
+
+  /// Remove from `elems` the element of given Virtual ID, the Virtual IDs being re-computed from
+  /// the tag of each element.
+  fn rm_from<T, F>(&mut self, elems: &mut Vec<T>, vid_to_rm: &str, tag_of: F)
+  where
+    F: Fn(&T) -> Tag,
+  {
+    self.clear_current_counts();
+    if let Some(index) = elems
+      .iter()
+      .position(|elem| vid_to_rm == self.get_sub_elem_vid(tag_of(elem), true))
+    {
+      elems.remove(index);
+    }
+  }
+
+  /// Remove the selected `PRIMARY_KEY`s and sub-elements of an `INSTANCE`.
+  fn rm_in_instance<P, E, F>(
+    &mut self,
+    tag: &str,
+    primarykeys: &mut Vec<P>,
+    elems: &mut Vec<E>,
+    tag_of: F,
+  ) where
+    F: Fn(&E) -> Tag,
+  {
+    for (tag_to_rm, vid_to_rm) in self.pop_rm_list(tag) {
+      match tag_to_rm {
+        Tag::PRIMARYKey => {
+          primarykeys.remove(Self::extract_last_digit(vid_to_rm.as_str()) - 1);
+        }
+        _ => self.rm_from(elems, vid_to_rm.as_str(), &tag_of),
+      }
+    }
+  }
+
+  /// Remove the selected sub-elements of a `COLLECTION` in an `INSTANCE` in `GLOBALS`.
+  fn rm_in_globals_collection(&mut self, tag: &str, elems: &mut GICElems) {
+    for (_, vid_to_rm) in self.pop_rm_list(tag) {
+      let index = Self::extract_last_digit(vid_to_rm.as_str()) - 1;
+      match elems {
+        GICElems::Attribute(attributes) => {
+          attributes.remove(index);
+        }
+        GICElems::Collection(collections) => {
+          collections.remove(index);
+        }
+        GICElems::InstanceOrRef(elems) => self.rm_from(elems, vid_to_rm.as_str(), |e| match e {
+          GICInstanceOrRef::Instance(_) => Tag::INSTANCE,
+          GICInstanceOrRef::Reference(_) => Tag::REFERENCE,
+        }),
+        GICElems::Join(_) => *elems = GICElems::InstanceOrRef(Vec::new()),
+      }
+    }
+  }
+
+  /// Remove the selected sub-elements of a `COLLECTION` in an `INSTANCE` in `TEMPLATES`.
+  fn rm_in_templates_collection(&mut self, tag: &str, elems: &mut TICElems) {
+    for (_, vid_to_rm) in self.pop_rm_list(tag) {
+      let index = Self::extract_last_digit(vid_to_rm.as_str()) - 1;
+      match elems {
+        TICElems::Attribute(attributes) => {
+          attributes.remove(index);
+        }
+        TICElems::Collection(collections) => {
+          collections.remove(index);
+        }
+        TICElems::InstanceOrRef(elems) => self.rm_from(elems, vid_to_rm.as_str(), |e| match e {
+          TICInstanceOrRef::Instance(_) => Tag::INSTANCE,
+          TICInstanceOrRef::Reference(_) => Tag::REFERENCE,
+        }),
+        TICElems::Join(_) => *elems = TICElems::InstanceOrRef(Vec::new()),
+      }
+    }
+  }
+}
+
+fn globals_instance_elem_tag(elem: &GIElem) -> Tag {
+  match elem {
+    GIElem::Attribute(_) => Tag::ATTRIBUTE,
+    GIElem::Instance(_) => Tag::INSTANCE,
+    GIElem::Reference(_) => Tag::REFERENCE,
+    GIElem::Collection(_) => Tag::COLLECTION,
+  }
+}
+
+fn templates_instance_elem_tag(elem: &TIElem) -> Tag {
+  match elem {
+    TIElem::Attribute(_) => Tag::ATTRIBUTE,
+    TIElem::Instance(_) => Tag::INSTANCE,
+    TIElem::Reference(_) => Tag::REFERENCE,
+    TIElem::Collection(_) => Tag::COLLECTION,
+  }
 }
 
 fn append_to_rm_list(tagvid_to_rm_stack: &mut [Vec<(Tag, String)>], tag: Tag, vid: String) {
@@ -184,7 +430,7 @@ fn append_to_rm_list(tagvid_to_rm_stack: &mut [Vec<(Tag, String)>], tag: Tag, vi
 impl<C: TableDataContent> VOTableVisitor<C> for UpdateVisitor {
   type E = VOTableError;
 
-  type M = DoNothing<Self::E>;
+  type M = UpdateVisitor;
 
   fn visit_votable_start(&mut self, _votable: &mut VOTable<C>) -> Result<(), Self::E> {
     const TAG: Tag = Tag::VOTABLE;
@@ -400,7 +646,7 @@ impl<C: TableDataContent> VOTableVisitor<C> for UpdateVisitor {
   }
 
   fn get_mivot_visitor(&mut self) -> Self::M {
-    Self::M::new()
+    self.new_mivot_visitor()
   }
 
   fn visit_table_group_start(&mut self, group: &mut TableGroup) -> Result<(), Self::E> {
@@ -574,6 +820,8 @@ impl<C: TableDataContent> VOTableVisitor<C> for UpdateVisitor {
         _ => {}
       }
     }
+    // MIVOT refs point to the FIELD ID, or to its name only if it has no ID
+    let ref_ = field.id.clone().unwrap_or_else(|| field.name.clone());
     // First remove, and then apply other modifications
     let vid = self.get_vid();
     for e in &self.updates_by_tag[TAG.index()] {
@@ -583,6 +831,12 @@ impl<C: TableDataContent> VOTableVisitor<C> for UpdateVisitor {
         )?;
       }
     }
+    let new_ref = field.id.as_ref().unwrap_or(&field.name);
+    if ref_ != *new_ref {
+      self
+        .projection
+        .rename_by_ref(self.table_key.clone(), ref_, new_ref.clone());
+    }
     self.go_up()
   }
 
@@ -819,6 +1073,7 @@ impl<C: TableDataContent> VOTableVisitor<C> for UpdateVisitor {
   fn visit_table_start(&mut self, table: &mut Table<C>) -> Result<(), Self::E> {
     const TAG: Tag = Tag::TABLE;
     self.go_down(TAG, true);
+    self.table_key = Projection::table_key(table).to_string();
     let vid = self.get_vid().to_string();
     for e in &self.rm_selector_by_tag[TAG.index()] {
       if e.is_ok(vid.as_str(), table.id.as_ref(), table.name.as_ref()) {
@@ -852,8 +1107,9 @@ impl<C: TableDataContent> VOTableVisitor<C> for UpdateVisitor {
               break;
             }
           }
-          if let Some(index) = rm_index {
-            table.elems.remove(index);
+          if let Some(TableElem::Field(field)) = rm_index.map(|index| table.elems.remove(index)) {
+            let ref_ = field.id.unwrap_or(field.name);
+            self.projection.remove_by_ref(self.table_key.clone(), ref_);
           }
         }
         Tag::LINK => {
@@ -1096,3 +1352,385 @@ impl<C: TableDataContent> VOTableVisitor<C> for UpdateVisitor {
     self.go_up()
   }
 }
+
+impl VodmlVisitor for UpdateVisitor {
+  type E = VOTableError;
+
+  fn visit_vodml_start(&mut self, _vodml: &mut Vodml) -> Result<(), Self::E> {
+    // Removal selection made by the parent RESOURCE visitor
+    self.go_down(Tag::VODML, false);
+    self.tagvid_to_rm_stack.push(Default::default());
+    Ok(())
+  }
+  fn visit_vodml_ended(&mut self, vodml: &mut Vodml) -> Result<(), Self::E> {
+    for (tag_to_rm, vid_to_rm) in self.pop_rm_list(vodml.tag()) {
+      match tag_to_rm {
+        Tag::REPORT => vodml.report = None,
+        Tag::MODEL => {
+          vodml
+            .models
+            .remove(Self::extract_last_digit(vid_to_rm.as_str()) - 1);
+        }
+        Tag::GLOBALS => vodml.globals = None,
+        Tag::TEMPLATES => {
+          vodml
+            .templates
+            .remove(Self::extract_last_digit(vid_to_rm.as_str()) - 1);
+        }
+        _ => {}
+      }
+    }
+    self.visit_mivot_ended(Tag::VODML, None, None, vodml)
+  }
+
+  fn visit_report(&mut self, report: &mut Report) -> Result<(), Self::E> {
+    self.visit_mivot_elem(Tag::REPORT, false, None, None, report)
+  }
+
+  fn visit_model(&mut self, model: &mut Model) -> Result<(), Self::E> {
+    self.visit_mivot_elem(Tag::MODEL, true, None, None, model)
+  }
+
+  fn visit_globals_start(&mut self, _globals: &mut Globals) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::GLOBALS, false, None, None);
+    Ok(())
+  }
+  fn visit_globals_ended(&mut self, globals: &mut Globals) -> Result<(), Self::E> {
+    for (_, vid_to_rm) in self.pop_rm_list(globals.tag()) {
+      self.rm_from(&mut globals.elems, vid_to_rm.as_str(), |e| match e {
+        GlobalsElem::Instance(_) => Tag::INSTANCE,
+        GlobalsElem::Collection(_) => Tag::COLLECTION,
+      });
+    }
+    self.visit_mivot_ended(Tag::GLOBALS, None, None, globals)
+  }
+
+  fn visit_templates_start(&mut self, _templates: &mut Templates) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::TEMPLATES, true, None, None);
+    Ok(())
+  }
+  fn visit_templates_ended(&mut self, templates: &mut Templates) -> Result<(), Self::E> {
+    for (tag_to_rm, vid_to_rm) in self.pop_rm_list(templates.tag()) {
+      let index = Self::extract_last_digit(vid_to_rm.as_str()) - 1;
+      match tag_to_rm {
+        Tag::WHERE => {
+          templates.wheres.remove(index);
+        }
+        Tag::INSTANCE => {
+          templates.instances.remove(index);
+        }
+        _ => {}
+      }
+    }
+    self.visit_mivot_ended(Tag::TEMPLATES, None, None, templates)
+  }
+
+  fn visit_instance_childof_globals_start(
+    &mut self,
+    instance: &mut InstanceGI,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::INSTANCE, true, instance.dmid.as_ref(), None);
+    Ok(())
+  }
+  fn visit_instance_childof_globals_ended(
+    &mut self,
+    instance: &mut InstanceGI,
+  ) -> Result<(), Self::E> {
+    self.rm_in_instance(
+      instance.tag(),
+      &mut instance.primarykeys,
+      &mut instance.elems,
+      globals_instance_elem_tag,
+    );
+    let dmid = instance.dmid.clone();
+    self.visit_mivot_ended(Tag::INSTANCE, dmid, None, instance)
+  }
+
+  fn visit_instance_childof_instance_in_globals_start(
+    &mut self,
+    instance: &mut InstanceGII,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(
+      Tag::INSTANCE,
+      true,
+      instance.dmid.as_ref(),
+      Some(&instance.dmrole),
+    );
+    Ok(())
+  }
+  fn visit_instance_childof_instance_in_globals_ended(
+    &mut self,
+    instance: &mut InstanceGII,
+  ) -> Result<(), Self::E> {
+    self.rm_in_instance(
+      instance.tag(),
+      &mut instance.primarykeys,
+      &mut instance.elems,
+      globals_instance_elem_tag,
+    );
+    let dmid = instance.dmid.clone();
+    let dmrole = Some(instance.dmrole.clone());
+    self.visit_mivot_ended(Tag::INSTANCE, dmid, dmrole, instance)
+  }
+
+  fn visit_instance_childof_collection_in_globals_start(
+    &mut self,
+    instance: &mut InstanceGCI,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::INSTANCE, true, instance.dmid.as_ref(), None);
+    Ok(())
+  }
+  fn visit_instance_childof_collection_in_globals_ended(
+    &mut self,
+    instance: &mut InstanceGCI,
+  ) -> Result<(), Self::E> {
+    self.rm_in_instance(
+      instance.tag(),
+      &mut instance.primarykeys,
+      &mut instance.elems,
+      globals_instance_elem_tag,
+    );
+    let dmid = instance.dmid.clone();
+    self.visit_mivot_ended(Tag::INSTANCE, dmid, None, instance)
+  }
+
+  fn visit_collection_childof_instance_in_globals_start(
+    &mut self,
+    collection: &mut CollectionGIC,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(
+      Tag::COLLECTION,
+      true,
+      collection.dmid.as_ref(),
+      Some(&collection.dmrole),
+    );
+    Ok(())
+  }
+  fn visit_collection_childof_instance_in_globals_ended(
+    &mut self,
+    collection: &mut CollectionGIC,
+  ) -> Result<(), Self::E> {
+    self.rm_in_globals_collection(collection.tag(), &mut collection.elems);
+    let dmid = collection.dmid.clone();
+    let dmrole = Some(collection.dmrole.clone());
+    self.visit_mivot_ended(Tag::COLLECTION, dmid, dmrole, collection)
+  }
+
+  fn visit_collection_childof_globals_start(
+    &mut self,
+    collection: &mut CollectionGC,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::COLLECTION, true, Some(&collection.dmid), None);
+    Ok(())
+  }
+  fn visit_collection_childof_globals_ended(
+    &mut self,
+    collection: &mut CollectionGC,
+  ) -> Result<(), Self::E> {
+    for (_, vid_to_rm) in self.pop_rm_list(collection.tag()) {
+      match &mut collection.elems {
+        GCElems::InstanceOrRef(elems) => self.rm_from(elems, vid_to_rm.as_str(), |e| match e {
+          GCInstanceOrRef::Instance(_) => Tag::INSTANCE,
+          GCInstanceOrRef::Reference(_) => Tag::REFERENCE,
+        }),
+        GCElems::Join(_) => collection.elems = GCElems::InstanceOrRef(Vec::new()),
+      }
+    }
+    let dmid = Some(collection.dmid.clone());
+    self.visit_mivot_ended(Tag::COLLECTION, dmid, None, collection)
+  }
+
+  fn visit_collection_childof_collection_in_globals_start(
+    &mut self,
+    collection: &mut CollectionGICC,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::COLLECTION, true, collection.dmid.as_ref(), None);
+    Ok(())
+  }
+  fn visit_collection_childof_collection_in_globals_ended(
+    &mut self,
+    collection: &mut CollectionGICC,
+  ) -> Result<(), Self::E> {
+    self.rm_in_globals_collection(collection.tag(), &mut collection.elems);
+    let dmid = collection.dmid.clone();
+    self.visit_mivot_ended(Tag::COLLECTION, dmid, None, collection)
+  }
+
+  fn visit_instance_childof_templates_start(
+    &mut self,
+    instance: &mut InstanceTI,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::INSTANCE, true, instance.dmid.as_ref(), None);
+    Ok(())
+  }
+  fn visit_instance_childof_templates_ended(
+    &mut self,
+    instance: &mut InstanceTI,
+  ) -> Result<(), Self::E> {
+    self.rm_in_instance(
+      instance.tag(),
+      &mut instance.primarykeys,
+      &mut instance.elems,
+      templates_instance_elem_tag,
+    );
+    let dmid = instance.dmid.clone();
+    self.visit_mivot_ended(Tag::INSTANCE, dmid, None, instance)
+  }
+
+  fn visit_instance_childof_instance_in_templates_start(
+    &mut self,
+    instance: &mut InstanceTII,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(
+      Tag::INSTANCE,
+      true,
+      instance.dmid.as_ref(),
+      Some(&instance.dmrole),
+    );
+    Ok(())
+  }
+  fn visit_instance_childof_instance_in_templates_ended(
+    &mut self,
+    instance: &mut InstanceTII,
+  ) -> Result<(), Self::E> {
+    self.rm_in_instance(
+      instance.tag(),
+      &mut instance.primarykeys,
+      &mut instance.elems,
+      templates_instance_elem_tag,
+    );
+    let dmid = instance.dmid.clone();
+    let dmrole = Some(instance.dmrole.clone());
+    self.visit_mivot_ended(Tag::INSTANCE, dmid, dmrole, instance)
+  }
+
+  fn visit_collection_childof_instance_in_templates_start(
+    &mut self,
+    collection: &mut CollectionTIC,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(
+      Tag::COLLECTION,
+      true,
+      collection.dmid.as_ref(),
+      Some(&collection.dmrole),
+    );
+    Ok(())
+  }
+  fn visit_collection_childof_instance_in_templates_ended(
+    &mut self,
+    collection: &mut CollectionTIC,
+  ) -> Result<(), Self::E> {
+    self.rm_in_templates_collection(collection.tag(), &mut collection.elems);
+    let dmid = collection.dmid.clone();
+    let dmrole = Some(collection.dmrole.clone());
+    self.visit_mivot_ended(Tag::COLLECTION, dmid, dmrole, collection)
+  }
+
+  fn visit_collection_childof_collection_in_templates_start(
+    &mut self,
+    collection: &mut CollectionTICC,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::COLLECTION, true, collection.dmid.as_ref(), None);
+    Ok(())
+  }
+  fn visit_collection_childof_collection_in_templates_ended(
+    &mut self,
+    collection: &mut CollectionTICC,
+  ) -> Result<(), Self::E> {
+    self.rm_in_templates_collection(collection.tag(), &mut collection.elems);
+    let dmid = collection.dmid.clone();
+    self.visit_mivot_ended(Tag::COLLECTION, dmid, None, collection)
+  }
+
+  fn visit_reference_dynamic_childof_instance_in_templates_start(
+    &mut self,
+    reference: &mut RefDynTIR,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::REFERENCE, true, None, Some(&reference.dmrole));
+    Ok(())
+  }
+  fn visit_reference_dynamic_childof_instance_in_templates_ended(
+    &mut self,
+    reference: &mut RefDynTIR,
+  ) -> Result<(), Self::E> {
+    for (_, vid_to_rm) in self.pop_rm_list(reference.tag()) {
+      reference
+        .foreignkeys
+        .remove(Self::extract_last_digit(vid_to_rm.as_str()) - 1);
+    }
+    let dmrole = Some(reference.dmrole.clone());
+    self.visit_mivot_ended(Tag::REFERENCE, None, dmrole, reference)
+  }
+
+  fn visit_reference_dynamic_childof_collection_in_templates_start(
+    &mut self,
+    _reference: &mut RefDynTICR,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::REFERENCE, true, None, None);
+    Ok(())
+  }
+  fn visit_reference_dynamic_childof_collection_in_templates_ended(
+    &mut self,
+    reference: &mut RefDynTICR,
+  ) -> Result<(), Self::E> {
+    for (_, vid_to_rm) in self.pop_rm_list(reference.tag()) {
+      reference
+        .foreignkeys
+        .remove(Self::extract_last_digit(vid_to_rm.as_str()) - 1);
+    }
+    self.visit_mivot_ended(Tag::REFERENCE, None, None, reference)
+  }
+
+  fn visit_attribute_childof_instance(&mut self, attr: &mut AttributeI) -> Result<(), Self::E> {
+    let dmrole = Some(attr.dmrole.clone());
+    self.visit_mivot_elem(Tag::ATTRIBUTE, true, None, dmrole, attr)
+  }
+  fn visit_attribute_childof_collection(&mut self, attr: &mut AttributeC) -> Result<(), Self::E> {
+    self.visit_mivot_elem(Tag::ATTRIBUTE, true, None, None, attr)
+  }
+
+  fn visit_reference_static_childof_instance(
+    &mut self,
+    reference: &mut RefGIR,
+  ) -> Result<(), Self::E> {
+    let dmrole = Some(reference.dmrole.clone());
+    self.visit_mivot_elem(Tag::REFERENCE, true, None, dmrole, reference)
+  }
+  fn visit_reference_static_childof_collection(
+    &mut self,
+    reference: &mut RefGCR,
+  ) -> Result<(), Self::E> {
+    self.visit_mivot_elem(Tag::REFERENCE, true, None, None, reference)
+  }
+
+  fn visit_primarykey_static(&mut self, pk: &mut PrimaryKeyS) -> Result<(), Self::E> {
+    self.visit_mivot_elem(Tag::PRIMARYKey, true, None, None, pk)
+  }
+  fn visit_primarykey_dynamic(&mut self, pk: &mut PrimaryKeyDyn) -> Result<(), Self::E> {
+    self.visit_mivot_elem(Tag::PRIMARYKey, true, None, None, pk)
+  }
+
+  fn visit_foreign_key(&mut self, fk: &mut ForeignKey) -> Result<(), Self::E> {
+    self.visit_mivot_elem(Tag::FOREIGNKey, true, None, None, fk)
+  }
+
+  fn visit_join_start(&mut self, _join: &mut Join) -> Result<(), Self::E> {
+    self.visit_mivot_start(Tag::JOIN, false, None, None);
+    Ok(())
+  }
+  fn visit_join_ended(&mut self, join: &mut Join) -> Result<(), Self::E> {
+    for (_, vid_to_rm) in self.pop_rm_list(join.tag()) {
+      join
+        .wheres
+        .remove(Self::extract_last_digit(vid_to_rm.as_str()) - 1);
+    }
+    self.visit_mivot_ended(Tag::JOIN, None, None, join)
+  }
+
+  fn visit_where_childof_join(&mut self, r#where: &mut WhereJ) -> Result<(), Self::E> {
+    self.visit_mivot_elem(Tag::WHERE, true, None, None, r#where)
+  }
+  fn visit_where_childof_templates(&mut self, r#where: &mut WhereT) -> Result<(), Self::E> {
+    self.visit_mivot_elem(Tag::WHERE, true, None, None, r#where)
+  }
+}
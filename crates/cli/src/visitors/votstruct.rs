@@ -11,7 +11,12 @@ use votable::{
   group::{Group, TableGroup},
   info::Info,
   link::Link,
-  mivot::visitors::donothing::DoNothing,
+  mivot::{
+    AttributeC, AttributeI, CollectionGC, CollectionGIC, CollectionGICC, CollectionTIC,
+    CollectionTICC, ForeignKey, Globals, InstanceGCI, InstanceGI, InstanceGII, InstanceTI,
+    InstanceTII, Join, Model, PrimaryKeyDyn, PrimaryKeyS, RefDynTICR, RefDynTIR, RefGCR, RefGIR,
+    Report, Templates, Vodml, VodmlVisitor, WhereJ, WhereT,
+  },
   param::Param,
   paramref::ParamRef,
   resource::Resource,
@@ -277,6 +282,49 @@ impl AsciiStructVisitor {
     Ok(())
   }
 
+  /// Print a MIVOT element possibly having sub-elements, and go down in the tag hierarchy.
+  fn print_mivot_start<T: VOTableElement>(
+    &mut self,
+    tag: Tag,
+    may_be_repeated: bool,
+    elem: &T,
+  ) -> Result<(), StringError> {
+    let curr_indent = self.indent.clone();
+    self.go_down(tag, may_be_repeated);
+    let mut tagp = TagPrinter::new(
+      self.line_width,
+      curr_indent.as_str(),
+      tag,
+      self.get_vid(),
+      self.content_size_min,
+    );
+    elem.for_each_attribute(|key: &str, val: &str| tagp.push_attr_mand(key, val));
+    tagp.print()
+  }
+
+  /// Print a MIVOT element having no sub-element.
+  fn print_mivot_leaf<T: VOTableElement>(
+    &mut self,
+    tag: Tag,
+    may_be_repeated: bool,
+    elem: &T,
+    content: Option<&str>,
+  ) -> Result<(), StringError> {
+    let vid = self.get_sub_elem_vid(tag, may_be_repeated);
+    let mut tagp = TagPrinter::new(
+      self.line_width,
+      self.indent.as_str(),
+      tag,
+      vid.as_str(),
+      self.content_size_min,
+    );
+    elem.for_each_attribute(|key: &str, val: &str| tagp.push_attr_mand(key, val));
+    if let Some(content) = content {
+      tagp.set_content(content);
+    }
+    tagp.print()
+  }
+
   fn append_tag_to_vid(tag: &Tag, vid: &mut Vec<u8>) {
     vid.push(tag.char());
   }
@@ -297,7 +345,7 @@ impl AsciiStructVisitor {
 impl<C: TableDataContent> VOTableVisitor<C> for AsciiStructVisitor {
   type E = StringError;
 
-  type M = DoNothing<Self::E>;
+  type M = AsciiStructVisitor;
 
   fn visit_votable_start(&mut self, votable: &mut VOTable<C>) -> Result<(), Self::E> {
     const TAG: Tag = Tag::VOTABLE;
@@ -383,7 +431,13 @@ impl<C: TableDataContent> VOTableVisitor<C> for AsciiStructVisitor {
   }
 
   fn get_mivot_visitor(&mut self) -> Self::M {
-    Self::M::new()
+    Self {
+      line_width: self.line_width,
+      content_size_min: self.content_size_min,
+      indent: self.indent.clone(),
+      cur_vid: self.cur_vid.clone(),
+      cur_counts: vec![HashMap::with_capacity(20)],
+    }
   }
 
   fn visit_table_group_start(&mut self, group: &mut TableGroup) -> Result<(), Self::E> {
@@ -719,3 +773,241 @@ impl<C: TableDataContent> VOTableVisitor<C> for AsciiStructVisitor {
     self.go_up()
   }
 }
+
+impl VodmlVisitor for AsciiStructVisitor {
+  type E = StringError;
+
+  fn visit_report(&mut self, report: &mut Report) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::REPORT, false, report, report.content.as_deref())
+  }
+
+  fn visit_model(&mut self, model: &mut Model) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::MODEL, true, model, None)
+  }
+
+  fn visit_attribute_childof_instance(&mut self, attr: &mut AttributeI) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::ATTRIBUTE, true, attr, None)
+  }
+
+  fn visit_attribute_childof_collection(&mut self, attr: &mut AttributeC) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::ATTRIBUTE, true, attr, None)
+  }
+
+  fn visit_reference_static_childof_instance(
+    &mut self,
+    reference: &mut RefGIR,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::REFERENCE, true, reference, None)
+  }
+
+  fn visit_reference_static_childof_collection(
+    &mut self,
+    reference: &mut RefGCR,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::REFERENCE, true, reference, None)
+  }
+
+  fn visit_primarykey_static(&mut self, pk: &mut PrimaryKeyS) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::PRIMARYKey, true, pk, None)
+  }
+
+  fn visit_primarykey_dynamic(&mut self, pk: &mut PrimaryKeyDyn) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::PRIMARYKey, true, pk, None)
+  }
+
+  fn visit_foreign_key(&mut self, fk: &mut ForeignKey) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::FOREIGNKey, true, fk, None)
+  }
+
+  fn visit_where_childof_join(&mut self, r#where: &mut WhereJ) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::WHERE, true, r#where, None)
+  }
+
+  fn visit_where_childof_templates(&mut self, r#where: &mut WhereT) -> Result<(), Self::E> {
+    self.print_mivot_leaf(Tag::WHERE, true, r#where, None)
+  }
+
+  fn visit_vodml_start(&mut self, vodml: &mut Vodml) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::VODML, false, vodml)
+  }
+  fn visit_vodml_ended(&mut self, _vodml: &mut Vodml) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_globals_start(&mut self, globals: &mut Globals) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::GLOBALS, false, globals)
+  }
+  fn visit_globals_ended(&mut self, _globals: &mut Globals) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_templates_start(&mut self, templates: &mut Templates) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::TEMPLATES, true, templates)
+  }
+  fn visit_templates_ended(&mut self, _templates: &mut Templates) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_instance_childof_globals_start(
+    &mut self,
+    instance: &mut InstanceGI,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::INSTANCE, true, instance)
+  }
+  fn visit_instance_childof_globals_ended(
+    &mut self,
+    _instance: &mut InstanceGI,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_instance_childof_instance_in_globals_start(
+    &mut self,
+    instance: &mut InstanceGII,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::INSTANCE, true, instance)
+  }
+  fn visit_instance_childof_instance_in_globals_ended(
+    &mut self,
+    _instance: &mut InstanceGII,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_instance_childof_collection_in_globals_start(
+    &mut self,
+    instance: &mut InstanceGCI,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::INSTANCE, true, instance)
+  }
+  fn visit_instance_childof_collection_in_globals_ended(
+    &mut self,
+    _instance: &mut InstanceGCI,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_collection_childof_instance_in_globals_start(
+    &mut self,
+    collection: &mut CollectionGIC,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::COLLECTION, true, collection)
+  }
+  fn visit_collection_childof_instance_in_globals_ended(
+    &mut self,
+    _collection: &mut CollectionGIC,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_collection_childof_globals_start(
+    &mut self,
+    collection: &mut CollectionGC,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::COLLECTION, true, collection)
+  }
+  fn visit_collection_childof_globals_ended(
+    &mut self,
+    _collection: &mut CollectionGC,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_collection_childof_collection_in_globals_start(
+    &mut self,
+    collection: &mut CollectionGICC,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::COLLECTION, true, collection)
+  }
+  fn visit_collection_childof_collection_in_globals_ended(
+    &mut self,
+    _collection: &mut CollectionGICC,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_instance_childof_templates_start(
+    &mut self,
+    instance: &mut InstanceTI,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::INSTANCE, true, instance)
+  }
+  fn visit_instance_childof_templates_ended(
+    &mut self,
+    _instance: &mut InstanceTI,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_instance_childof_instance_in_templates_start(
+    &mut self,
+    instance: &mut InstanceTII,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::INSTANCE, true, instance)
+  }
+  fn visit_instance_childof_instance_in_templates_ended(
+    &mut self,
+    _instance: &mut InstanceTII,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_collection_childof_instance_in_templates_start(
+    &mut self,
+    collection: &mut CollectionTIC,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::COLLECTION, true, collection)
+  }
+  fn visit_collection_childof_instance_in_templates_ended(
+    &mut self,
+    _collection: &mut CollectionTIC,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_collection_childof_collection_in_templates_start(
+    &mut self,
+    collection: &mut CollectionTICC,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::COLLECTION, true, collection)
+  }
+  fn visit_collection_childof_collection_in_templates_ended(
+    &mut self,
+    _collection: &mut CollectionTICC,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_reference_dynamic_childof_instance_in_templates_start(
+    &mut self,
+    reference: &mut RefDynTIR,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::REFERENCE, true, reference)
+  }
+  fn visit_reference_dynamic_childof_instance_in_templates_ended(
+    &mut self,
+    _reference: &mut RefDynTIR,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_reference_dynamic_childof_collection_in_templates_start(
+    &mut self,
+    reference: &mut RefDynTICR,
+  ) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::REFERENCE, true, reference)
+  }
+  fn visit_reference_dynamic_childof_collection_in_templates_ended(
+    &mut self,
+    _reference: &mut RefDynTICR,
+  ) -> Result<(), Self::E> {
+    self.go_up()
+  }
+
+  fn visit_join_start(&mut self, join: &mut Join) -> Result<(), Self::E> {
+    self.print_mivot_start(Tag::JOIN, false, join)
+  }
+  fn visit_join_ended(&mut self, _join: &mut Join) -> Result<(), Self::E> {
+    self.go_up()
+  }
+}
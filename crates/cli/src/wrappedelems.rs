@@ -1,6 +1,12 @@
 //! Wrapper elements for easier dynamic build (but less efficient).
 
 use votable::{
+  mivot::{
+    AttributeC, AttributeI, CollectionGC, CollectionGIC, CollectionGICC, CollectionTIC,
+    CollectionTICC, ForeignKey, Globals, InstanceGCI, InstanceGI, InstanceGII, InstanceTI,
+    InstanceTII, Join, Model, PrimaryKeyDyn, PrimaryKeyS, RefDynTICR, RefDynTIR, RefGCR, RefGIR,
+    Report, Templates, Vodml, WhereJ, WhereT,
+  },
   Binary, Binary2, CooSys, Data, Definitions, Description, Field, FieldRef, Fits, Group,
   HasContent, Info, Link, Max, Min, Opt, Param, ParamRef, Resource, Stream, Table,
   TableDataContent, TableGroup, TimeSys, VOTable, VOTableElement, VOTableError, Values,
@@ -35,13 +41,15 @@ pub enum VOTableWrappedElem<C: TableDataContent> {
   FieldRef(FieldRef),
   ParamRef(ParamRef),
   // MIVOT related tags
-  // Vodml(Vodml),
-  // REPORT
-  // MODEL
-  // GLOBAL
-  // TEMPLATE
-  // ATTRIBUTE / COLLECTION / INSTANCE / REFERENCE
-  // JOIN / WHERE / PRIMARY_KEY / FOREIGN_KEY
+  Vodml(Vodml),
+  Report(Report),
+  Model(Model),
+  Globals(Globals),
+  Templates(Templates),
+  InstanceGI(InstanceGI),
+  InstanceGII(InstanceGII),
+  InstanceTI(InstanceTI),
+  InstanceTII(InstanceTII),
 }
 
 impl<C: TableDataContent> From<VOTable<C>> for VOTableWrappedElem<C> {
@@ -154,6 +162,51 @@ impl<C: TableDataContent> From<ParamRef> for VOTableWrappedElem<C> {
     Self::ParamRef(value)
   }
 }
+impl<C: TableDataContent> From<Vodml> for VOTableWrappedElem<C> {
+  fn from(value: Vodml) -> Self {
+    Self::Vodml(value)
+  }
+}
+impl<C: TableDataContent> From<Report> for VOTableWrappedElem<C> {
+  fn from(value: Report) -> Self {
+    Self::Report(value)
+  }
+}
+impl<C: TableDataContent> From<Model> for VOTableWrappedElem<C> {
+  fn from(value: Model) -> Self {
+    Self::Model(value)
+  }
+}
+impl<C: TableDataContent> From<Globals> for VOTableWrappedElem<C> {
+  fn from(value: Globals) -> Self {
+    Self::Globals(value)
+  }
+}
+impl<C: TableDataContent> From<Templates> for VOTableWrappedElem<C> {
+  fn from(value: Templates) -> Self {
+    Self::Templates(value)
+  }
+}
+impl<C: TableDataContent> From<InstanceGI> for VOTableWrappedElem<C> {
+  fn from(value: InstanceGI) -> Self {
+    Self::InstanceGI(value)
+  }
+}
+impl<C: TableDataContent> From<InstanceGII> for VOTableWrappedElem<C> {
+  fn from(value: InstanceGII) -> Self {
+    Self::InstanceGII(value)
+  }
+}
+impl<C: TableDataContent> From<InstanceTI> for VOTableWrappedElem<C> {
+  fn from(value: InstanceTI) -> Self {
+    Self::InstanceTI(value)
+  }
+}
+impl<C: TableDataContent> From<InstanceTII> for VOTableWrappedElem<C> {
+  fn from(value: InstanceTII) -> Self {
+    Self::InstanceTII(value)
+  }
+}
 
 impl VOTableWrappedElem<VoidTableDataContent> {
   pub fn from_attrs<T, K, V, I>(attrs: I) -> Result<Self, VOTableError>
@@ -195,6 +248,15 @@ impl<C: TableDataContent> VOTableWrappedElem<C> {
       Self::Max(e) => VOTableWrappedElemMut::Max(e),
       Self::FieldRef(e) => VOTableWrappedElemMut::FieldRef(e),
       Self::ParamRef(e) => VOTableWrappedElemMut::ParamRef(e),
+      Self::Vodml(e) => VOTableWrappedElemMut::Vodml(e),
+      Self::Report(e) => VOTableWrappedElemMut::Report(e),
+      Self::Model(e) => VOTableWrappedElemMut::Model(e),
+      Self::Globals(e) => VOTableWrappedElemMut::Globals(e),
+      Self::Templates(e) => VOTableWrappedElemMut::Templates(e),
+      Self::InstanceGI(e) => VOTableWrappedElemMut::InstanceGI(e),
+      Self::InstanceGII(e) => VOTableWrappedElemMut::InstanceGII(e),
+      Self::InstanceTI(e) => VOTableWrappedElemMut::InstanceTI(e),
+      Self::InstanceTII(e) => VOTableWrappedElemMut::InstanceTII(e),
     }
   }
 
@@ -225,6 +287,15 @@ impl<C: TableDataContent> VOTableWrappedElem<C> {
       Self::Max(e) => e.tag(),
       Self::FieldRef(e) => e.tag(),
       Self::ParamRef(e) => e.tag(),
+      Self::Vodml(e) => e.tag(),
+      Self::Report(e) => e.tag(),
+      Self::Model(e) => e.tag(),
+      Self::Globals(e) => e.tag(),
+      Self::Templates(e) => e.tag(),
+      Self::InstanceGI(e) => e.tag(),
+      Self::InstanceGII(e) => e.tag(),
+      Self::InstanceTI(e) => e.tag(),
+      Self::InstanceTII(e) => e.tag(),
     }
   }
 
@@ -373,6 +444,60 @@ impl<C: TableDataContent> VOTableWrappedElem<C> {
       _ => Err(self.wrong_type(ParamRef::TAG)),
     }
   }
+  pub fn vodml(self) -> Result<Vodml, VOTableError> {
+    match self {
+      Self::Vodml(x) => Ok(x),
+      _ => Err(self.wrong_type(Vodml::TAG)),
+    }
+  }
+  pub fn report(self) -> Result<Report, VOTableError> {
+    match self {
+      Self::Report(x) => Ok(x),
+      _ => Err(self.wrong_type(Report::TAG)),
+    }
+  }
+  pub fn model(self) -> Result<Model, VOTableError> {
+    match self {
+      Self::Model(x) => Ok(x),
+      _ => Err(self.wrong_type(Model::TAG)),
+    }
+  }
+  pub fn globals(self) -> Result<Globals, VOTableError> {
+    match self {
+      Self::Globals(x) => Ok(x),
+      _ => Err(self.wrong_type(Globals::TAG)),
+    }
+  }
+  pub fn templates(self) -> Result<Templates, VOTableError> {
+    match self {
+      Self::Templates(x) => Ok(x),
+      _ => Err(self.wrong_type(Templates::TAG)),
+    }
+  }
+  pub fn instance_gi(self) -> Result<InstanceGI, VOTableError> {
+    match self {
+      Self::InstanceGI(x) => Ok(x),
+      _ => Err(self.wrong_type(InstanceGI::TAG)),
+    }
+  }
+  pub fn instance_gii(self) -> Result<InstanceGII, VOTableError> {
+    match self {
+      Self::InstanceGII(x) => Ok(x),
+      _ => Err(self.wrong_type(InstanceGII::TAG)),
+    }
+  }
+  pub fn instance_ti(self) -> Result<InstanceTI, VOTableError> {
+    match self {
+      Self::InstanceTI(x) => Ok(x),
+      _ => Err(self.wrong_type(InstanceTI::TAG)),
+    }
+  }
+  pub fn instance_tii(self) -> Result<InstanceTII, VOTableError> {
+    match self {
+      Self::InstanceTII(x) => Ok(x),
+      _ => Err(self.wrong_type(InstanceTII::TAG)),
+    }
+  }
 
   pub fn set_attributes<K, V, I>(&mut self, attrs: I) -> Result<(), VOTableError>
   where
@@ -494,6 +619,45 @@ impl<C: TableDataContent> VOTableWrappedElem<C> {
     self.as_mut().set_fits_stream(stream)
   }
 
+  pub fn push_vodml(&mut self, vodml: Vodml) -> Result<(), VOTableError> {
+    self.as_mut().push_vodml(vodml)
+  }
+
+  pub fn set_report(&mut self, report: Report) -> Result<(), VOTableError> {
+    self.as_mut().set_report(report)
+  }
+
+  pub fn push_model(&mut self, model: Model) -> Result<(), VOTableError> {
+    self.as_mut().push_model(model)
+  }
+
+  pub fn set_globals(&mut self, globals: Globals) -> Result<(), VOTableError> {
+    self.as_mut().set_globals(globals)
+  }
+
+  pub fn push_templates(&mut self, templates: Templates) -> Result<(), VOTableError> {
+    self.as_mut().push_templates(templates)
+  }
+
+  pub fn push_instance<K, V, I, F>(&mut self, attrs: I, init: F) -> Result<(), VOTableError>
+  where
+    K: AsRef<str> + Into<String>,
+    V: AsRef<str> + Into<String>,
+    I: Iterator<Item = (K, V)>,
+    F: FnOnce(&mut VOTableWrappedElem<C>) -> Result<(), VOTableError>,
+  {
+    self.as_mut().push_instance(attrs, init)
+  }
+
+  pub fn push_attribute<K, V, I>(&mut self, attrs: I) -> Result<(), VOTableError>
+  where
+    K: AsRef<str> + Into<String>,
+    V: AsRef<str> + Into<String>,
+    I: Iterator<Item = (K, V)>,
+  {
+    self.as_mut().push_attribute(attrs)
+  }
+
   pub fn push(&mut self, elem: VOTableWrappedElem<C>) -> Result<(), VOTableError> {
     self.as_mut().push(elem)
   }
@@ -526,13 +690,33 @@ pub enum VOTableWrappedElemMut<'a, C: TableDataContent> {
   FieldRef(&'a mut FieldRef),
   ParamRef(&'a mut ParamRef),
   // MIVOT related tags
-  // Vodml(Vodml),
-  // REPORT
-  // MODEL
-  // GLOBAL
-  // TEMPLATE
-  // ATTRIBUTE / COLLECTION / INSTANCE / REFERENCE
-  // JOIN / WHERE / PRIMARY_KEY / FOREIGN_KEY
+  Vodml(&'a mut Vodml),
+  Report(&'a mut Report),
+  Model(&'a mut Model),
+  Globals(&'a mut Globals),
+  Templates(&'a mut Templates),
+  InstanceGI(&'a mut InstanceGI),
+  InstanceGII(&'a mut InstanceGII),
+  InstanceGCI(&'a mut InstanceGCI),
+  InstanceTI(&'a mut InstanceTI),
+  InstanceTII(&'a mut InstanceTII),
+  CollectionGC(&'a mut CollectionGC),
+  CollectionGIC(&'a mut CollectionGIC),
+  CollectionGICC(&'a mut CollectionGICC),
+  CollectionTIC(&'a mut CollectionTIC),
+  CollectionTICC(&'a mut CollectionTICC),
+  AttributeI(&'a mut AttributeI),
+  AttributeC(&'a mut AttributeC),
+  RefGIR(&'a mut RefGIR),
+  RefGCR(&'a mut RefGCR),
+  RefDynTIR(&'a mut RefDynTIR),
+  RefDynTICR(&'a mut RefDynTICR),
+  Join(&'a mut Join),
+  WhereJ(&'a mut WhereJ),
+  WhereT(&'a mut WhereT),
+  PrimaryKeyS(&'a mut PrimaryKeyS),
+  PrimaryKeyDyn(&'a mut PrimaryKeyDyn),
+  ForeignKey(&'a mut ForeignKey),
 }
 
 impl<'a, C: TableDataContent> From<&'a mut VOTable<C>> for VOTableWrappedElemMut<'a, C> {
@@ -645,6 +829,141 @@ impl<'a, C: TableDataContent> From<&'a mut ParamRef> for VOTableWrappedElemMut<'
     Self::ParamRef(value)
   }
 }
+impl<'a, C: TableDataContent> From<&'a mut Vodml> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut Vodml) -> Self {
+    Self::Vodml(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut Report> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut Report) -> Self {
+    Self::Report(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut Model> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut Model) -> Self {
+    Self::Model(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut Globals> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut Globals) -> Self {
+    Self::Globals(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut Templates> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut Templates) -> Self {
+    Self::Templates(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut InstanceGI> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut InstanceGI) -> Self {
+    Self::InstanceGI(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut InstanceGII> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut InstanceGII) -> Self {
+    Self::InstanceGII(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut InstanceGCI> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut InstanceGCI) -> Self {
+    Self::InstanceGCI(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut InstanceTI> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut InstanceTI) -> Self {
+    Self::InstanceTI(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut InstanceTII> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut InstanceTII) -> Self {
+    Self::InstanceTII(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut CollectionGC> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut CollectionGC) -> Self {
+    Self::CollectionGC(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut CollectionGIC> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut CollectionGIC) -> Self {
+    Self::CollectionGIC(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut CollectionGICC> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut CollectionGICC) -> Self {
+    Self::CollectionGICC(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut CollectionTIC> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut CollectionTIC) -> Self {
+    Self::CollectionTIC(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut CollectionTICC> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut CollectionTICC) -> Self {
+    Self::CollectionTICC(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut AttributeI> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut AttributeI) -> Self {
+    Self::AttributeI(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut AttributeC> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut AttributeC) -> Self {
+    Self::AttributeC(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut RefGIR> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut RefGIR) -> Self {
+    Self::RefGIR(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut RefGCR> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut RefGCR) -> Self {
+    Self::RefGCR(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut RefDynTIR> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut RefDynTIR) -> Self {
+    Self::RefDynTIR(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut RefDynTICR> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut RefDynTICR) -> Self {
+    Self::RefDynTICR(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut Join> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut Join) -> Self {
+    Self::Join(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut WhereJ> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut WhereJ) -> Self {
+    Self::WhereJ(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut WhereT> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut WhereT) -> Self {
+    Self::WhereT(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut PrimaryKeyS> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut PrimaryKeyS) -> Self {
+    Self::PrimaryKeyS(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut PrimaryKeyDyn> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut PrimaryKeyDyn) -> Self {
+    Self::PrimaryKeyDyn(value)
+  }
+}
+impl<'a, C: TableDataContent> From<&'a mut ForeignKey> for VOTableWrappedElemMut<'a, C> {
+  fn from(value: &'a mut ForeignKey) -> Self {
+    Self::ForeignKey(value)
+  }
+}
 
 impl<'a, C: TableDataContent> VOTableWrappedElemMut<'a, C> {
   pub fn tag(self) -> &'static str {
@@ -674,6 +993,33 @@ impl<'a, C: TableDataContent> VOTableWrappedElemMut<'a, C> {
       Self::Max(e) => e.tag(),
       Self::FieldRef(e) => e.tag(),
       Self::ParamRef(e) => e.tag(),
+      Self::Vodml(e) => e.tag(),
+      Self::Report(e) => e.tag(),
+      Self::Model(e) => e.tag(),
+      Self::Globals(e) => e.tag(),
+      Self::Templates(e) => e.tag(),
+      Self::InstanceGI(e) => e.tag(),
+      Self::InstanceGII(e) => e.tag(),
+      Self::InstanceGCI(e) => e.tag(),
+      Self::InstanceTI(e) => e.tag(),
+      Self::InstanceTII(e) => e.tag(),
+      Self::CollectionGC(e) => e.tag(),
+      Self::CollectionGIC(e) => e.tag(),
+      Self::CollectionGICC(e) => e.tag(),
+      Self::CollectionTIC(e) => e.tag(),
+      Self::CollectionTICC(e) => e.tag(),
+      Self::AttributeI(e) => e.tag(),
+      Self::AttributeC(e) => e.tag(),
+      Self::RefGIR(e) => e.tag(),
+      Self::RefGCR(e) => e.tag(),
+      Self::RefDynTIR(e) => e.tag(),
+      Self::RefDynTICR(e) => e.tag(),
+      Self::Join(e) => e.tag(),
+      Self::WhereJ(e) => e.tag(),
+      Self::WhereT(e) => e.tag(),
+      Self::PrimaryKeyS(e) => e.tag(),
+      Self::PrimaryKeyDyn(e) => e.tag(),
+      Self::ForeignKey(e) => e.tag(),
     }
   }
 
@@ -709,6 +1055,33 @@ impl<'a, C: TableDataContent> VOTableWrappedElemMut<'a, C> {
       Self::Max(e) => e.set_attrs_by_ref(attrs),
       Self::FieldRef(e) => e.set_attrs_by_ref(attrs),
       Self::ParamRef(e) => e.set_attrs_by_ref(attrs),
+      Self::Vodml(e) => e.set_attrs_by_ref(attrs),
+      Self::Report(e) => e.set_attrs_by_ref(attrs),
+      Self::Model(e) => e.set_attrs_by_ref(attrs),
+      Self::Globals(e) => e.set_attrs_by_ref(attrs),
+      Self::Templates(e) => e.set_attrs_by_ref(attrs),
+      Self::InstanceGI(e) => e.set_attrs_by_ref(attrs),
+      Self::InstanceGII(e) => e.set_attrs_by_ref(attrs),
+      Self::InstanceGCI(e) => e.set_attrs_by_ref(attrs),
+      Self::InstanceTI(e) => e.set_attrs_by_ref(attrs),
+      Self::InstanceTII(e) => e.set_attrs_by_ref(attrs),
+      Self::CollectionGC(e) => e.set_attrs_by_ref(attrs),
+      Self::CollectionGIC(e) => e.set_attrs_by_ref(attrs),
+      Self::CollectionGICC(e) => e.set_attrs_by_ref(attrs),
+      Self::CollectionTIC(e) => e.set_attrs_by_ref(attrs),
+      Self::CollectionTICC(e) => e.set_attrs_by_ref(attrs),
+      Self::AttributeI(e) => e.set_attrs_by_ref(attrs),
+      Self::AttributeC(e) => e.set_attrs_by_ref(attrs),
+      Self::RefGIR(e) => e.set_attrs_by_ref(attrs),
+      Self::RefGCR(e) => e.set_attrs_by_ref(attrs),
+      Self::RefDynTIR(e) => e.set_attrs_by_ref(attrs),
+      Self::RefDynTICR(e) => e.set_attrs_by_ref(attrs),
+      Self::Join(e) => e.set_attrs_by_ref(attrs),
+      Self::WhereJ(e) => e.set_attrs_by_ref(attrs),
+      Self::WhereT(e) => e.set_attrs_by_ref(attrs),
+      Self::PrimaryKeyS(e) => e.set_attrs_by_ref(attrs),
+      Self::PrimaryKeyDyn(e) => e.set_attrs_by_ref(attrs),
+      Self::ForeignKey(e) => e.set_attrs_by_ref(attrs),
     }
   }
 
@@ -719,6 +1092,7 @@ impl<'a, C: TableDataContent> VOTableWrappedElemMut<'a, C> {
       Self::Link(e) => e.set_content_by_ref(content),
       Self::ParamRef(e) => e.set_content_by_ref(content),
       Self::FieldRef(e) => e.set_content_by_ref(content),
+      Self::Report(e) => e.set_content_by_ref(content),
       _ => {
         return Err(VOTableError::Custom(format!(
           "Tag '{}' cannot have a content.",
@@ -1123,6 +1497,103 @@ impl<'a, C: TableDataContent> VOTableWrappedElemMut<'a, C> {
     Ok(())
   }
 
+  pub fn push_vodml(self, vodml: Vodml) -> Result<(), VOTableError> {
+    match self {
+      Self::Resource(e) if e.vodml.is_none() => e.set_vodml_by_ref(vodml),
+      Self::Resource(_) => {
+        return Err(VOTableError::Custom(format!(
+          "Tag '{}' already contains a {}.",
+          self.tag(),
+          vodml.tag()
+        )))
+      }
+      _ => return Err(self.cannot_contain(vodml.tag())),
+    }
+    Ok(())
+  }
+
+  pub fn set_report(self, report: Report) -> Result<(), VOTableError> {
+    match self {
+      Self::Vodml(e) => e.reset_report_by_ref(report),
+      _ => return Err(self.cannot_contain(report.tag())),
+    }
+    Ok(())
+  }
+
+  pub fn push_model(self, model: Model) -> Result<(), VOTableError> {
+    match self {
+      Self::Vodml(e) => e.push_model_by_ref(model),
+      _ => return Err(self.cannot_contain(model.tag())),
+    }
+    Ok(())
+  }
+
+  pub fn set_globals(self, globals: Globals) -> Result<(), VOTableError> {
+    match self {
+      Self::Vodml(e) => e.reset_globals_by_ref(globals),
+      _ => return Err(self.cannot_contain(globals.tag())),
+    }
+    Ok(())
+  }
+
+  pub fn push_templates(self, templates: Templates) -> Result<(), VOTableError> {
+    match self {
+      Self::Vodml(e) => e.push_templates_by_ref(templates),
+      _ => return Err(self.cannot_contain(templates.tag())),
+    }
+    Ok(())
+  }
+
+  /// Build a new `INSTANCE` from the given attributes and push it in this element.
+  /// The actual type of `INSTANCE` (child of `GLOBALS`, of `TEMPLATES` or of another `INSTANCE`)
+  /// depends on this (parent) element.
+  /// The `init` closure is called on the new `INSTANCE` before pushing it, e.g. to add sub-elements.
+  pub fn push_instance<K, V, I, F>(self, attrs: I, init: F) -> Result<(), VOTableError>
+  where
+    K: AsRef<str> + Into<String>,
+    V: AsRef<str> + Into<String>,
+    I: Iterator<Item = (K, V)>,
+    F: FnOnce(&mut VOTableWrappedElem<C>) -> Result<(), VOTableError>,
+  {
+    let mut instance: VOTableWrappedElem<C> = match self {
+      Self::Globals(_) => InstanceGI::from_attrs(attrs)?.into(),
+      Self::InstanceGI(_) | Self::InstanceGII(_) | Self::InstanceGCI(_) => {
+        InstanceGII::from_attrs(attrs)?.into()
+      }
+      Self::Templates(_) => InstanceTI::from_attrs(attrs)?.into(),
+      Self::InstanceTI(_) | Self::InstanceTII(_) => InstanceTII::from_attrs(attrs)?.into(),
+      _ => return Err(self.cannot_contain(InstanceGI::TAG)),
+    };
+    init(&mut instance)?;
+    self.push(instance)
+  }
+
+  /// Build a new `ATTRIBUTE` from the given attributes and push it in this `INSTANCE`.
+  pub fn push_attribute<K, V, I>(self, attrs: I) -> Result<(), VOTableError>
+  where
+    K: AsRef<str> + Into<String>,
+    V: AsRef<str> + Into<String>,
+    I: Iterator<Item = (K, V)>,
+  {
+    match self {
+      Self::InstanceGI(e) => e.push_attribute_by_ref(AttributeI::from_attrs(attrs)?),
+      Self::InstanceGII(e) => e.push_attribute_by_ref(AttributeI::from_attrs(attrs)?),
+      Self::InstanceGCI(e) => e.push_attribute_by_ref(AttributeI::from_attrs(attrs)?),
+      Self::InstanceTI(e) => e.push_attribute_by_ref(AttributeI::from_attrs(attrs)?),
+      Self::InstanceTII(e) => e.push_attribute_by_ref(AttributeI::from_attrs(attrs)?),
+      _ => return Err(self.cannot_contain(AttributeI::TAG)),
+    }
+    Ok(())
+  }
+
+  fn cannot_contain(self, sub_tag: &str) -> VOTableError {
+    VOTableError::Custom(format!(
+      "Tag '{}' cannot contain a {}.",
+      self.tag(),
+      sub_tag
+    ))
+  }
+
   pub fn push(self, elem: VOTableWrappedElem<C>) -> Result<(), VOTableError> {
     match elem {
       VOTableWrappedElem::<C>::VOTable(_) => Err(VOTableError::Custom(
@@ -1152,6 +1623,42 @@ impl<'a, C: TableDataContent> VOTableWrappedElemMut<'a, C> {
       VOTableWrappedElem::<C>::Max(e) => self.set_max(e),
       VOTableWrappedElem::<C>::FieldRef(e) => self.push_fieldref(e),
       VOTableWrappedElem::<C>::ParamRef(e) => self.push_paramref(e),
+      VOTableWrappedElem::<C>::Vodml(e) => self.push_vodml(e),
+      VOTableWrappedElem::<C>::Report(e) => self.set_report(e),
+      VOTableWrappedElem::<C>::Model(e) => self.push_model(e),
+      VOTableWrappedElem::<C>::Globals(e) => self.set_globals(e),
+      VOTableWrappedElem::<C>::Templates(e) => self.push_templates(e),
+      VOTableWrappedElem::<C>::InstanceGI(e) => match self {
+        Self::Globals(p) => {
+          p.push_instance_by_ref(e);
+          Ok(())
+        }
+        _ => Err(self.cannot_contain(e.tag())),
+      },
+      VOTableWrappedElem::<C>::InstanceGII(e) => {
+        match self {
+          Self::InstanceGI(p) => p.push_instance_by_ref(e),
+          Self::InstanceGII(p) => p.push_instance_by_ref(e),
+          Self::InstanceGCI(p) => p.push_instance_by_ref(e),
+          _ => return Err(self.cannot_contain(e.tag())),
+        }
+        Ok(())
+      }
+      VOTableWrappedElem::<C>::InstanceTI(e) => match self {
+        Self::Templates(p) => {
+          p.push_instance_by_ref(e);
+          Ok(())
+        }
+        _ => Err(self.cannot_contain(e.tag())),
+      },
+      VOTableWrappedElem::<C>::InstanceTII(e) => {
+        match self {
+          Self::InstanceTI(p) => p.push_instance_by_ref(e),
+          Self::InstanceTII(p) => p.push_instance_by_ref(e),
+          _ => return Err(self.cannot_contain(e.tag())),
+        }
+        Ok(())
+      }
     }
   }
 }
@@ -0,0 +1,235 @@
+//! Provides `#[derive(VOTableRow)]`, generating the FIELDs of a VOTable table and the rows
+//! values from a Rust `struct` having named fields.
+//!
+//! Each field type must implement `votable::impls::typed::VOTableColumn`.
+//! The FIELD metadata can be set with the `votable` attribute:
+//! ```ignore
+//! #[derive(VOTableRow)]
+//! struct Source {
+//!   #[votable(name = "RA_ICRS", unit = "deg", ucd = "pos.eq.ra;meta.main")]
+//!   ra: f64,
+//!   #[votable(datatype = "unicodeChar", arraysize = "16*")]
+//!   name: String,
+//!   #[votable(skip)]
+//!   internal_id: u64,
+//! }
+//! ```
+//! Supported keys are: `name`, `datatype`, `arraysize`, `unit`, `ucd`, `utype`, `xtype`,
+//! `description` and `skip`.
+//!
+//! A `datatype` not compatible with the values of the field type (see
+//! `votable::impls::typed::row::HasDatatype`) is rejected at compile time:
+//! ```compile_fail
+//! use votable::VOTableRow;
+//!
+//! #[derive(VOTableRow)]
+//! struct Source {
+//!   #[votable(datatype = "int")]
+//!   ra: f64,
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, LitStr};
+
+#[proc_macro_derive(VOTableRow, attributes(votable))]
+pub fn derive_votable_row(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  expand(input)
+    .unwrap_or_else(Error::into_compile_error)
+    .into()
+}
+
+/// Content of the `#[votable(...)]` attribute of a struct field.
+#[derive(Default)]
+struct FieldAttrs {
+  name: Option<LitStr>,
+  datatype: Option<LitStr>,
+  arraysize: Option<LitStr>,
+  unit: Option<LitStr>,
+  ucd: Option<LitStr>,
+  utype: Option<LitStr>,
+  xtype: Option<LitStr>,
+  description: Option<LitStr>,
+  skip: bool,
+}
+
+impl FieldAttrs {
+  fn from_field(field: &syn::Field) -> syn::Result<Self> {
+    let mut attrs = Self::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("votable")) {
+      attr.parse_nested_meta(|meta| {
+        let target = if meta.path.is_ident("skip") {
+          attrs.skip = true;
+          return Ok(());
+        } else if meta.path.is_ident("name") {
+          &mut attrs.name
+        } else if meta.path.is_ident("datatype") {
+          &mut attrs.datatype
+        } else if meta.path.is_ident("arraysize") {
+          &mut attrs.arraysize
+        } else if meta.path.is_ident("unit") {
+          &mut attrs.unit
+        } else if meta.path.is_ident("ucd") {
+          &mut attrs.ucd
+        } else if meta.path.is_ident("utype") {
+          &mut attrs.utype
+        } else if meta.path.is_ident("xtype") {
+          &mut attrs.xtype
+        } else if meta.path.is_ident("description") {
+          &mut attrs.description
+        } else {
+          return Err(meta.error(
+            "unknown votable attribute, expected one of: name, datatype, arraysize, unit, ucd, \
+             utype, xtype, description, skip",
+          ));
+        };
+        *target = Some(meta.value()?.parse()?);
+        Ok(())
+      })?;
+    }
+    Ok(attrs)
+  }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+  let fields = match &input.data {
+    Data::Struct(data) => match &data.fields {
+      Fields::Named(fields) => &fields.named,
+      _ => {
+        return Err(Error::new(
+          input.span(),
+          "VOTableRow can only be derived for structs with named fields",
+        ));
+      }
+    },
+    _ => {
+      return Err(Error::new(
+        input.span(),
+        "VOTableRow can only be derived for structs",
+      ));
+    }
+  };
+
+  let mut field_exprs = Vec::with_capacity(fields.len());
+  let mut value_exprs = Vec::with_capacity(fields.len());
+  for field in fields {
+    let attrs = FieldAttrs::from_field(field)?;
+    if attrs.skip {
+      continue;
+    }
+    let ident = field.ident.as_ref().unwrap();
+    let ty = &field.ty;
+    let name = attrs
+      .name
+      .map(|lit| lit.value())
+      .unwrap_or_else(|| ident.to_string());
+    let arraysize = match &attrs.arraysize {
+      Some(lit) => {
+        check_arraysize(lit)?;
+        quote!(::std::option::Option::Some(
+          #lit.parse::<::votable::field::ArraySize>().unwrap()
+        ))
+      }
+      None => quote!(::std::option::Option::None),
+    };
+    let setters = [
+      (quote!(set_unit), &attrs.unit),
+      (quote!(set_ucd), &attrs.ucd),
+      (quote!(set_utype), &attrs.utype),
+      (quote!(set_xtype), &attrs.xtype),
+    ]
+    .into_iter()
+    .filter_map(|(setter, value)| value.as_ref().map(|value| quote!(.#setter(#value))));
+    let description = attrs
+      .description
+      .as_ref()
+      .map(|desc| quote!(.set_description(::votable::desc::Description::new(#desc))));
+    // The compatibility of the datatype with the field type is checked by the compiler
+    // (trait bound of `new_field_with_datatype`), the error pointing to the given datatype
+    let new_field = match &attrs.datatype {
+      Some(lit) => {
+        let variant = datatype_variant(lit)?;
+        quote_spanned! {lit.span()=>
+          ::votable::impls::typed::row::new_field_with_datatype::<
+            #ty,
+            ::votable::impls::typed::row::datatype::#variant,
+            _,
+          >(#name, #arraysize)
+        }
+      }
+      None => quote!(::votable::impls::typed::row::new_field::<#ty, _>(#name, #arraysize)),
+    };
+    field_exprs.push(quote! {
+      #new_field
+        #(#setters)*
+        #description
+    });
+    value_exprs.push(quote! {
+      ::votable::impls::typed::VOTableColumn::to_value(&self.#ident)
+    });
+  }
+
+  let ident = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+  Ok(quote! {
+    impl #impl_generics ::votable::impls::typed::VOTableRow for #ident #ty_generics #where_clause {
+      fn fields() -> ::std::vec::Vec<::votable::field::Field> {
+        ::std::vec![#(#field_exprs),*]
+      }
+
+      fn to_row(&self) -> ::std::vec::Vec<::votable::impls::VOTableValue> {
+        ::std::vec![#(#value_exprs),*]
+      }
+    }
+  })
+}
+
+/// Returns the `Datatype` variant corresponding to the given VOTable datatype.
+fn datatype_variant(lit: &LitStr) -> syn::Result<TokenStream2> {
+  Ok(match lit.value().as_str() {
+    "boolean" => quote!(Logical),
+    "bit" => quote!(Bit),
+    "unsignedByte" => quote!(Byte),
+    "short" => quote!(ShortInt),
+    "int" => quote!(Int),
+    "long" => quote!(LongInt),
+    "char" => quote!(CharASCII),
+    "unicodeChar" => quote!(CharUnicode),
+    "float" => quote!(Float),
+    "double" => quote!(Double),
+    "floatComplex" => quote!(ComplexFloat),
+    "doubleComplex" => quote!(ComplexDouble),
+    s => {
+      return Err(Error::new(
+        lit.span(),
+        format!("unknown VOTable datatype '{}'", s),
+      ));
+    }
+  })
+}
+
+/// Checks the arraysize syntax, e.g. `8`, `8*`, `*`, `3x2`, `3x*`.
+fn check_arraysize(lit: &LitStr) -> syn::Result<()> {
+  let value = lit.value();
+  let mut dims = value.split('x').peekable();
+  while let Some(dim) = dims.next() {
+    let is_valid = match dims.peek() {
+      // Only the last dimension may be variable
+      None => dim.strip_suffix('*').map_or_else(
+        || dim.parse::<u32>().is_ok(),
+        |dim| dim.is_empty() || dim.parse::<u32>().is_ok(),
+      ),
+      Some(_) => dim.parse::<u32>().is_ok(),
+    };
+    if !is_valid {
+      return Err(Error::new(
+        lit.span(),
+        format!("wrong arraysize '{}'", value),
+      ));
+    }
+  }
+  Ok(())
+}
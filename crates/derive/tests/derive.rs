@@ -0,0 +1,156 @@
+use votable::{
+  datatype::Datatype,
+  field::ArraySize,
+  impls::{b64::write::BinarySerializer, mem::InMemTableDataRows, VOTableValue},
+  iter::SimpleVOTableRowIterator,
+  resource::Resource,
+  votable::{VOTable, Version},
+  VOTableRow,
+};
+
+#[derive(VOTableRow)]
+struct Source {
+  #[votable(name = "RA", unit = "deg", ucd = "pos.eq.ra;meta.main")]
+  ra: f64,
+  #[votable(name = "Dec", unit = "deg", ucd = "pos.eq.dec;meta.main")]
+  dec: f64,
+  #[votable(arraysize = "8*", description = "Source name")]
+  name: String,
+  #[votable(unit = "mag")]
+  mag: Option<f32>,
+  n_obs: Option<i32>,
+  pm: [f32; 2],
+  #[votable(skip)]
+  #[allow(dead_code)]
+  internal: u64,
+}
+
+fn sources() -> Vec<Source> {
+  vec![
+    Source {
+      ra: 10.68,
+      dec: 41.27,
+      name: String::from("M31"),
+      mag: Some(3.44),
+      n_obs: None,
+      pm: [0.5, -0.25],
+      internal: 0,
+    },
+    Source {
+      ra: 83.82,
+      dec: -5.39,
+      name: String::from("M42"),
+      mag: None,
+      n_obs: Some(12),
+      pm: [1.0, 2.0],
+      internal: 1,
+    },
+  ]
+}
+
+#[test]
+fn test_derive_fields() {
+  let fields = Source::fields();
+  let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+  assert_eq!(names, vec!["RA", "Dec", "name", "mag", "n_obs", "pm"]);
+  assert_eq!(fields[0].datatype, Datatype::Double);
+  assert_eq!(fields[0].unit.as_deref(), Some("deg"));
+  assert_eq!(fields[0].ucd.as_deref(), Some("pos.eq.ra;meta.main"));
+  assert_eq!(fields[2].datatype, Datatype::CharASCII);
+  assert_eq!(
+    fields[2].arraysize,
+    Some(ArraySize::new_variable_1d_with_upper_limit(8))
+  );
+  assert!(fields[2].description.is_some());
+  assert_eq!(fields[3].datatype, Datatype::Float);
+  assert!(fields[3].values.is_none());
+  assert_eq!(fields[4].datatype, Datatype::Int);
+  assert_eq!(
+    fields[4].values.as_ref().and_then(|v| v.null.as_deref()),
+    Some("-2147483648")
+  );
+  assert_eq!(fields[5].arraysize, Some(ArraySize::new_fixed_1d(2)));
+}
+
+#[test]
+fn test_derive_rows() {
+  let row = sources()[0].to_row();
+  assert_eq!(
+    row,
+    vec![
+      VOTableValue::Double(10.68),
+      VOTableValue::Double(41.27),
+      VOTableValue::String(String::from("M31")),
+      VOTableValue::Float(3.44),
+      VOTableValue::Null,
+      VOTableValue::FloatArray(vec![0.5, -0.25]),
+    ]
+  );
+  // Row writer usable in streaming mode
+  let schema = Source::table_schema();
+  let mut buff = Vec::new();
+  let mut serializer = BinarySerializer::new(&mut buff);
+  for source in sources() {
+    InMemTableDataRows::write_binary2_row(&mut serializer, source.to_row(), &schema).unwrap();
+  }
+  assert!(!buff.is_empty());
+}
+
+#[test]
+fn test_derive_table() {
+  let mut table = Source::to_table(&sources());
+  table.to_binary2().unwrap();
+  let xml = VOTable::new(Version::V1_4, Resource::default().push_table(table))
+    .wrap()
+    .to_ivoa_xml_string()
+    .unwrap();
+  let mut it = SimpleVOTableRowIterator::from_reader(xml.as_bytes()).unwrap();
+  let rows = it
+    .to_row_value_iter()
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+  let expected: Vec<Vec<VOTableValue>> = sources().iter().map(|s| s.to_row()).collect();
+  assert_eq!(rows, expected);
+}
+
+#[derive(VOTableRow)]
+struct Names {
+  #[votable(datatype = "unicodeChar", arraysize = "16*")]
+  name: String,
+  #[votable(datatype = "unicodeChar")]
+  alias: Option<String>,
+  #[votable(datatype = "char")]
+  flag: char,
+  #[votable(datatype = "double")]
+  pos: [f64; 2],
+}
+
+#[test]
+fn test_derive_datatype_override() {
+  let fields = Names::fields();
+  assert_eq!(fields[0].datatype, Datatype::CharUnicode);
+  assert_eq!(
+    fields[0].arraysize,
+    Some(ArraySize::new_variable_1d_with_upper_limit(16))
+  );
+  assert_eq!(fields[1].datatype, Datatype::CharUnicode);
+  assert_eq!(fields[2].datatype, Datatype::CharASCII);
+  assert_eq!(fields[3].datatype, Datatype::Double);
+  // The header is consistent with the data
+  let rows = vec![Names {
+    name: String::from("Barnard's star"),
+    alias: None,
+    flag: 'A',
+    pos: [269.45, 4.69],
+  }];
+  let mut table = Names::to_table(&rows);
+  table.to_binary2().unwrap();
+  let xml = VOTable::new(Version::V1_4, Resource::default().push_table(table))
+    .wrap()
+    .to_ivoa_xml_string()
+    .unwrap();
+  let mut it = SimpleVOTableRowIterator::from_reader(xml.as_bytes()).unwrap();
+  let row = it.to_row_value_iter().next().unwrap().unwrap();
+  assert_eq!(row[0], VOTableValue::String(String::from("Barnard's star")));
+  assert_eq!(row[3], VOTableValue::DoubleArray(vec![269.45, 4.69]));
+}
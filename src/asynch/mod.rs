@@ -0,0 +1,376 @@
+//! Asynchronous (`tokio`) streaming reader and writer (feature `async`).
+//!
+//! The version of `quick-xml` we use has no async support, so the XML is parsed (or written)
+//! from (or into) in-memory buffers with the regular code, while the I/O operations on the
+//! underlying `AsyncRead` (or `AsyncWrite`) are asynchronous. Thus, no call blocks the
+//! runtime.
+//!
+//! Example:
+//! ```ignore
+//! use futures_util::StreamExt;
+//! use votable::asynch::AsyncSimpleVOTableRowIterator;
+//!
+//! let file = tokio::fs::File::open("resources/sdss12.vot").await?;
+//! let mut it = AsyncSimpleVOTableRowIterator::from_reader(file).await?;
+//! let mut rows = Box::pin(it.row_stream());
+//! while let Some(row) = rows.next().await {
+//!   println!("{:?}", row?);
+//! }
+//! drop(rows);
+//! let votable = it.read_to_end().await?;
+//! ```
+
+pub mod read;
+pub mod write;
+
+pub use self::{read::AsyncSimpleVOTableRowIterator, write::AsyncVOTableWriter};
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    pin::Pin,
+    task::{Context, Poll},
+  };
+
+  use futures_util::StreamExt;
+  use tokio::io::{duplex, AsyncRead, AsyncWrite, ReadBuf};
+
+  use super::{AsyncSimpleVOTableRowIterator, AsyncVOTableWriter};
+  use crate::{
+    data::DataElem,
+    error::VOTableError,
+    impls::{mem::VoidTableDataContent, TableSchema, VOTableValue},
+    iter::SimpleVOTableRowIterator,
+    votable::VOTable,
+  };
+
+  fn read_sdss12() -> (VOTable<VoidTableDataContent>, Vec<Vec<VOTableValue>>) {
+    let mut it = SimpleVOTableRowIterator::from_file("resources/sdss12.vot").unwrap();
+    let rows = it
+      .to_row_value_iter()
+      .collect::<Result<Vec<_>, VOTableError>>()
+      .unwrap();
+    (it.read_to_end().unwrap(), rows)
+  }
+
+  async fn write_rows<W: AsyncWrite + Unpin>(
+    writer: W,
+    mut votable: VOTable<VoidTableDataContent>,
+    rows: &[Vec<VOTableValue>],
+  ) -> W {
+    let schema = TableSchema::from(votable.get_first_table().unwrap().elems.as_slice());
+    let mut writer = AsyncVOTableWriter::new(writer);
+    assert!(writer
+      .write_to_data_beginning(&mut votable, false)
+      .await
+      .unwrap());
+    match votable
+      .get_first_table()
+      .unwrap()
+      .data
+      .as_ref()
+      .map(|d| &d.data)
+    {
+      Some(DataElem::Binary(_)) => {
+        for row in rows {
+          writer.write_binary_row(row, &schema).await.unwrap();
+        }
+      }
+      Some(DataElem::Binary2(_)) => {
+        for row in rows {
+          writer.write_binary2_row(row, &schema).await.unwrap();
+        }
+      }
+      _ => writer
+        .write_tabledata_rows(rows.iter(), &schema)
+        .await
+        .unwrap(),
+    }
+    writer
+      .write_from_data_end(&mut votable, false)
+      .await
+      .unwrap();
+    writer.into_inner()
+  }
+
+  async fn read_rows<R: AsyncRead + Unpin>(
+    reader: R,
+  ) -> (Vec<Vec<VOTableValue>>, VOTable<VoidTableDataContent>) {
+    let mut it = AsyncSimpleVOTableRowIterator::from_reader(reader)
+      .await
+      .unwrap();
+    let rows = it
+      .row_stream()
+      .collect::<Vec<_>>()
+      .await
+      .into_iter()
+      .collect::<Result<Vec<_>, VOTableError>>()
+      .unwrap();
+    (rows, it.read_to_end().await.unwrap())
+  }
+
+  fn read_sync(bytes: &[u8]) -> (Vec<Vec<VOTableValue>>, VOTable<VoidTableDataContent>) {
+    let mut it = SimpleVOTableRowIterator::from_reader(bytes).unwrap();
+    let rows = it
+      .to_row_value_iter()
+      .collect::<Result<Vec<_>, VOTableError>>()
+      .unwrap();
+    (rows, it.read_to_end().unwrap())
+  }
+
+  #[tokio::test]
+  async fn test_async_tabledata() {
+    let (votable, rows) = read_sdss12();
+    assert_eq!(rows.len(), 50);
+    let (actual_rows, actual_votable) =
+      read_rows(tokio::fs::File::open("resources/sdss12.vot").await.unwrap()).await;
+    assert_eq!(actual_rows, rows);
+    assert_eq!(actual_votable, votable);
+  }
+
+  #[tokio::test]
+  async fn test_async_write_then_read() {
+    let (votable, rows) = read_sdss12();
+    for to_binary2 in [false, true] {
+      let mut votable = votable.clone();
+      if to_binary2 {
+        votable.to_binary2().unwrap();
+      } else {
+        votable.to_binary().unwrap();
+      }
+      // Output of the async writer read identically by the sync and the async readers
+      let bytes = write_rows(Vec::new(), votable, &rows).await;
+      let (expected_rows, expected_votable) = read_sync(&bytes);
+      let (actual_rows, actual_votable) = read_rows(bytes.as_slice()).await;
+      assert_eq!(actual_rows, expected_rows);
+      assert_eq!(actual_votable, expected_votable);
+      // BINARY2 supports NULL strings, so we get back the original rows
+      assert_eq!(actual_rows.len(), rows.len());
+      if to_binary2 {
+        assert_eq!(actual_rows, rows);
+      }
+    }
+  }
+
+  #[tokio::test]
+  async fn test_async_duplex() {
+    let (votable, rows) = read_sdss12();
+    for to_binary2 in [false, true] {
+      let mut votable = votable.clone();
+      if to_binary2 {
+        votable.to_binary2().unwrap();
+      }
+      let (_, expected_votable) = read_sync(&write_rows(Vec::new(), votable.clone(), &rows).await);
+      // Small buffer size to test rows split in several chunks
+      let (client, server) = duplex(64);
+      let write = async {
+        // The writer is dropped at the end, closing the stream
+        write_rows(client, votable, &rows).await;
+      };
+      let ((), (actual_rows, actual_votable)) = tokio::join!(write, read_rows(server));
+      assert_eq!(actual_rows, rows);
+      assert_eq!(actual_votable, expected_votable);
+    }
+  }
+
+  #[tokio::test]
+  async fn test_async_premature_eof() {
+    let vot = std::fs::read("resources/sdss12.vot").unwrap();
+    // Cut in the middle of the rows
+    let data_start = memchr::memmem::find(&vot, b"<TABLEDATA>").unwrap();
+    let truncated = &vot[..(data_start + vot.len()) / 2];
+    let mut it = AsyncSimpleVOTableRowIterator::from_reader(truncated)
+      .await
+      .unwrap();
+    let mut n_ok = 0;
+    let err = loop {
+      match it.next_row().await {
+        Some(Ok(_)) => n_ok += 1,
+        Some(Err(e)) => break e,
+        None => panic!("Error expected"),
+      }
+    };
+    assert!(n_ok > 0);
+    assert!(matches!(err, VOTableError::PrematureEOF(_)));
+  }
+
+  /// Reader returning at most `chunk_len` bytes at each call, to test the various ways
+  /// a document can be cut.
+  struct ChunkedReader<'a> {
+    bytes: &'a [u8],
+    chunk_len: usize,
+  }
+
+  impl AsyncRead for ChunkedReader<'_> {
+    fn poll_read(
+      mut self: Pin<&mut Self>,
+      _cx: &mut Context<'_>,
+      buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+      let n = self.chunk_len.min(self.bytes.len()).min(buf.remaining());
+      let (head, tail) = self.bytes.split_at(n);
+      buf.put_slice(head);
+      self.bytes = tail;
+      Poll::Ready(Ok(()))
+    }
+  }
+
+  /// Small VOTable document with a single `int` column, followed by a footer.
+  fn small_votable(data: &str) -> String {
+    format!(
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <TABLE name="t1">
+      <FIELD name="id" datatype="int"/>
+      <DATA>
+        {}
+      </DATA>
+    </TABLE>
+    <INFO name="QUERY_STATUS" value="OK"/>
+  </RESOURCE>
+</VOTABLE>
+"#,
+      data
+    )
+  }
+
+  fn small_votables() -> Vec<String> {
+    vec![
+      small_votable("<TABLEDATA><TR><TD>1</TD></TR>\n<TR><TD>2</TD></TR></TABLEDATA>"),
+      small_votable("<BINARY><STREAM encoding=\"base64\">AAAAAQAA\nAAI=</STREAM></BINARY>"),
+      small_votable("<BINARY2><STREAM encoding=\"base64\">AAAAAAEAAAAAAg==</STREAM></BINARY2>"),
+    ]
+  }
+
+  async fn try_read_rows<R: AsyncRead + Unpin>(
+    reader: R,
+  ) -> Result<Vec<Vec<VOTableValue>>, VOTableError> {
+    let mut it = AsyncSimpleVOTableRowIterator::from_reader(reader).await?;
+    let mut rows = Vec::new();
+    while let Some(row) = it.next_row().await {
+      rows.push(row?);
+    }
+    Ok(rows)
+  }
+
+  #[tokio::test]
+  async fn test_async_whole_doc_in_one_chunk() {
+    for vot in small_votables() {
+      let (actual_rows, actual_votable) = read_rows(vot.as_bytes()).await;
+      assert_eq!(
+        actual_rows,
+        vec![vec![VOTableValue::Int(1)], vec![VOTableValue::Int(2)]]
+      );
+      assert_eq!(actual_votable, read_sync(vot.as_bytes()).1);
+    }
+  }
+
+  #[tokio::test]
+  async fn test_async_chunk_boundaries() {
+    // Cut the documents at every possible position
+    for vot in small_votables() {
+      let (expected_rows, expected_votable) = read_sync(vot.as_bytes());
+      for chunk_len in 1..=vot.len() {
+        let reader = ChunkedReader {
+          bytes: vot.as_bytes(),
+          chunk_len,
+        };
+        let (actual_rows, actual_votable) = read_rows(reader).await;
+        assert_eq!(actual_rows, expected_rows, "chunk length: {}", chunk_len);
+        assert_eq!(actual_votable, expected_votable);
+      }
+    }
+    // Rows larger than the chunks
+    let (votable, rows) = read_sdss12();
+    let mut votable2 = votable.clone();
+    votable2.to_binary2().unwrap();
+    for bytes in [
+      std::fs::read("resources/sdss12.vot").unwrap(),
+      write_rows(Vec::new(), votable2, &rows).await,
+    ] {
+      for chunk_len in [1, 3, 77] {
+        let reader = ChunkedReader {
+          bytes: &bytes,
+          chunk_len,
+        };
+        let (actual_rows, _) = read_rows(reader).await;
+        assert_eq!(actual_rows, rows);
+      }
+    }
+  }
+
+  #[tokio::test]
+  async fn test_async_two_tables_in_one_chunk() {
+    let vot = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <TABLE name="t1">
+      <FIELD name="id" datatype="int"/>
+      <DATA><TABLEDATA><TR><TD>1</TD></TR><TR><TD>2</TD></TR></TABLEDATA></DATA>
+    </TABLE>
+    <TABLE name="t2">
+      <FIELD name="id" datatype="int"/>
+      <DATA><TABLEDATA><TR><TD>3</TD></TR><TR><TD>4</TD></TR><TR><TD>5</TD></TR></TABLEDATA></DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>
+"#;
+    for chunk_len in [vot.len(), 1, 13] {
+      let reader = ChunkedReader {
+        bytes: vot.as_bytes(),
+        chunk_len,
+      };
+      let rows = try_read_rows(reader).await.unwrap();
+      assert_eq!(
+        rows,
+        vec![vec![VOTableValue::Int(1)], vec![VOTableValue::Int(2)]]
+      );
+    }
+  }
+
+  #[tokio::test]
+  async fn test_async_errors() {
+    // Truncated inside the base64 stream
+    let vot = small_votables().remove(2);
+    let end = memchr::memmem::find(vot.as_bytes(), b"AAAAAAEA").unwrap() + 8;
+    let err = try_read_rows(&vot.as_bytes()[..end]).await.unwrap_err();
+    assert!(matches!(err, VOTableError::PrematureEOF(_)));
+    // Truncated inside the rows
+    let vot = small_votables().remove(0);
+    let end = memchr::memmem::find(vot.as_bytes(), b"<TD>2").unwrap();
+    let err = try_read_rows(&vot.as_bytes()[..end]).await.unwrap_err();
+    assert!(matches!(err, VOTableError::PrematureEOF(_)));
+    // Invalid base64 char
+    let vot = small_votable("<BINARY><STREAM encoding=\"base64\">AAA*AQAAAAI=</STREAM></BINARY>");
+    let err = try_read_rows(vot.as_bytes()).await.unwrap_err();
+    assert!(err.to_string().contains("base64"), "{}", err);
+    // Incomplete last row: 6 bytes for 4-bytes rows
+    let vot = small_votable("<BINARY><STREAM encoding=\"base64\">AAAAAQAA</STREAM></BINARY>");
+    let mut it = AsyncSimpleVOTableRowIterator::from_reader(vot.as_bytes())
+      .await
+      .unwrap();
+    assert_eq!(
+      it.next_row().await.unwrap().unwrap(),
+      vec![VOTableValue::Int(1)]
+    );
+    assert!(it.next_row().await.unwrap().is_err());
+    // Invalid second row (lone UCS-2 surrogate) in a truncated stream: the decoding error is
+    // returned, not an EOF error
+    let vot = small_votable("<BINARY><STREAM encoding=\"base64\">AEHYAABCAEM=</STREAM></BINARY>")
+      .replace(
+        "datatype=\"int\"",
+        "datatype=\"unicodeChar\" arraysize=\"1\"",
+      );
+    let end = memchr::memmem::find(vot.as_bytes(), b"AEHYAABC").unwrap() + 8;
+    let mut it = AsyncSimpleVOTableRowIterator::from_reader(&vot.as_bytes()[..end])
+      .await
+      .unwrap();
+    assert_eq!(
+      it.next_row().await.unwrap().unwrap(),
+      vec![VOTableValue::String(String::from("A"))]
+    );
+    let err = it.next_row().await.unwrap().unwrap_err();
+    assert!(!matches!(err, VOTableError::PrematureEOF(_)), "{:?}", err);
+  }
+}
@@ -0,0 +1,320 @@
+//! Asynchronous equivalent of `SimpleVOTableRowIterator`.
+
+use std::{collections::VecDeque, io::ErrorKind};
+
+use base64::{engine::general_purpose, Engine};
+use futures_util::stream::{self, Stream};
+use memchr::memmem;
+use quick_xml::Reader;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{
+  data::TableOrBinOrBin2,
+  error::VOTableError,
+  impls::{
+    b64::read::BinaryDeserializer, mem::VoidTableDataContent, visitors::FixedLengthArrayVisitor,
+    Schema, TableSchema, VOTableValue,
+  },
+  iter::{elems::DataTableRowValueIterator, SimpleVOTableRowIterator},
+  table::Table,
+  votable::VOTable,
+};
+
+use serde::{de::DeserializeSeed, Deserializer};
+
+/// Number of bytes we try to read at once from the async reader.
+const CHUNK_SIZE: usize = 8192;
+
+/// Asynchronously read the VOTable till the first table data, then provides the table rows.
+///
+/// Internally, the input bytes are stored in a buffer till a self-consistent part (the header,
+/// a set of `TR` rows, a set of base64 characters, or the footer) is found.
+/// This part is then parsed from memory using the regular (synchronous) code.
+///
+/// Limitation: the `</TR>` and `</TABLEDATA>` sequences are assumed not to appear in
+/// `CDATA` sections or comments inside `TABLEDATA`.
+pub struct AsyncSimpleVOTableRowIterator<R: AsyncRead + Unpin> {
+  reader: R,
+  /// Bytes read but not yet parsed
+  buff: Vec<u8>,
+  /// The underlying reader has been read entirely
+  is_eof: bool,
+  votable: VOTable<VoidTableDataContent>,
+  data_type: TableOrBinOrBin2,
+  schema: Vec<Schema>,
+  /// Rows already parsed (TABLEDATA only)
+  rows: VecDeque<Vec<VOTableValue>>,
+  /// Decoded binary bytes not yet parsed (BINARY and BINARY2 only)
+  bin_buff: Vec<u8>,
+  /// End of data reached (`</TABLEDATA>` or `</STREAM>` found, but not consumed)
+  is_data_end: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncSimpleVOTableRowIterator<R> {
+  /// Read the VOTable till (inclusive):
+  /// * `TABLEDATA` for the `TABLEDATA` tag
+  /// * `STREAM` for `BINARY` and `BINARY2` tags
+  pub async fn from_reader(mut reader: R) -> Result<Self, VOTableError> {
+    let mut buff: Vec<u8> = Vec::with_capacity(CHUNK_SIZE);
+    let mut is_eof = false;
+    let mut from = 0;
+    let header_len = loop {
+      if let Some(len) = find_header_end(&buff, from) {
+        break len;
+      }
+      if is_eof {
+        // Let the synchronous parser return the right error
+        break buff.len();
+      }
+      // Restart from the last (possibly incomplete) tag, keeping a margin in case a tag name is
+      // split between two chunks
+      from = memchr::memrchr(b'<', &buff[from..])
+        .map_or(buff.len(), |pos| from + pos)
+        .min(buff.len().saturating_sub(16));
+      is_eof = read_chunk(&mut reader, &mut buff).await? == 0;
+    };
+    let SimpleVOTableRowIterator {
+      votable, data_type, ..
+    } = SimpleVOTableRowIterator::from_reader(&buff[..header_len])?;
+    buff.drain(..header_len);
+    let schema = votable
+      .get_first_table()
+      .map(|table| TableSchema::from(table.elems.as_slice()).unwrap())
+      .unwrap_or_default();
+    Ok(Self {
+      reader,
+      buff,
+      is_eof,
+      votable,
+      data_type,
+      schema,
+      rows: VecDeque::new(),
+      bin_buff: Vec::new(),
+      is_data_end: false,
+    })
+  }
+
+  pub fn data_type(&self) -> &TableOrBinOrBin2 {
+    &self.data_type
+  }
+
+  pub fn votable(&self) -> &VOTable<VoidTableDataContent> {
+    &self.votable
+  }
+
+  /// Returns the metadata of the table the rows belong to.
+  pub fn table(&mut self) -> &mut Table<VoidTableDataContent> {
+    self.votable.get_first_table_mut().unwrap()
+  }
+
+  /// Returns the next row, or `None` if all rows have been read.
+  pub async fn next_row(&mut self) -> Option<Result<Vec<VOTableValue>, VOTableError>> {
+    match self.data_type {
+      TableOrBinOrBin2::TableData => self.next_tabledata_row().await,
+      TableOrBinOrBin2::Binary => self.next_binary_row(false).await,
+      TableOrBinOrBin2::Binary2 => self.next_binary_row(true).await,
+      TableOrBinOrBin2::Fits(_) => Err(VOTableError::Custom(String::from(
+        "FITS data not supported",
+      ))),
+    }
+    .transpose()
+  }
+
+  /// Returns a stream on the remaining rows.
+  pub fn row_stream(&mut self) -> impl Stream<Item = Result<Vec<VOTableValue>, VOTableError>> + '_ {
+    stream::unfold(self, |it| async move {
+      it.next_row().await.map(|row| (row, it))
+    })
+  }
+
+  /// Skip the remaining rows (if any), and read the remaining of the VOTable (assuming it
+  /// does not contain another table).
+  pub async fn read_to_end(mut self) -> Result<VOTable<VoidTableDataContent>, VOTableError> {
+    // Skip remaining rows
+    let end_marker: &[u8] = match self.data_type {
+      TableOrBinOrBin2::TableData => b"</TABLEDATA>",
+      _ => b"<",
+    };
+    let mut from = 0;
+    while !self.is_data_end {
+      match memmem::find(&self.buff[from..], end_marker) {
+        Some(pos) => {
+          self.buff.drain(..from + pos);
+          self.is_data_end = true;
+        }
+        None if self.is_eof => return Err(VOTableError::PrematureEOF("DATA")),
+        None => {
+          from = self.buff.len().saturating_sub(end_marker.len());
+          self.is_eof = read_chunk(&mut self.reader, &mut self.buff).await? == 0;
+        }
+      }
+    }
+    // Read and parse the footer
+    while !self.is_eof {
+      self.is_eof = read_chunk(&mut self.reader, &mut self.buff).await? == 0;
+    }
+    let Self {
+      buff,
+      mut votable,
+      data_type,
+      ..
+    } = self;
+    let mut reader = Reader::from_reader(buff.as_slice());
+    reader.check_end_names(false);
+    let mut reader_buff: Vec<u8> = Vec::with_capacity(512);
+    let end_tag: &[u8] = match data_type {
+      TableOrBinOrBin2::TableData => b"TABLEDATA",
+      TableOrBinOrBin2::Binary => b"BINARY",
+      _ => b"BINARY2",
+    };
+    reader
+      .read_to_end(end_tag, &mut reader_buff)
+      .map_err(VOTableError::Read)?;
+    votable
+      .read_from_data_end_to_end(&mut reader, &mut reader_buff)
+      .map(|()| votable)
+  }
+
+  async fn next_tabledata_row(&mut self) -> Result<Option<Vec<VOTableValue>>, VOTableError> {
+    let mut from = 0;
+    while self.rows.is_empty() && !self.is_data_end {
+      // Rows after the end of the data (e.g. of a next table in the same chunk) must be ignored
+      let data_end = memmem::find(&self.buff, b"</TABLEDATA>");
+      let to = data_end.unwrap_or(self.buff.len());
+      match memmem::rfind(&self.buff[from.min(to)..to], b"</TR>") {
+        Some(pos) => {
+          // Parse all complete rows at once, adding the end tag so that the parser stops
+          let end = from.min(to) + pos + 5;
+          let mut chunk: Vec<u8> = self.buff.drain(..end).collect();
+          chunk.extend_from_slice(b"</TABLEDATA>");
+          let mut reader = Reader::from_reader(chunk.as_slice());
+          reader.check_end_names(false);
+          let mut reader_buff: Vec<u8> = Vec::with_capacity(512);
+          let table = self.votable.get_first_table_mut().unwrap();
+          for row in DataTableRowValueIterator::new(
+            &mut reader,
+            &mut reader_buff,
+            table,
+            self.schema.clone(),
+          ) {
+            self.rows.push_back(row?);
+          }
+          from = 0;
+        }
+        None if data_end.is_some() => self.is_data_end = true,
+        None if self.is_eof => return Err(VOTableError::PrematureEOF("TABLEDATA")),
+        None => {
+          from = self.buff.len().saturating_sub(16);
+          self.is_eof = read_chunk(&mut self.reader, &mut self.buff).await? == 0;
+        }
+      }
+    }
+    Ok(self.rows.pop_front())
+  }
+
+  async fn next_binary_row(
+    &mut self,
+    is_binary2: bool,
+  ) -> Result<Option<Vec<VOTableValue>>, VOTableError> {
+    loop {
+      if !self.bin_buff.is_empty() {
+        let mut bytes = self.bin_buff.as_slice();
+        match read_binary_row(&mut bytes, &self.schema, is_binary2) {
+          Ok(row) => {
+            let n_read = self.bin_buff.len() - bytes.len();
+            self.bin_buff.drain(..n_read);
+            return Ok(Some(row));
+          }
+          Err(VOTableError::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+            // Not enough data: decode more base64 chars, unless the end of the data is reached
+            if self.is_data_end {
+              return Err(VOTableError::Io(e));
+            }
+          }
+          Err(e) => return Err(e),
+        }
+      } else if self.is_data_end {
+        return Ok(None);
+      }
+      self.decode_more_base64().await?;
+    }
+  }
+
+  /// Decode the pending base64 chars, reading more chars if needed.
+  /// The bytes following the end of the data (i.e. the footer) are never decoded.
+  async fn decode_more_base64(&mut self) -> Result<(), VOTableError> {
+    // The end of the data may already be in the buffer (e.g. if the whole document fits in a chunk)
+    if let Some(end) = memchr::memchr(b'<', &self.buff) {
+      self.is_data_end = true;
+      // Remove white spaces and decode all remaining base64 chars
+      let mut b64: Vec<u8> = self.buff.drain(..end).collect();
+      b64.retain(|c| !c.is_ascii_whitespace());
+      return decode_base64(&b64, &mut self.bin_buff);
+    }
+    // Decode the largest number of chars multiple of 4
+    self.buff.retain(|c| !c.is_ascii_whitespace());
+    let n = self.buff.len() & !3;
+    if n > 0 {
+      let b64: Vec<u8> = self.buff.drain(..n).collect();
+      decode_base64(&b64, &mut self.bin_buff)
+    } else if self.is_eof {
+      Err(VOTableError::PrematureEOF("STREAM"))
+    } else {
+      self.is_eof = read_chunk(&mut self.reader, &mut self.buff).await? == 0;
+      Ok(())
+    }
+  }
+}
+
+/// Returns the number of bytes of the header, i.e. the bytes till (inclusive) the end of either
+/// the `TABLEDATA` or the `STREAM` start tag.
+fn find_header_end(buff: &[u8], from: usize) -> Option<usize> {
+  [b"<TABLEDATA".as_slice(), b"<STREAM".as_slice()]
+    .into_iter()
+    .filter_map(|tag| {
+      memmem::find(&buff[from..], tag).and_then(|start| {
+        let start = from + start;
+        memchr::memchr(b'>', &buff[start..]).map(|end| start + end + 1)
+      })
+    })
+    .min()
+}
+
+async fn read_chunk<R: AsyncRead + Unpin>(
+  reader: &mut R,
+  buff: &mut Vec<u8>,
+) -> Result<usize, VOTableError> {
+  buff.reserve(CHUNK_SIZE);
+  reader.read_buf(buff).await.map_err(VOTableError::Io)
+}
+
+fn decode_base64(b64: &[u8], bin_buff: &mut Vec<u8>) -> Result<(), VOTableError> {
+  general_purpose::STANDARD
+    .decode_vec(b64, bin_buff)
+    .map_err(|e| VOTableError::Custom(format!("Error decoding base64: {:?}", e)))
+}
+
+/// Read a row from the given `BINARY` or `BINARY2` bytes.
+fn read_binary_row(
+  bytes: &mut &[u8],
+  schema: &[Schema],
+  is_binary2: bool,
+) -> Result<Vec<VOTableValue>, VOTableError> {
+  let mut binary_deser = BinaryDeserializer::new(bytes);
+  let null_flags: Vec<u8> = if is_binary2 {
+    let n_bytes = schema.len().div_ceil(8);
+    (&mut binary_deser).deserialize_tuple(n_bytes, FixedLengthArrayVisitor::new(n_bytes))?
+  } else {
+    vec![]
+  };
+  let mut row: Vec<VOTableValue> = Vec::with_capacity(schema.len());
+  for (i_col, field_schema) in schema.iter().enumerate() {
+    let field = field_schema.deserialize(&mut binary_deser)?;
+    if is_binary2 && (null_flags[i_col >> 3] & (128_u8 >> (i_col & 7))) != 0 {
+      row.push(VOTableValue::Null);
+    } else {
+      row.push(field);
+    }
+  }
+  Ok(row)
+}
@@ -0,0 +1,149 @@
+//! Asynchronous equivalent of the `write_to_data_beginning`, rows writers
+//! and `write_from_data_end` methods.
+
+use base64::{engine::general_purpose, Engine};
+use quick_xml::Writer;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{
+  error::VOTableError,
+  impls::{b64::write::BinarySerializer, mem::InMemTableDataRows, TableSchema, VOTableValue},
+  votable::VOTable,
+  TableDataContent,
+};
+
+/// Number of raw bytes encoded in a line of 64 base64 chars.
+const N_BYTES_PER_LINE: usize = 48;
+
+/// Write a VOTable in an async writer, the rows being provided one by one (or by chunks).
+///
+/// Each call serializes its XML (or base64) output in an internal buffer using the regular
+/// (synchronous) code, the buffer being then written (and emptied) asynchronously.
+///
+/// Usage:
+/// * `write_to_data_beginning`
+/// * `write_tabledata_rows`, or `write_binary_row`, or `write_binary2_row` (depending on the
+///   table data type), as many times as needed
+/// * `write_from_data_end`
+pub struct AsyncVOTableWriter<W: AsyncWrite + Unpin> {
+  writer: W,
+  xml: Writer<Vec<u8>>,
+  /// Binary data not yet base64 encoded (less than `N_BYTES_PER_LINE` bytes)
+  pending_bin: Vec<u8>,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncVOTableWriter<W> {
+  pub fn new(writer: W) -> Self {
+    Self {
+      writer,
+      xml: Writer::new_with_indent(Vec::with_capacity(8192), b' ', 4),
+      pending_bin: Vec::with_capacity(N_BYTES_PER_LINE),
+    }
+  }
+
+  /// Returns `true` if a table has been found, else the full content of the VOTable is written.
+  /// See `VOTable::write_to_data_beginning`.
+  pub async fn write_to_data_beginning<C: TableDataContent>(
+    &mut self,
+    votable: &mut VOTable<C>,
+    stop_before_data: bool,
+  ) -> Result<bool, VOTableError> {
+    let res = votable.write_to_data_beginning(&mut self.xml, &(), stop_before_data)?;
+    self.flush_xml().await.map(|()| res)
+  }
+
+  /// Write the given rows in `TABLEDATA`.
+  pub async fn write_tabledata_rows<I>(
+    &mut self,
+    rows: I,
+    schema: &TableSchema,
+  ) -> Result<(), VOTableError>
+  where
+    I: Iterator,
+    I::Item: AsRef<[VOTableValue]>,
+  {
+    InMemTableDataRows::write_tabledata_rows(&mut self.xml, rows, schema.clone())?;
+    self.flush_xml().await
+  }
+
+  /// Write the given row according to the `BINARY` scheme.
+  pub async fn write_binary_row(
+    &mut self,
+    row: &[VOTableValue],
+    schema: &TableSchema,
+  ) -> Result<(), VOTableError> {
+    InMemTableDataRows::write_binary_row(
+      &mut BinarySerializer::new(&mut self.pending_bin),
+      row.iter(),
+      schema,
+    )?;
+    self.write_base64_lines().await
+  }
+
+  /// Write the given row according to the `BINARY2` scheme.
+  pub async fn write_binary2_row(
+    &mut self,
+    row: &[VOTableValue],
+    schema: &TableSchema,
+  ) -> Result<(), VOTableError> {
+    InMemTableDataRows::write_binary2_row(
+      &mut BinarySerializer::new(&mut self.pending_bin),
+      row,
+      schema,
+    )?;
+    self.write_base64_lines().await
+  }
+
+  /// Write the remaining of the VOTable, i.e. from the end of the table data, and flush the
+  /// writer.
+  /// See `VOTable::write_from_data_end`.
+  pub async fn write_from_data_end<C: TableDataContent>(
+    &mut self,
+    votable: &mut VOTable<C>,
+    start_after_data: bool,
+  ) -> Result<(), VOTableError> {
+    if !self.pending_bin.is_empty() {
+      // Last base64 line, with padding if needed
+      let b64 = general_purpose::STANDARD.encode(&self.pending_bin);
+      self.pending_bin.clear();
+      self
+        .writer
+        .write_all(b64.as_bytes())
+        .await
+        .map_err(VOTableError::Io)?;
+    }
+    votable.write_from_data_end(&mut self.xml, &(), start_after_data)?;
+    self.flush_xml().await?;
+    self.writer.flush().await.map_err(VOTableError::Io)
+  }
+
+  /// Returns the underlying writer.
+  pub fn into_inner(self) -> W {
+    self.writer
+  }
+
+  /// Write the content of the XML buffer in the async writer.
+  async fn flush_xml(&mut self) -> Result<(), VOTableError> {
+    let buff = std::mem::take(self.xml.inner());
+    self.writer.write_all(&buff).await.map_err(VOTableError::Io)
+  }
+
+  /// Encode and write all full base64 lines, keeping the remaining bytes for later.
+  async fn write_base64_lines(&mut self) -> Result<(), VOTableError> {
+    let n_lines = self.pending_bin.len() / N_BYTES_PER_LINE;
+    if n_lines > 0 {
+      let mut b64 = String::with_capacity(n_lines * 65);
+      for line in self.pending_bin[..n_lines * N_BYTES_PER_LINE].chunks(N_BYTES_PER_LINE) {
+        general_purpose::STANDARD.encode_string(line, &mut b64);
+        b64.push('\n');
+      }
+      self.pending_bin.drain(..n_lines * N_BYTES_PER_LINE);
+      self
+        .writer
+        .write_all(b64.as_bytes())
+        .await
+        .map_err(VOTableError::Io)?;
+    }
+    Ok(())
+  }
+}
@@ -20,6 +20,8 @@ use crate::{
   utils::{discard_comment, discard_event, unexpected_attr_warn},
 };
 
+pub mod transform;
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(tag = "elem_type")]
 pub enum CooSysElem {
@@ -300,62 +302,134 @@ impl Display for JulianYear {
   }
 }
 
-/// Missing coosys are:
-/// * xy
-/// * barycentric
-/// * geo_app
+/// Either a year, or a reference (i.e. the `ID` or the `name`) to the `PARAM` or `FIELD`
+/// providing the year (e.g. an epoch varying from one row to another).
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum YearOrRef<Y> {
+  Year(Y),
+  Ref(String),
+}
+impl<Y> YearOrRef<Y> {
+  pub fn year(&self) -> Option<&Y> {
+    match self {
+      YearOrRef::Year(year) => Some(year),
+      YearOrRef::Ref(_) => None,
+    }
+  }
+
+  pub fn reference(&self) -> Option<&str> {
+    match self {
+      YearOrRef::Year(_) => None,
+      YearOrRef::Ref(reference) => Some(reference.as_str()),
+    }
+  }
+}
+impl<Y: FromStr<Err = ParseFloatError>> FromStr for YearOrRef<Y> {
+  type Err = ParseFloatError;
+
+  /// The string is considered as a reference if it does not look like a year, i.e. if it does
+  /// not start (possibly after a 'B' or a 'J' prefix) by a digit, a sign or a dot.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.parse::<Y>() {
+      Ok(year) => Ok(YearOrRef::Year(year)),
+      Err(e) => {
+        let stripped = s.strip_prefix(['B', 'J']).unwrap_or(s);
+        if stripped.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '+' | '-' | '.'))
+          || s.is_empty()
+        {
+          Err(e)
+        } else {
+          trace!("Parse year reference: '{}'", s);
+          Ok(YearOrRef::Ref(s.to_string()))
+        }
+      }
+    }
+  }
+}
+impl<Y: Display> Display for YearOrRef<Y> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      YearOrRef::Year(year) => year.fmt(f),
+      YearOrRef::Ref(reference) => f.write_str(reference),
+    }
+  }
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(tag = "system")]
 pub enum System {
   #[serde(rename = "eq_FK4")]
   EquatorialFK4 {
-    /// Equinox in julian years (ex: 1950.0)
-    equinox: BesselianYear,
-    /// Epoch value in julian years (ex: 2010.5506), or colname
+    /// Equinox in Besselian years (ex: 1950.0), or reference to a PARAM
+    equinox: YearOrRef<BesselianYear>,
+    /// Epoch value in Besselian years (ex: 1950.0), or reference to a PARAM or a FIELD
     #[serde(skip_serializing_if = "Option::is_none")]
-    epoch: Option<BesselianYear>,
+    epoch: Option<YearOrRef<BesselianYear>>,
   },
   #[serde(rename = "eq_FK5")]
   EquatorialFK5 {
-    /// Equinox in Besselian years (ex: 1950.0)
-    equinox: JulianYear,
-    /// Epoch value in Besseilan years (ex: 2010.5506), or colname
+    /// Equinox in julian years (ex: 2000.0), or reference to a PARAM
+    equinox: YearOrRef<JulianYear>,
+    /// Epoch value in julian years (ex: 2010.5506), or reference to a PARAM or a FIELD
     #[serde(skip_serializing_if = "Option::is_none")]
-    epoch: Option<JulianYear>,
+    epoch: Option<YearOrRef<JulianYear>>,
   },
   #[serde(rename = "ICRS")]
   ICRS {
-    /// Epoch value in julian years (ex: 2010.5506), or colname
+    /// Epoch value in julian years (ex: 2010.5506), or reference to a PARAM or a FIELD
     #[serde(skip_serializing_if = "Option::is_none")]
-    epoch: Option<JulianYear>,
+    epoch: Option<YearOrRef<JulianYear>>,
   },
   #[serde(rename = "ecl_FK4")]
   EcliptiqueFK4 {
-    /// Equinox in julian years (ex: 1950.0)
-    equinox: BesselianYear,
-    /// Epoch value in julian years (ex: 2010.5506), or colname
+    /// Equinox in Besselian years (ex: 1950.0), or reference to a PARAM
+    equinox: YearOrRef<BesselianYear>,
+    /// Epoch value in Besselian years (ex: 1950.0), or reference to a PARAM or a FIELD
     #[serde(skip_serializing_if = "Option::is_none")]
-    epoch: Option<BesselianYear>,
+    epoch: Option<YearOrRef<BesselianYear>>,
   },
   #[serde(rename = "ecl_FK5")]
   EcliptiqueFK5 {
-    /// Equinox in julian years (ex: 1950.0)
-    equinox: JulianYear,
-    /// Epoch value in julian years (ex: 2010.5506), or colname
+    /// Equinox in julian years (ex: 2000.0), or reference to a PARAM
+    equinox: YearOrRef<JulianYear>,
+    /// Epoch value in julian years (ex: 2010.5506), or reference to a PARAM or a FIELD
     #[serde(skip_serializing_if = "Option::is_none")]
-    epoch: Option<JulianYear>,
+    epoch: Option<YearOrRef<JulianYear>>,
   },
   #[serde(rename = "galactic")]
   Galactic {
-    /// Epoch value in julian years (ex: 2010.5506), or colname
+    /// Epoch value in julian years (ex: 2010.5506), or reference to a PARAM or a FIELD
     #[serde(skip_serializing_if = "Option::is_none")]
-    epoch: Option<JulianYear>,
+    epoch: Option<YearOrRef<JulianYear>>,
   },
   #[serde(rename = "supergalactic")]
   SuperGalactic {
-    /// Epoch value in julian years (ex: 2010.5506), or colname
+    /// Epoch value in julian years (ex: 2010.5506), or reference to a PARAM or a FIELD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    epoch: Option<YearOrRef<JulianYear>>,
+  },
+  /// Cartesian (x, y) coordinates, without specified frame.
+  #[serde(rename = "xy")]
+  XY {
+    /// Epoch value in julian years (ex: 2010.5506), or reference to a PARAM or a FIELD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    epoch: Option<YearOrRef<JulianYear>>,
+  },
+  /// Barycentric coordinates (ICRS orientation), e.g. from JPL ephemerides.
+  #[serde(rename = "barycentric")]
+  Barycentric {
+    /// Epoch value in julian years (ex: 2010.5506), or reference to a PARAM or a FIELD
     #[serde(skip_serializing_if = "Option::is_none")]
-    epoch: Option<JulianYear>,
+    epoch: Option<YearOrRef<JulianYear>>,
+  },
+  /// Apparent geocentric position.
+  #[serde(rename = "geo_app")]
+  GeocentricApparent {
+    /// Epoch (i.e. date of observation) in julian years (ex: 2010.5506), or reference to a
+    /// PARAM or a FIELD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    epoch: Option<YearOrRef<JulianYear>>,
   },
 }
 
@@ -366,7 +440,7 @@ impl System {
 
   pub fn new_eq_fk4(equinox_in_besselian_year: f64) -> System {
     System::EquatorialFK4 {
-      equinox: BesselianYear(equinox_in_besselian_year),
+      equinox: YearOrRef::Year(BesselianYear(equinox_in_besselian_year)),
       epoch: None,
     }
   }
@@ -377,29 +451,29 @@ impl System {
 
   pub fn new_ecl_fk4(equinox_in_besselian_year: f64) -> System {
     System::EcliptiqueFK4 {
-      equinox: BesselianYear(equinox_in_besselian_year),
+      equinox: YearOrRef::Year(BesselianYear(equinox_in_besselian_year)),
       epoch: None,
     }
   }
 
   pub fn new_default_eq_fk5() -> System {
-    System::new_eq_fk4(2000.0)
+    System::new_eq_fk5(2000.0)
   }
 
   pub fn new_eq_fk5(equinox_in_julian_year: f64) -> System {
     System::EquatorialFK5 {
-      equinox: JulianYear(equinox_in_julian_year),
+      equinox: YearOrRef::Year(JulianYear(equinox_in_julian_year)),
       epoch: None,
     }
   }
 
   pub fn new_default_ecl_fk5() -> System {
-    System::new_ecl_fk4(1950.0)
+    System::new_ecl_fk5(2000.0)
   }
 
   pub fn new_ecl_fk5(equinox_in_julian_year: f64) -> System {
     System::EcliptiqueFK5 {
-      equinox: JulianYear(equinox_in_julian_year),
+      equinox: YearOrRef::Year(JulianYear(equinox_in_julian_year)),
       epoch: None,
     }
   }
@@ -416,6 +490,18 @@ impl System {
     System::SuperGalactic { epoch: None }
   }
 
+  pub fn new_xy() -> System {
+    System::XY { epoch: None }
+  }
+
+  pub fn new_barycentric() -> System {
+    System::Barycentric { epoch: None }
+  }
+
+  pub fn new_geo_app() -> System {
+    System::GeocentricApparent { epoch: None }
+  }
+
   pub fn from_system<S>(system: S) -> Result<Self, VOTableError>
   where
     S: AsRef<str>,
@@ -429,8 +515,11 @@ impl System {
       "ecl_FK5" => Ok(System::new_default_ecl_fk5()),
       "galactic" => Ok(System::new_galactic()),
       "supergalactic" => Ok(System::new_supergalactic()),
+      "xy" => Ok(System::new_xy()),
+      "barycentric" => Ok(System::new_barycentric()),
+      "geo_app" => Ok(System::new_geo_app()),
       _ => Err(VOTableError::Custom(format!(
-        "System not recognized in tag '{}'. Expected: one of [eq_FK4, eq_FK5, ICRS, ecl_FK4, ecl_FK5, galactic, supergalactic]. Actual: '{}'.",
+        "System not recognized in tag '{}'. Expected: one of [eq_FK4, eq_FK5, ICRS, ecl_FK4, ecl_FK5, galactic, supergalactic, xy, barycentric, geo_app]. Actual: '{}'.",
         CooSys::TAG,
         system,
       ))),
@@ -445,30 +534,10 @@ impl System {
     let system = system.as_ref();
     let equinox = equinox.as_ref();
     match system {
-      "eq_FK4" => equinox
-        .parse::<BesselianYear>()
-        .map_err(|e| VOTableError::ParseYear(equinox.to_string(), e))
-        .map(|equinox| System::new_eq_fk4(equinox.0)),
-      "eq_FK5" => equinox
-        .parse::<JulianYear>()
-        .map_err(|e| VOTableError::ParseYear(equinox.to_string(), e))
-        .map(|equinox| System::new_eq_fk5(equinox.0)),
-      "ICRS" => Ok(System::new_icrs()),
-      "ecl_FK4" => equinox
-        .parse::<BesselianYear>()
-        .map_err(|e| VOTableError::ParseYear(equinox.to_string(), e))
-        .map(|equinox| System::new_ecl_fk4(equinox.0)),
-      "ecl_FK5" => equinox
-        .parse::<JulianYear>()
-        .map_err(|e| VOTableError::ParseYear(equinox.to_string(), e))
-        .map(|equinox| System::new_ecl_fk5(equinox.0)),
-      "galactic" => Ok(System::new_galactic()),
-      "supergalactic" => Ok(System::new_supergalactic()),
-      _ => Err(VOTableError::Custom(format!(
-        "System not recognized in tag '{}'. Expected: one of [eq_FK4, eq_FK5, ICRS, ecl_FK4, ecl_FK5, galactic, supergalactic]. Actual: '{}'.",
-        CooSys::TAG,
-        system,
-      ))),
+      "eq_FK4" | "ecl_FK4" | "eq_FK5" | "ecl_FK5" => {
+        System::from_system(system).and_then(|system| system.set_equinox_from_str(equinox))
+      }
+      _ => System::from_system(system),
     }
   }
 
@@ -483,10 +552,12 @@ impl System {
   /// For FK5 systems, the epoch must be provided in Julian years
   pub fn set_equinox_by_ref(&mut self, equinox_in_years: f64) {
     match self {
-      System::EquatorialFK4 { equinox, .. } => equinox.0 = equinox_in_years,
-      System::EcliptiqueFK4 { equinox, .. } => equinox.0 = equinox_in_years,
-      System::EquatorialFK5 { equinox, .. } => equinox.0 = equinox_in_years,
-      System::EcliptiqueFK5 { equinox, .. } => equinox.0 = equinox_in_years,
+      System::EquatorialFK4 { equinox, .. } | System::EcliptiqueFK4 { equinox, .. } => {
+        *equinox = YearOrRef::Year(BesselianYear(equinox_in_years))
+      }
+      System::EquatorialFK5 { equinox, .. } | System::EcliptiqueFK5 { equinox, .. } => {
+        *equinox = YearOrRef::Year(JulianYear(equinox_in_years))
+      }
       _ => {}
     }
   }
@@ -495,26 +566,19 @@ impl System {
     self.set_equinox_from_str_by_ref(equinox).map(|()| self)
   }
 
+  /// The equinox can be either a year or a reference to a PARAM.
   pub fn set_equinox_from_str_by_ref<S: AsRef<str>>(
     &mut self,
     equinox: S,
   ) -> Result<(), VOTableError> {
     let equinox_str = equinox.as_ref();
     match self {
-      System::EquatorialFK4 { equinox, .. } => equinox_str
-        .parse::<BesselianYear>()
+      System::EquatorialFK4 { equinox, .. } | System::EcliptiqueFK4 { equinox, .. } => equinox_str
+        .parse::<YearOrRef<BesselianYear>>()
         .map_err(|e| VOTableError::ParseYear(equinox_str.to_string(), e))
         .map(|new_equinox| *equinox = new_equinox),
-      System::EcliptiqueFK4 { equinox, .. } => equinox_str
-        .parse::<BesselianYear>()
-        .map_err(|e| VOTableError::ParseYear(equinox_str.to_string(), e))
-        .map(|new_equinox| *equinox = new_equinox),
-      System::EquatorialFK5 { equinox, .. } => equinox_str
-        .parse::<JulianYear>()
-        .map_err(|e| VOTableError::ParseYear(equinox_str.to_string(), e))
-        .map(|new_equinox| *equinox = new_equinox),
-      System::EcliptiqueFK5 { equinox, .. } => equinox_str
-        .parse::<JulianYear>()
+      System::EquatorialFK5 { equinox, .. } | System::EcliptiqueFK5 { equinox, .. } => equinox_str
+        .parse::<YearOrRef<JulianYear>>()
         .map_err(|e| VOTableError::ParseYear(equinox_str.to_string(), e))
         .map(|new_equinox| *equinox = new_equinox),
       _ => Ok(()),
@@ -529,42 +593,46 @@ impl System {
   }
 
   pub fn set_epoch_by_ref(&mut self, epoch_in_years: f64) {
-    let _ = match self {
-      System::EquatorialFK4 { equinox: _, epoch } => epoch.insert(BesselianYear(epoch_in_years)).0,
-      System::EcliptiqueFK4 { equinox: _, epoch } => epoch.insert(BesselianYear(epoch_in_years)).0,
-      System::EquatorialFK5 { equinox: _, epoch } => epoch.insert(JulianYear(epoch_in_years)).0,
-      System::EcliptiqueFK5 { equinox: _, epoch } => epoch.insert(JulianYear(epoch_in_years)).0,
-      System::ICRS { epoch } => epoch.insert(JulianYear(epoch_in_years)).0,
-      System::Galactic { epoch } => epoch.insert(JulianYear(epoch_in_years)).0,
-      System::SuperGalactic { epoch } => epoch.insert(JulianYear(epoch_in_years)).0,
-    };
+    match self {
+      System::EquatorialFK4 { epoch, .. } | System::EcliptiqueFK4 { epoch, .. } => {
+        *epoch = Some(YearOrRef::Year(BesselianYear(epoch_in_years)))
+      }
+      System::EquatorialFK5 { epoch, .. }
+      | System::EcliptiqueFK5 { epoch, .. }
+      | System::ICRS { epoch }
+      | System::Galactic { epoch }
+      | System::SuperGalactic { epoch }
+      | System::XY { epoch }
+      | System::Barycentric { epoch }
+      | System::GeocentricApparent { epoch } => {
+        *epoch = Some(YearOrRef::Year(JulianYear(epoch_in_years)))
+      }
+    }
   }
 
   pub fn set_epoch_from_str<S: AsRef<str>>(mut self, epoch: S) -> Result<Self, VOTableError> {
     self.set_epoch_from_str_by_ref(epoch).map(|()| self)
   }
 
+  /// The epoch can be either a year or a reference to a PARAM or a FIELD.
   pub fn set_epoch_from_str_by_ref<S: AsRef<str>>(&mut self, epoch: S) -> Result<(), VOTableError> {
     let epoch_str = epoch.as_ref();
     match self {
-      System::EquatorialFK4 { equinox: _, epoch } => epoch_str
-        .parse::<BesselianYear>()
-        .map(|y| epoch.insert(y).0),
-      System::EcliptiqueFK4 { equinox: _, epoch } => epoch_str
-        .parse::<BesselianYear>()
-        .map(|y| epoch.insert(y).0),
-      System::EquatorialFK5 { equinox: _, epoch } => {
-        epoch_str.parse::<JulianYear>().map(|y| epoch.insert(y).0)
-      }
-      System::EcliptiqueFK5 { equinox: _, epoch } => {
-        epoch_str.parse::<JulianYear>().map(|y| epoch.insert(y).0)
-      }
-      System::ICRS { epoch } => epoch_str.parse::<JulianYear>().map(|y| epoch.insert(y).0),
-      System::Galactic { epoch } => epoch_str.parse::<JulianYear>().map(|y| epoch.insert(y).0),
-      System::SuperGalactic { epoch } => epoch_str.parse::<JulianYear>().map(|y| epoch.insert(y).0),
+      System::EquatorialFK4 { epoch, .. } | System::EcliptiqueFK4 { epoch, .. } => epoch_str
+        .parse::<YearOrRef<BesselianYear>>()
+        .map(|y| *epoch = Some(y)),
+      System::EquatorialFK5 { epoch, .. }
+      | System::EcliptiqueFK5 { epoch, .. }
+      | System::ICRS { epoch }
+      | System::Galactic { epoch }
+      | System::SuperGalactic { epoch }
+      | System::XY { epoch }
+      | System::Barycentric { epoch }
+      | System::GeocentricApparent { epoch } => epoch_str
+        .parse::<YearOrRef<JulianYear>>()
+        .map(|y| *epoch = Some(y)),
     }
     .map_err(|e| VOTableError::ParseYear(epoch_str.to_string(), e))
-    .map(|_| ())
   }
 
   pub fn for_each_attribute<F>(&self, f: &mut F)
@@ -618,6 +686,24 @@ impl System {
           f("epoch", epoch.to_string().as_str());
         }
       }
+      System::XY { epoch } => {
+        f("system", "xy");
+        if let Some(epoch) = epoch {
+          f("epoch", epoch.to_string().as_str());
+        }
+      }
+      System::Barycentric { epoch } => {
+        f("system", "barycentric");
+        if let Some(epoch) = epoch {
+          f("epoch", epoch.to_string().as_str());
+        }
+      }
+      System::GeocentricApparent { epoch } => {
+        f("system", "geo_app");
+        if let Some(epoch) = epoch {
+          f("epoch", epoch.to_string().as_str());
+        }
+      }
     }
   }
 }
@@ -630,7 +716,7 @@ mod tests {
 
   use crate::{
     QuickXmlReadWrite, VOTableElement,
-    coosys::{CooSys, System},
+    coosys::{CooSys, JulianYear, System, YearOrRef},
   };
 
   #[test]
@@ -647,7 +733,7 @@ mod tests {
           assert_eq!(coosys.id, "J2000");
           match &coosys.coosys {
             System::EquatorialFK5 { equinox, epoch } => {
-              assert_eq!(equinox.0, 2000.0);
+              assert_eq!(equinox.year(), Some(&JulianYear(2000.0)));
               assert!(epoch.is_none());
             }
             _ => unreachable!(),
@@ -665,4 +751,34 @@ mod tests {
     let output_str = unsafe { std::str::from_utf8_unchecked(output.as_slice()) };
     assert_eq!(output_str, xml);
   }
+
+  #[test]
+  fn test_coosys_refs_and_systems() {
+    let coosys =
+      CooSys::from_attrs([("ID", "sys"), ("system", "ICRS"), ("epoch", "obs_epoch")].into_iter())
+        .unwrap();
+    assert_eq!(
+      coosys.coosys,
+      System::ICRS {
+        epoch: Some(YearOrRef::Ref(String::from("obs_epoch")))
+      }
+    );
+    let coosys =
+      CooSys::from_attrs([("ID", "sys"), ("system", "ICRS"), ("epoch", "J2015.5")].into_iter())
+        .unwrap();
+    assert_eq!(coosys.coosys, System::new_icrs().set_epoch(2015.5));
+    // Looks like a year, but not a Besselian one
+    assert!(System::from_system_and_equinox("eq_FK4", "J2000").is_err());
+    assert_eq!(
+      System::from_system("eq_FK5").unwrap(),
+      System::new_eq_fk5(2000.0)
+    );
+    for system in ["xy", "barycentric", "geo_app"] {
+      let mut attrs: Vec<(String, String)> = Vec::new();
+      System::from_system(system)
+        .unwrap()
+        .for_each_attribute(&mut |k, v| attrs.push((k.to_string(), v.to_string())));
+      assert_eq!(attrs, vec![(String::from("system"), String::from(system))]);
+    }
+  }
 }
@@ -0,0 +1,1263 @@
+//! Transformation of positions (and proper motions) between the celestial frames a `COOSYS`
+//! may define: ICRS, FK5, FK4 (B1950 only), galactic, supergalactic and ecliptic.
+//!
+//! All transformations go through ICRS:
+//! * FK5 <-> ICRS takes into account the frame bias, but neglects the (< 1 mas/yr) FK5 spin;
+//! * FK4 <-> FK5 follows the SOFA `fk425`, `fk45z` (and inverse) algorithms, i.e. it removes
+//!   (or adds) the E-terms of aberration and uses the Standish matrix;
+//! * FK5 equinoxes other than J2000 use the IAU 1976 precession;
+//! * ecliptic coordinates use the IAU 1976 obliquity of the ecliptic at the equinox.
+//!
+//! Parallaxes and radial velocities are ignored (i.e. considered null) and the epoch
+//! propagation is linear in the tangent plane.
+
+use std::{
+  f64::consts::PI,
+  fmt::{self, Display, Formatter},
+  str::FromStr,
+};
+
+use log::warn;
+use once_cell::sync::Lazy;
+
+use super::{CooSys, System, YearOrRef};
+use crate::{
+  TableDataContent,
+  datatype::Datatype,
+  error::VOTableError,
+  field::Field,
+  impls::VOTableValue,
+  resource::{Resource, ResourceElem, ResourceOrTable},
+  table::TableElem,
+  units::Unit,
+  votable::{VOTable, VOTableElem},
+};
+
+type V3 = [f64; 3];
+type M3 = [[f64; 3]; 3];
+
+const DEG2RAD: f64 = PI / 180.0;
+const ARCSEC2RAD: f64 = DEG2RAD / 3600.0;
+const MAS2RAD: f64 = ARCSEC2RAD / 1000.0;
+/// Radians per year to arcsec per century
+const PMF: f64 = 100.0 / ARCSEC2RAD;
+const JULIAN_YEAR: f64 = 365.25;
+const TROPICAL_YEAR: f64 = 365.242198781;
+
+/// Rotation matrix from ICRS to galactic coordinates (Hipparcos, ESA 1997).
+#[rustfmt::skip]
+#[allow(clippy::excessive_precision)]
+const ICRS_TO_GAL: M3 = [
+  [-0.0548755604162154, -0.8734370902348850, -0.4838350155487132],
+  [ 0.4941094278755837, -0.4448296299600112,  0.7469822444972189],
+  [-0.8676661490190047, -0.1980763734312015,  0.4559837761750669],
+];
+
+/// E-terms of aberration vector and its time derivative (per tropical century),
+/// cf. Seidelmann 1992, 3.591-2.
+const A: V3 = [-1.62557e-6, -0.31919e-6, -0.13843e-6];
+const AD: V3 = [1.245e-3, -1.580e-3, -0.659e-3];
+
+/// FK4 (B1950) to FK5 (J2000) position-velocity matrix, cf. Seidelmann 1992, 3.591-4:
+/// `EM[i][j][k]` is the row `j` of the block transforming the input part `k` into the output
+/// part `i` (`0`: position; `1`: velocity in arcsec per century).
+#[rustfmt::skip]
+const EM: [[[V3; 2]; 3]; 2] = [
+  [
+    [[ 0.9999256782, -0.0111820611, -0.0048579477], [ 0.00000242395018, -0.00000002710663, -0.00000001177656]],
+    [[ 0.0111820610,  0.9999374784, -0.0000271765], [ 0.00000002710663,  0.00000242397878, -0.00000000006587]],
+    [[ 0.0048579479, -0.0000271474,  0.9999881997], [ 0.00000001177656, -0.00000000006582,  0.00000242410173]],
+  ],
+  [
+    [[-0.000551, -0.238565,  0.435739], [ 0.99994704, -0.01118251, -0.00485767]],
+    [[ 0.238514, -0.002667, -0.008541], [ 0.01118251,  0.99995883, -0.00002718]],
+    [[-0.435623,  0.012254,  0.002117], [ 0.00485767, -0.00002714,  1.00000956]],
+  ],
+];
+
+/// Rotation matrix from FK5 (J2000) to ICRS (frame bias, Hipparcos).
+static FK5_TO_ICRS: Lazy<M3> = Lazy::new(|| {
+  rv2m(&[
+    -19.9 * MAS2RAD, // x
+    -9.1 * MAS2RAD,  // y
+    22.9 * MAS2RAD,  // z
+  ])
+});
+
+/// Rotation matrix from galactic to supergalactic coordinates
+/// (supergalactic north pole at l=47.37, b=6.32; origin at l=137.37, b=0).
+static GAL_TO_SGAL: Lazy<M3> = Lazy::new(|| {
+  let x = sph2cart(137.37 * DEG2RAD, 0.0);
+  let z = sph2cart(47.37 * DEG2RAD, 6.32 * DEG2RAD);
+  [x, cross(&z, &x), z]
+});
+/// Unit of the proper motions used in the transformations.
+static MAS_PER_YEAR: Lazy<Unit> = Lazy::new(|| "mas/yr".parse().unwrap());
+
+/// A celestial frame, i.e. a `COOSYS` system with its equinox.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Frame {
+  ICRS,
+  /// Equatorial FK5, equinox in Julian years
+  FK5 {
+    equinox: f64,
+  },
+  /// Equatorial FK4, equinox in Besselian years (only B1950 is supported)
+  FK4 {
+    equinox: f64,
+  },
+  /// Ecliptic FK5, equinox in Julian years
+  EclipticFK5 {
+    equinox: f64,
+  },
+  /// Ecliptic FK4, equinox in Besselian years (only B1950 is supported)
+  EclipticFK4 {
+    equinox: f64,
+  },
+  Galactic,
+  SuperGalactic,
+}
+
+impl Frame {
+  /// Returns the frame of the given system, its equinox (if any) being already resolved.
+  pub fn from_system(system: &System) -> Result<Self, VOTableError> {
+    fn year<Y, F: Fn(&Y) -> f64>(equinox: &YearOrRef<Y>, f: F) -> Result<f64, VOTableError> {
+      equinox.year().map(f).ok_or_else(|| {
+        VOTableError::Custom(format!(
+          "Unresolved equinox reference '{}'",
+          equinox.reference().unwrap_or_default()
+        ))
+      })
+    }
+    let frame = match system {
+      System::ICRS { .. } => Frame::ICRS,
+      System::EquatorialFK5 { equinox, .. } => Frame::FK5 {
+        equinox: year(equinox, |y| y.0)?,
+      },
+      System::EquatorialFK4 { equinox, .. } => Frame::FK4 {
+        equinox: year(equinox, |y| y.0)?,
+      },
+      System::EcliptiqueFK5 { equinox, .. } => Frame::EclipticFK5 {
+        equinox: year(equinox, |y| y.0)?,
+      },
+      System::EcliptiqueFK4 { equinox, .. } => Frame::EclipticFK4 {
+        equinox: year(equinox, |y| y.0)?,
+      },
+      System::Galactic { .. } => Frame::Galactic,
+      System::SuperGalactic { .. } => Frame::SuperGalactic,
+      System::XY { .. } | System::Barycentric { .. } | System::GeocentricApparent { .. } => {
+        let mut name = String::new();
+        system.for_each_attribute(&mut |k, v| {
+          if k == "system" {
+            name.push_str(v)
+          }
+        });
+        return Err(VOTableError::Custom(format!(
+          "Coordinate transformation from/to system '{}' not supported",
+          name
+        )));
+      }
+    };
+    frame.check().map(|()| frame)
+  }
+
+  /// Returns the system corresponding to the frame (without epoch).
+  pub fn to_system(&self) -> System {
+    match *self {
+      Frame::ICRS => System::new_icrs(),
+      Frame::FK5 { equinox } => System::new_eq_fk5(equinox),
+      Frame::FK4 { equinox } => System::new_eq_fk4(equinox),
+      Frame::EclipticFK5 { equinox } => System::new_ecl_fk5(equinox),
+      Frame::EclipticFK4 { equinox } => System::new_ecl_fk4(equinox),
+      Frame::Galactic => System::new_galactic(),
+      Frame::SuperGalactic => System::new_supergalactic(),
+    }
+  }
+
+  /// Returns the primary UCDs of the longitude and of the latitude in this frame.
+  pub fn lon_lat_ucds(&self) -> (&'static str, &'static str) {
+    match self {
+      Frame::ICRS | Frame::FK5 { .. } | Frame::FK4 { .. } => ("pos.eq.ra", "pos.eq.dec"),
+      Frame::EclipticFK5 { .. } | Frame::EclipticFK4 { .. } => {
+        ("pos.ecliptic.lon", "pos.ecliptic.lat")
+      }
+      Frame::Galactic => ("pos.galactic.lon", "pos.galactic.lat"),
+      Frame::SuperGalactic => ("pos.supergalactic.lon", "pos.supergalactic.lat"),
+    }
+  }
+
+  /// Tells whether epochs are given in Besselian years in this frame.
+  pub fn is_fk4(&self) -> bool {
+    matches!(self, Frame::FK4 { .. } | Frame::EclipticFK4 { .. })
+  }
+
+  fn check(&self) -> Result<(), VOTableError> {
+    match self {
+      Frame::FK4 { equinox } | Frame::EclipticFK4 { equinox } if *equinox != 1950.0 => {
+        Err(VOTableError::Custom(format!(
+          "Only the B1950 equinox is supported for FK4 frames. Actual: B{}",
+          equinox
+        )))
+      }
+      _ => Ok(()),
+    }
+  }
+
+  /// Position in this frame to position in ICRS (the epoch, in Julian years, being used only
+  /// for FK4 frames).
+  fn pos_to_icrs(&self, p: V3, epoch: f64) -> V3 {
+    match *self {
+      Frame::ICRS => p,
+      Frame::FK5 { equinox } => mxv(&FK5_TO_ICRS, &mtxv(&prec_fk5(equinox), &p)),
+      Frame::FK4 { .. } => {
+        Frame::FK5 { equinox: 2000.0 }.pos_to_icrs(fk45z(&p, julian_to_besselian(epoch)), epoch)
+      }
+      Frame::EclipticFK5 { equinox } => {
+        Frame::FK5 { equinox }.pos_to_icrs(mtxv(&ecl_fk5(equinox), &p), epoch)
+      }
+      Frame::EclipticFK4 { equinox } => {
+        Frame::FK4 { equinox }.pos_to_icrs(mtxv(&ecl_fk4(equinox), &p), epoch)
+      }
+      Frame::Galactic => mtxv(&ICRS_TO_GAL, &p),
+      Frame::SuperGalactic => Frame::Galactic.pos_to_icrs(mtxv(&GAL_TO_SGAL, &p), epoch),
+    }
+  }
+
+  /// Inverse of `pos_to_icrs`.
+  fn pos_from_icrs(&self, p: V3, epoch: f64) -> V3 {
+    match *self {
+      Frame::ICRS => p,
+      Frame::FK5 { equinox } => mxv(&prec_fk5(equinox), &mtxv(&FK5_TO_ICRS, &p)),
+      Frame::FK4 { .. } => fk54z(
+        &Frame::FK5 { equinox: 2000.0 }.pos_from_icrs(p, epoch),
+        julian_to_besselian(epoch),
+      ),
+      Frame::EclipticFK5 { equinox } => mxv(
+        &ecl_fk5(equinox),
+        &Frame::FK5 { equinox }.pos_from_icrs(p, epoch),
+      ),
+      Frame::EclipticFK4 { equinox } => mxv(
+        &ecl_fk4(equinox),
+        &Frame::FK4 { equinox }.pos_from_icrs(p, epoch),
+      ),
+      Frame::Galactic => mxv(&ICRS_TO_GAL, &p),
+      Frame::SuperGalactic => mxv(&GAL_TO_SGAL, &Frame::Galactic.pos_from_icrs(p, epoch)),
+    }
+  }
+
+  /// Position and proper motion at the given epoch in this frame, to position and proper
+  /// motion in ICRS, at the returned epoch (epochs in Julian years).
+  fn pv_to_icrs(&self, pv: PosVel, epoch: f64) -> (PosVel, f64) {
+    match *self {
+      Frame::ICRS => (pv, epoch),
+      Frame::FK5 { equinox } => (pv.rotate_t(&prec_fk5(equinox)).rotate(&FK5_TO_ICRS), epoch),
+      Frame::FK4 { .. } => {
+        // FK4 at epoch B1950 to FK5 at epoch J2000
+        let pv = pv.propagate(besselian_to_julian(1950.0) - epoch);
+        let (p, v) = fk425(&pv.p, &scale(&pv.v, TROPICAL_YEAR / JULIAN_YEAR));
+        Frame::FK5 { equinox: 2000.0 }.pv_to_icrs(PosVel { p, v }, 2000.0)
+      }
+      Frame::EclipticFK5 { equinox } => {
+        Frame::FK5 { equinox }.pv_to_icrs(pv.rotate_t(&ecl_fk5(equinox)), epoch)
+      }
+      Frame::EclipticFK4 { equinox } => {
+        Frame::FK4 { equinox }.pv_to_icrs(pv.rotate_t(&ecl_fk4(equinox)), epoch)
+      }
+      Frame::Galactic => (pv.rotate_t(&ICRS_TO_GAL), epoch),
+      Frame::SuperGalactic => Frame::Galactic.pv_to_icrs(pv.rotate_t(&GAL_TO_SGAL), epoch),
+    }
+  }
+
+  /// Inverse of `pv_to_icrs`.
+  fn pv_from_icrs(&self, pv: PosVel, epoch: f64) -> (PosVel, f64) {
+    match *self {
+      Frame::ICRS => (pv, epoch),
+      Frame::FK5 { equinox } => (pv.rotate_t(&FK5_TO_ICRS).rotate(&prec_fk5(equinox)), epoch),
+      Frame::FK4 { .. } => {
+        // FK5 at epoch J2000 to FK4 at epoch B1950
+        let (pv, _) =
+          Frame::FK5 { equinox: 2000.0 }.pv_from_icrs(pv.propagate(2000.0 - epoch), 2000.0);
+        let (p, v) = fk524(&pv.p, &pv.v);
+        (
+          PosVel {
+            p,
+            v: scale(&v, JULIAN_YEAR / TROPICAL_YEAR),
+          },
+          besselian_to_julian(1950.0),
+        )
+      }
+      Frame::EclipticFK5 { equinox } => {
+        let (pv, epoch) = Frame::FK5 { equinox }.pv_from_icrs(pv, epoch);
+        (pv.rotate(&ecl_fk5(equinox)), epoch)
+      }
+      Frame::EclipticFK4 { equinox } => {
+        let (pv, epoch) = Frame::FK4 { equinox }.pv_from_icrs(pv, epoch);
+        (pv.rotate(&ecl_fk4(equinox)), epoch)
+      }
+      Frame::Galactic => (pv.rotate(&ICRS_TO_GAL), epoch),
+      Frame::SuperGalactic => {
+        let (pv, epoch) = Frame::Galactic.pv_from_icrs(pv, epoch);
+        (pv.rotate(&GAL_TO_SGAL), epoch)
+      }
+    }
+  }
+}
+
+impl FromStr for Frame {
+  type Err = VOTableError;
+
+  /// Parse either a short frame name (case insensitive), or a `COOSYS` system name (with
+  /// its default equinox).
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "icrs" => Ok(Frame::ICRS),
+      "fk5" | "eq_fk5" => Ok(Frame::FK5 { equinox: 2000.0 }),
+      "fk4" | "eq_fk4" => Ok(Frame::FK4 { equinox: 1950.0 }),
+      "ecliptic" | "ecl_fk5" => Ok(Frame::EclipticFK5 { equinox: 2000.0 }),
+      "ecl_fk4" => Ok(Frame::EclipticFK4 { equinox: 1950.0 }),
+      "galactic" => Ok(Frame::Galactic),
+      "supergalactic" => Ok(Frame::SuperGalactic),
+      _ => Err(VOTableError::Custom(format!(
+        "Unrecognized frame. Actual: '{}'. Expected: one of [icrs, fk5, fk4, ecliptic, ecl_fk4, galactic, supergalactic]",
+        s
+      ))),
+    }
+  }
+}
+
+impl Display for Frame {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Frame::ICRS => f.write_str("ICRS"),
+      Frame::FK5 { equinox } => write!(f, "FK5(J{})", equinox),
+      Frame::FK4 { equinox } => write!(f, "FK4(B{})", equinox),
+      Frame::EclipticFK5 { equinox } => write!(f, "ecliptic FK5(J{})", equinox),
+      Frame::EclipticFK4 { equinox } => write!(f, "ecliptic FK4(B{})", equinox),
+      Frame::Galactic => f.write_str("galactic"),
+      Frame::SuperGalactic => f.write_str("supergalactic"),
+    }
+  }
+}
+
+/// Transformation of positions (and proper motions) from a frame to another.
+#[derive(Clone, Debug)]
+pub struct CoordTransform {
+  from: Frame,
+  to: Frame,
+}
+
+impl CoordTransform {
+  pub fn new(from: Frame, to: Frame) -> Result<Self, VOTableError> {
+    from.check().and_then(|()| to.check())?;
+    Ok(Self { from, to })
+  }
+
+  pub fn from(&self) -> &Frame {
+    &self.from
+  }
+
+  pub fn to(&self) -> &Frame {
+    &self.to
+  }
+
+  /// Transform the given position, in degrees.
+  /// The epoch of the position, in Julian years, is used only for FK4 frames
+  /// (the FK4 proper motion is assumed to be null in FK5).
+  pub fn transform(&self, lon: f64, lat: f64, epoch: f64) -> (f64, f64) {
+    if self.from == self.to {
+      (lon, lat)
+    } else {
+      let p = sph2cart(lon * DEG2RAD, lat * DEG2RAD);
+      let p = self.from.pos_to_icrs(p, epoch);
+      let (lon, lat) = cart2sph(&self.to.pos_from_icrs(p, epoch));
+      (lon / DEG2RAD, lat / DEG2RAD)
+    }
+  }
+
+  /// Transform the given position (in degrees) and proper motion (in mas/yr, the longitude
+  /// component including the `cos(lat)` factor) from the given epoch to `to_epoch` (both in
+  /// Julian years).
+  pub fn transform_with_pm(
+    &self,
+    lon: f64,
+    lat: f64,
+    pm_lon: f64,
+    pm_lat: f64,
+    epoch: f64,
+    to_epoch: f64,
+  ) -> (f64, f64, f64, f64) {
+    let pv = PosVel::from_sph(
+      lon * DEG2RAD,
+      lat * DEG2RAD,
+      pm_lon * MAS2RAD,
+      pm_lat * MAS2RAD,
+    );
+    let (pv, epoch) = if self.from == self.to {
+      (pv, epoch)
+    } else {
+      let (pv, epoch) = self.from.pv_to_icrs(pv, epoch);
+      self.to.pv_from_icrs(pv, epoch)
+    };
+    let (lon, lat, pm_lon, pm_lat) = pv.propagate(to_epoch - epoch).to_sph();
+    (
+      lon / DEG2RAD,
+      lat / DEG2RAD,
+      pm_lon / MAS2RAD,
+      pm_lat / MAS2RAD,
+    )
+  }
+}
+
+/// Epoch of the positions of a table.
+#[derive(Clone, Debug)]
+enum TableEpoch {
+  /// Constant epoch, in Julian years
+  Const(f64),
+  /// Epoch provided by the column of given index
+  Column { index: usize, is_besselian: bool },
+  /// No epoch
+  Unknown,
+}
+
+/// Indices of the proper motion columns and factors to convert their values in mas/yr.
+#[derive(Clone, Debug)]
+struct PmColumns {
+  i_lon: usize,
+  i_lat: usize,
+  to_mas_per_year_lon: f64,
+  to_mas_per_year_lat: f64,
+}
+
+/// Transforms the positions (and proper motions, if any) of the first table of a VOTable
+/// into a given frame.
+///
+/// The positions are found from their UCDs (`pos.eq.ra`/`pos.eq.dec`, `pos.galactic.lon`/...,
+/// preferably with `meta.main`), in degrees, and the proper motions (`pos.pm` with the position
+/// UCD as a secondary word), in mas/yr, arcsec/yr or deg/yr. The frame of the input positions
+/// is given by the `COOSYS` referenced by the longitude `FIELD` (or by the only `COOSYS` of the
+/// VOTable).
+///
+/// Rows without proper motions are not propagated to the target epoch.
+pub struct TableFrameConverter {
+  transform: CoordTransform,
+  i_lon: usize,
+  i_lat: usize,
+  pm: Option<PmColumns>,
+  epoch: TableEpoch,
+  to_epoch: Option<f64>,
+}
+
+impl TableFrameConverter {
+  /// Prepares the transformation and modifies the VOTable metadata accordingly, i.e. the
+  /// `COOSYS` system (and epoch) and the UCDs of the transformed columns.
+  /// # Params
+  /// * `votable`: the VOTable containing (at least) one table
+  /// * `to`: the target frame
+  /// * `to_epoch`: the target epoch, in Julian years (only for tables with proper motions)
+  pub fn new<C: TableDataContent>(
+    votable: &mut VOTable<C>,
+    to: Frame,
+    to_epoch: Option<f64>,
+  ) -> Result<Self, VOTableError> {
+    let table = votable
+      .get_first_table()
+      .ok_or_else(|| VOTableError::Custom(String::from("No table found in the VOTable")))?;
+    let fields: Vec<Field> = table
+      .elems
+      .iter()
+      .filter_map(|elem| match elem {
+        TableElem::Field(field) => Some(field.clone()),
+        _ => None,
+      })
+      .collect();
+    // Look for the position columns
+    let (i_lon, i_lat, lon_ucd, lat_ucd) = [
+      ("pos.eq.ra", "pos.eq.dec"),
+      ("pos.galactic.lon", "pos.galactic.lat"),
+      ("pos.supergalactic.lon", "pos.supergalactic.lat"),
+      ("pos.ecliptic.lon", "pos.ecliptic.lat"),
+    ]
+    .into_iter()
+    .find_map(|(lon_ucd, lat_ucd)| {
+      match (
+        find_column(&fields, |atoms| atoms[0] == lon_ucd),
+        find_column(&fields, |atoms| atoms[0] == lat_ucd),
+      ) {
+        (Some(i_lon), Some(i_lat)) => Some((i_lon, i_lat, lon_ucd, lat_ucd)),
+        _ => None,
+      }
+    })
+    .ok_or_else(|| VOTableError::Custom(String::from("No position columns found")))?;
+    for i in [i_lon, i_lat] {
+      check_numeric(&fields[i])?;
+    }
+    // Look for the proper motion columns
+    let pm = match (
+      find_column(&fields, |atoms| {
+        atoms[0] == "pos.pm" && atoms.contains(&lon_ucd)
+      }),
+      find_column(&fields, |atoms| {
+        atoms[0] == "pos.pm" && atoms.contains(&lat_ucd)
+      }),
+    ) {
+      (Some(i_lon), Some(i_lat)) => {
+        check_numeric(&fields[i_lon]).and_then(|()| check_numeric(&fields[i_lat]))?;
+        Some(PmColumns {
+          i_lon,
+          i_lat,
+          to_mas_per_year_lon: pm_unit_to_mas_per_year(&fields[i_lon])?,
+          to_mas_per_year_lat: pm_unit_to_mas_per_year(&fields[i_lat])?,
+        })
+      }
+      _ => None,
+    };
+    // Look for the COOSYS
+    let coosys_ref = fields[i_lon].ref_.clone();
+    let mut system: Option<System> = None;
+    let mut n_coosys = 0;
+    for_each_coosys_mut(votable, &mut |coosys| {
+      n_coosys += 1;
+      if coosys_ref.as_deref() == Some(coosys.id.as_str()) || system.is_none() {
+        system = Some(coosys.coosys.clone());
+      }
+    });
+    let mut system = match system {
+      Some(system) if coosys_ref.is_some() || n_coosys == 1 => system,
+      _ => {
+        return Err(VOTableError::Custom(format!(
+          "No COOSYS found for column '{}'",
+          fields[i_lon].name
+        )));
+      }
+    };
+    // Resolve the equinox (if any)
+    let table = votable.get_first_table().unwrap();
+    let equinox_ref = match &system {
+      System::EquatorialFK4 { equinox, .. } | System::EcliptiqueFK4 { equinox, .. } => {
+        equinox.reference().map(String::from)
+      }
+      System::EquatorialFK5 { equinox, .. } | System::EcliptiqueFK5 { equinox, .. } => {
+        equinox.reference().map(String::from)
+      }
+      _ => None,
+    };
+    if let Some(equinox_ref) = equinox_ref {
+      let equinox = find_param_value(votable, &table.elems, &equinox_ref)?;
+      system.set_equinox_from_str_by_ref(equinox)?;
+    }
+    let from = Frame::from_system(&system)?;
+    let transform = CoordTransform::new(from, to)?;
+    // Epoch
+    let epoch_attr: Option<YearOrRef<f64>> = match &system {
+      System::EquatorialFK4 { epoch, .. } | System::EcliptiqueFK4 { epoch, .. } => {
+        epoch.as_ref().map(|epoch| match epoch {
+          YearOrRef::Year(y) => YearOrRef::Year(besselian_to_julian(y.0)),
+          YearOrRef::Ref(r) => YearOrRef::Ref(r.clone()),
+        })
+      }
+      System::EquatorialFK5 { epoch, .. }
+      | System::EcliptiqueFK5 { epoch, .. }
+      | System::ICRS { epoch }
+      | System::Galactic { epoch }
+      | System::SuperGalactic { epoch }
+      | System::XY { epoch }
+      | System::Barycentric { epoch }
+      | System::GeocentricApparent { epoch } => epoch.as_ref().map(|epoch| match epoch {
+        YearOrRef::Year(y) => YearOrRef::Year(y.0),
+        YearOrRef::Ref(r) => YearOrRef::Ref(r.clone()),
+      }),
+    };
+    let epoch = match epoch_attr {
+      Some(YearOrRef::Year(y)) => TableEpoch::Const(y),
+      Some(YearOrRef::Ref(r)) => match fields
+        .iter()
+        .position(|f| f.id.as_deref() == Some(r.as_str()) || f.name == r)
+      {
+        Some(index) => TableEpoch::Column {
+          index,
+          is_besselian: from.is_fk4(),
+        },
+        None => {
+          let value = find_param_value(votable, &table.elems, &r)?;
+          let epoch = value
+            .parse::<f64>()
+            .map_err(|e| VOTableError::ParseYear(value.clone(), e))?;
+          TableEpoch::Const(if from.is_fk4() {
+            besselian_to_julian(epoch)
+          } else {
+            epoch
+          })
+        }
+      },
+      // Default epoch equal to the equinox
+      None => match from {
+        Frame::FK4 { equinox } | Frame::EclipticFK4 { equinox } => {
+          TableEpoch::Const(besselian_to_julian(equinox))
+        }
+        Frame::FK5 { equinox } | Frame::EclipticFK5 { equinox } => TableEpoch::Const(equinox),
+        _ => TableEpoch::Unknown,
+      },
+    };
+    if matches!(epoch, TableEpoch::Unknown) {
+      if pm.is_some() && to_epoch.is_some() {
+        return Err(VOTableError::Custom(String::from(
+          "Unknown epoch: unable to propagate the positions",
+        )));
+      }
+      if from.is_fk4() || to.is_fk4() {
+        warn!("Unknown epoch, J2000 assumed in the FK4 transformation");
+      }
+    }
+    // Update the metadata
+    let mut new_system = to.to_system();
+    let to_epoch = to_epoch.filter(|_| pm.is_some());
+    match (to_epoch, &epoch) {
+      (Some(epoch), _) | (None, &TableEpoch::Const(epoch)) => {
+        new_system.set_epoch_by_ref(if to.is_fk4() {
+          julian_to_besselian(epoch)
+        } else {
+          epoch
+        })
+      }
+      (None, TableEpoch::Column { index, .. }) => {
+        let field = &fields[*index];
+        new_system.set_epoch_from_str_by_ref(field.id.as_ref().unwrap_or(&field.name))?
+      }
+      // FK4 positions default to the B1950 epoch: explicit the assumed J2000 epoch
+      (None, TableEpoch::Unknown) if to.is_fk4() => {
+        new_system.set_epoch_by_ref(julian_to_besselian(2000.0))
+      }
+      (None, TableEpoch::Unknown) => {}
+    }
+    for_each_coosys_mut(votable, &mut |coosys| {
+      if coosys_ref.as_deref() == Some(coosys.id.as_str()) || n_coosys == 1 {
+        coosys.coosys = new_system.clone();
+      }
+    });
+    let (to_lon_ucd, to_lat_ucd) = to.lon_lat_ucds();
+    let mut i_field = 0;
+    for elem in votable.get_first_table_mut().unwrap().elems.iter_mut() {
+      if let TableElem::Field(field) = elem {
+        if i_field == i_lon || pm.as_ref().is_some_and(|pm| pm.i_lon == i_field) {
+          replace_ucd_atom(field, lon_ucd, to_lon_ucd);
+        } else if i_field == i_lat || pm.as_ref().is_some_and(|pm| pm.i_lat == i_field) {
+          replace_ucd_atom(field, lat_ucd, to_lat_ucd);
+        }
+        i_field += 1;
+      }
+    }
+    Ok(Self {
+      transform,
+      i_lon,
+      i_lat,
+      pm,
+      epoch,
+      to_epoch,
+    })
+  }
+
+  pub fn transform(&self) -> &CoordTransform {
+    &self.transform
+  }
+
+  /// Transforms the position (and proper motion) of the given row. Rows with a NULL position
+  /// are left unchanged.
+  pub fn transform_row(&self, row: &mut [VOTableValue]) -> Result<(), VOTableError> {
+    let (lon, lat) = match (
+      row.get(self.i_lon).and_then(VOTableValue::as_f64),
+      row.get(self.i_lat).and_then(VOTableValue::as_f64),
+    ) {
+      (Some(lon), Some(lat)) => (lon, lat),
+      _ => return Ok(()),
+    };
+    let epoch = match &self.epoch {
+      TableEpoch::Const(epoch) => *epoch,
+      TableEpoch::Column {
+        index,
+        is_besselian,
+      } => match row[*index].as_f64() {
+        Some(epoch) if *is_besselian => besselian_to_julian(epoch),
+        Some(epoch) => epoch,
+        None => {
+          return Err(VOTableError::Custom(String::from(
+            "NULL epoch: unable to transform the position",
+          )));
+        }
+      },
+      TableEpoch::Unknown => 2000.0,
+    };
+    let pm =
+      self.pm.as_ref().and_then(
+        |pm| match (row[pm.i_lon].as_f64(), row[pm.i_lat].as_f64()) {
+          (Some(pm_lon), Some(pm_lat)) => Some((pm, pm_lon, pm_lat)),
+          _ => None,
+        },
+      );
+    match pm {
+      Some((pm, pm_lon, pm_lat)) => {
+        let (lon, lat, pm_lon, pm_lat) = self.transform.transform_with_pm(
+          lon,
+          lat,
+          pm_lon * pm.to_mas_per_year_lon,
+          pm_lat * pm.to_mas_per_year_lat,
+          epoch,
+          self.to_epoch.unwrap_or(epoch),
+        );
+        set_f64(&mut row[self.i_lon], lon);
+        set_f64(&mut row[self.i_lat], lat);
+        set_f64(&mut row[pm.i_lon], pm_lon / pm.to_mas_per_year_lon);
+        set_f64(&mut row[pm.i_lat], pm_lat / pm.to_mas_per_year_lat);
+      }
+      None => {
+        let (lon, lat) = self.transform.transform(lon, lat, epoch);
+        set_f64(&mut row[self.i_lon], lon);
+        set_f64(&mut row[self.i_lat], lat);
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Convert a Besselian epoch into a Julian epoch.
+pub fn besselian_to_julian(besselian_year: f64) -> f64 {
+  let jd = 2415020.31352 + (besselian_year - 1900.0) * TROPICAL_YEAR;
+  2000.0 + (jd - 2451545.0) / JULIAN_YEAR
+}
+
+/// Convert a Julian epoch into a Besselian epoch.
+pub fn julian_to_besselian(julian_year: f64) -> f64 {
+  let jd = 2451545.0 + (julian_year - 2000.0) * JULIAN_YEAR;
+  1900.0 + (jd - 2415020.31352) / TROPICAL_YEAR
+}
+
+/// Returns the index of the column which UCD atoms match the given predicate, giving the
+/// priority to the columns having the `meta.main` atom.
+fn find_column<F>(fields: &[Field], predicate: F) -> Option<usize>
+where
+  F: Fn(&[&str]) -> bool,
+{
+  let mut found: Option<usize> = None;
+  for (i, field) in fields.iter().enumerate() {
+    if let Some(ucd) = &field.ucd {
+      let ucd = ucd.to_lowercase();
+      let atoms: Vec<&str> = ucd.split(';').map(str::trim).collect();
+      if predicate(&atoms) {
+        if atoms.contains(&"meta.main") {
+          return Some(i);
+        } else if found.is_none() {
+          found = Some(i);
+        }
+      }
+    }
+  }
+  found
+}
+
+fn check_numeric(field: &Field) -> Result<(), VOTableError> {
+  match (&field.datatype, &field.arraysize) {
+    (Datatype::Float | Datatype::Double, None) => Ok(()),
+    _ => Err(VOTableError::Custom(format!(
+      "Column '{}' must be a float or a double scalar",
+      field.name
+    ))),
+  }
+}
+
+/// Returns the factor to convert the proper motion values of the given field in mas/yr.
+fn pm_unit_to_mas_per_year(field: &Field) -> Result<f64, VOTableError> {
+  match field.unit.as_deref().map(str::trim) {
+    None => Ok(1.0),
+    Some(unit) => unit
+      .parse::<Unit>()
+      .and_then(|unit| unit.scale_factor_to(&MAS_PER_YEAR))
+      .map_err(|e| {
+        VOTableError::Custom(format!(
+          "Unsupported proper motion unit '{}' in column '{}': {}",
+          unit, field.name, e
+        ))
+      }),
+  }
+}
+
+fn replace_ucd_atom(field: &mut Field, from: &str, to: &str) {
+  if let Some(ucd) = &field.ucd {
+    let new_ucd = ucd
+      .split(';')
+      .map(|atom| {
+        if atom.trim().eq_ignore_ascii_case(from) {
+          to
+        } else {
+          atom
+        }
+      })
+      .collect::<Vec<&str>>()
+      .join(";");
+    field.ucd = Some(new_ucd);
+  }
+}
+
+/// Set the value, keeping its type.
+fn set_f64(value: &mut VOTableValue, v: f64) {
+  *value = match value {
+    VOTableValue::Float(_) => VOTableValue::Float(v as f32),
+    _ => VOTableValue::Double(v),
+  };
+}
+
+fn for_each_coosys_mut<C, F>(votable: &mut VOTable<C>, f: &mut F)
+where
+  C: TableDataContent,
+  F: FnMut(&mut CooSys),
+{
+  fn for_each_in_resource<C, F>(resource: &mut Resource<C>, f: &mut F)
+  where
+    C: TableDataContent,
+    F: FnMut(&mut CooSys),
+  {
+    for elem in resource.elems.iter_mut() {
+      if let ResourceElem::CooSys(coosys) = elem {
+        f(coosys);
+      }
+    }
+    for sub_elem in resource.sub_elems.iter_mut() {
+      if let ResourceOrTable::Resource(resource) = &mut sub_elem.resource_or_table {
+        for_each_in_resource(resource, f);
+      }
+    }
+  }
+  for elem in votable.elems.iter_mut() {
+    if let VOTableElem::CooSys(coosys) = elem {
+      f(coosys);
+    }
+  }
+  for resource in votable.resources.iter_mut() {
+    for_each_in_resource(resource, f);
+  }
+}
+
+/// Look for the value of the `PARAM` of given `ID` (or name) in the table, the resources or the
+/// VOTable.
+fn find_param_value<C: TableDataContent>(
+  votable: &VOTable<C>,
+  table_elems: &[TableElem],
+  reference: &str,
+) -> Result<String, VOTableError> {
+  fn find_in_resource<C: TableDataContent>(
+    resource: &Resource<C>,
+    reference: &str,
+  ) -> Option<String> {
+    resource
+      .elems
+      .iter()
+      .find_map(|elem| match elem {
+        ResourceElem::Param(param) if is_ref(&param.field, reference) => Some(param.value.clone()),
+        _ => None,
+      })
+      .or_else(|| {
+        resource
+          .sub_elems
+          .iter()
+          .find_map(|sub_elem| match &sub_elem.resource_or_table {
+            ResourceOrTable::Resource(resource) => find_in_resource(resource, reference),
+            _ => None,
+          })
+      })
+  }
+  fn is_ref(field: &Field, reference: &str) -> bool {
+    field.id.as_deref() == Some(reference) || field.name == reference
+  }
+  table_elems
+    .iter()
+    .find_map(|elem| match elem {
+      TableElem::Param(param) if is_ref(&param.field, reference) => Some(param.value.clone()),
+      _ => None,
+    })
+    .or_else(|| {
+      votable
+        .resources
+        .iter()
+        .find_map(|resource| find_in_resource(resource, reference))
+    })
+    .or_else(|| {
+      votable.elems.iter().find_map(|elem| match elem {
+        VOTableElem::Param(param) if is_ref(&param.field, reference) => Some(param.value.clone()),
+        _ => None,
+      })
+    })
+    .ok_or_else(|| {
+      VOTableError::Custom(format!(
+        "PARAM or FIELD '{}' referenced in COOSYS not found",
+        reference
+      ))
+    })
+}
+
+/// Unit position vector and its time derivative (in radians per year).
+#[derive(Clone, Copy, Debug)]
+struct PosVel {
+  p: V3,
+  v: V3,
+}
+
+impl PosVel {
+  /// `pm_lon` includes the `cos(lat)` factor.
+  fn from_sph(lon: f64, lat: f64, pm_lon: f64, pm_lat: f64) -> Self {
+    let (e_lon, e_lat) = tangent_basis(lon, lat);
+    Self {
+      p: sph2cart(lon, lat),
+      v: add(&scale(&e_lon, pm_lon), &scale(&e_lat, pm_lat)),
+    }
+  }
+
+  fn to_sph(self) -> (f64, f64, f64, f64) {
+    let (lon, lat) = cart2sph(&self.p);
+    let (e_lon, e_lat) = tangent_basis(lon, lat);
+    (lon, lat, dot(&self.v, &e_lon), dot(&self.v, &e_lat))
+  }
+
+  fn rotate(&self, m: &M3) -> Self {
+    Self {
+      p: mxv(m, &self.p),
+      v: mxv(m, &self.v),
+    }
+  }
+
+  fn rotate_t(&self, m: &M3) -> Self {
+    Self {
+      p: mtxv(m, &self.p),
+      v: mtxv(m, &self.v),
+    }
+  }
+
+  /// Linear propagation of the given number of years.
+  fn propagate(&self, dt: f64) -> Self {
+    if dt == 0.0 {
+      *self
+    } else {
+      let p = add(&self.p, &scale(&self.v, dt));
+      let n = norm(&p);
+      let p = scale(&p, 1.0 / n);
+      Self {
+        p,
+        v: scale(&tangential(&self.v, &p), 1.0 / n),
+      }
+    }
+  }
+}
+
+/// FK4 (B1950.0) to FK5 (J2000.0), cf. SOFA `fk425` (with null parallax and radial velocity).
+/// Input velocity in radians per tropical year, output velocity in radians per Julian year.
+fn fk425(p: &V3, v: &V3) -> (V3, V3) {
+  let v = scale(v, PMF);
+  // Allow for E-terms
+  let r1p = add(&sub(p, &A), &scale(p, dot(p, &A)));
+  let r1v = add(&sub(&v, &AD), &scale(p, dot(p, &AD)));
+  // Convert to the Fricke system
+  let mut pv2 = [[0.0; 3]; 2];
+  for (i, pv2i) in pv2.iter_mut().enumerate() {
+    for (j, pv2ij) in pv2i.iter_mut().enumerate() {
+      *pv2ij = dot(&EM[i][j][0], &r1p) + dot(&EM[i][j][1], &r1v);
+    }
+  }
+  let w = norm(&pv2[0]);
+  let p2 = scale(&pv2[0], 1.0 / w);
+  (p2, scale(&tangential(&pv2[1], &p2), 1.0 / (w * PMF)))
+}
+
+/// FK5 (J2000.0) to FK4 (B1950.0), i.e. the inverse of `fk425`, computed iteratively.
+/// Input velocity in radians per Julian year, output velocity in radians per tropical year.
+fn fk524(p5: &V3, v5: &V3) -> (V3, V3) {
+  let m: M3 = [EM[0][0][0], EM[0][1][0], EM[0][2][0]];
+  let mut p4 = normalize(&mtxv(&m, p5));
+  let mut v4 = tangential(&mtxv(&m, v5), &p4);
+  for _ in 0..20 {
+    let (p, v) = fk425(&p4, &v4);
+    let dp = sub(p5, &p);
+    let dv = sub(v5, &v);
+    p4 = normalize(&add(&p4, &mtxv(&m, &dp)));
+    v4 = tangential(&add(&v4, &mtxv(&m, &dv)), &p4);
+    if norm(&dp) < 1e-15 && norm(&dv) < 1e-20 {
+      break;
+    }
+  }
+  (p4, v4)
+}
+
+/// FK4 (B1950.0) position at the given Besselian epoch, assuming no proper motion in FK5, to
+/// FK5 (J2000.0) position, cf. SOFA `fk45z`.
+fn fk45z(p: &V3, besselian_epoch: f64) -> V3 {
+  // Adjust vector A to give zero proper motion in FK5
+  let a = add(&A, &scale(&AD, (besselian_epoch - 1950.0) / PMF));
+  // Remove E-terms
+  let r = sub(p, &sub(&a, &scale(p, dot(p, &a))));
+  // Convert to the Fricke system
+  let mut pv = [[0.0; 3]; 2];
+  for (i, pvi) in pv.iter_mut().enumerate() {
+    for (j, pvij) in pvi.iter_mut().enumerate() {
+      *pvij = dot(&EM[i][j][0], &r);
+    }
+  }
+  // Allow for fictitious proper motion
+  let dt = (besselian_to_julian(besselian_epoch) - 2000.0) / PMF;
+  normalize(&add(&pv[0], &scale(&pv[1], dt)))
+}
+
+/// FK5 (J2000.0) position, assuming no proper motion in FK5, to FK4 (B1950.0) position at the
+/// given Besselian epoch, cf. SOFA `fk54z`.
+fn fk54z(p: &V3, besselian_epoch: f64) -> V3 {
+  let (p, v) = fk524(p, &[0.0; 3]);
+  normalize(&add(&p, &scale(&v, besselian_epoch - 1950.0)))
+}
+
+/// IAU 1976 precession matrix from FK5 J2000 to the given (Julian) equinox.
+fn prec_fk5(equinox: f64) -> M3 {
+  if equinox == 2000.0 {
+    return [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+  }
+  let t = (equinox - 2000.0) / 100.0;
+  let zeta = (2306.2181 + (0.30188 + 0.017998 * t) * t) * t * ARCSEC2RAD;
+  let z = (2306.2181 + (1.09468 + 0.018203 * t) * t) * t * ARCSEC2RAD;
+  let theta = (2004.3109 - (0.42665 + 0.041833 * t) * t) * t * ARCSEC2RAD;
+  mxm(&rz(-z), &mxm(&ry(theta), &rz(-zeta)))
+}
+
+/// Rotation from FK5 equatorial to ecliptic coordinates at the given (Julian) equinox.
+fn ecl_fk5(equinox: f64) -> M3 {
+  rx(obliquity(equinox))
+}
+
+/// Rotation from FK4 equatorial to ecliptic coordinates at the given (Besselian) equinox.
+fn ecl_fk4(equinox: f64) -> M3 {
+  rx(obliquity(besselian_to_julian(equinox)))
+}
+
+/// IAU 1976 mean obliquity of the ecliptic, in radians, at the given Julian epoch.
+fn obliquity(julian_year: f64) -> f64 {
+  let t = (julian_year - 2000.0) / 100.0;
+  (84381.448 + (-46.8150 + (-0.00059 + 0.001813 * t) * t) * t) * ARCSEC2RAD
+}
+
+/// Rotation vector to rotation matrix, cf. SOFA `rv2m`.
+fn rv2m(w: &V3) -> M3 {
+  let phi = norm(w);
+  let (s, c) = phi.sin_cos();
+  let f = 1.0 - c;
+  let [x, y, z] = if phi > 0.0 { scale(w, 1.0 / phi) } else { *w };
+  [
+    [x * x * f + c, x * y * f + z * s, x * z * f - y * s],
+    [y * x * f - z * s, y * y * f + c, y * z * f + x * s],
+    [z * x * f + y * s, z * y * f - x * s, z * z * f + c],
+  ]
+}
+
+fn rx(phi: f64) -> M3 {
+  let (s, c) = phi.sin_cos();
+  [[1.0, 0.0, 0.0], [0.0, c, s], [0.0, -s, c]]
+}
+
+fn ry(theta: f64) -> M3 {
+  let (s, c) = theta.sin_cos();
+  [[c, 0.0, -s], [0.0, 1.0, 0.0], [s, 0.0, c]]
+}
+
+fn rz(psi: f64) -> M3 {
+  let (s, c) = psi.sin_cos();
+  [[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]]
+}
+
+fn sph2cart(lon: f64, lat: f64) -> V3 {
+  let (slon, clon) = lon.sin_cos();
+  let (slat, clat) = lat.sin_cos();
+  [clat * clon, clat * slon, slat]
+}
+
+/// Returns the longitude in `[0, 2pi[` and the latitude in `[-pi/2, pi/2]`.
+fn cart2sph(p: &V3) -> (f64, f64) {
+  let lon = p[1].atan2(p[0]);
+  let lat = p[2].atan2(p[0].hypot(p[1]));
+  (if lon < 0.0 { lon + 2.0 * PI } else { lon }, lat)
+}
+
+/// Unit vectors in the direction of increasing longitude and increasing latitude.
+fn tangent_basis(lon: f64, lat: f64) -> (V3, V3) {
+  let (slon, clon) = lon.sin_cos();
+  let (slat, clat) = lat.sin_cos();
+  ([-slon, clon, 0.0], [-slat * clon, -slat * slon, clat])
+}
+
+fn mxv(m: &M3, v: &V3) -> V3 {
+  [dot(&m[0], v), dot(&m[1], v), dot(&m[2], v)]
+}
+
+fn mtxv(m: &M3, v: &V3) -> V3 {
+  [
+    m[0][0] * v[0] + m[1][0] * v[1] + m[2][0] * v[2],
+    m[0][1] * v[0] + m[1][1] * v[1] + m[2][1] * v[2],
+    m[0][2] * v[0] + m[1][2] * v[1] + m[2][2] * v[2],
+  ]
+}
+
+fn mxm(a: &M3, b: &M3) -> M3 {
+  let mut m = [[0.0; 3]; 3];
+  for (i, row) in m.iter_mut().enumerate() {
+    for (j, mij) in row.iter_mut().enumerate() {
+      *mij = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+    }
+  }
+  m
+}
+
+fn dot(a: &V3, b: &V3) -> f64 {
+  a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: &V3, b: &V3) -> V3 {
+  [
+    a[1] * b[2] - a[2] * b[1],
+    a[2] * b[0] - a[0] * b[2],
+    a[0] * b[1] - a[1] * b[0],
+  ]
+}
+
+fn add(a: &V3, b: &V3) -> V3 {
+  [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: &V3, b: &V3) -> V3 {
+  [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: &V3, s: f64) -> V3 {
+  [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn norm(a: &V3) -> f64 {
+  dot(a, a).sqrt()
+}
+
+fn normalize(a: &V3) -> V3 {
+  scale(a, 1.0 / norm(a))
+}
+
+/// Component of `v` orthogonal to the unit vector `p`.
+fn tangential(v: &V3, p: &V3) -> V3 {
+  sub(v, &scale(p, dot(v, p)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{VOTableError, iter::SimpleVOTableRowIterator};
+
+  /// Angular distance, in arcsec, between two positions in degrees.
+  fn dist_arcsec(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let pa = sph2cart(a.0 * DEG2RAD, a.1 * DEG2RAD);
+    let pb = sph2cart(b.0 * DEG2RAD, b.1 * DEG2RAD);
+    norm(&cross(&pa, &pb)).atan2(dot(&pa, &pb)) / ARCSEC2RAD
+  }
+
+  fn transform(from: Frame, to: Frame, lon: f64, lat: f64) -> (f64, f64) {
+    CoordTransform::new(from, to)
+      .unwrap()
+      .transform(lon, lat, 2000.0)
+  }
+
+  #[test]
+  fn test_galactic() {
+    // Galactic center and north galactic pole
+    let gc = transform(Frame::ICRS, Frame::Galactic, 266.40499, -28.93617);
+    assert!(dist_arcsec(gc, (0.0, 0.0)) < 0.1);
+    let ngp = transform(Frame::Galactic, Frame::ICRS, 0.0, 90.0);
+    assert!(dist_arcsec(ngp, (192.85948, 27.12825)) < 1e-6);
+    // Supergalactic north pole
+    let sgp = transform(Frame::SuperGalactic, Frame::Galactic, 0.0, 90.0);
+    assert!(dist_arcsec(sgp, (47.37, 6.32)) < 1e-6);
+  }
+
+  #[test]
+  fn test_fk5_ecliptic() {
+    // FK5 J2000 differs from ICRS by a few tens of mas
+    let d = dist_arcsec(
+      transform(Frame::ICRS, Frame::FK5 { equinox: 2000.0 }, 10.0, 20.0),
+      (10.0, 20.0),
+    );
+    assert!(d > 0.01 && d < 0.03);
+    // Vernal equinox and north ecliptic pole
+    let fk5 = Frame::FK5 { equinox: 2000.0 };
+    let ecl = Frame::EclipticFK5 { equinox: 2000.0 };
+    assert!(dist_arcsec(transform(fk5, ecl, 0.0, 0.0), (0.0, 0.0)) < 1e-6);
+    let nep = transform(ecl, fk5, 0.0, 90.0);
+    assert!(dist_arcsec(nep, (270.0, 90.0 - 84381.448 / 3600.0)) < 1e-6);
+    // Precession of about 50.3 arcsec/yr in ecliptic longitude
+    let (lon, _) = transform(fk5, Frame::EclipticFK5 { equinox: 2050.0 }, 0.0, 0.0);
+    assert!((lon * 3600.0 / 50.0 - 50.29).abs() < 0.01);
+  }
+
+  #[test]
+  fn test_fk4() {
+    // The galactic frame is defined (IAU 1958) in FK4 B1950 by the north galactic pole at
+    // (192.25, 27.4) and the galactic center at (265.6108333, -28.9166667)
+    let epoch = besselian_to_julian(1950.0);
+    let t = CoordTransform::new(Frame::Galactic, Frame::FK4 { equinox: 1950.0 }).unwrap();
+    assert!(dist_arcsec(t.transform(0.0, 90.0, epoch), (192.25, 27.4)) < 0.5);
+    assert!(dist_arcsec(t.transform(0.0, 0.0, epoch), (265.6108333, -28.9166667)) < 0.5);
+    // Back and forth
+    let fk4 = Frame::FK4 { equinox: 1950.0 };
+    let fk5 = Frame::FK5 { equinox: 2000.0 };
+    let (ra, dec) = CoordTransform::new(fk4, fk5)
+      .unwrap()
+      .transform(10.0, -20.0, epoch);
+    assert!(dist_arcsec((ra, dec), (10.0, -20.0)) > 100.0);
+    let (ra, dec) = CoordTransform::new(fk5, fk4)
+      .unwrap()
+      .transform(ra, dec, epoch);
+    assert!(dist_arcsec((ra, dec), (10.0, -20.0)) < 1e-6);
+    // Only B1950 is supported
+    assert!(CoordTransform::new(Frame::FK4 { equinox: 1900.0 }, fk5).is_err());
+    assert!("fk4".parse::<Frame>().unwrap() == fk4);
+  }
+
+  #[test]
+  fn test_pm_round_trip() {
+    let t = CoordTransform::new(Frame::ICRS, Frame::FK4 { equinox: 1950.0 }).unwrap();
+    let t_inv = CoordTransform::new(Frame::FK4 { equinox: 1950.0 }, Frame::ICRS).unwrap();
+    let (ra, dec, pm_ra, pm_dec) = t.transform_with_pm(120.0, 45.0, 500.0, -300.0, 2016.0, 2016.0);
+    let (ra, dec, pm_ra, pm_dec) = t_inv.transform_with_pm(ra, dec, pm_ra, pm_dec, 2016.0, 2016.0);
+    assert!(dist_arcsec((ra, dec), (120.0, 45.0)) < 1e-4);
+    assert!((pm_ra - 500.0).abs() < 1e-3 && (pm_dec + 300.0).abs() < 1e-3);
+    // Pure propagation
+    let t = CoordTransform::new(Frame::ICRS, Frame::ICRS).unwrap();
+    let (ra, dec, _, _) = t.transform_with_pm(0.0, 0.0, 0.0, 1000.0, 2000.0, 2010.0);
+    assert!(dist_arcsec((ra, dec), (0.0, 10.0 / 3600.0)) < 1e-6);
+  }
+
+  #[test]
+  fn test_table_frame_converter() {
+    let mut it = SimpleVOTableRowIterator::from_file("resources/sdss12.vot").unwrap();
+    let converter = TableFrameConverter::new(&mut it.votable, Frame::Galactic, None).unwrap();
+    let rows = it
+      .to_row_value_iter()
+      .collect::<Result<Vec<_>, VOTableError>>()
+      .unwrap();
+    let mut votable = it.read_to_end().unwrap();
+    let mut galactic = 0;
+    for_each_coosys_mut(&mut votable, &mut |coosys| {
+      if coosys.coosys == System::new_galactic() {
+        galactic += 1;
+      }
+    });
+    assert_eq!(galactic, 1);
+    let table = votable.get_first_table().unwrap();
+    match &table.elems[0] {
+      TableElem::Field(field) => {
+        assert_eq!(field.ucd.as_deref(), Some("pos.galactic.lon;meta.main"))
+      }
+      _ => unreachable!(),
+    }
+    let mut row = rows[0].clone();
+    converter.transform_row(&mut row).unwrap();
+    let (ra, dec) = (rows[0][0].as_f64().unwrap(), rows[0][1].as_f64().unwrap());
+    let (l, b) = (row[0].as_f64().unwrap(), row[1].as_f64().unwrap());
+    assert!(dist_arcsec(transform(Frame::ICRS, Frame::Galactic, ra, dec), (l, b)) < 1e-6);
+  }
+}
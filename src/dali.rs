@@ -0,0 +1,271 @@
+//! Helpers for [DALI](https://www.ivoa.net/documents/DALI/) (TAP, SSA, SIA, ...) results:
+//! the status of the query is given by an `INFO` named `QUERY_STATUS` in the `results`
+//! `RESOURCE`, possibly repeated after the `TABLE` (e.g. `OVERFLOW` in case of truncation).
+//!
+//! The status of a VOTable is the one of the last `QUERY_STATUS` `INFO` in document order,
+//! so that a trailing `OVERFLOW` takes precedence over a leading `OK`.
+//! In streaming mode, the trailing `INFO`s are available in the VOTable returned by
+//! `SimpleVOTableRowIterator::read_to_end`.
+
+use std::{
+  fmt::{self, Display, Formatter},
+  str::FromStr,
+};
+
+use crate::{
+  error::VOTableError,
+  info::Info,
+  resource::{Resource, ResourceOrTable},
+  votable::{VOTable, VOTableElem, Version},
+  HasContent, TableDataContent,
+};
+
+/// Name of the `INFO` giving the status of a query.
+pub const QUERY_STATUS: &str = "QUERY_STATUS";
+/// `type` of the `RESOURCE` containing the results of a query.
+pub const RESULTS_RESOURCE_TYPE: &str = "results";
+
+/// Status of a query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryStatus {
+  /// The query executed successfully
+  Ok,
+  /// An error occurred, the content of the `INFO` contains the error message
+  Error,
+  /// The result has been truncated (e.g. because of the `MAXREC` limit)
+  Overflow,
+}
+
+impl QueryStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::Ok => "OK",
+      Self::Error => "ERROR",
+      Self::Overflow => "OVERFLOW",
+    }
+  }
+}
+
+impl FromStr for QueryStatus {
+  type Err = VOTableError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "OK" => Ok(Self::Ok),
+      "ERROR" => Ok(Self::Error),
+      "OVERFLOW" => Ok(Self::Overflow),
+      _ => Err(VOTableError::Custom(format!(
+        "Unknown QUERY_STATUS value. Actual: '{}'. Expected: 'OK', 'ERROR' or 'OVERFLOW'.",
+        s
+      ))),
+    }
+  }
+}
+
+impl Display for QueryStatus {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+/// Returns the `INFO` giving the query status, with the given status and optional message.
+pub fn new_query_status_info(status: QueryStatus, message: Option<&str>) -> Info {
+  let info = Info::new(QUERY_STATUS, status.as_str());
+  match message {
+    Some(message) => info.set_content(message),
+    None => info,
+  }
+}
+
+/// Parse the status of the given `QUERY_STATUS` `INFO`.
+fn parse_status(info: &Info) -> Result<(QueryStatus, &Info), VOTableError> {
+  info.value.parse().map(|status| (status, info))
+}
+
+fn is_query_status(info: &Info) -> bool {
+  info.name == QUERY_STATUS
+}
+
+impl<C: TableDataContent> Resource<C> {
+  /// Calls the given function on all the `INFO`s of the resource, in document order.
+  fn for_each_info<'a, F>(&'a self, f: &mut F)
+  where
+    F: FnMut(&'a Info),
+  {
+    self.infos.iter().for_each(&mut *f);
+    for sub_elem in self.sub_elems.iter() {
+      match &sub_elem.resource_or_table {
+        ResourceOrTable::Resource(resource) => resource.for_each_info(f),
+        ResourceOrTable::Table(table) => {
+          table.infos.iter().for_each(&mut *f);
+          table.post_infos.iter().for_each(&mut *f);
+        }
+      }
+      sub_elem.infos.iter().for_each(&mut *f);
+    }
+  }
+
+  /// Returns the last `QUERY_STATUS` `INFO` of the resource (in document order), if any.
+  pub fn query_status_info(&self) -> Option<&Info> {
+    let mut last = None;
+    self.for_each_info(&mut |info| {
+      if is_query_status(info) {
+        last = Some(info);
+      }
+    });
+    last
+  }
+
+  /// Returns the query status of the resource and the `INFO` it comes from (which may contain
+  /// a message), if any.
+  /// # Errors
+  /// If the `QUERY_STATUS` value is not valid.
+  pub fn query_status(&self) -> Result<Option<(QueryStatus, &Info)>, VOTableError> {
+    self.query_status_info().map(parse_status).transpose()
+  }
+
+  /// Set the query status of the resource: the `QUERY_STATUS` `INFO`s of the resource (including
+  /// the ones following its tables) are removed and replaced by a single `INFO` placed before the
+  /// other `INFO`s of the resource.
+  pub fn set_query_status(&mut self, status: QueryStatus, message: Option<&str>) {
+    fn remove_status<C: TableDataContent>(resource: &mut Resource<C>) {
+      resource.infos.retain(|info| !is_query_status(info));
+      for sub_elem in resource.sub_elems.iter_mut() {
+        sub_elem.infos.retain(|info| !is_query_status(info));
+        match &mut sub_elem.resource_or_table {
+          ResourceOrTable::Resource(resource) => remove_status(resource),
+          ResourceOrTable::Table(table) => {
+            table.infos.retain(|info| !is_query_status(info));
+            table.post_infos.retain(|info| !is_query_status(info));
+          }
+        }
+      }
+    }
+    remove_status(self);
+    self.infos.insert(0, new_query_status_info(status, message));
+  }
+}
+
+impl<C: TableDataContent> VOTable<C> {
+  /// Returns the last `QUERY_STATUS` `INFO` of the VOTable (in document order), if any.
+  pub fn query_status_info(&self) -> Option<&Info> {
+    let last = self.elems.iter().rev().find_map(|elem| match elem {
+      VOTableElem::Info(info) if is_query_status(info) => Some(info.as_ref()),
+      _ => None,
+    });
+    let last = self
+      .resources
+      .iter()
+      .rev()
+      .find_map(|resource| resource.query_status_info())
+      .or(last);
+    self
+      .post_infos
+      .iter()
+      .rev()
+      .find(|info| is_query_status(info))
+      .or(last)
+  }
+
+  /// Returns the query status of the VOTable and the `INFO` it comes from (which may contain
+  /// a message), if any.
+  /// # Errors
+  /// If the `QUERY_STATUS` value is not valid.
+  pub fn query_status(&self) -> Result<Option<(QueryStatus, &Info)>, VOTableError> {
+    self.query_status_info().map(parse_status).transpose()
+  }
+
+  /// Set the query status in the first `results` resource (or in the first resource if no
+  /// resource has `type="results"`), removing the `QUERY_STATUS` `INFO`s of the VOTable.
+  pub fn set_query_status(&mut self, status: QueryStatus, message: Option<&str>) {
+    self.elems.retain(|elem| match elem {
+      VOTableElem::Info(info) => !is_query_status(info),
+      _ => true,
+    });
+    self.post_infos.retain(|info| !is_query_status(info));
+    for resource in self.resources.iter_mut() {
+      resource.infos.retain(|info| !is_query_status(info));
+    }
+    let index = self
+      .resources
+      .iter()
+      .position(|resource| resource.type_.as_deref() == Some(RESULTS_RESOURCE_TYPE))
+      .unwrap_or(0);
+    match self.resources.get_mut(index) {
+      Some(resource) => resource.set_query_status(status, message),
+      None => self.push_resource_by_ref(
+        Resource::new()
+          .set_type(RESULTS_RESOURCE_TYPE)
+          .push_info(new_query_status_info(status, message)),
+      ),
+    }
+  }
+
+  /// Tells whether the result is truncated, i.e. whether the query status is `OVERFLOW` or,
+  /// if `maxrec` is provided, whether the number of rows reached `maxrec`.
+  pub fn is_truncated(&self, n_rows: u64, maxrec: Option<u64>) -> bool {
+    matches!(self.query_status(), Ok(Some((QueryStatus::Overflow, _))))
+      || maxrec.map(|maxrec| n_rows >= maxrec).unwrap_or(false)
+  }
+
+  /// Create a DALI error document, i.e. a VOTable containing a single `results` resource with a
+  /// `QUERY_STATUS` `INFO` of value `ERROR` containing the given error message.
+  pub fn new_error_document<S: AsRef<str>>(message: S) -> Self {
+    VOTable::new(
+      Version::V1_4,
+      Resource::new()
+        .set_type(RESULTS_RESOURCE_TYPE)
+        .push_info(new_query_status_info(
+          QueryStatus::Error,
+          Some(message.as_ref()),
+        )),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    impls::mem::{InMemTableDataRows, VoidTableDataContent},
+    iter::SimpleVOTableRowIterator,
+    votable::VOTableWrapper,
+  };
+
+  #[test]
+  fn test_query_status() {
+    // Trailing OVERFLOW read in streaming mode
+    let mut it = SimpleVOTableRowIterator::from_file("resources/sdss12.vot").unwrap();
+    assert_eq!(it.votable.query_status().unwrap(), None);
+    it.skip_remaining_data().unwrap();
+    let votable = it.read_to_end().unwrap();
+    let (status, info) = votable.query_status().unwrap().unwrap();
+    assert_eq!(status, QueryStatus::Overflow);
+    assert_eq!(info.get_content(), Some("truncated result (maxtup=50)"));
+    assert!(votable.is_truncated(50, None));
+
+    let mut votable =
+      VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_file("resources/obscore.vot")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+      votable.query_status().unwrap().map(|(s, _)| s),
+      Some(QueryStatus::Ok)
+    );
+    assert!(!votable.is_truncated(10, Some(100)));
+    assert!(votable.is_truncated(100, Some(100)));
+    votable.set_query_status(QueryStatus::Overflow, None);
+    assert_eq!(
+      votable.query_status().unwrap().map(|(s, _)| s),
+      Some(QueryStatus::Overflow)
+    );
+
+    let error = VOTable::<VoidTableDataContent>::new_error_document("Syntax error");
+    let (status, info) = error.query_status().unwrap().unwrap();
+    assert_eq!(status, QueryStatus::Error);
+    assert_eq!(info.get_content(), Some("Syntax error"));
+    let xml = error.wrap().to_ivoa_xml_string().unwrap();
+    assert!(xml.contains(r#"<RESOURCE type="results">"#));
+    assert!(xml.contains(r#"<INFO name="QUERY_STATUS" value="ERROR">Syntax error</INFO>"#));
+  }
+}
@@ -0,0 +1,646 @@
+//! Module dedicated to [DataLink](https://www.ivoa.net/documents/DataLink/) responses:
+//! * typed access to the **service descriptors**, i.e. the `RESOURCE`s having `type="meta"`
+//!   and `utype="adhoc:service"`, with their `accessURL`, `standardID`, `resourceIdentifier`
+//!   `PARAM`s and their `inputParams` `GROUP`;
+//! * typed access to the rows of the DataLink **results table** (`ID`, `access_url`,
+//!   `service_def`, `error_message`, `semantics`, ...).
+//!
+//! Query URLs can be built from the input parameters of a service descriptor (no network access
+//! is made by this module).
+
+use std::fmt::Write;
+
+use log::warn;
+use paste::paste;
+
+use crate::{
+  datatype::Datatype,
+  desc::Description,
+  error::VOTableError,
+  field::{ArraySize, Field},
+  group::{Group, GroupElem},
+  impls::VOTableValue,
+  param::Param,
+  resource::{Resource, ResourceElem, ResourceOrTable},
+  table::TableElem,
+  votable::VOTable,
+  HasContent, TableDataContent,
+};
+
+/// `type` of a service descriptor `RESOURCE`.
+pub const SERVICE_RESOURCE_TYPE: &str = "meta";
+/// `utype` of a service descriptor `RESOURCE`.
+pub const SERVICE_RESOURCE_UTYPE: &str = "adhoc:service";
+/// Name of the `GROUP` containing the input parameters of a service descriptor.
+pub const INPUT_PARAMS_GROUP_NAME: &str = "inputParams";
+
+const ACCESS_URL: &str = "accessURL";
+const STANDARD_ID: &str = "standardID";
+const RESOURCE_IDENTIFIER: &str = "resourceIdentifier";
+const CONTENT_TYPE: &str = "contentType";
+
+/// Typed view of a DataLink service descriptor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServiceDescriptor {
+  /// `ID` of the `RESOURCE`, used in the `service_def` column of the results table
+  pub id: Option<String>,
+  pub name: Option<String>,
+  pub description: Option<String>,
+  /// URL of the service (`accessURL` `PARAM`)
+  pub access_url: String,
+  /// Standard implemented by the service, e.g. `ivo://ivoa.net/std/SODA#sync-1.0`
+  pub standard_id: Option<String>,
+  /// Registry identifier of the service
+  pub resource_identifier: Option<String>,
+  /// MIME type of the service response
+  pub content_type: Option<String>,
+  /// Input parameters (`PARAM`s of the `inputParams` `GROUP`)
+  pub params: Vec<Param>,
+}
+
+impl ServiceDescriptor {
+  pub fn new<S: Into<String>>(access_url: S) -> Self {
+    Self {
+      id: None,
+      name: None,
+      description: None,
+      access_url: access_url.into(),
+      standard_id: None,
+      resource_identifier: None,
+      content_type: None,
+      params: vec![],
+    }
+  }
+
+  impl_builder_opt_string_attr!(id);
+  impl_builder_opt_string_attr!(name);
+  impl_builder_opt_string_attr!(description);
+  impl_builder_opt_string_attr!(standard_id);
+  impl_builder_opt_string_attr!(resource_identifier);
+  impl_builder_opt_string_attr!(content_type);
+
+  impl_builder_push!(Param);
+
+  /// Tells whether the given `RESOURCE` is a service descriptor.
+  pub fn is_service_descriptor<C: TableDataContent>(resource: &Resource<C>) -> bool {
+    resource.type_.as_deref() == Some(SERVICE_RESOURCE_TYPE)
+      && resource.utype.as_deref() == Some(SERVICE_RESOURCE_UTYPE)
+  }
+
+  /// Build a service descriptor from the given `RESOURCE`.
+  /// # Errors
+  /// If the `RESOURCE` is not a service descriptor or has no `accessURL` `PARAM`.
+  pub fn from_resource<C: TableDataContent>(resource: &Resource<C>) -> Result<Self, VOTableError> {
+    if !Self::is_service_descriptor(resource) {
+      return Err(VOTableError::Custom(format!(
+        "RESOURCE {}is not a service descriptor (type=\"{}\" and utype=\"{}\" expected)",
+        resource
+          .id
+          .as_ref()
+          .map(|id| format!("'{}' ", id))
+          .unwrap_or_default(),
+        SERVICE_RESOURCE_TYPE,
+        SERVICE_RESOURCE_UTYPE
+      )));
+    }
+    let mut access_url: Option<String> = None;
+    let mut descriptor = Self::new("");
+    descriptor.id = resource.id.clone();
+    descriptor.name = resource.name.clone();
+    descriptor.description = resource
+      .description
+      .as_ref()
+      .and_then(|desc| desc.get_content())
+      .map(String::from);
+    for elem in resource.elems.iter() {
+      match elem {
+        ResourceElem::Param(param) => {
+          let value = Some(param.value.clone());
+          match param.field.name.as_str() {
+            ACCESS_URL => access_url = value,
+            STANDARD_ID => descriptor.standard_id = value,
+            RESOURCE_IDENTIFIER => descriptor.resource_identifier = value,
+            CONTENT_TYPE => descriptor.content_type = value,
+            _ => (),
+          }
+        }
+        ResourceElem::Group(group) if group.name.as_deref() == Some(INPUT_PARAMS_GROUP_NAME) => {
+          for group_elem in group.elems.iter() {
+            match group_elem {
+              GroupElem::Param(param) => descriptor.params.push(param.as_ref().clone()),
+              _ => warn!("Only PARAMs are supported in the 'inputParams' GROUP. Element ignored."),
+            }
+          }
+        }
+        _ => (),
+      }
+    }
+    descriptor.access_url = access_url.ok_or_else(|| {
+      VOTableError::Custom(format!(
+        "No 'accessURL' PARAM in service descriptor{}",
+        resource
+          .id
+          .as_ref()
+          .map(|id| format!(" '{}'", id))
+          .unwrap_or_default()
+      ))
+    })?;
+    Ok(descriptor)
+  }
+
+  /// Build the `RESOURCE` corresponding to this service descriptor.
+  pub fn to_resource<C: TableDataContent>(&self) -> Resource<C> {
+    fn char_param(name: &str, value: &str) -> Param {
+      Param::new(name, Datatype::CharASCII, value).set_arraysize(ArraySize::new_variable_1d())
+    }
+    let mut resource = Resource::<C>::new()
+      .set_type(SERVICE_RESOURCE_TYPE)
+      .set_utype(SERVICE_RESOURCE_UTYPE);
+    if let Some(id) = &self.id {
+      resource.set_id_by_ref(id);
+    }
+    if let Some(name) = &self.name {
+      resource.set_name_by_ref(name);
+    }
+    if let Some(description) = &self.description {
+      resource.set_description_by_ref(Description::new(description));
+    }
+    for (name, value) in [
+      (RESOURCE_IDENTIFIER, &self.resource_identifier),
+      (STANDARD_ID, &self.standard_id),
+      (ACCESS_URL, &Some(self.access_url.clone())),
+      (CONTENT_TYPE, &self.content_type),
+    ] {
+      if let Some(value) = value {
+        resource.push_param_by_ref(char_param(name, value));
+      }
+    }
+    if !self.params.is_empty() {
+      let group = self.params.iter().cloned().fold(
+        Group::new().set_name(INPUT_PARAMS_GROUP_NAME),
+        |group, param| group.push_param(param),
+      );
+      resource.push_group_by_ref(group);
+    }
+    resource
+  }
+
+  /// Returns the input parameter of given name, if any.
+  pub fn param(&self, name: &str) -> Option<&Param> {
+    self.params.iter().find(|param| param.field.name == name)
+  }
+
+  /// Returns the `(name, value)` of the input parameters referencing (`ref` attribute) a
+  /// column of the results table, the value being taken from the given row.
+  /// # Params
+  /// * `elems`: the elements of the results table
+  /// * `row`: a row of the results table
+  pub fn row_param_values(
+    &self,
+    elems: &[TableElem],
+    row: &[VOTableValue],
+  ) -> Vec<(String, String)> {
+    let fields: Vec<&Field> = elems
+      .iter()
+      .filter_map(|elem| match elem {
+        TableElem::Field(field) => Some(field),
+        _ => None,
+      })
+      .collect();
+    self
+      .params
+      .iter()
+      .filter_map(|param| {
+        let ref_ = param.field.ref_.as_deref()?;
+        let i = fields
+          .iter()
+          .position(|field| field.id.as_deref() == Some(ref_))
+          .or_else(|| fields.iter().position(|field| field.name == ref_))?;
+        value_to_opt_string(row.get(i)?).map(|value| (param.field.name.clone(), value))
+      })
+      .collect()
+  }
+
+  /// Build the (HTTP GET) query URL from the given `(name, value)` input parameter values.
+  /// Input parameters having a non-empty `value` attribute are used with this value, unless a
+  /// value is provided. A same parameter can be provided several times.
+  /// # Errors
+  /// If a given parameter name is not an input parameter of the service.
+  pub fn query_url<'a, I>(&self, values: I) -> Result<String, VOTableError>
+  where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+  {
+    let values: Vec<(&str, &str)> = values.into_iter().collect();
+    if let Some((name, _)) = values.iter().find(|(name, _)| self.param(name).is_none()) {
+      return Err(VOTableError::Custom(format!(
+        "Parameter '{}' not in the service descriptor input parameters",
+        name
+      )));
+    }
+    let mut url = self.access_url.clone();
+    let mut sep = if url.contains('?') { '&' } else { '?' };
+    for param in self.params.iter() {
+      let name = param.field.name.as_str();
+      let mut provided = values.iter().filter(|(n, _)| *n == name).peekable();
+      let param_values: Vec<&str> = if provided.peek().is_some() {
+        provided.map(|(_, v)| *v).collect()
+      } else if !param.value.is_empty() {
+        vec![param.value.as_str()]
+      } else {
+        vec![]
+      };
+      for value in param_values {
+        url.push(sep);
+        push_percent_encoded(&mut url, name);
+        url.push('=');
+        push_percent_encoded(&mut url, value);
+        sep = '&';
+      }
+    }
+    Ok(url)
+  }
+}
+
+/// Returns all the service descriptors of the given VOTable (including the ones in
+/// sub-resources).
+pub fn service_descriptors<C: TableDataContent>(
+  votable: &VOTable<C>,
+) -> Result<Vec<ServiceDescriptor>, VOTableError> {
+  fn push_descriptors<C: TableDataContent>(
+    resource: &Resource<C>,
+    res: &mut Vec<ServiceDescriptor>,
+  ) -> Result<(), VOTableError> {
+    if ServiceDescriptor::is_service_descriptor(resource) {
+      res.push(ServiceDescriptor::from_resource(resource)?);
+    }
+    for sub_elem in resource.sub_elems.iter() {
+      if let ResourceOrTable::Resource(sub_resource) = &sub_elem.resource_or_table {
+        push_descriptors(sub_resource, res)?;
+      }
+    }
+    Ok(())
+  }
+  let mut res = Vec::new();
+  for resource in votable.resources.iter() {
+    push_descriptors(resource, &mut res)?;
+  }
+  Ok(res)
+}
+
+/// A row of a DataLink results table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataLinkRecord {
+  /// Identifier of the dataset the link is about
+  pub id: String,
+  /// URL of the linked resource (exclusive with `service_def` and `error_message`)
+  pub access_url: Option<String>,
+  /// `ID` of the service descriptor to be used (exclusive with `access_url` and `error_message`)
+  pub service_def: Option<String>,
+  /// Error message (exclusive with `access_url` and `service_def`)
+  pub error_message: Option<String>,
+  /// Term from the DataLink vocabulary, e.g. `#this`, `#preview`, `#cutout`
+  pub semantics: String,
+  pub description: Option<String>,
+  pub content_type: Option<String>,
+  /// Size of the linked resource, in bytes
+  pub content_length: Option<i64>,
+  pub content_qualifier: Option<String>,
+  pub local_semantics: Option<String>,
+}
+
+impl DataLinkRecord {
+  fn new(id: String, semantics: String) -> Self {
+    Self {
+      id,
+      access_url: None,
+      service_def: None,
+      error_message: None,
+      semantics,
+      description: None,
+      content_type: None,
+      content_length: None,
+      content_qualifier: None,
+      local_semantics: None,
+    }
+  }
+
+  /// New record linking to the given URL.
+  pub fn new_access_url<I, U, S>(id: I, access_url: U, semantics: S) -> Self
+  where
+    I: Into<String>,
+    U: Into<String>,
+    S: Into<String>,
+  {
+    let mut record = Self::new(id.into(), semantics.into());
+    record.access_url = Some(access_url.into());
+    record
+  }
+
+  /// New record linking to the service descriptor having the given `ID`.
+  pub fn new_service_def<I, D, S>(id: I, service_def: D, semantics: S) -> Self
+  where
+    I: Into<String>,
+    D: Into<String>,
+    S: Into<String>,
+  {
+    let mut record = Self::new(id.into(), semantics.into());
+    record.service_def = Some(service_def.into());
+    record
+  }
+
+  /// New record reporting an error.
+  pub fn new_error<I, E, S>(id: I, error_message: E, semantics: S) -> Self
+  where
+    I: Into<String>,
+    E: Into<String>,
+    S: Into<String>,
+  {
+    let mut record = Self::new(id.into(), semantics.into());
+    record.error_message = Some(error_message.into());
+    record
+  }
+
+  impl_builder_opt_string_attr!(description);
+  impl_builder_opt_string_attr!(content_type);
+  impl_builder_opt_attr!(content_length, i64);
+  impl_builder_opt_string_attr!(content_qualifier);
+  impl_builder_opt_string_attr!(local_semantics);
+
+  /// Returns the `FIELD`s of a DataLink results table, in the order of the values returned by
+  /// [DataLinkRecord::to_row].
+  pub fn table_elems() -> Vec<TableElem> {
+    fn char_field(name: &str, ucd: &str) -> TableElem {
+      TableElem::Field(
+        Field::new(name, Datatype::CharASCII)
+          .set_arraysize(ArraySize::new_variable_1d())
+          .set_ucd(ucd),
+      )
+    }
+    vec![
+      char_field("ID", "meta.id;meta.main"),
+      char_field("access_url", "meta.ref.url"),
+      char_field("service_def", "meta.ref"),
+      char_field("error_message", "meta.code.error"),
+      char_field("semantics", "meta.code"),
+      char_field("description", "meta.note"),
+      char_field("content_type", "meta.code.mime"),
+      TableElem::Field(
+        Field::new("content_length", Datatype::LongInt)
+          .set_ucd("phys.size;meta.file")
+          .set_unit("byte"),
+      ),
+      char_field("content_qualifier", "meta.code"),
+      char_field("local_semantics", "meta.code"),
+    ]
+  }
+
+  /// Returns the row values, in the order of the fields returned by
+  /// [DataLinkRecord::table_elems].
+  pub fn to_row(&self) -> Vec<VOTableValue> {
+    fn to_value(s: &Option<String>) -> VOTableValue {
+      s.as_ref()
+        .map(|s| VOTableValue::String(s.clone()))
+        .unwrap_or(VOTableValue::Null)
+    }
+    vec![
+      VOTableValue::String(self.id.clone()),
+      to_value(&self.access_url),
+      to_value(&self.service_def),
+      to_value(&self.error_message),
+      VOTableValue::String(self.semantics.clone()),
+      to_value(&self.description),
+      to_value(&self.content_type),
+      self
+        .content_length
+        .map(VOTableValue::Long)
+        .unwrap_or(VOTableValue::Null),
+      to_value(&self.content_qualifier),
+      to_value(&self.local_semantics),
+    ]
+  }
+
+  /// Check that exactly one of `access_url`, `service_def` and `error_message` is set.
+  pub fn check(&self) -> Result<(), VOTableError> {
+    let n_set = [&self.access_url, &self.service_def, &self.error_message]
+      .iter()
+      .filter(|v| v.is_some())
+      .count();
+    if n_set == 1 {
+      Ok(())
+    } else {
+      Err(VOTableError::Custom(format!(
+        "DataLink record '{}': exactly one of 'access_url', 'service_def' and 'error_message' must be set (actual: {})",
+        self.id, n_set
+      )))
+    }
+  }
+}
+
+/// Indices of the columns of a DataLink results table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataLinkColumns {
+  id: usize,
+  access_url: usize,
+  service_def: usize,
+  error_message: usize,
+  semantics: usize,
+  description: Option<usize>,
+  content_type: Option<usize>,
+  content_length: Option<usize>,
+  content_qualifier: Option<usize>,
+  local_semantics: Option<usize>,
+}
+
+impl DataLinkColumns {
+  /// Look for the DataLink columns (from their names) in the given table elements.
+  /// # Errors
+  /// If one of the mandatory columns (`ID`, `access_url`, `service_def`, `error_message` and
+  /// `semantics`) is missing.
+  pub fn from_table_elems(elems: &[TableElem]) -> Result<Self, VOTableError> {
+    let names: Vec<&str> = elems
+      .iter()
+      .filter_map(|elem| match elem {
+        TableElem::Field(field) => Some(field.name.as_str()),
+        _ => None,
+      })
+      .collect();
+    let opt_index = |name: &str| names.iter().position(|n| *n == name);
+    let index = |name: &str| {
+      opt_index(name).ok_or_else(|| {
+        VOTableError::Custom(format!("Mandatory DataLink column '{}' not found", name))
+      })
+    };
+    Ok(Self {
+      id: index("ID")?,
+      access_url: index("access_url")?,
+      service_def: index("service_def")?,
+      error_message: index("error_message")?,
+      semantics: index("semantics")?,
+      description: opt_index("description"),
+      content_type: opt_index("content_type"),
+      content_length: opt_index("content_length"),
+      content_qualifier: opt_index("content_qualifier"),
+      local_semantics: opt_index("local_semantics"),
+    })
+  }
+
+  /// Build the record from the given row of the results table.
+  /// # Errors
+  /// If `ID` or `semantics` is null, or if not exactly one of `access_url`, `service_def` and
+  /// `error_message` is set.
+  pub fn record(&self, row: &[VOTableValue]) -> Result<DataLinkRecord, VOTableError> {
+    let get = |i: usize| row.get(i).and_then(value_to_opt_string);
+    let get_opt = |i: Option<usize>| i.and_then(get);
+    let id =
+      get(self.id).ok_or_else(|| VOTableError::Custom(String::from("Null DataLink 'ID' value")))?;
+    let semantics = get(self.semantics).ok_or_else(|| {
+      VOTableError::Custom(format!("Null DataLink 'semantics' value for ID '{}'", id))
+    })?;
+    let content_length = match self.content_length.and_then(|i| row.get(i)) {
+      None | Some(VOTableValue::Null) => None,
+      Some(VOTableValue::Long(v)) => Some(*v),
+      Some(VOTableValue::Int(v)) => Some(*v as i64),
+      Some(VOTableValue::Short(v)) => Some(*v as i64),
+      Some(VOTableValue::Byte(v)) => Some(*v as i64),
+      Some(value) => {
+        let s = value.to_string();
+        if s.trim().is_empty() {
+          None
+        } else {
+          Some(s.trim().parse::<i64>().map_err(|e| {
+            VOTableError::Custom(format!("Wrong DataLink 'content_length' '{}': {}", s, e))
+          })?)
+        }
+      }
+    };
+    let record = DataLinkRecord {
+      id,
+      access_url: get(self.access_url),
+      service_def: get(self.service_def),
+      error_message: get(self.error_message),
+      semantics,
+      description: get_opt(self.description),
+      content_type: get_opt(self.content_type),
+      content_length,
+      content_qualifier: get_opt(self.content_qualifier),
+      local_semantics: get_opt(self.local_semantics),
+    };
+    record.check().map(|()| record)
+  }
+}
+
+/// Returns `None` for `NULL` values and empty strings.
+fn value_to_opt_string(value: &VOTableValue) -> Option<String> {
+  match value {
+    VOTableValue::Null => None,
+    value => Some(value.to_string()).filter(|s| !s.is_empty()),
+  }
+}
+
+/// Percent-encode all characters except the unreserved ones of
+/// [RFC-3986](https://www.rfc-editor.org/rfc/rfc3986#section-2.3).
+fn push_percent_encoded(url: &mut String, s: &str) {
+  for b in s.bytes() {
+    match b {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => url.push(b as char),
+      _ => {
+        let _ = write!(url, "%{:02X}", b);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{impls::mem::InMemTableDataRows, votable::VOTableWrapper};
+
+  #[test]
+  fn test_datalink_003() {
+    let votable =
+      VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_file("resources/dataLink_003.xml")
+        .unwrap()
+        .unwrap();
+    let descriptors = service_descriptors(&votable).unwrap();
+    assert_eq!(descriptors.len(), 3);
+    let hips = &descriptors[0];
+    assert_eq!(hips.id.as_deref(), Some("soda-HiPS"));
+    assert_eq!(
+      hips.standard_id.as_deref(),
+      Some("ivo://ivoa.net/std/SODA#sync-1.0")
+    );
+    assert_eq!(
+      hips.access_url,
+      "http://aladin.u-strasbg.fr/cgi-bin/SODA-HiPS.aladin-sync.py"
+    );
+    assert_eq!(hips.params.len(), 3);
+    assert_eq!(
+      hips.query_url([("CIRCLE", "214.2 57.2 0.1")]).unwrap(),
+      "http://aladin.u-strasbg.fr/cgi-bin/SODA-HiPS.aladin-sync.py\
+       ?ID=https%3A%2F%2Fcds%2Fhips%2FP%2FAPERTIF_DR1%2F200104010_AP_B007%2FHI_image_cube1\
+       &CIRCLE=214.2%2057.2%200.1"
+    );
+    assert!(hips.query_url([("FOO", "bar")]).is_err());
+    // Round trip
+    let resource = hips.to_resource::<InMemTableDataRows>();
+    assert_eq!(&ServiceDescriptor::from_resource(&resource).unwrap(), hips);
+
+    // Results table
+    let table = votable.get_first_table().unwrap();
+    let columns = DataLinkColumns::from_table_elems(&table.elems).unwrap();
+    let rows = match table.data.as_ref().map(|data| &data.data) {
+      Some(crate::data::DataElem::TableData(td)) => &td.content.rows,
+      _ => panic!("TABLEDATA expected"),
+    };
+    let records = rows
+      .iter()
+      .map(|row| columns.record(row))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    assert_eq!(records.len(), 7);
+    assert_eq!(records[0].semantics, "#this");
+    assert_eq!(records[0].content_length, Some(2128688640));
+    assert_eq!(records[4].service_def.as_deref(), Some("soda-HiPS"));
+    assert!(descriptors
+      .iter()
+      .any(|d| d.id.as_deref() == records[4].service_def.as_deref()));
+  }
+
+  #[test]
+  fn test_datalink_builder() {
+    let record = DataLinkRecord::new_access_url("ivo://x/y?1", "http://x/1.fits", "#this")
+      .set_content_type("image/fits")
+      .set_content_length(1024);
+    let row = record.to_row();
+    let columns = DataLinkColumns::from_table_elems(&DataLinkRecord::table_elems()).unwrap();
+    assert_eq!(columns.record(&row).unwrap(), record);
+    assert!(DataLinkRecord::new_access_url("a", "b", "#this")
+      .set_content_qualifier("image")
+      .check()
+      .is_ok());
+
+    let descriptor = ServiceDescriptor::new("http://x/soda?lang=en")
+      .set_id("soda")
+      .set_standard_id("ivo://ivoa.net/std/SODA#sync-1.0")
+      .push_param(
+        Param::new("ID", Datatype::CharASCII, "")
+          .set_arraysize(ArraySize::new_variable_1d())
+          .set_ref("pubDID"),
+      )
+      .push_param(Param::new("BAND", Datatype::Double, ""));
+    let elems = vec![TableElem::Field(
+      Field::new("pubDID", Datatype::CharASCII).set_arraysize(ArraySize::new_variable_1d()),
+    )];
+    let row_values =
+      descriptor.row_param_values(&elems, &[VOTableValue::String("ivo://x/y?1".into())]);
+    let mut values: Vec<(&str, &str)> = row_values
+      .iter()
+      .map(|(n, v)| (n.as_str(), v.as_str()))
+      .collect();
+    values.push(("BAND", "0.1 0.2"));
+    assert_eq!(
+      descriptor.query_url(values).unwrap(),
+      "http://x/soda?lang=en&ID=ivo%3A%2F%2Fx%2Fy%3F1&BAND=0.1%200.2"
+    );
+  }
+}
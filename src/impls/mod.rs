@@ -5,6 +5,7 @@ use crate::{
   field::Field,
   impls::visitors::{FixedLengthArrayVisitor, VariableLengthArrayVisitor},
   table::TableElem,
+  values::resolve::ValuesRegistry,
 };
 use bitvec::{order::Msb0, vec::BitVec as BV};
 use log::{trace, warn};
@@ -23,6 +24,8 @@ use std::{
 pub mod b64;
 pub mod mem;
 pub mod seeds;
+pub mod stats;
+pub mod typed;
 pub mod visitors;
 
 use crate::impls::{
@@ -58,13 +61,43 @@ impl TableSchema {
   pub fn iter(&self) -> Iter<'_, Schema> {
     self.0.iter()
   }
+
+  /// Build the schema of the given table elements, resolving the `VALUES` references
+  /// (`VALUES` having a `ref` attribute) among the table `FIELD`s and `PARAM`s.
+  /// # Errors
+  /// If a referenced `VALUES` is not found in the table elements.
+  pub fn try_from_table_elems(context: &[TableElem]) -> Result<Self, VOTableError> {
+    let registry = ValuesRegistry::from_table_elems(context);
+    context
+      .iter()
+      .filter_map(|table_elem| match table_elem {
+        TableElem::Field(field) => Some(
+          registry
+            .resolve_field(field)
+            .map(|field| field.as_ref().into()),
+        ),
+        _ => None,
+      })
+      .collect::<Result<Vec<Schema>, VOTableError>>()
+      .map(Self)
+  }
 }
 impl From<&[TableElem]> for TableSchema {
+  /// Same as [TableSchema::try_from_table_elems], except that unresolved `VALUES` references
+  /// only lead to a warning (to resolve references to `VALUES` outside of the table, see
+  /// [inline_values_refs](crate::values::resolve::inline_values_refs)).
   fn from(context: &[TableElem]) -> Self {
+    let registry = ValuesRegistry::from_table_elems(context);
     let schema: Vec<Schema> = context
       .iter()
       .filter_map(|table_elem| match table_elem {
-        TableElem::Field(field) => Some(field.into()),
+        TableElem::Field(field) => Some(match registry.resolve_field(field) {
+          Ok(field) => field.as_ref().into(),
+          Err(e) => {
+            warn!("{} (FIELD '{}'). Reference ignored.", e, field.name);
+            field.into()
+          }
+        }),
         _ => None,
       })
       .collect();
@@ -156,6 +189,21 @@ impl AsRef<VOTableValue> for VOTableValue {
     self
   }
 }
+impl VOTableValue {
+  /// Returns the value as a `f64` if it is a numeric (non-NaN) scalar value.
+  pub fn as_f64(&self) -> Option<f64> {
+    match self {
+      VOTableValue::Byte(v) => Some(*v as f64),
+      VOTableValue::Short(v) => Some(*v as f64),
+      VOTableValue::Int(v) => Some(*v as f64),
+      VOTableValue::Long(v) => Some(*v as f64),
+      VOTableValue::Float(v) => Some(*v as f64),
+      VOTableValue::Double(v) => Some(*v),
+      _ => None,
+    }
+    .filter(|v| !v.is_nan())
+  }
+}
 /// Here it is the implementation for Serde compatible data types.
 impl Serialize for VOTableValue {
   fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -322,7 +370,7 @@ impl Display for VOTableValue {
       let mut it = elems.iter();
       if let Some(e) = it.next() {
         fmt.write_fmt(format_args!("{}", e))?;
-        for e in elems.iter() {
+        for e in it {
           fmt.write_fmt(format_args!(" {}", e))?;
         }
       }
@@ -335,7 +383,7 @@ impl Display for VOTableValue {
           Some(b) => fmt.write_fmt(format_args!("{}", b)),
           None => fmt.write_str("?"),
         }?;
-        for e in elems.iter() {
+        for e in it {
           match e {
             Some(b) => fmt.write_fmt(format_args!(" {}", b)),
             None => fmt.write_str(" ?"),
@@ -348,7 +396,7 @@ impl Display for VOTableValue {
       let mut it = elems.iter();
       if let Some(e) = it.next() {
         fmt.write_fmt(format_args!("{} {}", e.0, e.1))?;
-        for e in elems.iter() {
+        for e in it {
           fmt.write_fmt(format_args!(" {} {}", e.0, e.1))?;
         }
       }
@@ -0,0 +1,374 @@
+//! Per-column statistics computed from `VOTableValue` rows: number of values, number of `NULL`,
+//! min, max, mean and an estimate of the number of distinct values.
+//!
+//! Statistics are computed row by row (to be used in streaming mode) and can be merged, e.g.
+//! to combine the statistics computed on row chunks by several threads.
+//! Min/max are computed on numeric scalar values and on strings (lexicographic order).
+//! The number of distinct values is estimated with an HyperLogLog counter.
+
+use std::{
+  cmp::Ordering,
+  collections::hash_map::DefaultHasher,
+  hash::{Hash, Hasher},
+};
+
+use super::VOTableValue;
+use crate::{
+  error::VOTableError,
+  info::Info,
+  table::{Table, TableElem},
+  values::{Max, Min, Values},
+  TableDataContent,
+};
+
+/// Name of the `INFO` containing the number of rows, see [TableStats::inject_into_table].
+pub const NROWS_INFO_NAME: &str = "n_rows";
+
+/// Number of bits of the hash used to select a HyperLogLog register.
+const HLL_P: u32 = 12;
+/// Number of HyperLogLog registers.
+const HLL_M: usize = 1 << HLL_P;
+
+/// HyperLogLog distinct values counter (relative standard error of ~1.6%).
+#[derive(Clone, Debug)]
+struct HyperLogLog {
+  registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+  fn new() -> Self {
+    Self {
+      registers: vec![0; HLL_M],
+    }
+  }
+
+  fn push_hash(&mut self, hash: u64) {
+    let i = (hash >> (64 - HLL_P)) as usize;
+    // Position of the leftmost 1 in the remaining bits (sentinel bit to bound the value)
+    let rank = ((hash << HLL_P) | (1 << (HLL_P - 1))).leading_zeros() as u8 + 1;
+    if rank > self.registers[i] {
+      self.registers[i] = rank;
+    }
+  }
+
+  fn merge(&mut self, other: &Self) {
+    for (r, o) in self.registers.iter_mut().zip(other.registers.iter()) {
+      if *o > *r {
+        *r = *o;
+      }
+    }
+  }
+
+  fn estimate(&self) -> u64 {
+    let m = HLL_M as f64;
+    let alpha = 0.7213 / (1.0 + 1.079 / m);
+    let sum: f64 = self
+      .registers
+      .iter()
+      .map(|r| 2_f64.powi(-(*r as i32)))
+      .sum();
+    let e = alpha * m * m / sum;
+    let n_zeros = self.registers.iter().filter(|r| **r == 0).count();
+    if e <= 2.5 * m && n_zeros > 0 {
+      // Small range correction: linear counting
+      (m * (m / n_zeros as f64).ln()).round() as u64
+    } else {
+      e.round() as u64
+    }
+  }
+}
+
+/// Statistics on the values of a single column.
+#[derive(Clone, Debug)]
+pub struct ColumnStats {
+  /// Number of non-`NULL` values
+  count: u64,
+  /// Number of `NULL` (or `NaN`) values
+  n_null: u64,
+  min: Option<VOTableValue>,
+  max: Option<VOTableValue>,
+  /// Number of numeric values (used to compute the mean)
+  n_num: u64,
+  sum: f64,
+  distinct: HyperLogLog,
+}
+
+impl Default for ColumnStats {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl ColumnStats {
+  pub fn new() -> Self {
+    Self {
+      count: 0,
+      n_null: 0,
+      min: None,
+      max: None,
+      n_num: 0,
+      sum: 0.0,
+      distinct: HyperLogLog::new(),
+    }
+  }
+
+  /// Update the statistics with the given value.
+  pub fn push(&mut self, value: &VOTableValue) {
+    if is_null(value) {
+      self.n_null += 1;
+      return;
+    }
+    self.count += 1;
+    let mut hasher = DefaultHasher::new();
+    value.to_string().hash(&mut hasher);
+    self.distinct.push_hash(hasher.finish());
+    if let Some(v) = value.as_f64() {
+      self.n_num += 1;
+      self.sum += v;
+    }
+    if is_ordered(value) {
+      self.update_min(value);
+      self.update_max(value);
+    }
+  }
+
+  /// Merge the statistics computed on an other set of values of the same column.
+  pub fn merge(&mut self, other: &Self) {
+    self.count += other.count;
+    self.n_null += other.n_null;
+    self.n_num += other.n_num;
+    self.sum += other.sum;
+    self.distinct.merge(&other.distinct);
+    if let Some(min) = &other.min {
+      self.update_min(min);
+    }
+    if let Some(max) = &other.max {
+      self.update_max(max);
+    }
+  }
+
+  fn update_min(&mut self, value: &VOTableValue) {
+    let replace = match &self.min {
+      None => true,
+      Some(min) => cmp_values(value, min) == Some(Ordering::Less),
+    };
+    if replace {
+      self.min = Some(value.clone());
+    }
+  }
+
+  fn update_max(&mut self, value: &VOTableValue) {
+    let replace = match &self.max {
+      None => true,
+      Some(max) => cmp_values(value, max) == Some(Ordering::Greater),
+    };
+    if replace {
+      self.max = Some(value.clone());
+    }
+  }
+
+  /// Number of non-`NULL` values.
+  pub fn count(&self) -> u64 {
+    self.count
+  }
+
+  /// Number of `NULL` values (`NaN` floating point values are counted as `NULL`).
+  pub fn n_null(&self) -> u64 {
+    self.n_null
+  }
+
+  /// Minimum value, `None` if the column contains no numeric or string value.
+  pub fn min(&self) -> Option<&VOTableValue> {
+    self.min.as_ref()
+  }
+
+  /// Maximum value, `None` if the column contains no numeric or string value.
+  pub fn max(&self) -> Option<&VOTableValue> {
+    self.max.as_ref()
+  }
+
+  /// Mean of the numeric values, `None` if the column contains no numeric value.
+  pub fn mean(&self) -> Option<f64> {
+    if self.n_num > 0 {
+      Some(self.sum / self.n_num as f64)
+    } else {
+      None
+    }
+  }
+
+  /// Estimate of the number of distinct non-`NULL` values.
+  pub fn distinct_estimate(&self) -> u64 {
+    self.distinct.estimate().min(self.count)
+  }
+}
+
+/// Statistics on the columns of a table.
+#[derive(Clone, Debug)]
+pub struct TableStats {
+  names: Vec<String>,
+  n_rows: u64,
+  columns: Vec<ColumnStats>,
+}
+
+impl TableStats {
+  /// Create empty statistics for the columns having the given names.
+  pub fn new(names: Vec<String>) -> Self {
+    let columns = vec![ColumnStats::new(); names.len()];
+    Self {
+      names,
+      n_rows: 0,
+      columns,
+    }
+  }
+
+  /// Create empty statistics for the `FIELD`s of the given table elements.
+  pub fn from_table_elems(elems: &[TableElem]) -> Self {
+    Self::new(
+      elems
+        .iter()
+        .filter_map(|elem| match elem {
+          TableElem::Field(field) => Some(field.name.clone()),
+          _ => None,
+        })
+        .collect(),
+    )
+  }
+
+  /// Update the statistics with the given row.
+  pub fn push_row(&mut self, row: &[VOTableValue]) {
+    self.n_rows += 1;
+    for (stats, value) in self.columns.iter_mut().zip(row.iter()) {
+      stats.push(value);
+    }
+  }
+
+  /// Merge the statistics computed on an other set of rows of the same table.
+  pub fn merge(&mut self, other: &Self) -> Result<(), VOTableError> {
+    if self.names != other.names {
+      return Err(VOTableError::Custom(String::from(
+        "Unable to merge statistics computed on different columns",
+      )));
+    }
+    self.n_rows += other.n_rows;
+    for (stats, other_stats) in self.columns.iter_mut().zip(other.columns.iter()) {
+      stats.merge(other_stats);
+    }
+    Ok(())
+  }
+
+  /// Number of rows.
+  pub fn n_rows(&self) -> u64 {
+    self.n_rows
+  }
+
+  /// Names of the columns.
+  pub fn names(&self) -> &[String] {
+    self.names.as_slice()
+  }
+
+  /// Statistics of each column.
+  pub fn columns(&self) -> &[ColumnStats] {
+    self.columns.as_slice()
+  }
+
+  /// Set the `MIN`/`MAX` of the `VALUES` of the `FIELD`s of the given table elements
+  /// (creating the `VALUES` if needed).
+  /// The elements must be the ones the statistics have been computed from.
+  pub fn set_min_max(&self, elems: &mut [TableElem]) {
+    let fields = elems.iter_mut().filter_map(|elem| match elem {
+      TableElem::Field(field) => Some(field),
+      _ => None,
+    });
+    for (field, stats) in fields.zip(self.columns.iter()) {
+      if let (Some(min), Some(max)) = (stats.min(), stats.max()) {
+        let values = field.values.get_or_insert_with(Values::new);
+        values.min = Some(Min::new(min.to_string()));
+        values.max = Some(Max::new(max.to_string()));
+      }
+    }
+  }
+
+  /// Set the `MIN`/`MAX` of the `VALUES` of the table `FIELD`s and add (or update) an `INFO`
+  /// named `n_rows` containing the number of rows.
+  pub fn inject_into_table<C: TableDataContent>(&self, table: &mut Table<C>) {
+    self.set_min_max(table.elems.as_mut_slice());
+    let n_rows = self.n_rows.to_string();
+    match table
+      .infos
+      .iter_mut()
+      .find(|info| info.name == NROWS_INFO_NAME)
+    {
+      Some(info) => info.value = n_rows,
+      None => table.infos.push(Info::new(NROWS_INFO_NAME, n_rows)),
+    }
+  }
+}
+
+fn is_null(value: &VOTableValue) -> bool {
+  match value {
+    VOTableValue::Null => true,
+    VOTableValue::Float(v) => v.is_nan(),
+    VOTableValue::Double(v) => v.is_nan(),
+    _ => false,
+  }
+}
+
+fn is_ordered(value: &VOTableValue) -> bool {
+  value.as_f64().is_some() || as_str(value).is_some()
+}
+
+fn as_str(value: &VOTableValue) -> Option<String> {
+  match value {
+    VOTableValue::CharASCII(c) | VOTableValue::CharUnicode(c) => Some(c.to_string()),
+    VOTableValue::String(s) => Some(s.clone()),
+    _ => None,
+  }
+}
+
+fn cmp_values(a: &VOTableValue, b: &VOTableValue) -> Option<Ordering> {
+  match (a.as_f64(), b.as_f64()) {
+    (Some(a), Some(b)) => a.partial_cmp(&b),
+    _ => match (as_str(a), as_str(b)) {
+      (Some(a), Some(b)) => Some(a.cmp(&b)),
+      _ => None,
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_table_stats() {
+    let names = vec![String::from("x"), String::from("s")];
+    let mut stats1 = TableStats::new(names.clone());
+    let mut stats2 = TableStats::new(names);
+    for i in 0..1000 {
+      let row = [
+        VOTableValue::Double(i as f64),
+        VOTableValue::String(format!("v{}", i % 10)),
+      ];
+      if i % 2 == 0 {
+        stats1.push_row(&row);
+      } else {
+        stats2.push_row(&row);
+      }
+    }
+    stats1.push_row(&[VOTableValue::Double(f64::NAN), VOTableValue::Null]);
+    stats1.merge(&stats2).unwrap();
+    assert_eq!(stats1.n_rows(), 1001);
+    let x = &stats1.columns()[0];
+    assert_eq!(x.count(), 1000);
+    assert_eq!(x.n_null(), 1);
+    assert_eq!(x.min(), Some(&VOTableValue::Double(0.0)));
+    assert_eq!(x.max(), Some(&VOTableValue::Double(999.0)));
+    assert_eq!(x.mean(), Some(499.5));
+    assert!((x.distinct_estimate() as i64 - 1000).abs() < 50);
+    let s = &stats1.columns()[1];
+    assert_eq!(s.min(), Some(&VOTableValue::String(String::from("v0"))));
+    assert_eq!(s.max(), Some(&VOTableValue::String(String::from("v9"))));
+    assert_eq!(s.mean(), None);
+    assert_eq!(s.distinct_estimate(), 10);
+  }
+}
@@ -0,0 +1,543 @@
+//! Deserialization of a row of `VOTableValue`s into a user defined type.
+
+use serde::{
+  de::{
+    value::StrDeserializer, DeserializeSeed, EnumAccess, Error as DeError, IntoDeserializer,
+    MapAccess, SeqAccess, Visitor,
+  },
+  forward_to_deserialize_any, Deserializer,
+};
+
+use crate::{error::VOTableError, impls::VOTableValue};
+
+/// Deserializer of a full row, seen either as a map (keys are the FIELD names) or as a sequence.
+pub struct RowDeserializer<'a, 'de> {
+  col_names: &'a [String],
+  row: &'de [VOTableValue],
+}
+
+impl<'a, 'de> RowDeserializer<'a, 'de> {
+  pub fn new(col_names: &'a [String], row: &'de [VOTableValue]) -> Self {
+    Self { col_names, row }
+  }
+
+  fn check_len(&self) -> Result<(), VOTableError> {
+    if self.col_names.len() == self.row.len() {
+      Ok(())
+    } else {
+      Err(VOTableError::WrongFieldNumber(
+        self.col_names.len(),
+        self.row.len(),
+      ))
+    }
+  }
+}
+
+impl<'a, 'de> Deserializer<'de> for RowDeserializer<'a, 'de> {
+  type Error = VOTableError;
+
+  fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_map(visitor)
+  }
+
+  fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    self.check_len()?;
+    visitor.visit_map(RowMapAccess {
+      col_names: self.col_names,
+      row: self.row,
+      i: 0,
+    })
+  }
+
+  fn deserialize_struct<V>(
+    self,
+    _name: &'static str,
+    _fields: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_map(visitor)
+  }
+
+  fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    self.check_len()?;
+    visit_elems(
+      self
+        .row
+        .iter()
+        .zip(self.col_names.iter())
+        .map(|(value, name)| ColumnDeserializer { name, value }),
+      visitor,
+    )
+  }
+
+  fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_seq(visitor)
+  }
+
+  fn deserialize_tuple_struct<V>(
+    self,
+    _name: &'static str,
+    _len: usize,
+    visitor: V,
+  ) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_seq(visitor)
+  }
+
+  fn deserialize_newtype_struct<V>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    visitor.visit_newtype_struct(self)
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+    bytes byte_buf option unit unit_struct enum identifier ignored_any
+  }
+}
+
+/// Gives access to the columns of a row as a map of (FIELD name, value).
+struct RowMapAccess<'a, 'de> {
+  col_names: &'a [String],
+  row: &'de [VOTableValue],
+  i: usize,
+}
+
+impl<'a, 'de> MapAccess<'de> for RowMapAccess<'a, 'de> {
+  type Error = VOTableError;
+
+  fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+  where
+    K: DeserializeSeed<'de>,
+  {
+    match self.col_names.get(self.i) {
+      Some(name) => seed
+        .deserialize(StrDeserializer::<VOTableError>::new(name.as_str()))
+        .map(Some),
+      None => Ok(None),
+    }
+  }
+
+  fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+  where
+    V: DeserializeSeed<'de>,
+  {
+    let i = self.i;
+    self.i += 1;
+    seed.deserialize(ColumnDeserializer {
+      name: &self.col_names[i],
+      value: &self.row[i],
+    })
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.col_names.len() - self.i)
+  }
+}
+
+/// Wraps a `ValueDeserializer` to add the column name in error messages.
+struct ColumnDeserializer<'a, 'de> {
+  name: &'a str,
+  value: &'de VOTableValue,
+}
+
+impl<'a, 'de> ColumnDeserializer<'a, 'de> {
+  fn add_context(&self, err: VOTableError) -> VOTableError {
+    VOTableError::Custom(format!(
+      "Column '{}': {}",
+      self.name,
+      match err {
+        VOTableError::Custom(msg) => msg,
+        e => e.to_string(),
+      }
+    ))
+  }
+}
+
+macro_rules! forward_with_context {
+  ($($method:ident),*) => {
+    $(
+      fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+      where
+        V: Visitor<'de>,
+      {
+        ValueDeserializer(self.value)
+          .$method(visitor)
+          .map_err(|e| self.add_context(e))
+      }
+    )*
+  };
+}
+
+impl<'a, 'de> Deserializer<'de> for ColumnDeserializer<'a, 'de> {
+  type Error = VOTableError;
+
+  forward_with_context!(
+    deserialize_any,
+    deserialize_option,
+    deserialize_seq,
+    deserialize_unit,
+    deserialize_ignored_any
+  );
+
+  fn deserialize_newtype_struct<V>(
+    self,
+    name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    ValueDeserializer(self.value)
+      .deserialize_newtype_struct(name, visitor)
+      .map_err(|e| self.add_context(e))
+  }
+
+  fn deserialize_enum<V>(
+    self,
+    name: &'static str,
+    variants: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    ValueDeserializer(self.value)
+      .deserialize_enum(name, variants, visitor)
+      .map_err(|e| self.add_context(e))
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+    bytes byte_buf unit_struct tuple tuple_struct map struct identifier
+  }
+}
+
+/// Deserializer of a single `VOTableValue`.
+///
+/// Remarks:
+/// * `VOTableValue::Null` is deserialized as `None` (or `()`), and so are `NaN` floating point
+///   values when the target type is an `Option` (`NaN` being the `NULL` value of floats in VOTable);
+/// * arrays are deserialized as sequences, so they can be deserialized in `Vec<T>` or `[T; N]`
+///   (in which case the length is checked);
+/// * a string value can be deserialized into a unit variant of an `enum`.
+pub struct ValueDeserializer<'de>(pub &'de VOTableValue);
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+  type Error = VOTableError;
+
+  fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    match self.0 {
+      VOTableValue::Null => visitor.visit_none(),
+      VOTableValue::Bool(v) => visitor.visit_bool(*v),
+      VOTableValue::Byte(v) => visitor.visit_u8(*v),
+      VOTableValue::Short(v) => visitor.visit_i16(*v),
+      VOTableValue::Int(v) => visitor.visit_i32(*v),
+      VOTableValue::Long(v) => visitor.visit_i64(*v),
+      VOTableValue::Float(v) => visitor.visit_f32(*v),
+      VOTableValue::Double(v) => visitor.visit_f64(*v),
+      VOTableValue::ComplexFloat((l, r)) => {
+        visit_elems([*l, *r].into_iter().map(|v| v.into_deserializer()), visitor)
+      }
+      VOTableValue::ComplexDouble((l, r)) => {
+        visit_elems([*l, *r].into_iter().map(|v| v.into_deserializer()), visitor)
+      }
+      VOTableValue::CharASCII(v) | VOTableValue::CharUnicode(v) => visitor.visit_char(*v),
+      VOTableValue::String(v) => visitor.visit_borrowed_str(v.as_str()),
+      VOTableValue::BitArray(v) => {
+        visit_elems(v.0.iter().map(|b| (*b).into_deserializer()), visitor)
+      }
+      VOTableValue::BooleanArray(v) => visit_elems(v.iter().map(OptBoolDeserializer), visitor),
+      VOTableValue::ByteArray(v) => {
+        visit_elems(v.iter().map(|v| (*v).into_deserializer()), visitor)
+      }
+      VOTableValue::ShortArray(v) => {
+        visit_elems(v.iter().map(|v| (*v).into_deserializer()), visitor)
+      }
+      VOTableValue::IntArray(v) => visit_elems(v.iter().map(|v| (*v).into_deserializer()), visitor),
+      VOTableValue::LongArray(v) => {
+        visit_elems(v.iter().map(|v| (*v).into_deserializer()), visitor)
+      }
+      VOTableValue::FloatArray(v) => {
+        visit_elems(v.iter().map(|v| (*v).into_deserializer()), visitor)
+      }
+      VOTableValue::DoubleArray(v) => {
+        visit_elems(v.iter().map(|v| (*v).into_deserializer()), visitor)
+      }
+      VOTableValue::ComplexFloatArray(v) => {
+        visit_elems(v.iter().map(|(l, r)| PairDeserializer(*l, *r)), visitor)
+      }
+      VOTableValue::ComplexDoubleArray(v) => {
+        visit_elems(v.iter().map(|(l, r)| PairDeserializer(*l, *r)), visitor)
+      }
+      VOTableValue::StringArray(v) => visit_elems(
+        v.iter()
+          .map(|s| serde::de::value::BorrowedStrDeserializer::new(s.as_str())),
+        visitor,
+      ),
+    }
+  }
+
+  fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    match self.0 {
+      VOTableValue::Null => visitor.visit_none(),
+      VOTableValue::Float(v) if v.is_nan() => visitor.visit_none(),
+      VOTableValue::Double(v) if v.is_nan() => visitor.visit_none(),
+      _ => visitor.visit_some(self),
+    }
+  }
+
+  fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    match self.0 {
+      // An empty variable length array may be written as NULL
+      VOTableValue::Null => visit_elems(std::iter::empty::<ValueDeserializer>(), visitor),
+      _ => self.deserialize_any(visitor),
+    }
+  }
+
+  fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    match self.0 {
+      VOTableValue::Null => visitor.visit_unit(),
+      _ => self.deserialize_any(visitor),
+    }
+  }
+
+  fn deserialize_newtype_struct<V>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_enum<V>(
+    self,
+    _name: &'static str,
+    _variants: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    match self.0 {
+      VOTableValue::String(s) => visitor.visit_enum(UnitVariantAccess(s.as_str())),
+      _ => self.deserialize_any(visitor),
+    }
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+    bytes byte_buf unit_struct tuple tuple_struct map struct identifier ignored_any
+  }
+}
+
+/// Calls `visitor.visit_seq` on the given elements, checking that all elements have been consumed.
+fn visit_elems<'de, I, D, V>(elems: I, visitor: V) -> Result<V::Value, VOTableError>
+where
+  I: Iterator<Item = D>,
+  D: Deserializer<'de, Error = VOTableError>,
+  V: Visitor<'de>,
+{
+  let mut access = ElemsAccess {
+    elems,
+    n_consumed: 0,
+  };
+  let value = visitor.visit_seq(&mut access)?;
+  let n_remaining = access.elems.count();
+  if n_remaining == 0 {
+    Ok(value)
+  } else {
+    Err(VOTableError::invalid_length(
+      access.n_consumed + n_remaining,
+      &format!("an array of {} elements", access.n_consumed).as_str(),
+    ))
+  }
+}
+
+struct ElemsAccess<I> {
+  elems: I,
+  n_consumed: usize,
+}
+
+impl<'de, I, D> SeqAccess<'de> for ElemsAccess<I>
+where
+  I: Iterator<Item = D>,
+  D: Deserializer<'de, Error = VOTableError>,
+{
+  type Error = VOTableError;
+
+  fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+  where
+    T: DeserializeSeed<'de>,
+  {
+    match self.elems.next() {
+      Some(elem) => {
+        self.n_consumed += 1;
+        seed.deserialize(elem).map(Some)
+      }
+      None => Ok(None),
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    match self.elems.size_hint() {
+      (lower, Some(upper)) if lower == upper => Some(upper),
+      _ => None,
+    }
+  }
+}
+
+/// Deserializer for elements of boolean arrays (in which elements may be null).
+struct OptBoolDeserializer<'a>(&'a Option<bool>);
+
+impl<'de, 'a> Deserializer<'de> for OptBoolDeserializer<'a> {
+  type Error = VOTableError;
+
+  fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    match self.0 {
+      Some(b) => visitor.visit_bool(*b),
+      None => visitor.visit_none(),
+    }
+  }
+
+  fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    match self.0 {
+      Some(b) => visitor.visit_some((*b).into_deserializer()),
+      None => visitor.visit_none(),
+    }
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+    bytes byte_buf unit unit_struct newtype_struct seq tuple
+    tuple_struct map struct enum identifier ignored_any
+  }
+}
+
+/// Deserializer for elements of complex arrays (deserialized as 2 elements sequences).
+struct PairDeserializer<T>(T, T);
+
+impl<'de, T> Deserializer<'de> for PairDeserializer<T>
+where
+  T: IntoDeserializer<'de, VOTableError>,
+{
+  type Error = VOTableError;
+
+  fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    visit_elems(
+      [self.0, self.1].into_iter().map(|v| v.into_deserializer()),
+      visitor,
+    )
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+    bytes byte_buf option unit unit_struct newtype_struct seq tuple
+    tuple_struct map struct enum identifier ignored_any
+  }
+}
+
+/// Allows to deserialize a string into a unit enum variant.
+struct UnitVariantAccess<'de>(&'de str);
+
+impl<'de> EnumAccess<'de> for UnitVariantAccess<'de> {
+  type Error = VOTableError;
+  type Variant = Self;
+
+  fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+  where
+    V: DeserializeSeed<'de>,
+  {
+    seed
+      .deserialize(serde::de::value::BorrowedStrDeserializer::<VOTableError>::new(self.0))
+      .map(|v| (v, self))
+  }
+}
+
+impl<'de> serde::de::VariantAccess<'de> for UnitVariantAccess<'de> {
+  type Error = VOTableError;
+
+  fn unit_variant(self) -> Result<(), Self::Error> {
+    Ok(())
+  }
+
+  fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Self::Error>
+  where
+    T: DeserializeSeed<'de>,
+  {
+    Err(VOTableError::custom(format!(
+      "Variant '{}' is not a unit variant",
+      self.0
+    )))
+  }
+
+  fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    Err(VOTableError::custom(format!(
+      "Variant '{}' is not a unit variant",
+      self.0
+    )))
+  }
+
+  fn struct_variant<V>(
+    self,
+    _fields: &'static [&'static str],
+    _visitor: V,
+  ) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    Err(VOTableError::custom(format!(
+      "Variant '{}' is not a unit variant",
+      self.0
+    )))
+  }
+}
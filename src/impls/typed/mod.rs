@@ -0,0 +1,460 @@
+//! Typed access to table rows using `serde`.
+//!
+//! A row is deserialized (see [from_row]) as a map in which the keys are the FIELD names,
+//! so that a `struct` field is matched with the FIELD having the same name (`#[serde(rename)]`
+//! can be used if the FIELD name is not a valid Rust identifier).
+//! A row can also be deserialized as a tuple, in which case the FIELD order is used.
+//! `NULL` values (and `NaN` floating point values) are deserialized as `None` into `Option`s.
+//! Multi-dimensional arrays are seen as flat sequences (the first dimension varying the fastest).
+//!
+//! In the other direction, a slice of `struct`s can be serialized into a table (see [to_table]),
+//! the FIELDs being generated from the `struct` fields:
+//! * `bool` -> `boolean`
+//! * `u8` -> `unsignedByte`
+//! * `i8`, `i16` -> `short`
+//! * `u16`, `i32` -> `int`
+//! * `u32`, `i64`, `u64` -> `long`
+//! * `f32` -> `float`
+//! * `f64` -> `double`
+//! * `char` -> `char` or `unicodeChar`
+//! * `String`, `&str`, unit enum variants -> `char` or `unicodeChar` with `arraysize="*"`
+//! * `Vec<T>`, `&[T]` -> variable length array (`arraysize="*"`)
+//! * `[T; N]`, tuples -> fixed length array (`arraysize="N"`, or `"NxM"` for arrays of arrays)
+//! * `Option<T>` -> same as `T`, `None` being `NULL`
+
+use std::{io::BufRead, marker::PhantomData};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+  data::Data,
+  datatype::Datatype,
+  error::VOTableError,
+  field::{ArraySize, Field},
+  impls::{mem::InMemTableDataRows, VOTableValue},
+  iter::{elems::RowValueIterator, SimpleVOTableRowIterator, TableIter},
+  table::{Table, TableElem},
+  values::Values,
+};
+
+pub mod de;
+pub mod row;
+pub mod ser;
+
+pub use self::row::{VOTableArrayElem, VOTableColumn, VOTableRow};
+
+use self::{
+  de::RowDeserializer,
+  ser::{merge_datatypes, Cell, RowSerializer},
+};
+
+/// Returns the names of the FIELDs of the given table elements, in order.
+pub fn col_names(elems: &[TableElem]) -> Vec<String> {
+  elems
+    .iter()
+    .filter_map(|elem| match elem {
+      TableElem::Field(field) => Some(field.name.clone()),
+      _ => None,
+    })
+    .collect()
+}
+
+/// Deserialize a row into a user defined type.
+/// # Params
+/// * `col_names`: the name of the FIELDs, in the same order as the row values;
+/// * `row`: the row values.
+pub fn from_row<'de, T>(col_names: &[String], row: &'de [VOTableValue]) -> Result<T, VOTableError>
+where
+  T: Deserialize<'de>,
+{
+  T::deserialize(RowDeserializer::new(col_names, row))
+}
+
+/// Serialize a user defined type into a row, returning, for each column, its name and its cell.
+pub fn to_row<T: Serialize + ?Sized>(row: &T) -> Result<Vec<(String, Cell)>, VOTableError> {
+  row.serialize(RowSerializer)
+}
+
+/// Serialize the given rows, returning the FIELDs generated from the row type together with
+/// the in-memory rows.
+pub fn to_fields_and_rows<T: Serialize>(
+  rows: &[T],
+) -> Result<(Vec<Field>, InMemTableDataRows), VOTableError> {
+  let mut cols: Vec<ColumnInfo> = Vec::new();
+  let mut out_rows: Vec<Vec<VOTableValue>> = Vec::with_capacity(rows.len());
+  for (irow, row) in rows.iter().enumerate() {
+    let cells = to_row(row).map_err(|e| VOTableError::Custom(format!("Row {}: {}", irow, e)))?;
+    if irow == 0 {
+      cols = cells
+        .iter()
+        .map(|(name, _)| ColumnInfo::new(name.clone()))
+        .collect();
+    } else if cells.len() != cols.len() {
+      return Err(VOTableError::WrongFieldNumber(cols.len(), cells.len()));
+    }
+    let mut out_row = Vec::with_capacity(cells.len());
+    for (col, (name, cell)) in cols.iter_mut().zip(cells) {
+      if col.name != name {
+        return Err(VOTableError::Custom(format!(
+          "Row {}: wrong column name. Expected: '{}'. Actual: '{}'.",
+          irow, col.name, name
+        )));
+      }
+      col
+        .update(cell.datatype, cell.arraysize)
+        .map_err(|e| VOTableError::Custom(format!("Row {}, column '{}': {}", irow, name, e)))?;
+      out_row.push(cell.value);
+    }
+    out_rows.push(out_row);
+  }
+  let fields = cols.into_iter().map(ColumnInfo::into_field).collect();
+  Ok((fields, InMemTableDataRows::new(out_rows)))
+}
+
+/// Serialize the given rows into a `TABLEDATA` table, the FIELDs being generated from the row type.
+pub fn to_table<T: Serialize>(rows: &[T]) -> Result<Table<InMemTableDataRows>, VOTableError> {
+  let (fields, rows) = to_fields_and_rows(rows)?;
+  let table = fields
+    .into_iter()
+    .fold(Table::new(), |table, field| table.push_field(field));
+  Ok(table.set_data(Data::new_empty().set_tabledata(rows)))
+}
+
+/// Information gathered on a column while serializing rows.
+struct ColumnInfo {
+  name: String,
+  datatype: Option<Datatype>,
+  arraysize: Option<ArraySize>,
+  has_value: bool,
+  has_null: bool,
+}
+
+impl ColumnInfo {
+  fn new(name: String) -> Self {
+    Self {
+      name,
+      datatype: None,
+      arraysize: None,
+      has_value: false,
+      has_null: false,
+    }
+  }
+
+  fn update(
+    &mut self,
+    datatype: Option<Datatype>,
+    arraysize: Option<ArraySize>,
+  ) -> Result<(), VOTableError> {
+    match datatype {
+      None => {
+        self.has_null = true;
+        // Keep the arraysize of empty arrays
+        if !self.has_value && self.arraysize.is_none() {
+          self.arraysize = arraysize;
+        }
+      }
+      Some(dt) => {
+        self.datatype = Some(match self.datatype {
+          None => dt,
+          Some(prev) => merge_datatypes(prev, dt)?,
+        });
+        self.arraysize = match (self.arraysize.take(), arraysize) {
+          // First non-NULL value
+          (l, r) if !self.has_value => r.or(l),
+          (None, None) => None,
+          (Some(l), Some(r)) if l == r => Some(l),
+          // Fixed length arrays of different lengths are stored in a variable length array
+          (
+            Some(ArraySize::Fixed1D { .. } | ArraySize::Variable1D),
+            Some(ArraySize::Fixed1D { .. } | ArraySize::Variable1D),
+          ) => Some(ArraySize::new_variable_1d()),
+          (l, r) => {
+            return Err(VOTableError::Custom(format!(
+              "Incompatible arraysizes: {:?} and {:?}",
+              l, r
+            )));
+          }
+        };
+        self.has_value = true;
+      }
+    }
+    Ok(())
+  }
+
+  fn into_field(self) -> Field {
+    // Columns containing only NULL values are written as strings
+    let (datatype, arraysize) = match self.datatype {
+      None => (
+        Datatype::CharASCII,
+        Some(self.arraysize.unwrap_or(ArraySize::new_variable_1d())),
+      ),
+      Some(dt) => (dt, self.arraysize),
+    };
+    let mut field = Field::new(self.name, datatype);
+    let is_scalar = arraysize.is_none();
+    if let Some(arraysize) = arraysize {
+      field = field.set_arraysize(arraysize);
+    }
+    if self.has_null && is_scalar {
+      let null = match datatype {
+        Datatype::Byte => Some(u8::MAX.to_string()),
+        Datatype::ShortInt => Some(i16::MIN.to_string()),
+        Datatype::Int => Some(i32::MIN.to_string()),
+        Datatype::LongInt => Some(i64::MIN.to_string()),
+        _ => None,
+      };
+      if let Some(null) = null {
+        field = field.set_values(Values::new().set_null(null));
+      }
+    }
+    field
+  }
+}
+
+/// Iterator deserializing each row of an underlying row iterator into a user defined type.
+pub struct TypedRowIterator<I, T> {
+  col_names: Vec<String>,
+  rows: I,
+  _type: PhantomData<T>,
+}
+
+impl<I, T> TypedRowIterator<I, T>
+where
+  I: Iterator<Item = Result<Vec<VOTableValue>, VOTableError>>,
+  T: DeserializeOwned,
+{
+  pub fn new(col_names: Vec<String>, rows: I) -> Self {
+    Self {
+      col_names,
+      rows,
+      _type: PhantomData,
+    }
+  }
+
+  pub fn col_names(&self) -> &[String] {
+    self.col_names.as_slice()
+  }
+
+  /// Returns the underlying row iterator.
+  pub fn into_inner(self) -> I {
+    self.rows
+  }
+}
+
+impl<I, T> Iterator for TypedRowIterator<I, T>
+where
+  I: Iterator<Item = Result<Vec<VOTableValue>, VOTableError>>,
+  T: DeserializeOwned,
+{
+  type Item = Result<T, VOTableError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self
+      .rows
+      .next()
+      .map(|row_res| row_res.and_then(|row| from_row(self.col_names.as_slice(), row.as_slice())))
+  }
+}
+
+impl<'a, R: BufRead> RowValueIterator<'a, R> {
+  /// Transforms this iterator into an iterator deserializing each row into a `T`.
+  pub fn into_typed<T: DeserializeOwned>(mut self) -> TypedRowIterator<Self, T> {
+    let col_names = col_names(self.table().elems.as_slice());
+    TypedRowIterator::new(col_names, self)
+  }
+}
+
+impl<R: BufRead> SimpleVOTableRowIterator<R> {
+  /// Same as `to_row_value_iter`, but deserializing each row into a `T`.
+  pub fn to_typed_row_iter<T: DeserializeOwned>(
+    &mut self,
+  ) -> TypedRowIterator<RowValueIterator<'_, R>, T> {
+    self.to_row_value_iter().into_typed()
+  }
+}
+
+impl InMemTableDataRows {
+  /// Deserialize all rows into a `T`.
+  /// # Params
+  /// * `context`: the table elements, used to retrieve the FIELD names.
+  pub fn deserialize_rows<'de, T>(&'de self, context: &[TableElem]) -> Result<Vec<T>, VOTableError>
+  where
+    T: Deserialize<'de>,
+  {
+    let col_names = col_names(context);
+    self
+      .rows
+      .iter()
+      .map(|row| from_row(col_names.as_slice(), row.as_slice()))
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde::{Deserialize, Serialize};
+
+  use super::*;
+  use crate::{
+    impls::mem::InMemTableDataRows,
+    iter::SimpleVOTableRowIterator,
+    resource::Resource,
+    votable::{VOTable, Version},
+  };
+
+  #[derive(Debug, Deserialize)]
+  struct SdssRow {
+    #[serde(rename = "RA_ICRS")]
+    ra: f64,
+    #[serde(rename = "DE_ICRS")]
+    dec: f64,
+    #[serde(rename = "SDSS12")]
+    name: String,
+    mode: u8,
+    #[serde(rename = "zsp")]
+    zsp: Option<f64>,
+  }
+
+  #[test]
+  fn test_typed_iter_sdss() {
+    let mut it = SimpleVOTableRowIterator::from_file("resources/sdss12.vot").unwrap();
+    let rows: Vec<SdssRow> = it
+      .to_typed_row_iter::<SdssRow>()
+      .collect::<Result<_, _>>()
+      .unwrap();
+    assert_eq!(rows.len(), 50);
+    assert!(rows.iter().all(|r| (0.0..360.0).contains(&r.ra)));
+    assert!(rows.iter().all(|r| (-90.0..=90.0).contains(&r.dec)));
+    assert!(rows.iter().all(|r| !r.name.is_empty() && r.mode > 0));
+    assert!(rows.iter().any(|r| r.zsp.is_none()));
+  }
+
+  #[test]
+  fn test_typed_wrong_type() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct WrongRow {
+      #[serde(rename = "RA_ICRS")]
+      ra: String,
+    }
+    let mut it = SimpleVOTableRowIterator::from_file("resources/sdss12.vot").unwrap();
+    let err = it
+      .to_typed_row_iter::<WrongRow>()
+      .next()
+      .unwrap()
+      .unwrap_err();
+    assert!(err.to_string().contains("RA_ICRS"), "{}", err);
+  }
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  enum Kind {
+    Star,
+    Galaxy,
+  }
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Source {
+    id: u32,
+    name: String,
+    kind: Kind,
+    pos: [f64; 2],
+    flux: Option<f32>,
+    mag: Option<i16>,
+    flags: Vec<u8>,
+    flag: bool,
+  }
+
+  fn sources() -> Vec<Source> {
+    vec![
+      Source {
+        id: 1,
+        name: String::from("α Cen"),
+        kind: Kind::Star,
+        pos: [219.9, -60.8],
+        flux: Some(1.5),
+        mag: None,
+        flags: vec![1, 2, 3],
+        flag: true,
+      },
+      Source {
+        id: 2,
+        name: String::from("M31"),
+        kind: Kind::Galaxy,
+        pos: [10.68, 41.27],
+        flux: None,
+        mag: Some(3),
+        flags: vec![],
+        flag: false,
+      },
+    ]
+  }
+
+  #[test]
+  fn test_typed_serialize_fields() {
+    let (fields, rows) = to_fields_and_rows(&sources()).unwrap();
+    let desc: Vec<(&str, Datatype, Option<ArraySize>)> = fields
+      .iter()
+      .map(|f| (f.name.as_str(), f.datatype, f.arraysize.clone()))
+      .collect();
+    assert_eq!(
+      desc,
+      vec![
+        ("id", Datatype::LongInt, None),
+        (
+          "name",
+          Datatype::CharUnicode,
+          Some(ArraySize::new_variable_1d())
+        ),
+        (
+          "kind",
+          Datatype::CharASCII,
+          Some(ArraySize::new_variable_1d())
+        ),
+        ("pos", Datatype::Double, Some(ArraySize::new_fixed_1d(2))),
+        ("flux", Datatype::Float, None),
+        ("mag", Datatype::ShortInt, None),
+        ("flags", Datatype::Byte, Some(ArraySize::new_variable_1d())),
+        ("flag", Datatype::Logical, None),
+      ]
+    );
+    assert_eq!(
+      fields[5].values.as_ref().and_then(|v| v.null.as_deref()),
+      Some("-32768")
+    );
+    assert_eq!(rows.rows.len(), 2);
+  }
+
+  #[test]
+  fn test_typed_roundtrip() {
+    for to_bin in [false, true] {
+      let mut table = to_table(&sources()).unwrap().set_name("sources");
+      if to_bin {
+        table.to_binary2().unwrap();
+      }
+      let content = VOTable::new(Version::V1_4, Resource::default().push_table(table))
+        .wrap()
+        .to_ivoa_xml_bytes()
+        .unwrap();
+      // Read back the table
+      let mut it = SimpleVOTableRowIterator::from_reader(content.as_slice()).unwrap();
+      let actual: Vec<Source> = it
+        .to_typed_row_iter::<Source>()
+        .collect::<Result<_, _>>()
+        .unwrap();
+      assert_eq!(actual, sources());
+    }
+  }
+
+  #[test]
+  fn test_typed_inmem() {
+    let (fields, rows) = to_fields_and_rows(&sources()).unwrap();
+    let elems: Vec<TableElem> = fields.into_iter().map(TableElem::Field).collect();
+    let actual: Vec<Source> = rows.deserialize_rows(elems.as_slice()).unwrap();
+    assert_eq!(actual, sources());
+    let empty = InMemTableDataRows::default();
+    assert!(empty
+      .deserialize_rows::<Source>(elems.as_slice())
+      .unwrap()
+      .is_empty());
+  }
+}
@@ -0,0 +1,318 @@
+//! Traits implemented by types which can be written as VOTable rows without going through
+//! `serde`, typically by using `#[derive(VOTableRow)]` (feature `derive`).
+//!
+//! Example:
+//! ```ignore
+//! use votable::VOTableRow;
+//!
+//! #[derive(VOTableRow)]
+//! struct Source {
+//!   #[votable(name = "RA", unit = "deg", ucd = "pos.eq.ra;meta.main")]
+//!   ra: f64,
+//!   #[votable(name = "Dec", unit = "deg", ucd = "pos.eq.dec;meta.main")]
+//!   dec: f64,
+//!   #[votable(arraysize = "8")]
+//!   name: String,
+//!   mag: Option<f32>,
+//! }
+//! ```
+
+use crate::{
+  data::Data,
+  datatype::Datatype,
+  field::{ArraySize, Field},
+  impls::{mem::InMemTableDataRows, TableSchema, VOTableValue},
+  table::{Table, TableElem},
+  values::Values,
+};
+
+/// A type which can be stored in a single column.
+///
+/// Remark: strings are stored as `char` (in which UTF-8 encoded strings are supported by this
+/// library), use `#[votable(datatype = "unicodeChar")]` to store them as `unicodeChar`.
+pub trait VOTableColumn {
+  /// Datatype of the column.
+  fn datatype() -> Datatype;
+  /// Default arraysize of the column (`None` for scalar types).
+  fn arraysize() -> Option<ArraySize> {
+    None
+  }
+  /// Tells whether the column may contain NULL values.
+  fn is_nullable() -> bool {
+    false
+  }
+  /// Value used to represent NULL for types not having a natural NULL value (i.e. integers),
+  /// to be set in the FIELD `VALUES` if the column is nullable.
+  fn null_repr() -> Option<String> {
+    None
+  }
+  /// Returns the value to be written in the column.
+  fn to_value(&self) -> VOTableValue;
+}
+
+/// A type which can be an element of a (1D) array column.
+pub trait VOTableArrayElem: VOTableColumn + Sized {
+  fn to_array(elems: &[Self]) -> VOTableValue;
+}
+
+/// Type-level VOTable datatypes, used to check at compile time that the datatype given in
+/// `#[votable(datatype = "...")]` is compatible with the field type (see [HasDatatype]).
+pub mod datatype {
+  use crate::datatype::Datatype;
+
+  /// A type-level VOTable datatype.
+  pub trait DatatypeMarker {
+    const DATATYPE: Datatype;
+  }
+
+  macro_rules! marker {
+    ($($variant:ident),*) => {
+      $(
+        #[doc = concat!("Type-level `Datatype::", stringify!($variant), "`.")]
+        pub struct $variant;
+
+        impl DatatypeMarker for $variant {
+          const DATATYPE: Datatype = Datatype::$variant;
+        }
+      )*
+    };
+  }
+
+  marker!(
+    Logical,
+    Bit,
+    Byte,
+    ShortInt,
+    Int,
+    LongInt,
+    CharASCII,
+    CharUnicode,
+    Float,
+    Double,
+    ComplexFloat,
+    ComplexDouble
+  );
+}
+
+pub use self::datatype::DatatypeMarker;
+
+/// Implemented by the column types whose values can be written in a column of datatype `D`.
+#[diagnostic::on_unimplemented(
+  message = "`{Self}` values can not be written in a VOTable column of datatype `{D}`",
+  label = "wrong `#[votable(datatype = ...)]` for this field type"
+)]
+pub trait HasDatatype<D: DatatypeMarker> {}
+
+/// A type which can be written as a table row.
+pub trait VOTableRow {
+  /// The FIELDs of the table, in the same order as the values returned by `to_row`.
+  fn fields() -> Vec<Field>;
+
+  /// The values of the row.
+  fn to_row(&self) -> Vec<VOTableValue>;
+
+  /// The table header (i.e. FIELDs only).
+  fn table_elems() -> Vec<TableElem> {
+    Self::fields().into_iter().map(TableElem::Field).collect()
+  }
+
+  /// The schema to be used with e.g. `InMemTableDataRows::write_binary2_row`.
+  fn table_schema() -> TableSchema {
+    TableSchema::from(Self::table_elems().as_slice())
+  }
+
+  /// Build a `TABLEDATA` table containing the given rows.
+  fn to_table(rows: &[Self]) -> Table<InMemTableDataRows>
+  where
+    Self: Sized,
+  {
+    let rows = InMemTableDataRows::new(rows.iter().map(|row| row.to_row()).collect());
+    Self::fields()
+      .into_iter()
+      .fold(Table::new(), |table, field| table.push_field(field))
+      .set_data(Data::new_empty().set_tabledata(rows))
+  }
+}
+
+/// Build a FIELD from the column type, possibly overwriting its arraysize.
+/// Used by the code generated by `#[derive(VOTableRow)]`.
+pub fn new_field<T: VOTableColumn, S: Into<String>>(
+  name: S,
+  arraysize: Option<ArraySize>,
+) -> Field {
+  build_field::<T, S>(name, T::datatype(), arraysize)
+}
+
+/// Same as [new_field], overwriting the column type datatype by the datatype `D`, which must be
+/// compatible with the values of the column type (checked at compile time).
+/// Used by the code generated by `#[derive(VOTableRow)]` when `datatype` is provided.
+pub fn new_field_with_datatype<T, D, S>(name: S, arraysize: Option<ArraySize>) -> Field
+where
+  T: VOTableColumn + HasDatatype<D>,
+  D: DatatypeMarker,
+  S: Into<String>,
+{
+  build_field::<T, S>(name, D::DATATYPE, arraysize)
+}
+
+fn build_field<T: VOTableColumn, S: Into<String>>(
+  name: S,
+  datatype: Datatype,
+  arraysize: Option<ArraySize>,
+) -> Field {
+  let mut field = Field::new(name, datatype);
+  if let Some(arraysize) = arraysize.or_else(T::arraysize) {
+    field = field.set_arraysize(arraysize);
+  }
+  if T::is_nullable()
+    && let Some(null) = T::null_repr()
+  {
+    field = field.set_values(Values::new().set_null(null));
+  }
+  field
+}
+
+macro_rules! impl_column {
+  ($t:ty, $dt:ident, $variant:ident, $array_variant:ident, $null:expr) => {
+    impl VOTableColumn for $t {
+      fn datatype() -> Datatype {
+        Datatype::$dt
+      }
+      fn null_repr() -> Option<String> {
+        $null
+      }
+      fn to_value(&self) -> VOTableValue {
+        VOTableValue::$variant(*self)
+      }
+    }
+
+    impl VOTableArrayElem for $t {
+      fn to_array(elems: &[Self]) -> VOTableValue {
+        VOTableValue::$array_variant(elems.to_vec())
+      }
+    }
+
+    impl HasDatatype<datatype::$dt> for $t {}
+  };
+}
+
+impl_column!(u8, Byte, Byte, ByteArray, Some(u8::MAX.to_string()));
+impl_column!(i16, ShortInt, Short, ShortArray, Some(i16::MIN.to_string()));
+impl_column!(i32, Int, Int, IntArray, Some(i32::MIN.to_string()));
+impl_column!(i64, LongInt, Long, LongArray, Some(i64::MIN.to_string()));
+impl_column!(f32, Float, Float, FloatArray, None);
+impl_column!(f64, Double, Double, DoubleArray, None);
+
+impl VOTableColumn for bool {
+  fn datatype() -> Datatype {
+    Datatype::Logical
+  }
+  fn to_value(&self) -> VOTableValue {
+    VOTableValue::Bool(*self)
+  }
+}
+
+impl HasDatatype<datatype::Logical> for bool {}
+
+impl VOTableArrayElem for bool {
+  fn to_array(elems: &[Self]) -> VOTableValue {
+    VOTableValue::BooleanArray(elems.iter().map(|b| Some(*b)).collect())
+  }
+}
+
+impl VOTableColumn for char {
+  fn datatype() -> Datatype {
+    Datatype::CharUnicode
+  }
+  fn to_value(&self) -> VOTableValue {
+    VOTableValue::CharUnicode(*self)
+  }
+}
+
+// Characters and strings can be written either as `char` or as `unicodeChar`
+impl HasDatatype<datatype::CharASCII> for char {}
+impl HasDatatype<datatype::CharUnicode> for char {}
+
+impl VOTableColumn for String {
+  fn datatype() -> Datatype {
+    Datatype::CharASCII
+  }
+  fn arraysize() -> Option<ArraySize> {
+    Some(ArraySize::new_variable_1d())
+  }
+  fn to_value(&self) -> VOTableValue {
+    VOTableValue::String(self.clone())
+  }
+}
+
+impl HasDatatype<datatype::CharASCII> for String {}
+impl HasDatatype<datatype::CharUnicode> for String {}
+
+impl VOTableColumn for &str {
+  fn datatype() -> Datatype {
+    Datatype::CharASCII
+  }
+  fn arraysize() -> Option<ArraySize> {
+    Some(ArraySize::new_variable_1d())
+  }
+  fn to_value(&self) -> VOTableValue {
+    VOTableValue::String(self.to_string())
+  }
+}
+
+impl HasDatatype<datatype::CharASCII> for &str {}
+impl HasDatatype<datatype::CharUnicode> for &str {}
+
+impl<T: VOTableColumn> VOTableColumn for Option<T> {
+  fn datatype() -> Datatype {
+    T::datatype()
+  }
+  fn arraysize() -> Option<ArraySize> {
+    T::arraysize()
+  }
+  fn is_nullable() -> bool {
+    true
+  }
+  fn null_repr() -> Option<String> {
+    T::null_repr()
+  }
+  fn to_value(&self) -> VOTableValue {
+    match self {
+      Some(v) => v.to_value(),
+      None => VOTableValue::Null,
+    }
+  }
+}
+
+impl<T: HasDatatype<D>, D: DatatypeMarker> HasDatatype<D> for Option<T> {}
+
+impl<T: VOTableArrayElem> VOTableColumn for Vec<T> {
+  fn datatype() -> Datatype {
+    T::datatype()
+  }
+  fn arraysize() -> Option<ArraySize> {
+    Some(ArraySize::new_variable_1d())
+  }
+  fn to_value(&self) -> VOTableValue {
+    T::to_array(self.as_slice())
+  }
+}
+
+impl<T: VOTableArrayElem + HasDatatype<D>, D: DatatypeMarker> HasDatatype<D> for Vec<T> {}
+
+impl<T: VOTableArrayElem, const N: usize> VOTableColumn for [T; N] {
+  fn datatype() -> Datatype {
+    T::datatype()
+  }
+  fn arraysize() -> Option<ArraySize> {
+    Some(ArraySize::new_fixed_1d(N as u32))
+  }
+  fn to_value(&self) -> VOTableValue {
+    T::to_array(self.as_slice())
+  }
+}
+
+impl<T: VOTableArrayElem + HasDatatype<D>, D: DatatypeMarker, const N: usize> HasDatatype<D>
+  for [T; N]
+{
+}
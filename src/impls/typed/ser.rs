@@ -0,0 +1,645 @@
+//! Serialization of a user defined type into a row of `VOTableValue`s, keeping track of the
+//! information needed to build the associated FIELDs.
+
+use serde::{
+  ser::{
+    Error as SerError, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple,
+    SerializeTupleStruct,
+  },
+  Serialize, Serializer,
+};
+
+use crate::{datatype::Datatype, error::VOTableError, field::ArraySize, impls::VOTableValue};
+
+/// A serialized value, together with its VOTable datatype (`None` for `NULL` values and
+/// empty arrays) and its arraysize.
+#[derive(Debug, Clone)]
+pub struct Cell {
+  pub value: VOTableValue,
+  pub datatype: Option<Datatype>,
+  pub arraysize: Option<ArraySize>,
+}
+
+impl Cell {
+  fn new(value: VOTableValue, datatype: Datatype) -> Self {
+    Self {
+      value,
+      datatype: Some(datatype),
+      arraysize: None,
+    }
+  }
+
+  fn null() -> Self {
+    Self {
+      value: VOTableValue::Null,
+      datatype: None,
+      arraysize: None,
+    }
+  }
+}
+
+/// Serializer of a full row: a `struct` (or a `map` with string keys), each entry being a column.
+pub struct RowSerializer;
+
+/// Accumulates the (name, cell) pairs of a row.
+#[derive(Default)]
+pub struct RowCollector {
+  cols: Vec<(String, Cell)>,
+  next_key: Option<String>,
+}
+
+fn not_a_row<T>(found: &str) -> Result<T, VOTableError> {
+  Err(VOTableError::Custom(format!(
+    "Only structs (or maps with string keys) can be serialized as a VOTable row. Found: {}.",
+    found
+  )))
+}
+
+impl Serializer for RowSerializer {
+  type Ok = Vec<(String, Cell)>;
+  type Error = VOTableError;
+
+  type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+  type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+  type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+  type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+  type SerializeMap = RowCollector;
+  type SerializeStruct = RowCollector;
+  type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+  fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> {
+    not_a_row("bool")
+  }
+  fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> {
+    not_a_row("i8")
+  }
+  fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Self::Error> {
+    not_a_row("i16")
+  }
+  fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Self::Error> {
+    not_a_row("i32")
+  }
+  fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Self::Error> {
+    not_a_row("i64")
+  }
+  fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
+    not_a_row("u8")
+  }
+  fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
+    not_a_row("u16")
+  }
+  fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> {
+    not_a_row("u32")
+  }
+  fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> {
+    not_a_row("u64")
+  }
+  fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+    not_a_row("f32")
+  }
+  fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> {
+    not_a_row("f64")
+  }
+  fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
+    not_a_row("char")
+  }
+  fn serialize_str(self, _v: &str) -> Result<Self::Ok, Self::Error> {
+    not_a_row("str")
+  }
+  fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+    not_a_row("bytes")
+  }
+  fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+    not_a_row("none")
+  }
+  fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+    value.serialize(self)
+  }
+  fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+    not_a_row("unit")
+  }
+  fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+    not_a_row(name)
+  }
+  fn serialize_unit_variant(
+    self,
+    name: &'static str,
+    _variant_index: u32,
+    _variant: &'static str,
+  ) -> Result<Self::Ok, Self::Error> {
+    not_a_row(name)
+  }
+  fn serialize_newtype_struct<T: ?Sized + Serialize>(
+    self,
+    _name: &'static str,
+    value: &T,
+  ) -> Result<Self::Ok, Self::Error> {
+    value.serialize(self)
+  }
+  fn serialize_newtype_variant<T: ?Sized + Serialize>(
+    self,
+    name: &'static str,
+    _variant_index: u32,
+    _variant: &'static str,
+    _value: &T,
+  ) -> Result<Self::Ok, Self::Error> {
+    not_a_row(name)
+  }
+  fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+    not_a_row("seq")
+  }
+  fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+    not_a_row("tuple")
+  }
+  fn serialize_tuple_struct(
+    self,
+    name: &'static str,
+    _len: usize,
+  ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+    not_a_row(name)
+  }
+  fn serialize_tuple_variant(
+    self,
+    name: &'static str,
+    _variant_index: u32,
+    _variant: &'static str,
+    _len: usize,
+  ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+    not_a_row(name)
+  }
+  fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+    Ok(RowCollector {
+      cols: Vec::with_capacity(len.unwrap_or(16)),
+      next_key: None,
+    })
+  }
+  fn serialize_struct(
+    self,
+    _name: &'static str,
+    len: usize,
+  ) -> Result<Self::SerializeStruct, Self::Error> {
+    Ok(RowCollector {
+      cols: Vec::with_capacity(len),
+      next_key: None,
+    })
+  }
+  fn serialize_struct_variant(
+    self,
+    name: &'static str,
+    _variant_index: u32,
+    _variant: &'static str,
+    _len: usize,
+  ) -> Result<Self::SerializeStructVariant, Self::Error> {
+    not_a_row(name)
+  }
+}
+
+impl SerializeStruct for RowCollector {
+  type Ok = Vec<(String, Cell)>;
+  type Error = VOTableError;
+
+  fn serialize_field<T: ?Sized + Serialize>(
+    &mut self,
+    key: &'static str,
+    value: &T,
+  ) -> Result<(), Self::Error> {
+    value
+      .serialize(ValueSerializer)
+      .map_err(|e| VOTableError::Custom(format!("Field '{}': {}", key, e)))
+      .map(|cell| self.cols.push((key.to_string(), cell)))
+  }
+
+  /// Fields skipped by `#[serde(skip_serializing_if = ...)]` lead to `NULL` values.
+  fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+    self.cols.push((key.to_string(), Cell::null()));
+    Ok(())
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+    Ok(self.cols)
+  }
+}
+
+impl SerializeMap for RowCollector {
+  type Ok = Vec<(String, Cell)>;
+  type Error = VOTableError;
+
+  fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+    match key.serialize(ValueSerializer)?.value {
+      VOTableValue::String(s) => self.next_key = Some(s),
+      VOTableValue::CharASCII(c) | VOTableValue::CharUnicode(c) => {
+        self.next_key = Some(c.to_string())
+      }
+      v => return not_a_row(format!("map with key {:?}", v).as_str()),
+    }
+    Ok(())
+  }
+
+  fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+    let key = self
+      .next_key
+      .take()
+      .ok_or_else(|| VOTableError::custom("Map value serialized before its key"))?;
+    value
+      .serialize(ValueSerializer)
+      .map_err(|e| VOTableError::Custom(format!("Field '{}': {}", key, e)))
+      .map(|cell| self.cols.push((key, cell)))
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+    Ok(self.cols)
+  }
+}
+
+/// Serializer of a single value (i.e. of a single cell).
+pub struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+  type Ok = Cell;
+  type Error = VOTableError;
+
+  type SerializeSeq = ArrayCollector;
+  type SerializeTuple = ArrayCollector;
+  type SerializeTupleStruct = ArrayCollector;
+  type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+  type SerializeMap = Impossible<Self::Ok, Self::Error>;
+  type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+  type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+  fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell::new(VOTableValue::Bool(v), Datatype::Logical))
+  }
+  fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+    // No signed byte in VOTable
+    self.serialize_i16(v as i16)
+  }
+  fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell::new(VOTableValue::Short(v), Datatype::ShortInt))
+  }
+  fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell::new(VOTableValue::Int(v), Datatype::Int))
+  }
+  fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell::new(VOTableValue::Long(v), Datatype::LongInt))
+  }
+  fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell::new(VOTableValue::Byte(v), Datatype::Byte))
+  }
+  fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+    self.serialize_i32(v as i32)
+  }
+  fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+    self.serialize_i64(v as i64)
+  }
+  fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+    i64::try_from(v)
+      .map_err(|_| VOTableError::Custom(format!("u64 value {} overflows a VOTable long", v)))
+      .and_then(|v| self.serialize_i64(v))
+  }
+  fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell::new(VOTableValue::Float(v), Datatype::Float))
+  }
+  fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell::new(VOTableValue::Double(v), Datatype::Double))
+  }
+  fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+    Ok(if v.is_ascii() {
+      Cell::new(VOTableValue::CharASCII(v), Datatype::CharASCII)
+    } else {
+      Cell::new(VOTableValue::CharUnicode(v), Datatype::CharUnicode)
+    })
+  }
+  fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell {
+      value: VOTableValue::String(v.to_string()),
+      datatype: Some(if v.is_ascii() {
+        Datatype::CharASCII
+      } else {
+        Datatype::CharUnicode
+      }),
+      arraysize: Some(ArraySize::new_variable_1d()),
+    })
+  }
+  fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell {
+      value: VOTableValue::ByteArray(v.to_vec()),
+      datatype: Some(Datatype::Byte),
+      arraysize: Some(ArraySize::new_variable_1d()),
+    })
+  }
+  fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell::null())
+  }
+  fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+    value.serialize(self)
+  }
+  fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell::null())
+  }
+  fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+    Ok(Cell::null())
+  }
+  fn serialize_unit_variant(
+    self,
+    _name: &'static str,
+    _variant_index: u32,
+    variant: &'static str,
+  ) -> Result<Self::Ok, Self::Error> {
+    self.serialize_str(variant)
+  }
+  fn serialize_newtype_struct<T: ?Sized + Serialize>(
+    self,
+    _name: &'static str,
+    value: &T,
+  ) -> Result<Self::Ok, Self::Error> {
+    value.serialize(self)
+  }
+  fn serialize_newtype_variant<T: ?Sized + Serialize>(
+    self,
+    name: &'static str,
+    _variant_index: u32,
+    variant: &'static str,
+    _value: &T,
+  ) -> Result<Self::Ok, Self::Error> {
+    unsupported(format!("newtype variant {}::{}", name, variant))
+  }
+  fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+    Ok(ArrayCollector::new(len.unwrap_or(8), false))
+  }
+  fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+    Ok(ArrayCollector::new(len, true))
+  }
+  fn serialize_tuple_struct(
+    self,
+    _name: &'static str,
+    len: usize,
+  ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+    Ok(ArrayCollector::new(len, true))
+  }
+  fn serialize_tuple_variant(
+    self,
+    name: &'static str,
+    _variant_index: u32,
+    variant: &'static str,
+    _len: usize,
+  ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+    unsupported(format!("tuple variant {}::{}", name, variant))
+  }
+  fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+    unsupported(String::from("map"))
+  }
+  fn serialize_struct(
+    self,
+    name: &'static str,
+    _len: usize,
+  ) -> Result<Self::SerializeStruct, Self::Error> {
+    unsupported(format!("nested struct {}", name))
+  }
+  fn serialize_struct_variant(
+    self,
+    name: &'static str,
+    _variant_index: u32,
+    variant: &'static str,
+    _len: usize,
+  ) -> Result<Self::SerializeStructVariant, Self::Error> {
+    unsupported(format!("struct variant {}::{}", name, variant))
+  }
+}
+
+fn unsupported<T>(what: String) -> Result<T, VOTableError> {
+  Err(VOTableError::Custom(format!(
+    "Unable to serialize a {} in a VOTable cell",
+    what
+  )))
+}
+
+/// Collects the elements of a sequence (`Vec<T>`, `&[T]`, ...: variable length array)
+/// or of a tuple (`[T; N]`, `(T, T)`, ...: fixed length array).
+pub struct ArrayCollector {
+  elems: Vec<Cell>,
+  is_fixed: bool,
+}
+
+impl ArrayCollector {
+  fn new(capacity: usize, is_fixed: bool) -> Self {
+    Self {
+      elems: Vec::with_capacity(capacity),
+      is_fixed,
+    }
+  }
+
+  fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), VOTableError> {
+    value
+      .serialize(ValueSerializer)
+      .map(|cell| self.elems.push(cell))
+  }
+
+  /// Build a single array cell from the collected elements.
+  /// Arrays of fixed length arrays are flattened (the first VOTable dimension being
+  /// the one varying the fastest).
+  fn build(self) -> Result<Cell, VOTableError> {
+    let n = self.elems.len();
+    // Datatype and arraysize of the elements
+    let mut datatype: Option<Datatype> = None;
+    let mut sub_sizes: Option<Vec<u32>> = None;
+    for cell in self.elems.iter() {
+      if let Some(dt) = cell.datatype {
+        datatype = match datatype {
+          None => Some(dt),
+          Some(prev) => Some(merge_datatypes(prev, dt)?),
+        };
+        let sizes = match &cell.arraysize {
+          None => vec![],
+          Some(ArraySize::Fixed1D { size }) => vec![*size],
+          Some(ArraySize::FixedND { sizes }) => sizes.clone(),
+          Some(_) => return unsupported(String::from("array of variable length arrays")),
+        };
+        match &sub_sizes {
+          None => sub_sizes = Some(sizes),
+          Some(prev) if *prev != sizes => {
+            return unsupported(String::from("array of arrays of different sizes"));
+          }
+          _ => {}
+        }
+      }
+    }
+    let mut sizes = sub_sizes.unwrap_or_default();
+    let arraysize = if self.is_fixed {
+      sizes.push(n as u32);
+      if sizes.len() == 1 {
+        ArraySize::new_fixed_1d(sizes[0])
+      } else {
+        ArraySize::new_fixed_nd(sizes)
+      }
+    } else if sizes.is_empty() {
+      ArraySize::new_variable_1d()
+    } else {
+      ArraySize::new_variable_nd(sizes)
+    };
+    let value = match datatype {
+      None => {
+        return Ok(Cell {
+          value: if n == 0 {
+            VOTableValue::Null
+          } else {
+            return unsupported(String::from("array containing only null values"));
+          },
+          datatype: None,
+          arraysize: Some(arraysize),
+        })
+      }
+      Some(dt) => flatten(dt, self.elems)?,
+    };
+    Ok(Cell {
+      value,
+      datatype,
+      arraysize: Some(arraysize),
+    })
+  }
+}
+
+impl SerializeSeq for ArrayCollector {
+  type Ok = Cell;
+  type Error = VOTableError;
+
+  fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+    self.push(value)
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+    self.build()
+  }
+}
+
+impl SerializeTuple for ArrayCollector {
+  type Ok = Cell;
+  type Error = VOTableError;
+
+  fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+    self.push(value)
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+    self.build()
+  }
+}
+
+impl SerializeTupleStruct for ArrayCollector {
+  type Ok = Cell;
+  type Error = VOTableError;
+
+  fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+    self.push(value)
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+    self.build()
+  }
+}
+
+/// Returns the datatype able to store both given datatypes.
+pub(crate) fn merge_datatypes(l: Datatype, r: Datatype) -> Result<Datatype, VOTableError> {
+  match (l, r) {
+    (l, r) if l == r => Ok(l),
+    (Datatype::CharASCII, Datatype::CharUnicode) | (Datatype::CharUnicode, Datatype::CharASCII) => {
+      Ok(Datatype::CharUnicode)
+    }
+    _ => Err(VOTableError::Custom(format!(
+      "Incompatible datatypes: {} and {}",
+      l, r
+    ))),
+  }
+}
+
+fn null_err<T>(what: &str) -> Result<T, VOTableError> {
+  Err(VOTableError::Custom(format!(
+    "Null values not supported in arrays of {}",
+    what
+  )))
+}
+
+/// Put all elements in a single `VOTableValue` array.
+fn flatten(datatype: Datatype, elems: Vec<Cell>) -> Result<VOTableValue, VOTableError> {
+  macro_rules! collect_prim {
+    ($elems:expr, $variant:ident, $from:ident, $null:expr) => {{
+      let mut res = Vec::with_capacity($elems.len());
+      for v in $elems {
+        match v {
+          VOTableValue::$from(v) => res.push(v),
+          VOTableValue::Null => res.push($null?),
+          v => {
+            return Err(VOTableError::Custom(format!(
+              "Unexpected array element: {:?}",
+              v
+            )));
+          }
+        }
+      }
+      VOTableValue::$variant(res)
+    }};
+  }
+  let mut flat = Vec::with_capacity(elems.len());
+  for cell in elems {
+    match cell.value {
+      // Flatten array of arrays
+      VOTableValue::BooleanArray(v) => flat.extend(
+        v.into_iter()
+          .map(|b| b.map(VOTableValue::Bool).unwrap_or(VOTableValue::Null)),
+      ),
+      VOTableValue::ByteArray(v) => flat.extend(v.into_iter().map(VOTableValue::Byte)),
+      VOTableValue::ShortArray(v) => flat.extend(v.into_iter().map(VOTableValue::Short)),
+      VOTableValue::IntArray(v) => flat.extend(v.into_iter().map(VOTableValue::Int)),
+      VOTableValue::LongArray(v) => flat.extend(v.into_iter().map(VOTableValue::Long)),
+      VOTableValue::FloatArray(v) => flat.extend(v.into_iter().map(VOTableValue::Float)),
+      VOTableValue::DoubleArray(v) => flat.extend(v.into_iter().map(VOTableValue::Double)),
+      VOTableValue::String(_) | VOTableValue::StringArray(_) => {
+        return unsupported(String::from("array of strings"));
+      }
+      v => flat.push(v),
+    }
+  }
+  let elems = flat;
+  Ok(match datatype {
+    Datatype::Logical => {
+      let mut res = Vec::with_capacity(elems.len());
+      for v in elems {
+        match v {
+          VOTableValue::Bool(b) => res.push(Some(b)),
+          VOTableValue::Null => res.push(None),
+          v => {
+            return Err(VOTableError::Custom(format!(
+              "Unexpected array element: {:?}",
+              v
+            )))
+          }
+        }
+      }
+      VOTableValue::BooleanArray(res)
+    }
+    Datatype::Byte => collect_prim!(elems, ByteArray, Byte, null_err("unsignedByte")),
+    Datatype::ShortInt => collect_prim!(elems, ShortArray, Short, null_err("short")),
+    Datatype::Int => collect_prim!(elems, IntArray, Int, null_err("int")),
+    Datatype::LongInt => collect_prim!(elems, LongArray, Long, null_err("long")),
+    Datatype::Float => collect_prim!(elems, FloatArray, Float, Ok(f32::NAN)),
+    Datatype::Double => collect_prim!(elems, DoubleArray, Double, Ok(f64::NAN)),
+    Datatype::CharASCII | Datatype::CharUnicode => {
+      let mut res = String::with_capacity(elems.len());
+      for v in elems {
+        match v {
+          VOTableValue::CharASCII(c) | VOTableValue::CharUnicode(c) => res.push(c),
+          VOTableValue::Null => res.push('\0'),
+          v => {
+            return Err(VOTableError::Custom(format!(
+              "Unexpected array element: {:?}",
+              v
+            )))
+          }
+        }
+      }
+      VOTableValue::String(res)
+    }
+    Datatype::Bit | Datatype::ComplexFloat | Datatype::ComplexDouble => {
+      return unsupported(format!("array of {}", datatype));
+    }
+  })
+}
@@ -0,0 +1,237 @@
+//! Streaming conversion of the data of all the tables a VOTable contains.
+//!
+//! The conversion only changes the `TABLEDATA`, `BINARY` and `BINARY2` elements: all other bytes
+//! (metadata, comments, ...) are copied unchanged. Tables containing `FITS` data are copied
+//! unchanged too.
+
+use std::{
+  cell::RefCell,
+  io::{BufRead, Read, Write},
+  rc::Rc,
+};
+
+use memchr::memmem;
+
+use crate::{
+  data::{Data, TableOrBinOrBin2},
+  error::VOTableError,
+  impls::{mem::InMemTableDataRows, mem::VoidTableDataContent, TableSchema},
+  iter::{elems::RowValueIterator, TableIter, VOTableIterator},
+  votable::{new_xml_writer, VOTable},
+};
+
+/// Reader keeping a copy of all the bytes it provides.
+struct RecordingReader<R: BufRead> {
+  reader: R,
+  record: Rc<RefCell<Vec<u8>>>,
+}
+
+impl<R: BufRead> Read for RecordingReader<R> {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    let n = self.reader.read(buf)?;
+    self.record.borrow_mut().extend_from_slice(&buf[..n]);
+    Ok(n)
+  }
+}
+
+impl<R: BufRead> BufRead for RecordingReader<R> {
+  fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+    self.reader.fill_buf()
+  }
+
+  fn consume(&mut self, amt: usize) {
+    // No I/O here since `amt` bytes are already in the buffer
+    if let Ok(buf) = self.reader.fill_buf() {
+      self
+        .record
+        .borrow_mut()
+        .extend_from_slice(&buf[..amt.min(buf.len())]);
+    }
+    self.reader.consume(amt)
+  }
+}
+
+/// Convert, in streaming mode, the data of all the tables of the input VOTable into
+/// the given `format`, and returns the VOTable metadata.
+///
+/// # Params
+/// * `reader`: the input XML VOTable
+/// * `write`: the output XML VOTable
+/// * `format`: the target data format (`Fits` not supported)
+pub fn convert_all_tables<R: BufRead, W: Write>(
+  reader: R,
+  write: W,
+  format: &TableOrBinOrBin2,
+) -> Result<VOTable<VoidTableDataContent>, VOTableError> {
+  let data_template: Data<VoidTableDataContent> = match format {
+    TableOrBinOrBin2::TableData => Data::new_empty(),
+    TableOrBinOrBin2::Binary => Data::new_empty().to_binary()?,
+    TableOrBinOrBin2::Binary2 => Data::new_empty().to_binary2()?,
+    TableOrBinOrBin2::Fits(_) => {
+      return Err(VOTableError::Custom(String::from(
+        "Conversion to FITS not supported",
+      )));
+    }
+  };
+  let record: Rc<RefCell<Vec<u8>>> = Rc::new(RefCell::new(Vec::with_capacity(8192)));
+  let mut writer = new_xml_writer(write, None, None);
+  let mut it = VOTableIterator::from_reader(RecordingReader {
+    reader,
+    record: record.clone(),
+  })?;
+  while let Some(mut row_it) = it.next_table_row_value_iter()? {
+    let (input_format, tag): (TableOrBinOrBin2, &[u8]) = match &row_it {
+      RowValueIterator::TableData(_) => (TableOrBinOrBin2::TableData, b"TABLEDATA"),
+      RowValueIterator::BinaryTable(_) => (TableOrBinOrBin2::Binary, b"BINARY"),
+      RowValueIterator::Binary2Table(_) => (TableOrBinOrBin2::Binary2, b"BINARY2"),
+    };
+    if &input_format == format {
+      // Simply copy the data, row by row
+      for row in row_it.by_ref() {
+        row?;
+        copy_record(&record, writer.inner())?;
+      }
+      row_it.read_to_end()?;
+      copy_record(&record, writer.inner())?;
+    } else {
+      // Copy all bytes till the data start tag (excluded)
+      {
+        let mut bytes = record.borrow_mut();
+        let data_start = find_data_start(&bytes, tag)?;
+        writer
+          .inner()
+          .write_all(&bytes[..data_start])
+          .map_err(VOTableError::Io)?;
+        bytes.clear();
+      }
+      let schema = TableSchema::from(row_it.table().elems.as_slice());
+      let mut data = data_template.clone();
+      data.data.write_to_data_beginning(&mut writer)?;
+      // Write rows, discarding the input bytes as they are read
+      let mut error: Option<VOTableError> = None;
+      let rows = row_it.by_ref().map_while(|row_res| {
+        record.borrow_mut().clear();
+        row_res.map_err(|e| error = Some(e)).ok()
+      });
+      match format {
+        TableOrBinOrBin2::TableData => {
+          InMemTableDataRows::write_tabledata_rows(&mut writer, rows, schema)
+        }
+        TableOrBinOrBin2::Binary => {
+          InMemTableDataRows::write_binary_rows(writer.inner(), rows, schema)
+        }
+        _ => InMemTableDataRows::write_binary2_rows(writer.inner(), rows, schema),
+      }?;
+      if let Some(e) = error {
+        return Err(e);
+      }
+      // Discard the input data end tag(s)
+      row_it.read_to_end()?;
+      record.borrow_mut().clear();
+      data.data.write_from_data_end(&mut writer)?;
+    }
+  }
+  // Write the remaining of the VOTable
+  copy_record(&record, writer.inner())?;
+  Ok(it.end_of_it())
+}
+
+/// Write all recorded bytes and clear the record.
+fn copy_record<W: Write>(record: &RefCell<Vec<u8>>, write: &mut W) -> Result<(), VOTableError> {
+  let mut bytes = record.borrow_mut();
+  write.write_all(&bytes).map_err(VOTableError::Io)?;
+  bytes.clear();
+  Ok(())
+}
+
+/// Returns the index of the `<` starting the last `tag` (possibly prefixed by a namespace)
+/// start tag, plus the possible indentation preceding it.
+fn find_data_start(bytes: &[u8], tag: &[u8]) -> Result<usize, VOTableError> {
+  memmem::rfind(bytes, tag)
+    .and_then(|pos| memchr::memrchr(b'<', &bytes[..pos]))
+    .ok_or_else(|| {
+      VOTableError::Custom(format!(
+        "Start tag '{}' not found",
+        String::from_utf8_lossy(tag)
+      ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::convert_all_tables;
+  use crate::{
+    data::TableOrBinOrBin2,
+    impls::mem::InMemTableDataRows,
+    info::Info,
+    resource::Resource,
+    votable::{VOTable, VOTableWrapper, Version},
+  };
+
+  /// Build a VOTable containing 2 resources, the first one containing 2 tables and a
+  /// sub-resource also containing a table.
+  fn multi_table_votable() -> VOTable<InMemTableDataRows> {
+    let mut sdss12 =
+      VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_file("resources/sdss12.vot")
+        .unwrap()
+        .unwrap();
+    let table = sdss12.get_first_table_mut().unwrap().clone();
+    let mut votable = VOTable::new(
+      Version::V1_4,
+      Resource::default()
+        .set_id("r1")
+        .push_table(table.clone().set_id("t1"))
+        .push_table(
+          table
+            .clone()
+            .set_id("t2")
+            .push_post_info(Info::new("post", "info")),
+        )
+        .push_resource(Resource::default().push_table(table.clone().set_id("t3"))),
+    );
+    votable.push_resource_by_ref(
+      Resource::default()
+        .set_id("r2")
+        .push_table(table.set_id("t4")),
+    );
+    votable
+  }
+
+  fn convert(input: &[u8], format: TableOrBinOrBin2) -> Vec<u8> {
+    let mut output: Vec<u8> = Vec::with_capacity(input.len());
+    let metadata = convert_all_tables(input, &mut output, &format).unwrap();
+    assert_eq!(metadata.resources.len(), 2);
+    output
+  }
+
+  #[test]
+  fn test_convert_all_tables() {
+    let votable = multi_table_votable();
+    let td = votable.clone().wrap().to_ivoa_xml_bytes().unwrap();
+    // Same format: output equals input
+    assert_eq!(convert(&td, TableOrBinOrBin2::TableData), td);
+    // TD -> BINARY2 -> BINARY -> TD
+    let bin2 = convert(&td, TableOrBinOrBin2::Binary2);
+    let mut expected = votable.clone();
+    expected.to_binary2().unwrap();
+    let actual = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_bytes(&bin2)
+      .unwrap()
+      .unwrap();
+    assert_eq!(actual, expected);
+    let bin = convert(&bin2, TableOrBinOrBin2::Binary);
+    let actual = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_bytes(&bin)
+      .unwrap()
+      .unwrap();
+    assert_eq!(actual.resources.len(), 2);
+    let td2 = convert(&bin, TableOrBinOrBin2::TableData);
+    let mut actual = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_bytes(&td2)
+      .unwrap()
+      .unwrap();
+    // NULL strings lost in BINARY
+    actual.to_binary2().unwrap();
+    assert_eq!(
+      actual.get_first_table_mut().unwrap().elems,
+      expected.get_first_table_mut().unwrap().elems
+    );
+  }
+}
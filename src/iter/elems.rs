@@ -65,6 +65,8 @@ pub struct DataTableRowValueIterator<'a, R: BufRead> {
   reader_buff: &'a mut Vec<u8>,
   table: &'a mut Table<VoidTableDataContent>,
   schema: Vec<Schema>,
+  /// `</TABLEDATA>` already consumed
+  is_over: bool,
 }
 
 impl<'a, R: BufRead> DataTableRowValueIterator<'a, R> {
@@ -79,6 +81,7 @@ impl<'a, R: BufRead> DataTableRowValueIterator<'a, R> {
       reader_buff,
       table,
       schema,
+      is_over: false,
     }
   }
 }
@@ -89,6 +92,9 @@ impl<'a, R: BufRead> TableIter for DataTableRowValueIterator<'a, R> {
   }
 
   fn read_to_end(self) -> Result<(), VOTableError> {
+    if self.is_over {
+      return Ok(());
+    }
     self
       .reader
       .read_to_end(
@@ -103,6 +109,9 @@ impl<'a, R: BufRead> Iterator for DataTableRowValueIterator<'a, R> {
   type Item = Result<Vec<VOTableValue>, VOTableError>;
 
   fn next(&mut self) -> Option<Self::Item> {
+    if self.is_over {
+      return None;
+    }
     loop {
       let event = self.reader.read_event(self.reader_buff);
       match event {
@@ -125,7 +134,10 @@ impl<'a, R: BufRead> Iterator for DataTableRowValueIterator<'a, R> {
                 }),
             );
           }
-          Event::End(e) if e.local_name() == b"TABLEDATA" => return None,
+          Event::End(e) if e.local_name() == b"TABLEDATA" => {
+            self.is_over = true;
+            return None;
+          }
           Event::Eof => return Some(Err(VOTableError::PrematureEOF("TABLEDATA"))),
           Event::Text(e) if is_empty(e) => {}
           Event::Comment(e) => discard_comment(e, self.reader, "TABLEDATA"),
@@ -166,28 +178,28 @@ impl<'a, R: BufRead> TableIter for BinaryRowValueIterator<'a, R> {
   }
 
   fn read_to_end(self) -> Result<(), VOTableError> {
-    let reader = self
-      .binary_deser
-      .into_inner()
-      .into_inner()
-      .into_inner()
-      .into_inner();
+    let b64_cleaner = self.binary_deser.into_inner().into_inner().into_inner();
+    // `</STREAM>` already consumed if all rows have been read
+    let is_stream_over = b64_cleaner.is_over();
+    let reader = b64_cleaner.into_inner();
     // Stream::<VoidTableDataContent>::TAG_BYTES,
     let mut stream_buf = [0_u8; 8];
     let mut bin_buf = [0_u8; 8]; // we could have reuse stream_buf, but not for BINARY2...
-    skip_until(reader, b'<')
-      .and_then(|_| reader.read_exact(&mut stream_buf))
-      .map_err(VOTableError::Io)
-      .and_then(|_| {
-        if &stream_buf == b"/STREAM>" {
-          Ok(())
-        } else {
-          Err(VOTableError::UnexpectedEndTag(
-            (&stream_buf)[1..8].to_vec(),
-            Stream::<VoidTableDataContent>::TAG,
-          ))
-        }
-      })?;
+    if !is_stream_over {
+      skip_until(reader, b'<')
+        .and_then(|_| reader.read_exact(&mut stream_buf))
+        .map_err(VOTableError::Io)
+        .and_then(|_| {
+          if &stream_buf == b"/STREAM>" {
+            Ok(())
+          } else {
+            Err(VOTableError::UnexpectedEndTag(
+              (&stream_buf)[1..8].to_vec(),
+              Stream::<VoidTableDataContent>::TAG,
+            ))
+          }
+        })?;
+    }
     skip_until(reader, b'<')
       .and_then(|_| reader.read_exact(&mut bin_buf))
       .map_err(VOTableError::Io)
@@ -259,29 +271,29 @@ impl<'a, R: BufRead> TableIter for Binary2RowValueIterator<'a, R> {
   }
 
   fn read_to_end(self) -> Result<(), VOTableError> {
-    let reader = self
-      .binary_deser
-      .into_inner()
-      .into_inner()
-      .into_inner()
-      .into_inner();
+    let b64_cleaner = self.binary_deser.into_inner().into_inner().into_inner();
+    // `</STREAM>` already consumed if all rows have been read
+    let is_stream_over = b64_cleaner.is_over();
+    let reader = b64_cleaner.into_inner();
     // Stream::<VoidTableDataContent>::TAG_BYTES,
     let mut stream_buf = [0_u8; 8];
     let mut bin2_buf = [0_u8; 9]; // we could have reuse stream_buf, but not for BINARY2...
 
-    skip_until(reader, b'<')
-      .and_then(|_| reader.read_exact(&mut stream_buf))
-      .map_err(VOTableError::Io)
-      .and_then(|_| {
-        if &stream_buf == b"/STREAM>" {
-          Ok(())
-        } else {
-          Err(VOTableError::UnexpectedEndTag(
-            (&stream_buf)[1..8].to_vec(),
-            Stream::<VoidTableDataContent>::TAG,
-          ))
-        }
-      })?;
+    if !is_stream_over {
+      skip_until(reader, b'<')
+        .and_then(|_| reader.read_exact(&mut stream_buf))
+        .map_err(VOTableError::Io)
+        .and_then(|_| {
+          if &stream_buf == b"/STREAM>" {
+            Ok(())
+          } else {
+            Err(VOTableError::UnexpectedEndTag(
+              (&stream_buf)[1..8].to_vec(),
+              Stream::<VoidTableDataContent>::TAG,
+            ))
+          }
+        })?;
+    }
     skip_until(reader, b'<')
       .and_then(|_| reader.read_exact(&mut bin2_buf))
       .map_err(VOTableError::Io)
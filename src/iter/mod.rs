@@ -22,7 +22,7 @@ use crate::{
       B64Cleaner, BulkBinaryRowDeserializer, OwnedB64Cleaner, OwnedBulkBinaryRowDeserializer,
     },
     mem::VoidTableDataContent,
-    Schema, VOTableValue,
+    Schema, TableSchema, VOTableValue,
   },
   iter::elems::{
     Binary2RowValueIterator, BinaryRowValueIterator, DataTableRowValueIterator, RowValueIterator,
@@ -31,11 +31,13 @@ use crate::{
   table::{Table, TableElem},
   utils::{discard_comment, discard_event, is_empty},
   votable::{VOTable, VOTableWrapper},
-  VOTableElement,
+  HasSubElements, VOTableElement,
 };
 
+pub mod convert;
 pub mod elems;
 pub mod strings;
+pub mod writer;
 
 static TR_END_FINDER: Lazy<Finder<'static>> = Lazy::new(|| Finder::new("</TR>"));
 static STREAM_END_FINDER: Lazy<Finder<'static>> = Lazy::new(|| Finder::new("</STREAM>"));
@@ -386,13 +388,7 @@ impl<'a, R: BufRead> Binary1or2RowIterator<'a, R> {
     let b64_cleaner = B64Cleaner::new(reader.get_mut());
     let decoder = DecoderReader::new(b64_cleaner, &general_purpose::STANDARD);
     // Get schema
-    let schema: Vec<Schema> = context
-      .iter()
-      .filter_map(|table_elem| match table_elem {
-        TableElem::Field(field) => Some(field.into()),
-        _ => None,
-      })
-      .collect();
+    let schema: Vec<Schema> = TableSchema::from(context).unwrap();
     let reader = if is_binary2 {
       BulkBinaryRowDeserializer::new_binary2(decoder, schema.as_slice())
     } else {
@@ -429,16 +425,14 @@ impl<R: BufRead> OwnedBinary1or2RowIterator<R> {
     let b64_cleaner = OwnedB64Cleaner::new(reader.into_inner());
     let decoder = DecoderReader::new(b64_cleaner, &general_purpose::STANDARD);
     // Get schema
-    let schema: Vec<Schema> = votable
-      .get_first_table()
-      .unwrap() // .resources[0].tables[0]
-      .elems
-      .iter()
-      .filter_map(|table_elem| match table_elem {
-        TableElem::Field(field) => Some(field.into()),
-        _ => None,
-      })
-      .collect();
+    let schema: Vec<Schema> = TableSchema::from(
+      votable
+        .get_first_table()
+        .unwrap() // .resources[0].tables[0]
+        .elems
+        .as_slice(),
+    )
+    .unwrap();
     let reader = if is_binary2 {
       OwnedBulkBinaryRowDeserializer::new_binary2(decoder, schema.as_slice())
     } else {
@@ -646,14 +640,7 @@ impl<R: BufRead> SimpleVOTableRowIterator<R> {
   /// (since they will consume data rows).
   pub fn to_row_value_iter(&mut self) -> RowValueIterator<'_, R> {
     let table = self.votable.get_first_table_mut().unwrap();
-    let schema: Vec<Schema> = table
-      .elems
-      .iter()
-      .filter_map(|table_elem| match table_elem {
-        TableElem::Field(field) => Some(field.into()),
-        _ => None,
-      })
-      .collect();
+    let schema: Vec<Schema> = TableSchema::from(table.elems.as_slice()).unwrap();
     match &self.data_type {
       TableOrBinOrBin2::TableData => RowValueIterator::TableData(DataTableRowValueIterator::new(
         &mut self.reader,
@@ -813,7 +800,8 @@ pub trait TableIter: Iterator<Item = Result<Vec<VOTableValue>, VOTableError>> {
 
 /// Returns an Iterator on the tables a VOTable contains.
 /// For each table, an iterator on the table rows is provided.
-/// The iteration on a table rows must be complete before iterating to the the new table.
+/// The iteration on a table rows must be complete (or the remaining rows skipped using
+/// `TableIter::read_to_end`) before iterating to the the new table.
 /// TODO:
 /// * to use this iterator like `SimpleVOTableRowIterator`, we **must** implement
 ///   methods starting reading again after the last table.
@@ -823,6 +811,9 @@ pub struct VOTableIterator<R: BufRead> {
   votable: VOTable<VoidTableDataContent>,
   resource_stack: Vec<Resource<VoidTableDataContent>>,
   resource_sub_elems_stack: Vec<ResourceSubElem<VoidTableDataContent>>,
+  /// Tells that the data of the last table has been (or is being) read, but not the remaining
+  /// of the table (i.e. the end of the `DATA` and of the `TABLE` elements).
+  is_in_table_data: bool,
 }
 
 impl VOTableIterator<BufReader<File>> {
@@ -840,12 +831,9 @@ impl VOTableIterator<BufReader<File>> {
       votable,
       resource_stack,
       resource_sub_elems_stack: Vec::with_capacity(10),
+      is_in_table_data: false,
     })
   }
-
-  pub fn end_of_it(self) -> VOTable<VoidTableDataContent> {
-    self.votable
-  }
 }
 
 impl<R: BufRead> VOTableIterator<R> {
@@ -861,9 +849,14 @@ impl<R: BufRead> VOTableIterator<R> {
       votable,
       resource_stack,
       resource_sub_elems_stack: Vec::with_capacity(10),
+      is_in_table_data: false,
     })
   }
 
+  pub fn end_of_it(self) -> VOTable<VoidTableDataContent> {
+    self.votable
+  }
+
   pub fn read_all_skipping_data(mut self) -> Result<VOTable<VoidTableDataContent>, VOTableError> {
     while let Some(table_it) = self.next_table_row_value_iter()? {
       table_it.read_to_end()?;
@@ -876,6 +869,10 @@ impl<R: BufRead> VOTableIterator<R> {
   pub fn next_table_row_value_iter(
     &mut self,
   ) -> Result<Option<RowValueIterator<'_, R>>, VOTableError> {
+    if self.is_in_table_data {
+      self.is_in_table_data = false;
+      self.read_last_table_from_data_end()?;
+    }
     loop {
       if let Some(mut sub_resource) = self.resource_sub_elems_stack.pop() {
         match &mut sub_resource.resource_or_table {
@@ -911,14 +908,7 @@ impl<R: BufRead> VOTableIterator<R> {
                 Some(TableOrBinOrBin2::TableData) => {
                   table.set_data_by_ref(data);
 
-                  let schema: Vec<Schema> = table
-                    .elems
-                    .iter()
-                    .filter_map(|table_elem| match table_elem {
-                      TableElem::Field(field) => Some(field.into()),
-                      _ => None,
-                    })
-                    .collect();
+                  let schema: Vec<Schema> = TableSchema::from(table.elems.as_slice()).unwrap();
 
                   if let Some(last) = self.resource_sub_elems_stack.last_mut() {
                     last.push_sub_elem_by_ref(sub_resource)?;
@@ -933,14 +923,11 @@ impl<R: BufRead> VOTableIterator<R> {
                   let row_it = DataTableRowValueIterator::new(
                     &mut self.reader,
                     &mut self.reader_buff,
-                    self
-                      .resource_stack
-                      .last_mut()
-                      .unwrap()
-                      .get_last_table_mut()
+                    last_table_mut(&mut self.resource_sub_elems_stack, &mut self.resource_stack)
                       .unwrap(),
                     schema,
                   );
+                  self.is_in_table_data = true;
                   return Ok(Some(RowValueIterator::TableData(row_it)));
                 }
                 Some(TableOrBinOrBin2::Binary) => {
@@ -949,14 +936,7 @@ impl<R: BufRead> VOTableIterator<R> {
                   data.set_binary_by_ref(binary);
                   table.set_data_by_ref(data);
 
-                  let schema: Vec<Schema> = table
-                    .elems
-                    .iter()
-                    .filter_map(|table_elem| match table_elem {
-                      TableElem::Field(field) => Some(field.into()),
-                      _ => None,
-                    })
-                    .collect();
+                  let schema: Vec<Schema> = TableSchema::from(table.elems.as_slice()).unwrap();
 
                   if let Some(last) = self.resource_sub_elems_stack.last_mut() {
                     last.push_sub_elem_by_ref(sub_resource)?;
@@ -970,14 +950,11 @@ impl<R: BufRead> VOTableIterator<R> {
 
                   let row_it = BinaryRowValueIterator::new(
                     &mut self.reader,
-                    self
-                      .resource_stack
-                      .last_mut()
-                      .unwrap()
-                      .get_last_table_mut()
+                    last_table_mut(&mut self.resource_sub_elems_stack, &mut self.resource_stack)
                       .unwrap(),
                     schema,
                   );
+                  self.is_in_table_data = true;
                   return Ok(Some(RowValueIterator::BinaryTable(row_it)));
                 }
                 Some(TableOrBinOrBin2::Binary2) => {
@@ -986,14 +963,7 @@ impl<R: BufRead> VOTableIterator<R> {
                   data.set_binary2_by_ref(binary2);
                   table.set_data_by_ref(data);
 
-                  let schema: Vec<Schema> = table
-                    .elems
-                    .iter()
-                    .filter_map(|table_elem| match table_elem {
-                      TableElem::Field(field) => Some(field.into()),
-                      _ => None,
-                    })
-                    .collect();
+                  let schema: Vec<Schema> = TableSchema::from(table.elems.as_slice()).unwrap();
 
                   if let Some(last) = self.resource_sub_elems_stack.last_mut() {
                     last.push_sub_elem_by_ref(sub_resource)?;
@@ -1007,14 +977,11 @@ impl<R: BufRead> VOTableIterator<R> {
 
                   let row_it = Binary2RowValueIterator::new(
                     &mut self.reader,
-                    self
-                      .resource_stack
-                      .last_mut()
-                      .unwrap()
-                      .get_last_table_mut()
+                    last_table_mut(&mut self.resource_sub_elems_stack, &mut self.resource_stack)
                       .unwrap(),
                     schema,
                   );
+                  self.is_in_table_data = true;
                   return Ok(Some(RowValueIterator::Binary2Table(row_it)));
                 }
                 Some(TableOrBinOrBin2::Fits(fits)) => {
@@ -1030,6 +997,7 @@ impl<R: BufRead> VOTableIterator<R> {
                       "No more RESOURCE in the stack :o/",
                     )));
                   }
+                  self.read_last_table_from_data_end()?;
                 }
                 None => {
                   return Err(VOTableError::Custom(String::from("Unexpected empty DATA")));
@@ -1077,6 +1045,35 @@ impl<R: BufRead> VOTableIterator<R> {
       }
     }
   }
+
+  /// Read the end of the `DATA` element (possibly containing `INFO`s) and the end of the
+  /// `TABLE` element (possibly containing post-`INFO`s) of the last table.
+  fn read_last_table_from_data_end(&mut self) -> Result<(), VOTableError> {
+    let table = last_table_mut(&mut self.resource_sub_elems_stack, &mut self.resource_stack)
+      .ok_or_else(|| VOTableError::Custom(String::from("No table found in the stack :o/")))?;
+    if let Some(data) = table.data.as_mut() {
+      // The `</BINARY>` or `</BINARY2>` tag may remain if the rows have been fully read
+      // without calling `read_to_end`: it is simply discarded.
+      data.read_sub_elements_by_ref(&mut self.reader, &mut self.reader_buff, &table.elems)?;
+    }
+    table.read_sub_elements_by_ref(&mut self.reader, &mut self.reader_buff, &())
+  }
+}
+
+/// Returns the last table pushed in the deepest resource being read.
+fn last_table_mut<'a>(
+  resource_sub_elems_stack: &'a mut [ResourceSubElem<VoidTableDataContent>],
+  resource_stack: &'a mut [Resource<VoidTableDataContent>],
+) -> Option<&'a mut Table<VoidTableDataContent>> {
+  match resource_sub_elems_stack.last_mut() {
+    Some(sub_elem) => match &mut sub_elem.resource_or_table {
+      ResourceOrTable::Resource(resource) => resource.get_last_table_mut(),
+      ResourceOrTable::Table(table) => Some(table),
+    },
+    None => resource_stack
+      .last_mut()
+      .and_then(|resource| resource.get_last_table_mut()),
+  }
 }
 
 #[cfg(test)]
@@ -1088,10 +1085,12 @@ mod tests {
   use crate::{
     data::TableOrBinOrBin2,
     impls::{
-      b64::read::BinaryDeserializer, visitors::FixedLengthArrayVisitor, Schema, VOTableValue,
+      b64::read::BinaryDeserializer, visitors::FixedLengthArrayVisitor, Schema, TableSchema,
+      VOTableValue,
+    },
+    iter::{
+      Binary1or2RowIterator, SimpleVOTableRowIterator, TabledataRowIterator, VOTableIterator,
     },
-    iter::{Binary1or2RowIterator, SimpleVOTableRowIterator, TabledataRowIterator},
-    table::TableElem,
   };
 
   #[test]
@@ -1152,13 +1151,7 @@ mod tests {
     let context = svor.votable.get_first_table().unwrap().elems.as_slice();
     // svor.skip_remaining_data().unwrap();
     let raw_row_it = Binary1or2RowIterator::new(&mut svor.reader, context, false);
-    let schema: Vec<Schema> = context
-      .iter()
-      .filter_map(|table_elem| match table_elem {
-        TableElem::Field(field) => Some(field.into()),
-        _ => None,
-      })
-      .collect();
+    let schema: Vec<Schema> = TableSchema::from(context).unwrap();
     let schema_len = schema.len();
     for raw_row_res in raw_row_it {
       /*eprintln!(
@@ -1197,13 +1190,7 @@ mod tests {
       .elems
       .as_slice();
     // svor.skip_remaining_data().unwrap();
-    let schema: Vec<Schema> = context
-      .iter()
-      .filter_map(|table_elem| match table_elem {
-        TableElem::Field(field) => Some(field.into()),
-        _ => None,
-      })
-      .collect();
+    let schema: Vec<Schema> = TableSchema::from(context).unwrap();
     let schema_len = schema.len();
     let n_bytes = (schema.len() + 7) / 8;
     let raw_row_it = Binary1or2RowIterator::new(&mut svor.reader, context, true);
@@ -1240,6 +1227,46 @@ mod tests {
     assert!(true)
   }
 
+  #[test]
+  fn test_streaming_values_ref_null() {
+    let votable = |data: &str| {
+      format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4">
+  <RESOURCE>
+    <TABLE>
+      <FIELD name="a" datatype="int"><VALUES ID="flag" null="-1"/></FIELD>
+      <FIELD name="b" datatype="int"><VALUES ref="flag"/></FIELD>
+      <DATA>{}</DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>
+"#,
+        data
+      )
+    };
+    for doc in [
+      votable("<TABLEDATA><TR><TD>-1</TD><TD>-1</TD></TR></TABLEDATA>"),
+      votable("<BINARY2><STREAM encoding=\"base64\">AP//////////</STREAM></BINARY2>"),
+    ] {
+      let mut svor = SimpleVOTableRowIterator::from_reader(doc.as_bytes()).unwrap();
+      let rows = svor
+        .to_row_value_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+      assert_eq!(rows, vec![vec![VOTableValue::Null, VOTableValue::Null]]);
+
+      let mut it = VOTableIterator::from_reader(doc.as_bytes()).unwrap();
+      let rows = it
+        .next_table_row_value_iter()
+        .unwrap()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+      assert_eq!(rows, vec![vec![VOTableValue::Null, VOTableValue::Null]]);
+    }
+  }
+
   /*
   #[test]
   fn test_simple_votable_read_iter_tabledata_owned_local_fxp() {
@@ -0,0 +1,466 @@
+//! Module defining a streaming writer, writing a table row by row.
+
+use std::io::Write;
+
+use base64::{engine::general_purpose, Engine};
+use quick_xml::{
+  events::{BytesStart, Event},
+  Writer,
+};
+
+use crate::{
+  data::{Data, TableOrBinOrBin2},
+  error::VOTableError,
+  impls::{
+    b64::write::BinarySerializer, mem::InMemTableDataRows, mem::VoidTableDataContent, TableSchema,
+    VOTableValue,
+  },
+  votable::{new_xml_writer, VOTable},
+};
+
+/// Number of raw bytes encoded in a line of 64 base64 chars.
+const N_BYTES_PER_LINE: usize = 48;
+
+/// Structure made to write a "simple" VOTable row by row, i.e. the counterpart of
+/// `SimpleVOTableRowIterator`.
+/// The rows are written in the first table of the header VOTable, using the requested
+/// serialization (`TABLEDATA`, `BINARY` or `BINARY2`).
+///
+/// The end of the VOTable is written when calling `finish` or, ignoring possible errors,
+/// when the writer is dropped.
+///
+/// After an I/O error on the destination, the writer is poisoned: nothing more is written
+/// and all subsequent calls return an error.
+pub struct VOTableRowWriter<W: Write> {
+  /// Destination, `None` once the VOTable has been finished
+  write: Option<W>,
+  /// XML writer on the bytes not yet copied into the destination, so that a row rejected
+  /// partway through is not written
+  writer: Writer<Vec<u8>>,
+  votable: VOTable<VoidTableDataContent>,
+  data_type: TableOrBinOrBin2,
+  schema: TableSchema,
+  /// Binary data not yet base64 encoded (less than `N_BYTES_PER_LINE` bytes)
+  pending_bin: Vec<u8>,
+  n_rows: usize,
+  /// An I/O error occurred, so the destination may contain a partially written part
+  poisoned: bool,
+}
+
+impl<W: Write> VOTableRowWriter<W> {
+  /// Write the given header VOTable till the beginning of the data of its first table.
+  /// # Params
+  /// * `write`: the destination
+  /// * `votable`: the VOTable metadata, must contain at least one table
+  /// * `data_type`: the serialization of the rows (`Fits` not supported)
+  pub fn new(
+    write: W,
+    mut votable: VOTable<VoidTableDataContent>,
+    data_type: TableOrBinOrBin2,
+  ) -> Result<Self, VOTableError> {
+    let table = votable
+      .get_first_table_mut()
+      .ok_or_else(|| VOTableError::Custom(String::from("No table found in the VOTable")))?;
+    if table.data.is_none() {
+      table.set_data_by_ref(Data::new_empty());
+    }
+    match data_type {
+      TableOrBinOrBin2::TableData => table.to_tabledata(),
+      TableOrBinOrBin2::Binary => table.to_binary(),
+      TableOrBinOrBin2::Binary2 => table.to_binary2(),
+      TableOrBinOrBin2::Fits(_) => Err(VOTableError::Custom(String::from(
+        "FITS serialization not supported",
+      ))),
+    }?;
+    let schema = TableSchema::from(table.elems.as_slice());
+    let mut writer = new_xml_writer(Vec::new(), None, None);
+    votable.write_to_data_beginning(&mut writer, &(), false)?;
+    let mut row_writer = Self {
+      write: Some(write),
+      writer,
+      votable,
+      data_type,
+      schema,
+      pending_bin: Vec::with_capacity(N_BYTES_PER_LINE),
+      n_rows: 0,
+      poisoned: false,
+    };
+    row_writer.write_buffered()?;
+    Ok(row_writer)
+  }
+
+  pub fn data_type(&self) -> &TableOrBinOrBin2 {
+    &self.data_type
+  }
+
+  pub fn schema(&self) -> &TableSchema {
+    &self.schema
+  }
+
+  /// Returns the number of rows written so far.
+  pub fn n_rows(&self) -> usize {
+    self.n_rows
+  }
+
+  /// Write the given row, after having checked its number of fields and the type of each
+  /// value (values of a smaller type are casted, e.g. a `Short` in a `Int` column).
+  pub fn write_row(&mut self, row: &[VOTableValue]) -> Result<(), VOTableError> {
+    self.check_not_poisoned()?;
+    self.check_n_fields(row.len())?;
+    let mut row = row.to_vec();
+    for (schema, value) in self.schema.iter().zip(row.iter_mut()) {
+      schema
+        .replace_by_proper_value_if_necessary(value)
+        .map_err(VOTableError::Custom)?;
+    }
+    self.write_checked_row(&row)
+  }
+
+  /// Write the given row made of raw `TABLEDATA` fields, i.e. of the (unescaped) strings
+  /// which would be found in each `TD` tag, after having parsed them according to the schema.
+  pub fn write_raw_row<S: AsRef<str>>(&mut self, fields: &[S]) -> Result<(), VOTableError> {
+    self.check_not_poisoned()?;
+    self.check_n_fields(fields.len())?;
+    let row = self
+      .schema
+      .iter()
+      .zip(fields)
+      .map(|(schema, field)| schema.value_from_str(field.as_ref()))
+      .collect::<Result<Vec<VOTableValue>, VOTableError>>()?;
+    self.write_checked_row(&row)
+  }
+
+  /// Write all the rows of the given iterator.
+  pub fn write_rows<I>(&mut self, rows: I) -> Result<(), VOTableError>
+  where
+    I: Iterator,
+    I::Item: AsRef<[VOTableValue]>,
+  {
+    for row in rows {
+      self.write_row(row.as_ref())?;
+    }
+    Ok(())
+  }
+
+  /// Write the end of the VOTable, flush it and returns the underlying writer.
+  pub fn finish(mut self) -> Result<W, VOTableError> {
+    self.check_not_poisoned()?;
+    // The destination is only taken here or when the writer is dropped
+    let write = self.write.take().unwrap();
+    self.write_end(write)
+  }
+
+  fn write_end(&mut self, mut write: W) -> Result<W, VOTableError> {
+    if !self.pending_bin.is_empty() {
+      // Last base64 line, with padding if needed
+      let b64 = general_purpose::STANDARD.encode(&self.pending_bin);
+      self.pending_bin.clear();
+      self.writer.inner().extend_from_slice(b64.as_bytes());
+    }
+    self
+      .votable
+      .write_from_data_end(&mut self.writer, &(), false)?;
+    write
+      .write_all(self.writer.inner())
+      .and_then(|()| write.flush())
+      .map_err(VOTableError::Io)?;
+    self.writer.inner().clear();
+    Ok(write)
+  }
+
+  /// Copy the buffered bytes into the destination.
+  fn write_buffered(&mut self) -> Result<(), VOTableError> {
+    let res = match self.write.as_mut() {
+      Some(write) => write
+        .write_all(self.writer.inner())
+        .map_err(VOTableError::Io),
+      None => Ok(()),
+    };
+    self.writer.inner().clear();
+    self.poisoned = res.is_err();
+    res
+  }
+
+  fn check_not_poisoned(&self) -> Result<(), VOTableError> {
+    if self.poisoned {
+      Err(VOTableError::Custom(String::from(
+        "VOTable writer unusable after a previous I/O error",
+      )))
+    } else {
+      Ok(())
+    }
+  }
+
+  fn check_n_fields(&self, n_fields: usize) -> Result<(), VOTableError> {
+    if n_fields == self.schema.as_slice().len() {
+      Ok(())
+    } else {
+      Err(VOTableError::WrongFieldNumber(
+        self.schema.as_slice().len(),
+        n_fields,
+      ))
+    }
+  }
+
+  fn write_checked_row(&mut self, row: &[VOTableValue]) -> Result<(), VOTableError> {
+    match &self.data_type {
+      TableOrBinOrBin2::TableData => {
+        let len = self.writer.inner().len();
+        let tr_tag = BytesStart::borrowed_name(b"TR");
+        let res = self
+          .writer
+          .write_event(Event::Start(tr_tag.to_borrowed()))
+          .map_err(VOTableError::Write)
+          .and_then(|()| {
+            InMemTableDataRows::write_tabledata_row(&mut self.writer, row.iter(), &self.schema)
+          })
+          .and_then(|()| {
+            self
+              .writer
+              .write_event(Event::End(tr_tag.to_end()))
+              .map_err(VOTableError::Write)
+          });
+        if res.is_err() {
+          // Remove the bytes of the partially written row
+          self.writer.inner().truncate(len);
+        }
+        res?;
+      }
+      data_type => {
+        let len = self.pending_bin.len();
+        let mut serializer = BinarySerializer::new(&mut self.pending_bin);
+        let res = match data_type {
+          TableOrBinOrBin2::Binary => {
+            InMemTableDataRows::write_binary_row(&mut serializer, row.iter(), &self.schema)
+          }
+          _ => InMemTableDataRows::write_binary2_row(&mut serializer, row, &self.schema),
+        };
+        if res.is_err() {
+          // Remove the bytes of the partially written row
+          self.pending_bin.truncate(len);
+        }
+        res?;
+        self.write_base64_lines();
+      }
+    }
+    self.write_buffered()?;
+    self.n_rows += 1;
+    Ok(())
+  }
+
+  /// Encode all full base64 lines, keeping the remaining bytes for later.
+  fn write_base64_lines(&mut self) {
+    let n_lines = self.pending_bin.len() / N_BYTES_PER_LINE;
+    if n_lines > 0 {
+      let mut b64 = String::with_capacity(n_lines * 65);
+      for line in self.pending_bin[..n_lines * N_BYTES_PER_LINE].chunks(N_BYTES_PER_LINE) {
+        general_purpose::STANDARD.encode_string(line, &mut b64);
+        b64.push('\n');
+      }
+      self.pending_bin.drain(..n_lines * N_BYTES_PER_LINE);
+      self.writer.inner().extend_from_slice(b64.as_bytes());
+    }
+  }
+}
+
+impl<W: Write> Drop for VOTableRowWriter<W> {
+  fn drop(&mut self) {
+    // Errors are ignored here, call `finish` to get them
+    if self.poisoned {
+      return;
+    }
+    if let Some(write) = self.write.take() {
+      let _ = self.write_end(write);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::{self, Write};
+
+  use super::VOTableRowWriter;
+  use crate::{
+    data::TableOrBinOrBin2,
+    error::VOTableError,
+    impls::{mem::VoidTableDataContent, VOTableValue},
+    iter::SimpleVOTableRowIterator,
+    votable::VOTable,
+  };
+
+  fn read(bytes: &[u8]) -> (Vec<Vec<VOTableValue>>, VOTable<VoidTableDataContent>) {
+    let mut it = SimpleVOTableRowIterator::from_reader(bytes).unwrap();
+    let rows = it
+      .to_row_value_iter()
+      .collect::<Result<Vec<_>, VOTableError>>()
+      .unwrap();
+    (rows, it.read_to_end().unwrap())
+  }
+
+  #[test]
+  fn test_row_writer() {
+    let (rows, votable) = read(&std::fs::read("resources/sdss12.vot").unwrap());
+    for data_type in [
+      TableOrBinOrBin2::TableData,
+      TableOrBinOrBin2::Binary,
+      TableOrBinOrBin2::Binary2,
+    ] {
+      let is_binary = matches!(data_type, TableOrBinOrBin2::Binary);
+      let mut writer = VOTableRowWriter::new(Vec::new(), votable.clone(), data_type).unwrap();
+      writer.write_rows(rows.iter()).unwrap();
+      assert_eq!(writer.n_rows(), rows.len());
+      let (actual_rows, _) = read(&writer.finish().unwrap());
+      assert_eq!(actual_rows.len(), rows.len());
+      // BINARY does not support NULL strings
+      if !is_binary {
+        assert_eq!(actual_rows, rows);
+      }
+    }
+  }
+
+  #[test]
+  fn test_row_writer_raw_rows_and_drop() {
+    let (rows, votable) = read(&std::fs::read("resources/sdss12.vot").unwrap());
+    let mut bytes: Vec<u8> = Vec::new();
+    {
+      let mut writer =
+        VOTableRowWriter::new(&mut bytes, votable, TableOrBinOrBin2::Binary2).unwrap();
+      for row in rows.iter() {
+        let fields: Vec<String> = row
+          .iter()
+          .map(|value| match value {
+            VOTableValue::Null => String::new(),
+            _ => value.to_string(),
+          })
+          .collect();
+        writer.write_raw_row(&fields).unwrap();
+      }
+      // Wrong number of fields
+      assert!(matches!(
+        writer.write_raw_row(&["1", "2"]),
+        Err(VOTableError::WrongFieldNumber(_, 2))
+      ));
+      // Wrong value type
+      let mut row = rows[0].clone();
+      row[0] = VOTableValue::CharASCII('x');
+      assert!(writer.write_row(&row).is_err());
+      assert_eq!(writer.n_rows(), rows.len());
+      // The VOTable is finished when the writer is dropped
+    }
+    let (actual_rows, _) = read(&bytes);
+    assert_eq!(actual_rows, rows);
+  }
+
+  /// Destination failing, without writing anything, when its capacity would be exceeded.
+  struct LimitedWrite {
+    bytes: Vec<u8>,
+    capacity: usize,
+  }
+
+  impl Write for LimitedWrite {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      if self.bytes.len() + buf.len() > self.capacity {
+        Err(io::Error::other("No space left"))
+      } else {
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+      }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn test_row_writer_row_rejected_partway() {
+    let (rows, votable) = read(&std::fs::read("resources/sdss12.vot").unwrap());
+    // Wrong type in the last column, detected once the other columns have been serialized
+    let mut bad_row = rows[0].clone();
+    *bad_row.last_mut().unwrap() = VOTableValue::String(String::from("x"));
+    for data_type in [TableOrBinOrBin2::Binary, TableOrBinOrBin2::Binary2] {
+      let mut writer = VOTableRowWriter::new(Vec::new(), votable.clone(), data_type).unwrap();
+      writer.write_row(&rows[0]).unwrap();
+      assert!(writer.write_checked_row(&bad_row).is_err());
+      writer.write_row(&rows[1]).unwrap();
+      assert_eq!(writer.n_rows(), 2);
+      let (actual_rows, _) = read(&writer.finish().unwrap());
+      assert_eq!(actual_rows.len(), 2);
+      assert_eq!(actual_rows[1][0], rows[1][0]);
+    }
+    // Destination full in the middle of a row: the row is not written at all
+    let mut bytes = Vec::new();
+    let mut writer =
+      VOTableRowWriter::new(&mut bytes, votable.clone(), TableOrBinOrBin2::TableData).unwrap();
+    writer.write_rows(rows.iter().take(2)).unwrap();
+    drop(writer);
+    let header_and_two_rows_len = memchr::memmem::rfind(&bytes, b"</TR>").unwrap() + 5;
+    let mut writer = VOTableRowWriter::new(
+      LimitedWrite {
+        bytes: Vec::new(),
+        capacity: header_and_two_rows_len + 100,
+      },
+      votable,
+      TableOrBinOrBin2::TableData,
+    )
+    .unwrap();
+    writer.write_rows(rows.iter().take(2)).unwrap();
+    assert!(matches!(
+      writer.write_row(&rows[2]),
+      Err(VOTableError::Io(_))
+    ));
+    assert_eq!(writer.n_rows(), 2);
+    let write = writer.write.take().unwrap();
+    assert_eq!(write.bytes, bytes[..header_and_two_rows_len]);
+  }
+
+  /// Destination failing once, after `n_ok` successful writes.
+  struct FailingOnceWrite {
+    bytes: Vec<u8>,
+    n_ok: usize,
+    failed: bool,
+  }
+
+  impl Write for FailingOnceWrite {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      if self.n_ok == 0 && !self.failed {
+        self.failed = true;
+        Err(io::Error::other("Connection reset"))
+      } else {
+        self.n_ok = self.n_ok.saturating_sub(1);
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+      }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn test_row_writer_poisoned() {
+    let (rows, votable) = read(&std::fs::read("resources/sdss12.vot").unwrap());
+    for data_type in [TableOrBinOrBin2::TableData, TableOrBinOrBin2::Binary2] {
+      // The header, then the first row, are written successfully
+      let mut write = FailingOnceWrite {
+        bytes: Vec::new(),
+        n_ok: 2,
+        failed: false,
+      };
+      let mut writer = VOTableRowWriter::new(&mut write, votable.clone(), data_type).unwrap();
+      writer.write_row(&rows[0]).unwrap();
+      let len = writer.write.as_ref().unwrap().bytes.len();
+      assert!(matches!(
+        writer.write_row(&rows[1]),
+        Err(VOTableError::Io(_))
+      ));
+      // Even if the destination is writable again, nothing more is written
+      assert!(writer.write_row(&rows[2]).is_err());
+      assert!(writer.write_rows(rows.iter().skip(3)).is_err());
+      assert_eq!(writer.n_rows(), 1);
+      assert!(writer.finish().is_err());
+      assert_eq!(write.bytes.len(), len);
+    }
+  }
+}
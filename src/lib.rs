@@ -28,9 +28,12 @@ use quick_xml::{
 #[macro_use]
 mod macros;
 mod utils;
+mod walk;
 
 pub mod coosys;
+pub mod dali;
 pub mod data;
+pub mod datalink;
 pub mod datatype;
 pub mod definitions;
 pub mod desc;
@@ -45,16 +48,23 @@ pub mod link;
 pub mod param;
 pub mod paramref;
 pub mod resource;
+pub mod standards;
 pub mod table;
 pub mod timesys;
+pub mod ucd;
+pub mod units;
 pub mod values;
 pub mod votable;
 
+#[cfg(feature = "async")]
+pub mod asynch;
 #[cfg(feature = "mivot")]
 pub mod mivot;
 
 #[cfg(feature = "mivot")]
 pub use self::mivot::VodmlVisitor;
+#[cfg(feature = "derive")]
+pub use votable_derive::VOTableRow;
 use self::utils::{discard_comment, discard_event};
 pub use self::{
   coosys::CooSys,
@@ -67,7 +77,7 @@ pub use self::{
   field::Field,
   fieldref::FieldRef,
   group::{Group, TableGroup},
-  impls::mem::VoidTableDataContent,
+  impls::{mem::VoidTableDataContent, typed::VOTableRow},
   info::Info,
   link::Link,
   param::Param,
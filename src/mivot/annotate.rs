@@ -0,0 +1,925 @@
+//! Automatic generation of MIVOT annotations from the metadata of a `TABLE`.
+//!
+//! The [Annotator] proposes a `VODML` block mapping the columns of a table on the
+//! [MANGO](https://github.com/ivoa-std/MANGO) model. The columns are identified from their UCDs
+//! (and checked against their units):
+//! * a `mango:EpochPosition` is made of the positions (`pos.eq.ra`/`pos.eq.dec`, or their galactic
+//!   or ecliptic equivalents), the proper motions (`pos.pm`), the parallax (`pos.parallax`), the
+//!   radial velocity (`spect.dopplerVeloc`), the epoch (`time.epoch`) and their errors;
+//! * a `mango:Brightness` is made of each magnitude or flux column (`phot.mag`, `phot.flux`),
+//!   with its error.
+//!
+//! The properties are put in the property dock of a `mango:MangoObject`, identified by the
+//! `meta.id` column (if any).
+//! A `coords:SpaceSys` (resp. `coords:TimeSys`) is built in `GLOBALS` from the `COOSYS`
+//! (resp. `TIMESYS`) referred to by the position (resp. epoch) columns.
+//! If the epoch is not given by a column, it is taken from the `COOSYS` `epoch`.
+//!
+//! The error of a column is the `stat.error` column in the same `GROUP`, if any, or else the column
+//! having the UCD `stat.error` followed by the words of the value column UCD
+//! (e.g. `stat.error;pos.eq.ra` for `pos.eq.ra;meta.main`).
+//!
+//! `ref`s (and `tableref`) are made of `FIELD` (and `TABLE`) `ID`s: an `ID` is generated, from the
+//! name, for the table and for each `FIELD` having none.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+  coosys::CooSys,
+  datatype::Datatype,
+  error::VOTableError,
+  field::Field,
+  group::{TableGroup, TableGroupElem},
+  resource::ResourceElem,
+  table::{Table, TableElem},
+  timesys::TimeSys,
+  ucd::META_MAIN,
+  units::Unit,
+  votable::{VOTable, VOTableElem},
+  walk::{walk_votable, Meta},
+  TableDataContent,
+};
+
+use super::{
+  attribute::AttributeChildOfInstance as Attribute,
+  builder::TimeSys as TimeSysBuilder,
+  globals::{
+    instance::{
+      instance::Instance as GlobalsChildInstance, reference::Reference as StaticReference,
+      Instance as GlobalsInstance,
+    },
+    Globals,
+  },
+  model::Model,
+  report::{Report, Status},
+  templates::{
+    instance::{
+      collection::Collection, instance::Instance as ChildInstance, reference::Reference, Instance,
+    },
+    Templates,
+  },
+  vodml::Vodml,
+};
+
+/// MIVOT namespace, for the `xmlns` attribute of `VODML`.
+pub const MIVOT_XMLNS: &str = "http://www.ivoa.net/xml/mivot";
+
+/// Models used in the generated annotations, with their URL (if known).
+const MODELS: [(&str, Option<&str>); 3] = [
+  (
+    "ivoa",
+    Some("https://www.ivoa.net/xml/VODML/IVOA-v1.vo-dml.xml"),
+  ),
+  (
+    "coords",
+    Some("https://www.ivoa.net/xml/STC/20200908/Coords-v1.0.vo-dml.xml"),
+  ),
+  ("mango", None),
+];
+
+/// UCD primary words of the (longitude, latitude) columns of supported positions, with the
+/// `spaceRefFrame` to be used if no `COOSYS` is found.
+const POSITIONS: [(&str, &str, Option<&str>); 3] = [
+  ("pos.eq.ra", "pos.eq.dec", None),
+  ("pos.galactic.lon", "pos.galactic.lat", Some("GALACTIC")),
+  ("pos.ecliptic.lon", "pos.ecliptic.lat", Some("ECLIPTIC")),
+];
+
+/// Generates MIVOT annotations of tables, knowing the `COOSYS` and `TIMESYS` they may refer to.
+#[derive(Clone, Debug, Default)]
+pub struct Annotator {
+  coosys: Vec<CooSys>,
+  timesys: Vec<TimeSys>,
+  /// `ID`s already in use, not to be generated
+  ids: HashSet<String>,
+}
+
+impl Annotator {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Creates an annotator knowing all the `COOSYS` and `TIMESYS` of the given VOTable, and the
+  /// `ID`s of its `RESOURCE`s, `TABLE`s, `FIELD`s (and their `VALUES`) and `PARAM`s.
+  pub fn from_votable<C: TableDataContent>(votable: &VOTable<C>) -> Self {
+    let mut annotator = Self::new();
+    for elem in votable.elems.iter() {
+      match elem {
+        VOTableElem::CooSys(coosys) => annotator.push_coosys_by_ref(coosys.as_ref().clone()),
+        VOTableElem::TimeSys(timesys) => annotator.push_timesys_by_ref(timesys.as_ref().clone()),
+        _ => (),
+      }
+    }
+    walk_votable(votable, &mut |meta| match meta {
+      Meta::ResourceStart(resource) => {
+        annotator.ids.extend(resource.id.clone());
+        for elem in resource.elems.iter() {
+          match elem {
+            ResourceElem::CooSys(coosys) => annotator.push_coosys_by_ref(coosys.as_ref().clone()),
+            ResourceElem::TimeSys(timesys) => {
+              annotator.push_timesys_by_ref(timesys.as_ref().clone())
+            }
+            _ => (),
+          }
+        }
+      }
+      Meta::TableStart(table) => annotator.ids.extend(table.id.clone()),
+      Meta::Field(field) => {
+        annotator.ids.extend(field.id.clone());
+        annotator
+          .ids
+          .extend(field.values.as_ref().and_then(|values| values.id.clone()));
+      }
+      Meta::Param(param) => annotator.ids.extend(param.field.id.clone()),
+      _ => (),
+    });
+    annotator
+  }
+
+  pub fn push_coosys(mut self, coosys: CooSys) -> Self {
+    self.push_coosys_by_ref(coosys);
+    self
+  }
+
+  pub fn push_coosys_by_ref(&mut self, coosys: CooSys) {
+    self.ids.insert(coosys.id.clone());
+    self.coosys.push(coosys);
+  }
+
+  pub fn push_timesys(mut self, timesys: TimeSys) -> Self {
+    self.push_timesys_by_ref(timesys);
+    self
+  }
+
+  pub fn push_timesys_by_ref(&mut self, timesys: TimeSys) {
+    self.ids.insert(timesys.id.clone());
+    self.timesys.push(timesys);
+  }
+
+  /// Returns the `VODML` block annotating the given table, to be put in a `RESOURCE` of type
+  /// `meta` in the `RESOURCE` containing the table.
+  /// The missing `ID`s of the table and of its `FIELD`s are generated and set, so that the
+  /// annotations do not refer to names.
+  /// # Errors
+  /// If no column of the table can be mapped (the table is then left untouched).
+  pub fn annotate<C: TableDataContent>(&self, table: &mut Table<C>) -> Result<Vodml, VOTableError> {
+    let new_ids = NewIds::new(table, &self.ids);
+    let vodml = self.annotate_with_ids(table, &new_ids)?;
+    new_ids.set(table);
+    Ok(vodml)
+  }
+
+  fn annotate_with_ids<C: TableDataContent>(
+    &self,
+    table: &Table<C>,
+    new_ids: &NewIds,
+  ) -> Result<Vodml, VOTableError> {
+    let columns = Columns::new(table, new_ids);
+    let mut globals = Globals::new();
+    let mut properties = Vec::new();
+    if let Some(position) = self.epoch_position(&columns, &mut globals) {
+      properties.push(position);
+    }
+    for column in columns
+      .columns
+      .iter()
+      .filter(|column| column.has_primary("phot.mag") || column.has_primary("phot.flux"))
+    {
+      properties.push(brightness(&columns, column));
+    }
+    if properties.is_empty() {
+      return Err(VOTableError::Custom(format!(
+        "No column of table '{}' can be mapped on the MANGO model",
+        table
+          .name
+          .as_ref()
+          .or(table.id.as_ref())
+          .map_or("", String::as_str)
+      )));
+    }
+    let mut object = Instance::new("mango:MangoObject");
+    if let Some(id) = columns.find("meta.id", &[]) {
+      object.push_attribute_by_ref(Attribute::from_ref(
+        "mango:MangoObject.identifier",
+        "ivoa:string",
+        id.ref_(),
+      ));
+    }
+    object.push_collection_by_ref(Collection::from_instances(
+      "mango:MangoObject.propertyDock",
+      properties,
+    )?);
+    let tableref = table.id.as_ref().unwrap_or(&new_ids.table);
+    let templates = Templates::new()
+      .set_tableref(tableref)
+      .push_instance(object);
+    let mut vodml = Vodml::new().set_xmlns(MIVOT_XMLNS).set_report(
+      Report::new(Status::OK).set_content("MIVOT annotation generated from FIELD metadata"),
+    );
+    for (name, url) in MODELS {
+      if name != "coords" || !globals.elems.is_empty() {
+        let model = Model::new(name);
+        vodml.push_model_by_ref(match url {
+          Some(url) => model.set_url(url),
+          None => model,
+        });
+      }
+    }
+    if !globals.elems.is_empty() {
+      vodml.set_globals_by_ref(globals);
+    }
+    Ok(vodml.push_templates(templates))
+  }
+
+  /// Returns the `mango:EpochPosition`, if position columns are found, pushing its space and time
+  /// systems in `globals`.
+  fn epoch_position(&self, columns: &Columns, globals: &mut Globals) -> Option<Instance> {
+    let (lon, lat, default_frame) = POSITIONS.iter().find_map(|(lon, lat, frame)| {
+      let lon = columns.find(lon, &[]).filter(|c| c.has_unit_like("deg"))?;
+      let lat = columns.find(lat, &[]).filter(|c| c.has_unit_like("deg"))?;
+      Some((lon, lat, *frame))
+    })?;
+    let [lon_word, lat_word] = [&lon.words[0], &lat.words[0]];
+    let mut instance = Instance::new("mango:EpochPosition")
+      .push_attribute(quantity("mango:EpochPosition.longitude", lon))
+      .push_attribute(quantity("mango:EpochPosition.latitude", lat));
+    let parallax = columns
+      .find("pos.parallax", &[])
+      .filter(|c| c.has_unit_like("mas"));
+    let rv = columns
+      .find("spect.dopplerveloc", &[])
+      .filter(|c| c.has_unit_like("km/s"));
+    let pm_lon = columns
+      .find("pos.pm", &[lon_word])
+      .filter(|c| c.has_unit_like("mas/yr"));
+    let pm_lat = columns
+      .find("pos.pm", &[lat_word])
+      .filter(|c| c.has_unit_like("mas/yr"));
+    for (dmrole, column) in [
+      ("mango:EpochPosition.parallax", parallax),
+      ("mango:EpochPosition.radialVelocity", rv),
+      ("mango:EpochPosition.pmLongitude", pm_lon),
+      ("mango:EpochPosition.pmLatitude", pm_lat),
+    ] {
+      if let Some(column) = column {
+        instance.push_attribute_by_ref(quantity(dmrole, column));
+      }
+    }
+    // Space and time systems
+    let coosys = lon
+      .field
+      .ref_
+      .as_deref()
+      .and_then(|ref_| self.coosys.iter().find(|coosys| coosys.id == ref_))
+      .or_else(|| (self.coosys.len() == 1).then(|| &self.coosys[0]));
+    let epoch = columns.find("time.epoch", &[]);
+    let (obs_date, timesys) = match epoch {
+      Some(epoch) => {
+        let timesys = epoch
+          .field
+          .ref_
+          .as_deref()
+          .and_then(|ref_| self.timesys.iter().find(|timesys| timesys.id == ref_));
+        (obs_date_from_column(epoch, timesys), timesys)
+      }
+      None => (
+        coosys.and_then(|coosys| obs_date_from_coosys(coosys, columns)),
+        None,
+      ),
+    };
+    if let Some(obs_date) = obs_date {
+      instance.push_instance_by_ref(obs_date);
+    }
+    // Errors
+    let mut errors = ChildInstance::new("mango:EpochPosition.errors", "mango:EpochPositionErrors");
+    for (dmrole, value1, value2) in [
+      ("mango:EpochPositionErrors.position", Some(lon), Some(lat)),
+      ("mango:EpochPositionErrors.properMotion", pm_lon, pm_lat),
+    ] {
+      if let Some(error) = value1
+        .zip(value2)
+        .and_then(|(value1, value2)| error_2d(columns, dmrole, value1, value2))
+      {
+        errors.push_instance_by_ref(error);
+      }
+    }
+    for (dmrole, value) in [
+      ("mango:EpochPositionErrors.parallax", parallax),
+      ("mango:EpochPositionErrors.radialVelocity", rv),
+    ] {
+      if let Some(error) = value.and_then(|value| columns.error_of(value)) {
+        errors.push_instance_by_ref(error_1d(dmrole, error));
+      }
+    }
+    if !errors.elems.is_empty() {
+      instance.push_instance_by_ref(errors);
+    }
+    let space_sys = match (coosys, default_frame) {
+      (Some(coosys), _) => Some(space_sys_from_coosys(coosys)),
+      (None, Some(frame)) => Some(space_sys(&frame.to_lowercase(), frame, None, None)),
+      (None, None) => None,
+    };
+    if let Some(space_sys) = space_sys {
+      instance.push_reference_by_ref(Reference::Static(StaticReference::new(
+        "mango:EpochPosition.spaceSys",
+        space_sys.dmid.as_deref().unwrap_or_default(),
+      )));
+      globals.push_instance_by_ref(space_sys);
+    }
+    if let Some(timesys) = timesys {
+      let time_sys = time_sys(timesys);
+      instance.push_reference_by_ref(Reference::Static(StaticReference::new(
+        "mango:EpochPosition.timeSys",
+        time_sys.dmid.as_deref().unwrap_or_default(),
+      )));
+      globals.push_instance_by_ref(time_sys);
+    }
+    Some(instance)
+  }
+}
+
+/// `ID`s to be set on a table and on its `FIELD`s having none.
+struct NewIds {
+  /// Used only if the table has no `ID`
+  table: String,
+  /// By index of the `FIELD` in the table elements
+  fields: HashMap<usize, String>,
+}
+
+impl NewIds {
+  fn new<C: TableDataContent>(table: &Table<C>, ids: &HashSet<String>) -> Self {
+    let mut ids = ids.clone();
+    let table_id = match &table.id {
+      Some(id) => id.clone(),
+      None => new_id(table.name.as_deref().unwrap_or("table"), &mut ids),
+    };
+    let fields = table
+      .elems
+      .iter()
+      .enumerate()
+      .filter_map(|(i, elem)| match elem {
+        TableElem::Field(field) if field.id.is_none() => Some((i, new_id(&field.name, &mut ids))),
+        _ => None,
+      })
+      .collect();
+    Self {
+      table: table_id,
+      fields,
+    }
+  }
+
+  fn set<C: TableDataContent>(mut self, table: &mut Table<C>) {
+    if table.id.is_none() {
+      table.id = Some(self.table);
+    }
+    for (i, elem) in table.elems.iter_mut().enumerate() {
+      if let (TableElem::Field(field), Some(id)) = (elem, self.fields.remove(&i)) {
+        field.id = Some(id);
+      }
+    }
+  }
+}
+
+/// Returns a valid `ID` (`NCName`) made from the given name, not in `ids`, and adds it to `ids`.
+fn new_id(name: &str, ids: &mut HashSet<String>) -> String {
+  let mut id: String = name
+    .chars()
+    .map(|c| match c {
+      'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' => c,
+      _ => '_',
+    })
+    .collect();
+  if !id.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+    id.insert(0, '_');
+  }
+  let mut unique = id.clone();
+  let mut i = 1;
+  while ids.contains(&unique) {
+    i += 1;
+    unique = format!("{}_{}", id, i);
+  }
+  ids.insert(unique.clone());
+  unique
+}
+
+/// A `FIELD` having a UCD.
+struct Column<'t> {
+  field: &'t Field,
+  /// `ID` of the `FIELD`, possibly generated
+  id: &'t str,
+  /// Lower case UCD words, without `meta.main`
+  words: Vec<String>,
+  main: bool,
+}
+
+impl<'t> Column<'t> {
+  fn new(field: &'t Field, id: &'t str) -> Option<Self> {
+    field.parsed_ucd().map(|ucd| Self {
+      field,
+      id,
+      words: ucd
+        .words()
+        .iter()
+        .filter(|word| !word.eq_ignore_ascii_case(META_MAIN))
+        .map(|word| word.to_lowercase())
+        .collect(),
+      main: ucd.is_main(),
+    })
+  }
+
+  /// Value of the `ref` pointing to the column.
+  fn ref_(&self) -> &str {
+    self.id
+  }
+
+  /// Tells whether the given `ref` (to an `ID` or a name) points to the column.
+  fn is_referred_to_by(&self, ref_: &str) -> bool {
+    self.field.id.as_deref() == Some(ref_) || self.field.name == ref_
+  }
+
+  fn has_primary(&self, word: &str) -> bool {
+    self.words.first().is_some_and(|primary| {
+      primary == word
+        || primary
+          .strip_prefix(word)
+          .is_some_and(|suffix| suffix.starts_with('.'))
+    })
+  }
+
+  fn is_error_of(&self, value: &Column) -> bool {
+    self.words.first().map(String::as_str) == Some("stat.error") && self.words[1..] == value.words
+  }
+
+  /// Tells whether the unit of the column (if any) is equivalent to the given unit.
+  fn has_unit_like(&self, unit: &str) -> bool {
+    match (
+      self.field.unit.as_deref().map(str::parse::<Unit>),
+      unit.parse::<Unit>(),
+    ) {
+      (None, _) => true,
+      (Some(Ok(actual)), Ok(expected)) => actual.is_equivalent(&expected),
+      _ => false,
+    }
+  }
+
+  /// Tells whether the unit of the column is the given unit, possibly with a different notation.
+  fn has_unit(&self, unit: &str) -> bool {
+    match (
+      self.field.unit.as_deref().map(str::parse::<Unit>),
+      unit.parse::<Unit>(),
+    ) {
+      (Some(Ok(actual)), Ok(expected)) => actual
+        .scale_factor_to(&expected)
+        .is_ok_and(|factor| (factor - 1.0).abs() < 1e-9),
+      _ => false,
+    }
+  }
+}
+
+/// The columns of a table having a UCD, with the `FIELD` groups.
+struct Columns<'t> {
+  columns: Vec<Column<'t>>,
+  /// `ref`s of the `FIELDref` of each `GROUP`
+  groups: Vec<Vec<&'t str>>,
+}
+
+impl<'t> Columns<'t> {
+  fn new<C: TableDataContent>(table: &'t Table<C>, new_ids: &'t NewIds) -> Self {
+    let mut columns = Vec::new();
+    let mut groups = Vec::new();
+    for (i, elem) in table.elems.iter().enumerate() {
+      match elem {
+        TableElem::Field(field) => {
+          let id = field
+            .id
+            .as_ref()
+            .or_else(|| new_ids.fields.get(&i))
+            .map_or(field.name.as_str(), String::as_str);
+          columns.extend(Column::new(field, id))
+        }
+        TableElem::TableGroup(group) => push_group(group, &mut groups),
+        TableElem::Param(_) => (),
+      }
+    }
+    Self { columns, groups }
+  }
+
+  /// Returns the column having the given primary word (or one of its children) and secondary
+  /// words, favouring the `meta.main` column.
+  fn find(&self, primary: &str, secondaries: &[&str]) -> Option<&Column<'t>> {
+    let mut candidates = self.columns.iter().filter(|column| {
+      column.has_primary(primary)
+        && secondaries
+          .iter()
+          .all(|word| column.words[1..].iter().any(|w| w == word))
+    });
+    let first = candidates.next()?;
+    if first.main {
+      Some(first)
+    } else {
+      candidates.find(|column| column.main).or(Some(first))
+    }
+  }
+
+  /// Returns the column (referred to by `ID` or name) having the given `ref`.
+  fn by_ref(&self, ref_: &str) -> Option<&Column<'t>> {
+    self
+      .columns
+      .iter()
+      .find(|column| column.field.id.as_deref() == Some(ref_))
+      .or_else(|| self.columns.iter().find(|column| column.field.name == ref_))
+  }
+
+  /// Returns the error column of the given column.
+  fn error_of(&self, value: &Column) -> Option<&Column<'t>> {
+    self
+      .groups
+      .iter()
+      .filter(|refs| refs.iter().any(|ref_| value.is_referred_to_by(ref_)))
+      .flat_map(|refs| refs.iter().filter_map(|ref_| self.by_ref(ref_)))
+      .find(|column| column.has_primary("stat.error"))
+      .or_else(|| self.columns.iter().find(|column| column.is_error_of(value)))
+  }
+
+  /// Returns the column of the correlation between the two given columns.
+  fn correlation_of(&self, value1: &Column, value2: &Column) -> Option<&Column<'t>> {
+    let mut words: Vec<&String> = value1.words.iter().chain(value2.words.iter()).collect();
+    words.sort();
+    words.dedup();
+    self.columns.iter().find(|column| {
+      let mut secondaries: Vec<&String> = column.words[1..].iter().collect();
+      secondaries.sort();
+      column.has_primary("stat.correlation") && secondaries == words
+    })
+  }
+}
+
+fn push_group<'t>(group: &'t TableGroup, groups: &mut Vec<Vec<&'t str>>) {
+  let mut refs = Vec::new();
+  for elem in group.elems.iter() {
+    match elem {
+      TableGroupElem::FieldRef(fieldref) => refs.push(fieldref.ref_.as_str()),
+      TableGroupElem::TableGroup(group) => push_group(group, groups),
+      _ => (),
+    }
+  }
+  if !refs.is_empty() {
+    groups.push(refs);
+  }
+}
+
+/// `ATTRIBUTE` of type `ivoa:RealQuantity` mapping the given column.
+fn quantity(dmrole: &str, column: &Column) -> Attribute {
+  real(dmrole, "ivoa:RealQuantity", column)
+}
+
+fn real(dmrole: &str, dmtype: &str, column: &Column) -> Attribute {
+  let attribute = Attribute::from_ref(dmrole, dmtype, column.ref_());
+  match &column.field.unit {
+    Some(unit) => attribute.set_unit(unit),
+    None => attribute,
+  }
+}
+
+/// `mango:error.PropertyError1D` made of the given error column.
+fn error_1d(dmrole: &str, error: &Column) -> ChildInstance {
+  ChildInstance::new(dmrole, "mango:error.PropertyError1D").push_attribute(real(
+    "mango:error.PropertyError1D.sigma",
+    "ivoa:real",
+    error,
+  ))
+}
+
+/// `mango:error.ErrorCorrMatrix` made of the errors (and correlation) of the two given columns.
+fn error_2d(
+  columns: &Columns,
+  dmrole: &str,
+  value1: &Column,
+  value2: &Column,
+) -> Option<ChildInstance> {
+  let sigma1 = columns.error_of(value1)?;
+  let sigma2 = columns.error_of(value2)?;
+  let mut error = ChildInstance::new(dmrole, "mango:error.ErrorCorrMatrix")
+    .push_attribute(real(
+      "mango:error.ErrorCorrMatrix.sigma1",
+      "ivoa:real",
+      sigma1,
+    ))
+    .push_attribute(real(
+      "mango:error.ErrorCorrMatrix.sigma2",
+      "ivoa:real",
+      sigma2,
+    ));
+  if let Some(rho) = columns.correlation_of(value1, value2) {
+    error.push_attribute_by_ref(Attribute::from_ref(
+      "mango:error.ErrorCorrMatrix.rho",
+      "ivoa:real",
+      rho.ref_(),
+    ));
+  }
+  Some(error)
+}
+
+/// `mango:Brightness` mapping the given magnitude (or flux) column.
+fn brightness(columns: &Columns, column: &Column) -> Instance {
+  let mut instance = Instance::new("mango:Brightness")
+    .push_attribute(Attribute::from_val(
+      "mango:Property.label",
+      "ivoa:string",
+      column.field.name.as_str(),
+    ))
+    .push_attribute(quantity("mango:PhotometricProperty.value", column));
+  if let Some(error) = columns.error_of(column) {
+    instance.push_instance_by_ref(error_1d("mango:PhotometricProperty.error", error));
+  }
+  instance
+}
+
+/// `mango:DateTime` made of the given representation (`mango:TimeFormat`) and date.
+fn obs_date(representation: &str, date: Attribute) -> ChildInstance {
+  ChildInstance::new("mango:EpochPosition.obsDate", "mango:DateTime")
+    .push_attribute(Attribute::from_val(
+      "mango:DateTime.representation",
+      "mango:TimeFormat",
+      representation,
+    ))
+    .push_attribute(date)
+}
+
+/// `mango:DateTime` mapping the given epoch column, if its representation is recognized.
+fn obs_date_from_column(epoch: &Column, timesys: Option<&TimeSys>) -> Option<ChildInstance> {
+  let representation = match epoch.field.datatype {
+    Datatype::CharASCII | Datatype::CharUnicode => "iso",
+    _ if epoch.has_unit("d") => match timesys.and_then(|timesys| timesys.timeorigin) {
+      Some(0.0) => "jd",
+      _ => "mjd",
+    },
+    _ if epoch.has_unit("yr") => "jyear",
+    _ => return None,
+  };
+  let date = Attribute::from_ref("mango:DateTime.dateTime", "ivoa:datetime", epoch.ref_());
+  Some(obs_date(representation, date))
+}
+
+/// `mango:DateTime` mapping the `epoch` of the given `COOSYS` (if any).
+fn obs_date_from_coosys(coosys: &CooSys, columns: &Columns) -> Option<ChildInstance> {
+  let epoch = coosys_attribute(coosys, "epoch")?;
+  let (representation, year) = match epoch.split_at(1) {
+    ("J", year) => ("jyear", year),
+    ("B", year) => ("byear", year),
+    _ => {
+      return columns
+        .by_ref(&epoch)
+        .and_then(|column| obs_date_from_column(column, None));
+    }
+  };
+  let date = Attribute::from_val("mango:DateTime.dateTime", "ivoa:datetime", year);
+  Some(obs_date(representation, date))
+}
+
+/// Value of the given attribute (e.g. `system`, `equinox` or `epoch`) of a `COOSYS`.
+fn coosys_attribute(coosys: &CooSys, name: &str) -> Option<String> {
+  let mut value = None;
+  coosys.coosys.for_each_attribute(&mut |key, val| {
+    if key == name {
+      value = Some(val.to_string());
+    }
+  });
+  value
+}
+
+/// `coords:StdRefLocation` of the given reference position.
+fn ref_location(dmrole: &str, position: &str) -> GlobalsChildInstance {
+  GlobalsChildInstance::new(dmrole, "coords:StdRefLocation").push_attribute(Attribute::from_val(
+    "coords:StdRefLocation.position",
+    "ivoa:string",
+    position,
+  ))
+}
+
+/// `coords:SpaceSys` mapping the given `COOSYS`.
+fn space_sys_from_coosys(coosys: &CooSys) -> GlobalsInstance {
+  let system = coosys_attribute(coosys, "system").unwrap_or_default();
+  let frame = match system.as_str() {
+    "eq_FK4" => "FK4",
+    "eq_FK5" => "FK5",
+    "ecl_FK4" | "ecl_FK5" => "ECLIPTIC",
+    "galactic" => "GALACTIC",
+    "supergalactic" => "SUPER_GALACTIC",
+    other => other,
+  };
+  space_sys(
+    &coosys.id,
+    frame,
+    coosys_attribute(coosys, "equinox"),
+    coosys.refposition.as_ref().map(|pos| pos.to_string()),
+  )
+}
+
+fn space_sys(
+  id: &str,
+  frame: &str,
+  equinox: Option<String>,
+  ref_position: Option<String>,
+) -> GlobalsInstance {
+  let mut space_frame =
+    GlobalsChildInstance::new("coords:PhysicalCoordSys.frame", "coords:SpaceFrame").push_attribute(
+      Attribute::from_val("coords:SpaceFrame.spaceRefFrame", "ivoa:string", frame),
+    );
+  if let Some(equinox) = equinox {
+    space_frame.push_attribute_by_ref(Attribute::from_val(
+      String::from("coords:SpaceFrame.equinox"),
+      String::from("coords:Epoch"),
+      equinox,
+    ));
+  }
+  if let Some(ref_position) = ref_position {
+    space_frame.push_instance_by_ref(ref_location("coords:SpaceFrame.refPosition", &ref_position));
+  }
+  GlobalsInstance::new("coords:SpaceSys")
+    .set_dmid(format!("_spacesys_{}", id))
+    .push_instance(space_frame)
+}
+
+/// `coords:TimeSys` mapping the given `TIMESYS`.
+fn time_sys(timesys: &TimeSys) -> GlobalsInstance {
+  TimeSysBuilder::new(
+    format!("_timesys_{}", timesys.id),
+    timesys.timescale.to_string(),
+    timesys.refposition.to_string(),
+  )
+  .into_globals_instance()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Annotator;
+  use crate::{
+    impls::mem::InMemTableDataRows,
+    mivot::{registry::Registry, validate::validate_with_registry},
+    resource::Resource,
+    table::TableElem,
+    votable::{new_xml_writer, VOTableWrapper},
+    QuickXmlReadWrite,
+  };
+
+  #[test]
+  fn test_annotate() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <COOSYS ID="icrs" system="ICRS" epoch="J2016.0" refposition="BARYCENTER"/>
+  <RESOURCE type="results">
+    <TABLE ID="gaia">
+      <FIELD ID="source_id" name="source_id" datatype="long" ucd="meta.id;meta.main"/>
+      <FIELD ID="ra" name="ra" datatype="double" unit="deg" ucd="pos.eq.ra;meta.main" ref="icrs"/>
+      <FIELD ID="ra_error" name="ra_error" datatype="double" unit="mas" ucd="stat.error;pos.eq.ra"/>
+      <FIELD ID="dec" name="dec" datatype="double" unit="deg" ucd="pos.eq.dec;meta.main" ref="icrs"/>
+      <FIELD ID="dec_error" name="dec_error" datatype="float" unit="mas" ucd="stat.error;pos.eq.dec"/>
+      <FIELD ID="parallax" name="parallax" datatype="double" unit="mas" ucd="pos.parallax.trig"/>
+      <FIELD ID="parallax_error" name="parallax_error" datatype="float" unit="mas" ucd="stat.error;pos.parallax.trig"/>
+      <FIELD ID="pmra" name="pmra" datatype="double" unit="mas.yr**-1" ucd="pos.pm;pos.eq.ra"/>
+      <FIELD ID="pmdec" name="pmdec" datatype="double" unit="mas.yr**-1" ucd="pos.pm;pos.eq.dec"/>
+      <FIELD ID="g" name="phot_g_mean_mag" datatype="float" unit="mag" ucd="phot.mag;em.opt"/>
+      <FIELD ID="e_g" name="phot_g_mean_mag_error" datatype="float" unit="mag" ucd="stat.error;phot.mag"/>
+      <GROUP name="G">
+        <FIELDref ref="g"/>
+        <FIELDref ref="e_g"/>
+      </GROUP>
+      <DATA><TABLEDATA></TABLEDATA></DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let mut votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let annotator = Annotator::from_votable(&votable);
+    let mut vodml = annotator
+      .annotate(votable.resources[0].get_first_table_mut().unwrap())
+      .unwrap();
+    let mut writer = new_xml_writer(Vec::new(), None, Some(2));
+    vodml.write(&mut writer, &()).unwrap();
+    let actual = String::from_utf8(writer.into_inner()).unwrap();
+    let expected = r#"<VODML xmlns="http://www.ivoa.net/xml/mivot">
+  <REPORT status="OK">MIVOT annotation generated from FIELD metadata</REPORT>
+  <MODEL name="ivoa" url="https://www.ivoa.net/xml/VODML/IVOA-v1.vo-dml.xml"/>
+  <MODEL name="coords" url="https://www.ivoa.net/xml/STC/20200908/Coords-v1.0.vo-dml.xml"/>
+  <MODEL name="mango"/>
+  <GLOBALS>
+    <INSTANCE dmid="_spacesys_icrs" dmtype="coords:SpaceSys">
+      <INSTANCE dmrole="coords:PhysicalCoordSys.frame" dmtype="coords:SpaceFrame">
+        <ATTRIBUTE dmrole="coords:SpaceFrame.spaceRefFrame" dmtype="ivoa:string" value="ICRS"/>
+        <INSTANCE dmrole="coords:SpaceFrame.refPosition" dmtype="coords:StdRefLocation">
+          <ATTRIBUTE dmrole="coords:StdRefLocation.position" dmtype="ivoa:string" value="BARYCENTER"/>
+        </INSTANCE>
+      </INSTANCE>
+    </INSTANCE>
+  </GLOBALS>
+  <TEMPLATES tableref="gaia">
+    <INSTANCE dmtype="mango:MangoObject">
+      <ATTRIBUTE dmrole="mango:MangoObject.identifier" dmtype="ivoa:string" ref="source_id"/>
+      <COLLECTION dmrole="mango:MangoObject.propertyDock">
+        <INSTANCE dmtype="mango:EpochPosition">
+          <ATTRIBUTE dmrole="mango:EpochPosition.longitude" dmtype="ivoa:RealQuantity" ref="ra" unit="deg"/>
+          <ATTRIBUTE dmrole="mango:EpochPosition.latitude" dmtype="ivoa:RealQuantity" ref="dec" unit="deg"/>
+          <ATTRIBUTE dmrole="mango:EpochPosition.parallax" dmtype="ivoa:RealQuantity" ref="parallax" unit="mas"/>
+          <ATTRIBUTE dmrole="mango:EpochPosition.pmLongitude" dmtype="ivoa:RealQuantity" ref="pmra" unit="mas.yr**-1"/>
+          <ATTRIBUTE dmrole="mango:EpochPosition.pmLatitude" dmtype="ivoa:RealQuantity" ref="pmdec" unit="mas.yr**-1"/>
+          <INSTANCE dmrole="mango:EpochPosition.obsDate" dmtype="mango:DateTime">
+            <ATTRIBUTE dmrole="mango:DateTime.representation" dmtype="mango:TimeFormat" value="jyear"/>
+            <ATTRIBUTE dmrole="mango:DateTime.dateTime" dmtype="ivoa:datetime" value="2016"/>
+          </INSTANCE>
+          <INSTANCE dmrole="mango:EpochPosition.errors" dmtype="mango:EpochPositionErrors">
+            <INSTANCE dmrole="mango:EpochPositionErrors.position" dmtype="mango:error.ErrorCorrMatrix">
+              <ATTRIBUTE dmrole="mango:error.ErrorCorrMatrix.sigma1" dmtype="ivoa:real" ref="ra_error" unit="mas"/>
+              <ATTRIBUTE dmrole="mango:error.ErrorCorrMatrix.sigma2" dmtype="ivoa:real" ref="dec_error" unit="mas"/>
+            </INSTANCE>
+            <INSTANCE dmrole="mango:EpochPositionErrors.parallax" dmtype="mango:error.PropertyError1D">
+              <ATTRIBUTE dmrole="mango:error.PropertyError1D.sigma" dmtype="ivoa:real" ref="parallax_error" unit="mas"/>
+            </INSTANCE>
+          </INSTANCE>
+          <REFERENCE dmrole="mango:EpochPosition.spaceSys" dmref="_spacesys_icrs"/>
+        </INSTANCE>
+        <INSTANCE dmtype="mango:Brightness">
+          <ATTRIBUTE dmrole="mango:Property.label" dmtype="ivoa:string" value="phot_g_mean_mag"/>
+          <ATTRIBUTE dmrole="mango:PhotometricProperty.value" dmtype="ivoa:RealQuantity" ref="g" unit="mag"/>
+          <INSTANCE dmrole="mango:PhotometricProperty.error" dmtype="mango:error.PropertyError1D">
+            <ATTRIBUTE dmrole="mango:error.PropertyError1D.sigma" dmtype="ivoa:real" ref="e_g" unit="mag"/>
+          </INSTANCE>
+        </INSTANCE>
+      </COLLECTION>
+    </INSTANCE>
+  </TEMPLATES>
+</VODML>"#;
+    assert_eq!(actual, expected);
+    // The generated annotation is valid
+    let mut meta = Resource::new().set_type("meta");
+    meta.set_vodml_by_ref(vodml);
+    votable.resources[0].prepend_resource_by_ref(meta);
+    assert_eq!(
+      validate_with_registry(&votable, &Registry::bundled()),
+      vec![]
+    );
+  }
+
+  #[test]
+  fn test_annotate_nothing() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <TABLE name="t">
+      <FIELD name="x" datatype="double"/>
+      <DATA><TABLEDATA></TABLEDATA></DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let mut votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let table = votable.resources[0].get_first_table_mut().unwrap();
+    assert!(Annotator::new().annotate(table).is_err());
+    assert_eq!(table.id, None);
+  }
+
+  #[test]
+  fn test_annotate_generated_ids() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <TABLE name="2mass">
+      <FIELD name="RA(ICRS)" datatype="double" unit="deg" ucd="pos.eq.ra;meta.main"/>
+      <FIELD name="DE(ICRS)" datatype="double" unit="deg" ucd="pos.eq.dec;meta.main"/>
+      <FIELD ID="DE_ICRS_" name="Jmag" datatype="float" unit="mag" ucd="phot.mag;em.IR.J"/>
+      <DATA><TABLEDATA></TABLEDATA></DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let mut votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let annotator = Annotator::from_votable(&votable);
+    let table = votable.resources[0].get_first_table_mut().unwrap();
+    let vodml = annotator.annotate(table).unwrap();
+    assert_eq!(table.id.as_deref(), Some("_2mass"));
+    let ids: Vec<Option<&str>> = table
+      .elems
+      .iter()
+      .map(|elem| match elem {
+        TableElem::Field(field) => field.id.as_deref(),
+        _ => unreachable!(),
+      })
+      .collect();
+    assert_eq!(
+      ids,
+      vec![Some("RA_ICRS_"), Some("DE_ICRS__2"), Some("DE_ICRS_")]
+    );
+    assert_eq!(vodml.templates[0].tableref.as_deref(), Some("_2mass"));
+    // The annotation refers to IDs only
+    let mut meta = Resource::new().set_type("meta");
+    meta.set_vodml_by_ref(vodml);
+    votable.resources[0].prepend_resource_by_ref(meta);
+    assert_eq!(
+      validate_with_registry(&votable, &Registry::bundled()),
+      vec![]
+    );
+  }
+}
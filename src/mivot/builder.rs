@@ -0,0 +1,547 @@
+//! Typed builders of the MIVOT annotations of the most used classes:
+//! * `mango:EpochPosition`, see [EpochPosition];
+//! * `meas:Position`, see [Position], with `meas:Symmetrical` errors, see [Symmetrical];
+//! * `coords:SpaceSys` and `coords:TimeSys`, see [SpaceSys] and [TimeSys];
+//! * `ivoa:RealQuantity`, see [RealQuantity].
+//!
+//! The mandatory attributes of a class are parameters of its constructor while the optional ones
+//! are set using setters, so that role names cannot be misspelled and mandatory attributes cannot
+//! be forgotten. A reference to a coordinate system is set from the typed system itself (its `dmid`
+//! being used as `dmref`).
+//! The builders are then converted into the generic structures (`INSTANCE`s of `TEMPLATES` or of
+//! `GLOBALS`, `ATTRIBUTE`s) to be put in a [Vodml](super::Vodml) block:
+//! ```ignore
+//! let space_sys = SpaceSys::new("_spacesys_icrs", "ICRS", "BARYCENTER");
+//! let position = EpochPosition::new(
+//!   RealQuantity::from_ref("ra").set_unit("deg"),
+//!   RealQuantity::from_ref("dec").set_unit("deg"),
+//! )
+//! .set_parallax(RealQuantity::from_ref("plx").set_unit("mas"))
+//! .set_space_sys(&space_sys);
+//! let vodml = Vodml::new()
+//!   .set_globals(Globals::new().push_instance(space_sys.into_globals_instance()))
+//!   .push_templates(Templates::new().push_instance(position.into_templates_instance()));
+//! ```
+
+use paste::paste;
+
+use super::{
+  attribute::{AttributeChildOfInstance, RefOrValueOrBoth},
+  globals::instance::{
+    instance::Instance as InstanceGII, reference::Reference as RefGIR, Instance as InstanceGI,
+  },
+  templates::instance::{
+    instance::Instance as InstanceTII, reference::Reference, Instance as InstanceTI,
+  },
+};
+
+/// `ivoa:RealQuantity`, i.e. a real value (taken from a column, a constant or both) with a unit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RealQuantity {
+  value: RefOrValueOrBoth,
+  unit: Option<String>,
+}
+
+impl RealQuantity {
+  pub const DMTYPE: &'static str = "ivoa:RealQuantity";
+
+  /// Value of the `FIELD` (or `PARAM`) having the given `ID` (or `name`).
+  pub fn from_ref<S: Into<String>>(ref_: S) -> Self {
+    Self {
+      value: RefOrValueOrBoth::from_ref(ref_),
+      unit: None,
+    }
+  }
+
+  /// Constant value.
+  pub fn from_value(value: f64) -> Self {
+    Self {
+      value: RefOrValueOrBoth::from_value(value.to_string()),
+      unit: None,
+    }
+  }
+
+  /// Value of the `FIELD` (or `PARAM`) having the given `ID` (or `name`), replaced by the
+  /// given default value if NULL.
+  pub fn from_ref_with_default<S: Into<String>>(ref_: S, default_value: f64) -> Self {
+    Self {
+      value: RefOrValueOrBoth::from_ref_with_default(ref_.into(), default_value.to_string()),
+      unit: None,
+    }
+  }
+
+  impl_builder_opt_string_attr!(unit);
+
+  /// Returns the `ATTRIBUTE` having the given `dmrole` and mapping this quantity.
+  pub fn into_attribute<S: Into<String>>(self, dmrole: S) -> AttributeChildOfInstance {
+    let attribute =
+      AttributeChildOfInstance::new(dmrole.into(), String::from(Self::DMTYPE), self.value);
+    match self.unit {
+      Some(unit) => attribute.set_unit(unit),
+      None => attribute,
+    }
+  }
+}
+
+/// `meas:Symmetrical`, i.e. an uncertainty given by a single radius.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symmetrical {
+  radius: RealQuantity,
+}
+
+impl Symmetrical {
+  pub const DMTYPE: &'static str = "meas:Symmetrical";
+  pub const RADIUS: &'static str = "meas:Symmetrical.radius";
+
+  pub fn new(radius: RealQuantity) -> Self {
+    Self { radius }
+  }
+
+  fn into_instance(self, dmrole: &str) -> InstanceTII {
+    InstanceTII::new(dmrole, Self::DMTYPE).push_attribute(self.radius.into_attribute(Self::RADIUS))
+  }
+}
+
+/// `meas:Position` whose coordinate is a `coords:LonLatPoint`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+  lon: RealQuantity,
+  lat: RealQuantity,
+  dist: Option<RealQuantity>,
+  ucd: Option<String>,
+  /// `dmid` of the `coords:SpaceSys` the coordinate refers to
+  coord_sys: Option<String>,
+  stat_error: Option<Symmetrical>,
+  sys_error: Option<Symmetrical>,
+}
+
+impl Position {
+  pub const DMTYPE: &'static str = "meas:Position";
+  pub const UCD: &'static str = "meas:Measure.ucd";
+  pub const COORD: &'static str = "meas:Measure.coord";
+  pub const ERROR: &'static str = "meas:Measure.error";
+  pub const POINT_DMTYPE: &'static str = "coords:LonLatPoint";
+  pub const LON: &'static str = "coords:LonLatPoint.lon";
+  pub const LAT: &'static str = "coords:LonLatPoint.lat";
+  pub const DIST: &'static str = "coords:LonLatPoint.dist";
+  pub const COORD_SYS: &'static str = "coords:Coordinate.coordSys";
+  pub const ERROR_DMTYPE: &'static str = "meas:Error";
+  pub const STAT_ERROR: &'static str = "meas:Error.statError";
+  pub const SYS_ERROR: &'static str = "meas:Error.sysError";
+
+  pub fn new(lon: RealQuantity, lat: RealQuantity) -> Self {
+    Self {
+      lon,
+      lat,
+      dist: None,
+      ucd: None,
+      coord_sys: None,
+      stat_error: None,
+      sys_error: None,
+    }
+  }
+
+  impl_builder_opt_attr!(dist, RealQuantity);
+  impl_builder_opt_string_attr!(ucd);
+  impl_builder_opt_attr!(stat_error, Symmetrical);
+  impl_builder_opt_attr!(sys_error, Symmetrical);
+
+  /// Set the coordinate system of the position, taking the ownership and returning itself.
+  pub fn set_coord_sys(mut self, space_sys: &SpaceSys) -> Self {
+    self.set_coord_sys_by_ref(space_sys);
+    self
+  }
+
+  /// Set the coordinate system of the position, by mutable ref.
+  pub fn set_coord_sys_by_ref(&mut self, space_sys: &SpaceSys) {
+    self.coord_sys = Some(space_sys.dmid.clone());
+  }
+
+  /// Returns the `INSTANCE`, child of `TEMPLATES`, mapping this position.
+  pub fn into_templates_instance(self) -> InstanceTI {
+    let mut point = InstanceTII::new(Self::COORD, Self::POINT_DMTYPE)
+      .push_attribute(self.lon.into_attribute(Self::LON))
+      .push_attribute(self.lat.into_attribute(Self::LAT));
+    if let Some(dist) = self.dist {
+      point.push_attribute_by_ref(dist.into_attribute(Self::DIST));
+    }
+    if let Some(dmref) = self.coord_sys {
+      point.push_reference_by_ref(Reference::Static(RefGIR::new(
+        String::from(Self::COORD_SYS),
+        dmref,
+      )));
+    }
+    let mut instance = InstanceTI::new(Self::DMTYPE);
+    if let Some(ucd) = self.ucd {
+      instance.push_attribute_by_ref(AttributeChildOfInstance::from_val(
+        String::from(Self::UCD),
+        String::from("ivoa:string"),
+        ucd,
+      ));
+    }
+    instance.push_instance_by_ref(point);
+    if self.stat_error.is_some() || self.sys_error.is_some() {
+      let mut error = InstanceTII::new(Self::ERROR, Self::ERROR_DMTYPE);
+      if let Some(stat_error) = self.stat_error {
+        error.push_instance_by_ref(stat_error.into_instance(Self::STAT_ERROR));
+      }
+      if let Some(sys_error) = self.sys_error {
+        error.push_instance_by_ref(sys_error.into_instance(Self::SYS_ERROR));
+      }
+      instance.push_instance_by_ref(error);
+    }
+    instance
+  }
+}
+
+/// `mango:EpochPosition`, i.e. a position with its proper motion, parallax and radial velocity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpochPosition {
+  longitude: RealQuantity,
+  latitude: RealQuantity,
+  parallax: Option<RealQuantity>,
+  radial_velocity: Option<RealQuantity>,
+  pm_longitude: Option<RealQuantity>,
+  pm_latitude: Option<RealQuantity>,
+  pm_cos_delt_applied: Option<bool>,
+  /// `dmid` of the referenced `coords:SpaceSys`
+  space_sys: Option<String>,
+  /// `dmid` of the referenced `coords:TimeSys`
+  time_sys: Option<String>,
+}
+
+impl EpochPosition {
+  pub const DMTYPE: &'static str = "mango:EpochPosition";
+  pub const LONGITUDE: &'static str = "mango:EpochPosition.longitude";
+  pub const LATITUDE: &'static str = "mango:EpochPosition.latitude";
+  pub const PARALLAX: &'static str = "mango:EpochPosition.parallax";
+  pub const RADIAL_VELOCITY: &'static str = "mango:EpochPosition.radialVelocity";
+  pub const PM_LONGITUDE: &'static str = "mango:EpochPosition.pmLongitude";
+  pub const PM_LATITUDE: &'static str = "mango:EpochPosition.pmLatitude";
+  pub const PM_COS_DELT_APPLIED: &'static str = "mango:EpochPosition.pmCosDeltApplied";
+  pub const SPACE_SYS: &'static str = "mango:EpochPosition.spaceSys";
+  pub const TIME_SYS: &'static str = "mango:EpochPosition.timeSys";
+
+  pub fn new(longitude: RealQuantity, latitude: RealQuantity) -> Self {
+    Self {
+      longitude,
+      latitude,
+      parallax: None,
+      radial_velocity: None,
+      pm_longitude: None,
+      pm_latitude: None,
+      pm_cos_delt_applied: None,
+      space_sys: None,
+      time_sys: None,
+    }
+  }
+
+  impl_builder_opt_attr!(parallax, RealQuantity);
+  impl_builder_opt_attr!(radial_velocity, RealQuantity);
+  impl_builder_opt_attr!(pm_longitude, RealQuantity);
+  impl_builder_opt_attr!(pm_latitude, RealQuantity);
+  impl_builder_opt_attr!(pm_cos_delt_applied, bool);
+
+  /// Set the space coordinate system, taking the ownership and returning itself.
+  pub fn set_space_sys(mut self, space_sys: &SpaceSys) -> Self {
+    self.set_space_sys_by_ref(space_sys);
+    self
+  }
+
+  /// Set the space coordinate system, by mutable ref.
+  pub fn set_space_sys_by_ref(&mut self, space_sys: &SpaceSys) {
+    self.space_sys = Some(space_sys.dmid.clone());
+  }
+
+  /// Set the time coordinate system, taking the ownership and returning itself.
+  pub fn set_time_sys(mut self, time_sys: &TimeSys) -> Self {
+    self.set_time_sys_by_ref(time_sys);
+    self
+  }
+
+  /// Set the time coordinate system, by mutable ref.
+  pub fn set_time_sys_by_ref(&mut self, time_sys: &TimeSys) {
+    self.time_sys = Some(time_sys.dmid.clone());
+  }
+
+  /// Returns the `INSTANCE`, child of `TEMPLATES`, mapping this position.
+  pub fn into_templates_instance(self) -> InstanceTI {
+    let mut instance = InstanceTI::new(Self::DMTYPE)
+      .push_attribute(self.longitude.into_attribute(Self::LONGITUDE))
+      .push_attribute(self.latitude.into_attribute(Self::LATITUDE));
+    for (dmrole, quantity) in [
+      (Self::PARALLAX, self.parallax),
+      (Self::RADIAL_VELOCITY, self.radial_velocity),
+      (Self::PM_LONGITUDE, self.pm_longitude),
+      (Self::PM_LATITUDE, self.pm_latitude),
+    ] {
+      if let Some(quantity) = quantity {
+        instance.push_attribute_by_ref(quantity.into_attribute(dmrole));
+      }
+    }
+    if let Some(applied) = self.pm_cos_delt_applied {
+      instance.push_attribute_by_ref(AttributeChildOfInstance::from_val(
+        String::from(Self::PM_COS_DELT_APPLIED),
+        String::from("ivoa:boolean"),
+        applied.to_string(),
+      ));
+    }
+    for (dmrole, dmref) in [
+      (Self::SPACE_SYS, self.space_sys),
+      (Self::TIME_SYS, self.time_sys),
+    ] {
+      if let Some(dmref) = dmref {
+        instance.push_reference_by_ref(Reference::Static(RefGIR::new(String::from(dmrole), dmref)));
+      }
+    }
+    instance
+  }
+}
+
+/// `coords:SpaceSys` whose frame is a `coords:SpaceFrame`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpaceSys {
+  dmid: String,
+  space_ref_frame: String,
+  ref_position: String,
+  equinox: Option<String>,
+}
+
+impl SpaceSys {
+  pub const DMTYPE: &'static str = "coords:SpaceSys";
+  pub const FRAME_DMTYPE: &'static str = "coords:SpaceFrame";
+  pub const SPACE_REF_FRAME: &'static str = "coords:SpaceFrame.spaceRefFrame";
+  pub const REF_POSITION: &'static str = "coords:SpaceFrame.refPosition";
+  pub const EQUINOX: &'static str = "coords:SpaceFrame.equinox";
+
+  /// # Params
+  /// * `dmid`: identifier of the system, to be referenced
+  /// * `space_ref_frame`: e.g. `ICRS`, `FK5`, `GALACTIC`, ...
+  /// * `ref_position`: standard reference position, e.g. `BARYCENTER`, `TOPOCENTER`, ...
+  pub fn new<S: Into<String>>(dmid: S, space_ref_frame: S, ref_position: S) -> Self {
+    Self {
+      dmid: dmid.into(),
+      space_ref_frame: space_ref_frame.into(),
+      ref_position: ref_position.into(),
+      equinox: None,
+    }
+  }
+
+  impl_builder_opt_string_attr!(equinox);
+
+  pub fn dmid(&self) -> &str {
+    &self.dmid
+  }
+
+  /// Returns the `INSTANCE`, child of `GLOBALS`, mapping this coordinate system.
+  pub fn into_globals_instance(self) -> InstanceGI {
+    let mut frame = InstanceGII::new(PHYSICAL_COORD_SYS_FRAME, Self::FRAME_DMTYPE).push_attribute(
+      AttributeChildOfInstance::from_val(
+        String::from(Self::SPACE_REF_FRAME),
+        String::from("ivoa:string"),
+        self.space_ref_frame,
+      ),
+    );
+    if let Some(equinox) = self.equinox {
+      frame.push_attribute_by_ref(AttributeChildOfInstance::from_val(
+        String::from(Self::EQUINOX),
+        String::from("coords:Epoch"),
+        equinox,
+      ));
+    }
+    frame.push_instance_by_ref(std_ref_location(Self::REF_POSITION, self.ref_position));
+    InstanceGI::new(Self::DMTYPE)
+      .set_dmid(self.dmid)
+      .push_instance(frame)
+  }
+}
+
+/// `coords:TimeSys` whose frame is a `coords:TimeFrame`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeSys {
+  dmid: String,
+  timescale: String,
+  ref_position: String,
+}
+
+impl TimeSys {
+  pub const DMTYPE: &'static str = "coords:TimeSys";
+  pub const FRAME_DMTYPE: &'static str = "coords:TimeFrame";
+  pub const TIMESCALE: &'static str = "coords:TimeFrame.timescale";
+  pub const REF_POSITION: &'static str = "coords:TimeFrame.refPosition";
+
+  /// # Params
+  /// * `dmid`: identifier of the system, to be referenced
+  /// * `timescale`: e.g. `TCB`, `TDB`, `UTC`, ...
+  /// * `ref_position`: standard reference position, e.g. `BARYCENTER`, `TOPOCENTER`, ...
+  pub fn new<S: Into<String>>(dmid: S, timescale: S, ref_position: S) -> Self {
+    Self {
+      dmid: dmid.into(),
+      timescale: timescale.into(),
+      ref_position: ref_position.into(),
+    }
+  }
+
+  pub fn dmid(&self) -> &str {
+    &self.dmid
+  }
+
+  /// Returns the `INSTANCE`, child of `GLOBALS`, mapping this coordinate system.
+  pub fn into_globals_instance(self) -> InstanceGI {
+    let frame = InstanceGII::new(PHYSICAL_COORD_SYS_FRAME, Self::FRAME_DMTYPE)
+      .push_attribute(AttributeChildOfInstance::from_val(
+        String::from(Self::TIMESCALE),
+        String::from("ivoa:string"),
+        self.timescale,
+      ))
+      .push_instance(std_ref_location(Self::REF_POSITION, self.ref_position));
+    InstanceGI::new(Self::DMTYPE)
+      .set_dmid(self.dmid)
+      .push_instance(frame)
+  }
+}
+
+const PHYSICAL_COORD_SYS_FRAME: &str = "coords:PhysicalCoordSys.frame";
+
+/// `coords:StdRefLocation` of the given reference position.
+fn std_ref_location(dmrole: &str, position: String) -> InstanceGII {
+  InstanceGII::new(dmrole, "coords:StdRefLocation").push_attribute(
+    AttributeChildOfInstance::from_val(
+      String::from("coords:StdRefLocation.position"),
+      String::from("ivoa:string"),
+      position,
+    ),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{EpochPosition, Position, RealQuantity, SpaceSys, Symmetrical, TimeSys};
+  use crate::{
+    impls::mem::InMemTableDataRows,
+    mivot::{
+      registry::Registry, validate::validate_with_registry, Globals, Model, Templates, Vodml,
+    },
+    resource::Resource,
+    votable::{new_xml_writer, VOTableWrapper},
+    QuickXmlReadWrite,
+  };
+
+  #[test]
+  fn test_typed_builders() {
+    let space_sys = SpaceSys::new("_spacesys_icrs", "ICRS", "BARYCENTER");
+    let time_sys = TimeSys::new("_timesys_tcb", "TCB", "BARYCENTER");
+    let epoch_position = EpochPosition::new(
+      RealQuantity::from_ref("ra").set_unit("deg"),
+      RealQuantity::from_ref("dec").set_unit("deg"),
+    )
+    .set_parallax(RealQuantity::from_ref_with_default("plx", 0.0).set_unit("mas"))
+    .set_pm_cos_delt_applied(true)
+    .set_space_sys(&space_sys)
+    .set_time_sys(&time_sys);
+    let position = Position::new(
+      RealQuantity::from_ref("ra").set_unit("deg"),
+      RealQuantity::from_ref("dec").set_unit("deg"),
+    )
+    .set_ucd("pos")
+    .set_coord_sys(&space_sys)
+    .set_stat_error(Symmetrical::new(
+      RealQuantity::from_value(0.5).set_unit("arcsec"),
+    ));
+    let mut vodml = Vodml::new()
+      .set_xmlns("http://www.ivoa.net/xml/mivot")
+      .push_model(Model::new("ivoa").set_url("https://www.ivoa.net/xml/VODML/IVOA-v1.vo-dml.xml"))
+      .push_model(
+        Model::new("coords")
+          .set_url("https://www.ivoa.net/xml/STC/20200908/Coords-v1.0.vo-dml.xml"),
+      )
+      .push_model(
+        Model::new("meas").set_url("https://www.ivoa.net/xml/Meas/20200908/Meas-v1.0.vo-dml.xml"),
+      )
+      .push_model(Model::new("mango"))
+      .set_globals(
+        Globals::new()
+          .push_instance(space_sys.into_globals_instance())
+          .push_instance(time_sys.into_globals_instance()),
+      )
+      .push_templates(
+        Templates::new()
+          .set_tableref("t")
+          .push_instance(epoch_position.into_templates_instance())
+          .push_instance(position.into_templates_instance()),
+      );
+    let mut writer = new_xml_writer(Vec::new(), None, Some(2));
+    vodml.write(&mut writer, &()).unwrap();
+    let actual = String::from_utf8(writer.into_inner()).unwrap();
+    let expected = r#"<VODML xmlns="http://www.ivoa.net/xml/mivot">
+  <MODEL name="ivoa" url="https://www.ivoa.net/xml/VODML/IVOA-v1.vo-dml.xml"/>
+  <MODEL name="coords" url="https://www.ivoa.net/xml/STC/20200908/Coords-v1.0.vo-dml.xml"/>
+  <MODEL name="meas" url="https://www.ivoa.net/xml/Meas/20200908/Meas-v1.0.vo-dml.xml"/>
+  <MODEL name="mango"/>
+  <GLOBALS>
+    <INSTANCE dmid="_spacesys_icrs" dmtype="coords:SpaceSys">
+      <INSTANCE dmrole="coords:PhysicalCoordSys.frame" dmtype="coords:SpaceFrame">
+        <ATTRIBUTE dmrole="coords:SpaceFrame.spaceRefFrame" dmtype="ivoa:string" value="ICRS"/>
+        <INSTANCE dmrole="coords:SpaceFrame.refPosition" dmtype="coords:StdRefLocation">
+          <ATTRIBUTE dmrole="coords:StdRefLocation.position" dmtype="ivoa:string" value="BARYCENTER"/>
+        </INSTANCE>
+      </INSTANCE>
+    </INSTANCE>
+    <INSTANCE dmid="_timesys_tcb" dmtype="coords:TimeSys">
+      <INSTANCE dmrole="coords:PhysicalCoordSys.frame" dmtype="coords:TimeFrame">
+        <ATTRIBUTE dmrole="coords:TimeFrame.timescale" dmtype="ivoa:string" value="TCB"/>
+        <INSTANCE dmrole="coords:TimeFrame.refPosition" dmtype="coords:StdRefLocation">
+          <ATTRIBUTE dmrole="coords:StdRefLocation.position" dmtype="ivoa:string" value="BARYCENTER"/>
+        </INSTANCE>
+      </INSTANCE>
+    </INSTANCE>
+  </GLOBALS>
+  <TEMPLATES tableref="t">
+    <INSTANCE dmtype="mango:EpochPosition">
+      <ATTRIBUTE dmrole="mango:EpochPosition.longitude" dmtype="ivoa:RealQuantity" ref="ra" unit="deg"/>
+      <ATTRIBUTE dmrole="mango:EpochPosition.latitude" dmtype="ivoa:RealQuantity" ref="dec" unit="deg"/>
+      <ATTRIBUTE dmrole="mango:EpochPosition.parallax" dmtype="ivoa:RealQuantity" ref="plx" value="0" unit="mas"/>
+      <ATTRIBUTE dmrole="mango:EpochPosition.pmCosDeltApplied" dmtype="ivoa:boolean" value="true"/>
+      <REFERENCE dmrole="mango:EpochPosition.spaceSys" dmref="_spacesys_icrs"/>
+      <REFERENCE dmrole="mango:EpochPosition.timeSys" dmref="_timesys_tcb"/>
+    </INSTANCE>
+    <INSTANCE dmtype="meas:Position">
+      <ATTRIBUTE dmrole="meas:Measure.ucd" dmtype="ivoa:string" value="pos"/>
+      <INSTANCE dmrole="meas:Measure.coord" dmtype="coords:LonLatPoint">
+        <ATTRIBUTE dmrole="coords:LonLatPoint.lon" dmtype="ivoa:RealQuantity" ref="ra" unit="deg"/>
+        <ATTRIBUTE dmrole="coords:LonLatPoint.lat" dmtype="ivoa:RealQuantity" ref="dec" unit="deg"/>
+        <REFERENCE dmrole="coords:Coordinate.coordSys" dmref="_spacesys_icrs"/>
+      </INSTANCE>
+      <INSTANCE dmrole="meas:Measure.error" dmtype="meas:Error">
+        <INSTANCE dmrole="meas:Error.statError" dmtype="meas:Symmetrical">
+          <ATTRIBUTE dmrole="meas:Symmetrical.radius" dmtype="ivoa:RealQuantity" value="0.5" unit="arcsec"/>
+        </INSTANCE>
+      </INSTANCE>
+    </INSTANCE>
+  </TEMPLATES>
+</VODML>"#;
+    assert_eq!(actual, expected);
+    // The generated annotation is valid
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <TABLE ID="t">
+      <FIELD ID="ra" name="ra" datatype="double" unit="deg"/>
+      <FIELD ID="dec" name="dec" datatype="double" unit="deg"/>
+      <FIELD ID="plx" name="plx" datatype="double" unit="mas"/>
+      <DATA><TABLEDATA></TABLEDATA></DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let mut votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let mut meta = Resource::new().set_type("meta");
+    meta.set_vodml_by_ref(vodml);
+    votable.resources[0].prepend_resource_by_ref(meta);
+    assert_eq!(
+      validate_with_registry(&votable, &Registry::bundled()),
+      vec![]
+    );
+  }
+}
@@ -147,7 +147,7 @@ impl HasSubElements for Instance {
     &mut self,
     mut reader: &mut Reader<R>,
     mut reader_buff: &mut Vec<u8>,
-    _context: &Self::Context,
+    context: &Self::Context,
   ) -> Result<(), VOTableError> {
     loop {
       let mut event = reader.read_event(reader_buff).map_err(VOTableError::Read)?;
@@ -159,12 +159,9 @@ impl HasSubElements for Instance {
           Reference::TAG_BYTES => {
             self.push_reference_by_ref(from_event_start_by_ref!(Reference, reader, reader_buff, e))
           }
-          Collection::TAG_BYTES => self.push_collection_by_ref(from_event_start_by_ref!(
-            Collection,
-            reader,
-            reader_buff,
-            e
-          )),
+          Collection::TAG_BYTES => self.push_collection_by_ref(
+            Collection::from_event_start_reading_sub_elems(e, context, reader)?,
+          ),
           _ => {
             return Err(VOTableError::UnexpectedStartTag(
               e.local_name().to_vec(),
@@ -8,7 +8,7 @@ use std::{
 
 use paste::paste;
 use quick_xml::{
-  events::{attributes::Attributes, Event},
+  events::{attributes::Attributes, BytesStart, Event},
   Reader, Writer,
 };
 
@@ -163,6 +163,19 @@ impl Collection {
     Ok((dmrole, dmid))
   }
 
+  /// Reads a `COLLECTION` from its start tag and its sub-elements.
+  /// The start tag borrowing the buffer of the reader, the sub-elements are read using a
+  /// dedicated buffer.
+  pub(crate) fn from_event_start_reading_sub_elems<R: BufRead>(
+    e: &BytesStart,
+    context: &(),
+    reader: &mut Reader<R>,
+  ) -> Result<Self, VOTableError> {
+    let (dmrole, dmid) = Self::get_dmrole_opt_dmid_from_atttributes(e.attributes())?;
+    let mut reader_buff = Vec::with_capacity(1024);
+    Self::from_dmrole_and_reading_sub_elems(dmrole, dmid, context, reader, &mut reader_buff)
+  }
+
   /// Special case since we check that the Collection contains attribute...
   pub(crate) fn from_dmrole_and_reading_sub_elems<R: BufRead>(
     dmrole: String,
@@ -175,18 +175,9 @@ impl HasSubElements for Instance {
             from_event_start_by_ref!(InstanceChildOfInstance, reader, reader_buff, e),
           ),
           Reference::TAG_BYTES => push_from_event_start!(self, Reference, reader, reader_buff, e),
-          Collection::TAG_BYTES => {
-            let (dmrole, dmid_opt) =
-              Collection::get_dmrole_opt_dmid_from_atttributes(e.attributes())?;
-            let collection = Collection::from_dmrole_and_reading_sub_elems(
-              dmrole,
-              dmid_opt,
-              &(),
-              reader,
-              reader_buff,
-            )?;
-            self.push_collection_by_ref(collection)
-          }
+          Collection::TAG_BYTES => self.push_collection_by_ref(
+            Collection::from_event_start_reading_sub_elems(e, &(), reader)?,
+          ),
           _ => {
             return Err(VOTableError::UnexpectedStartTag(
               e.local_name().to_vec(),
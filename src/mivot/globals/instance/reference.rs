@@ -83,7 +83,7 @@ impl VOTableElement for Reference {
   where
     F: FnMut(&str, &str),
   {
-    f("dmrole", self.dmref.as_str());
+    f("dmrole", self.dmrole.as_str());
     f("dmref", self.dmref.as_str());
   }
 }
@@ -33,34 +33,30 @@ impl JoinAttributes {
     V: AsRef<str> + Into<String>,
     I: Iterator<Item = (K, V)>,
   {
-    let mut new_dmref = String::new();
-    let mut new_sourceref = String::new();
+    let (mut dmref, mut sourceref) = match self {
+      JoinAttributes::DmRef { dmref } => (dmref.clone(), String::new()),
+      JoinAttributes::SrcRef { sourceref } => (String::new(), sourceref.clone()),
+      JoinAttributes::BothRef { dmref, sourceref } => (dmref.clone(), sourceref.clone()),
+    };
     for (key, val) in attrs {
       let key = key.as_ref();
       match key {
-        "dmref" => new_dmref = val.into(),
-        "sourceref" => new_sourceref = val.into(),
+        "dmref" => dmref = val.into(),
+        "sourceref" => sourceref = val.into(),
         _ => return Err(unexpected_attr_err(key, Join::TAG)),
       }
     }
-    match self {
-      JoinAttributes::DmRef { dmref } => {
-        *dmref = new_dmref;
-        if new_sourceref.is_empty() {
-          return Err(VOTableError::Custom("Unable to set 'sourceref'".into()));
-        }
-      }
-      JoinAttributes::SrcRef { sourceref } => {
-        *sourceref = new_sourceref;
-        if new_dmref.is_empty() {
-          return Err(VOTableError::Custom("Unable to set 'dmref'".into()));
-        }
-      }
-      JoinAttributes::BothRef { dmref, sourceref } => {
-        *dmref = new_dmref;
-        *sourceref = new_sourceref;
+    *self = match (dmref.is_empty(), sourceref.is_empty()) {
+      (false, false) => JoinAttributes::BothRef { dmref, sourceref },
+      (false, true) => JoinAttributes::DmRef { dmref },
+      (true, false) => JoinAttributes::SrcRef { sourceref },
+      (true, true) => {
+        return Err(VOTableError::Custom(format!(
+          "One of the attribute 'dmref' or 'sourceref' mandatory in {}.",
+          Join::TAG
+        )))
       }
-    }
+    };
     Ok(())
   }
 
@@ -69,10 +65,10 @@ impl JoinAttributes {
     F: FnMut(&str, &str),
   {
     match &self {
-      JoinAttributes::DmRef { dmref } => f("dmtype", dmref.as_str()),
+      JoinAttributes::DmRef { dmref } => f("dmref", dmref.as_str()),
       JoinAttributes::SrcRef { sourceref } => f("sourceref", sourceref.as_str()),
       JoinAttributes::BothRef { dmref, sourceref } => {
-        f("dmtype", dmref.as_str());
+        f("dmref", dmref.as_str());
         f("sourceref", sourceref.as_str());
       }
     }
@@ -248,10 +244,25 @@ impl HasSubElements for Join {
 
 #[cfg(test)]
 mod tests {
-  use crate::{mivot::test::get_xml, tests::test_read};
+  use crate::{
+    mivot::test::get_xml,
+    tests::{test_read, test_writer},
+  };
 
   use super::Join;
 
+  #[test]
+  fn test_join_write_read() {
+    for xml in [
+      r#"<JOIN dmref="_cat"/>"#,
+      r#"<JOIN sourceref="_src"><WHERE foreignkey="id" primarykey="src_id"/></JOIN>"#,
+      r#"<JOIN dmref="_cat" sourceref="_src"><WHERE foreignkey="id" primarykey="src_id"/></JOIN>"#,
+    ] {
+      let join = test_read::<Join>(xml);
+      test_writer(join.clone(), xml);
+    }
+  }
+
   #[test]
   fn test_join_read() {
     // OK JOINS
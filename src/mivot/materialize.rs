@@ -0,0 +1,2004 @@
+//! Materialization of the `INSTANCE`s of a `TEMPLATES` block for each row of the table it maps.
+//!
+//! Each `INSTANCE` is turned into a JSON-like object tree:
+//! * an `INSTANCE` is an object containing its `dmtype` (and `dmid`, if any) plus one entry per
+//!   sub-element, keyed by the sub-element `dmrole`;
+//! * an `ATTRIBUTE` is an object containing its `dmtype`, its `value` (taken from the row, from a
+//!   `PARAM` or from the `value` attribute) and, if any, its `unit` and its `ref`;
+//! * a `COLLECTION` is an array of its (materialized) items;
+//! * a static `REFERENCE` is replaced by the (materialized) `GLOBALS` element having the
+//!   referenced `dmid`;
+//! * a dynamic `REFERENCE` is replaced by the item of the referenced `GLOBALS` `COLLECTION`, or
+//!   by the instance built from the row of the referenced `TEMPLATES`, whose primary key matches
+//!   the foreign key values of the current row;
+//! * a `JOIN` is replaced by the list of all the matching items (or rows).
+//!
+//! With [GlobalsMode::Once], a static `REFERENCE` to a `GLOBALS` `INSTANCE` is instead replaced
+//! by an object containing only the referenced `dmref`, the referenced instances being
+//! materialized once (see [RowMaterializer::globals]).
+//!
+//! The materialized `INSTANCE`s are also provided as [MaterializedInstance]s, implementing
+//! `Serialize`, e.g. to export them as JSON documents.
+//!
+//! Resolving a `JOIN` or a dynamic `REFERENCE` to a `TEMPLATES` requires the rows of the table
+//! it maps, to be provided in [TableSources]. The primary key index of a referenced `COLLECTION`
+//! or `TEMPLATES` is built only once.
+//!
+//! The templates are "compiled" once (the `ref` of the `ATTRIBUTE`s being resolved into column
+//! indices or constant values), so that rows can be materialized efficiently while streaming
+//! them, e.g. using a [RowValueIterator](crate::iter::elems::RowValueIterator):
+//! ```ignore
+//! let mut it = VOTableIterator::from_file(path)?;
+//! let templates = all_templates(it.votable()).first().cloned().cloned().unwrap();
+//! let materializer = RowMaterializer::new(it.votable(), &templates)?;
+//! for row in it.to_row_value_iter() {
+//!   let row = row?;
+//!   if materializer.is_selected(&row) {
+//!     let instances = materializer.materialize_row(&row)?;
+//!   }
+//! }
+//! ```
+
+use std::{borrow::Cow, cell::RefCell, collections::HashMap, sync::Arc};
+
+use log::warn;
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::{
+  data::DataElem,
+  error::VOTableError,
+  impls::{mem::InMemTableDataRows, Schema, TableSchema, VOTableValue},
+  param::Param,
+  resource::Resource,
+  table::Table,
+  values::resolve::ValuesRegistry,
+  votable::VOTable,
+  walk::{walk_table_elems, walk_votable, Meta},
+  TableDataContent,
+};
+
+use super::{
+  attribute::{AttributeChildOfCollection, AttributeChildOfInstance, RefOrValueOrBoth},
+  globals::{
+    collection::{
+      instance::Instance as GlobalsCollectionInstance, Collection as GlobalsCollection,
+      CollectionElems as GlobalsCollectionElems, InstanceOrRef as GlobalsCollectionItem,
+    },
+    instance::{
+      collection::collection::{
+        CollectionElems as GlobalsInnerCollectionElems, InstanceOrRef as GlobalsInnerItem,
+      },
+      primary_key::PrimaryKeyStatic,
+      Instance as GlobalsInstance, InstanceElem as GlobalsInstanceElem,
+    },
+    GlobalsElem,
+  },
+  join::{Join, JoinAttributes},
+  templates::{
+    instance::{
+      collection::{
+        collection::{CollectionElems, InstanceOrRef},
+        reference::Reference as CollectionReference,
+      },
+      reference::Reference,
+      Instance, InstanceElem,
+    },
+    Templates,
+  },
+  vodml::Vodml,
+};
+
+/// Returns all the `VODML` blocks of the given VOTable.
+pub fn all_vodmls<C: TableDataContent>(votable: &VOTable<C>) -> Vec<&Vodml> {
+  let mut vodmls = Vec::new();
+  walk_votable(votable, &mut |meta| {
+    if let Meta::ResourceStart(Resource {
+      vodml: Some(vodml), ..
+    }) = meta
+    {
+      vodmls.push(vodml);
+    }
+  });
+  vodmls
+}
+
+/// Returns all the `TEMPLATES` blocks found in the `VODML` blocks of the given VOTable.
+pub fn all_templates<C: TableDataContent>(votable: &VOTable<C>) -> Vec<&Templates> {
+  all_vodmls(votable)
+    .into_iter()
+    .flat_map(|vodml| vodml.templates.iter())
+    .collect()
+}
+
+/// Returns all the tables of the given VOTable, in document order.
+fn all_tables<C: TableDataContent>(votable: &VOTable<C>) -> Vec<&Table<C>> {
+  let mut tables = Vec::new();
+  walk_votable(votable, &mut |meta| {
+    if let Meta::TableStart(table) = meta {
+      tables.push(table);
+    }
+  });
+  tables
+}
+
+/// Returns the table having the given `ID` or, if no table has such an `ID`, the given `name`.
+/// If `tableref` is `None`, returns the first table of the VOTable.
+pub fn find_table<'a, C: TableDataContent>(
+  votable: &'a VOTable<C>,
+  tableref: Option<&str>,
+) -> Option<&'a Table<C>> {
+  match tableref {
+    None => votable.get_first_table(),
+    Some(tableref) => {
+      let tables = all_tables(votable);
+      tables
+        .iter()
+        .find(|table| table.id.as_deref() == Some(tableref))
+        .or_else(|| {
+          tables
+            .iter()
+            .find(|table| table.name.as_deref() == Some(tableref))
+        })
+        .copied()
+    }
+  }
+}
+
+/// Rows of the tables which may be referenced by a `JOIN` or by a dynamic `REFERENCE`,
+/// by table `ID` (or `name`).
+#[derive(Debug, Clone, Default)]
+pub struct TableSources {
+  rows: HashMap<String, Arc<Vec<Vec<VOTableValue>>>>,
+}
+
+impl TableSources {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Registers the rows of the table having the given `ID` (or `name`).
+  pub fn insert<S: Into<String>>(&mut self, tableref: S, rows: Vec<Vec<VOTableValue>>) {
+    self.rows.insert(tableref.into(), Arc::new(rows));
+  }
+
+  /// Registers the rows of all the tables of the given in-memory VOTable,
+  /// by table `ID` and by table `name`.
+  pub fn from_votable(votable: &VOTable<InMemTableDataRows>) -> Self {
+    let mut sources = Self::new();
+    for table in all_tables(votable) {
+      let content = match table.data.as_ref().map(|data| &data.data) {
+        Some(DataElem::TableData(data)) => Some(&data.content),
+        Some(DataElem::Binary(data)) => data.stream.content.as_ref(),
+        Some(DataElem::Binary2(data)) => data.stream.content.as_ref(),
+        _ => None,
+      };
+      if let Some(content) = content {
+        let rows = Arc::new(content.rows.clone());
+        for tableref in table.id.iter().chain(table.name.iter()) {
+          sources.rows.insert(tableref.clone(), rows.clone());
+        }
+      }
+    }
+    sources
+  }
+}
+
+/// How the `GLOBALS` `INSTANCE`s statically referenced (`REFERENCE dmref`) are materialized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GlobalsMode {
+  /// The referenced `INSTANCE` is copied in place of each reference.
+  #[default]
+  Inline,
+  /// Each reference is replaced by `{"dmref": <dmid>}`, the referenced `INSTANCE`s being
+  /// materialized only once.
+  Once,
+}
+
+/// A materialized `INSTANCE`, serialized as an object made of its `dmtype`, of its `dmid` (if any)
+/// and of its materialized sub-elements keyed by their `dmrole`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaterializedInstance {
+  pub dmtype: String,
+  pub dmid: Option<String>,
+  /// Materialized sub-elements, by `dmrole`.
+  pub elems: Map<String, Value>,
+}
+
+impl MaterializedInstance {
+  /// Splits the object tree built from an `INSTANCE` node.
+  fn from_value(value: Value) -> Result<Self, VOTableError> {
+    let mut map = match value {
+      Value::Object(map) => map,
+      _ => {
+        return Err(VOTableError::Custom(String::from(
+          "Materialized INSTANCE is not an object",
+        )))
+      }
+    };
+    let dmtype = match map.shift_remove("dmtype") {
+      Some(Value::String(dmtype)) => dmtype,
+      _ => {
+        return Err(VOTableError::Custom(String::from(
+          "Materialized INSTANCE has no dmtype",
+        )))
+      }
+    };
+    let dmid = match map.shift_remove("dmid") {
+      Some(Value::String(dmid)) => Some(dmid),
+      _ => None,
+    };
+    Ok(Self {
+      dmtype,
+      dmid,
+      elems: map,
+    })
+  }
+
+  /// Returns the object tree of this instance.
+  pub fn to_value(&self) -> Value {
+    let mut map = Map::with_capacity(self.elems.len() + 2);
+    map.insert(String::from("dmtype"), Value::String(self.dmtype.clone()));
+    if let Some(dmid) = &self.dmid {
+      map.insert(String::from("dmid"), Value::String(dmid.clone()));
+    }
+    for (dmrole, elem) in self.elems.iter() {
+      map.insert(dmrole.clone(), elem.clone());
+    }
+    Value::Object(map)
+  }
+}
+
+impl Serialize for MaterializedInstance {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    let len = self.elems.len() + 1 + usize::from(self.dmid.is_some());
+    let mut map = serializer.serialize_map(Some(len))?;
+    map.serialize_entry("dmtype", &self.dmtype)?;
+    if let Some(dmid) = &self.dmid {
+      map.serialize_entry("dmid", dmid)?;
+    }
+    for (dmrole, elem) in self.elems.iter() {
+      map.serialize_entry(dmrole, elem)?;
+    }
+    map.end()
+  }
+}
+
+/// Where the value of an `ATTRIBUTE` comes from.
+#[derive(Debug, Clone)]
+enum Source {
+  /// Value of the column of given index, replaced by the given default value if NULL.
+  Column(usize, Option<Value>),
+  /// Value not depending on the row.
+  Constant(Value),
+}
+
+/// Items of a `GLOBALS` `COLLECTION`, or instances built from the rows of a `TEMPLATES`,
+/// indexed by primary key.
+#[derive(Debug, Default)]
+struct KeyIndex {
+  items: Vec<Value>,
+  by_key: HashMap<Vec<String>, Vec<usize>>,
+}
+
+impl KeyIndex {
+  fn push(&mut self, item: Value, key: Vec<String>) {
+    if !key.is_empty() {
+      self.by_key.entry(key).or_default().push(self.items.len());
+    }
+    self.items.push(item);
+  }
+}
+
+/// A pre-processed mapping element.
+#[derive(Debug, Clone)]
+enum Node {
+  Instance {
+    dmtype: String,
+    dmid: Option<String>,
+    elems: Vec<(String, Node)>,
+  },
+  Attribute {
+    dmtype: String,
+    source: Source,
+    arrayindex: Option<u32>,
+    unit: Option<String>,
+    ref_: Option<String>,
+  },
+  Collection(Vec<Node>),
+  /// Element not depending on the row (e.g. a referenced `GLOBALS` element).
+  Constant(Value),
+  /// Item(s) of a `GLOBALS` `COLLECTION`, or of a `TEMPLATES`, selected by foreign key.
+  Lookup {
+    /// `dmid` of the `COLLECTION`, or `tableref` of the `TEMPLATES`.
+    target: String,
+    /// Index of the foreign key columns (all items are selected if empty).
+    foreign_keys: Vec<usize>,
+    index: Arc<KeyIndex>,
+    /// `JOIN` (returning all matching items) or dynamic `REFERENCE` (returning a single item).
+    many: bool,
+  },
+}
+
+impl Node {
+  fn materialize(&self, row: &[VOTableValue]) -> Result<Value, VOTableError> {
+    match self {
+      Node::Instance {
+        dmtype,
+        dmid,
+        elems,
+      } => {
+        let mut map = Map::with_capacity(elems.len() + 2);
+        map.insert(String::from("dmtype"), Value::String(dmtype.clone()));
+        if let Some(dmid) = dmid {
+          map.insert(String::from("dmid"), Value::String(dmid.clone()));
+        }
+        for (dmrole, node) in elems {
+          map.insert(dmrole.clone(), node.materialize(row)?);
+        }
+        Ok(Value::Object(map))
+      }
+      Node::Attribute {
+        dmtype,
+        source,
+        arrayindex,
+        unit,
+        ref_,
+      } => {
+        let value = match source {
+          Source::Constant(value) => value.clone(),
+          Source::Column(index, default) => match (to_json(cell(row, *index)?)?, default) {
+            (Value::Null, Some(default)) => default.clone(),
+            (value, _) => value,
+          },
+        };
+        let value = match (arrayindex, value) {
+          (Some(i), Value::Array(mut values)) if (*i as usize) < values.len() => {
+            values.swap_remove(*i as usize)
+          }
+          (Some(_), Value::Array(_)) => Value::Null,
+          (_, value) => value,
+        };
+        let mut map = Map::with_capacity(4);
+        map.insert(String::from("dmtype"), Value::String(dmtype.clone()));
+        map.insert(String::from("value"), value);
+        if let Some(unit) = unit {
+          map.insert(String::from("unit"), Value::String(unit.clone()));
+        }
+        if let Some(ref_) = ref_ {
+          map.insert(String::from("ref"), Value::String(ref_.clone()));
+        }
+        Ok(Value::Object(map))
+      }
+      Node::Collection(items) => items
+        .iter()
+        .map(|item| item.materialize(row))
+        .collect::<Result<Vec<Value>, VOTableError>>()
+        .map(Value::Array),
+      Node::Constant(value) => Ok(value.clone()),
+      Node::Lookup {
+        target,
+        foreign_keys,
+        index,
+        many,
+      } => {
+        if foreign_keys.is_empty() {
+          return Ok(if *many {
+            Value::Array(index.items.clone())
+          } else {
+            index.items.first().cloned().unwrap_or(Value::Null)
+          });
+        }
+        let key = foreign_keys
+          .iter()
+          .map(|i| cell(row, *i).map(cell_key))
+          .collect::<Result<Vec<String>, VOTableError>>()?;
+        let matches = index
+          .by_key
+          .get(&key)
+          .map(|matches| matches.as_slice())
+          .unwrap_or(&[]);
+        if *many {
+          Ok(Value::Array(
+            matches.iter().map(|i| index.items[*i].clone()).collect(),
+          ))
+        } else {
+          matches
+            .first()
+            .map(|i| index.items[*i].clone())
+            .ok_or_else(|| {
+              VOTableError::Custom(format!(
+                "Unresolved reference to '{}': no item having the primary key [{}]",
+                target,
+                key.join(", ")
+              ))
+            })
+        }
+      }
+    }
+  }
+}
+
+impl FromIterator<Node> for Node {
+  fn from_iter<T: IntoIterator<Item = Node>>(iter: T) -> Self {
+    Node::Collection(iter.into_iter().collect())
+  }
+}
+
+/// Materializes the `INSTANCE`s of a `TEMPLATES` block for each row of the table it maps.
+#[derive(Debug, Clone)]
+pub struct RowMaterializer {
+  /// One node per `INSTANCE` child of `TEMPLATES`.
+  instances: Vec<Node>,
+  /// Row selection criteria (from the `WHERE`s), i.e. column index and expected value.
+  wheres: Vec<(usize, VOTableValue)>,
+  /// Referenced `GLOBALS` `INSTANCE`s (only with [GlobalsMode::Once]).
+  globals: Vec<MaterializedInstance>,
+}
+
+impl RowMaterializer {
+  /// Prepares the materialization of the given templates, looking for the table it maps
+  /// (from its `tableref`), for the `PARAM`s and for the `GLOBALS` it may reference in the
+  /// given VOTable.
+  /// Use [with_sources](Self::with_sources) if the templates contains `JOIN`s or dynamic
+  /// `REFERENCE`s to other `TEMPLATES`.
+  pub fn new<C: TableDataContent>(
+    votable: &VOTable<C>,
+    templates: &Templates,
+  ) -> Result<Self, VOTableError> {
+    Self::with_sources(votable, templates, &TableSources::default())
+  }
+
+  /// Same as [new](Self::new), providing the rows of the tables which may be referenced
+  /// by `JOIN`s or dynamic `REFERENCE`s.
+  pub fn with_sources<C: TableDataContent>(
+    votable: &VOTable<C>,
+    templates: &Templates,
+    sources: &TableSources,
+  ) -> Result<Self, VOTableError> {
+    Self::with_globals_mode(votable, templates, sources, GlobalsMode::Inline)
+  }
+
+  /// Same as [with_sources](Self::with_sources), choosing how the referenced `GLOBALS`
+  /// `INSTANCE`s are materialized.
+  pub fn with_globals_mode<C: TableDataContent>(
+    votable: &VOTable<C>,
+    templates: &Templates,
+    sources: &TableSources,
+    globals_mode: GlobalsMode,
+  ) -> Result<Self, VOTableError> {
+    let context = Context::new(votable, sources, globals_mode);
+    let (wheres, instances) = context.templates_nodes(templates)?;
+    let globals = match globals_mode {
+      GlobalsMode::Inline => Vec::new(),
+      GlobalsMode::Once => context
+        .global_values
+        .borrow()
+        .iter()
+        .filter(|(dmid, _)| context.is_global_instance(dmid))
+        .map(|(_, value)| MaterializedInstance::from_value(value.clone()))
+        .collect::<Result<Vec<MaterializedInstance>, VOTableError>>()?,
+    };
+    Ok(Self {
+      instances,
+      wheres,
+      globals,
+    })
+  }
+
+  /// Returns the `tableref`s of the tables whose rows must be provided to
+  /// [with_sources](Self::with_sources) to resolve the `JOIN`s and the dynamic `REFERENCE`s
+  /// of the given templates.
+  /// The tables referenced by the `TEMPLATES` of the tables returned here are only returned
+  /// if their metadata is in the given VOTable.
+  pub fn required_sources<C: TableDataContent>(
+    votable: &VOTable<C>,
+    templates: &Templates,
+  ) -> Result<Vec<String>, VOTableError> {
+    let sources = TableSources::default();
+    let mut context = Context::new(votable, &sources, GlobalsMode::Inline);
+    context.missing_sources = Some(Default::default());
+    context.templates_nodes(templates)?;
+    Ok(
+      context
+        .missing_sources
+        .map(RefCell::into_inner)
+        .unwrap_or_default(),
+    )
+  }
+
+  /// Returns the `GLOBALS` `INSTANCE`s referenced by the templates, each referenced instance
+  /// preceding the instances referencing it. Always empty with [GlobalsMode::Inline].
+  pub fn globals(&self) -> &[MaterializedInstance] {
+    &self.globals
+  }
+
+  /// Tells whether the given row fulfills the `WHERE` conditions of the templates (if any).
+  pub fn is_selected(&self, row: &[VOTableValue]) -> bool {
+    is_selected(&self.wheres, row)
+  }
+
+  /// Returns one object tree per `INSTANCE` child of `TEMPLATES`, filled with the given row values.
+  pub fn materialize_row(&self, row: &[VOTableValue]) -> Result<Vec<Value>, VOTableError> {
+    self
+      .instances
+      .iter()
+      .map(|instance| instance.materialize(row))
+      .collect()
+  }
+
+  /// Same as [materialize_row](Self::materialize_row), returning [MaterializedInstance]s.
+  pub fn materialize_instances(
+    &self,
+    row: &[VOTableValue],
+  ) -> Result<Vec<MaterializedInstance>, VOTableError> {
+    self
+      .instances
+      .iter()
+      .map(|instance| {
+        instance
+          .materialize(row)
+          .and_then(MaterializedInstance::from_value)
+      })
+      .collect()
+  }
+}
+
+fn is_selected(wheres: &[(usize, VOTableValue)], row: &[VOTableValue]) -> bool {
+  wheres
+    .iter()
+    .all(|(index, expected)| row.get(*index) == Some(expected))
+}
+
+/// The columns and the `PARAM`s a `ref` may point to.
+#[derive(Debug, Clone, Default)]
+struct Scope {
+  /// Column index and schema, by `FIELD` `ID` and by `FIELD` name.
+  columns: HashMap<String, (usize, Schema)>,
+  /// `PARAM` values, by `PARAM` `ID` and by `PARAM` name.
+  params: HashMap<String, Value>,
+}
+
+impl Scope {
+  /// The `VALUES` reference of the `PARAM` (if any) is resolved using the given registry.
+  fn push_param(&mut self, param: &Param, registry: &ValuesRegistry) {
+    let field = registry.resolve_field(&param.field).unwrap_or_else(|e| {
+      warn!("{} (PARAM '{}'). Reference ignored.", e, param.field.name);
+      Cow::Borrowed(&param.field)
+    });
+    let value = Schema::from(field.as_ref())
+      .value_from_str(&param.value)
+      .ok()
+      .and_then(|value| to_json(&value).ok())
+      .unwrap_or_else(|| Value::String(param.value.clone()));
+    if let Some(id) = &param.field.id {
+      self.params.insert(id.clone(), value.clone());
+    }
+    self.params.insert(param.field.name.clone(), value);
+  }
+
+  /// The column schemas are the ones used to read the table rows (see [TableSchema]).
+  fn push_table<C: TableDataContent>(&mut self, table: &Table<C>, registry: &ValuesRegistry) {
+    let schema = TableSchema::from(table.elems.as_slice()).unwrap();
+    let mut index = 0;
+    walk_table_elems::<C, _>(&table.elems, &mut |meta| match meta {
+      Meta::Field(field) => {
+        let schema = schema[index].clone();
+        if let Some(id) = &field.id {
+          self.columns.insert(id.clone(), (index, schema.clone()));
+        }
+        self
+          .columns
+          .entry(field.name.clone())
+          .or_insert((index, schema));
+        index += 1;
+      }
+      Meta::Param(param) => self.push_param(param, registry),
+      _ => (),
+    });
+  }
+}
+
+/// An element of a `GLOBALS` block which can be referenced by its `dmid`.
+#[derive(Clone, Copy)]
+enum Global<'a> {
+  Instance(&'a GlobalsInstance),
+  CollectionItem(&'a GlobalsCollectionInstance),
+  Collection(&'a GlobalsCollection),
+}
+
+/// Column index and expected value of each `WHERE` condition of a `TEMPLATES`.
+type Wheres = Vec<(usize, VOTableValue)>;
+
+/// Everything needed to resolve the references of the templates of a VOTable.
+struct Context<'a> {
+  /// `PARAM`s not in a table.
+  globals_scope: Scope,
+  /// Scope of each table, with the table `ID` and `name`.
+  tables: Vec<(Option<&'a str>, Option<&'a str>, Scope)>,
+  templates: Vec<&'a Templates>,
+  globals: HashMap<&'a str, Global<'a>>,
+  sources: &'a TableSources,
+  globals_mode: GlobalsMode,
+  /// Already materialized `GLOBALS` elements, by `dmid` (in the order they have been materialized).
+  global_values: RefCell<Map<String, Value>>,
+  /// Already built indices.
+  indices: RefCell<HashMap<String, Arc<KeyIndex>>>,
+  /// `dmid`s and `tableref`s being resolved, to detect cycles.
+  stack: RefCell<Vec<String>>,
+  /// If set, the `tableref`s of the tables whose rows are needed but not provided are collected
+  /// here instead of returning an error.
+  missing_sources: Option<RefCell<Vec<String>>>,
+}
+
+impl<'a> Context<'a> {
+  fn new<C: TableDataContent>(
+    votable: &'a VOTable<C>,
+    sources: &'a TableSources,
+    globals_mode: GlobalsMode,
+  ) -> Self {
+    let registry = ValuesRegistry::from_votable(votable);
+    let mut globals_scope = Scope::default();
+    let mut tables = Vec::new();
+    let mut in_table = false;
+    walk_votable(votable, &mut |meta| match meta {
+      Meta::TableStart(table) => {
+        in_table = true;
+        tables.push(table);
+      }
+      Meta::TableEnded => in_table = false,
+      Meta::Param(param) if !in_table => globals_scope.push_param(param, &registry),
+      _ => (),
+    });
+    let tables = tables
+      .into_iter()
+      .map(|table| {
+        let mut scope = globals_scope.clone();
+        scope.push_table(table, &registry);
+        (table.id.as_deref(), table.name.as_deref(), scope)
+      })
+      .collect();
+    let vodmls = all_vodmls(votable);
+    let mut globals = HashMap::new();
+    for elem in vodmls
+      .iter()
+      .filter_map(|vodml| vodml.globals.as_ref())
+      .flat_map(|globals| globals.elems.iter())
+    {
+      match elem {
+        GlobalsElem::Instance(instance) => {
+          if let Some(dmid) = &instance.dmid {
+            globals.insert(dmid.as_str(), Global::Instance(instance));
+          }
+        }
+        GlobalsElem::Collection(collection) => {
+          globals.insert(collection.dmid.as_str(), Global::Collection(collection));
+          if let GlobalsCollectionElems::InstanceOrRef(items) = &collection.elems {
+            for (dmid, instance) in items.iter().filter_map(|item| match item {
+              GlobalsCollectionItem::Instance(instance) => {
+                instance.dmid.as_ref().map(|dmid| (dmid, instance))
+              }
+              GlobalsCollectionItem::Reference(_) => None,
+            }) {
+              globals.insert(dmid.as_str(), Global::CollectionItem(instance));
+            }
+          }
+        }
+      }
+    }
+    Self {
+      globals_scope,
+      tables,
+      templates: vodmls
+        .iter()
+        .flat_map(|vodml| vodml.templates.iter())
+        .collect(),
+      globals,
+      sources,
+      globals_mode,
+      global_values: Default::default(),
+      indices: Default::default(),
+      stack: Default::default(),
+      missing_sources: None,
+    }
+  }
+
+  /// Returns the `WHERE` conditions and the nodes of the `INSTANCE`s of the given templates.
+  fn templates_nodes(&self, templates: &Templates) -> Result<(Wheres, Vec<Node>), VOTableError> {
+    let tableref = templates.tableref.as_deref();
+    let scope = self.scope(tableref).ok_or_else(|| {
+      VOTableError::Custom(format!(
+        "Table '{}' referenced by TEMPLATES not found",
+        tableref.unwrap_or("")
+      ))
+    })?;
+    let resolver = Resolver {
+      context: self,
+      scope,
+    };
+    if let Some(tableref) = tableref {
+      self.enter(tableref)?;
+    }
+    let wheres = resolver.wheres(templates)?;
+    let instances = templates
+      .instances
+      .iter()
+      .map(|instance| resolver.instance(instance))
+      .collect::<Result<Vec<Node>, VOTableError>>()?;
+    Ok((wheres, instances))
+  }
+
+  /// Scope of the table having the given `ID` (or `name`), or of the first table if `None`.
+  fn scope(&self, tableref: Option<&str>) -> Option<&Scope> {
+    match tableref {
+      None => self.tables.first(),
+      Some(tableref) => self
+        .tables
+        .iter()
+        .find(|(id, _, _)| *id == Some(tableref))
+        .or_else(|| {
+          self
+            .tables
+            .iter()
+            .find(|(_, name, _)| *name == Some(tableref))
+        }),
+    }
+    .map(|(_, _, scope)| scope)
+  }
+
+  /// Returns the `tableref` of the `TEMPLATES` containing the `INSTANCE` having the given `dmid`.
+  fn tableref_of_instance(&self, dmid: &str) -> Option<&'a str> {
+    self
+      .templates
+      .iter()
+      .find(|templates| {
+        templates
+          .instances
+          .iter()
+          .any(|instance| instance.dmid.as_deref() == Some(dmid))
+      })
+      .and_then(|templates| templates.tableref.as_deref())
+  }
+
+  fn enter(&self, id: &str) -> Result<(), VOTableError> {
+    let mut stack = self.stack.borrow_mut();
+    if stack.iter().any(|elem| elem == id) {
+      Err(VOTableError::Custom(format!(
+        "Cyclic MIVOT references: {} -> {}",
+        stack.join(" -> "),
+        id
+      )))
+    } else {
+      stack.push(id.to_string());
+      Ok(())
+    }
+  }
+
+  fn leave(&self) {
+    self.stack.borrow_mut().pop();
+  }
+
+  fn globals_resolver(&self) -> Resolver<'_, 'a> {
+    Resolver {
+      context: self,
+      scope: &self.globals_scope,
+    }
+  }
+
+  /// Returns the materialized `GLOBALS` `INSTANCE` or `COLLECTION` having the given `dmid`.
+  fn global_value(&self, dmref: &str) -> Result<Value, VOTableError> {
+    if let Some(value) = self.global_values.borrow().get(dmref) {
+      return Ok(value.clone());
+    }
+    let global = *self.globals.get(dmref).ok_or_else(|| {
+      VOTableError::Custom(format!(
+        "Unresolved reference: no INSTANCE or COLLECTION having dmid '{}' in GLOBALS",
+        dmref
+      ))
+    })?;
+    self.enter(dmref)?;
+    let resolver = self.globals_resolver();
+    let value = match global {
+      Global::Instance(instance) => resolver
+        .global_instance(&instance.dmtype, &instance.dmid, &instance.elems)
+        .and_then(|node| node.materialize(&[])),
+      Global::CollectionItem(instance) => resolver
+        .global_instance(&instance.dmtype, &instance.dmid, &instance.elems)
+        .and_then(|node| node.materialize(&[])),
+      Global::Collection(collection) => self
+        .globals_index(collection)
+        .map(|index| Value::Array(index.items)),
+    };
+    self.leave();
+    let value = value?;
+    self
+      .global_values
+      .borrow_mut()
+      .insert(dmref.to_string(), value.clone());
+    Ok(value)
+  }
+
+  /// Tells whether the given `dmid` is the one of a `GLOBALS` `INSTANCE` (possibly in a `COLLECTION`).
+  fn is_global_instance(&self, dmid: &str) -> bool {
+    matches!(
+      self.globals.get(dmid),
+      Some(Global::Instance(_) | Global::CollectionItem(_))
+    )
+  }
+
+  /// Returns the primary key of the `GLOBALS` `INSTANCE` having the given `dmid`.
+  fn global_primary_key(&self, dmref: &str) -> Vec<String> {
+    match self.globals.get(dmref) {
+      Some(Global::Instance(instance)) => static_key(&instance.primarykeys),
+      Some(Global::CollectionItem(instance)) => static_key(&instance.primarykeys),
+      _ => Vec::new(),
+    }
+  }
+
+  /// Returns the index of the `GLOBALS` `COLLECTION` having the given `dmid`, or of the instances
+  /// built from the rows of the `TEMPLATES` having the given `tableref`.
+  /// For a `TEMPLATES`:
+  /// * `dmref` is the `dmid` of the `INSTANCE` to be built (the first one if `None`);
+  /// * `primary_keys` are the primary key columns (the `PRIMARY_KEY`s of the `INSTANCE` if `None`).
+  fn index(
+    &self,
+    sourceref: &str,
+    dmref: Option<&str>,
+    primary_keys: Option<&[&str]>,
+  ) -> Result<Arc<KeyIndex>, VOTableError> {
+    let key = format!(
+      "{}#{}#{}",
+      sourceref,
+      dmref.unwrap_or(""),
+      primary_keys.map(|pks| pks.join(",")).unwrap_or_default()
+    );
+    if let Some(index) = self.indices.borrow().get(&key) {
+      return Ok(index.clone());
+    }
+    let index = match self.globals.get(sourceref) {
+      Some(Global::Collection(collection)) => {
+        self.enter(sourceref)?;
+        let index = self.globals_index(collection);
+        self.leave();
+        index
+      }
+      _ => {
+        let templates = self
+          .templates
+          .iter()
+          .find(|templates| templates.tableref.as_deref() == Some(sourceref))
+          .ok_or_else(|| {
+            VOTableError::Custom(format!(
+              "Unresolved reference: no COLLECTION having dmid '{}' in GLOBALS, nor TEMPLATES having tableref '{}'",
+              sourceref, sourceref
+            ))
+          })?;
+        self.enter(sourceref)?;
+        let index = self.templates_index(sourceref, templates, dmref, primary_keys);
+        self.leave();
+        index
+      }
+    };
+    let index = Arc::new(index?);
+    self.indices.borrow_mut().insert(key, index.clone());
+    Ok(index)
+  }
+
+  fn globals_index(&self, collection: &GlobalsCollection) -> Result<KeyIndex, VOTableError> {
+    let resolver = self.globals_resolver();
+    let mut index = KeyIndex::default();
+    match &collection.elems {
+      GlobalsCollectionElems::InstanceOrRef(items) => {
+        for item in items {
+          match item {
+            GlobalsCollectionItem::Instance(instance) => index.push(
+              resolver
+                .global_instance(&instance.dmtype, &instance.dmid, &instance.elems)?
+                .materialize(&[])?,
+              static_key(&instance.primarykeys),
+            ),
+            GlobalsCollectionItem::Reference(reference) => index.push(
+              self.global_value(&reference.dmref)?,
+              self.global_primary_key(&reference.dmref),
+            ),
+          }
+        }
+      }
+      GlobalsCollectionElems::Join(join) => {
+        if let Value::Array(items) = resolver.join(join)?.materialize(&[])? {
+          for item in items {
+            index.push(item, Vec::new());
+          }
+        }
+      }
+    }
+    Ok(index)
+  }
+
+  fn templates_index(
+    &self,
+    tableref: &str,
+    templates: &Templates,
+    dmref: Option<&str>,
+    primary_keys: Option<&[&str]>,
+  ) -> Result<KeyIndex, VOTableError> {
+    let rows: &[Vec<VOTableValue>] = match (self.sources.rows.get(tableref), &self.missing_sources)
+    {
+      (Some(rows), _) => rows,
+      (None, Some(missing_sources)) => {
+        let mut missing_sources = missing_sources.borrow_mut();
+        if !missing_sources.iter().any(|missing| missing == tableref) {
+          missing_sources.push(tableref.to_string());
+        }
+        if self.scope(Some(tableref)).is_none() {
+          // The sources of the table can only be looked for with the table metadata
+          return Ok(KeyIndex::default());
+        }
+        &[]
+      }
+      (None, None) => {
+        return Err(VOTableError::Custom(format!(
+          "The rows of table '{}' are needed to resolve a JOIN or a REFERENCE, but were not provided",
+          tableref
+        )))
+      }
+    };
+    let scope = self.scope(Some(tableref)).ok_or_else(|| {
+      VOTableError::Custom(format!(
+        "Table '{}' referenced by TEMPLATES not found",
+        tableref
+      ))
+    })?;
+    let resolver = Resolver {
+      context: self,
+      scope,
+    };
+    let instance = match dmref {
+      Some(dmref) => templates
+        .instances
+        .iter()
+        .find(|instance| instance.dmid.as_deref() == Some(dmref)),
+      None => templates.instances.first(),
+    }
+    .ok_or_else(|| {
+      VOTableError::Custom(format!(
+        "Unresolved reference: no INSTANCE having dmid '{}' in TEMPLATES '{}'",
+        dmref.unwrap_or(""),
+        tableref
+      ))
+    })?;
+    let node = resolver.instance(instance)?;
+    let primary_keys = match primary_keys {
+      Some(primary_keys) => primary_keys
+        .iter()
+        .map(|pk| resolver.column(pk))
+        .collect::<Result<Vec<usize>, VOTableError>>()?,
+      None => instance
+        .primarykeys
+        .iter()
+        .map(|pk| resolver.column(&pk.ref_))
+        .collect::<Result<Vec<usize>, VOTableError>>()?,
+    };
+    let wheres = resolver.wheres(templates)?;
+    let mut index = KeyIndex::default();
+    for row in rows.iter().filter(|row| is_selected(&wheres, row)) {
+      let key = primary_keys
+        .iter()
+        .map(|i| cell(row, *i).map(cell_key))
+        .collect::<Result<Vec<String>, VOTableError>>()?;
+      index.push(node.materialize(row)?, key);
+    }
+    Ok(index)
+  }
+}
+
+/// Builds the nodes of the elements of a `TEMPLATES` (or of the `GLOBALS`) block.
+struct Resolver<'c, 'a> {
+  context: &'c Context<'a>,
+  scope: &'c Scope,
+}
+
+impl Resolver<'_, '_> {
+  fn column(&self, ref_: &str) -> Result<usize, VOTableError> {
+    self
+      .scope
+      .columns
+      .get(ref_)
+      .map(|(index, _)| *index)
+      .ok_or_else(|| VOTableError::Custom(format!("Reference '{}' matches no FIELD", ref_)))
+  }
+
+  fn wheres(&self, templates: &Templates) -> Result<Vec<(usize, VOTableValue)>, VOTableError> {
+    templates
+      .wheres
+      .iter()
+      .map(|w| {
+        self
+          .scope
+          .columns
+          .get(&w.primary_key)
+          .map(|(index, schema)| {
+            let value = schema
+              .value_from_str(&w.value)
+              .unwrap_or_else(|_| VOTableValue::String(w.value.clone()));
+            (*index, value)
+          })
+          .ok_or_else(|| {
+            VOTableError::Custom(format!(
+              "Primary key '{}' of TEMPLATES WHERE matches no FIELD",
+              w.primary_key
+            ))
+          })
+      })
+      .collect()
+  }
+
+  fn instance(&self, instance: &Instance) -> Result<Node, VOTableError> {
+    self.instance_elems(&instance.dmtype, &instance.dmid, &instance.elems)
+  }
+
+  fn instance_elems(
+    &self,
+    dmtype: &str,
+    dmid: &Option<String>,
+    elems: &[InstanceElem],
+  ) -> Result<Node, VOTableError> {
+    let elems = elems
+      .iter()
+      .map(|elem| match elem {
+        InstanceElem::Attribute(attr) => self.attribute_childof_instance(attr),
+        InstanceElem::Instance(instance) => self
+          .instance_elems(&instance.dmtype, &instance.dmid, &instance.elems)
+          .map(|node| (instance.dmrole.clone(), node)),
+        InstanceElem::Reference(Reference::Static(reference)) => self
+          .static_reference(&reference.dmref)
+          .map(|node| (reference.dmrole.clone(), node)),
+        InstanceElem::Reference(Reference::Dynamic(reference)) => self
+          .dynamic_reference(
+            &reference.sourceref,
+            reference.foreignkeys.iter().map(|fk| fk.ref_.as_str()),
+          )
+          .map(|node| (reference.dmrole.clone(), node)),
+        InstanceElem::Collection(collection) => self
+          .collection(&collection.elems)
+          .map(|node| (collection.dmrole.clone(), node)),
+      })
+      .collect::<Result<Vec<(String, Node)>, VOTableError>>()?;
+    Ok(Node::Instance {
+      dmtype: dmtype.to_string(),
+      dmid: dmid.clone(),
+      elems,
+    })
+  }
+
+  fn collection(&self, elems: &CollectionElems) -> Result<Node, VOTableError> {
+    match elems {
+      CollectionElems::Attribute(attrs) => attrs
+        .iter()
+        .map(|attr| self.attribute_childof_collection(attr))
+        .collect(),
+      CollectionElems::Collection(collections) => collections
+        .iter()
+        .map(|collection| self.collection(&collection.elems))
+        .collect(),
+      CollectionElems::InstanceOrRef(elems) => elems
+        .iter()
+        .map(|elem| match elem {
+          InstanceOrRef::Instance(instance) => self.instance(instance),
+          InstanceOrRef::Reference(CollectionReference::Static(reference)) => {
+            self.static_reference(&reference.dmref)
+          }
+          InstanceOrRef::Reference(CollectionReference::Dynamic(reference)) => self
+            .dynamic_reference(
+              &reference.sourceref,
+              reference.foreignkeys.iter().map(|fk| fk.ref_.as_str()),
+            ),
+        })
+        .collect(),
+      CollectionElems::Join(join) => self.join(join),
+    }
+  }
+
+  fn global_instance(
+    &self,
+    dmtype: &str,
+    dmid: &Option<String>,
+    elems: &[GlobalsInstanceElem],
+  ) -> Result<Node, VOTableError> {
+    let elems = elems
+      .iter()
+      .map(|elem| match elem {
+        GlobalsInstanceElem::Attribute(attr) => self.attribute_childof_instance(attr),
+        GlobalsInstanceElem::Instance(instance) => self
+          .global_instance(&instance.dmtype, &instance.dmid, &instance.elems)
+          .map(|node| (instance.dmrole.clone(), node)),
+        GlobalsInstanceElem::Reference(reference) => self
+          .static_reference(&reference.dmref)
+          .map(|node| (reference.dmrole.clone(), node)),
+        GlobalsInstanceElem::Collection(collection) => self
+          .global_collection(&collection.elems)
+          .map(|node| (collection.dmrole.clone(), node)),
+      })
+      .collect::<Result<Vec<(String, Node)>, VOTableError>>()?;
+    Ok(Node::Instance {
+      dmtype: dmtype.to_string(),
+      dmid: dmid.clone(),
+      elems,
+    })
+  }
+
+  fn global_collection(&self, elems: &GlobalsInnerCollectionElems) -> Result<Node, VOTableError> {
+    match elems {
+      GlobalsInnerCollectionElems::Attribute(attrs) => attrs
+        .iter()
+        .map(|attr| self.attribute_childof_collection(attr))
+        .collect(),
+      GlobalsInnerCollectionElems::Collection(collections) => collections
+        .iter()
+        .map(|collection| self.global_collection(&collection.elems))
+        .collect(),
+      GlobalsInnerCollectionElems::InstanceOrRef(elems) => elems
+        .iter()
+        .map(|elem| match elem {
+          GlobalsInnerItem::Instance(instance) => {
+            self.global_instance(&instance.dmtype, &instance.dmid, &instance.elems)
+          }
+          GlobalsInnerItem::Reference(reference) => self.static_reference(&reference.dmref),
+        })
+        .collect(),
+      GlobalsInnerCollectionElems::Join(join) => self.join(join),
+    }
+  }
+
+  fn static_reference(&self, dmref: &str) -> Result<Node, VOTableError> {
+    let value = self.context.global_value(dmref)?;
+    if self.context.globals_mode == GlobalsMode::Once && self.context.is_global_instance(dmref) {
+      let mut map = Map::with_capacity(1);
+      map.insert(String::from("dmref"), Value::String(dmref.to_string()));
+      Ok(Node::Constant(Value::Object(map)))
+    } else {
+      Ok(Node::Constant(value))
+    }
+  }
+
+  fn dynamic_reference<'r, I>(&self, sourceref: &str, foreign_keys: I) -> Result<Node, VOTableError>
+  where
+    I: Iterator<Item = &'r str>,
+  {
+    let foreign_keys = foreign_keys
+      .map(|fk| self.column(fk))
+      .collect::<Result<Vec<usize>, VOTableError>>()?;
+    let index = self.context.index(sourceref, None, None)?;
+    Ok(Node::Lookup {
+      target: sourceref.to_string(),
+      foreign_keys,
+      index,
+      many: false,
+    })
+  }
+
+  /// In a `JOIN` `WHERE`, `primarykey` refers to a column of the current table while
+  /// `foreignkey` refers to a column of the joined table.
+  fn join(&self, join: &Join) -> Result<Node, VOTableError> {
+    let (sourceref, dmref) = match &join.attr {
+      JoinAttributes::DmRef { dmref } => match self.context.tableref_of_instance(dmref) {
+        Some(tableref) => (tableref, Some(dmref.as_str())),
+        None => (dmref.as_str(), None),
+      },
+      JoinAttributes::SrcRef { sourceref } => (sourceref.as_str(), None),
+      JoinAttributes::BothRef { dmref, sourceref } => (sourceref.as_str(), Some(dmref.as_str())),
+    };
+    let foreign_keys = join
+      .wheres
+      .iter()
+      .map(|w| self.column(&w.primary_key))
+      .collect::<Result<Vec<usize>, VOTableError>>()?;
+    let primary_keys: Vec<&str> = join.wheres.iter().map(|w| w.foreign_key.as_str()).collect();
+    let index = self
+      .context
+      .index(sourceref, dmref, Some(primary_keys.as_slice()))?;
+    Ok(Node::Lookup {
+      target: sourceref.to_string(),
+      foreign_keys,
+      index,
+      many: true,
+    })
+  }
+
+  fn attribute_childof_instance(
+    &self,
+    attr: &AttributeChildOfInstance,
+  ) -> Result<(String, Node), VOTableError> {
+    self
+      .attribute(
+        &attr.dmtype,
+        &attr.ref_or_val_or_both,
+        attr.arrayindex,
+        &attr.unit,
+      )
+      .map(|node| (attr.dmrole.clone(), node))
+  }
+
+  fn attribute_childof_collection(
+    &self,
+    attr: &AttributeChildOfCollection,
+  ) -> Result<Node, VOTableError> {
+    self.attribute(
+      &attr.dmtype,
+      &attr.ref_or_val_or_both,
+      attr.arrayindex,
+      &attr.unit,
+    )
+  }
+
+  fn attribute(
+    &self,
+    dmtype: &str,
+    ref_or_val: &RefOrValueOrBoth,
+    arrayindex: Option<u32>,
+    unit: &Option<String>,
+  ) -> Result<Node, VOTableError> {
+    let (ref_, default) = match ref_or_val {
+      RefOrValueOrBoth::Value { value } => (None, Some(value)),
+      RefOrValueOrBoth::Ref { ref_ } => (Some(ref_), None),
+      RefOrValueOrBoth::RefAndValue { ref_, value } => (Some(ref_), Some(value)),
+    };
+    let source = match ref_ {
+      None => Source::Constant(literal(dmtype, default.map(|s| s.as_str()).unwrap_or(""))),
+      Some(ref_) => match (
+        self.scope.columns.get(ref_),
+        self.scope.params.get(ref_),
+        default,
+      ) {
+        (Some((index, schema)), _, default) => Source::Column(
+          *index,
+          default.map(|default| {
+            schema
+              .value_from_str(default)
+              .ok()
+              .and_then(|value| to_json(&value).ok())
+              .unwrap_or_else(|| literal(dmtype, default))
+          }),
+        ),
+        (None, Some(Value::Null), Some(default)) => Source::Constant(literal(dmtype, default)),
+        (None, Some(value), _) => Source::Constant(value.clone()),
+        (None, None, Some(default)) => Source::Constant(literal(dmtype, default)),
+        (None, None, None) => {
+          return Err(VOTableError::Custom(format!(
+            "Reference '{}' of an ATTRIBUTE of type '{}' matches no FIELD or PARAM",
+            ref_, dmtype
+          )))
+        }
+      },
+    };
+    Ok(Node::Attribute {
+      dmtype: dmtype.to_string(),
+      source,
+      arrayindex,
+      unit: unit.clone(),
+      ref_: ref_.cloned(),
+    })
+  }
+}
+
+fn cell(row: &[VOTableValue], index: usize) -> Result<&VOTableValue, VOTableError> {
+  row.get(index).ok_or_else(|| {
+    VOTableError::Custom(format!(
+      "Column index {} out of bounds (row of {} values)",
+      index,
+      row.len()
+    ))
+  })
+}
+
+fn to_json(value: &VOTableValue) -> Result<Value, VOTableError> {
+  serde_json::to_value(value).map_err(|e| VOTableError::Custom(e.to_string()))
+}
+
+/// Primary key made of the values of the given static `PRIMARY_KEY`s.
+fn static_key(primary_keys: &[PrimaryKeyStatic]) -> Vec<String> {
+  primary_keys
+    .iter()
+    .map(|pk| key_part(&literal(&pk.dmtype, &pk.value)))
+    .collect()
+}
+
+fn cell_key(value: &VOTableValue) -> String {
+  to_json(value).map(|v| key_part(&v)).unwrap_or_default()
+}
+
+/// Normalized string representation of a key value, so that e.g. the column value `1.0` matches
+/// the static primary key `"1"`.
+fn key_part(value: &Value) -> String {
+  fn from_f64(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 {
+      format!("{}", v as i64)
+    } else {
+      v.to_string()
+    }
+  }
+  match value {
+    Value::String(s) => {
+      let s = s.trim();
+      if let Ok(v) = s.parse::<i64>() {
+        v.to_string()
+      } else if let Ok(v) = s.parse::<f64>() {
+        from_f64(v)
+      } else {
+        s.to_string()
+      }
+    }
+    Value::Number(n) => match (n.as_i64(), n.as_u64(), n.as_f64()) {
+      (Some(v), _, _) => v.to_string(),
+      (_, Some(v), _) => v.to_string(),
+      (_, _, Some(v)) => from_f64(v),
+      _ => n.to_string(),
+    },
+    value => value.to_string(),
+  }
+}
+
+/// Interprets a literal `value` according to the (basic) type it is supposed to have.
+fn literal(dmtype: &str, value: &str) -> Value {
+  let basic_type = dmtype.rsplit(':').next().unwrap_or("").to_lowercase();
+  let parsed = if basic_type.starts_with("bool") {
+    match value.trim() {
+      "true" | "T" | "1" => Some(Value::Bool(true)),
+      "false" | "F" | "0" => Some(Value::Bool(false)),
+      _ => None,
+    }
+  } else if basic_type.starts_with("real") || basic_type == "double" || basic_type == "float" {
+    value.trim().parse::<f64>().ok().map(Value::from)
+  } else if basic_type.starts_with("int") || basic_type == "long" || basic_type == "short" {
+    value.trim().parse::<i64>().ok().map(Value::from)
+  } else {
+    None
+  };
+  parsed.unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::{all_templates, GlobalsMode, RowMaterializer, TableSources};
+  use crate::{
+    data::DataElem,
+    impls::{mem::InMemTableDataRows, VOTableValue},
+    votable::VOTableWrapper,
+  };
+
+  #[test]
+  fn test_materialize_rows() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="meas" url="https://www.ivoa.net/xml/Meas/20200908/Meas-v1.0.vo-dml.xml"/>
+        <GLOBALS>
+          <INSTANCE dmid="SpaceFrame_ICRS" dmtype="coords:SpaceFrame">
+            <ATTRIBUTE dmrole="coords:SpaceFrame.spaceRefFrame" dmtype="ivoa:string" value="ICRS"/>
+          </INSTANCE>
+        </GLOBALS>
+        <TEMPLATES tableref="Results">
+          <INSTANCE dmtype="meas:Position">
+            <ATTRIBUTE dmrole="meas:Measure.ucd" dmtype="ivoa:string" value="pos"/>
+            <INSTANCE dmrole="meas:Measure.coord" dmtype="coords:LonLatPoint">
+              <ATTRIBUTE dmrole="coords:LonLatPoint.lon" dmtype="ivoa:RealQuantity" ref="ra" unit="deg"/>
+              <ATTRIBUTE dmrole="coords:LonLatPoint.lat" dmtype="ivoa:RealQuantity" ref="dec" unit="deg"/>
+              <ATTRIBUTE dmrole="coords:LonLatPoint.dist" dmtype="ivoa:RealQuantity" ref="dist" value="10" unit="pc"/>
+              <REFERENCE dmrole="coords:Coordinate.coordSys" dmref="SpaceFrame_ICRS"/>
+            </INSTANCE>
+            <INSTANCE dmrole="meas:Measure.error" dmtype="meas:Ellipse">
+              <ATTRIBUTE dmrole="meas:Ellipse.posAngle" dmtype="ivoa:real" ref="pa"/>
+              <COLLECTION dmrole="meas:Ellipse.semiAxis">
+                <ATTRIBUTE dmtype="ivoa:RealQuantity" ref="err" arrayindex="0" unit="mas"/>
+                <ATTRIBUTE dmtype="ivoa:RealQuantity" ref="err" arrayindex="1" unit="mas"/>
+              </COLLECTION>
+            </INSTANCE>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="Results">
+      <PARAM name="pa" datatype="double" value="12.5"/>
+      <FIELD name="ra" datatype="double" unit="deg"/>
+      <FIELD ID="dec" name="DEJ2000" datatype="double" unit="deg"/>
+      <FIELD name="dist" datatype="double" unit="pc"/>
+      <FIELD name="err" datatype="float" arraysize="2" unit="mas"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>10.5</TD><TD>-20.25</TD><TD>3.5</TD><TD>1 2</TD></TR>
+          <TR><TD>11</TD><TD>21</TD><TD></TD><TD>0.5 0.25</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let templates = all_templates(&votable);
+    assert_eq!(templates.len(), 1);
+    let materializer = RowMaterializer::new(&votable, templates[0]).unwrap();
+    let rows = match &votable
+      .get_first_table()
+      .unwrap()
+      .data
+      .as_ref()
+      .unwrap()
+      .data
+    {
+      DataElem::TableData(data) => data.content.rows.clone(),
+      _ => unreachable!(),
+    };
+    assert!(materializer.is_selected(&rows[0]));
+    let instances = materializer.materialize_row(&rows[1]).unwrap();
+    assert_eq!(instances.len(), 1);
+    assert_eq!(
+      instances[0],
+      json!({
+        "dmtype": "meas:Position",
+        "meas:Measure.ucd": { "dmtype": "ivoa:string", "value": "pos" },
+        "meas:Measure.coord": {
+          "dmtype": "coords:LonLatPoint",
+          "coords:LonLatPoint.lon": { "dmtype": "ivoa:RealQuantity", "value": 11.0, "unit": "deg", "ref": "ra" },
+          "coords:LonLatPoint.lat": { "dmtype": "ivoa:RealQuantity", "value": 21.0, "unit": "deg", "ref": "dec" },
+          "coords:LonLatPoint.dist": { "dmtype": "ivoa:RealQuantity", "value": 10.0, "unit": "pc", "ref": "dist" },
+          "coords:Coordinate.coordSys": {
+            "dmtype": "coords:SpaceFrame",
+            "dmid": "SpaceFrame_ICRS",
+            "coords:SpaceFrame.spaceRefFrame": { "dmtype": "ivoa:string", "value": "ICRS" }
+          }
+        },
+        "meas:Measure.error": {
+          "dmtype": "meas:Ellipse",
+          "meas:Ellipse.posAngle": { "dmtype": "ivoa:real", "value": 12.5, "ref": "pa" },
+          "meas:Ellipse.semiAxis": [
+            { "dmtype": "ivoa:RealQuantity", "value": 0.5, "unit": "mas", "ref": "err" },
+            { "dmtype": "ivoa:RealQuantity", "value": 0.25, "unit": "mas", "ref": "err" }
+          ]
+        }
+      })
+    );
+    // Unknown reference
+    let mut templates = templates[0].clone();
+    templates.instances[0] =
+      templates.instances[0]
+        .clone()
+        .push_attribute(crate::mivot::AttributeI::from_ref(
+          "r",
+          "ivoa:real",
+          "unknown",
+        ));
+    assert!(RowMaterializer::new(&votable, &templates).is_err());
+  }
+
+  #[test]
+  fn test_materialize_template_errors() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="test" url="https://example.org/test.vo-dml.xml"/>
+        <TEMPLATES tableref="Results">
+          <WHERE primarykey="band" value="G"/>
+          <INSTANCE dmtype="test:Mag">
+            <ATTRIBUTE dmrole="test:Mag.value" dmtype="ivoa:real" ref="mag"/>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="Results">
+      <FIELD name="band" datatype="char" arraysize="*"/>
+      <FIELD name="mag" datatype="double"/>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let materialize_err = |xml: &str| {
+      let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+        .unwrap()
+        .unwrap();
+      RowMaterializer::new(&votable, all_templates(&votable)[0])
+        .err()
+        .map(|e| e.to_string())
+        .unwrap_or_default()
+    };
+    assert_eq!(materialize_err(xml), "");
+    // Unknown table
+    let err = materialize_err(&xml.replace(
+      r#"TEMPLATES tableref="Results""#,
+      r#"TEMPLATES tableref="Unknown""#,
+    ));
+    assert!(err.contains("Table 'Unknown'"), "{}", err);
+    // WHERE on an unknown column
+    let err = materialize_err(&xml.replace(r#"primarykey="band""#, r#"primarykey="filter""#));
+    assert!(err.contains("'filter'"), "{}", err);
+    // ATTRIBUTE referencing an unknown column, with or without default value
+    let err = materialize_err(&xml.replace(r#"ref="mag""#, r#"ref="flux""#));
+    assert!(err.contains("'flux'"), "{}", err);
+    assert_eq!(
+      materialize_err(&xml.replace(r#"ref="mag""#, r#"ref="flux" value="1.5""#)),
+      ""
+    );
+    // Rows not matching the table
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let materializer = RowMaterializer::new(&votable, all_templates(&votable)[0]).unwrap();
+    let short_row = [VOTableValue::String(String::from("G"))];
+    assert!(materializer.is_selected(&short_row));
+    assert!(materializer.materialize_row(&short_row).is_err());
+  }
+
+  #[test]
+  fn test_materialize_values_ref_nulls() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="test" url="https://example.org/test.vo-dml.xml"/>
+        <TEMPLATES tableref="Results">
+          <INSTANCE dmtype="test:Flags">
+            <ATTRIBUTE dmrole="test:Flags.b" dmtype="ivoa:integer" ref="b" value="-1"/>
+            <ATTRIBUTE dmrole="test:Flags.p" dmtype="ivoa:integer" ref="p"/>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="Results">
+      <PARAM name="p" datatype="int" value="-1"><VALUES ref="flag"/></PARAM>
+      <FIELD name="a" datatype="int"><VALUES ID="flag" null="-1"/></FIELD>
+      <FIELD name="b" datatype="int"><VALUES ref="flag"/></FIELD>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let materializer = RowMaterializer::new(&votable, all_templates(&votable)[0]).unwrap();
+    let instances = materializer
+      .materialize_row(&[VOTableValue::Int(1), VOTableValue::Null])
+      .unwrap();
+    assert_eq!(
+      instances[0],
+      json!({
+        "dmtype": "test:Flags",
+        "test:Flags.b": { "dmtype": "ivoa:integer", "value": null, "ref": "b" },
+        "test:Flags.p": { "dmtype": "ivoa:integer", "value": null, "ref": "p" }
+      })
+    );
+  }
+
+  #[test]
+  fn test_materialize_join_keys() {
+    use crate::impls::VOTableValue;
+
+    // Host and joined columns have different names: `primarykey` refers to the host table
+    // while `foreignkey` refers to the joined table
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="test" url="https://example.org/test.vo-dml.xml"/>
+        <TEMPLATES tableref="Sources">
+          <INSTANCE dmtype="test:Source">
+            <ATTRIBUTE dmrole="test:Source.id" dmtype="ivoa:string" ref="src_id"/>
+            <COLLECTION dmrole="test:Source.fluxes">
+              <JOIN dmref="Flux">
+                <WHERE primarykey="src_id" foreignkey="lc_src"/>
+                <WHERE primarykey="src_band" foreignkey="lc_band"/>
+              </JOIN>
+            </COLLECTION>
+          </INSTANCE>
+        </TEMPLATES>
+        <TEMPLATES tableref="LightCurves">
+          <INSTANCE dmid="Flux" dmtype="test:Flux">
+            <ATTRIBUTE dmrole="test:Flux.value" dmtype="ivoa:real" ref="flux"/>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="Sources">
+      <FIELD name="src_id" datatype="char" arraysize="*"/>
+      <FIELD name="src_band" datatype="char" arraysize="*"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>A</TD><TD>G</TD></TR>
+          <TR><TD>B</TD><TD>G</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+    <TABLE ID="LightCurves">
+      <FIELD name="lc_band" datatype="char" arraysize="*"/>
+      <FIELD name="flux" datatype="double"/>
+      <FIELD name="lc_src" datatype="char" arraysize="*"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>G</TD><TD>1.5</TD><TD>A</TD></TR>
+          <TR><TD>V</TD><TD>2.5</TD><TD>A</TD></TR>
+          <TR><TD>G</TD><TD>3.5</TD><TD>A</TD></TR>
+          <TR><TD>G</TD><TD>4.5</TD><TD>C</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let templates = all_templates(&votable);
+    let sources = TableSources::from_votable(&votable);
+    let materializer = RowMaterializer::with_sources(&votable, templates[0], &sources).unwrap();
+    let flux = |value: f64| {
+      json!({
+        "dmtype": "test:Flux",
+        "dmid": "Flux",
+        "test:Flux.value": { "dmtype": "ivoa:real", "value": value, "ref": "flux" }
+      })
+    };
+    let row = [
+      VOTableValue::String(String::from("A")),
+      VOTableValue::String(String::from("G")),
+    ];
+    assert_eq!(
+      materializer.materialize_row(&row).unwrap()[0]["test:Source.fluxes"],
+      json!([flux(1.5), flux(3.5)])
+    );
+    let row = [
+      VOTableValue::String(String::from("B")),
+      VOTableValue::String(String::from("G")),
+    ];
+    assert_eq!(
+      materializer.materialize_row(&row).unwrap()[0]["test:Source.fluxes"],
+      json!([])
+    );
+    // Keys given the wrong way round: the joined table columns are not in the host table
+    let xml = xml
+      .replace(
+        r#"primarykey="src_id" foreignkey="lc_src""#,
+        r#"primarykey="lc_src" foreignkey="src_id""#,
+      )
+      .replace(
+        r#"primarykey="src_band" foreignkey="lc_band""#,
+        r#"primarykey="lc_band" foreignkey="src_band""#,
+      );
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(&xml)
+      .unwrap()
+      .unwrap();
+    let err =
+      RowMaterializer::with_sources(&votable, all_templates(&votable)[0], &sources).unwrap_err();
+    assert!(err.to_string().contains("lc_src"));
+  }
+
+  #[test]
+  fn test_materialize_references_and_joins() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="mango" url="https://ivoa.net/mango.vo-dml.xml"/>
+        <GLOBALS>
+          <COLLECTION dmid="Filters">
+            <INSTANCE dmid="Filter_G" dmtype="Phot:PhotFilter">
+              <PRIMARY_KEY dmtype="ivoa:string" value="G"/>
+              <ATTRIBUTE dmrole="Phot:PhotFilter.name" dmtype="ivoa:string" value="Gaia G"/>
+            </INSTANCE>
+            <INSTANCE dmid="Filter_V" dmtype="Phot:PhotFilter">
+              <PRIMARY_KEY dmtype="ivoa:string" value="V"/>
+              <ATTRIBUTE dmrole="Phot:PhotFilter.name" dmtype="ivoa:string" value="Johnson V"/>
+            </INSTANCE>
+          </COLLECTION>
+        </GLOBALS>
+        <TEMPLATES tableref="Sources">
+          <INSTANCE dmtype="mango:Source">
+            <ATTRIBUTE dmrole="mango:Source.id" dmtype="ivoa:integer" ref="id"/>
+            <REFERENCE dmrole="mango:Source.refFilter" sourceref="Filters">
+              <FOREIGN_KEY ref="band"/>
+            </REFERENCE>
+            <COLLECTION dmrole="mango:Source.photometry">
+              <JOIN dmref="Mag">
+                <WHERE primarykey="id" foreignkey="src"/>
+              </JOIN>
+            </COLLECTION>
+          </INSTANCE>
+        </TEMPLATES>
+        <TEMPLATES tableref="Photometry">
+          <INSTANCE dmid="Mag" dmtype="mango:Mag">
+            <PRIMARY_KEY dmtype="ivoa:integer" ref="src"/>
+            <ATTRIBUTE dmrole="mango:Mag.value" dmtype="ivoa:real" ref="mag"/>
+            <REFERENCE dmrole="mango:Mag.filter" sourceref="Filters">
+              <FOREIGN_KEY ref="filter"/>
+            </REFERENCE>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="Sources">
+      <FIELD name="id" datatype="long"/>
+      <FIELD name="band" datatype="char" arraysize="*"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>1</TD><TD>G</TD></TR>
+          <TR><TD>2</TD><TD>V</TD></TR>
+          <TR><TD>3</TD><TD>R</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+    <TABLE ID="Photometry">
+      <FIELD name="src" datatype="int"/>
+      <FIELD name="filter" datatype="char" arraysize="*"/>
+      <FIELD name="mag" datatype="float"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>1</TD><TD>G</TD><TD>12.5</TD></TR>
+          <TR><TD>2</TD><TD>G</TD><TD>14.25</TD></TR>
+          <TR><TD>1</TD><TD>V</TD><TD>13</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let templates = all_templates(&votable);
+    assert_eq!(templates.len(), 2);
+    // The rows of the joined table are required
+    assert!(RowMaterializer::new(&votable, templates[0]).is_err());
+    let sources = TableSources::from_votable(&votable);
+    let materializer = RowMaterializer::with_sources(&votable, templates[0], &sources).unwrap();
+    let rows = match &votable
+      .get_first_table()
+      .unwrap()
+      .data
+      .as_ref()
+      .unwrap()
+      .data
+    {
+      DataElem::TableData(data) => data.content.rows.clone(),
+      _ => unreachable!(),
+    };
+    let filter_g = json!({
+      "dmtype": "Phot:PhotFilter",
+      "dmid": "Filter_G",
+      "Phot:PhotFilter.name": { "dmtype": "ivoa:string", "value": "Gaia G" }
+    });
+    let filter_v = json!({
+      "dmtype": "Phot:PhotFilter",
+      "dmid": "Filter_V",
+      "Phot:PhotFilter.name": { "dmtype": "ivoa:string", "value": "Johnson V" }
+    });
+    assert_eq!(
+      materializer.materialize_row(&rows[0]).unwrap()[0],
+      json!({
+        "dmtype": "mango:Source",
+        "mango:Source.id": { "dmtype": "ivoa:integer", "value": 1, "ref": "id" },
+        "mango:Source.refFilter": filter_g.clone(),
+        "mango:Source.photometry": [
+          {
+            "dmtype": "mango:Mag",
+            "dmid": "Mag",
+            "mango:Mag.value": { "dmtype": "ivoa:real", "value": 12.5, "ref": "mag" },
+            "mango:Mag.filter": filter_g
+          },
+          {
+            "dmtype": "mango:Mag",
+            "dmid": "Mag",
+            "mango:Mag.value": { "dmtype": "ivoa:real", "value": 13.0, "ref": "mag" },
+            "mango:Mag.filter": filter_v
+          }
+        ]
+      })
+    );
+    // No filter having the primary key 'R'
+    let err = materializer.materialize_row(&rows[2]).unwrap_err();
+    assert!(err.to_string().contains("Filters"));
+  }
+
+  #[test]
+  fn test_materialize_unresolved_references() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="test" url="https://example.org/test.vo-dml.xml"/>
+        <GLOBALS>
+          <INSTANCE dmid="Survey" dmtype="test:Survey">
+            <ATTRIBUTE dmrole="test:Survey.name" dmtype="ivoa:string" value="S"/>
+            <REFERENCE dmrole="test:Survey.system" dmref="System"/>
+          </INSTANCE>
+          <INSTANCE dmid="System" dmtype="test:System">
+            <ATTRIBUTE dmrole="test:System.name" dmtype="ivoa:string" value="AB"/>
+          </INSTANCE>
+          <COLLECTION dmid="Filters">
+            <INSTANCE dmid="Filter_G" dmtype="test:Filter">
+              <PRIMARY_KEY dmtype="ivoa:string" value="G"/>
+            </INSTANCE>
+          </COLLECTION>
+        </GLOBALS>
+        <TEMPLATES tableref="Sources">
+          <INSTANCE dmtype="test:Source">
+            <REFERENCE dmrole="test:Source.survey" dmref="Survey"/>
+            <REFERENCE dmrole="test:Source.filter" sourceref="Filters">
+              <FOREIGN_KEY ref="band"/>
+            </REFERENCE>
+            <COLLECTION dmrole="test:Source.mags">
+              <JOIN dmref="Mag">
+                <WHERE primarykey="id" foreignkey="src"/>
+              </JOIN>
+            </COLLECTION>
+          </INSTANCE>
+        </TEMPLATES>
+        <TEMPLATES tableref="Photometry">
+          <INSTANCE dmid="Mag" dmtype="test:Mag">
+            <ATTRIBUTE dmrole="test:Mag.value" dmtype="ivoa:real" ref="mag"/>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="Sources">
+      <FIELD name="id" datatype="int"/>
+      <FIELD name="band" datatype="char" arraysize="*"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>1</TD><TD>G</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+    <TABLE ID="Photometry">
+      <FIELD name="src" datatype="int"/>
+      <FIELD name="mag" datatype="float"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>1</TD><TD>12.5</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let materialize_err = |xml: &str, with_sources: bool| {
+      let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+        .unwrap()
+        .unwrap();
+      let sources = if with_sources {
+        TableSources::from_votable(&votable)
+      } else {
+        TableSources::new()
+      };
+      RowMaterializer::with_sources(&votable, all_templates(&votable)[0], &sources)
+        .err()
+        .map(|e| e.to_string())
+        .unwrap_or_default()
+    };
+    assert_eq!(materialize_err(xml, true), "");
+    // Rows of the joined table not provided
+    let err = materialize_err(xml, false);
+    assert!(err.contains("'Photometry'"), "{}", err);
+    // Unknown GLOBALS instance, including in a GLOBALS instance
+    for (from, to) in [
+      (r#"dmref="Survey""#, r#"dmref="Unknown""#),
+      (r#"dmref="System""#, r#"dmref="Unknown""#),
+    ] {
+      let err = materialize_err(&xml.replace(from, to), true);
+      assert!(err.contains("Unresolved reference"), "{}", err);
+      assert!(err.contains("'Unknown'"), "{}", err);
+    }
+    // Unknown GLOBALS collection or TEMPLATES
+    for (from, to) in [
+      (r#"sourceref="Filters""#, r#"sourceref="Unknown""#),
+      (r#"JOIN dmref="Mag""#, r#"JOIN dmref="Unknown""#),
+    ] {
+      let err = materialize_err(&xml.replace(from, to), true);
+      assert!(err.contains("Unresolved reference"), "{}", err);
+      assert!(err.contains("'Unknown'"), "{}", err);
+    }
+    // Foreign keys matching no column of the current or of the joined table
+    for (from, to, column) in [
+      (
+        r#"FOREIGN_KEY ref="band""#,
+        r#"FOREIGN_KEY ref="filter""#,
+        "'filter'",
+      ),
+      (r#"primarykey="id""#, r#"primarykey="src_id""#, "'src_id'"),
+      (r#"foreignkey="src""#, r#"foreignkey="source""#, "'source'"),
+    ] {
+      let err = materialize_err(&xml.replace(from, to), true);
+      assert!(err.contains(column), "{}", err);
+    }
+    // Cyclic references between GLOBALS instances
+    let cyclic = xml.replace(
+      r#"<ATTRIBUTE dmrole="test:System.name" dmtype="ivoa:string" value="AB"/>"#,
+      r#"<REFERENCE dmrole="test:System.survey" dmref="Survey"/>"#,
+    );
+    let err = materialize_err(&cyclic, true);
+    assert!(err.contains("Cyclic"), "{}", err);
+  }
+
+  #[test]
+  fn test_materialize_globals_once() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="meas" url="https://www.ivoa.net/xml/Meas/20200908/Meas-v1.0.vo-dml.xml"/>
+        <GLOBALS>
+          <INSTANCE dmid="SpaceFrame_ICRS" dmtype="coords:SpaceFrame">
+            <ATTRIBUTE dmrole="coords:SpaceFrame.spaceRefFrame" dmtype="ivoa:string" value="ICRS"/>
+          </INSTANCE>
+          <INSTANCE dmid="SpaceSys_ICRS" dmtype="coords:SpaceSys">
+            <REFERENCE dmrole="coords:PhysicalCoordSys.frame" dmref="SpaceFrame_ICRS"/>
+          </INSTANCE>
+        </GLOBALS>
+        <TEMPLATES tableref="Results">
+          <INSTANCE dmtype="coords:LonLatPoint">
+            <ATTRIBUTE dmrole="coords:LonLatPoint.lon" dmtype="ivoa:RealQuantity" ref="ra" unit="deg"/>
+            <REFERENCE dmrole="coords:Coordinate.coordSys" dmref="SpaceSys_ICRS"/>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="Results">
+      <FIELD name="ra" datatype="double" unit="deg"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>10.5</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let templates = all_templates(&votable);
+    let row = [VOTableValue::Double(10.5)];
+    // Inlined
+    let materializer = RowMaterializer::new(&votable, templates[0]).unwrap();
+    assert!(materializer.globals().is_empty());
+    let instances = materializer.materialize_instances(&row).unwrap();
+    assert_eq!(
+      serde_json::to_string(&instances[0]).unwrap(),
+      r#"{"dmtype":"coords:LonLatPoint","coords:LonLatPoint.lon":{"dmtype":"ivoa:RealQuantity","value":10.5,"unit":"deg","ref":"ra"},"coords:Coordinate.coordSys":{"dmtype":"coords:SpaceSys","dmid":"SpaceSys_ICRS","coords:PhysicalCoordSys.frame":{"dmtype":"coords:SpaceFrame","dmid":"SpaceFrame_ICRS","coords:SpaceFrame.spaceRefFrame":{"dmtype":"ivoa:string","value":"ICRS"}}}}"#
+    );
+    assert_eq!(
+      instances[0].to_value(),
+      materializer.materialize_row(&row).unwrap()[0]
+    );
+    // Emitted once
+    let materializer = RowMaterializer::with_globals_mode(
+      &votable,
+      templates[0],
+      &TableSources::new(),
+      GlobalsMode::Once,
+    )
+    .unwrap();
+    let globals = materializer
+      .globals()
+      .iter()
+      .map(|instance| serde_json::to_string(instance).unwrap())
+      .collect::<Vec<String>>();
+    assert_eq!(
+      globals,
+      vec![
+        r#"{"dmtype":"coords:SpaceFrame","dmid":"SpaceFrame_ICRS","coords:SpaceFrame.spaceRefFrame":{"dmtype":"ivoa:string","value":"ICRS"}}"#,
+        r#"{"dmtype":"coords:SpaceSys","dmid":"SpaceSys_ICRS","coords:PhysicalCoordSys.frame":{"dmref":"SpaceFrame_ICRS"}}"#,
+      ]
+    );
+    let instances = materializer.materialize_instances(&row).unwrap();
+    assert_eq!(
+      serde_json::to_string(&instances[0]).unwrap(),
+      r#"{"dmtype":"coords:LonLatPoint","coords:LonLatPoint.lon":{"dmtype":"ivoa:RealQuantity","value":10.5,"unit":"deg","ref":"ra"},"coords:Coordinate.coordSys":{"dmref":"SpaceSys_ICRS"}}"#
+    );
+  }
+}
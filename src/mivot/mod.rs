@@ -2,16 +2,24 @@
 
 use std::error::Error;
 
+pub mod annotate;
 pub mod attribute;
+pub mod builder;
 pub mod globals;
 pub mod join;
+pub mod materialize;
 pub mod model;
+pub mod project;
+pub mod registry;
 pub mod report;
+pub mod stream;
 pub mod templates;
+pub mod validate;
 pub mod visitors;
 pub mod vodml;
 
 pub use self::{
+  annotate::Annotator,
   attribute::{AttributeChildOfCollection as AttributeC, AttributeChildOfInstance as AttributeI},
   globals::{
     collection::{
@@ -27,8 +35,12 @@ pub use self::{
     Globals,
   },
   join::{r#where::Where as WhereJ, Join},
+  materialize::{GlobalsMode, MaterializedInstance, RowMaterializer, TableSources},
   model::Model,
+  project::{FieldChange, Projection, ProjectionReport},
+  registry::Registry,
   report::Report,
+  stream::{scan_vodmls, MaterializedRows, MivotRowStream, VodmlBlock},
   templates::{
     instance::{
       collection::{
@@ -43,6 +55,7 @@ pub use self::{
     r#where::Where as WhereT,
     Templates,
   },
+  validate::{validate, validate_with_registry, Diagnostic},
   vodml::Vodml,
 };
 
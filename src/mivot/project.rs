@@ -0,0 +1,705 @@
+//! Keep MIVOT annotations consistent when the `FIELD`s they refer to are renamed, removed or
+//! replaced by constant values.
+//!
+//! A [Projection] maps, for each edited table, `ref`s (i.e. `FIELD` `ID`s, or names) on
+//! [FieldChange]s. Applying it on a `VODML` block only modifies the `TEMPLATES` mapping an edited
+//! table (the `GLOBALS` can only refer to `PARAM`s, and the `FIELD`s of two different tables may
+//! share the same name):
+//! * renames the `ref`s of `ATTRIBUTE`s, `PRIMARY_KEY`s and `FOREIGN_KEY`s, and the keys of `WHERE`s;
+//! * replaces the `ref` of an `ATTRIBUTE` by a constant `value` if the referenced column has been
+//!   replaced by a constant (e.g. by a `PARAM` having no `ID`);
+//! * replaces the `ref` of an `ATTRIBUTE` by its default `value` (if any) when the referenced
+//!   column is removed, and else removes the `ATTRIBUTE`;
+//! * removes a `PRIMARY_KEY` or a `WHERE` of a `TEMPLATES` whose key is removed;
+//! * removes a dynamic `REFERENCE` (resp. a `COLLECTION` made of a `JOIN`) if one of its
+//!   `FOREIGN_KEY`s (resp. one of the `WHERE` keys of the `JOIN`) is removed;
+//! * prunes an `INSTANCE` as soon as one of its mandatory roles (according to the data models of a
+//!   [Registry]) is removed, or when it no longer has any child. The pruning is recursive: the
+//!   parent `INSTANCE` of a pruned `INSTANCE` may be pruned in turn.
+//!
+//! Roles of models missing from the registry are considered optional.
+
+use std::collections::HashMap;
+
+use crate::{
+  resource::{Resource, ResourceOrTable},
+  table::Table,
+  votable::VOTable,
+  TableDataContent,
+};
+
+use super::{
+  attribute::RefOrValueOrBoth,
+  join::{Join, JoinAttributes},
+  materialize::{all_templates, find_table},
+  registry::Registry,
+  templates::{
+    instance::{
+      collection::{
+        collection::{CollectionElems, InstanceOrRef},
+        reference::Reference as CollectionReference,
+      },
+      primary_key::{PrimaryKey, PrimaryKeyDyn},
+      reference::Reference,
+      InstanceElem,
+    },
+    Templates,
+  },
+  vodml::Vodml,
+};
+
+/// Change undergone by a `FIELD`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldChange {
+  /// The `FIELD` is now referred to by the given `ref`.
+  Rename(String),
+  /// The `FIELD` has been removed.
+  Remove,
+  /// The `FIELD` has been replaced by the given constant value.
+  Value(String),
+}
+
+/// Summary of the modifications made by [Projection::apply].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProjectionReport {
+  /// Number of renamed `ref`s (and `WHERE` keys).
+  pub n_renamed: usize,
+  /// Number of `ATTRIBUTE` `ref`s replaced by a constant `value`.
+  pub n_valued: usize,
+  /// Removed elements, e.g. `ATTRIBUTE dmrole=meas:Measure.coord`.
+  pub removed: Vec<String>,
+}
+
+impl ProjectionReport {
+  /// Tells whether the annotations have been left untouched.
+  pub fn is_empty(&self) -> bool {
+    self.n_renamed == 0 && self.n_valued == 0 && self.removed.is_empty()
+  }
+
+  fn append(&mut self, mut other: Self) {
+    self.n_renamed += other.n_renamed;
+    self.n_valued += other.n_valued;
+    self.removed.append(&mut other.removed);
+  }
+}
+
+/// Set of `FIELD` renamings, removals and replacements by constant values.
+///
+/// Tables are identified by their `ID`, or by their `name` if they have no `ID`
+/// (see [Projection::table_key]).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Projection {
+  /// Map from the edited tables to the map from the `ref`s in the annotations to their change.
+  changes: HashMap<String, HashMap<String, FieldChange>>,
+}
+
+impl Projection {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Returns the key identifying the given table in a projection, i.e. its `ID` or, if it has no
+  /// `ID`, its `name` (or an empty string if it has neither).
+  pub fn table_key<C: TableDataContent>(table: &Table<C>) -> &str {
+    table
+      .id
+      .as_deref()
+      .or(table.name.as_deref())
+      .unwrap_or_default()
+  }
+
+  /// Records that the `FIELD` of `table` referred to by `ref_` is now referred to by `new_ref`.
+  pub fn rename<S: Into<String>>(mut self, table: S, ref_: S, new_ref: S) -> Self {
+    self.rename_by_ref(table, ref_, new_ref);
+    self
+  }
+  pub fn rename_by_ref<S: Into<String>>(&mut self, table: S, ref_: S, new_ref: S) {
+    self.set_change_by_ref(table, ref_, FieldChange::Rename(new_ref.into()));
+  }
+
+  /// Records that the `FIELD` of `table` referred to by `ref_` has been removed.
+  pub fn remove<S: Into<String>>(mut self, table: S, ref_: S) -> Self {
+    self.remove_by_ref(table, ref_);
+    self
+  }
+  pub fn remove_by_ref<S: Into<String>>(&mut self, table: S, ref_: S) {
+    self.set_change_by_ref(table, ref_, FieldChange::Remove);
+  }
+
+  /// Records that the `FIELD` of `table` referred to by `ref_` has been replaced by the given
+  /// constant value.
+  /// If the column is replaced by a `PARAM` having an `ID`, use [Projection::rename] instead.
+  pub fn replace_by_value<S: Into<String>>(mut self, table: S, ref_: S, value: S) -> Self {
+    self.replace_by_value_by_ref(table, ref_, value);
+    self
+  }
+  pub fn replace_by_value_by_ref<S: Into<String>>(&mut self, table: S, ref_: S, value: S) {
+    self.set_change_by_ref(table, ref_, FieldChange::Value(value.into()));
+  }
+
+  /// Records the change of the `FIELD` of `table` referred to by `ref_`, replacing the previous
+  /// one (if any).
+  pub fn set_change_by_ref<S: Into<String>>(&mut self, table: S, ref_: S, change: FieldChange) {
+    self
+      .changes
+      .entry(table.into())
+      .or_default()
+      .insert(ref_.into(), change);
+  }
+
+  /// Returns the change of the `FIELD` of `table` referred to by `ref_`, if any.
+  pub fn get(&self, table: &str, ref_: &str) -> Option<&FieldChange> {
+    self
+      .changes
+      .get(table)
+      .and_then(|changes| changes.get(ref_))
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.changes.is_empty()
+  }
+
+  /// Applies the projection on the given `VODML` block, using the bundled data models to know
+  /// which roles are mandatory.
+  /// Without the VOTable, the `tableref` of a `TEMPLATES` is taken as the key of its table, and
+  /// `TEMPLATES` having no `tableref` are left untouched: use [Projection::apply_to_votable]
+  /// to resolve `tableref`s.
+  pub fn apply(&self, vodml: &mut Vodml) -> ProjectionReport {
+    self.apply_with_registry(vodml, &Registry::bundled())
+  }
+
+  /// Same as [Projection::apply], using the data models of the given registry.
+  pub fn apply_with_registry(&self, vodml: &mut Vodml, registry: &Registry) -> ProjectionReport {
+    self.apply_with_table_keys(vodml, registry, &|tableref| tableref.map(String::from))
+  }
+
+  /// Applies the projection on all the `VODML` blocks of the given VOTable, the `tableref` of
+  /// each `TEMPLATES` being resolved like in [materialize](super::materialize).
+  pub fn apply_to_votable<C: TableDataContent>(
+    &self,
+    votable: &mut VOTable<C>,
+  ) -> ProjectionReport {
+    let mut report = ProjectionReport::default();
+    if self.is_empty() {
+      return report;
+    }
+    let key_of = |tableref: Option<&str>| {
+      find_table(votable, tableref).map(|table| Self::table_key(table).to_string())
+    };
+    let default_key = key_of(None);
+    let keys: HashMap<String, String> = all_templates(votable)
+      .into_iter()
+      .filter_map(|templates| templates.tableref.as_deref())
+      .filter_map(|tableref| key_of(Some(tableref)).map(|key| (tableref.to_string(), key)))
+      .collect();
+    let table_key = |tableref: Option<&str>| match tableref {
+      Some(tableref) => keys.get(tableref).cloned(),
+      None => default_key.clone(),
+    };
+    let registry = Registry::bundled();
+    for resource in votable.resources.iter_mut() {
+      self.apply_to_resource(resource, &registry, &table_key, &mut report);
+    }
+    report
+  }
+
+  fn apply_to_resource<C: TableDataContent>(
+    &self,
+    resource: &mut Resource<C>,
+    registry: &Registry,
+    table_key: &dyn Fn(Option<&str>) -> Option<String>,
+    report: &mut ProjectionReport,
+  ) {
+    if let Some(vodml) = &mut resource.vodml {
+      report.append(self.apply_with_table_keys(vodml, registry, table_key));
+    }
+    for sub_elem in resource.sub_elems.iter_mut() {
+      if let ResourceOrTable::Resource(sub_resource) = &mut sub_elem.resource_or_table {
+        self.apply_to_resource(sub_resource, registry, table_key, report);
+      }
+    }
+  }
+
+  fn apply_with_table_keys(
+    &self,
+    vodml: &mut Vodml,
+    registry: &Registry,
+    table_key: &dyn Fn(Option<&str>) -> Option<String>,
+  ) -> ProjectionReport {
+    let mut projector = Projector {
+      projection: self,
+      registry,
+      table_key,
+      instance_tablerefs: instance_tablerefs(vodml),
+      changes: None,
+      report: Default::default(),
+    };
+    projector.vodml(vodml);
+    projector.report
+  }
+}
+
+/// Returns the `tableref` of the `TEMPLATES` of each `INSTANCE` having a `dmid` in a `TEMPLATES`,
+/// to know the table joined by a `JOIN` having only a `dmref`.
+fn instance_tablerefs(vodml: &Vodml) -> HashMap<String, Option<String>> {
+  vodml
+    .templates
+    .iter()
+    .flat_map(|templates| {
+      templates.instances.iter().filter_map(|instance| {
+        instance
+          .dmid
+          .clone()
+          .map(|dmid| (dmid, templates.tableref.clone()))
+      })
+    })
+    .collect()
+}
+
+/// Walks the `TEMPLATES` of a `VODML` block, applying a projection.
+/// Methods returns `false` if the visited element has to be removed.
+struct Projector<'a> {
+  projection: &'a Projection,
+  registry: &'a Registry,
+  /// Returns the key, in the projection, of the table mapped by a `tableref` (or by a `TEMPLATES`
+  /// having no `tableref`).
+  table_key: &'a dyn Fn(Option<&str>) -> Option<String>,
+  /// See [instance_tablerefs].
+  instance_tablerefs: HashMap<String, Option<String>>,
+  /// Changes of the table mapped by the `TEMPLATES` being walked.
+  changes: Option<&'a HashMap<String, FieldChange>>,
+  report: ProjectionReport,
+}
+
+impl<'a> Projector<'a> {
+  fn removed(&mut self, tag: &str, key: &str, value: &str) {
+    self
+      .report
+      .removed
+      .push(format!("{} {}={}", tag, key, value));
+  }
+
+  /// Returns the changes of the table mapped by the given `tableref`.
+  fn changes_of(&self, tableref: Option<&str>) -> Option<&'a HashMap<String, FieldChange>> {
+    let projection = self.projection;
+    (self.table_key)(tableref).and_then(|key| projection.changes.get(&key))
+  }
+
+  /// Tells whether the role `dmrole` of `dmtype` is mandatory.
+  fn is_mandatory(&self, dmtype: &str, dmrole: &str) -> bool {
+    self
+      .registry
+      .role(dmtype, dmrole)
+      .is_some_and(|role| role.multiplicity.min > 0)
+  }
+
+  /// Tells whether the given number of items is allowed in the collection of role `dmrole`.
+  fn allows(&self, dmtype: &str, dmrole: &str, n_items: usize) -> bool {
+    self
+      .registry
+      .role(dmtype, dmrole)
+      .is_none_or(|role| role.multiplicity.allows(n_items))
+  }
+
+  /// Returns `false` if the `ATTRIBUTE` has to be removed.
+  fn attribute(&mut self, ref_or_val: &mut RefOrValueOrBoth) -> bool {
+    let (ref_, default) = match ref_or_val {
+      RefOrValueOrBoth::Ref { ref_ } => (ref_, None),
+      RefOrValueOrBoth::RefAndValue { ref_, value } => (ref_, Some(value)),
+      RefOrValueOrBoth::Value { .. } => return true,
+    };
+    match self.changes.and_then(|changes| changes.get(ref_.as_str())) {
+      None => true,
+      Some(FieldChange::Rename(new_ref)) => {
+        ref_.clone_from(new_ref);
+        self.report.n_renamed += 1;
+        true
+      }
+      Some(FieldChange::Value(value)) => {
+        *ref_or_val = RefOrValueOrBoth::from_value(value.clone());
+        self.report.n_valued += 1;
+        true
+      }
+      Some(FieldChange::Remove) => match default {
+        Some(value) => {
+          *ref_or_val = RefOrValueOrBoth::from_value(value.clone());
+          self.report.n_valued += 1;
+          true
+        }
+        None => false,
+      },
+    }
+  }
+
+  /// Returns `false` if the element whose key is `ref_` has to be removed.
+  /// A key cannot be replaced by a constant value.
+  fn key(&mut self, ref_: &mut String, changes: Option<&HashMap<String, FieldChange>>) -> bool {
+    match changes.and_then(|changes| changes.get(ref_.as_str())) {
+      None => true,
+      Some(FieldChange::Rename(new_ref)) => {
+        ref_.clone_from(new_ref);
+        self.report.n_renamed += 1;
+        true
+      }
+      Some(FieldChange::Remove) | Some(FieldChange::Value(_)) => false,
+    }
+  }
+
+  /// Returns `false` if one of the `WHERE` keys of the `JOIN` is removed.
+  /// The primary keys are columns of the table of the `TEMPLATES` being walked, while the foreign
+  /// keys are columns of the joined table.
+  fn join(&mut self, join: &mut Join) -> bool {
+    let joined = match &join.attr {
+      JoinAttributes::SrcRef { sourceref } | JoinAttributes::BothRef { sourceref, .. } => {
+        self.changes_of(Some(sourceref))
+      }
+      JoinAttributes::DmRef { dmref } => match self.instance_tablerefs.get(dmref) {
+        Some(tableref) => self.changes_of(tableref.as_deref()),
+        None => None,
+      },
+    };
+    let mut keep = true;
+    for w in join.wheres.iter_mut() {
+      keep &= self.key(&mut w.foreign_key, joined);
+      keep &= self.key(&mut w.primary_key, self.changes);
+    }
+    keep
+  }
+
+  fn vodml(&mut self, vodml: &mut Vodml) {
+    for templates in vodml.templates.iter_mut() {
+      self.changes = self.changes_of(templates.tableref.as_deref());
+      if self.changes.is_some() {
+        self.templates(templates);
+      }
+    }
+  }
+
+  fn templates(&mut self, templates: &mut Templates) {
+    templates.wheres.retain_mut(|w| {
+      let keep = self.key(&mut w.primary_key, self.changes);
+      if !keep {
+        self.removed("WHERE", "primarykey", &w.primary_key);
+      }
+      keep
+    });
+    templates.instances.retain_mut(|instance| {
+      instance.primarykeys.retain_mut(|pk| self.primarykey(pk));
+      let keep = self.templates_instance(&instance.dmtype, &mut instance.elems);
+      if !keep {
+        self.removed("INSTANCE", "dmtype", &instance.dmtype);
+      }
+      keep
+    });
+  }
+
+  /// Returns `false` if the `PRIMARY_KEY` has to be removed.
+  fn primarykey(&mut self, pk: &mut PrimaryKeyDyn) -> bool {
+    let keep = self.key(&mut pk.ref_, self.changes);
+    if !keep {
+      self.removed("PRIMARY_KEY", "ref", &pk.ref_);
+    }
+    keep
+  }
+
+  /// Returns `false` if the `INSTANCE` of type `dmtype` has to be pruned.
+  fn templates_instance(&mut self, dmtype: &str, elems: &mut Vec<InstanceElem>) -> bool {
+    let n = elems.len();
+    let mut prune = false;
+    elems.retain_mut(|elem| {
+      let (keep, tag, dmrole) = match elem {
+        InstanceElem::Attribute(attr) => (
+          self.attribute(&mut attr.ref_or_val_or_both),
+          "ATTRIBUTE",
+          &attr.dmrole,
+        ),
+        InstanceElem::Instance(instance) => (
+          {
+            instance.primarykeys.retain_mut(|pk| match pk {
+              PrimaryKey::Static(_) => true,
+              PrimaryKey::Dynamic(pk) => self.primarykey(pk),
+            });
+            self.templates_instance(&instance.dmtype, &mut instance.elems)
+          },
+          "INSTANCE",
+          &instance.dmrole,
+        ),
+        InstanceElem::Reference(Reference::Static(_)) => return true,
+        InstanceElem::Reference(Reference::Dynamic(reference)) => {
+          let mut keep = true;
+          for fk in reference.foreignkeys.iter_mut() {
+            keep &= self.key(&mut fk.ref_, self.changes);
+          }
+          (keep, "REFERENCE", &reference.dmrole)
+        }
+        InstanceElem::Collection(collection) => {
+          let keep = match &mut collection.elems {
+            CollectionElems::Join(join) => self.join(join),
+            elems => self
+              .templates_collection(elems)
+              .is_some_and(|n_items| self.allows(dmtype, &collection.dmrole, n_items)),
+          };
+          (keep, "COLLECTION", &collection.dmrole)
+        }
+      };
+      if !keep {
+        self.removed(tag, "dmrole", dmrole);
+        prune |= self.is_mandatory(dmtype, dmrole);
+      }
+      keep
+    });
+    !prune && (n == 0 || !elems.is_empty())
+  }
+
+  /// Returns the number of items left in the collection, or `None` if the collection has to
+  /// be removed.
+  fn templates_collection(&mut self, elems: &mut CollectionElems) -> Option<usize> {
+    let (n, n_left) = match elems {
+      CollectionElems::Attribute(attrs) => {
+        let n = attrs.len();
+        attrs.retain_mut(|attr| {
+          let keep = self.attribute(&mut attr.ref_or_val_or_both);
+          if !keep {
+            self.removed("ATTRIBUTE", "dmtype", &attr.dmtype);
+          }
+          keep
+        });
+        (n, attrs.len())
+      }
+      CollectionElems::Collection(collections) => {
+        let n = collections.len();
+        collections.retain_mut(|collection| match &mut collection.elems {
+          CollectionElems::Join(join) => self.join(join),
+          elems => self.templates_collection(elems).is_some(),
+        });
+        (n, collections.len())
+      }
+      CollectionElems::InstanceOrRef(items) => {
+        let n = items.len();
+        items.retain_mut(|item| match item {
+          InstanceOrRef::Instance(instance) => {
+            instance.primarykeys.retain_mut(|pk| self.primarykey(pk));
+            let keep = self.templates_instance(&instance.dmtype, &mut instance.elems);
+            if !keep {
+              self.removed("INSTANCE", "dmtype", &instance.dmtype);
+            }
+            keep
+          }
+          InstanceOrRef::Reference(CollectionReference::Static(_)) => true,
+          InstanceOrRef::Reference(CollectionReference::Dynamic(reference)) => {
+            let mut keep = true;
+            for fk in reference.foreignkeys.iter_mut() {
+              keep &= self.key(&mut fk.ref_, self.changes);
+            }
+            if !keep {
+              self.removed("REFERENCE", "sourceref", &reference.sourceref);
+            }
+            keep
+          }
+        });
+        (n, items.len())
+      }
+      CollectionElems::Join(join) => return self.join(join).then_some(1),
+    };
+    (n == 0 || n_left > 0).then_some(n_left)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Projection, ProjectionReport};
+  use crate::{
+    impls::mem::InMemTableDataRows,
+    mivot::vodml::Vodml,
+    resource::ResourceOrTable,
+    votable::{new_xml_writer, VOTable, VOTableWrapper},
+    QuickXmlReadWrite,
+  };
+
+  fn get_vodml(votable: &mut VOTable<InMemTableDataRows>) -> &mut Vodml {
+    match &mut votable.resources[0].sub_elems[0].resource_or_table {
+      ResourceOrTable::Resource(resource) => resource.vodml.as_mut().unwrap(),
+      ResourceOrTable::Table(_) => unreachable!(),
+    }
+  }
+
+  fn to_string(vodml: &mut Vodml) -> String {
+    let mut writer = new_xml_writer(Vec::new(), None, Some(2));
+    vodml.write(&mut writer, &()).unwrap();
+    String::from_utf8(writer.into_inner()).unwrap()
+  }
+
+  fn load() -> VOTable<InMemTableDataRows> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="mango"/>
+        <TEMPLATES tableref="gaia">
+          <WHERE primarykey="flag" value="0"/>
+          <INSTANCE dmtype="mango:MangoObject">
+            <ATTRIBUTE dmrole="mango:MangoObject.identifier" dmtype="ivoa:string" ref="source_id"/>
+            <COLLECTION dmrole="mango:MangoObject.propertyDock">
+              <INSTANCE dmtype="mango:EpochPosition">
+                <ATTRIBUTE dmrole="mango:EpochPosition.longitude" dmtype="ivoa:RealQuantity" ref="ra"/>
+                <ATTRIBUTE dmrole="mango:EpochPosition.latitude" dmtype="ivoa:RealQuantity" ref="dec"/>
+                <ATTRIBUTE dmrole="mango:EpochPosition.parallax" dmtype="ivoa:RealQuantity" ref="plx"/>
+                <ATTRIBUTE dmrole="mango:EpochPosition.radialVelocity" dmtype="ivoa:RealQuantity" ref="rv" value="0"/>
+                <INSTANCE dmrole="mango:EpochPosition.obsDate" dmtype="mango:DateTime">
+                  <ATTRIBUTE dmrole="mango:DateTime.representation" dmtype="mango:TimeFormat" value="jyear"/>
+                  <ATTRIBUTE dmrole="mango:DateTime.dateTime" dmtype="ivoa:datetime" ref="epoch"/>
+                </INSTANCE>
+              </INSTANCE>
+            </COLLECTION>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="gaia">
+      <FIELD ID="source_id" name="source_id" datatype="long"/>
+      <DATA><TABLEDATA></TABLEDATA></DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap()
+  }
+
+  #[test]
+  fn test_project_rename_and_replace() {
+    let mut votable = load();
+    let projection = Projection::new()
+      .rename("gaia", "ra", "RAJ2000")
+      .rename("gaia", "flag", "quality")
+      .replace_by_value("gaia", "epoch", "2016.0")
+      .remove("gaia", "plx")
+      .remove("gaia", "rv");
+    let report = projection.apply_to_votable(&mut votable);
+    assert_eq!(
+      report,
+      ProjectionReport {
+        n_renamed: 2,
+        n_valued: 2,
+        removed: vec![String::from(
+          "ATTRIBUTE dmrole=mango:EpochPosition.parallax"
+        )],
+      }
+    );
+    let expected = r#"<VODML xmlns="http://www.ivoa.net/xml/mivot">
+  <MODEL name="mango"/>
+  <TEMPLATES tableref="gaia">
+    <WHERE primarykey="quality" value="0"/>
+    <INSTANCE dmtype="mango:MangoObject">
+      <ATTRIBUTE dmrole="mango:MangoObject.identifier" dmtype="ivoa:string" ref="source_id"/>
+      <COLLECTION dmrole="mango:MangoObject.propertyDock">
+        <INSTANCE dmtype="mango:EpochPosition">
+          <ATTRIBUTE dmrole="mango:EpochPosition.longitude" dmtype="ivoa:RealQuantity" ref="RAJ2000"/>
+          <ATTRIBUTE dmrole="mango:EpochPosition.latitude" dmtype="ivoa:RealQuantity" ref="dec"/>
+          <ATTRIBUTE dmrole="mango:EpochPosition.radialVelocity" dmtype="ivoa:RealQuantity" value="0"/>
+          <INSTANCE dmrole="mango:EpochPosition.obsDate" dmtype="mango:DateTime">
+            <ATTRIBUTE dmrole="mango:DateTime.representation" dmtype="mango:TimeFormat" value="jyear"/>
+            <ATTRIBUTE dmrole="mango:DateTime.dateTime" dmtype="ivoa:datetime" value="2016.0"/>
+          </INSTANCE>
+        </INSTANCE>
+      </COLLECTION>
+    </INSTANCE>
+  </TEMPLATES>
+</VODML>"#;
+    assert_eq!(to_string(get_vodml(&mut votable)), expected);
+  }
+
+  #[test]
+  fn test_project_prune() {
+    let mut votable = load();
+    // Latitude is mandatory: the whole EpochPosition is pruned, and so is the (now empty)
+    // property dock
+    let report = Projection::new()
+      .remove("gaia", "dec")
+      .remove("gaia", "flag")
+      .apply_to_votable(&mut votable);
+    assert_eq!(
+      report.removed,
+      vec![
+        String::from("WHERE primarykey=flag"),
+        String::from("ATTRIBUTE dmrole=mango:EpochPosition.latitude"),
+        String::from("INSTANCE dmtype=mango:EpochPosition"),
+        String::from("COLLECTION dmrole=mango:MangoObject.propertyDock"),
+      ]
+    );
+    let expected = r#"<VODML xmlns="http://www.ivoa.net/xml/mivot">
+  <MODEL name="mango"/>
+  <TEMPLATES tableref="gaia">
+    <INSTANCE dmtype="mango:MangoObject">
+      <ATTRIBUTE dmrole="mango:MangoObject.identifier" dmtype="ivoa:string" ref="source_id"/>
+    </INSTANCE>
+  </TEMPLATES>
+</VODML>"#;
+    assert_eq!(to_string(get_vodml(&mut votable)), expected);
+  }
+
+  #[test]
+  fn test_project_same_name_in_two_tables() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <PARAM name="id" datatype="char" arraysize="*" value="DR3"/>
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="mango"/>
+        <GLOBALS>
+          <INSTANCE dmid="survey" dmtype="mango:MangoObject">
+            <ATTRIBUTE dmrole="mango:MangoObject.identifier" dmtype="ivoa:string" ref="id"/>
+          </INSTANCE>
+        </GLOBALS>
+        <TEMPLATES tableref="gaia">
+          <INSTANCE dmtype="mango:MangoObject">
+            <ATTRIBUTE dmrole="mango:MangoObject.identifier" dmtype="ivoa:string" ref="id"/>
+          </INSTANCE>
+        </TEMPLATES>
+        <TEMPLATES tableref="2mass">
+          <INSTANCE dmtype="mango:MangoObject">
+            <ATTRIBUTE dmrole="mango:MangoObject.identifier" dmtype="ivoa:string" ref="id"/>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="gaia">
+      <FIELD name="id" datatype="long"/>
+      <DATA><TABLEDATA></TABLEDATA></DATA>
+    </TABLE>
+    <TABLE name="2mass">
+      <FIELD name="id" datatype="long"/>
+      <DATA><TABLEDATA></TABLEDATA></DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let mut votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let report = Projection::new()
+      .rename("2mass", "id", "tmass_id")
+      .apply_to_votable(&mut votable);
+    assert_eq!(report.n_renamed, 1);
+    let expected = r#"<VODML xmlns="http://www.ivoa.net/xml/mivot">
+  <MODEL name="mango"/>
+  <GLOBALS>
+    <INSTANCE dmid="survey" dmtype="mango:MangoObject">
+      <ATTRIBUTE dmrole="mango:MangoObject.identifier" dmtype="ivoa:string" ref="id"/>
+    </INSTANCE>
+  </GLOBALS>
+  <TEMPLATES tableref="gaia">
+    <INSTANCE dmtype="mango:MangoObject">
+      <ATTRIBUTE dmrole="mango:MangoObject.identifier" dmtype="ivoa:string" ref="id"/>
+    </INSTANCE>
+  </TEMPLATES>
+  <TEMPLATES tableref="2mass">
+    <INSTANCE dmtype="mango:MangoObject">
+      <ATTRIBUTE dmrole="mango:MangoObject.identifier" dmtype="ivoa:string" ref="tmass_id"/>
+    </INSTANCE>
+  </TEMPLATES>
+</VODML>"#;
+    assert_eq!(to_string(get_vodml(&mut votable)), expected);
+  }
+}
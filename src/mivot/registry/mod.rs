@@ -0,0 +1,521 @@
+//! Registry of [VO-DML](https://www.ivoa.net/documents/VODML/) data models, used to check that
+//! the `dmtype`s and `dmrole`s of a MIVOT annotation exist in the models they refer to.
+//!
+//! The registry is loaded from VO-DML/XML model descriptions. Condensed descriptions (i.e. the
+//! main types, with their attributes, compositions and references) of the models commonly used
+//! in MIVOT annotations (`ivoa`, `meas`, `coords`, `mango` and `Phot`) are bundled, see
+//! [Registry::bundled] (the model version each description is condensed from is given in its
+//! header comment). Other (or complete) model descriptions can be added from disk with
+//! [Registry::load_file].
+//!
+//! A `dmtype` is the `vodml-id` of a type prefixed by the model name, e.g. `meas:Position`.
+//! A `dmrole` is the `vodml-id` of an attribute, composition or reference, prefixed by the name
+//! of the model declaring it, e.g. `meas:Measure.coord`. A role declared in a super-type is also
+//! a role of its sub-types.
+
+use std::{collections::HashMap, fs, path::Path, str};
+
+use once_cell::sync::Lazy;
+use quick_xml::{events::Event, Reader};
+
+use crate::error::VOTableError;
+
+static BUNDLED: Lazy<Registry> = Lazy::new(|| {
+  let mut registry = Registry::new();
+  for xml in [
+    include_str!("ivoa.vo-dml.xml"),
+    include_str!("coords.vo-dml.xml"),
+    include_str!("meas.vo-dml.xml"),
+    include_str!("mango.vo-dml.xml"),
+    include_str!("phot.vo-dml.xml"),
+  ] {
+    registry
+      .load_str(xml)
+      .expect("Wrong bundled VO-DML model description");
+  }
+  registry
+});
+
+/// The kind of a VO-DML type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypeKind {
+  PrimitiveType,
+  Enumeration,
+  DataType,
+  ObjectType,
+}
+
+/// The kind of a VO-DML role.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoleKind {
+  Attribute,
+  Composition,
+  Reference,
+}
+
+/// Multiplicity of a role.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Multiplicity {
+  pub min: u32,
+  /// `None` if unbounded
+  pub max: Option<u32>,
+}
+
+impl Multiplicity {
+  /// Tells whether the given number of values is allowed.
+  pub fn allows(&self, n: usize) -> bool {
+    n >= self.min as usize && self.max.is_none_or(|max| n <= max as usize)
+  }
+}
+
+impl Default for Multiplicity {
+  fn default() -> Self {
+    Self {
+      min: 1,
+      max: Some(1),
+    }
+  }
+}
+
+/// An attribute, composition or reference of a type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Role {
+  /// Identifier in the model, e.g. `Measure.coord`
+  pub vodml_id: String,
+  pub name: String,
+  pub kind: RoleKind,
+  /// `dmtype` of the role values, e.g. `coords:Coordinate`
+  pub datatype: String,
+  pub multiplicity: Multiplicity,
+}
+
+/// A type of a model.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DmType {
+  /// Identifier in the model, e.g. `Position`
+  pub vodml_id: String,
+  pub name: String,
+  pub kind: TypeKind,
+  pub is_abstract: bool,
+  /// `dmtype` of the super-type, if any
+  pub extends: Option<String>,
+  pub roles: Vec<Role>,
+  /// Names of the literals of an enumeration
+  pub literals: Vec<String>,
+}
+
+/// A VO-DML data model.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataModel {
+  /// Name of the model, used as prefix in `dmtype`s and `dmrole`s
+  pub name: String,
+  pub title: Option<String>,
+  pub version: Option<String>,
+  /// Types, by `vodml-id`
+  pub types: HashMap<String, DmType>,
+}
+
+/// A set of data models.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+  models: HashMap<String, DataModel>,
+}
+
+impl Registry {
+  /// Returns an empty registry.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Returns a registry containing the bundled models (`ivoa`, `meas`, `coords`, `mango`
+  /// and `Phot`), to which other models can be added.
+  pub fn bundled() -> Self {
+    BUNDLED.clone()
+  }
+
+  /// Adds the model described in the given VO-DML/XML string, replacing the model of same
+  /// name (if any).
+  pub fn load_str(&mut self, xml: &str) -> Result<&DataModel, VOTableError> {
+    let model = parse_model(xml)?;
+    let name = model.name.clone();
+    self.models.insert(name.clone(), model);
+    Ok(&self.models[&name])
+  }
+
+  /// Adds the model described in the given VO-DML/XML file, replacing the model of same
+  /// name (if any).
+  pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<&DataModel, VOTableError> {
+    let xml = fs::read_to_string(path).map_err(VOTableError::Io)?;
+    self.load_str(&xml)
+  }
+
+  pub fn model(&self, name: &str) -> Option<&DataModel> {
+    self.models.get(name)
+  }
+
+  /// Tells whether the model of the given `dmtype` (or `dmrole`) is in the registry.
+  pub fn knows_model_of(&self, dmtype: &str) -> bool {
+    split(dmtype).is_some_and(|(prefix, _)| self.models.contains_key(prefix))
+  }
+
+  /// Returns the type of given `dmtype`, e.g. `meas:Position`.
+  pub fn get(&self, dmtype: &str) -> Option<&DmType> {
+    split(dmtype)
+      .and_then(|(prefix, vodml_id)| self.models.get(prefix).map(|model| (model, vodml_id)))
+      .and_then(|(model, vodml_id)| model.types.get(vodml_id))
+  }
+
+  /// Returns the given `dmtype` followed by the `dmtype`s of all its (known) super-types.
+  pub fn ancestors<'a>(&'a self, dmtype: &'a str) -> Vec<&'a str> {
+    let mut ancestors = vec![dmtype];
+    let mut current = dmtype;
+    while let Some(parent) = self.get(current).and_then(|t| t.extends.as_deref()) {
+      if ancestors.contains(&parent) {
+        break;
+      }
+      ancestors.push(parent);
+      current = parent;
+    }
+    ancestors
+  }
+
+  /// Tells whether `dmtype` is `supertype` or one of its sub-types.
+  pub fn is_subtype_of(&self, dmtype: &str, supertype: &str) -> bool {
+    self.ancestors(dmtype).contains(&supertype)
+  }
+
+  /// Returns the role `dmrole` of the given type (or of one of its super-types).
+  pub fn role(&self, dmtype: &str, dmrole: &str) -> Option<&Role> {
+    let (prefix, vodml_id) = split(dmrole)?;
+    self
+      .ancestors(dmtype)
+      .into_iter()
+      .filter(|ancestor| split(ancestor).map(|(p, _)| p) == Some(prefix))
+      .filter_map(|ancestor| self.get(ancestor))
+      .flat_map(|t| t.roles.iter())
+      .find(|role| role.vodml_id == vodml_id)
+  }
+
+  /// Returns the `dmtype` of the primitive type values of the given type are made of:
+  /// * the top-most primitive super-type of a primitive type (e.g. `ivoa:string` for `ivoa:Unit`);
+  /// * `ivoa:string` for an enumeration;
+  /// * the primitive type of the `value` attribute of a data type (e.g. `ivoa:real` for
+  ///   `ivoa:RealQuantity`).
+  pub fn primitive(&self, dmtype: &str) -> Option<String> {
+    let t = self.get(dmtype)?;
+    match t.kind {
+      TypeKind::PrimitiveType => self
+        .ancestors(dmtype)
+        .last()
+        .map(|primitive| primitive.to_string()),
+      TypeKind::Enumeration => Some(String::from("ivoa:string")),
+      TypeKind::DataType => self
+        .ancestors(dmtype)
+        .into_iter()
+        .filter_map(|ancestor| self.get(ancestor))
+        .flat_map(|t| t.roles.iter())
+        .find(|role| role.kind == RoleKind::Attribute && role.name == "value")
+        .and_then(|role| self.primitive(&role.datatype)),
+      TypeKind::ObjectType => None,
+    }
+  }
+}
+
+/// Splits a `dmtype` (or `dmrole`) into the model prefix and the `vodml-id`.
+fn split(dmtype: &str) -> Option<(&str, &str)> {
+  dmtype.split_once(':')
+}
+
+/// Minimal XML element tree.
+#[derive(Debug, Default)]
+struct Element {
+  name: String,
+  attrs: Vec<(String, String)>,
+  text: String,
+  children: Vec<Element>,
+}
+
+impl Element {
+  fn child(&self, name: &str) -> Option<&Element> {
+    self.children.iter().find(|child| child.name == name)
+  }
+
+  fn child_text(&self, name: &str) -> Option<&str> {
+    self.child(name).map(|child| child.text.trim())
+  }
+
+  fn attr(&self, name: &str) -> Option<&str> {
+    self
+      .attrs
+      .iter()
+      .find(|(key, _)| key == name)
+      .map(|(_, value)| value.as_str())
+  }
+}
+
+fn read_tree(xml: &str) -> Result<Element, VOTableError> {
+  fn utf8(bytes: &[u8]) -> Result<String, VOTableError> {
+    str::from_utf8(bytes)
+      .map(String::from)
+      .map_err(VOTableError::Utf8)
+  }
+  fn new_element(e: &quick_xml::events::BytesStart) -> Result<Element, VOTableError> {
+    let mut attrs = Vec::new();
+    for attr_res in e.attributes() {
+      let attr = attr_res.map_err(VOTableError::Attr)?;
+      let value = attr.unescaped_value().map_err(VOTableError::Read)?;
+      attrs.push((utf8(attr.key)?, utf8(value.as_ref())?));
+    }
+    Ok(Element {
+      name: utf8(e.local_name())?,
+      attrs,
+      ..Default::default()
+    })
+  }
+  let mut reader = Reader::from_str(xml);
+  let mut buff = Vec::new();
+  let mut stack: Vec<Element> = vec![Element::default()];
+  loop {
+    match reader.read_event(&mut buff).map_err(VOTableError::Read)? {
+      Event::Start(e) => stack.push(new_element(&e)?),
+      Event::Empty(e) => {
+        let elem = new_element(&e)?;
+        stack.last_mut().unwrap().children.push(elem);
+      }
+      Event::End(_) => {
+        let elem = stack.pop().unwrap();
+        match stack.last_mut() {
+          Some(parent) => parent.children.push(elem),
+          None => return Err(VOTableError::Custom(String::from("Unbalanced XML tags"))),
+        }
+      }
+      Event::Text(e) => {
+        let text = e.unescaped().map_err(VOTableError::Read)?;
+        stack
+          .last_mut()
+          .unwrap()
+          .text
+          .push_str(&utf8(text.as_ref())?);
+      }
+      Event::Eof => break,
+      _ => (),
+    }
+    buff.clear();
+  }
+  match (stack.pop(), stack.is_empty()) {
+    (Some(mut root), true) if root.children.len() == 1 => Ok(root.children.remove(0)),
+    _ => Err(VOTableError::Custom(String::from(
+      "Wrong XML document: unbalanced tags or not a single root element",
+    ))),
+  }
+}
+
+fn parse_model(xml: &str) -> Result<DataModel, VOTableError> {
+  let root = read_tree(xml)?;
+  if root.name != "model" {
+    return Err(VOTableError::Custom(format!(
+      "Wrong VO-DML/XML document. Expected root element: 'model'. Actual: '{}'.",
+      root.name
+    )));
+  }
+  let name = root
+    .child_text("name")
+    .filter(|name| !name.is_empty())
+    .ok_or_else(|| VOTableError::Custom(String::from("VO-DML model without name")))?;
+  let mut model = DataModel {
+    name: name.to_string(),
+    title: root.child_text("title").map(String::from),
+    version: root.child_text("version").map(String::from),
+    types: Default::default(),
+  };
+  push_types(&root, &mut model)?;
+  Ok(model)
+}
+
+/// Adds the types of the given `model` or `package` element (and of its sub-packages).
+fn push_types(elem: &Element, model: &mut DataModel) -> Result<(), VOTableError> {
+  for child in elem.children.iter() {
+    let kind = match child.name.as_str() {
+      "primitiveType" => TypeKind::PrimitiveType,
+      "enumeration" => TypeKind::Enumeration,
+      "dataType" => TypeKind::DataType,
+      "objectType" => TypeKind::ObjectType,
+      "package" => {
+        push_types(child, model)?;
+        continue;
+      }
+      _ => continue,
+    };
+    let t = parse_type(child, kind)?;
+    model.types.insert(t.vodml_id.clone(), t);
+  }
+  Ok(())
+}
+
+fn vodml_id(elem: &Element) -> Result<String, VOTableError> {
+  elem
+    .child_text("vodml-id")
+    .filter(|id| !id.is_empty())
+    .map(String::from)
+    .ok_or_else(|| {
+      VOTableError::Custom(format!(
+        "Mandatory 'vodml-id' not found in VO-DML element '{}'",
+        elem.name
+      ))
+    })
+}
+
+fn vodml_ref(elem: &Element, name: &str) -> Option<String> {
+  elem
+    .child(name)
+    .and_then(|child| child.child_text("vodml-ref"))
+    .map(String::from)
+}
+
+fn parse_type(elem: &Element, kind: TypeKind) -> Result<DmType, VOTableError> {
+  let vodml_id = vodml_id(elem)?;
+  let roles = elem
+    .children
+    .iter()
+    .filter_map(|child| match child.name.as_str() {
+      "attribute" => Some((child, RoleKind::Attribute)),
+      "composition" => Some((child, RoleKind::Composition)),
+      "reference" => Some((child, RoleKind::Reference)),
+      _ => None,
+    })
+    .map(|(child, kind)| parse_role(child, kind))
+    .collect::<Result<Vec<Role>, VOTableError>>()?;
+  let literals = elem
+    .children
+    .iter()
+    .filter(|child| child.name == "literal")
+    .filter_map(|child| child.child_text("name").map(String::from))
+    .collect();
+  Ok(DmType {
+    name: elem
+      .child_text("name")
+      .map(String::from)
+      .unwrap_or_else(|| vodml_id.clone()),
+    vodml_id,
+    kind,
+    is_abstract: elem.attr("abstract") == Some("true"),
+    extends: vodml_ref(elem, "extends"),
+    roles,
+    literals,
+  })
+}
+
+fn parse_role(elem: &Element, kind: RoleKind) -> Result<Role, VOTableError> {
+  let vodml_id = vodml_id(elem)?;
+  let datatype = vodml_ref(elem, "datatype").ok_or_else(|| {
+    VOTableError::Custom(format!("Datatype of VO-DML role '{}' not found", vodml_id))
+  })?;
+  let multiplicity = match elem.child("multiplicity") {
+    None => Multiplicity::default(),
+    Some(m) => Multiplicity {
+      min: m
+        .child_text("minOccurs")
+        .and_then(|min| min.parse().ok())
+        .unwrap_or(1),
+      max: match m.child_text("maxOccurs").map(|max| max.parse::<i64>()) {
+        None => Some(1),
+        Some(Ok(max)) if max >= 0 => Some(max as u32),
+        Some(_) => None,
+      },
+    },
+  };
+  Ok(Role {
+    name: elem
+      .child_text("name")
+      .map(String::from)
+      .unwrap_or_else(|| vodml_id.clone()),
+    vodml_id,
+    kind,
+    datatype,
+    multiplicity,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Multiplicity, Registry, RoleKind, TypeKind};
+
+  #[test]
+  fn test_bundled_registry() {
+    let registry = Registry::bundled();
+    for name in ["ivoa", "coords", "meas", "mango", "Phot"] {
+      assert!(registry.model(name).is_some(), "Model {} not found", name);
+    }
+    let position = registry.get("meas:Position").unwrap();
+    assert_eq!(position.kind, TypeKind::ObjectType);
+    assert!(registry.get("meas:Measure").unwrap().is_abstract);
+    assert!(registry.get("meas:Unknown").is_none());
+    assert!(registry.is_subtype_of("coords:LonLatPoint", "coords:Coordinate"));
+    assert!(!registry.is_subtype_of("coords:Coordinate", "coords:LonLatPoint"));
+    // Role inherited from a super-type
+    let role = registry
+      .role("meas:Position", "meas:Measure.error")
+      .unwrap();
+    assert_eq!(role.kind, RoleKind::Attribute);
+    assert_eq!(role.datatype, "meas:Error");
+    assert!(registry
+      .role("meas:Position", "meas:Ellipse.semiAxis")
+      .is_none());
+    assert_eq!(
+      registry
+        .role("meas:Ellipse", "meas:Ellipse.semiAxis")
+        .unwrap()
+        .multiplicity,
+      Multiplicity {
+        min: 2,
+        max: Some(2)
+      }
+    );
+    assert_eq!(
+      registry.primitive("ivoa:RealQuantity").as_deref(),
+      Some("ivoa:real")
+    );
+    assert_eq!(
+      registry.primitive("ivoa:Unit").as_deref(),
+      Some("ivoa:string")
+    );
+    assert_eq!(
+      registry.primitive("ivoa:nonnegativeInteger").as_deref(),
+      Some("ivoa:integer")
+    );
+    assert_eq!(registry.primitive("meas:Position"), None);
+  }
+
+  #[test]
+  fn test_load_custom_model() {
+    let mut registry = Registry::bundled();
+    let model = registry
+      .load_str(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<vo-dml:model xmlns:vo-dml="http://www.ivoa.net/xml/VODML/v1">
+  <name>test</name>
+  <package>
+    <vodml-id>src</vodml-id><name>src</name>
+    <objectType>
+      <vodml-id>src.Source</vodml-id><name>Source</name>
+      <attribute>
+        <vodml-id>src.Source.mags</vodml-id><name>mags</name>
+        <datatype><vodml-ref>ivoa:real</vodml-ref></datatype>
+        <multiplicity><minOccurs>0</minOccurs><maxOccurs>-1</maxOccurs></multiplicity>
+      </attribute>
+    </objectType>
+  </package>
+</vo-dml:model>"#,
+      )
+      .unwrap();
+    assert_eq!(model.name, "test");
+    let role = registry
+      .role("test:src.Source", "test:src.Source.mags")
+      .unwrap();
+    assert_eq!(role.multiplicity.max, None);
+    assert!(role.multiplicity.allows(10));
+    assert!(Registry::new()
+      .load_str("<vo-dml:model><title>no name</title></vo-dml:model>")
+      .is_err());
+  }
+}
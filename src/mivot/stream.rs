@@ -0,0 +1,656 @@
+//! Streaming materialization of the rows of the first table of a VOTable, whatever the location
+//! of the `VODML` block(s) mapping it.
+//!
+//! A `VODML` block lives in a `RESOURCE` of type `meta` which may be located before the table
+//! data, but also after it (e.g. in a `RESOURCE` following the `TABLE`).
+//! Since a [SimpleVOTableRowIterator] stops reading the metadata at the first `DATA` element,
+//! such annotations are only discovered once the whole table has been read.
+//! Two strategies are provided:
+//! * for seekable inputs (e.g. files), the input is first scanned for `VODML` blocks
+//!   (see [scan_vodmls]), then rewound and streamed, the `VODML` blocks located after the data
+//!   being attached to the metadata VOTable. The input is thus parsed twice, the scan going
+//!   through the whole XML, table data included;
+//! * for non-seekable inputs (e.g. stdin), the rows can explicitly be buffered until the
+//!   annotation block is reached (at the cost of keeping all rows in memory).
+//!
+//! ```ignore
+//! let mut stream = MivotRowStream::from_file(path, GlobalsMode::Inline)?;
+//! for instances in stream.rows() {
+//!   let instances = instances?;
+//! }
+//! ```
+
+use std::{
+  fs::File,
+  io::{BufRead, BufReader, Seek, SeekFrom},
+  path::Path,
+  vec::Drain,
+};
+
+use log::warn;
+use quick_xml::{events::Event, Reader};
+
+use crate::{
+  error::VOTableError,
+  impls::{mem::VoidTableDataContent, VOTableValue},
+  iter::{elems::RowValueIterator, SimpleVOTableRowIterator, TableIter, VOTableIterator},
+  resource::Resource,
+  votable::VOTable,
+  QuickXmlReadWrite, VOTableElement,
+};
+
+use super::{
+  materialize::{
+    all_templates, find_table, GlobalsMode, MaterializedInstance, RowMaterializer, TableSources,
+  },
+  templates::Templates,
+  vodml::Vodml,
+};
+
+/// A `VODML` block found while scanning a VOTable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VodmlBlock {
+  /// Position, in bytes from the beginning of the input, of the `<VODML` start tag.
+  pub offset: u64,
+  pub vodml: Vodml,
+}
+
+/// Scans the whole given input and returns all the `VODML` blocks it contains, in document
+/// order, together with their byte offset.
+pub fn scan_vodmls<R: BufRead>(reader: R) -> Result<Vec<VodmlBlock>, VOTableError> {
+  let mut reader = Reader::from_reader(reader);
+  let mut buff: Vec<u8> = Vec::with_capacity(1024);
+  let mut content_buff: Vec<u8> = Vec::with_capacity(1024);
+  let mut blocks = Vec::new();
+  loop {
+    let event = reader.read_event(&mut buff).map_err(VOTableError::Read)?;
+    match event {
+      Event::Start(e) if e.local_name() == Vodml::TAG_BYTES => {
+        // The reader is positioned just after the '>' of the start tag
+        let offset = (reader.buffer_position() - (e.len() + 2)) as u64;
+        let vodml = Vodml::from_event_start(&e)
+          .and_then(|vodml| vodml.read_content(&mut reader, &mut content_buff, &()))?;
+        blocks.push(VodmlBlock { offset, vodml });
+      }
+      Event::Eof => return Ok(blocks),
+      _ => {}
+    }
+    buff.clear();
+  }
+}
+
+enum RowSource<R: BufRead> {
+  /// Rows read from the input while iterating.
+  Stream(Box<SimpleVOTableRowIterator<R>>),
+  /// Rows read in memory before reaching the annotation block.
+  Buffer(Vec<Vec<VOTableValue>>),
+}
+
+/// Materializes, row by row, the `TEMPLATES` mapping the first table of a VOTable.
+pub struct MivotRowStream<R: BufRead> {
+  /// VOTable metadata, including the `VODML` blocks mapping the table.
+  votable: VOTable<VoidTableDataContent>,
+  source: RowSource<R>,
+  materializers: Vec<RowMaterializer>,
+}
+
+impl MivotRowStream<BufReader<File>> {
+  /// Two-phase reading of the given file: the `VODML` blocks are first looked for in the whole
+  /// file, then the table rows are streamed.
+  pub fn from_file<P: AsRef<Path>>(
+    path: P,
+    globals_mode: GlobalsMode,
+  ) -> Result<Self, VOTableError> {
+    let file = File::open(path).map_err(VOTableError::Io)?;
+    Self::from_seekable(BufReader::new(file), globals_mode)
+  }
+}
+
+impl<R: BufRead + Seek> MivotRowStream<R> {
+  /// Two-phase reading of the given seekable input: the `VODML` blocks are first looked for in
+  /// the whole input, which is then rewound to stream the table rows.
+  /// If the `TEMPLATES` contain `JOIN`s or `REFERENCE`s to the rows of other tables, an
+  /// additional pass reads the rows of those tables in memory before streaming.
+  pub fn from_seekable(mut reader: R, globals_mode: GlobalsMode) -> Result<Self, VOTableError> {
+    let start = reader.stream_position().map_err(VOTableError::Io)?;
+    let blocks = scan_vodmls(&mut reader)?;
+    reader
+      .seek(SeekFrom::Start(start))
+      .map_err(VOTableError::Io)?;
+    let mut it = SimpleVOTableRowIterator::from_reader(reader)?;
+    // Blocks located before the data have already been parsed with the metadata
+    let data_offset = it.reader.buffer_position() as u64;
+    let mut votable = it.votable().clone();
+    for block in blocks
+      .into_iter()
+      .filter(|block| block.offset >= data_offset)
+    {
+      votable.push_resource_by_ref(Resource::new().set_type("meta").set_vodml(block.vodml));
+    }
+    let mut tablerefs = first_table_required_sources(&votable)?;
+    let mut sources = TableSources::new();
+    if !tablerefs.is_empty() {
+      let mut reader = it.reader.into_inner();
+      loop {
+        reader
+          .seek(SeekFrom::Start(start))
+          .map_err(VOTableError::Io)?;
+        (votable, sources) = read_sources(&mut reader, &tablerefs)?;
+        // With the metadata of all tables, we may discover tables referenced by the TEMPLATES
+        // of the tables just read
+        let missing: Vec<String> = first_table_required_sources(&votable)?
+          .into_iter()
+          .filter(|tableref| !tablerefs.contains(tableref))
+          .collect();
+        if missing.is_empty() {
+          break;
+        }
+        tablerefs.extend(missing);
+      }
+      reader
+        .seek(SeekFrom::Start(start))
+        .map_err(VOTableError::Io)?;
+      it = SimpleVOTableRowIterator::from_reader(reader)?;
+    }
+    let materializers = first_table_materializers(&votable, &sources, globals_mode)?;
+    Ok(Self {
+      votable,
+      source: RowSource::Stream(Box::new(it)),
+      materializers,
+    })
+  }
+}
+
+impl<R: BufRead> MivotRowStream<R> {
+  /// Reads the given (possibly non-seekable) input.
+  /// If no `TEMPLATES` mapping the first table is found before the table data:
+  /// * if `buffer_rows` is `true`, all rows are read in memory till the end of the VOTable,
+  ///   the annotation blocks located after the data being then taken into account;
+  /// * else, an error is returned.
+  ///
+  /// `JOIN`s and `REFERENCE`s to the rows of other tables are not supported (an error is
+  /// returned), use [from_seekable](MivotRowStream::from_seekable) instead.
+  pub fn from_reader(
+    reader: R,
+    globals_mode: GlobalsMode,
+    buffer_rows: bool,
+  ) -> Result<Self, VOTableError> {
+    let mut it = SimpleVOTableRowIterator::from_reader(reader)?;
+    if has_first_table_templates(it.votable()) {
+      let votable = it.votable().clone();
+      check_no_required_sources(&votable)?;
+      let materializers = first_table_materializers(&votable, &TableSources::new(), globals_mode)?;
+      Ok(Self {
+        votable,
+        source: RowSource::Stream(Box::new(it)),
+        materializers,
+      })
+    } else if buffer_rows {
+      let rows = it
+        .to_row_value_iter()
+        .collect::<Result<Vec<_>, VOTableError>>()?;
+      let votable = it.read_to_end()?;
+      check_no_required_sources(&votable)?;
+      let materializers = first_table_materializers(&votable, &TableSources::new(), globals_mode)?;
+      Ok(Self {
+        votable,
+        source: RowSource::Buffer(rows),
+        materializers,
+      })
+    } else {
+      Err(VOTableError::Custom(String::from(
+        "No MIVOT TEMPLATES mapping the first table found before the table data (rows buffering is disabled)",
+      )))
+    }
+  }
+
+  /// Returns the VOTable metadata, including the `VODML` blocks taken into account.
+  pub fn votable(&self) -> &VOTable<VoidTableDataContent> {
+    &self.votable
+  }
+
+  /// Tells whether the rows have been buffered in memory before reaching the annotation block.
+  pub fn is_buffered(&self) -> bool {
+    matches!(self.source, RowSource::Buffer(_))
+  }
+
+  /// Returns the materialized `GLOBALS` instances referenced by the `TEMPLATES`, each `dmid`
+  /// being returned only once (relevant with [GlobalsMode::Once] only).
+  pub fn globals(&self) -> Vec<&MaterializedInstance> {
+    let mut globals: Vec<&MaterializedInstance> = Vec::new();
+    for instance in self
+      .materializers
+      .iter()
+      .flat_map(|materializer| materializer.globals().iter())
+    {
+      let is_new = instance.dmid.as_ref().is_none_or(|dmid| {
+        !globals
+          .iter()
+          .any(|global| global.dmid.as_ref() == Some(dmid))
+      });
+      if is_new {
+        globals.push(instance);
+      }
+    }
+    globals
+  }
+
+  /// Returns an iterator providing, for each row, the instances of all `TEMPLATES` selecting it.
+  pub fn rows(&mut self) -> MaterializedRows<'_, R> {
+    let rows = match &mut self.source {
+      RowSource::Stream(it) => Rows::Stream(Box::new(it.to_row_value_iter())),
+      RowSource::Buffer(rows) => Rows::Buffer(rows.drain(..)),
+    };
+    MaterializedRows {
+      rows,
+      materializers: &self.materializers,
+    }
+  }
+
+  /// Reads the remaining of the VOTable and returns it.
+  /// The rows must have been consumed (or skipped) before calling this method.
+  pub fn read_to_end(self) -> Result<VOTable<VoidTableDataContent>, VOTableError> {
+    match self.source {
+      RowSource::Stream(it) => it.read_to_end(),
+      RowSource::Buffer(_) => Ok(self.votable),
+    }
+  }
+}
+
+enum Rows<'a, R: BufRead> {
+  Stream(Box<RowValueIterator<'a, R>>),
+  Buffer(Drain<'a, Vec<VOTableValue>>),
+}
+
+/// Iterator on the materialized instances of each row, see [MivotRowStream::rows].
+pub struct MaterializedRows<'a, R: BufRead> {
+  rows: Rows<'a, R>,
+  materializers: &'a [RowMaterializer],
+}
+
+impl<'a, R: BufRead> Iterator for MaterializedRows<'a, R> {
+  type Item = Result<Vec<MaterializedInstance>, VOTableError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let row = match &mut self.rows {
+      Rows::Stream(it) => it.next()?,
+      Rows::Buffer(it) => Ok(it.next()?),
+    };
+    Some(row.and_then(|row| {
+      let mut instances = Vec::new();
+      for materializer in self.materializers.iter() {
+        if materializer.is_selected(&row) {
+          instances.append(&mut materializer.materialize_instances(&row)?);
+        }
+      }
+      Ok(instances)
+    }))
+  }
+}
+
+/// Tells whether the given `TEMPLATES` maps the first table of the given VOTable.
+fn maps_first_table(votable: &VOTable<VoidTableDataContent>, templates: &Templates) -> bool {
+  votable.get_first_table().is_some_and(|table| {
+    find_table(votable, templates.tableref.as_deref())
+      .is_some_and(|mapped| std::ptr::eq(mapped, table))
+  })
+}
+
+/// Tells whether the given VOTable contains at least one `TEMPLATES` mapping its first table.
+fn has_first_table_templates(votable: &VOTable<VoidTableDataContent>) -> bool {
+  all_templates(votable)
+    .into_iter()
+    .any(|templates| maps_first_table(votable, templates))
+}
+
+/// Returns the `tableref`s of the tables whose rows are needed to materialize the first table.
+fn first_table_required_sources(
+  votable: &VOTable<VoidTableDataContent>,
+) -> Result<Vec<String>, VOTableError> {
+  let mut tablerefs: Vec<String> = Vec::new();
+  for templates in all_templates(votable)
+    .into_iter()
+    .filter(|templates| maps_first_table(votable, templates))
+  {
+    for tableref in RowMaterializer::required_sources(votable, templates)? {
+      if !tablerefs.contains(&tableref) {
+        tablerefs.push(tableref);
+      }
+    }
+  }
+  Ok(tablerefs)
+}
+
+/// Returns an error if the rows of other tables are needed to materialize the first table.
+fn check_no_required_sources(votable: &VOTable<VoidTableDataContent>) -> Result<(), VOTableError> {
+  let tablerefs = first_table_required_sources(votable)?;
+  if tablerefs.is_empty() {
+    Ok(())
+  } else {
+    Err(VOTableError::Custom(format!(
+      "The rows of table(s) '{}' are needed to resolve a JOIN or a REFERENCE: not supported in streaming mode on a non-seekable input",
+      tablerefs.join("', '")
+    )))
+  }
+}
+
+/// Reads the whole VOTable, keeping in memory the rows of the tables having the given `ID`s
+/// (or `name`s) only. Returns the VOTable metadata and the rows read.
+fn read_sources<R: BufRead>(
+  reader: R,
+  tablerefs: &[String],
+) -> Result<(VOTable<VoidTableDataContent>, TableSources), VOTableError> {
+  let mut sources = TableSources::new();
+  let mut it = VOTableIterator::from_reader(reader)?;
+  while let Some(mut row_it) = it.next_table_row_value_iter()? {
+    let table = row_it.table();
+    let keys: Vec<String> = table
+      .id
+      .iter()
+      .chain(table.name.iter())
+      .filter(|key| tablerefs.contains(key))
+      .cloned()
+      .collect();
+    if keys.is_empty() {
+      row_it.read_to_end()?;
+    } else {
+      let rows = row_it.collect::<Result<Vec<_>, VOTableError>>()?;
+      for key in keys {
+        sources.insert(key, rows.clone());
+      }
+    }
+  }
+  Ok((it.end_of_it(), sources))
+}
+
+/// Builds a materializer for each `TEMPLATES` mapping the first (i.e. the streamed) table.
+fn first_table_materializers(
+  votable: &VOTable<VoidTableDataContent>,
+  sources: &TableSources,
+  globals_mode: GlobalsMode,
+) -> Result<Vec<RowMaterializer>, VOTableError> {
+  if votable.get_first_table().is_none() {
+    return Err(VOTableError::Custom(String::from(
+      "No table found in the VOTable",
+    )));
+  }
+  let mut materializers = Vec::new();
+  for templates in all_templates(votable) {
+    if maps_first_table(votable, templates) {
+      materializers.push(RowMaterializer::with_globals_mode(
+        votable,
+        templates,
+        sources,
+        globals_mode,
+      )?);
+    } else {
+      warn!(
+        "TEMPLATES of tableref '{}' ignored: it does not map the first table",
+        templates.tableref.as_deref().unwrap_or("")
+      );
+    }
+  }
+  if materializers.is_empty() {
+    Err(VOTableError::Custom(String::from(
+      "No MIVOT TEMPLATES mapping the first table found",
+    )))
+  } else {
+    Ok(materializers)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    fs::File,
+    io::{BufReader, Cursor},
+  };
+
+  use super::{scan_vodmls, GlobalsMode, MivotRowStream};
+
+  const ANNOTATION: &str = r#"<RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="coords" url="https://www.ivoa.net/xml/STC/20200908/Coords-v1.0.vo-dml.xml"/>
+        <GLOBALS>
+          <INSTANCE dmid="SpaceFrame_ICRS" dmtype="coords:SpaceFrame">
+            <ATTRIBUTE dmrole="coords:SpaceFrame.spaceRefFrame" dmtype="ivoa:string" value="ICRS"/>
+          </INSTANCE>
+        </GLOBALS>
+        <TEMPLATES tableref="Results">
+          <INSTANCE dmtype="coords:LonLatPoint">
+            <ATTRIBUTE dmrole="coords:LonLatPoint.lon" dmtype="ivoa:RealQuantity" ref="ra" unit="deg"/>
+            <REFERENCE dmrole="coords:Coordinate.coordSys" dmref="SpaceFrame_ICRS"/>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>"#;
+
+  const TABLE: &str = r#"<TABLE ID="Results">
+      <FIELD name="ra" datatype="double" unit="deg"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>10.5</TD></TR>
+          <TR><TD>20.5</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>"#;
+
+  fn votable(annotation_first: bool) -> String {
+    let (first, second) = if annotation_first {
+      (ANNOTATION, TABLE)
+    } else {
+      (TABLE, ANNOTATION)
+    };
+    format!(
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    {}
+    {}
+  </RESOURCE>
+</VOTABLE>"#,
+      first, second
+    )
+  }
+
+  fn lons(stream: &mut MivotRowStream<impl std::io::BufRead>) -> Vec<String> {
+    stream
+      .rows()
+      .map(|instances| {
+        let instances = instances.unwrap();
+        assert_eq!(instances.len(), 1);
+        instances[0].elems["coords:LonLatPoint.lon"]["value"].to_string()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_scan_vodmls() {
+    let xml = votable(false);
+    let blocks = scan_vodmls(xml.as_bytes()).unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert!(xml[blocks[0].offset as usize..].starts_with("<VODML "));
+    assert_eq!(blocks[0].vodml.templates.len(), 1);
+  }
+
+  #[test]
+  fn test_stream_vodml_after_data() {
+    for annotation_first in [true, false] {
+      let xml = votable(annotation_first);
+      // Two-phase, seekable input
+      let mut stream =
+        MivotRowStream::from_seekable(Cursor::new(xml.as_bytes()), GlobalsMode::Once).unwrap();
+      assert!(!stream.is_buffered());
+      assert_eq!(stream.globals().len(), 1);
+      assert_eq!(lons(&mut stream), vec!["10.5", "20.5"]);
+      let votable = stream.read_to_end().unwrap();
+      assert!(votable.get_first_table().is_some());
+      // Non-seekable input
+      let res = MivotRowStream::from_reader(xml.as_bytes(), GlobalsMode::Inline, false);
+      assert_eq!(res.is_ok(), annotation_first);
+      let mut stream =
+        MivotRowStream::from_reader(xml.as_bytes(), GlobalsMode::Inline, true).unwrap();
+      assert_eq!(stream.is_buffered(), !annotation_first);
+      assert!(stream.globals().is_empty());
+      assert_eq!(lons(&mut stream), vec!["10.5", "20.5"]);
+    }
+  }
+
+  #[test]
+  fn test_stream_errors() {
+    let xml = votable(false);
+    // Annotation after the data of a non-seekable input, without rows buffering
+    let err = MivotRowStream::from_reader(xml.as_bytes(), GlobalsMode::Inline, false)
+      .err()
+      .unwrap();
+    assert!(err.to_string().contains("buffering is disabled"), "{}", err);
+    // No annotation at all, whatever the input
+    let no_annotation = xml.replace(ANNOTATION, "");
+    let err =
+      MivotRowStream::from_seekable(Cursor::new(no_annotation.as_bytes()), GlobalsMode::Inline)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("No MIVOT TEMPLATES"), "{}", err);
+    let err = MivotRowStream::from_reader(no_annotation.as_bytes(), GlobalsMode::Inline, true)
+      .err()
+      .unwrap();
+    assert!(err.to_string().contains("No MIVOT TEMPLATES"), "{}", err);
+    // Annotation of another table
+    let other_table = xml.replace(r#"tableref="Results""#, r#"tableref="Other""#);
+    for res in [
+      MivotRowStream::from_seekable(Cursor::new(other_table.as_bytes()), GlobalsMode::Inline)
+        .map(|_| ()),
+      MivotRowStream::from_reader(other_table.as_bytes(), GlobalsMode::Inline, true).map(|_| ()),
+    ] {
+      assert!(res.is_err());
+    }
+    // The seekable input is rewound to its initial position, not to its beginning
+    let mut cursor = Cursor::new(format!("garbage{}", xml).into_bytes());
+    cursor.set_position(7);
+    let mut stream = MivotRowStream::from_seekable(cursor, GlobalsMode::Inline).unwrap();
+    assert_eq!(lons(&mut stream), vec!["10.5", "20.5"]);
+    // Truncated input
+    let truncated = &xml[..xml.find("<TR><TD>20.5").unwrap()];
+    assert!(
+      MivotRowStream::from_seekable(Cursor::new(truncated.as_bytes()), GlobalsMode::Inline)
+        .is_err()
+    );
+    let unclosed_row = votable(true).replace("<TR><TD>20.5</TD></TR>", "<TR><TD>20.5</TD>");
+    let mut stream =
+      MivotRowStream::from_reader(unclosed_row.as_bytes(), GlobalsMode::Inline, false).unwrap();
+    assert!(stream.rows().any(|instances| instances.is_err()));
+  }
+
+  #[test]
+  fn test_stream_joined_table_rows() {
+    let path = "resources/mivot_appendix_A.xml";
+    // The JOIN of the first table points to the rows of the second table, read beforehand
+    let mut stream = MivotRowStream::from_file(path, GlobalsMode::Inline).unwrap();
+    let n_points: Vec<usize> = stream
+      .rows()
+      .map(|instances| {
+        let instances = instances.unwrap();
+        assert_eq!(instances.len(), 1);
+        instances[0].elems["cube:SparseCube.data"]
+          .as_array()
+          .unwrap()
+          .len()
+      })
+      .collect();
+    assert_eq!(n_points, vec![4, 3, 3]);
+    // Not supported on non-seekable inputs, with or without rows buffering
+    for buffer_rows in [false, true] {
+      let reader = BufReader::new(File::open(path).unwrap());
+      let err = MivotRowStream::from_reader(reader, GlobalsMode::Inline, buffer_rows)
+        .err()
+        .unwrap();
+      assert!(
+        err.to_string().contains("not supported in streaming mode"),
+        "{}",
+        err
+      );
+    }
+  }
+
+  #[test]
+  fn test_stream_nested_joins() {
+    // The first table joins the second one, which references the third one: the latter is only
+    // known once the metadata of the second table has been read
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE type="results">
+    <TABLE ID="Sources">
+      <FIELD name="id" datatype="int"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>1</TD></TR>
+          <TR><TD>2</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+    <TABLE ID="Photometry">
+      <FIELD name="src" datatype="int"/>
+      <FIELD name="band" datatype="char" arraysize="*"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>1</TD><TD>G</TD></TR>
+          <TR><TD>1</TD><TD>V</TD></TR>
+          <TR><TD>2</TD><TD>V</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+    <TABLE ID="Filters">
+      <FIELD name="name" datatype="char" arraysize="*"/>
+      <FIELD name="wavelength" datatype="double"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>G</TD><TD>639.74</TD></TR>
+          <TR><TD>V</TD><TD>551.0</TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="test" url="https://example.org/test.vo-dml.xml"/>
+        <TEMPLATES tableref="Sources">
+          <INSTANCE dmtype="test:Source">
+            <COLLECTION dmrole="test:Source.mags">
+              <JOIN dmref="Mag">
+                <WHERE primarykey="id" foreignkey="src"/>
+              </JOIN>
+            </COLLECTION>
+          </INSTANCE>
+        </TEMPLATES>
+        <TEMPLATES tableref="Photometry">
+          <INSTANCE dmid="Mag" dmtype="test:Mag">
+            <REFERENCE dmrole="test:Mag.filter" sourceref="Filters">
+              <FOREIGN_KEY ref="band"/>
+            </REFERENCE>
+          </INSTANCE>
+        </TEMPLATES>
+        <TEMPLATES tableref="Filters">
+          <INSTANCE dmtype="test:Filter">
+            <PRIMARY_KEY dmtype="ivoa:string" ref="name"/>
+            <ATTRIBUTE dmrole="test:Filter.wavelength" dmtype="ivoa:real" ref="wavelength"/>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let mut stream =
+      MivotRowStream::from_seekable(Cursor::new(xml.as_bytes()), GlobalsMode::Inline).unwrap();
+    let wavelengths: Vec<Vec<String>> = stream
+      .rows()
+      .map(|instances| {
+        instances.unwrap()[0].elems["test:Source.mags"]
+          .as_array()
+          .unwrap()
+          .iter()
+          .map(|mag| mag["test:Mag.filter"]["test:Filter.wavelength"]["value"].to_string())
+          .collect()
+      })
+      .collect();
+    assert_eq!(wavelengths, vec![vec!["639.74", "551.0"], vec!["551.0"]]);
+  }
+}
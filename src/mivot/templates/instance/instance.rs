@@ -137,7 +137,7 @@ impl HasSubElements for Instance {
     &mut self,
     mut reader: &mut Reader<R>,
     mut reader_buff: &mut Vec<u8>,
-    _context: &Self::Context,
+    context: &Self::Context,
   ) -> Result<(), VOTableError> {
     loop {
       let mut event = reader.read_event(reader_buff).map_err(VOTableError::Read)?;
@@ -145,7 +145,9 @@ impl HasSubElements for Instance {
         Event::Start(e) => match e.local_name() {
           Instance::TAG_BYTES => push_from_event_start!(self, Instance, reader, reader_buff, e),
           Reference::TAG_BYTES => push_from_event_start!(self, Reference, reader, reader_buff, e),
-          Collection::TAG_BYTES => push_from_event_start!(self, Collection, reader, reader_buff, e),
+          Collection::TAG_BYTES => self.push_collection_by_ref(
+            Collection::from_event_start_reading_sub_elems(e, context, reader)?,
+          ),
           _ => {
             return Err(VOTableError::UnexpectedStartTag(
               e.local_name().to_vec(),
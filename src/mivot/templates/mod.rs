@@ -20,7 +20,7 @@ pub mod r#where;
 use r#where::Where;
 
 /// Structure storing the content of the `TEMPLATES` tag.
-#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 pub struct Templates {
   #[serde(skip_serializing_if = "Option::is_none")]
   pub tableref: Option<String>,
@@ -30,7 +30,7 @@ pub struct Templates {
   pub instances: Vec<Instance>,
 }
 impl Templates {
-  fn new() -> Self {
+  pub fn new() -> Self {
     Self {
       tableref: None,
       wheres: vec![],
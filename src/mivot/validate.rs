@@ -0,0 +1,1395 @@
+//! Semantic validation of the `VODML` blocks of a VOTable.
+//!
+//! The parser only checks the structure of a MIVOT annotation. This module checks that:
+//! * the `REPORT` status is not `FAILED`;
+//! * `dmid`s are unique in a `VODML` block;
+//! * each `dmref` matches a `dmid`;
+//! * each `sourceref` matches the `dmid` of a `GLOBALS` `COLLECTION` or the `tableref` of a `TEMPLATES`;
+//! * each `tableref` matches the `ID` of a `TABLE`;
+//! * each `ref` (of `ATTRIBUTE`, `PRIMARY_KEY`, `FOREIGN_KEY`) and each `WHERE` key matches the `ID`
+//!   of a `FIELD` or of a `PARAM`. `FIELD`s and `PARAM`s are looked for in the `RESOURCE`
+//!   enclosing the `VODML` block (i.e. the parent of the `RESOURCE` of type `meta`), and in the
+//!   `VOTABLE` for `PARAM`s. A `ref` in `GLOBALS` must point to a `PARAM`, and a `ref` in `TEMPLATES`
+//!   must point to a `PARAM` or to a `FIELD` of the mapped table.
+//!
+//! When a [Registry] of data models is provided (see [validate_with_registry]), it also checks
+//! that:
+//! * each `dmtype` exists (and is not abstract) in its model;
+//! * each `dmrole` is a role of the type of the parent `INSTANCE` (or of one of its super-types),
+//!   and the `dmtype` of the child is the type of the role (or one of its sub-types);
+//! * the number of items of each `COLLECTION` is allowed by the multiplicity of its role;
+//! * the primitive type of an `ATTRIBUTE` `dmtype` is compatible with the `datatype` of the
+//!   referenced `FIELD` or `PARAM`.
+//!
+//! `dmtype`s and `dmrole`s of models missing from the registry are not checked.
+//!
+//! Each diagnostic comes with the XPath-like path of the faulty element, e.g.
+//! `/VOTABLE/RESOURCE[1]/RESOURCE[1]/VODML/TEMPLATES[1]/INSTANCE[1]/ATTRIBUTE[2]`.
+
+use std::{
+  collections::{HashMap, HashSet},
+  fmt::{self, Display, Formatter},
+};
+
+use crate::{
+  datatype::Datatype,
+  param::Param,
+  resource::Resource,
+  votable::VOTable,
+  walk::{walk_resource, walk_votable, Meta},
+  TableDataContent,
+};
+
+use super::{
+  attribute::RefOrValueOrBoth,
+  globals::{
+    collection::{
+      CollectionElems as GlobalsCollectionElems, InstanceOrRef as GlobalsCollectionItem,
+    },
+    instance::{
+      collection::collection::{
+        CollectionElems as GlobalsInnerCollectionElems, InstanceOrRef as GlobalsInnerItem,
+      },
+      InstanceElem as GlobalsInstanceElem,
+    },
+    GlobalsElem,
+  },
+  join::{Join, JoinAttributes},
+  registry::{Registry, Role},
+  report::Status,
+  templates::{
+    instance::{
+      collection::{
+        collection::{CollectionElems, InstanceOrRef},
+        reference::Reference as CollectionReference,
+      },
+      primary_key::PrimaryKey,
+      reference::Reference,
+      InstanceElem,
+    },
+    Templates,
+  },
+  vodml::Vodml,
+};
+
+/// Severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+  /// The annotation can be used, but is not compliant with the MIVOT recommendation.
+  Warning,
+  /// The annotation can not be (fully) used.
+  Error,
+}
+
+impl Display for Severity {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::Warning => "WARNING",
+      Self::Error => "ERROR",
+    })
+  }
+}
+
+/// A semantic issue in a `VODML` block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiagnosticKind {
+  /// `REPORT` with status `FAILED`
+  ReportFailed { content: Option<String> },
+  /// Several elements with the same `dmid`
+  DuplicateDmid { dmid: String },
+  /// `dmref` matching no `dmid`
+  UnknownDmref { dmref: String },
+  /// `sourceref` matching neither a `GLOBALS` `COLLECTION` `dmid` nor a `TEMPLATES` `tableref`
+  UnknownSourceref { sourceref: String },
+  /// `tableref` matching no `TABLE`
+  UnknownTableref { tableref: String },
+  /// `tableref` matching the name, but not the `ID`, of a `TABLE`
+  TablerefByName { tableref: String },
+  /// `ref` (or `WHERE` key) matching no `FIELD` or `PARAM`
+  UnknownRef { ref_: String },
+  /// `ref` (or `WHERE` key) matching the name, but not the `ID`, of a `FIELD` or `PARAM`
+  RefByName { ref_: String },
+  /// `ref` in `GLOBALS` pointing to a `FIELD`
+  FieldRefInGlobals { ref_: String },
+  /// `ref` in `TEMPLATES` pointing to a `FIELD` of another table
+  FieldOfOtherTable { ref_: String, tableref: String },
+  /// Model of a `dmtype` or `dmrole` not in the registry (reported once per model)
+  UnknownModel { model: String },
+  /// `dmtype` not in its model
+  UnknownDmtype { dmtype: String },
+  /// `dmtype` of an abstract type
+  AbstractDmtype { dmtype: String },
+  /// `dmrole` which is not a role of the parent type
+  UnknownDmrole { dmrole: String, dmtype: String },
+  /// `dmtype` which is not the type of its role, or one of its sub-types
+  WrongDmtype {
+    dmrole: String,
+    expected: String,
+    actual: String,
+  },
+  /// Number of `COLLECTION` items not allowed by the multiplicity of the role
+  Multiplicity {
+    dmrole: String,
+    min: u32,
+    max: Option<u32>,
+    actual: usize,
+  },
+  /// `ATTRIBUTE` `dmtype` not compatible with the `datatype` of the referenced `FIELD` or `PARAM`
+  DatatypeMismatch {
+    ref_: String,
+    dmtype: String,
+    datatype: Datatype,
+  },
+}
+
+impl DiagnosticKind {
+  pub fn severity(&self) -> Severity {
+    match self {
+      Self::TablerefByName { .. } | Self::RefByName { .. } | Self::UnknownModel { .. } => {
+        Severity::Warning
+      }
+      _ => Severity::Error,
+    }
+  }
+}
+
+impl Display for DiagnosticKind {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::ReportFailed { content } => write!(
+        f,
+        "annotation process FAILED: '{}'",
+        content.as_deref().unwrap_or("").trim()
+      ),
+      Self::DuplicateDmid { dmid } => write!(f, "duplicated dmid '{}'", dmid),
+      Self::UnknownDmref { dmref } => write!(f, "dmref '{}' matches no dmid", dmref),
+      Self::UnknownSourceref { sourceref } => write!(
+        f,
+        "sourceref '{}' matches neither a GLOBALS COLLECTION dmid nor a TEMPLATES tableref",
+        sourceref
+      ),
+      Self::UnknownTableref { tableref } => {
+        write!(f, "tableref '{}' matches no TABLE ID", tableref)
+      }
+      Self::TablerefByName { tableref } => write!(
+        f,
+        "tableref '{}' matches a TABLE name, but no TABLE ID",
+        tableref
+      ),
+      Self::UnknownRef { ref_ } => write!(f, "ref '{}' matches no FIELD or PARAM ID", ref_),
+      Self::RefByName { ref_ } => write!(
+        f,
+        "ref '{}' matches a FIELD or PARAM name, but no FIELD or PARAM ID",
+        ref_
+      ),
+      Self::FieldRefInGlobals { ref_ } => write!(
+        f,
+        "ref '{}' in GLOBALS points to a FIELD (only PARAMs are allowed)",
+        ref_
+      ),
+      Self::FieldOfOtherTable { ref_, tableref } => write!(
+        f,
+        "ref '{}' points to a FIELD which is not in table '{}'",
+        ref_, tableref
+      ),
+      Self::UnknownModel { model } => {
+        write!(f, "model '{}' not in the registry, not checked", model)
+      }
+      Self::UnknownDmtype { dmtype } => write!(f, "unknown dmtype '{}'", dmtype),
+      Self::AbstractDmtype { dmtype } => write!(f, "dmtype '{}' is abstract", dmtype),
+      Self::UnknownDmrole { dmrole, dmtype } => {
+        write!(f, "dmrole '{}' is not a role of '{}'", dmrole, dmtype)
+      }
+      Self::WrongDmtype {
+        dmrole,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "dmtype '{}' is not a '{}' as expected by dmrole '{}'",
+        actual, expected, dmrole
+      ),
+      Self::Multiplicity {
+        dmrole,
+        min,
+        max,
+        actual,
+      } => write!(
+        f,
+        "{} item(s) for dmrole '{}' of multiplicity {}..{}",
+        actual,
+        dmrole,
+        min,
+        max.map_or_else(|| String::from("*"), |max| max.to_string())
+      ),
+      Self::DatatypeMismatch {
+        ref_,
+        dmtype,
+        datatype,
+      } => write!(
+        f,
+        "dmtype '{}' not compatible with datatype '{}' of '{}'",
+        dmtype, datatype, ref_
+      ),
+    }
+  }
+}
+
+/// A semantic issue, with the path of the faulty element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+  /// XPath-like path of the faulty element
+  pub path: String,
+  pub kind: DiagnosticKind,
+}
+
+impl Diagnostic {
+  pub fn severity(&self) -> Severity {
+    self.kind.severity()
+  }
+}
+
+impl Display for Diagnostic {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(f, "{} {}: {}", self.severity(), self.path, self.kind)
+  }
+}
+
+/// Validates all the `VODML` blocks of the given VOTable.
+/// Returns the list of diagnostics (empty if all annotations are valid).
+pub fn validate<C: TableDataContent>(votable: &VOTable<C>) -> Vec<Diagnostic> {
+  validate_all(votable, None)
+}
+
+/// Same as [validate], also checking the `dmtype`s and `dmrole`s against the data models of the
+/// given registry.
+pub fn validate_with_registry<C: TableDataContent>(
+  votable: &VOTable<C>,
+  registry: &Registry,
+) -> Vec<Diagnostic> {
+  validate_all(votable, Some(registry))
+}
+
+fn validate_all<C: TableDataContent>(
+  votable: &VOTable<C>,
+  registry: Option<&Registry>,
+) -> Vec<Diagnostic> {
+  let mut votable_params = IdIndex::default();
+  let mut diagnostics = Vec::new();
+  // Resources being walked through, with their path and their number of sub-resources
+  let mut stack: Vec<(&Resource<C>, String, usize)> = Vec::new();
+  let mut n_resources = 0;
+  // The `PARAM`s of the VOTable come before its resources
+  walk_votable(votable, &mut |meta| match meta {
+    Meta::ResourceStart(resource) => {
+      let (parent, path) = match stack.last_mut() {
+        Some((parent, path, n_sub_resources)) => {
+          *n_sub_resources += 1;
+          (
+            Some(*parent),
+            format!("{}/RESOURCE[{}]", path, n_sub_resources),
+          )
+        }
+        None => {
+          n_resources += 1;
+          (None, format!("/VOTABLE/RESOURCE[{}]", n_resources))
+        }
+      };
+      if let Some(vodml) = &resource.vodml {
+        let enclosing = match parent {
+          Some(parent) if resource.type_.as_deref() == Some("meta") => parent,
+          _ => resource,
+        };
+        let mut index = votable_params.clone();
+        index.push_resource(enclosing);
+        let mut validator = Validator::new(&index, registry);
+        validator.vodml(vodml, format!("{}/VODML", path));
+        validator.resolve_pending();
+        diagnostics.append(&mut validator.diagnostics);
+      }
+      stack.push((resource, path, 0));
+    }
+    Meta::ResourceEnded => {
+      stack.pop();
+    }
+    Meta::Param(param) if stack.is_empty() => votable_params.push_param(param),
+    _ => (),
+  });
+  diagnostics
+}
+
+/// Builds the XPath-like paths of the children of an element.
+struct Children<'p> {
+  parent: &'p str,
+  counts: HashMap<&'static str, usize>,
+}
+
+impl<'p> Children<'p> {
+  fn new(parent: &'p str) -> Self {
+    Self {
+      parent,
+      counts: Default::default(),
+    }
+  }
+
+  fn path(&mut self, tag: &'static str) -> String {
+    let count = self.counts.entry(tag).or_insert(0);
+    *count += 1;
+    format!("{}/{}[{}]", self.parent, tag, count)
+  }
+}
+
+/// What a `FIELD` or `PARAM` `ID` (or name) points to.
+#[derive(Clone, Copy, Debug)]
+enum Target {
+  /// `FIELD` of the table of given index
+  Field(usize),
+  Param,
+}
+
+/// `ID`s and names of the `TABLE`s, `FIELD`s and `PARAM`s a `VODML` block may refer to.
+#[derive(Clone, Debug, Default)]
+struct IdIndex {
+  /// `ID` and name of each table
+  tables: Vec<(Option<String>, Option<String>)>,
+  ids: HashMap<String, Target>,
+  names: HashMap<String, Target>,
+  /// `datatype` of each `FIELD` and `PARAM`, by `ID` and by name
+  datatypes: HashMap<String, Datatype>,
+}
+
+impl IdIndex {
+  fn push(&mut self, id: &Option<String>, name: &str, datatype: Datatype, target: Target) {
+    if let Some(id) = id {
+      self.ids.insert(id.clone(), target);
+      self.datatypes.insert(id.clone(), datatype);
+    }
+    self.names.entry(name.to_string()).or_insert(target);
+    self.datatypes.entry(name.to_string()).or_insert(datatype);
+  }
+
+  fn push_param(&mut self, param: &Param) {
+    self.push(
+      &param.field.id,
+      &param.field.name,
+      param.field.datatype,
+      Target::Param,
+    );
+  }
+
+  fn push_resource<C: TableDataContent>(&mut self, resource: &Resource<C>) {
+    walk_resource(resource, &mut |meta| match meta {
+      Meta::TableStart(table) => self.tables.push((table.id.clone(), table.name.clone())),
+      Meta::Field(field) => {
+        let target = Target::Field(self.tables.len() - 1);
+        self.push(&field.id, &field.name, field.datatype, target)
+      }
+      Meta::Param(param) => self.push_param(param),
+      _ => (),
+    });
+  }
+
+  /// Returns the index of the table having the given `ID` (`true`) or name (`false`).
+  fn table(&self, tableref: &str) -> Option<(usize, bool)> {
+    self
+      .tables
+      .iter()
+      .position(|(id, _)| id.as_deref() == Some(tableref))
+      .map(|i| (i, true))
+      .or_else(|| {
+        self
+          .tables
+          .iter()
+          .position(|(_, name)| name.as_deref() == Some(tableref))
+          .map(|i| (i, false))
+      })
+  }
+}
+
+/// Elements a `ref` may point to.
+#[derive(Clone, Copy, Debug)]
+enum Scope {
+  /// `PARAM`s only
+  Globals,
+  /// `PARAM`s and `FIELD`s of the given table (or of any table if unknown)
+  Table(Option<usize>),
+}
+
+/// `JOIN` or dynamic `REFERENCE`, checked once the full `VODML` is read.
+struct PendingTarget<'a> {
+  path: String,
+  sourceref: Option<&'a str>,
+  /// Only in a `JOIN`
+  dmref: Option<&'a str>,
+  /// `JOIN` `WHERE` foreign keys, to be looked for in the joined table
+  foreign_keys: Vec<(String, &'a str)>,
+}
+
+struct Validator<'i, 'a> {
+  index: &'i IdIndex,
+  registry: Option<&'i Registry>,
+  /// Models already reported as missing from the registry
+  unknown_models: HashSet<String>,
+  dmids: HashSet<&'a str>,
+  collections: HashSet<&'a str>,
+  /// `tableref` of each `TEMPLATES` with the index of the mapped table (if found)
+  templates: HashMap<&'a str, Option<usize>>,
+  /// `dmid` of each `INSTANCE` child of a `TEMPLATES`, with the index of the mapped table (if found)
+  instances: HashMap<&'a str, Option<usize>>,
+  dmrefs: Vec<(String, &'a str)>,
+  targets: Vec<PendingTarget<'a>>,
+  diagnostics: Vec<Diagnostic>,
+}
+
+impl<'i, 'a> Validator<'i, 'a> {
+  fn new(index: &'i IdIndex, registry: Option<&'i Registry>) -> Self {
+    Self {
+      index,
+      registry,
+      unknown_models: Default::default(),
+      dmids: Default::default(),
+      collections: Default::default(),
+      templates: Default::default(),
+      instances: Default::default(),
+      dmrefs: Default::default(),
+      targets: Default::default(),
+      diagnostics: Default::default(),
+    }
+  }
+
+  fn push(&mut self, path: String, kind: DiagnosticKind) {
+    self.diagnostics.push(Diagnostic { path, kind });
+  }
+
+  fn dmid(&mut self, path: &str, dmid: &'a Option<String>) {
+    if let Some(dmid) = dmid {
+      self.unique_dmid(path, dmid);
+    }
+  }
+
+  fn unique_dmid(&mut self, path: &str, dmid: &'a str) {
+    if !self.dmids.insert(dmid) {
+      self.push(
+        path.to_string(),
+        DiagnosticKind::DuplicateDmid {
+          dmid: dmid.to_string(),
+        },
+      );
+    }
+  }
+
+  fn ref_(&mut self, path: String, ref_: &str, scope: Scope) {
+    let (target, by_id) = match (self.index.ids.get(ref_), self.index.names.get(ref_)) {
+      (Some(target), _) => (*target, true),
+      (None, Some(target)) => (*target, false),
+      (None, None) => {
+        return self.push(
+          path,
+          DiagnosticKind::UnknownRef {
+            ref_: ref_.to_string(),
+          },
+        )
+      }
+    };
+    match (target, scope) {
+      (Target::Field(_), Scope::Globals) => {
+        return self.push(
+          path,
+          DiagnosticKind::FieldRefInGlobals {
+            ref_: ref_.to_string(),
+          },
+        )
+      }
+      (Target::Field(table), Scope::Table(Some(expected))) if table != expected => {
+        let (id, name) = &self.index.tables[expected];
+        let tableref = id.as_ref().or(name.as_ref()).cloned().unwrap_or_default();
+        return self.push(
+          path,
+          DiagnosticKind::FieldOfOtherTable {
+            ref_: ref_.to_string(),
+            tableref,
+          },
+        );
+      }
+      _ => (),
+    }
+    if !by_id {
+      self.push(
+        path,
+        DiagnosticKind::RefByName {
+          ref_: ref_.to_string(),
+        },
+      );
+    }
+  }
+
+  fn attribute(&mut self, path: String, dmtype: &str, ref_or_val: &RefOrValueOrBoth, scope: Scope) {
+    self.dmtype(&path, dmtype);
+    match ref_or_val {
+      RefOrValueOrBoth::Ref { ref_ } | RefOrValueOrBoth::RefAndValue { ref_, .. } => {
+        self.datatype(&path, dmtype, ref_);
+        self.ref_(path, ref_, scope)
+      }
+      RefOrValueOrBoth::Value { .. } => (),
+    }
+  }
+
+  /// Returns the registry if it contains the model of the given `dmtype` (or `dmrole`).
+  /// Reports the model the first time it is found missing.
+  fn model_of(&mut self, path: &str, dmtype: &str) -> Option<&'i Registry> {
+    let registry = self.registry?;
+    if registry.knows_model_of(dmtype) {
+      return Some(registry);
+    }
+    if let Some((model, _)) = dmtype.split_once(':') {
+      if self.unknown_models.insert(model.to_string()) {
+        self.push(
+          path.to_string(),
+          DiagnosticKind::UnknownModel {
+            model: model.to_string(),
+          },
+        );
+      }
+    } else {
+      self.push(
+        path.to_string(),
+        DiagnosticKind::UnknownDmtype {
+          dmtype: dmtype.to_string(),
+        },
+      );
+    }
+    None
+  }
+
+  /// Checks that the given `dmtype` exists and is not abstract.
+  fn dmtype(&mut self, path: &str, dmtype: &str) {
+    let dmtype_def = match self.model_of(path, dmtype) {
+      Some(registry) => registry.get(dmtype),
+      None => return,
+    };
+    match dmtype_def {
+      None => self.push(
+        path.to_string(),
+        DiagnosticKind::UnknownDmtype {
+          dmtype: dmtype.to_string(),
+        },
+      ),
+      Some(dmtype_def) if dmtype_def.is_abstract => self.push(
+        path.to_string(),
+        DiagnosticKind::AbstractDmtype {
+          dmtype: dmtype.to_string(),
+        },
+      ),
+      Some(_) => (),
+    }
+  }
+
+  /// Checks that `dmrole` is a role of the `parent` type and, if given, that `dmtype` is
+  /// compatible with the type of the role.
+  /// Returns the role, if found.
+  fn dmrole(
+    &mut self,
+    path: &str,
+    parent: &str,
+    dmrole: &str,
+    dmtype: Option<&str>,
+  ) -> Option<&'i Role> {
+    if dmrole.is_empty() {
+      return None;
+    }
+    let registry = self.model_of(path, dmrole)?;
+    // Unknown parent types have already been reported
+    registry.get(parent)?;
+    match registry.role(parent, dmrole) {
+      None => {
+        self.push(
+          path.to_string(),
+          DiagnosticKind::UnknownDmrole {
+            dmrole: dmrole.to_string(),
+            dmtype: parent.to_string(),
+          },
+        );
+        None
+      }
+      Some(role) => {
+        if let Some(dmtype) = dmtype {
+          self.subtype(path, dmrole, &role.datatype, dmtype);
+        }
+        Some(role)
+      }
+    }
+  }
+
+  /// Checks that `dmtype` is `expected` (the type of the role `dmrole`) or one of its sub-types.
+  fn subtype(&mut self, path: &str, dmrole: &str, expected: &str, dmtype: &str) {
+    let registry = match self.registry {
+      Some(registry) => registry,
+      None => return,
+    };
+    if registry.get(dmtype).is_some()
+      && registry.get(expected).is_some()
+      && !registry.is_subtype_of(dmtype, expected)
+    {
+      self.push(
+        path.to_string(),
+        DiagnosticKind::WrongDmtype {
+          dmrole: dmrole.to_string(),
+          expected: expected.to_string(),
+          actual: dmtype.to_string(),
+        },
+      );
+    }
+  }
+
+  /// Checks that the number of items of a `COLLECTION` is allowed by the multiplicity of its role.
+  fn multiplicity(&mut self, path: &str, dmrole: &str, role: Option<&Role>, n_items: usize) {
+    if let Some(role) = role.filter(|role| !role.multiplicity.allows(n_items)) {
+      self.push(
+        path.to_string(),
+        DiagnosticKind::Multiplicity {
+          dmrole: dmrole.to_string(),
+          min: role.multiplicity.min,
+          max: role.multiplicity.max,
+          actual: n_items,
+        },
+      );
+    }
+  }
+
+  /// Checks that the primitive type of `dmtype` is compatible with the datatype of `ref_`.
+  fn datatype(&mut self, path: &str, dmtype: &str, ref_: &str) {
+    let primitive = match self
+      .registry
+      .and_then(|registry| registry.primitive(dmtype))
+    {
+      Some(primitive) => primitive,
+      None => return,
+    };
+    if let Some(datatype) = self
+      .index
+      .datatypes
+      .get(ref_)
+      .copied()
+      .filter(|datatype| !is_compatible(&primitive, *datatype))
+    {
+      self.push(
+        path.to_string(),
+        DiagnosticKind::DatatypeMismatch {
+          ref_: ref_.to_string(),
+          dmtype: dmtype.to_string(),
+          datatype,
+        },
+      );
+    }
+  }
+
+  fn vodml(&mut self, vodml: &'a Vodml, path: String) {
+    if let Some(report) = vodml
+      .report
+      .as_ref()
+      .filter(|report| report.status == Status::FAILED)
+    {
+      self.push(
+        format!("{}/REPORT", path),
+        DiagnosticKind::ReportFailed {
+          content: report.content.clone(),
+        },
+      );
+    }
+    if let Some(globals) = &vodml.globals {
+      self.globals(&globals.elems, format!("{}/GLOBALS", path));
+    }
+    let mut children = Children::new(&path);
+    for templates in vodml.templates.iter() {
+      self.templates(templates, children.path("TEMPLATES"));
+    }
+  }
+
+  fn globals(&mut self, elems: &'a [GlobalsElem], path: String) {
+    let mut children = Children::new(&path);
+    for elem in elems {
+      match elem {
+        GlobalsElem::Instance(instance) => {
+          let path = children.path("INSTANCE");
+          self.dmid(&path, &instance.dmid);
+          self.dmtype(&path, &instance.dmtype);
+          self.globals_instance(&instance.dmtype, &instance.elems, path);
+        }
+        GlobalsElem::Collection(collection) => {
+          let path = children.path("COLLECTION");
+          self.unique_dmid(&path, &collection.dmid);
+          self.collections.insert(&collection.dmid);
+          let mut children = Children::new(&path);
+          match &collection.elems {
+            GlobalsCollectionElems::InstanceOrRef(items) => {
+              for item in items {
+                match item {
+                  GlobalsCollectionItem::Instance(instance) => {
+                    let path = children.path("INSTANCE");
+                    self.dmid(&path, &instance.dmid);
+                    self.dmtype(&path, &instance.dmtype);
+                    self.globals_instance(&instance.dmtype, &instance.elems, path);
+                  }
+                  GlobalsCollectionItem::Reference(reference) => {
+                    self
+                      .dmrefs
+                      .push((children.path("REFERENCE"), &reference.dmref));
+                  }
+                }
+              }
+            }
+            GlobalsCollectionElems::Join(join) => {
+              self.join(join, children.path("JOIN"), Scope::Globals)
+            }
+          }
+        }
+      }
+    }
+  }
+
+  /// `dmtype`: type of the `INSTANCE`
+  fn globals_instance(&mut self, dmtype: &str, elems: &'a [GlobalsInstanceElem], path: String) {
+    let mut children = Children::new(&path);
+    for elem in elems {
+      match elem {
+        GlobalsInstanceElem::Attribute(attr) => {
+          let path = children.path("ATTRIBUTE");
+          self.dmrole(&path, dmtype, &attr.dmrole, Some(&attr.dmtype));
+          self.attribute(path, &attr.dmtype, &attr.ref_or_val_or_both, Scope::Globals);
+        }
+        GlobalsInstanceElem::Instance(instance) => {
+          let path = children.path("INSTANCE");
+          self.dmid(&path, &instance.dmid);
+          self.dmtype(&path, &instance.dmtype);
+          self.dmrole(&path, dmtype, &instance.dmrole, Some(&instance.dmtype));
+          self.globals_instance(&instance.dmtype, &instance.elems, path);
+        }
+        GlobalsInstanceElem::Reference(reference) => {
+          let path = children.path("REFERENCE");
+          self.dmrole(&path, dmtype, &reference.dmrole, None);
+          self.dmrefs.push((path, &reference.dmref));
+        }
+        GlobalsInstanceElem::Collection(collection) => {
+          let path = children.path("COLLECTION");
+          self.dmid(&path, &collection.dmid);
+          let role = self.dmrole(&path, dmtype, &collection.dmrole, None);
+          self.globals_collection(&collection.elems, path, &collection.dmrole, role);
+        }
+      }
+    }
+  }
+
+  /// `dmrole` and `role`: role of the `COLLECTION` (`None` for a nested `COLLECTION`)
+  fn globals_collection(
+    &mut self,
+    elems: &'a GlobalsInnerCollectionElems,
+    path: String,
+    dmrole: &str,
+    role: Option<&Role>,
+  ) {
+    let n_items = match elems {
+      GlobalsInnerCollectionElems::Attribute(attrs) => Some(attrs.len()),
+      GlobalsInnerCollectionElems::Collection(collections) => Some(collections.len()),
+      GlobalsInnerCollectionElems::InstanceOrRef(items) => Some(items.len()),
+      GlobalsInnerCollectionElems::Join(_) => None,
+    };
+    if let Some(n_items) = n_items {
+      self.multiplicity(&path, dmrole, role, n_items);
+    }
+    let mut children = Children::new(&path);
+    match elems {
+      GlobalsInnerCollectionElems::Attribute(attrs) => {
+        for attr in attrs {
+          let path = children.path("ATTRIBUTE");
+          if let Some(role) = role {
+            self.subtype(&path, dmrole, &role.datatype, &attr.dmtype);
+          }
+          self.attribute(path, &attr.dmtype, &attr.ref_or_val_or_both, Scope::Globals);
+        }
+      }
+      GlobalsInnerCollectionElems::Collection(collections) => {
+        for collection in collections {
+          let path = children.path("COLLECTION");
+          self.dmid(&path, &collection.dmid);
+          self.globals_collection(&collection.elems, path, dmrole, None);
+        }
+      }
+      GlobalsInnerCollectionElems::InstanceOrRef(items) => {
+        for item in items {
+          match item {
+            GlobalsInnerItem::Instance(instance) => {
+              let path = children.path("INSTANCE");
+              self.dmid(&path, &instance.dmid);
+              self.dmtype(&path, &instance.dmtype);
+              if let Some(role) = role {
+                self.subtype(&path, dmrole, &role.datatype, &instance.dmtype);
+              }
+              self.globals_instance(&instance.dmtype, &instance.elems, path);
+            }
+            GlobalsInnerItem::Reference(reference) => self
+              .dmrefs
+              .push((children.path("REFERENCE"), &reference.dmref)),
+          }
+        }
+      }
+      GlobalsInnerCollectionElems::Join(join) => {
+        self.join(join, children.path("JOIN"), Scope::Globals)
+      }
+    }
+  }
+
+  fn templates(&mut self, templates: &'a Templates, path: String) {
+    let table = match &templates.tableref {
+      None => (!self.index.tables.is_empty()).then_some(0),
+      Some(tableref) => {
+        let table = match self.index.table(tableref) {
+          Some((table, true)) => Some(table),
+          Some((table, false)) => {
+            self.push(
+              path.clone(),
+              DiagnosticKind::TablerefByName {
+                tableref: tableref.clone(),
+              },
+            );
+            Some(table)
+          }
+          None => {
+            self.push(
+              path.clone(),
+              DiagnosticKind::UnknownTableref {
+                tableref: tableref.clone(),
+              },
+            );
+            None
+          }
+        };
+        self.templates.insert(tableref, table);
+        table
+      }
+    };
+    let scope = Scope::Table(table);
+    let mut children = Children::new(&path);
+    for w in templates.wheres.iter() {
+      self.ref_(children.path("WHERE"), &w.primary_key, scope);
+    }
+    for instance in templates.instances.iter() {
+      let path = children.path("INSTANCE");
+      self.dmid(&path, &instance.dmid);
+      self.dmtype(&path, &instance.dmtype);
+      if let Some(dmid) = &instance.dmid {
+        self.instances.insert(dmid, table);
+      }
+      let mut pk_children = Children::new(&path);
+      for pk in instance.primarykeys.iter() {
+        self.ref_(pk_children.path("PRIMARY_KEY"), &pk.ref_, scope);
+      }
+      let counts = pk_children.counts;
+      self.instance(&instance.dmtype, &instance.elems, path, counts, scope);
+    }
+  }
+
+  /// `dmtype`: type of the `INSTANCE`
+  /// `counts`: the sibling counts of the already visited children (e.g. `PRIMARY_KEY`s)
+  fn instance(
+    &mut self,
+    dmtype: &str,
+    elems: &'a [InstanceElem],
+    path: String,
+    counts: HashMap<&'static str, usize>,
+    scope: Scope,
+  ) {
+    let mut children = Children {
+      parent: &path,
+      counts,
+    };
+    for elem in elems {
+      match elem {
+        InstanceElem::Attribute(attr) => {
+          let path = children.path("ATTRIBUTE");
+          self.dmrole(&path, dmtype, &attr.dmrole, Some(&attr.dmtype));
+          self.attribute(path, &attr.dmtype, &attr.ref_or_val_or_both, scope)
+        }
+        InstanceElem::Instance(instance) => {
+          let path = children.path("INSTANCE");
+          self.dmid(&path, &instance.dmid);
+          self.dmtype(&path, &instance.dmtype);
+          self.dmrole(&path, dmtype, &instance.dmrole, Some(&instance.dmtype));
+          let mut pk_children = Children::new(&path);
+          for pk in instance.primarykeys.iter() {
+            let pk_path = pk_children.path("PRIMARY_KEY");
+            if let PrimaryKey::Dynamic(pk) = pk {
+              self.ref_(pk_path, &pk.ref_, scope);
+            }
+          }
+          let counts = pk_children.counts;
+          self.instance(&instance.dmtype, &instance.elems, path, counts, scope);
+        }
+        InstanceElem::Reference(Reference::Static(reference)) => {
+          let path = children.path("REFERENCE");
+          self.dmrole(&path, dmtype, &reference.dmrole, None);
+          self.dmrefs.push((path, &reference.dmref));
+        }
+        InstanceElem::Reference(Reference::Dynamic(reference)) => {
+          let path = children.path("REFERENCE");
+          self.dmrole(&path, dmtype, &reference.dmrole, None);
+          let mut fk_children = Children::new(&path);
+          for fk in reference.foreignkeys.iter() {
+            self.ref_(fk_children.path("FOREIGN_KEY"), &fk.ref_, scope);
+          }
+          self.targets.push(PendingTarget {
+            path,
+            sourceref: Some(&reference.sourceref),
+            dmref: None,
+            foreign_keys: Vec::new(),
+          });
+        }
+        InstanceElem::Collection(collection) => {
+          let path = children.path("COLLECTION");
+          self.dmid(&path, &collection.dmid);
+          let role = self.dmrole(&path, dmtype, &collection.dmrole, None);
+          self.collection(&collection.elems, path, scope, &collection.dmrole, role);
+        }
+      }
+    }
+  }
+
+  /// `dmrole` and `role`: role of the `COLLECTION` (`None` for a nested `COLLECTION`)
+  fn collection(
+    &mut self,
+    elems: &'a CollectionElems,
+    path: String,
+    scope: Scope,
+    dmrole: &str,
+    role: Option<&Role>,
+  ) {
+    let n_items = match elems {
+      CollectionElems::Attribute(attrs) => Some(attrs.len()),
+      CollectionElems::Collection(collections) => Some(collections.len()),
+      CollectionElems::InstanceOrRef(items) => Some(items.len()),
+      CollectionElems::Join(_) => None,
+    };
+    if let Some(n_items) = n_items {
+      self.multiplicity(&path, dmrole, role, n_items);
+    }
+    let mut children = Children::new(&path);
+    match elems {
+      CollectionElems::Attribute(attrs) => {
+        for attr in attrs {
+          let path = children.path("ATTRIBUTE");
+          if let Some(role) = role {
+            self.subtype(&path, dmrole, &role.datatype, &attr.dmtype);
+          }
+          self.attribute(path, &attr.dmtype, &attr.ref_or_val_or_both, scope);
+        }
+      }
+      CollectionElems::Collection(collections) => {
+        for collection in collections {
+          let path = children.path("COLLECTION");
+          self.dmid(&path, &collection.dmid);
+          self.collection(&collection.elems, path, scope, dmrole, None);
+        }
+      }
+      CollectionElems::InstanceOrRef(items) => {
+        for item in items {
+          match item {
+            InstanceOrRef::Instance(instance) => {
+              let path = children.path("INSTANCE");
+              self.dmid(&path, &instance.dmid);
+              self.dmtype(&path, &instance.dmtype);
+              if let Some(role) = role {
+                self.subtype(&path, dmrole, &role.datatype, &instance.dmtype);
+              }
+              let mut pk_children = Children::new(&path);
+              for pk in instance.primarykeys.iter() {
+                self.ref_(pk_children.path("PRIMARY_KEY"), &pk.ref_, scope);
+              }
+              let counts = pk_children.counts;
+              self.instance(&instance.dmtype, &instance.elems, path, counts, scope);
+            }
+            InstanceOrRef::Reference(CollectionReference::Static(reference)) => self
+              .dmrefs
+              .push((children.path("REFERENCE"), &reference.dmref)),
+            InstanceOrRef::Reference(CollectionReference::Dynamic(reference)) => {
+              let path = children.path("REFERENCE");
+              let mut fk_children = Children::new(&path);
+              for fk in reference.foreignkeys.iter() {
+                self.ref_(fk_children.path("FOREIGN_KEY"), &fk.ref_, scope);
+              }
+              self.targets.push(PendingTarget {
+                path,
+                sourceref: Some(&reference.sourceref),
+                dmref: None,
+                foreign_keys: Vec::new(),
+              });
+            }
+          }
+        }
+      }
+      CollectionElems::Join(join) => self.join(join, children.path("JOIN"), scope),
+    }
+  }
+
+  /// In a `JOIN` `WHERE`, `primarykey` refers to a `FIELD` of the current table while
+  /// `foreignkey` refers to a `FIELD` of the joined table.
+  fn join(&mut self, join: &'a Join, path: String, scope: Scope) {
+    let mut children = Children::new(&path);
+    let mut foreign_keys = Vec::with_capacity(join.wheres.len());
+    for w in join.wheres.iter() {
+      let where_path = children.path("WHERE");
+      self.ref_(where_path.clone(), &w.primary_key, scope);
+      foreign_keys.push((where_path, w.foreign_key.as_str()));
+    }
+    let (dmref, sourceref) = match &join.attr {
+      JoinAttributes::DmRef { dmref } => (Some(dmref.as_str()), None),
+      JoinAttributes::SrcRef { sourceref } => (None, Some(sourceref.as_str())),
+      JoinAttributes::BothRef { dmref, sourceref } => {
+        (Some(dmref.as_str()), Some(sourceref.as_str()))
+      }
+    };
+    self.targets.push(PendingTarget {
+      path,
+      sourceref,
+      dmref,
+      foreign_keys,
+    });
+  }
+
+  /// Checks the `dmref`s and `sourceref`s, once all `dmid`s and `tableref`s are known.
+  fn resolve_pending(&mut self) {
+    for (path, dmref) in std::mem::take(&mut self.dmrefs) {
+      self.check_dmref(path, dmref);
+    }
+    for target in std::mem::take(&mut self.targets) {
+      let table = match (target.sourceref, target.dmref) {
+        (Some(sourceref), _) if self.collections.contains(sourceref) => None,
+        (Some(sourceref), dmref) => match self.templates.get(sourceref).copied() {
+          Some(table) => {
+            if let Some(dmref) = dmref {
+              self.check_dmref(target.path.clone(), dmref);
+            }
+            table
+          }
+          None => {
+            self.push(
+              target.path,
+              DiagnosticKind::UnknownSourceref {
+                sourceref: sourceref.to_string(),
+              },
+            );
+            continue;
+          }
+        },
+        (None, Some(dmref)) => {
+          if !self.check_dmref(target.path, dmref) {
+            continue;
+          }
+          self.instances.get(dmref).copied().flatten()
+        }
+        (None, None) => None,
+      };
+      // Keys of GLOBALS COLLECTIONs are static
+      if table.is_some() {
+        for (path, foreign_key) in target.foreign_keys {
+          self.ref_(path, foreign_key, Scope::Table(table));
+        }
+      }
+    }
+  }
+
+  /// Returns `false` (and pushes a diagnostic) if the given `dmref` matches no `dmid`.
+  fn check_dmref(&mut self, path: String, dmref: &str) -> bool {
+    let found = self.dmids.contains(dmref);
+    if !found {
+      self.push(
+        path,
+        DiagnosticKind::UnknownDmref {
+          dmref: dmref.to_string(),
+        },
+      );
+    }
+    found
+  }
+}
+
+/// Tells whether values of the given `FIELD` (or `PARAM`) datatype can be values of the given
+/// primitive `dmtype`.
+fn is_compatible(primitive: &str, datatype: Datatype) -> bool {
+  let is_integer = matches!(
+    datatype,
+    Datatype::Bit | Datatype::Byte | Datatype::ShortInt | Datatype::Int | Datatype::LongInt
+  );
+  match primitive {
+    "ivoa:boolean" => matches!(datatype, Datatype::Logical | Datatype::Bit),
+    "ivoa:integer" => is_integer,
+    "ivoa:real" | "ivoa:rational" => {
+      is_integer || matches!(datatype, Datatype::Float | Datatype::Double)
+    }
+    "ivoa:complex" => matches!(datatype, Datatype::ComplexFloat | Datatype::ComplexDouble),
+    "ivoa:datetime" => !matches!(
+      datatype,
+      Datatype::Logical | Datatype::ComplexFloat | Datatype::ComplexDouble
+    ),
+    _ => true,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{validate, validate_with_registry, DiagnosticKind, Severity};
+  use crate::{
+    datatype::Datatype, impls::mem::InMemTableDataRows, mivot::registry::Registry,
+    votable::VOTableWrapper,
+  };
+
+  #[test]
+  fn test_validate() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <PARAM ID="epoch" name="epoch" datatype="double" value="2015.5"/>
+  <RESOURCE type="results">
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <REPORT status="FAILED">no mapping for column 'mag'</REPORT>
+        <MODEL name="meas" url="https://www.ivoa.net/xml/Meas/20200908/Meas-v1.0.vo-dml.xml"/>
+        <GLOBALS>
+          <INSTANCE dmid="frame" dmtype="coords:SpaceFrame">
+            <ATTRIBUTE dmrole="coords:SpaceFrame.epoch" dmtype="ivoa:real" ref="epoch"/>
+            <ATTRIBUTE dmrole="coords:SpaceFrame.equinox" dmtype="ivoa:real" ref="ra"/>
+          </INSTANCE>
+          <INSTANCE dmid="frame" dmtype="coords:SpaceFrame"/>
+        </GLOBALS>
+        <TEMPLATES tableref="Results">
+          <INSTANCE dmtype="coords:LonLatPoint">
+            <ATTRIBUTE dmrole="coords:LonLatPoint.lon" dmtype="ivoa:RealQuantity" ref="ra"/>
+            <ATTRIBUTE dmrole="coords:LonLatPoint.lat" dmtype="ivoa:RealQuantity" ref="DEJ2000"/>
+            <ATTRIBUTE dmrole="coords:LonLatPoint.dist" dmtype="ivoa:RealQuantity" ref="plx"/>
+            <ATTRIBUTE dmrole="coords:LonLatPoint.mag" dmtype="ivoa:real" ref="mag"/>
+            <REFERENCE dmrole="coords:Coordinate.coordSys" dmref="frame"/>
+            <REFERENCE dmrole="coords:Coordinate.timeSys" dmref="time_frame"/>
+            <REFERENCE dmrole="coords:Coordinate.other" sourceref="Others">
+              <FOREIGN_KEY ref="ra"/>
+            </REFERENCE>
+          </INSTANCE>
+        </TEMPLATES>
+        <TEMPLATES tableref="Missing">
+          <INSTANCE dmtype="meas:Measure"/>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="Results">
+      <FIELD ID="ra" name="RA" datatype="double"/>
+      <FIELD ID="dec" name="DEJ2000" datatype="double"/>
+    </TABLE>
+    <TABLE ID="Photometry">
+      <FIELD ID="mag" name="mag" datatype="float"/>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let diagnostics = validate(&votable);
+    let vodml = "/VOTABLE/RESOURCE[1]/RESOURCE[1]/VODML";
+    let actual: Vec<(String, DiagnosticKind)> = diagnostics
+      .iter()
+      .map(|d| (d.path.replace(vodml, ""), d.kind.clone()))
+      .collect();
+    let expected = vec![
+      (
+        String::from("/REPORT"),
+        DiagnosticKind::ReportFailed {
+          content: Some(String::from("no mapping for column 'mag'")),
+        },
+      ),
+      (
+        String::from("/GLOBALS/INSTANCE[1]/ATTRIBUTE[2]"),
+        DiagnosticKind::FieldRefInGlobals {
+          ref_: String::from("ra"),
+        },
+      ),
+      (
+        String::from("/GLOBALS/INSTANCE[2]"),
+        DiagnosticKind::DuplicateDmid {
+          dmid: String::from("frame"),
+        },
+      ),
+      (
+        String::from("/TEMPLATES[1]/INSTANCE[1]/ATTRIBUTE[2]"),
+        DiagnosticKind::RefByName {
+          ref_: String::from("DEJ2000"),
+        },
+      ),
+      (
+        String::from("/TEMPLATES[1]/INSTANCE[1]/ATTRIBUTE[3]"),
+        DiagnosticKind::UnknownRef {
+          ref_: String::from("plx"),
+        },
+      ),
+      (
+        String::from("/TEMPLATES[1]/INSTANCE[1]/ATTRIBUTE[4]"),
+        DiagnosticKind::FieldOfOtherTable {
+          ref_: String::from("mag"),
+          tableref: String::from("Results"),
+        },
+      ),
+      (
+        String::from("/TEMPLATES[2]"),
+        DiagnosticKind::UnknownTableref {
+          tableref: String::from("Missing"),
+        },
+      ),
+      (
+        String::from("/TEMPLATES[1]/INSTANCE[1]/REFERENCE[2]"),
+        DiagnosticKind::UnknownDmref {
+          dmref: String::from("time_frame"),
+        },
+      ),
+      (
+        String::from("/TEMPLATES[1]/INSTANCE[1]/REFERENCE[3]"),
+        DiagnosticKind::UnknownSourceref {
+          sourceref: String::from("Others"),
+        },
+      ),
+    ];
+    assert_eq!(actual, expected);
+    assert_eq!(diagnostics[3].severity(), Severity::Warning);
+    assert_eq!(
+      diagnostics[4].to_string(),
+      format!(
+        "ERROR {}/TEMPLATES[1]/INSTANCE[1]/ATTRIBUTE[3]: ref 'plx' matches no FIELD or PARAM ID",
+        vodml
+      )
+    );
+  }
+
+  #[test]
+  fn test_validate_time_series() {
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_file(
+      "./resources/mivot/snippets/test_rich_instance_ok_TS1.xml",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(validate(&votable), vec![]);
+  }
+
+  #[test]
+  fn test_validate_with_registry() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <PARAM ID="epoch" name="epoch" datatype="double" value="2015.5"/>
+  <RESOURCE type="results">
+    <RESOURCE type="meta">
+      <VODML xmlns="http://www.ivoa.net/xml/mivot">
+        <MODEL name="meas" url="https://www.ivoa.net/xml/Meas/20200908/Meas-v1.0.vo-dml.xml"/>
+        <GLOBALS>
+          <INSTANCE dmid="frame" dmtype="coords:SpaceSys">
+            <INSTANCE dmrole="coords:PhysicalCoordSys.frame" dmtype="coords:SpaceFrame">
+              <ATTRIBUTE dmrole="coords:SpaceFrame.spaceRefFrame" dmtype="ivoa:string" value="ICRS"/>
+              <ATTRIBUTE dmrole="coords:SpaceFrame.epoch" dmtype="ivoa:real" ref="epoch"/>
+            </INSTANCE>
+          </INSTANCE>
+        </GLOBALS>
+        <TEMPLATES tableref="Results">
+          <INSTANCE dmtype="meas:Position">
+            <ATTRIBUTE dmrole="meas:Measure.ucd" dmtype="ivoa:string" value="pos.eq"/>
+            <INSTANCE dmrole="meas:Position.coord" dmtype="coords:LonLatPoint">
+              <ATTRIBUTE dmrole="coords:LonLatPoint.lon" dmtype="ivoa:RealQuantity" ref="ra"/>
+              <ATTRIBUTE dmrole="coords:LonLatPoint.lat" dmtype="ivoa:RealQuantity" ref="flag"/>
+              <REFERENCE dmrole="coords:Coordinate.coordSys" dmref="frame"/>
+            </INSTANCE>
+            <INSTANCE dmrole="meas:Measure.error" dmtype="meas:Error">
+              <INSTANCE dmrole="meas:Error.statError" dmtype="meas:Ellipse">
+                <COLLECTION dmrole="meas:Ellipse.semiAxis">
+                  <ATTRIBUTE dmtype="ivoa:RealQuantity" ref="ra_err"/>
+                </COLLECTION>
+                <ATTRIBUTE dmrole="meas:Ellipse.posAngle" dmtype="ivoa:real" ref="pa"/>
+              </INSTANCE>
+            </INSTANCE>
+          </INSTANCE>
+          <INSTANCE dmtype="meas:Uncertainty"/>
+          <INSTANCE dmtype="meas:Positon"/>
+          <INSTANCE dmtype="custom:Source">
+            <ATTRIBUTE dmrole="custom:Source.name" dmtype="ivoa:string" ref="ra"/>
+          </INSTANCE>
+        </TEMPLATES>
+      </VODML>
+    </RESOURCE>
+    <TABLE ID="Results">
+      <FIELD ID="ra" name="RA" datatype="double"/>
+      <FIELD ID="ra_err" name="e_RA" datatype="float"/>
+      <FIELD ID="pa" name="PA" datatype="double"/>
+      <FIELD ID="flag" name="flag" datatype="boolean"/>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    assert_eq!(validate(&votable), vec![]);
+    let diagnostics = validate_with_registry(&votable, &Registry::bundled());
+    let vodml = "/VOTABLE/RESOURCE[1]/RESOURCE[1]/VODML";
+    let actual: Vec<(String, DiagnosticKind)> = diagnostics
+      .iter()
+      .map(|d| (d.path.replace(vodml, ""), d.kind.clone()))
+      .collect();
+    let error = "/TEMPLATES[1]/INSTANCE[1]/INSTANCE[2]/INSTANCE[1]";
+    let expected = vec![
+      (
+        String::from("/GLOBALS/INSTANCE[1]/INSTANCE[1]/ATTRIBUTE[2]"),
+        DiagnosticKind::UnknownDmrole {
+          dmrole: String::from("coords:SpaceFrame.epoch"),
+          dmtype: String::from("coords:SpaceFrame"),
+        },
+      ),
+      (
+        String::from("/TEMPLATES[1]/INSTANCE[1]/INSTANCE[1]/ATTRIBUTE[2]"),
+        DiagnosticKind::DatatypeMismatch {
+          ref_: String::from("flag"),
+          dmtype: String::from("ivoa:RealQuantity"),
+          datatype: Datatype::Logical,
+        },
+      ),
+      (
+        format!("{}/COLLECTION[1]", error),
+        DiagnosticKind::Multiplicity {
+          dmrole: String::from("meas:Ellipse.semiAxis"),
+          min: 2,
+          max: Some(2),
+          actual: 1,
+        },
+      ),
+      (
+        format!("{}/ATTRIBUTE[1]", error),
+        DiagnosticKind::WrongDmtype {
+          dmrole: String::from("meas:Ellipse.posAngle"),
+          expected: String::from("ivoa:RealQuantity"),
+          actual: String::from("ivoa:real"),
+        },
+      ),
+      (
+        String::from("/TEMPLATES[1]/INSTANCE[2]"),
+        DiagnosticKind::AbstractDmtype {
+          dmtype: String::from("meas:Uncertainty"),
+        },
+      ),
+      (
+        String::from("/TEMPLATES[1]/INSTANCE[3]"),
+        DiagnosticKind::UnknownDmtype {
+          dmtype: String::from("meas:Positon"),
+        },
+      ),
+      (
+        String::from("/TEMPLATES[1]/INSTANCE[4]"),
+        DiagnosticKind::UnknownModel {
+          model: String::from("custom"),
+        },
+      ),
+    ];
+    assert_eq!(actual, expected);
+    assert_eq!(diagnostics[6].severity(), Severity::Warning);
+    assert_eq!(
+      diagnostics[2].kind.to_string(),
+      "1 item(s) for dmrole 'meas:Ellipse.semiAxis' of multiplicity 2..2"
+    );
+  }
+}
@@ -182,7 +182,7 @@ impl<C: TableDataContent> ResourceSubElem<C> {
   /// Do nothing if it already contains a TABLEDATA or if it contains a FITS.
   pub fn to_tabledata(&mut self) -> Result<(), VOTableError> {
     match &mut self.resource_or_table {
-      ResourceOrTable::Resource(_) => Ok(()),
+      ResourceOrTable::Resource(resource) => resource.to_tabledata(),
       ResourceOrTable::Table(table) => table.to_tabledata(),
     }
   }
@@ -191,7 +191,7 @@ impl<C: TableDataContent> ResourceSubElem<C> {
   /// Do nothing if it already contains a BINARY or if it contains a FITS.
   pub fn to_binary(&mut self) -> Result<(), VOTableError> {
     match &mut self.resource_or_table {
-      ResourceOrTable::Resource(_) => Ok(()),
+      ResourceOrTable::Resource(resource) => resource.to_binary(),
       ResourceOrTable::Table(table) => table.to_binary(),
     }
   }
@@ -200,7 +200,7 @@ impl<C: TableDataContent> ResourceSubElem<C> {
   /// Do nothing if it already contains a BINARY2 or if it contains a FITS.
   pub fn to_binary2(&mut self) -> Result<(), VOTableError> {
     match &mut self.resource_or_table {
-      ResourceOrTable::Resource(_) => Ok(()),
+      ResourceOrTable::Resource(resource) => resource.to_binary2(),
       ResourceOrTable::Table(table) => table.to_binary2(),
     }
   }
@@ -813,6 +813,8 @@ impl<C: TableDataContent> Resource<C> {
         info.write(writer, context)?;
       }
     }
+    #[cfg(feature = "mivot")]
+    write_elem!(self, vodml, writer, context);
     writer
       .write_event(Event::End(tag.to_end()))
       .map_err(VOTableError::Write)
@@ -849,6 +851,8 @@ impl<C: TableDataContent> Resource<C> {
       for se in iter {
         se.write(writer)?;
       }
+      #[cfg(feature = "mivot")]
+      write_elem!(self, vodml, writer, context);
       // Close tag
       writer
         .write_event(Event::End(tag.to_end()))
@@ -0,0 +1,608 @@
+//! [EPN-TAP](https://www.ivoa.net/documents/EPNTAP/) 2.0 `epn_core` columns.
+//!
+//! EPN-TAP does not define `utype`s, and the units and UCDs of the `c1`, `c2`, `c3` coordinates
+//! depend on the `spatial_frame_type`, so they are not checked.
+
+use super::{col, ColumnDef};
+use crate::datatype::Datatype::{CharASCII, Double, Int, LongInt};
+
+const S: Option<&str> = Some("*");
+
+pub(super) const COLUMNS: &[ColumnDef] = &[
+  // Mandatory columns
+  col(
+    "granule_uid",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id"),
+    None,
+    true,
+    "Internal table row index, unique in the table",
+  ),
+  col(
+    "granule_gid",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id"),
+    None,
+    true,
+    "Common to granules of same type (e.g. same map projection, or geometry data products)",
+  ),
+  col(
+    "obs_id",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id;obs"),
+    None,
+    true,
+    "Associates granules derived from the same data (e.g. various representations or processing levels)",
+  ),
+  col(
+    "dataproduct_type",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.code.class"),
+    None,
+    true,
+    "Organization of the data product, from enumerated list",
+  ),
+  col(
+    "target_name",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id;src"),
+    None,
+    true,
+    "Standard IAU name of target (from a list related to target class), case sensitive",
+  ),
+  col(
+    "target_class",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("src.class"),
+    None,
+    true,
+    "Type of target, from enumerated list",
+  ),
+  col(
+    "time_min",
+    Double,
+    None,
+    None,
+    Some("d"),
+    Some("time.start"),
+    None,
+    true,
+    "Acquisition start time (in JD)",
+  ),
+  col(
+    "time_max",
+    Double,
+    None,
+    None,
+    Some("d"),
+    Some("time.end"),
+    None,
+    true,
+    "Acquisition stop time (in JD)",
+  ),
+  col(
+    "time_sampling_step_min",
+    Double,
+    None,
+    None,
+    Some("s"),
+    Some("time.resolution;stat.min"),
+    None,
+    true,
+    "Min time sampling step",
+  ),
+  col(
+    "time_sampling_step_max",
+    Double,
+    None,
+    None,
+    Some("s"),
+    Some("time.resolution;stat.max"),
+    None,
+    true,
+    "Max time sampling step",
+  ),
+  col(
+    "time_exp_min",
+    Double,
+    None,
+    None,
+    Some("s"),
+    Some("time.duration;stat.min"),
+    None,
+    true,
+    "Min integration time",
+  ),
+  col(
+    "time_exp_max",
+    Double,
+    None,
+    None,
+    Some("s"),
+    Some("time.duration;stat.max"),
+    None,
+    true,
+    "Max integration time",
+  ),
+  col(
+    "spectral_range_min",
+    Double,
+    None,
+    None,
+    Some("Hz"),
+    Some("em.freq;stat.min"),
+    None,
+    true,
+    "Min spectral range (frequency)",
+  ),
+  col(
+    "spectral_range_max",
+    Double,
+    None,
+    None,
+    Some("Hz"),
+    Some("em.freq;stat.max"),
+    None,
+    true,
+    "Max spectral range (frequency)",
+  ),
+  col(
+    "spectral_sampling_step_min",
+    Double,
+    None,
+    None,
+    Some("Hz"),
+    Some("em.freq.step;stat.min"),
+    None,
+    true,
+    "Min spectral sampling step",
+  ),
+  col(
+    "spectral_sampling_step_max",
+    Double,
+    None,
+    None,
+    Some("Hz"),
+    Some("em.freq.step;stat.max"),
+    None,
+    true,
+    "Max spectral sampling step",
+  ),
+  col(
+    "spectral_resolution_min",
+    Double,
+    None,
+    None,
+    None,
+    Some("spect.resolution;stat.min"),
+    None,
+    true,
+    "Min spectral resolution",
+  ),
+  col(
+    "spectral_resolution_max",
+    Double,
+    None,
+    None,
+    None,
+    Some("spect.resolution;stat.max"),
+    None,
+    true,
+    "Max spectral resolution",
+  ),
+  col(
+    "c1min",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Min of first coordinate",
+  ),
+  col(
+    "c1max",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Max of first coordinate",
+  ),
+  col(
+    "c2min",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Min of second coordinate",
+  ),
+  col(
+    "c2max",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Max of second coordinate",
+  ),
+  col(
+    "c3min",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Min of third coordinate",
+  ),
+  col(
+    "c3max",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Max of third coordinate",
+  ),
+  col(
+    "s_region",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("pos.outline;obs.field"),
+    None,
+    true,
+    "ObsCore-like footprint, valid for celestial, spherical, or body-fixed frames",
+  ),
+  col(
+    "c1_resol_min",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Min resolution in first coordinate",
+  ),
+  col(
+    "c1_resol_max",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Max resolution in first coordinate",
+  ),
+  col(
+    "c2_resol_min",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Min resolution in second coordinate",
+  ),
+  col(
+    "c2_resol_max",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Max resolution in second coordinate",
+  ),
+  col(
+    "c3_resol_min",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Min resolution in third coordinate",
+  ),
+  col(
+    "c3_resol_max",
+    Double,
+    None,
+    None,
+    None,
+    None,
+    None,
+    true,
+    "Max resolution in third coordinate",
+  ),
+  col(
+    "spatial_frame_type",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.code.class;pos.frame"),
+    None,
+    true,
+    "Flavor of coordinate system, defines the nature of coordinates",
+  ),
+  col(
+    "incidence_min",
+    Double,
+    None,
+    None,
+    Some("deg"),
+    Some("pos.incidenceAng;stat.min"),
+    None,
+    true,
+    "Min incidence angle (solar zenithal angle)",
+  ),
+  col(
+    "incidence_max",
+    Double,
+    None,
+    None,
+    Some("deg"),
+    Some("pos.incidenceAng;stat.max"),
+    None,
+    true,
+    "Max incidence angle (solar zenithal angle)",
+  ),
+  col(
+    "emergence_min",
+    Double,
+    None,
+    None,
+    Some("deg"),
+    Some("pos.emergenceAng;stat.min"),
+    None,
+    true,
+    "Min emergence angle",
+  ),
+  col(
+    "emergence_max",
+    Double,
+    None,
+    None,
+    Some("deg"),
+    Some("pos.emergenceAng;stat.max"),
+    None,
+    true,
+    "Max emergence angle",
+  ),
+  col(
+    "phase_min",
+    Double,
+    None,
+    None,
+    Some("deg"),
+    Some("pos.phaseAng;stat.min"),
+    None,
+    true,
+    "Min phase angle",
+  ),
+  col(
+    "phase_max",
+    Double,
+    None,
+    None,
+    Some("deg"),
+    Some("pos.phaseAng;stat.max"),
+    None,
+    true,
+    "Max phase angle",
+  ),
+  col(
+    "instrument_host_name",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id;instr.obsty"),
+    None,
+    true,
+    "Standard name of the observatory or spacecraft",
+  ),
+  col(
+    "instrument_name",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id;instr"),
+    None,
+    true,
+    "Standard name of instrument",
+  ),
+  col(
+    "measurement_type",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ucd"),
+    None,
+    true,
+    "UCD(s) defining the data",
+  ),
+  col(
+    "processing_level",
+    Int,
+    None,
+    None,
+    None,
+    Some("meta.calibLevel"),
+    None,
+    true,
+    "CODMAC calibration level",
+  ),
+  col(
+    "creation_date",
+    CharASCII,
+    S,
+    Some("timestamp"),
+    None,
+    Some("time.creation"),
+    None,
+    true,
+    "Date of first entry of this granule",
+  ),
+  col(
+    "modification_date",
+    CharASCII,
+    S,
+    Some("timestamp"),
+    None,
+    Some("time.processing"),
+    None,
+    true,
+    "Date of last modification",
+  ),
+  col(
+    "release_date",
+    CharASCII,
+    S,
+    Some("timestamp"),
+    None,
+    Some("time.release"),
+    None,
+    true,
+    "Start of public access period",
+  ),
+  col(
+    "service_title",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.title"),
+    None,
+    true,
+    "Title of resource",
+  ),
+  // Optional columns
+  col(
+    "access_url",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ref.url;meta.file"),
+    None,
+    false,
+    "URL of the data file",
+  ),
+  col(
+    "access_format",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.code.mime"),
+    None,
+    false,
+    "File format type",
+  ),
+  col(
+    "access_estsize",
+    LongInt,
+    None,
+    None,
+    Some("kbyte"),
+    Some("phys.size;meta.file"),
+    None,
+    false,
+    "Estimated file size in kbyte",
+  ),
+  col(
+    "thumbnail_url",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ref.url;meta.preview"),
+    None,
+    false,
+    "URL of a thumbnail image",
+  ),
+  col(
+    "file_name",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id;meta.file"),
+    None,
+    false,
+    "Name of the data file only",
+  ),
+  col(
+    "species",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id;phys.atmol"),
+    None,
+    false,
+    "Identifies a chemical species, case sensitive",
+  ),
+  col(
+    "publisher",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.curation"),
+    None,
+    false,
+    "A short string identifying the entity running the data service used",
+  ),
+  col(
+    "bib_reference",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.bib"),
+    None,
+    false,
+    "Bibcode, doi, or other biblio id, URL",
+  ),
+];
@@ -0,0 +1,378 @@
+//! Column definitions of IVOA (and IVOA-related) standard tables: ObsCore, SSA, SIAv2 and
+//! EPN-TAP.
+//!
+//! For each standard, the module provides:
+//! * the list of required and optional columns (name, datatype, unit, UCD, utype);
+//! * a conformance checker reporting the missing and mismatched `FIELD`s of a `TABLE`;
+//! * a generator of skeleton `TABLE` headers.
+//!
+//! A `FIELD` matches a column definition if it has the same name (case-insensitive) or, if no
+//! `FIELD` has the column name, the same `utype` (case-insensitive, e.g. for SSA in which columns
+//! are identified by their `utype`).
+//! Datatypes are compared by family (integers, floating points, characters) since services
+//! often use e.g. `short` instead of `int`.
+
+use std::{
+  fmt::{self, Display, Formatter},
+  str::FromStr,
+};
+
+use crate::{datatype::Datatype, desc::Description, field::Field, table::Table, TableDataContent};
+
+mod epntap;
+mod obscore;
+mod ssa;
+
+/// Definition of a column of a standard table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnDef {
+  pub name: &'static str,
+  pub datatype: Datatype,
+  /// Array size, e.g. `*` for variable length strings
+  pub arraysize: Option<&'static str>,
+  pub xtype: Option<&'static str>,
+  pub unit: Option<&'static str>,
+  pub ucd: Option<&'static str>,
+  pub utype: Option<&'static str>,
+  /// Mandatory column in the standard
+  pub required: bool,
+  pub description: &'static str,
+}
+
+impl ColumnDef {
+  /// Returns the `FIELD` corresponding to this column definition.
+  pub fn to_field(&self) -> Field {
+    let mut field = Field::new(self.name, self.datatype).set_description(self.description.into());
+    if let Some(arraysize) = self.arraysize.and_then(|s| s.parse().ok()) {
+      field.set_arraysize_by_ref(arraysize);
+    }
+    if let Some(xtype) = self.xtype {
+      field.set_xtype_by_ref(xtype);
+    }
+    if let Some(unit) = self.unit {
+      field.set_unit_by_ref(unit);
+    }
+    if let Some(ucd) = self.ucd {
+      field.set_ucd_by_ref(ucd);
+    }
+    if let Some(utype) = self.utype {
+      field.set_utype_by_ref(utype);
+    }
+    field
+  }
+
+  /// Tells whether the given field corresponds to this column definition (same name, or same
+  /// `utype` if `by_utype` is `true`).
+  fn matches(&self, field: &Field, by_utype: bool) -> bool {
+    if by_utype {
+      matches!(self.utype, Some(utype) if eq_opt_ignore_case(&field.utype, utype))
+    } else {
+      self.name.eq_ignore_ascii_case(&field.name)
+    }
+  }
+}
+
+/// Supported standards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Standard {
+  /// [ObsCore](https://www.ivoa.net/documents/ObsCore/) 1.1
+  ObsCore,
+  /// [SSA](https://www.ivoa.net/documents/SSA/) 1.1 query response (main columns)
+  Ssa,
+  /// [SIAv2](https://www.ivoa.net/documents/SIA/) query response (ObsCore based)
+  SiaV2,
+  /// [EPN-TAP](https://www.ivoa.net/documents/EPNTAP/) 2.0
+  EpnTap,
+}
+
+impl Standard {
+  /// Returns the definitions of the columns of the standard.
+  pub fn columns(&self) -> &'static [ColumnDef] {
+    match self {
+      Self::ObsCore | Self::SiaV2 => obscore::COLUMNS,
+      Self::Ssa => ssa::COLUMNS,
+      Self::EpnTap => epntap::COLUMNS,
+    }
+  }
+
+  /// Returns the definitions of the required columns of the standard.
+  pub fn required_columns(&self) -> impl Iterator<Item = &'static ColumnDef> {
+    self.columns().iter().filter(|col| col.required)
+  }
+
+  /// Returns a skeleton of table (without data) following the standard.
+  /// # Params
+  /// * `with_optional`: also add the optional columns
+  pub fn new_table<C: TableDataContent>(&self, with_optional: bool) -> Table<C> {
+    let (name, description) = match self {
+      Self::ObsCore => ("ObsCore", "IVOA ObsCore table"),
+      Self::Ssa => ("SSA", "IVOA Simple Spectral Access query response"),
+      Self::SiaV2 => ("SIAv2", "IVOA Simple Image Access v2 query response"),
+      Self::EpnTap => ("epn_core", "EPN-TAP table"),
+    };
+    self
+      .columns()
+      .iter()
+      .filter(|col| with_optional || col.required)
+      .fold(
+        Table::new()
+          .set_name(name)
+          .set_description(Description::new(description)),
+        |table, col| table.push_field(col.to_field()),
+      )
+  }
+
+  /// Check the conformance of the given table with the standard.
+  /// Returns the list of issues (empty if the table is conform).
+  pub fn check<C: TableDataContent>(&self, table: &Table<C>) -> Vec<Issue> {
+    let fields: Vec<&Field> = table
+      .elems
+      .iter()
+      .filter_map(|elem| match elem {
+        crate::table::TableElem::Field(field) => Some(field),
+        _ => None,
+      })
+      .collect();
+    let mut issues = Vec::new();
+    for col in self.columns() {
+      let found = fields
+        .iter()
+        .find(|field| col.matches(field, false))
+        .or_else(|| fields.iter().find(|field| col.matches(field, true)));
+      match found {
+        None => {
+          if col.required {
+            issues.push(Issue::Missing { column: col.name });
+          }
+        }
+        Some(field) => check_field(col, field, &mut issues),
+      }
+    }
+    issues
+  }
+}
+
+impl FromStr for Standard {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "obscore" => Ok(Self::ObsCore),
+      "ssa" | "ssap" => Ok(Self::Ssa),
+      "sia" | "sia2" | "siav2" => Ok(Self::SiaV2),
+      "epntap" | "epn-tap" => Ok(Self::EpnTap),
+      _ => Err(format!(
+        "Unknown standard. Actual: '{}'. Expected: 'obscore', 'ssa', 'siav2' or 'epntap'.",
+        s
+      )),
+    }
+  }
+}
+
+impl Display for Standard {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::ObsCore => "obscore",
+      Self::Ssa => "ssa",
+      Self::SiaV2 => "siav2",
+      Self::EpnTap => "epntap",
+    })
+  }
+}
+
+/// A conformance issue.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Issue {
+  /// Required column not found
+  Missing { column: &'static str },
+  /// Datatype not compatible with the expected one
+  Datatype {
+    column: &'static str,
+    expected: Datatype,
+    actual: Datatype,
+  },
+  /// Unit different from the expected one
+  Unit {
+    column: &'static str,
+    expected: &'static str,
+    actual: Option<String>,
+  },
+  /// UCD different from the expected one
+  Ucd {
+    column: &'static str,
+    expected: &'static str,
+    actual: Option<String>,
+  },
+  /// Utype different from the expected one
+  Utype {
+    column: &'static str,
+    expected: &'static str,
+    actual: Option<String>,
+  },
+}
+
+impl Display for Issue {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    fn opt(s: &Option<String>) -> &str {
+      s.as_deref().unwrap_or("")
+    }
+    match self {
+      Self::Missing { column } => write!(f, "Column '{}': missing required column", column),
+      Self::Datatype {
+        column,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "Column '{}': wrong datatype. Expected: '{}'. Actual: '{}'.",
+        column, expected, actual
+      ),
+      Self::Unit {
+        column,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "Column '{}': wrong unit. Expected: '{}'. Actual: '{}'.",
+        column,
+        expected,
+        opt(actual)
+      ),
+      Self::Ucd {
+        column,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "Column '{}': wrong UCD. Expected: '{}'. Actual: '{}'.",
+        column,
+        expected,
+        opt(actual)
+      ),
+      Self::Utype {
+        column,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "Column '{}': wrong utype. Expected: '{}'. Actual: '{}'.",
+        column,
+        expected,
+        opt(actual)
+      ),
+    }
+  }
+}
+
+fn check_field(col: &ColumnDef, field: &Field, issues: &mut Vec<Issue>) {
+  if datatype_family(col.datatype) != datatype_family(field.datatype) {
+    issues.push(Issue::Datatype {
+      column: col.name,
+      expected: col.datatype,
+      actual: field.datatype,
+    });
+  }
+  if let Some(unit) = col.unit.filter(|unit| field.unit.as_deref() != Some(*unit)) {
+    issues.push(Issue::Unit {
+      column: col.name,
+      expected: unit,
+      actual: field.unit.clone(),
+    });
+  }
+  if let Some(ucd) = col.ucd.filter(|ucd| !eq_opt_ignore_case(&field.ucd, ucd)) {
+    issues.push(Issue::Ucd {
+      column: col.name,
+      expected: ucd,
+      actual: field.ucd.clone(),
+    });
+  }
+  if let Some(utype) = col
+    .utype
+    .filter(|utype| !eq_opt_ignore_case(&field.utype, utype))
+  {
+    issues.push(Issue::Utype {
+      column: col.name,
+      expected: utype,
+      actual: field.utype.clone(),
+    });
+  }
+}
+
+fn eq_opt_ignore_case(actual: &Option<String>, expected: &str) -> bool {
+  matches!(actual, Some(actual) if actual.eq_ignore_ascii_case(expected))
+}
+
+/// Group the datatypes in families of compatible types.
+fn datatype_family(datatype: Datatype) -> u8 {
+  match datatype {
+    Datatype::Logical | Datatype::Bit => 0,
+    Datatype::Byte | Datatype::ShortInt | Datatype::Int | Datatype::LongInt => 1,
+    Datatype::Float | Datatype::Double => 2,
+    Datatype::CharASCII | Datatype::CharUnicode => 3,
+    Datatype::ComplexFloat | Datatype::ComplexDouble => 4,
+  }
+}
+
+/// Column definition helper.
+#[allow(clippy::too_many_arguments)]
+const fn col(
+  name: &'static str,
+  datatype: Datatype,
+  arraysize: Option<&'static str>,
+  xtype: Option<&'static str>,
+  unit: Option<&'static str>,
+  ucd: Option<&'static str>,
+  utype: Option<&'static str>,
+  required: bool,
+  description: &'static str,
+) -> ColumnDef {
+  ColumnDef {
+    name,
+    datatype,
+    arraysize,
+    xtype,
+    unit,
+    ucd,
+    utype,
+    required,
+    description,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    impls::mem::{InMemTableDataRows, VoidTableDataContent},
+    votable::VOTableWrapper,
+  };
+
+  #[test]
+  fn test_obscore_check() {
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_file("resources/obscore.vot")
+      .unwrap()
+      .unwrap();
+    let table = votable.get_first_table().unwrap();
+    let issues = Standard::ObsCore.check(table);
+    // The file uses a few non-standard UCDs (e.g. `meta.id` for `dataproduct_type`)
+    assert!(!issues
+      .iter()
+      .any(|issue| matches!(issue, Issue::Missing { .. } | Issue::Datatype { .. })));
+
+    for standard in [
+      Standard::ObsCore,
+      Standard::Ssa,
+      Standard::SiaV2,
+      Standard::EpnTap,
+    ] {
+      assert_eq!(standard.to_string().parse::<Standard>(), Ok(standard));
+      let table = standard.new_table::<VoidTableDataContent>(true);
+      assert_eq!(standard.check(&table), vec![]);
+      let table = standard.new_table::<VoidTableDataContent>(false);
+      assert_eq!(standard.check(&table), vec![]);
+      assert!(!standard
+        .check(&Table::<VoidTableDataContent>::new())
+        .is_empty());
+    }
+  }
+}
@@ -0,0 +1,451 @@
+//! [ObsCore](https://www.ivoa.net/documents/ObsCore/) 1.1 columns (table 6 of the standard).
+
+use super::{col, ColumnDef};
+use crate::datatype::Datatype::{CharASCII, Double, Int, LongInt};
+
+const S: Option<&str> = Some("*");
+
+pub(super) const COLUMNS: &[ColumnDef] = &[
+  // Mandatory columns
+  col(
+    "dataproduct_type",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.code.class"),
+    Some("obscore:ObsDataset.dataProductType"),
+    true,
+    "Data product (file content) primary type",
+  ),
+  col(
+    "calib_level",
+    Int,
+    None,
+    None,
+    None,
+    Some("meta.code;obs.calib"),
+    Some("obscore:ObsDataset.calibLevel"),
+    true,
+    "Calibration level of the observation: in {0, 1, 2, 3, 4}",
+  ),
+  col(
+    "obs_collection",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id"),
+    Some("obscore:DataID.Collection"),
+    true,
+    "Name of the data collection",
+  ),
+  col(
+    "obs_id",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id"),
+    Some("obscore:DataID.observationID"),
+    true,
+    "Observation ID",
+  ),
+  col(
+    "obs_publisher_did",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ref.ivoid"),
+    Some("obscore:Curation.PublisherDID"),
+    true,
+    "Dataset identifier given by the publisher",
+  ),
+  col(
+    "access_url",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ref.url"),
+    Some("obscore:Access.Reference"),
+    true,
+    "URL used to access (download) dataset",
+  ),
+  col(
+    "access_format",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.code.mime"),
+    Some("obscore:Access.Format"),
+    true,
+    "File content format",
+  ),
+  col(
+    "access_estsize",
+    LongInt,
+    None,
+    None,
+    Some("kbyte"),
+    Some("phys.size;meta.file"),
+    Some("obscore:Access.Size"),
+    true,
+    "Estimated size of dataset in kilo bytes",
+  ),
+  col(
+    "target_name",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id;src"),
+    Some("obscore:Target.Name"),
+    true,
+    "Astronomical object observed, if any",
+  ),
+  col(
+    "s_ra",
+    Double,
+    None,
+    None,
+    Some("deg"),
+    Some("pos.eq.ra"),
+    Some("obscore:Char.SpatialAxis.Coverage.Location.Coord.Position2D.Value2.C1"),
+    true,
+    "Central right ascension, ICRS",
+  ),
+  col(
+    "s_dec",
+    Double,
+    None,
+    None,
+    Some("deg"),
+    Some("pos.eq.dec"),
+    Some("obscore:Char.SpatialAxis.Coverage.Location.Coord.Position2D.Value2.C2"),
+    true,
+    "Central declination, ICRS",
+  ),
+  col(
+    "s_fov",
+    Double,
+    None,
+    None,
+    Some("deg"),
+    Some("phys.angSize;instr.fov"),
+    Some("obscore:Char.SpatialAxis.Coverage.Bounds.Extent.diameter"),
+    true,
+    "Diameter (bounds) of the covered region",
+  ),
+  col(
+    "s_region",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("pos.outline;obs.field"),
+    Some("obscore:Char.SpatialAxis.Coverage.Support.Area"),
+    true,
+    "Sky region covered by the data product (expressed in ICRS frame)",
+  ),
+  col(
+    "s_resolution",
+    Double,
+    None,
+    None,
+    Some("arcsec"),
+    Some("pos.angResolution"),
+    Some("obscore:Char.SpatialAxis.Resolution.Refval.value"),
+    true,
+    "Spatial resolution of data as FWHM",
+  ),
+  col(
+    "s_xel1",
+    LongInt,
+    None,
+    None,
+    None,
+    Some("meta.number"),
+    Some("obscore:Char.SpatialAxis.numBins1"),
+    true,
+    "Number of elements along the first spatial axis",
+  ),
+  col(
+    "s_xel2",
+    LongInt,
+    None,
+    None,
+    None,
+    Some("meta.number"),
+    Some("obscore:Char.SpatialAxis.numBins2"),
+    true,
+    "Number of elements along the second spatial axis",
+  ),
+  col(
+    "t_xel",
+    LongInt,
+    None,
+    None,
+    None,
+    Some("meta.number"),
+    Some("obscore:Char.TimeAxis.numBins"),
+    true,
+    "Number of elements along the time axis",
+  ),
+  col(
+    "t_min",
+    Double,
+    None,
+    None,
+    Some("d"),
+    Some("time.start;obs.exposure"),
+    Some("obscore:Char.TimeAxis.Coverage.Bounds.Limits.StartTime"),
+    true,
+    "Start time in MJD",
+  ),
+  col(
+    "t_max",
+    Double,
+    None,
+    None,
+    Some("d"),
+    Some("time.end;obs.exposure"),
+    Some("obscore:Char.TimeAxis.Coverage.Bounds.Limits.StopTime"),
+    true,
+    "Stop time in MJD",
+  ),
+  col(
+    "t_exptime",
+    Double,
+    None,
+    None,
+    Some("s"),
+    Some("time.duration;obs.exposure"),
+    Some("obscore:Char.TimeAxis.Coverage.Support.Extent"),
+    true,
+    "Total exposure time",
+  ),
+  col(
+    "t_resolution",
+    Double,
+    None,
+    None,
+    Some("s"),
+    Some("time.resolution"),
+    Some("obscore:Char.TimeAxis.Resolution.Refval.value"),
+    true,
+    "Temporal resolution FWHM",
+  ),
+  col(
+    "em_xel",
+    LongInt,
+    None,
+    None,
+    None,
+    Some("meta.number"),
+    Some("obscore:Char.SpectralAxis.numBins"),
+    true,
+    "Number of elements along the spectral axis",
+  ),
+  col(
+    "em_min",
+    Double,
+    None,
+    None,
+    Some("m"),
+    Some("em.wl;stat.min"),
+    Some("obscore:Char.SpectralAxis.Coverage.Bounds.Limits.LoLimit"),
+    true,
+    "Start in spectral coordinates",
+  ),
+  col(
+    "em_max",
+    Double,
+    None,
+    None,
+    Some("m"),
+    Some("em.wl;stat.max"),
+    Some("obscore:Char.SpectralAxis.Coverage.Bounds.Limits.HiLimit"),
+    true,
+    "Stop in spectral coordinates",
+  ),
+  col(
+    "em_res_power",
+    Double,
+    None,
+    None,
+    None,
+    Some("spect.resolution"),
+    Some("obscore:Char.SpectralAxis.Resolution.ResolPower.refVal"),
+    true,
+    "Spectral resolving power",
+  ),
+  col(
+    "o_ucd",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ucd"),
+    Some("obscore:Char.ObservableAxis.ucd"),
+    true,
+    "UCD of observable (e.g. phot.flux.density, phot.count, etc.)",
+  ),
+  col(
+    "pol_states",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.code;phys.polarization"),
+    Some("obscore:Char.PolarizationAxis.stateList"),
+    true,
+    "List of polarization states or NULL if not applicable",
+  ),
+  col(
+    "pol_xel",
+    LongInt,
+    None,
+    None,
+    None,
+    Some("meta.number"),
+    Some("obscore:Char.PolarizationAxis.numBins"),
+    true,
+    "Number of polarization samples",
+  ),
+  col(
+    "facility_name",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id;instr.tel"),
+    Some("obscore:Provenance.ObsConfig.Facility.name"),
+    true,
+    "Name of the facility used for this observation",
+  ),
+  col(
+    "instrument_name",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id;instr"),
+    Some("obscore:Provenance.ObsConfig.Instrument.name"),
+    true,
+    "Name of the instrument used for this observation",
+  ),
+  // Optional columns
+  col(
+    "dataproduct_subtype",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.code.class"),
+    Some("obscore:ObsDataset.dataProductSubtype"),
+    false,
+    "Data product specific type",
+  ),
+  col(
+    "target_class",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("src.class"),
+    Some("obscore:Target.Class"),
+    false,
+    "Class of the Target object as in SSA",
+  ),
+  col(
+    "obs_creator_did",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id"),
+    Some("obscore:DataID.CreatorDID"),
+    false,
+    "IVOA dataset identifier given by the creator",
+  ),
+  col(
+    "obs_title",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.title;obs"),
+    Some("obscore:DataID.Title"),
+    false,
+    "Brief description of dataset in free format",
+  ),
+  col(
+    "s_pixel_scale",
+    Double,
+    None,
+    None,
+    Some("arcsec"),
+    Some("phys.angSize;instr.pixel"),
+    Some("obscore:Char.SpatialAxis.Sampling.RefVal.SamplingPeriod"),
+    false,
+    "Sampling period in world coordinate units along the spatial axis",
+  ),
+  col(
+    "em_ucd",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ucd"),
+    Some("obscore:Char.SpectralAxis.ucd"),
+    false,
+    "Nature of the spectral axis",
+  ),
+  col(
+    "em_unit",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.unit"),
+    Some("obscore:Char.SpectralAxis.unit"),
+    false,
+    "Units along the spectral axis",
+  ),
+  col(
+    "em_resolution",
+    Double,
+    None,
+    None,
+    Some("m"),
+    Some("spect.resolution;em.wl"),
+    Some("obscore:Char.SpectralAxis.Resolution.Refval.value"),
+    false,
+    "Value of resolution along the spectral axis",
+  ),
+  col(
+    "o_unit",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.unit"),
+    Some("obscore:Char.ObservableAxis.unit"),
+    false,
+    "Units used for the observable values",
+  ),
+  col(
+    "preview",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ref.url;datalink.preview"),
+    None,
+    false,
+    "URL of a preview of the data",
+  ),
+];
@@ -0,0 +1,290 @@
+//! Main columns of an [SSA](https://www.ivoa.net/documents/SSA/) 1.1 query response.
+//!
+//! SSA does not define column names (columns are identified by their `utype`), so the names
+//! used here are the ones commonly used by services (e.g. DaCHS) and a `FIELD` having the
+//! expected `utype` is accepted whatever its name.
+
+use super::{col, ColumnDef};
+use crate::datatype::Datatype::{CharASCII, Double, LongInt};
+
+const S: Option<&str> = Some("*");
+
+pub(super) const COLUMNS: &[ColumnDef] = &[
+  // Mandatory columns
+  col(
+    "ssa_model",
+    CharASCII,
+    S,
+    None,
+    None,
+    None,
+    Some("ssa:Dataset.DataModel"),
+    true,
+    "Data model name and version",
+  ),
+  col(
+    "ssa_length",
+    LongInt,
+    None,
+    None,
+    None,
+    Some("meta.number"),
+    Some("ssa:Dataset.Length"),
+    true,
+    "Number of points in the spectrum",
+  ),
+  col(
+    "ssa_dstitle",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.title;meta.dataset"),
+    Some("ssa:DataID.Title"),
+    true,
+    "Dataset title",
+  ),
+  col(
+    "accref",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ref.url"),
+    Some("ssa:Access.Reference"),
+    true,
+    "URL to retrieve the dataset",
+  ),
+  col(
+    "mime",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.code.mime"),
+    Some("ssa:Access.Format"),
+    true,
+    "MIME type of the dataset",
+  ),
+  col(
+    "ssa_targname",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id;src"),
+    Some("ssa:Target.Name"),
+    true,
+    "Common name of the object observed",
+  ),
+  col(
+    "ssa_location",
+    Double,
+    Some("2"),
+    None,
+    Some("deg"),
+    Some("pos.eq"),
+    Some("ssa:Char.SpatialAxis.Coverage.Location.Value"),
+    true,
+    "ICRS location of the aperture center",
+  ),
+  col(
+    "ssa_specmid",
+    Double,
+    None,
+    None,
+    Some("m"),
+    Some("instr.bandpass"),
+    Some("ssa:Char.SpectralAxis.Coverage.Location.Value"),
+    true,
+    "Midpoint of the spectral coverage",
+  ),
+  col(
+    "ssa_specext",
+    Double,
+    None,
+    None,
+    Some("m"),
+    Some("instr.bandwidth"),
+    Some("ssa:Char.SpectralAxis.Coverage.Bounds.Extent"),
+    true,
+    "Width of the spectrum",
+  ),
+  col(
+    "ssa_specstart",
+    Double,
+    None,
+    None,
+    Some("m"),
+    Some("em.wl;stat.min"),
+    Some("ssa:Char.SpectralAxis.Coverage.Bounds.Start"),
+    true,
+    "Lower limit of the spectral coverage",
+  ),
+  col(
+    "ssa_specend",
+    Double,
+    None,
+    None,
+    Some("m"),
+    Some("em.wl;stat.max"),
+    Some("ssa:Char.SpectralAxis.Coverage.Bounds.Stop"),
+    true,
+    "Upper limit of the spectral coverage",
+  ),
+  // Optional columns
+  col(
+    "accsize",
+    LongInt,
+    None,
+    None,
+    None,
+    Some("phys.size;meta.file"),
+    Some("ssa:Access.Size"),
+    false,
+    "Estimated size of the dataset",
+  ),
+  col(
+    "ssa_dstype",
+    CharASCII,
+    S,
+    None,
+    None,
+    None,
+    Some("ssa:Dataset.Type"),
+    false,
+    "Type of data (spectrum, timeseries, ...)",
+  ),
+  col(
+    "ssa_pubDID",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ref.uri;meta.curation"),
+    Some("ssa:Curation.PublisherDID"),
+    false,
+    "Dataset identifier assigned by the publisher",
+  ),
+  col(
+    "ssa_creatorDID",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id"),
+    Some("ssa:DataID.CreatorDID"),
+    false,
+    "Dataset identifier assigned by the creator",
+  ),
+  col(
+    "ssa_collection",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.id"),
+    Some("ssa:DataID.Collection"),
+    false,
+    "Name of the data collection",
+  ),
+  col(
+    "ssa_targclass",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("src.class"),
+    Some("ssa:Target.Class"),
+    false,
+    "Object class (star, QSO, ...)",
+  ),
+  col(
+    "ssa_redshift",
+    Double,
+    None,
+    None,
+    None,
+    Some("src.redshift"),
+    Some("ssa:Target.Redshift"),
+    false,
+    "Redshift of the target",
+  ),
+  col(
+    "ssa_dateObs",
+    Double,
+    None,
+    None,
+    Some("d"),
+    Some("time.epoch"),
+    Some("ssa:Char.TimeAxis.Coverage.Location.Value"),
+    false,
+    "Midpoint of the exposure (MJD)",
+  ),
+  col(
+    "ssa_timeExt",
+    Double,
+    None,
+    None,
+    Some("s"),
+    Some("time.duration"),
+    Some("ssa:Char.TimeAxis.Coverage.Bounds.Extent"),
+    false,
+    "Exposure duration",
+  ),
+  col(
+    "ssa_aperture",
+    Double,
+    None,
+    None,
+    Some("deg"),
+    Some("phys.angSize;instr.fov"),
+    Some("ssa:Char.SpatialAxis.Coverage.Bounds.Extent"),
+    false,
+    "Angular diameter of the aperture",
+  ),
+  col(
+    "ssa_specres",
+    Double,
+    None,
+    None,
+    Some("m"),
+    Some("spect.resolution;em.wl"),
+    Some("ssa:Char.SpectralAxis.Resolution"),
+    false,
+    "Spectral resolution",
+  ),
+  col(
+    "ssa_snr",
+    Double,
+    None,
+    None,
+    None,
+    Some("stat.snr"),
+    Some("ssa:Derived.SNR"),
+    false,
+    "Signal-to-noise ratio",
+  ),
+  col(
+    "ssa_spectralucd",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ucd"),
+    Some("ssa:Char.SpectralAxis.Ucd"),
+    false,
+    "UCD of the spectral axis",
+  ),
+  col(
+    "ssa_fluxucd",
+    CharASCII,
+    S,
+    None,
+    None,
+    Some("meta.ucd"),
+    Some("ssa:Char.FluxAxis.Ucd"),
+    false,
+    "UCD of the flux axis",
+  ),
+];
@@ -205,7 +205,13 @@ impl<C: TableDataContent> Table<C> {
           Param::TAG_BYTES => self.push_param_by_ref(Param::from_event_empty(e)?),
           TableGroup::TAG_BYTES => self.push_tablegroup_by_ref(TableGroup::from_event_empty(e)?),
           Link::TAG_BYTES => self.push_link_by_ref(Link::from_event_empty(e)?),
-          Info::TAG_BYTES => self.push_info_by_ref(Info::from_event_empty(e)?),
+          Info::TAG_BYTES => {
+            if self.elems.is_empty() && self.links.is_empty() && self.data.is_none() {
+              self.push_info_by_ref(Info::from_event_empty(e)?)
+            } else {
+              self.push_post_info_by_ref(Info::from_event_empty(e)?)
+            }
+          }
           _ => {
             return Err(VOTableError::UnexpectedEmptyTag(
               e.local_name().to_vec(),
@@ -434,7 +440,13 @@ impl<C: TableDataContent> HasSubElements for Table<C> {
           Param::TAG_BYTES => self.push_param_by_ref(Param::from_event_empty(e)?),
           TableGroup::TAG_BYTES => self.push_tablegroup_by_ref(TableGroup::from_event_empty(e)?),
           Link::TAG_BYTES => self.push_link_by_ref(Link::from_event_empty(e)?),
-          Info::TAG_BYTES => self.push_info_by_ref(Info::from_event_empty(e)?),
+          Info::TAG_BYTES => {
+            if self.elems.is_empty() && self.links.is_empty() && self.data.is_none() {
+              self.push_info_by_ref(Info::from_event_empty(e)?)
+            } else {
+              self.push_post_info_by_ref(Info::from_event_empty(e)?)
+            }
+          }
           _ => {
             return Err(VOTableError::UnexpectedEmptyTag(
               e.local_name().to_vec(),
@@ -0,0 +1,797 @@
+//! Conversion of the values of time columns, i.e. of the columns referencing a `TIMESYS`, from
+//! one representation and timescale to another.
+//!
+//! Supported representations are:
+//! * offsets from the `TIMESYS` `timeorigin` (e.g. JD, MJD), in days, hours, minutes, seconds or
+//!   Julian years;
+//! * Julian years (no `timeorigin`);
+//! * ISO-8601 strings (`YYYY-MM-DD[Thh:mm:ss[.sss]][Z]`, no `timeorigin`).
+//!
+//! Supported timescales are UTC, TAI, TT, TDB and GPS. UTC is supported from 1972-01-01 only,
+//! the leap seconds table being bundled in this module (last leap second: 2016-12-31).
+//! TDB is computed from TT using a two-term approximation (error < 30 us in 1900-2100).
+
+use std::{
+  fmt::{self, Display, Formatter},
+  str::FromStr,
+};
+
+use super::{TimeScale, TimeSys, JD_ORIGIN, MJD_ORIGIN};
+use crate::{
+  datatype::Datatype,
+  error::VOTableError,
+  field::{ArraySize, Field},
+  impls::VOTableValue,
+  resource::{Resource, ResourceElem, ResourceOrTable},
+  table::TableElem,
+  votable::{VOTable, VOTableElem},
+  TableDataContent,
+};
+
+const SEC_PER_DAY: f64 = 86400.0;
+/// Julian Date of J2000.0
+const JD_J2000: f64 = 2451545.0;
+const DAYS_PER_JULIAN_YEAR: f64 = 365.25;
+/// TT - TAI, in seconds
+const TT_MINUS_TAI: f64 = 32.184;
+/// TAI - GPS, in seconds
+const TAI_MINUS_GPS: f64 = 19.0;
+
+/// Leap seconds table: (year, month, TAI - UTC in seconds from the first day of the month).
+const LEAP_SECONDS: [(i32, u32, f64); 28] = [
+  (1972, 1, 10.0),
+  (1972, 7, 11.0),
+  (1973, 1, 12.0),
+  (1974, 1, 13.0),
+  (1975, 1, 14.0),
+  (1976, 1, 15.0),
+  (1977, 1, 16.0),
+  (1978, 1, 17.0),
+  (1979, 1, 18.0),
+  (1980, 1, 19.0),
+  (1981, 7, 20.0),
+  (1982, 7, 21.0),
+  (1983, 7, 22.0),
+  (1985, 7, 23.0),
+  (1988, 1, 24.0),
+  (1990, 1, 25.0),
+  (1991, 1, 26.0),
+  (1992, 7, 27.0),
+  (1993, 7, 28.0),
+  (1994, 7, 29.0),
+  (1996, 1, 30.0),
+  (1997, 7, 31.0),
+  (1999, 1, 32.0),
+  (2006, 1, 33.0),
+  (2009, 1, 34.0),
+  (2012, 7, 35.0),
+  (2015, 7, 36.0),
+  (2017, 1, 37.0),
+];
+
+/// Representation of time values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeRepr {
+  /// Offset from the given origin (a Julian Date), the unit of the offset being given in days
+  /// (e.g. `1.0` for days, `1.0 / 86400.0` for seconds).
+  Offset { origin: f64, unit_in_days: f64 },
+  /// Julian years, e.g. `2015.5`
+  JulianYear,
+  /// ISO-8601 strings, e.g. `2015-07-02T12:00:00.000`
+  Iso,
+}
+
+impl TimeRepr {
+  /// Julian Dates, in days.
+  pub const fn jd() -> Self {
+    TimeRepr::Offset {
+      origin: JD_ORIGIN,
+      unit_in_days: 1.0,
+    }
+  }
+
+  /// Modified Julian Dates, in days.
+  pub const fn mjd() -> Self {
+    TimeRepr::Offset {
+      origin: MJD_ORIGIN,
+      unit_in_days: 1.0,
+    }
+  }
+
+  /// Returns the representation of the values of the given field, knowing the `TIMESYS` it
+  /// references.
+  pub fn from_field(field: &Field, timesys: &TimeSys) -> Result<Self, VOTableError> {
+    match (&field.datatype, timesys.timeorigin) {
+      (Datatype::CharASCII | Datatype::CharUnicode, _) => Ok(TimeRepr::Iso),
+      (_, Some(origin)) => unit_in_days(field.unit.as_deref())
+        .map(|unit_in_days| TimeRepr::Offset {
+          origin,
+          unit_in_days,
+        })
+        .ok_or_else(|| {
+          VOTableError::Custom(format!(
+            "Unsupported time unit '{}' in column '{}'",
+            field.unit.as_deref().unwrap_or_default(),
+            field.name
+          ))
+        }),
+      (_, None) => match field.unit.as_deref() {
+        None | Some("yr") | Some("a") => Ok(TimeRepr::JulianYear),
+        Some(unit) => Err(VOTableError::Custom(format!(
+          "No 'timeorigin' in TIMESYS '{}' and unit '{}' of column '{}' is not a year",
+          timesys.id, unit, field.name
+        ))),
+      },
+    }
+  }
+
+  /// Returns the `timeorigin` of a `TIMESYS` for this representation.
+  pub fn timeorigin(&self) -> Option<f64> {
+    match self {
+      TimeRepr::Offset { origin, .. } => Some(*origin),
+      TimeRepr::JulianYear | TimeRepr::Iso => None,
+    }
+  }
+
+  /// Set the datatype, unit, xtype, arraysize, width and precision of a field for this
+  /// representation.
+  fn set_field(&self, field: &mut Field) {
+    field.width = None;
+    field.precision = None;
+    match self {
+      TimeRepr::Offset { unit_in_days, .. } => {
+        field.datatype = Datatype::Double;
+        field.arraysize = None;
+        field.xtype = None;
+        field.unit = Some(String::from(match *unit_in_days {
+          u if u == 1.0 / SEC_PER_DAY => "s",
+          u if u == 1.0 / 1440.0 => "min",
+          u if u == 1.0 / 24.0 => "h",
+          u if u == DAYS_PER_JULIAN_YEAR => "yr",
+          _ => "d",
+        }));
+      }
+      TimeRepr::JulianYear => {
+        field.datatype = Datatype::Double;
+        field.arraysize = None;
+        field.xtype = None;
+        field.unit = Some(String::from("yr"));
+      }
+      TimeRepr::Iso => {
+        field.datatype = Datatype::CharASCII;
+        field.arraysize = Some(ArraySize::new_variable_1d());
+        field.xtype = Some(String::from("timestamp"));
+        field.unit = None;
+      }
+    }
+  }
+
+  /// Returns the Julian Date corresponding to the given value (the number of seconds larger
+  /// than 59 being returned separately, in case of a leap second).
+  fn value_to_jd(self, value: &VOTableValue) -> Result<Option<(f64, f64)>, VOTableError> {
+    match (self, value) {
+      (_, VOTableValue::Null) => Ok(None),
+      (TimeRepr::Iso, VOTableValue::String(s)) => parse_iso(s).map(Some),
+      (TimeRepr::Iso, _) => Err(VOTableError::Custom(format!(
+        "Wrong ISO-8601 time value: '{}'",
+        value
+      ))),
+      (TimeRepr::Offset { .. } | TimeRepr::JulianYear, _) => match value.as_f64() {
+        None if is_nan(value) => Ok(None),
+        None => Err(VOTableError::Custom(format!(
+          "Wrong numerical time value: '{}'",
+          value
+        ))),
+        Some(v) => Ok(Some((
+          match self {
+            TimeRepr::Offset {
+              origin,
+              unit_in_days,
+            } => origin + v * unit_in_days,
+            _ => JD_J2000 + (v - 2000.0) * DAYS_PER_JULIAN_YEAR,
+          },
+          0.0,
+        ))),
+      },
+    }
+  }
+
+  /// Returns the value corresponding to the given Julian Date.
+  fn jd_to_value(self, jd: f64) -> VOTableValue {
+    match self {
+      TimeRepr::Offset {
+        origin,
+        unit_in_days,
+      } => VOTableValue::Double((jd - origin) / unit_in_days),
+      TimeRepr::JulianYear => VOTableValue::Double(2000.0 + (jd - JD_J2000) / DAYS_PER_JULIAN_YEAR),
+      TimeRepr::Iso => VOTableValue::String(format_iso(jd)),
+    }
+  }
+}
+
+impl FromStr for TimeRepr {
+  type Err = VOTableError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "jd" => Ok(TimeRepr::jd()),
+      "mjd" => Ok(TimeRepr::mjd()),
+      "jyear" => Ok(TimeRepr::JulianYear),
+      "iso" => Ok(TimeRepr::Iso),
+      _ => Err(VOTableError::Custom(format!(
+        "Unrecognized time representation. Actual: '{}'. Expected: 'jd', 'mjd', 'jyear' or 'iso'",
+        s
+      ))),
+    }
+  }
+}
+
+impl Display for TimeRepr {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      TimeRepr::Offset {
+        origin,
+        unit_in_days,
+      } if *unit_in_days == 1.0 && *origin == JD_ORIGIN => f.write_str("jd"),
+      TimeRepr::Offset {
+        origin,
+        unit_in_days,
+      } if *unit_in_days == 1.0 && *origin == MJD_ORIGIN => f.write_str("mjd"),
+      TimeRepr::Offset {
+        origin,
+        unit_in_days,
+      } => write!(
+        f,
+        "offset from JD {} in units of {} d",
+        origin, unit_in_days
+      ),
+      TimeRepr::JulianYear => f.write_str("jyear"),
+      TimeRepr::Iso => f.write_str("iso"),
+    }
+  }
+}
+
+/// Convert time values from a representation and a timescale to another.
+#[derive(Clone, Debug)]
+pub struct TimeConverter {
+  from_repr: TimeRepr,
+  from_scale: TimeScale,
+  to_repr: TimeRepr,
+  to_scale: TimeScale,
+}
+
+impl TimeConverter {
+  pub fn new(
+    from_repr: TimeRepr,
+    from_scale: TimeScale,
+    to_repr: TimeRepr,
+    to_scale: TimeScale,
+  ) -> Result<Self, VOTableError> {
+    if from_scale != to_scale {
+      check_supported_timescale(&from_scale).and_then(|()| check_supported_timescale(&to_scale))?;
+    }
+    Ok(Self {
+      from_repr,
+      from_scale,
+      to_repr,
+      to_scale,
+    })
+  }
+
+  /// Convert the given value, `NULL` (or `NaN`) values being converted into `NULL` values.
+  pub fn convert(&self, value: &VOTableValue) -> Result<VOTableValue, VOTableError> {
+    match self.from_repr.value_to_jd(value)? {
+      None => Ok(VOTableValue::Null),
+      Some((jd, leap_sec)) => {
+        let jd = if self.from_scale == self.to_scale {
+          jd + leap_sec / SEC_PER_DAY
+        } else {
+          let tt = to_tt(jd, leap_sec, &self.from_scale)?;
+          from_tt(tt, &self.to_scale)?
+        };
+        Ok(self.to_repr.jd_to_value(jd))
+      }
+    }
+  }
+}
+
+/// Convert the values of the time columns of the first table of a VOTable, i.e. of the
+/// columns referencing a `TIMESYS`.
+pub struct TableTimeConverter {
+  /// Index of each time column with its converter.
+  converters: Vec<(usize, TimeConverter)>,
+}
+
+impl TableTimeConverter {
+  /// Prepares the conversions and modifies the VOTable metadata accordingly, i.e. the `TIMESYS`
+  /// (`timescale` and `timeorigin`) and the time `FIELD`s (datatype, unit, ...).
+  ///
+  /// Since the datatype of the time `FIELD`s may change, in streaming mode this must be applied
+  /// on a copy of the VOTable used to read the rows.
+  /// # Params
+  /// * `votable`: the VOTable containing (at least) one table
+  /// * `to_repr`: the target representation (`None` to keep the current one)
+  /// * `to_scale`: the target timescale (`None` to keep the current one)
+  pub fn new<C: TableDataContent>(
+    votable: &mut VOTable<C>,
+    to_repr: Option<TimeRepr>,
+    to_scale: Option<TimeScale>,
+  ) -> Result<Self, VOTableError> {
+    let mut timesys_list: Vec<TimeSys> = Vec::new();
+    for_each_timesys_mut(votable, &mut |timesys| timesys_list.push(timesys.clone()));
+    let table = votable
+      .get_first_table_mut()
+      .ok_or_else(|| VOTableError::Custom(String::from("No table found in the VOTable")))?;
+    let mut converters: Vec<(usize, TimeConverter)> = Vec::new();
+    let mut new_timesys: Vec<TimeSys> = Vec::new();
+    let fields = table.elems.iter_mut().filter_map(|elem| match elem {
+      TableElem::Field(field) => Some(field),
+      _ => None,
+    });
+    for (index, field) in fields.enumerate() {
+      let timesys = field.ref_.as_deref().and_then(|ref_| {
+        timesys_list
+          .iter()
+          .find(|timesys| timesys.id.as_str() == ref_)
+      });
+      if let Some(timesys) = timesys {
+        let from_repr = TimeRepr::from_field(field, timesys)?;
+        let to_repr = match (to_repr, from_repr) {
+          // Keep a non-standard origin or unit when only changing the timescale
+          (None, from_repr) => from_repr,
+          (Some(to_repr), _) => to_repr,
+        };
+        let to_scale = to_scale.clone().unwrap_or(timesys.timescale.clone());
+        let converter = TimeConverter::new(
+          from_repr,
+          timesys.timescale.clone(),
+          to_repr,
+          to_scale.clone(),
+        )?;
+        let mut timesys = timesys.clone();
+        timesys.timescale = to_scale;
+        timesys.timeorigin = to_repr.timeorigin();
+        match new_timesys.iter().find(|t| t.id == timesys.id) {
+          Some(t) if *t != timesys => {
+            return Err(VOTableError::Custom(format!(
+              "Columns referencing TIMESYS '{}' would require different time origins",
+              timesys.id
+            )));
+          }
+          Some(_) => {}
+          None => new_timesys.push(timesys),
+        }
+        if to_repr != from_repr {
+          to_repr.set_field(field);
+        }
+        converters.push((index, converter));
+      }
+    }
+    if converters.is_empty() {
+      return Err(VOTableError::Custom(String::from(
+        "No column referencing a TIMESYS found",
+      )));
+    }
+    for_each_timesys_mut(votable, &mut |timesys| {
+      if let Some(new) = new_timesys.iter().find(|t| t.id == timesys.id) {
+        *timesys = new.clone();
+      }
+    });
+    Ok(Self { converters })
+  }
+
+  /// Convert the time values of the given row.
+  pub fn convert_row(&self, row: &mut [VOTableValue]) -> Result<(), VOTableError> {
+    for (index, converter) in self.converters.iter() {
+      if let Some(value) = row.get_mut(*index) {
+        *value = converter.convert(value)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+fn for_each_timesys_mut<C, F>(votable: &mut VOTable<C>, f: &mut F)
+where
+  C: TableDataContent,
+  F: FnMut(&mut TimeSys),
+{
+  fn for_each_in_resource<C, F>(resource: &mut Resource<C>, f: &mut F)
+  where
+    C: TableDataContent,
+    F: FnMut(&mut TimeSys),
+  {
+    for elem in resource.elems.iter_mut() {
+      if let ResourceElem::TimeSys(timesys) = elem {
+        f(timesys);
+      }
+    }
+    for sub_elem in resource.sub_elems.iter_mut() {
+      if let ResourceOrTable::Resource(resource) = &mut sub_elem.resource_or_table {
+        for_each_in_resource(resource, f);
+      }
+    }
+  }
+  for elem in votable.elems.iter_mut() {
+    if let VOTableElem::TimeSys(timesys) = elem {
+      f(timesys);
+    }
+  }
+  for resource in votable.resources.iter_mut() {
+    for_each_in_resource(resource, f);
+  }
+}
+
+fn is_nan(value: &VOTableValue) -> bool {
+  match value {
+    VOTableValue::Float(v) => v.is_nan(),
+    VOTableValue::Double(v) => v.is_nan(),
+    _ => false,
+  }
+}
+
+/// Returns the size of the given time unit, in days.
+fn unit_in_days(unit: Option<&str>) -> Option<f64> {
+  match unit {
+    None | Some("d") => Some(1.0),
+    Some("h") => Some(1.0 / 24.0),
+    Some("min") => Some(1.0 / 1440.0),
+    Some("s") => Some(1.0 / SEC_PER_DAY),
+    Some("yr") | Some("a") => Some(DAYS_PER_JULIAN_YEAR),
+    _ => None,
+  }
+}
+
+fn check_supported_timescale(scale: &TimeScale) -> Result<(), VOTableError> {
+  match scale {
+    TimeScale::UTC | TimeScale::TAI | TimeScale::TT | TimeScale::TDB | TimeScale::GPS => Ok(()),
+    _ => Err(VOTableError::Custom(format!(
+      "Conversion from/to timescale '{}' not supported",
+      scale
+    ))),
+  }
+}
+
+/// Returns TAI - UTC, in seconds, at the given UTC Julian Date.
+fn tai_minus_utc(jd_utc: f64) -> Result<f64, VOTableError> {
+  LEAP_SECONDS
+    .iter()
+    .rev()
+    .find(|(year, month, _)| jd_utc >= cal_to_jd(*year, *month, 1))
+    .map(|(_, _, delta)| *delta)
+    .ok_or_else(|| {
+      VOTableError::Custom(format!(
+        "UTC before 1972-01-01 not supported. Actual: {}",
+        format_iso(jd_utc)
+      ))
+    })
+}
+
+/// Returns TDB - TT, in seconds.
+fn tdb_minus_tt(jd: f64) -> f64 {
+  let g = (357.53 + 0.98560028 * (jd - JD_J2000)).to_radians();
+  0.001657 * g.sin() + 0.000014 * (2.0 * g).sin()
+}
+
+/// Julian Date in the given timescale to Julian Date in TT.
+/// `leap_sec` is the number of seconds of the given UTC date larger than 59.
+fn to_tt(jd: f64, leap_sec: f64, scale: &TimeScale) -> Result<f64, VOTableError> {
+  let dt = match scale {
+    TimeScale::UTC => tai_minus_utc(jd)? + leap_sec + TT_MINUS_TAI,
+    TimeScale::TAI => leap_sec + TT_MINUS_TAI,
+    TimeScale::GPS => leap_sec + TAI_MINUS_GPS + TT_MINUS_TAI,
+    TimeScale::TDB => leap_sec - tdb_minus_tt(jd),
+    _ => leap_sec,
+  };
+  Ok(jd + dt / SEC_PER_DAY)
+}
+
+/// Julian Date in TT to Julian Date in the given timescale.
+fn from_tt(tt: f64, scale: &TimeScale) -> Result<f64, VOTableError> {
+  let tai = tt - TT_MINUS_TAI / SEC_PER_DAY;
+  match scale {
+    TimeScale::UTC => {
+      // First guess with the TAI date, then with the UTC date (a time during a leap second
+      // is returned as the first second of the next day)
+      let utc = tai - tai_minus_utc(tai)? / SEC_PER_DAY;
+      Ok(tai - tai_minus_utc(utc)? / SEC_PER_DAY)
+    }
+    TimeScale::TAI => Ok(tai),
+    TimeScale::GPS => Ok(tai - TAI_MINUS_GPS / SEC_PER_DAY),
+    TimeScale::TDB => Ok(tt + tdb_minus_tt(tt) / SEC_PER_DAY),
+    _ => Ok(tt),
+  }
+}
+
+/// Julian Date at 0h of the given Gregorian calendar date.
+fn cal_to_jd(year: i32, month: u32, day: u32) -> f64 {
+  let (y, m, d) = (year as i64, month as i64, day as i64);
+  let a = (m - 14) / 12;
+  let jdn = (1461 * (y + 4800 + a)) / 4 + (367 * (m - 2 - 12 * a)) / 12
+    - (3 * ((y + 4900 + a) / 100)) / 4
+    + d
+    - 32075;
+  jdn as f64 - 0.5
+}
+
+/// Gregorian calendar date (year, month, day) of the given Julian Day Number.
+fn jdn_to_cal(jdn: i64) -> (i64, i64, i64) {
+  let mut l = jdn + 68569;
+  let n = (4 * l) / 146097;
+  l -= (146097 * n + 3) / 4;
+  let i = (4000 * (l + 1)) / 1461001;
+  l = l - (1461 * i) / 4 + 31;
+  let j = (80 * l) / 2447;
+  let d = l - (2447 * j) / 80;
+  l = j / 11;
+  let m = j + 2 - 12 * l;
+  let y = 100 * (n - 49) + i + l;
+  (y, m, d)
+}
+
+/// Parse an ISO-8601 date, returning the Julian Date and the number of seconds larger than 59
+/// (in case of a leap second).
+fn parse_iso(s: &str) -> Result<(f64, f64), VOTableError> {
+  let err = || VOTableError::Custom(format!("Wrong ISO-8601 time value: '{}'", s));
+  let s = s.trim();
+  let s = s.strip_suffix('Z').unwrap_or(s);
+  let (date, time) = match s.split_once(['T', ' ']) {
+    Some((date, time)) => (date, Some(time)),
+    None => (s, None),
+  };
+  // Date, possibly with a negative year
+  let (sign, date) = match date.strip_prefix('-') {
+    Some(date) => (-1, date),
+    None => (1, date),
+  };
+  let mut date_it = date.splitn(3, '-');
+  let (year, month, day) = match (date_it.next(), date_it.next(), date_it.next()) {
+    (Some(y), Some(m), Some(d)) => (
+      y.parse::<i32>().map_err(|_| err())? * sign,
+      m.parse::<u32>().map_err(|_| err())?,
+      d.parse::<u32>().map_err(|_| err())?,
+    ),
+    _ => return Err(err()),
+  };
+  if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+    return Err(err());
+  }
+  let jd = cal_to_jd(year, month, day);
+  // Time
+  let (hours, minutes, seconds) = match time {
+    None => (0, 0, 0.0),
+    Some(time) => {
+      let mut time_it = time.splitn(3, ':');
+      match (time_it.next(), time_it.next(), time_it.next()) {
+        (Some(h), Some(m), s) => (
+          h.parse::<u32>().map_err(|_| err())?,
+          m.parse::<u32>().map_err(|_| err())?,
+          s.map(|s| s.parse::<f64>().map_err(|_| err()))
+            .transpose()?
+            .unwrap_or(0.0),
+        ),
+        _ => return Err(err()),
+      }
+    }
+  };
+  if hours > 23 || minutes > 59 || !(0.0..61.0).contains(&seconds) {
+    return Err(err());
+  }
+  let (seconds, leap_sec) = if seconds >= 60.0 {
+    (59.0, seconds - 59.0)
+  } else {
+    (seconds, 0.0)
+  };
+  let day_sec = (hours * 3600 + minutes * 60) as f64 + seconds;
+  Ok((jd + day_sec / SEC_PER_DAY, leap_sec))
+}
+
+/// Format the given Julian Date in ISO-8601, with a millisecond precision.
+fn format_iso(jd: f64) -> String {
+  let jd = jd + 0.5;
+  let mut jdn = jd.floor() as i64;
+  let mut ms = ((jd - jd.floor()) * SEC_PER_DAY * 1000.0).round() as i64;
+  if ms >= 86_400_000 {
+    jdn += 1;
+    ms -= 86_400_000;
+  }
+  let (y, m, d) = jdn_to_cal(jdn);
+  format!(
+    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}",
+    y,
+    m,
+    d,
+    ms / 3_600_000,
+    (ms / 60_000) % 60,
+    (ms / 1000) % 60,
+    ms % 1000
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{iter::SimpleVOTableRowIterator, timesys::RefPosition};
+
+  fn convert(
+    from: (TimeRepr, TimeScale),
+    to: (TimeRepr, TimeScale),
+    value: VOTableValue,
+  ) -> VOTableValue {
+    TimeConverter::new(from.0, from.1, to.0, to.1)
+      .unwrap()
+      .convert(&value)
+      .unwrap()
+  }
+
+  #[test]
+  fn test_repr() {
+    let iso = |s: &str| VOTableValue::String(String::from(s));
+    // J2000.0 is 2000-01-01T12:00:00 TT
+    assert_eq!(
+      convert(
+        (TimeRepr::Iso, TimeScale::TT),
+        (TimeRepr::jd(), TimeScale::TT),
+        iso("2000-01-01T12:00:00")
+      ),
+      VOTableValue::Double(JD_J2000)
+    );
+    assert_eq!(
+      convert(
+        (TimeRepr::JulianYear, TimeScale::TT),
+        (TimeRepr::mjd(), TimeScale::TT),
+        VOTableValue::Float(2000.0)
+      ),
+      VOTableValue::Double(51544.5)
+    );
+    assert_eq!(
+      convert(
+        (TimeRepr::mjd(), TimeScale::UTC),
+        (TimeRepr::Iso, TimeScale::UTC),
+        VOTableValue::Double(57754.75)
+      ),
+      iso("2017-01-01T18:00:00.000")
+    );
+    assert_eq!(
+      convert(
+        (TimeRepr::Iso, TimeScale::UTC),
+        (TimeRepr::Iso, TimeScale::UTC),
+        iso("1858-11-17")
+      ),
+      iso("1858-11-17T00:00:00.000")
+    );
+    assert_eq!(
+      convert(
+        (TimeRepr::jd(), TimeScale::TT),
+        (TimeRepr::Iso, TimeScale::TT),
+        VOTableValue::Null
+      ),
+      VOTableValue::Null
+    );
+    assert!(parse_iso("2000-13-01").is_err());
+    assert!(parse_iso("2000-01-01T12:60:00").is_err());
+  }
+
+  #[test]
+  fn test_timescales() {
+    let iso = |s: &str| VOTableValue::String(String::from(s));
+    let iso_utc = || (TimeRepr::Iso, TimeScale::UTC);
+    // TT - UTC = 69.184 s since 2017
+    assert_eq!(
+      convert(
+        iso_utc(),
+        (TimeRepr::Iso, TimeScale::TT),
+        iso("2020-06-01T00:00:00Z")
+      ),
+      iso("2020-06-01T00:01:09.184")
+    );
+    assert_eq!(
+      convert(
+        iso_utc(),
+        (TimeRepr::Iso, TimeScale::GPS),
+        iso("2020-06-01T00:00:00Z")
+      ),
+      iso("2020-06-01T00:00:18.000")
+    );
+    // Leap second
+    assert_eq!(
+      convert(
+        iso_utc(),
+        (TimeRepr::Iso, TimeScale::TAI),
+        iso("2016-12-31T23:59:60.5")
+      ),
+      iso("2017-01-01T00:00:36.500")
+    );
+    assert_eq!(
+      convert(
+        iso_utc(),
+        (TimeRepr::Iso, TimeScale::TAI),
+        iso("2017-01-01T00:00:00")
+      ),
+      iso("2017-01-01T00:00:37.000")
+    );
+    assert_eq!(
+      convert(
+        (TimeRepr::Iso, TimeScale::TAI),
+        iso_utc(),
+        iso("2017-01-01T00:00:37")
+      ),
+      iso("2017-01-01T00:00:00.000")
+    );
+    // TDB - TT < 2 ms
+    let tdb = convert(
+      (TimeRepr::jd(), TimeScale::TT),
+      (TimeRepr::jd(), TimeScale::TDB),
+      VOTableValue::Double(2458000.0),
+    );
+    assert!(((tdb.as_f64().unwrap() - 2458000.0) * SEC_PER_DAY).abs() < 2e-3);
+    // Errors
+    assert!(
+      TimeConverter::new(TimeRepr::Iso, TimeScale::UTC, TimeRepr::Iso, TimeScale::UTC)
+        .unwrap()
+        .convert(&iso("1960-01-01"))
+        .is_ok()
+    );
+    assert!(
+      TimeConverter::new(TimeRepr::Iso, TimeScale::UTC, TimeRepr::Iso, TimeScale::TT)
+        .unwrap()
+        .convert(&iso("1960-01-01"))
+        .is_err()
+    );
+    assert!(
+      TimeConverter::new(TimeRepr::Iso, TimeScale::TCB, TimeRepr::Iso, TimeScale::TT).is_err()
+    );
+  }
+
+  #[test]
+  fn test_table_time_converter() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE>
+    <TIMESYS ID="ts" timeorigin="MJD-origin" timescale="UTC" refposition="TOPOCENTER"/>
+    <TABLE>
+      <FIELD name="id" datatype="int"/>
+      <FIELD name="obs_time" datatype="double" unit="d" ref="ts"/>
+      <DATA>
+        <TABLEDATA>
+          <TR><TD>1</TD><TD>57754.5</TD></TR>
+          <TR><TD>2</TD><TD></TD></TR>
+        </TABLEDATA>
+      </DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    let mut it = SimpleVOTableRowIterator::from_reader(xml.as_bytes()).unwrap();
+    let mut votable = it.votable.clone();
+    let converter =
+      TableTimeConverter::new(&mut votable, Some(TimeRepr::Iso), Some(TimeScale::TAI)).unwrap();
+    let mut rows = it
+      .to_row_value_iter()
+      .collect::<Result<Vec<_>, VOTableError>>()
+      .unwrap();
+    for row in rows.iter_mut() {
+      converter.convert_row(row).unwrap();
+    }
+    assert_eq!(
+      rows[0][1],
+      VOTableValue::String(String::from("2017-01-01T12:00:37.000"))
+    );
+    assert_eq!(rows[1][1], VOTableValue::Null);
+    let mut timesys_list = Vec::new();
+    for_each_timesys_mut(&mut votable, &mut |timesys| {
+      timesys_list.push(timesys.clone())
+    });
+    assert_eq!(
+      timesys_list,
+      vec![TimeSys::new("ts", TimeScale::TAI, RefPosition::TOPOCENTER)]
+    );
+    match &votable.get_first_table().unwrap().elems[1] {
+      TableElem::Field(field) => {
+        assert_eq!(field.datatype, Datatype::CharASCII);
+        assert_eq!(field.xtype.as_deref(), Some("timestamp"));
+        assert_eq!(field.unit, None);
+      }
+      _ => unreachable!(),
+    }
+  }
+}
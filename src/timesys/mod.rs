@@ -9,6 +9,13 @@ use paste::paste;
 
 use super::{error::VOTableError, utils::unexpected_attr_warn, EmptyElem, VOTableElement};
 
+pub mod convert;
+
+/// Value of the `JD-origin` special `timeorigin`.
+pub const JD_ORIGIN: f64 = 0.0;
+/// Value of the `MJD-origin` special `timeorigin`.
+pub const MJD_ORIGIN: f64 = 2400000.5;
+
 /// Struct corresponding to the `TIMESYS` XML tag.
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct TimeSys {
@@ -62,7 +69,7 @@ impl VOTableElement for TimeSys {
       let key = key.as_ref();
       match key {
         "ID" => id = Some(val.into()),
-        "timeorigin" => timeorigin = Some(val.as_ref().parse().map_err(VOTableError::ParseFloat)?),
+        "timeorigin" => timeorigin = Some(parse_timeorigin(val.as_ref())?),
         "timescale" => timescale = Some(val.as_ref().parse().map_err(VOTableError::Variant)?),
         "refposition" => refposition = Some(val.as_ref().parse().map_err(VOTableError::Variant)?),
         _ => unexpected_attr_warn(key, Self::TAG),
@@ -93,9 +100,7 @@ impl VOTableElement for TimeSys {
       let key = key.as_ref();
       match key {
         "ID" => self.set_id_by_ref(val),
-        "timeorigin" => {
-          self.set_timeorigin_by_ref(val.as_ref().parse().map_err(VOTableError::ParseFloat)?)
-        }
+        "timeorigin" => self.set_timeorigin_by_ref(parse_timeorigin(val.as_ref())?),
         "timescale" => {
           self.set_timescale_by_ref(val.as_ref().parse().map_err(VOTableError::Variant)?)
         }
@@ -121,6 +126,16 @@ impl VOTableElement for TimeSys {
   }
 }
 
+/// Parse a `timeorigin`, i.e. a Julian Date or one of the special values `JD-origin` and
+/// `MJD-origin`.
+fn parse_timeorigin(s: &str) -> Result<f64, VOTableError> {
+  match s.trim() {
+    "JD-origin" => Ok(JD_ORIGIN),
+    "MJD-origin" => Ok(MJD_ORIGIN),
+    s => s.parse().map_err(VOTableError::ParseFloat),
+  }
+}
+
 pub struct Info {
   pub label: &'static str,
   pub description: &'static str,
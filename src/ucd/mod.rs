@@ -0,0 +1,389 @@
+//! Parsing and validation of [UCD1+](https://www.ivoa.net/documents/UCD1+/) strings, and
+//! semantic queries on the columns of a table (e.g. "the main RA column" or "all photometric
+//! columns").
+//!
+//! A UCD is a list of `;` separated words, the first one being the *primary* word, the other
+//! ones the *secondary* words.
+//! Words are hierarchical (`phot.mag` is a child of `phot`) and case-insensitive.
+//! The validation is made against a bundled copy of the UCD1+ controlled vocabulary, in which
+//! each word has a syntax code telling if it can be used as a primary and/or as a secondary word.
+
+use std::{
+  collections::HashMap,
+  fmt::{self, Display, Formatter},
+  str::FromStr,
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{
+  error::VOTableError,
+  field::Field,
+  table::{Table, TableElem},
+  TableDataContent,
+};
+
+/// Word marking the main value among columns having the same primary word.
+pub const META_MAIN: &str = "meta.main";
+
+static UCD1P: Lazy<Vocabulary> = Lazy::new(|| {
+  include_str!("ucd1p-words.txt")
+    .parse()
+    .expect("Wrong bundled UCD1+ vocabulary")
+});
+
+/// A parsed UCD.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ucd {
+  /// Words of the UCD, the first one being the primary word
+  words: Vec<String>,
+}
+
+impl Ucd {
+  /// Returns all the words of the UCD.
+  pub fn words(&self) -> &[String] {
+    self.words.as_slice()
+  }
+
+  /// Returns the primary word of the UCD.
+  pub fn primary(&self) -> &str {
+    self.words[0].as_str()
+  }
+
+  /// Returns the secondary words of the UCD.
+  pub fn secondaries(&self) -> &[String] {
+    &self.words[1..]
+  }
+
+  /// Tells whether the UCD contains exactly the given word.
+  pub fn contains(&self, word: &str) -> bool {
+    self.words.iter().any(|w| w.eq_ignore_ascii_case(word))
+  }
+
+  /// Tells whether the primary word is the given word or one of its children
+  /// (e.g. `phot.mag` has primary `phot`).
+  pub fn has_primary(&self, word: &str) -> bool {
+    is_word_or_child(self.primary(), word)
+  }
+
+  /// Tells whether the UCD contains the given word or one of its children.
+  pub fn has_word(&self, word: &str) -> bool {
+    self.words.iter().any(|w| is_word_or_child(w, word))
+  }
+
+  /// Tells whether the UCD contains `meta.main`.
+  pub fn is_main(&self) -> bool {
+    self.contains(META_MAIN)
+  }
+
+  /// Check the UCD against the UCD1+ vocabulary, returning the list of issues (empty if the UCD
+  /// is valid).
+  pub fn check(&self) -> Vec<UcdIssue> {
+    Vocabulary::ucd1p().check(self)
+  }
+}
+
+impl FromStr for Ucd {
+  type Err = VOTableError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let words = s
+      .split(';')
+      .map(|word| {
+        let word = word.trim();
+        if word.is_empty()
+          || word.starts_with('.')
+          || word.ends_with('.')
+          || word.contains("..")
+          || !word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_')
+        {
+          Err(VOTableError::Custom(format!(
+            "Wrong UCD word '{}' in UCD '{}'",
+            word, s
+          )))
+        } else {
+          Ok(word.to_string())
+        }
+      })
+      .collect::<Result<Vec<String>, VOTableError>>()?;
+    Ok(Self { words })
+  }
+}
+
+impl Display for Ucd {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.words.join(";"))
+  }
+}
+
+/// Tells whether `word` is equal to `parent` or is one of its children (case-insensitive).
+fn is_word_or_child(word: &str, parent: &str) -> bool {
+  match word.get(..parent.len()) {
+    Some(prefix) if prefix.eq_ignore_ascii_case(parent) => {
+      word.len() == parent.len() || word.as_bytes()[parent.len()] == b'.'
+    }
+    _ => false,
+  }
+}
+
+/// Syntax code of a UCD1+ word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Syntax {
+  /// `P`: primary word only
+  Primary,
+  /// `S`: secondary word only
+  Secondary,
+  /// `Q`: primary or secondary word
+  Both,
+  /// `E`: photometric quantity, possibly followed by an `em` word
+  Photometric,
+  /// `C`: colour index, possibly followed by two `em` words
+  Colour,
+  /// `V`: vector, possibly followed by a word describing the axis or the frame
+  Vector,
+}
+
+impl Syntax {
+  pub fn can_be_primary(&self) -> bool {
+    !matches!(self, Self::Secondary)
+  }
+
+  pub fn can_be_secondary(&self) -> bool {
+    !matches!(self, Self::Primary)
+  }
+}
+
+impl FromStr for Syntax {
+  type Err = VOTableError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "P" => Ok(Self::Primary),
+      "S" => Ok(Self::Secondary),
+      "Q" => Ok(Self::Both),
+      "E" => Ok(Self::Photometric),
+      "C" => Ok(Self::Colour),
+      "V" => Ok(Self::Vector),
+      _ => Err(VOTableError::Custom(format!(
+        "Unknown UCD syntax code. Actual: '{}'. Expected: 'P', 'S', 'Q', 'E', 'C' or 'V'.",
+        s
+      ))),
+    }
+  }
+}
+
+/// Definition of a word of the vocabulary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WordDef {
+  pub word: String,
+  pub syntax: Syntax,
+  pub description: String,
+}
+
+/// A UCD controlled vocabulary.
+#[derive(Clone, Debug)]
+pub struct Vocabulary {
+  /// Definitions, by lower case words
+  words: HashMap<String, WordDef>,
+}
+
+impl Vocabulary {
+  /// Returns the bundled UCD1+ vocabulary.
+  pub fn ucd1p() -> &'static Self {
+    &UCD1P
+  }
+
+  /// Returns the definition of the given word, if it is in the vocabulary.
+  pub fn get(&self, word: &str) -> Option<&WordDef> {
+    self.words.get(&word.to_ascii_lowercase())
+  }
+
+  pub fn len(&self) -> usize {
+    self.words.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.words.is_empty()
+  }
+
+  /// Check the given UCD, returning the list of issues (empty if the UCD is valid).
+  pub fn check(&self, ucd: &Ucd) -> Vec<UcdIssue> {
+    ucd
+      .words()
+      .iter()
+      .enumerate()
+      .filter_map(|(i, word)| match self.get(word) {
+        None => Some(UcdIssue::UnknownWord(word.clone())),
+        Some(def) if i == 0 && !def.syntax.can_be_primary() => {
+          Some(UcdIssue::NotPrimary(word.clone()))
+        }
+        Some(def) if i > 0 && !def.syntax.can_be_secondary() => {
+          Some(UcdIssue::NotSecondary(word.clone()))
+        }
+        _ => None,
+      })
+      .collect()
+  }
+}
+
+impl FromStr for Vocabulary {
+  type Err = VOTableError;
+
+  /// Parse a vocabulary in the IVOA `ucd-list.txt` format, i.e. one `CODE | word | description`
+  /// line per word (lines starting with `#` are ignored).
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    s.lines()
+      .map(str::trim)
+      .filter(|line| !line.is_empty() && !line.starts_with('#'))
+      .map(|line| {
+        let mut it = line.splitn(3, '|').map(str::trim);
+        match (it.next(), it.next(), it.next()) {
+          (Some(code), Some(word), Some(description)) => code.parse().map(|syntax| WordDef {
+            word: word.to_string(),
+            syntax,
+            description: description.to_string(),
+          }),
+          _ => Err(VOTableError::Custom(format!(
+            "Wrong UCD vocabulary line: '{}'",
+            line
+          ))),
+        }
+      })
+      .map(|res| res.map(|def| (def.word.to_ascii_lowercase(), def)))
+      .collect::<Result<HashMap<String, WordDef>, VOTableError>>()
+      .map(|words| Self { words })
+  }
+}
+
+/// A UCD validation issue.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UcdIssue {
+  /// The word is not in the vocabulary
+  UnknownWord(String),
+  /// The word is used as primary word but it is a secondary only word
+  NotPrimary(String),
+  /// The word is used as secondary word but it is a primary only word
+  NotSecondary(String),
+}
+
+impl Display for UcdIssue {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::UnknownWord(word) => write!(f, "Unknown UCD word '{}'", word),
+      Self::NotPrimary(word) => write!(f, "UCD word '{}' can't be a primary word", word),
+      Self::NotSecondary(word) => write!(f, "UCD word '{}' can't be a secondary word", word),
+    }
+  }
+}
+
+impl Field {
+  /// Returns the parsed UCD of the field, `None` if the field has no UCD or if the UCD is not
+  /// syntactically valid.
+  pub fn parsed_ucd(&self) -> Option<Ucd> {
+    self.ucd.as_deref().and_then(|ucd| ucd.parse().ok())
+  }
+}
+
+/// Returns the indices (among the given fields) of the fields having the given primary word
+/// (or one of its children), e.g. `phot` for all photometric columns.
+pub fn find_columns<'a, I>(fields: I, primary: &str) -> Vec<usize>
+where
+  I: IntoIterator<Item = &'a Field>,
+{
+  fields
+    .into_iter()
+    .enumerate()
+    .filter_map(|(i, field)| {
+      field
+        .parsed_ucd()
+        .filter(|ucd| ucd.has_primary(primary))
+        .map(|_| i)
+    })
+    .collect()
+}
+
+/// Returns the index (among the given fields) of the main field having exactly the given
+/// primary word, e.g. `pos.eq.ra` for the main RA column: the first field also having
+/// `meta.main` or, if no such field exists, the first field having the primary word.
+pub fn find_main_column<'a, I>(fields: I, primary: &str) -> Option<usize>
+where
+  I: IntoIterator<Item = &'a Field>,
+{
+  let mut first = None;
+  for (i, field) in fields.into_iter().enumerate() {
+    match field
+      .parsed_ucd()
+      .filter(|ucd| ucd.primary().eq_ignore_ascii_case(primary))
+    {
+      Some(ucd) if ucd.is_main() => return Some(i),
+      Some(_) if first.is_none() => first = Some(i),
+      _ => {}
+    }
+  }
+  first
+}
+
+impl<C: TableDataContent> Table<C> {
+  fn fields(&self) -> impl Iterator<Item = &Field> {
+    self.elems.iter().filter_map(|elem| match elem {
+      TableElem::Field(field) => Some(field),
+      _ => None,
+    })
+  }
+
+  /// Returns the indices of the columns having the given UCD primary word (or one of its
+  /// children), see [find_columns].
+  pub fn columns_with_ucd(&self, primary: &str) -> Vec<usize> {
+    find_columns(self.fields(), primary)
+  }
+
+  /// Returns the index of the main column having the given UCD primary word,
+  /// see [find_main_column].
+  pub fn main_column_with_ucd(&self, primary: &str) -> Option<usize> {
+    find_main_column(self.fields(), primary)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{datatype::Datatype, impls::mem::VoidTableDataContent};
+
+  #[test]
+  fn test_ucd() {
+    let ucd: Ucd = "phot.mag;em.opt.V ; meta.main".parse().unwrap();
+    assert_eq!(ucd.primary(), "phot.mag");
+    assert_eq!(ucd.secondaries().len(), 2);
+    assert!(ucd.has_primary("phot") && !ucd.has_primary("pho"));
+    assert!(ucd.has_word("em.opt") && ucd.is_main());
+    assert_eq!(ucd.to_string(), "phot.mag;em.opt.V;meta.main");
+    assert_eq!(ucd.check(), vec![]);
+    assert!("pos.eq.ra;;meta.main".parse::<Ucd>().is_err());
+
+    assert!(Vocabulary::ucd1p().len() > 500);
+    assert_eq!(
+      "meta.main;pos.parallax".parse::<Ucd>().unwrap().check(),
+      vec![
+        UcdIssue::NotPrimary(String::from("meta.main")),
+        UcdIssue::NotSecondary(String::from("pos.parallax"))
+      ]
+    );
+    assert_eq!(
+      "pos.eq.foo".parse::<Ucd>().unwrap().check(),
+      vec![UcdIssue::UnknownWord(String::from("pos.eq.foo"))]
+    );
+
+    let table = Table::<VoidTableDataContent>::new()
+      .push_field(Field::new("e_RA", Datatype::Double).set_ucd("stat.error;pos.eq.ra"))
+      .push_field(Field::new("RA2", Datatype::Double).set_ucd("pos.eq.ra"))
+      .push_field(Field::new("RA", Datatype::Double).set_ucd("POS.EQ.RA;meta.main"))
+      .push_field(Field::new("Vmag", Datatype::Float).set_ucd("phot.mag;em.opt.V"))
+      .push_field(Field::new("B-V", Datatype::Float).set_ucd("phot.color;em.opt.B;em.opt.V"));
+    assert_eq!(table.main_column_with_ucd("pos.eq.ra"), Some(2));
+    assert_eq!(table.main_column_with_ucd("pos.eq.dec"), None);
+    assert_eq!(table.columns_with_ucd("phot"), vec![3, 4]);
+    assert_eq!(table.columns_with_ucd("phot.mag"), vec![3]);
+  }
+}
@@ -0,0 +1,1161 @@
+//! Parsing and conversion of units following the [IVOA VOUnits](https://www.ivoa.net/documents/VOUnits/)
+//! grammar, also accepting the CDS (e.g. VizieR) and OGIP dialects:
+//! * products: `km.s-1` (VOUnits/CDS), `km s**-1` or `km*s^-1` (OGIP/FITS);
+//! * powers: `m**2`, `m^2`, `m**(1/2)`, or CDS attached powers `m2`, `s-1`;
+//! * scale factors: `1e-3m`, `10+3m` or `1.5x10+3m` (CDS), `10**-3 m`;
+//! * SI prefixes (`km`, `mJy`, `Myr`, ...);
+//! * logarithmic units: `mag`, `dex`, `[Msun]` (CDS) or `log(Msun)`;
+//! * dimensionless values: `` (empty), `---` (CDS) or `%`.
+//!
+//! A unit is represented by a factor and a dimension (exponents of the SI base units, plus angles
+//! and information which are kept as separate dimensions to avoid converting e.g. `rad` into `m/m`).
+//! Two units are equivalent if they have the same dimension (and the same logarithmic scale),
+//! in which case values can be converted from one unit to the other.
+//! Converting magnitudes from/to linear units (e.g. fluxes) requires a zero point.
+
+use std::{
+  f64::consts::PI,
+  fmt::{self, Display, Formatter},
+  iter::Peekable,
+  str::{Chars, FromStr},
+};
+
+use crate::{
+  datatype::Datatype, error::VOTableError, field::Field, impls::VOTableValue, table::TableElem,
+  votable::VOTable, walk::for_each_field, TableDataContent,
+};
+
+/// Number of dimensions: m, kg, s, A, K, mol, cd, rad, bit.
+const N_DIMS: usize = 9;
+/// Symbols of the base units, in the dimension order.
+const BASE_UNITS: [&str; N_DIMS] = ["m", "kg", "s", "A", "K", "mol", "cd", "rad", "bit"];
+
+const ARCSEC: f64 = PI / 648000.0;
+const JULIAN_YEAR: f64 = 365.25 * 86400.0;
+
+/// Exponents of the base units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dimension([f64; N_DIMS]);
+
+impl Dimension {
+  pub const DIMENSIONLESS: Self = Self([0.0; N_DIMS]);
+
+  /// Exponents of m, kg, s, A, K, mol, cd, rad and bit.
+  const fn new(e: [i8; N_DIMS]) -> Self {
+    Self([
+      e[0] as f64,
+      e[1] as f64,
+      e[2] as f64,
+      e[3] as f64,
+      e[4] as f64,
+      e[5] as f64,
+      e[6] as f64,
+      e[7] as f64,
+      e[8] as f64,
+    ])
+  }
+
+  pub fn is_dimensionless(&self) -> bool {
+    self.same_as(&Self::DIMENSIONLESS)
+  }
+
+  /// Compare dimensions, allowing for rounding errors on fractional exponents.
+  pub fn same_as(&self, other: &Self) -> bool {
+    self
+      .0
+      .iter()
+      .zip(other.0.iter())
+      .all(|(a, b)| (a - b).abs() < 1e-9)
+  }
+
+  fn mul(&self, other: &Self) -> Self {
+    let mut res = *self;
+    for (r, o) in res.0.iter_mut().zip(other.0.iter()) {
+      *r += o;
+    }
+    res
+  }
+
+  fn powf(&self, exponent: f64) -> Self {
+    let mut res = *self;
+    for r in res.0.iter_mut() {
+      *r *= exponent;
+    }
+    res
+  }
+}
+
+impl Display for Dimension {
+  /// Write the dimension using the base units, e.g. `m.s-1`
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    if self.is_dimensionless() {
+      return f.write_str("1");
+    }
+    let mut first = true;
+    for (symbol, exp) in BASE_UNITS.iter().zip(self.0.iter()) {
+      if exp.abs() >= 1e-9 {
+        if !first {
+          f.write_str(".")?;
+        }
+        first = false;
+        if (exp - 1.0).abs() < 1e-9 {
+          f.write_str(symbol)?;
+        } else if (exp - exp.round()).abs() < 1e-9 {
+          write!(f, "{}{}", symbol, exp.round())?;
+        } else {
+          write!(f, "{}**({})", symbol, exp)?;
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Logarithmic scale of a unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogScale {
+  /// Magnitudes: `-2.5 log10(x / x0)`, `x0` being a zero point
+  Mag,
+  /// Decimal logarithm: `log10(x)`
+  Dex,
+}
+
+/// A parsed unit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unit {
+  /// Factor to convert a value in this unit (or in the inner unit of a logarithmic unit) into SI
+  factor: f64,
+  dimension: Dimension,
+  /// Logarithmic scale, with the factor applied to the logarithmic value (e.g. `1e-3` for `mmag`)
+  log: Option<(LogScale, f64)>,
+}
+
+impl Unit {
+  pub fn dimensionless() -> Self {
+    Self {
+      factor: 1.0,
+      dimension: Dimension::DIMENSIONLESS,
+      log: None,
+    }
+  }
+
+  /// Factor to convert a value in this unit into SI (for logarithmic units, factor of the unit
+  /// inside the logarithm).
+  pub fn factor(&self) -> f64 {
+    self.factor
+  }
+
+  pub fn dimension(&self) -> &Dimension {
+    &self.dimension
+  }
+
+  pub fn log_scale(&self) -> Option<LogScale> {
+    self.log.map(|(scale, _)| scale)
+  }
+
+  pub fn is_dimensionless(&self) -> bool {
+    self.log.is_none() && self.dimension.is_dimensionless()
+  }
+
+  /// Tells whether values in this unit can be converted into the other unit without any extra
+  /// information (i.e. same dimension and same logarithmic scale).
+  pub fn is_equivalent(&self, other: &Self) -> bool {
+    self.log_scale() == other.log_scale() && self.dimension.same_as(&other.dimension)
+  }
+
+  /// Returns the factor to multiply values in this unit by to obtain values in the other unit.
+  /// # Errors
+  /// If the units are not equivalent or are logarithmic.
+  pub fn scale_factor_to(&self, to: &Self) -> Result<f64, VOTableError> {
+    if self.log.is_some() || to.log.is_some() {
+      Err(VOTableError::Custom(String::from(
+        "No scale factor between logarithmic units",
+      )))
+    } else {
+      self.check_equivalent(to).map(|()| self.factor / to.factor)
+    }
+  }
+
+  /// Returns the converter of values from this unit into the other unit.
+  /// # Errors
+  /// If the units are not equivalent (except for `dex` from/to linear units having the same
+  /// dimension), or to convert magnitudes from/to linear units (see [Unit::mag_converter_to]).
+  pub fn converter_to(&self, to: &Self) -> Result<UnitConverter, VOTableError> {
+    if !self.dimension.same_as(&to.dimension) {
+      return Err(self.incompatible_error(to));
+    }
+    match (self.log, to.log) {
+      (Some((LogScale::Mag, _)), Some((LogScale::Mag, _))) | (None, None) => {}
+      (Some((LogScale::Dex, _)), None | Some((LogScale::Dex, _)))
+      | (None, Some((LogScale::Dex, _))) => {}
+      _ => {
+        return Err(VOTableError::Custom(String::from(
+          "Converting magnitudes from/to other units requires a zero point",
+        )))
+      }
+    }
+    // For magnitudes, the zero point cancels out
+    Ok(UnitConverter {
+      from: self.repr(1.0),
+      to: to.repr(1.0),
+    })
+  }
+
+  /// Returns the converter of values from magnitudes into a linear unit, or from a linear unit
+  /// into magnitudes.
+  /// # Params
+  /// * `to`: the target unit
+  /// * `zero_point`: the value, in the linear unit, corresponding to a magnitude of 0
+  ///   (e.g. 3631 for AB magnitudes to `Jy`)
+  pub fn mag_converter_to(
+    &self,
+    to: &Self,
+    zero_point: f64,
+  ) -> Result<UnitConverter, VOTableError> {
+    match (self.log, to.log) {
+      (Some((LogScale::Mag, _)), None) => Ok(UnitConverter {
+        from: self.repr(zero_point * to.factor / self.factor),
+        to: to.repr(1.0),
+      }),
+      (None, Some((LogScale::Mag, _))) => Ok(UnitConverter {
+        from: self.repr(1.0),
+        to: to.repr(zero_point * self.factor / to.factor),
+      }),
+      _ => Err(VOTableError::Custom(String::from(
+        "Zero point conversion only from magnitudes into a linear unit, or the reverse",
+      ))),
+    }
+  }
+
+  /// Representation of the unit for the conversions, with an additional factor (the zero point
+  /// for magnitudes).
+  fn repr(&self, zero_point: f64) -> Repr {
+    match self.log {
+      None => Repr::Linear(self.factor),
+      Some((LogScale::Dex, k)) => Repr::Dex(self.factor, k),
+      Some((LogScale::Mag, k)) => Repr::Mag(self.factor * zero_point, k),
+    }
+  }
+
+  fn check_equivalent(&self, other: &Self) -> Result<(), VOTableError> {
+    if self.is_equivalent(other) {
+      Ok(())
+    } else {
+      Err(self.incompatible_error(other))
+    }
+  }
+
+  fn incompatible_error(&self, other: &Self) -> VOTableError {
+    VOTableError::Custom(format!(
+      "Incompatible units. Dimensions: '{}' and '{}'.",
+      self.dimension, other.dimension
+    ))
+  }
+
+  fn mul(self, other: Self) -> Result<Self, VOTableError> {
+    let log = match (self.log, other.log) {
+      (Some(_), Some(_)) => {
+        return Err(VOTableError::Custom(String::from(
+          "Product of logarithmic units",
+        )))
+      }
+      (log, None) | (None, log) => log,
+    };
+    Ok(Self {
+      factor: self.factor * other.factor,
+      dimension: self.dimension.mul(&other.dimension),
+      log,
+    })
+  }
+
+  fn powf(self, exponent: f64) -> Result<Self, VOTableError> {
+    if self.log.is_some() && exponent != 1.0 {
+      Err(VOTableError::Custom(String::from(
+        "Power or division of a logarithmic unit",
+      )))
+    } else {
+      Ok(Self {
+        factor: self.factor.powf(exponent),
+        dimension: self.dimension.powf(exponent),
+        log: self.log,
+      })
+    }
+  }
+
+  fn log10(self) -> Result<Self, VOTableError> {
+    match self.log {
+      Some(_) => Err(VOTableError::Custom(String::from(
+        "Logarithm of a logarithmic unit",
+      ))),
+      None => Ok(Self {
+        log: Some((LogScale::Dex, 1.0)),
+        ..self
+      }),
+    }
+  }
+}
+
+impl FromStr for Unit {
+  type Err = VOTableError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let trimmed = s.trim();
+    if trimmed.is_empty() || trimmed.chars().all(|c| c == '-') {
+      return Ok(Self::dimensionless());
+    }
+    let mut parser = Parser {
+      chars: trimmed.chars().peekable(),
+    };
+    parser
+      .product()
+      .and_then(|unit| match parser.chars.peek() {
+        None => Ok(unit),
+        Some(c) => Err(VOTableError::Custom(format!(
+          "Unexpected character '{}'",
+          c
+        ))),
+      })
+      .map_err(|e| {
+        let msg = match e {
+          VOTableError::Custom(msg) => msg,
+          e => e.to_string(),
+        };
+        VOTableError::Custom(format!("Unable to parse unit '{}': {}", s, msg))
+      })
+  }
+}
+
+/// Representation of a unit for the conversions: factors to SI and to the log values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Repr {
+  /// `x = v * factor`
+  Linear(f64),
+  /// `x = 10^(v * k) * factor`
+  Dex(f64, f64),
+  /// `x = 10^(-0.4 * v * k) * factor`, `factor` including the zero point
+  Mag(f64, f64),
+}
+
+impl Repr {
+  fn to_si(self, v: f64) -> f64 {
+    match self {
+      Self::Linear(factor) => v * factor,
+      Self::Dex(factor, k) => 10_f64.powf(v * k) * factor,
+      Self::Mag(factor, k) => 10_f64.powf(-0.4 * v * k) * factor,
+    }
+  }
+
+  fn si_to(self, x: f64) -> f64 {
+    match self {
+      Self::Linear(factor) => x / factor,
+      Self::Dex(factor, k) => (x / factor).log10() / k,
+      Self::Mag(factor, k) => -2.5 * (x / factor).log10() / k,
+    }
+  }
+}
+
+/// Convert values from a unit to another.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UnitConverter {
+  from: Repr,
+  to: Repr,
+}
+
+impl UnitConverter {
+  /// Returns the linear scale factor of the conversion, if the conversion is linear.
+  pub fn scale_factor(&self) -> Option<f64> {
+    match (self.from, self.to) {
+      (Repr::Linear(from), Repr::Linear(to)) => Some(from / to),
+      _ => None,
+    }
+  }
+
+  pub fn convert(&self, value: f64) -> f64 {
+    match self.scale_factor() {
+      Some(factor) => value * factor,
+      None => self.to.si_to(self.from.to_si(value)),
+    }
+  }
+
+  /// Convert a numeric value (scalar or array) into a `double` value (or array),
+  /// `NULL` values remaining `NULL`.
+  pub fn convert_value(&self, value: &VOTableValue) -> Result<VOTableValue, VOTableError> {
+    fn convert_array<T: Copy + Into<f64>>(conv: &UnitConverter, values: &[T]) -> VOTableValue {
+      VOTableValue::DoubleArray(values.iter().map(|v| conv.convert((*v).into())).collect())
+    }
+    match value {
+      VOTableValue::Null => Ok(VOTableValue::Null),
+      VOTableValue::Byte(v) => Ok(VOTableValue::Double(self.convert(*v as f64))),
+      VOTableValue::Short(v) => Ok(VOTableValue::Double(self.convert(*v as f64))),
+      VOTableValue::Int(v) => Ok(VOTableValue::Double(self.convert(*v as f64))),
+      VOTableValue::Long(v) => Ok(VOTableValue::Double(self.convert(*v as f64))),
+      VOTableValue::Float(v) => Ok(VOTableValue::Double(self.convert(*v as f64))),
+      VOTableValue::Double(v) => Ok(VOTableValue::Double(self.convert(*v))),
+      VOTableValue::ByteArray(v) => Ok(convert_array(self, v)),
+      VOTableValue::ShortArray(v) => Ok(convert_array(self, v)),
+      VOTableValue::IntArray(v) => Ok(convert_array(self, v)),
+      VOTableValue::LongArray(v) => Ok(VOTableValue::DoubleArray(
+        v.iter().map(|v| self.convert(*v as f64)).collect(),
+      )),
+      VOTableValue::FloatArray(v) => Ok(convert_array(self, v)),
+      VOTableValue::DoubleArray(v) => Ok(convert_array(self, v)),
+      _ => Err(VOTableError::Custom(format!(
+        "Unit conversion of a non-numeric value: {:?}",
+        value
+      ))),
+    }
+  }
+}
+
+/// Convert the values of columns of the first table of a VOTable into other units.
+pub struct TableUnitConverter {
+  /// Index of each converted column with its converter.
+  converters: Vec<(usize, UnitConverter)>,
+}
+
+impl TableUnitConverter {
+  /// Prepares the conversions and modifies the VOTable metadata accordingly: the `unit` of the
+  /// converted `FIELD`s is replaced by the target unit, their datatype is set to `double`
+  /// (removing `precision`, `width` and the `VALUES` `null`) and their `VALUES` `MIN`/`MAX`
+  /// are converted.
+  ///
+  /// Since the datatype of the `FIELD`s may change, in streaming mode this must be applied
+  /// on a copy of the VOTable used to read the rows.
+  /// # Params
+  /// * `votable`: the VOTable containing (at least) one table
+  /// * `conversions`: list of (column name, target unit)
+  pub fn new<C: TableDataContent>(
+    votable: &mut VOTable<C>,
+    conversions: &[(&str, &str)],
+  ) -> Result<Self, VOTableError> {
+    let table = votable
+      .get_first_table_mut()
+      .ok_or_else(|| VOTableError::Custom(String::from("No table found in the VOTable")))?;
+    let mut converters: Vec<(usize, UnitConverter)> = Vec::with_capacity(conversions.len());
+    for (colname, to_unit) in conversions {
+      let (index, field) = table
+        .elems
+        .iter_mut()
+        .filter_map(|elem| match elem {
+          TableElem::Field(field) => Some(field),
+          _ => None,
+        })
+        .enumerate()
+        .find(|(_, field)| field.name.as_str() == *colname)
+        .ok_or_else(|| VOTableError::Custom(format!("Column '{}' not found", colname)))?;
+      let converter = field_unit(field).and_then(|from| from.converter_to(&to_unit.parse()?))?;
+      set_field(field, to_unit, &converter);
+      converters.push((index, converter));
+    }
+    Ok(Self { converters })
+  }
+
+  /// Convert the values of the given row.
+  pub fn convert_row(&self, row: &mut [VOTableValue]) -> Result<(), VOTableError> {
+    for (index, converter) in self.converters.iter() {
+      if let Some(value) = row.get_mut(*index) {
+        *value = converter.convert_value(value)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Returns the parsed unit of the given field (dimensionless if the field has no unit).
+fn field_unit(field: &Field) -> Result<Unit, VOTableError> {
+  field
+    .unit
+    .as_deref()
+    .map(|unit| unit.parse())
+    .unwrap_or_else(|| Ok(Unit::dimensionless()))
+    .map_err(|e| VOTableError::Custom(format!("Column '{}': {}", field.name, e)))
+}
+
+/// Set the metadata of a converted field.
+fn set_field(field: &mut Field, to_unit: &str, converter: &UnitConverter) {
+  field.unit = Some(to_unit.to_string());
+  field.datatype = Datatype::Double;
+  field.precision = None;
+  field.width = None;
+  if let Some(values) = &mut field.values {
+    values.null = None;
+    let convert = |s: &str| {
+      s.parse::<f64>()
+        .ok()
+        .map(|v| converter.convert(v).to_string())
+    };
+    values.min = values.min.take().and_then(|mut min| {
+      convert(&min.value).map(|v| {
+        min.value = v;
+        min
+      })
+    });
+    values.max = values.max.take().and_then(|mut max| {
+      convert(&max.value).map(|v| {
+        max.value = v;
+        max
+      })
+    });
+  }
+}
+
+/// A unit which can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnitIssue {
+  /// Name of the `FIELD` or of the `PARAM`
+  pub name: String,
+  pub unit: String,
+  pub message: String,
+}
+
+impl Display for UnitIssue {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(f, "'{}': {}", self.name, self.message)
+  }
+}
+
+/// Returns the list of the `FIELD`s and `PARAM`s having a unit which can't be parsed.
+///
+/// Quoted CDS units describing a representation (e.g. `"h:m:s"` or `"Y:M:D"`) are not reported.
+pub fn check_units<C: TableDataContent>(votable: &VOTable<C>) -> Vec<UnitIssue> {
+  let mut issues = Vec::new();
+  for_each_field(votable, &mut |field| {
+    let parsed = field
+      .unit
+      .as_deref()
+      .filter(|unit| !(unit.starts_with('"') || unit.contains(':')))
+      .map(|unit| (unit, unit.parse::<Unit>()));
+    if let Some((unit, Err(e))) = parsed {
+      issues.push(UnitIssue {
+        name: field.name.clone(),
+        unit: unit.to_string(),
+        message: e.to_string(),
+      });
+    }
+  });
+  issues
+}
+
+/// Known unit: (symbol, factor to SI, dimension, accepts SI prefixes)
+type KnownUnit = (&'static str, f64, Dimension, bool);
+
+const D0: Dimension = Dimension::DIMENSIONLESS;
+const LENGTH: Dimension = Dimension::new([1, 0, 0, 0, 0, 0, 0, 0, 0]);
+const MASS: Dimension = Dimension::new([0, 1, 0, 0, 0, 0, 0, 0, 0]);
+const TIME: Dimension = Dimension::new([0, 0, 1, 0, 0, 0, 0, 0, 0]);
+const ANGLE: Dimension = Dimension::new([0, 0, 0, 0, 0, 0, 0, 1, 0]);
+const ENERGY: Dimension = Dimension::new([2, 1, -2, 0, 0, 0, 0, 0, 0]);
+const POWER: Dimension = Dimension::new([2, 1, -3, 0, 0, 0, 0, 0, 0]);
+
+/// Units without logarithmic scale.
+const UNITS: &[KnownUnit] = &[
+  // SI base units (`g` instead of `kg` for prefixes)
+  ("m", 1.0, LENGTH, true),
+  ("g", 1e-3, MASS, true),
+  ("s", 1.0, TIME, true),
+  ("A", 1.0, Dimension::new([0, 0, 0, 1, 0, 0, 0, 0, 0]), true),
+  ("K", 1.0, Dimension::new([0, 0, 0, 0, 1, 0, 0, 0, 0]), true),
+  (
+    "mol",
+    1.0,
+    Dimension::new([0, 0, 0, 0, 0, 1, 0, 0, 0]),
+    true,
+  ),
+  ("cd", 1.0, Dimension::new([0, 0, 0, 0, 0, 0, 1, 0, 0]), true),
+  ("rad", 1.0, ANGLE, true),
+  ("sr", 1.0, Dimension::new([0, 0, 0, 0, 0, 0, 0, 2, 0]), true),
+  (
+    "bit",
+    1.0,
+    Dimension::new([0, 0, 0, 0, 0, 0, 0, 0, 1]),
+    true,
+  ),
+  (
+    "byte",
+    8.0,
+    Dimension::new([0, 0, 0, 0, 0, 0, 0, 0, 1]),
+    true,
+  ),
+  // SI derived units
+  (
+    "Hz",
+    1.0,
+    Dimension::new([0, 0, -1, 0, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  ("N", 1.0, Dimension::new([1, 1, -2, 0, 0, 0, 0, 0, 0]), true),
+  ("J", 1.0, ENERGY, true),
+  ("W", 1.0, POWER, true),
+  (
+    "Pa",
+    1.0,
+    Dimension::new([-1, 1, -2, 0, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  ("C", 1.0, Dimension::new([0, 0, 1, 1, 0, 0, 0, 0, 0]), true),
+  (
+    "V",
+    1.0,
+    Dimension::new([2, 1, -3, -1, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "Ohm",
+    1.0,
+    Dimension::new([2, 1, -3, -2, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "ohm",
+    1.0,
+    Dimension::new([2, 1, -3, -2, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "S",
+    1.0,
+    Dimension::new([-2, -1, 3, 2, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "F",
+    1.0,
+    Dimension::new([-2, -1, 4, 2, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "Wb",
+    1.0,
+    Dimension::new([2, 1, -2, -1, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "T",
+    1.0,
+    Dimension::new([0, 1, -2, -1, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "H",
+    1.0,
+    Dimension::new([2, 1, -2, -2, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  ("lm", 1.0, Dimension::new([0, 0, 0, 0, 0, 0, 1, 2, 0]), true),
+  (
+    "lx",
+    1.0,
+    Dimension::new([-2, 0, 0, 0, 0, 0, 1, 2, 0]),
+    true,
+  ),
+  (
+    "Bq",
+    1.0,
+    Dimension::new([0, 0, -1, 0, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "Gy",
+    1.0,
+    Dimension::new([2, 0, -2, 0, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "Sv",
+    1.0,
+    Dimension::new([2, 0, -2, 0, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  // Angles
+  ("deg", PI / 180.0, ANGLE, false),
+  ("arcmin", PI / 10800.0, ANGLE, true),
+  ("arcsec", ARCSEC, ANGLE, true),
+  ("mas", 1e-3 * ARCSEC, ANGLE, false),
+  ("uas", 1e-6 * ARCSEC, ANGLE, false),
+  ("'", PI / 10800.0, ANGLE, false),
+  ("\"", ARCSEC, ANGLE, false),
+  // Time
+  ("min", 60.0, TIME, false),
+  ("h", 3600.0, TIME, false),
+  ("d", 86400.0, TIME, false),
+  ("a", JULIAN_YEAR, TIME, true),
+  ("yr", JULIAN_YEAR, TIME, true),
+  ("cy", 100.0 * JULIAN_YEAR, TIME, false),
+  // Length
+  ("AU", 1.495978707e11, LENGTH, false),
+  ("au", 1.495978707e11, LENGTH, false),
+  ("pc", 3.085_677_581_491_367e16, LENGTH, true),
+  ("lyr", 9.4607304725808e15, LENGTH, true),
+  ("Angstrom", 1e-10, LENGTH, false),
+  ("angstrom", 1e-10, LENGTH, false),
+  ("\u{c5}", 1e-10, LENGTH, false),
+  ("solRad", 6.957e8, LENGTH, false),
+  ("Rsun", 6.957e8, LENGTH, false),
+  ("Rgeo", 6.3781e6, LENGTH, false),
+  ("Rearth", 6.3781e6, LENGTH, false),
+  ("Rjup", 7.1492e7, LENGTH, false),
+  (
+    "barn",
+    1e-28,
+    Dimension::new([2, 0, 0, 0, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  // Mass
+  ("u", 1.66053906660e-27, MASS, false),
+  ("solMass", 1.98840987e30, MASS, false),
+  ("Msun", 1.98840987e30, MASS, false),
+  ("geoMass", 5.9722e24, MASS, false),
+  ("Mgeo", 5.9722e24, MASS, false),
+  ("Mearth", 5.9722e24, MASS, false),
+  ("jovMass", 1.89813e27, MASS, false),
+  ("Mjup", 1.89813e27, MASS, false),
+  // Energy, power, ...
+  ("eV", 1.602176634e-19, ENERGY, true),
+  ("erg", 1e-7, ENERGY, false),
+  ("Ry", 2.1798723611035e-18, ENERGY, true),
+  ("solLum", 3.828e26, POWER, false),
+  ("Lsun", 3.828e26, POWER, false),
+  (
+    "Jy",
+    1e-26,
+    Dimension::new([0, 1, -2, 0, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "G",
+    1e-4,
+    Dimension::new([0, 1, -2, -1, 0, 0, 0, 0, 0]),
+    true,
+  ),
+  (
+    "dyn",
+    1e-5,
+    Dimension::new([1, 1, -2, 0, 0, 0, 0, 0, 0]),
+    false,
+  ),
+  (
+    "Ba",
+    0.1,
+    Dimension::new([-1, 1, -2, 0, 0, 0, 0, 0, 0]),
+    false,
+  ),
+  (
+    "D",
+    3.33564e-30,
+    Dimension::new([1, 0, 1, 1, 0, 0, 0, 0, 0]),
+    false,
+  ),
+  (
+    "R",
+    1e10 / (4.0 * PI),
+    Dimension::new([-2, 0, -1, 0, 0, 0, 0, -2, 0]),
+    true,
+  ),
+  ("l", 1e-3, Dimension::new([3, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+  ("L", 1e-3, Dimension::new([3, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+  // Dimensionless
+  ("%", 1e-2, D0, false),
+  ("ct", 1.0, D0, true),
+  ("count", 1.0, D0, true),
+  ("ph", 1.0, D0, true),
+  ("photon", 1.0, D0, true),
+  ("pix", 1.0, D0, true),
+  ("pixel", 1.0, D0, true),
+  ("voxel", 1.0, D0, true),
+  ("beam", 1.0, D0, false),
+  ("chan", 1.0, D0, false),
+  ("bin", 1.0, D0, false),
+  ("adu", 1.0, D0, false),
+  ("electron", 1.0, D0, false),
+  ("e-", 1.0, D0, false),
+];
+
+/// SI prefixes (the 2 letters prefix `da` first).
+const PREFIXES: &[(&str, f64)] = &[
+  ("da", 1e1),
+  ("y", 1e-24),
+  ("z", 1e-21),
+  ("a", 1e-18),
+  ("f", 1e-15),
+  ("p", 1e-12),
+  ("n", 1e-9),
+  ("u", 1e-6),
+  ("\u{b5}", 1e-6),
+  ("m", 1e-3),
+  ("c", 1e-2),
+  ("d", 1e-1),
+  ("h", 1e2),
+  ("k", 1e3),
+  ("M", 1e6),
+  ("G", 1e9),
+  ("T", 1e12),
+  ("P", 1e15),
+  ("E", 1e18),
+  ("Z", 1e21),
+  ("Y", 1e24),
+];
+
+/// Look for the given symbol, possibly with a SI prefix.
+fn lookup(symbol: &str) -> Option<Unit> {
+  fn log_unit(scale: LogScale, k: f64) -> Unit {
+    Unit {
+      factor: 1.0,
+      dimension: Dimension::DIMENSIONLESS,
+      log: Some((scale, k)),
+    }
+  }
+  let find = |symbol: &str, prefixed: bool| -> Option<Unit> {
+    match symbol {
+      "mag" => Some(log_unit(LogScale::Mag, 1.0)),
+      "dex" if !prefixed => Some(log_unit(LogScale::Dex, 1.0)),
+      _ => UNITS
+        .iter()
+        .find(|(s, _, _, prefixable)| *s == symbol && (*prefixable || !prefixed))
+        .map(|(_, factor, dimension, _)| Unit {
+          factor: *factor,
+          dimension: *dimension,
+          log: None,
+        }),
+    }
+  };
+  find(symbol, false).or_else(|| {
+    PREFIXES.iter().find_map(|(prefix, prefix_factor)| {
+      symbol
+        .strip_prefix(prefix)
+        .filter(|s| !s.is_empty())
+        .and_then(|s| find(s, true))
+        .map(|mut unit| {
+          match &mut unit.log {
+            Some((_, k)) => *k *= prefix_factor,
+            None => unit.factor *= prefix_factor,
+          }
+          unit
+        })
+    })
+  })
+}
+
+fn is_symbol_char(c: char) -> bool {
+  c.is_alphabetic() || c == '%' || c == '\'' || c == '"' || c == '_'
+}
+
+/// Recursive descent parser.
+struct Parser<'a> {
+  chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+  /// Returns the `n`-th next character (0 being the next one) without consuming it.
+  fn lookahead(&self, n: usize) -> Option<char> {
+    self.chars.clone().nth(n)
+  }
+
+  fn skip_spaces(&mut self) {
+    while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+  }
+
+  /// `product := factor (('.' | '*' | ' ') factor | '/' factor)*`
+  fn product(&mut self) -> Result<Unit, VOTableError> {
+    self.skip_spaces();
+    let mut unit = self.factor()?;
+    loop {
+      let had_space = self.chars.peek().is_some_and(|c| c.is_whitespace());
+      self.skip_spaces();
+      match self.chars.peek() {
+        Some('.') | Some('*') => {
+          self.chars.next();
+          self.skip_spaces();
+          unit = unit.mul(self.factor()?)?;
+        }
+        Some('/') => {
+          self.chars.next();
+          self.skip_spaces();
+          unit = unit.mul(self.factor()?.powf(-1.0)?)?;
+        }
+        Some(c) if had_space && *c != ')' && *c != ']' => {
+          unit = unit.mul(self.factor()?)?;
+        }
+        _ => return Ok(unit),
+      }
+    }
+  }
+
+  /// `factor := primary [('**' | '^') exponent | attached_exponent]`
+  fn factor(&mut self) -> Result<Unit, VOTableError> {
+    let (unit, is_symbol) = self.primary()?;
+    match self.lookahead(0) {
+      Some('*') if self.lookahead(1) == Some('*') => {
+        self.chars.nth(1);
+        self.exponent().and_then(|e| unit.powf(e))
+      }
+      Some('^') => {
+        self.chars.next();
+        self.exponent().and_then(|e| unit.powf(e))
+      }
+      // CDS attached exponent, e.g. `m2` or `s-1`
+      Some(c) if is_symbol && (c.is_ascii_digit() || c == '+' || c == '-') => {
+        self.signed_integer().and_then(|e| unit.powf(e as f64))
+      }
+      _ => Ok(unit),
+    }
+  }
+
+  /// `primary := number | symbol | function '(' product ')' | '(' product ')' | '[' product ']'`
+  /// Returns the unit and a flag telling if it is a unit symbol.
+  fn primary(&mut self) -> Result<(Unit, bool), VOTableError> {
+    match self.chars.peek().copied() {
+      Some('(') => {
+        self.chars.next();
+        let unit = self.product()?;
+        self.expect(')').map(|()| (unit, false))
+      }
+      Some('[') => {
+        self.chars.next();
+        // CDS `[-]`: logarithm of a dimensionless value
+        let unit = if self.chars.next_if_eq(&'-').is_some() {
+          while self.chars.next_if_eq(&'-').is_some() {}
+          Unit::dimensionless()
+        } else {
+          self.product()?
+        };
+        self
+          .expect(']')
+          .and_then(|()| unit.log10())
+          .map(|unit| (unit, false))
+      }
+      Some(c) if c.is_ascii_digit() => {
+        let unit = Unit {
+          factor: self.scale_factor()?,
+          ..Unit::dimensionless()
+        };
+        // Scale factor directly followed by a unit, e.g. `10+3m` or `0.1nm`
+        match self.chars.peek() {
+          Some(c) if is_symbol_char(*c) || *c == '(' || *c == '[' => {
+            self.factor().and_then(|u| unit.mul(u)).map(|u| (u, false))
+          }
+          _ => Ok((unit, false)),
+        }
+      }
+      Some(c) if is_symbol_char(c) => {
+        let mut symbol = String::new();
+        while let Some(c) = self.chars.next_if(|c| is_symbol_char(*c)) {
+          symbol.push(c);
+        }
+        // Electrons, e.g. `e-/s` (Gaia)
+        if symbol == "e"
+          && self.lookahead(0) == Some('-')
+          && !self.lookahead(1).is_some_and(|c| c.is_ascii_digit())
+        {
+          self.chars.next();
+          symbol.push('-');
+        }
+        match (symbol.as_str(), self.chars.peek()) {
+          ("log", Some('(')) => self.primary().and_then(|(unit, _)| unit.log10()),
+          ("sqrt", Some('(')) => self.primary().and_then(|(unit, _)| unit.powf(0.5)),
+          _ => lookup(&symbol)
+            .ok_or_else(|| VOTableError::Custom(format!("Unknown unit '{}'", symbol))),
+        }
+        .map(|unit| (unit, true))
+      }
+      Some(c) => Err(VOTableError::Custom(format!(
+        "Unexpected character '{}'",
+        c
+      ))),
+      None => Err(VOTableError::Custom(String::from("Unexpected end of unit"))),
+    }
+  }
+
+  /// Scale factor: `1.5`, `1e-3`, `10+3` or `1.5x10+3` (CDS), possibly followed by a power.
+  fn scale_factor(&mut self) -> Result<f64, VOTableError> {
+    let mut number = String::new();
+    loop {
+      match self.lookahead(0) {
+        Some(c) if c.is_ascii_digit() => number.push(c),
+        // Do not consume a '.' being a product operator
+        Some('.') if self.lookahead(1).is_some_and(|c| c.is_ascii_digit()) => number.push('.'),
+        _ => break,
+      }
+      self.chars.next();
+    }
+    // Exponent, not to be confused with a unit starting with 'e' or 'E' (e.g. `1eV`)
+    let is_exp = match (self.lookahead(1), self.lookahead(2)) {
+      (Some(c), _) if c.is_ascii_digit() => true,
+      (Some('+' | '-'), Some(c)) => c.is_ascii_digit(),
+      _ => false,
+    };
+    if let Some(e) = self.chars.next_if(|c| is_exp && (*c == 'e' || *c == 'E')) {
+      number.push(e);
+      if let Some(sign) = self.chars.next_if(|c| *c == '+' || *c == '-') {
+        number.push(sign);
+      }
+      while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit()) {
+        number.push(c);
+      }
+    }
+    let mut value: f64 = number
+      .parse()
+      .map_err(|_| VOTableError::Custom(format!("Wrong number '{}'", number)))?;
+    // CDS: `1.5x10+3`
+    if self.chars.peek() == Some(&'x')
+      && self.lookahead(1) == Some('1')
+      && self.lookahead(2) == Some('0')
+    {
+      self.chars.nth(2);
+      value *= 10_f64.powi(self.signed_integer()?);
+    } else if number == "10" && matches!(self.chars.peek(), Some('+') | Some('-')) {
+      // CDS: `10+3`, `10-3`
+      value = 10_f64.powi(self.signed_integer()?);
+    }
+    Ok(value)
+  }
+
+  /// `exponent := signed_number | '(' signed_number ['/' number] ')'`
+  fn exponent(&mut self) -> Result<f64, VOTableError> {
+    if self.chars.next_if_eq(&'(').is_some() {
+      let num = self.signed_number()?;
+      let res = if self.chars.next_if_eq(&'/').is_some() {
+        num / self.signed_number()?
+      } else {
+        num
+      };
+      self.expect(')').map(|()| res)
+    } else {
+      self.signed_number()
+    }
+  }
+
+  fn signed_number(&mut self) -> Result<f64, VOTableError> {
+    let mut number = String::new();
+    if let Some(sign) = self.chars.next_if(|c| *c == '+' || *c == '-') {
+      number.push(sign);
+    }
+    loop {
+      match self.lookahead(0) {
+        Some(c) if c.is_ascii_digit() => number.push(c),
+        // Do not consume a '.' being a product operator
+        Some('.') if self.lookahead(1).is_some_and(|c| c.is_ascii_digit()) => number.push('.'),
+        _ => break,
+      }
+      self.chars.next();
+    }
+    number
+      .parse()
+      .map_err(|_| VOTableError::Custom(format!("Wrong exponent '{}'", number)))
+  }
+
+  fn signed_integer(&mut self) -> Result<i32, VOTableError> {
+    let mut number = String::new();
+    if let Some(sign) = self.chars.next_if(|c| *c == '+' || *c == '-') {
+      number.push(sign);
+    }
+    while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit()) {
+      number.push(c);
+    }
+    number
+      .parse()
+      .map_err(|_| VOTableError::Custom(format!("Wrong exponent '{}'", number)))
+  }
+
+  fn expect(&mut self, expected: char) -> Result<(), VOTableError> {
+    self.skip_spaces();
+    match self.chars.next() {
+      Some(c) if c == expected => Ok(()),
+      Some(c) => Err(VOTableError::Custom(format!(
+        "Unexpected character. Actual: '{}'. Expected: '{}'.",
+        c, expected
+      ))),
+      None => Err(VOTableError::Custom(format!(
+        "Unexpected end of unit. Expected: '{}'.",
+        expected
+      ))),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    impls::mem::InMemTableDataRows, iter::SimpleVOTableRowIterator, votable::VOTableWrapper,
+  };
+
+  fn unit(s: &str) -> Unit {
+    s.parse().unwrap()
+  }
+
+  fn assert_factor(from: &str, to: &str, expected: f64) {
+    let factor = unit(from).scale_factor_to(&unit(to)).unwrap();
+    assert!(
+      ((factor - expected) / expected).abs() < 1e-12,
+      "{} -> {}: {} != {}",
+      from,
+      to,
+      factor,
+      expected
+    );
+  }
+
+  #[test]
+  fn test_parse_and_convert() {
+    // VOUnits, CDS and OGIP syntaxes
+    assert_factor("mas/yr", "deg/yr", 1.0 / 3.6e6);
+    assert_factor("mas.yr-1", "deg.a**-1", 1.0 / 3.6e6);
+    assert_factor("km.s-1", "m/s", 1e3);
+    assert_factor("km s**(-1)", "m*s^-1", 1e3);
+    assert_factor("erg/s/cm2/Angstrom", "W.m**-2.nm**-1", 1e-7 * 1e4 * 10.0);
+    assert_factor("10+3m", "km", 1.0);
+    assert_factor("1.5x10+3m", "km", 1.5);
+    assert_factor("1e-3Jy", "mJy", 1.0);
+    assert_factor("0.1nm", "Angstrom", 1.0);
+    assert_factor("Mpc", "pc", 1e6);
+    assert_factor("kpc", "lyr", 3.085_677_581_491_367e19 / 9.4607304725808e15);
+    assert_factor("%", "", 0.01);
+    assert_factor("---", "", 1.0);
+    assert_factor("m**(1/2)", "cm**0.5", 10.0);
+    assert_factor("(km/s)/Mpc", "s-1", 1e3 / 3.085_677_581_491_367e22);
+    assert!(unit("ct/s").is_equivalent(&unit("Hz")));
+    assert!(unit("e-/s").is_equivalent(&unit("s-1")));
+    assert!(!unit("mas").is_equivalent(&unit("m")));
+    assert!(unit("mas").scale_factor_to(&unit("s")).is_err());
+    for wrong in ["km/", "foo", "m**", "[Msun", "mag.mag", "m)"] {
+      assert!(wrong.parse::<Unit>().is_err(), "{}", wrong);
+    }
+    // Logarithmic units
+    let conv = unit("[Msun]").converter_to(&unit("Msun")).unwrap();
+    assert!((conv.convert(2.0) - 100.0).abs() < 1e-9);
+    let conv = unit("log(cm.s-2)").converter_to(&unit("[m/s2]")).unwrap();
+    assert!((conv.convert(4.44) - 2.44).abs() < 1e-9);
+    let conv = unit("mmag").converter_to(&unit("mag")).unwrap();
+    assert!((conv.convert(150.0) - 0.15).abs() < 1e-12);
+    assert!(unit("mag").converter_to(&unit("Jy")).is_err());
+    let conv = unit("mag").mag_converter_to(&unit("mJy"), 3631e3).unwrap();
+    assert!((conv.convert(0.0) - 3631e3).abs() < 1e-6);
+    assert!((conv.convert(5.0) - 36310.0).abs() < 1e-6);
+    let conv = unit("Jy").mag_converter_to(&unit("mag"), 3631.0).unwrap();
+    assert!((conv.convert(36.31) - 5.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_table_unit_converter() {
+    let mut it = SimpleVOTableRowIterator::from_file("resources/sdss12.vot").unwrap();
+    let mut votable = it.votable.clone();
+    let converter = TableUnitConverter::new(&mut votable, &[("RA_ICRS", "rad")]).unwrap();
+    let table = votable.get_first_table().unwrap();
+    let field = match &table.elems[0] {
+      TableElem::Field(field) => field,
+      _ => unreachable!(),
+    };
+    assert_eq!(field.name, "RA_ICRS");
+    assert_eq!(field.unit.as_deref(), Some("rad"));
+    assert_eq!(field.datatype, Datatype::Double);
+    let mut row = it.to_row_value_iter().next().unwrap().unwrap();
+    let ra = row[0].as_f64().unwrap();
+    converter.convert_row(&mut row).unwrap();
+    assert!((row[0].as_f64().unwrap() - ra.to_radians()).abs() < 1e-15);
+
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_file("resources/sdss12.vot")
+      .unwrap()
+      .unwrap();
+    assert_eq!(check_units(&votable), vec![]);
+  }
+}
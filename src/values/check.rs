@@ -0,0 +1,430 @@
+//! Check the rows of a table against the constraints of the `VALUES` of its `FIELD`s, i.e.
+//! the `MIN`/`MAX` bounds and, for `type="legal"`, the list of `OPTION` values.
+//!
+//! Bounds apply to numeric scalar values and to strings (lexicographic order); `NULL` values
+//! are never considered as violations.
+
+use std::{
+  cmp::Ordering,
+  fmt::{self, Display, Formatter},
+};
+
+use super::Values;
+use crate::{
+  error::VOTableError,
+  impls::{Schema, TableSchema, VOTableValue},
+  table::TableElem,
+};
+
+/// What to do with a cell violating a constraint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnViolation {
+  /// Only report the violation
+  Report,
+  /// Report the violation and replace the value by `NULL`
+  SetNull,
+  /// Report the violation and drop the row
+  DropRow,
+}
+
+/// The constraint a cell is violating.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ViolationKind {
+  /// Value lower than (or equal to, if not inclusive) the `MIN` value
+  BelowMin { min: String, inclusive: bool },
+  /// Value greater than (or equal to, if not inclusive) the `MAX` value
+  AboveMax { max: String, inclusive: bool },
+  /// Value not among the legal `OPTION` values
+  NotLegal,
+}
+
+/// A cell violating a constraint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+  /// Index of the column
+  pub col: usize,
+  /// Name of the column
+  pub name: String,
+  /// The faulty value
+  pub value: VOTableValue,
+  pub kind: ViolationKind,
+}
+
+impl Display for Violation {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match &self.kind {
+      ViolationKind::BelowMin { min, inclusive } => write!(
+        f,
+        "Column '{}': value '{}' {} MIN '{}'",
+        self.name,
+        self.value,
+        if *inclusive { "<" } else { "<=" },
+        min
+      ),
+      ViolationKind::AboveMax { max, inclusive } => write!(
+        f,
+        "Column '{}': value '{}' {} MAX '{}'",
+        self.name,
+        self.value,
+        if *inclusive { ">" } else { ">=" },
+        max
+      ),
+      ViolationKind::NotLegal => write!(
+        f,
+        "Column '{}': value '{}' not among the legal OPTION values",
+        self.name, self.value
+      ),
+    }
+  }
+}
+
+/// A `MIN` or `MAX` bound.
+#[derive(Clone, Debug)]
+struct Bound {
+  /// Value as given in the VOTable
+  raw: String,
+  value: VOTableValue,
+  inclusive: bool,
+}
+
+/// Constraints on the values of a column.
+#[derive(Clone, Debug)]
+struct ColumnConstraints {
+  name: String,
+  min: Option<Bound>,
+  max: Option<Bound>,
+  /// The `OPTION` values are the only legal values (`type="legal"`)
+  legal: bool,
+  /// The `OPTION` values with their names
+  enumeration: Vec<(VOTableValue, Option<String>)>,
+}
+
+/// Checker of the rows of a table against the `VALUES` constraints of its `FIELD`s.
+pub struct ValuesChecker {
+  columns: Vec<Option<ColumnConstraints>>,
+  on_violation: OnViolation,
+}
+
+impl ValuesChecker {
+  /// # Params
+  /// * `schema`: the schema of the table
+  /// * `names`: the name of each column
+  /// * `values`: the `VALUES` of each column (if any)
+  /// * `on_violation`: what to do with cells violating a constraint
+  pub fn new(
+    schema: &TableSchema,
+    names: &[&str],
+    values: &[Option<&Values>],
+    on_violation: OnViolation,
+  ) -> Result<Self, VOTableError> {
+    if schema.as_slice().len() != values.len() || names.len() != values.len() {
+      return Err(VOTableError::Custom(format!(
+        "Wrong number of VALUES or names. Expected: {}. Actual: {} and {}.",
+        schema.as_slice().len(),
+        values.len(),
+        names.len()
+      )));
+    }
+    let columns = schema
+      .iter()
+      .zip(names)
+      .zip(values)
+      .map(|((schema, name), values)| {
+        values
+          .map(|values| ColumnConstraints::new(schema, name, values))
+          .transpose()
+      })
+      .collect::<Result<Vec<_>, VOTableError>>()?;
+    Ok(Self {
+      columns,
+      on_violation,
+    })
+  }
+
+  /// Build a checker from the `FIELD`s of a table.
+  pub fn from_table_elems(
+    elems: &[TableElem],
+    on_violation: OnViolation,
+  ) -> Result<Self, VOTableError> {
+    let fields: Vec<_> = elems
+      .iter()
+      .filter_map(|elem| match elem {
+        TableElem::Field(field) => Some(field),
+        _ => None,
+      })
+      .collect();
+    let names: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+    let values: Vec<Option<&Values>> = fields.iter().map(|field| field.values.as_ref()).collect();
+    Self::new(&TableSchema::from(elems), &names, &values, on_violation)
+  }
+
+  pub fn on_violation(&self) -> OnViolation {
+    self.on_violation
+  }
+
+  /// Check the given row, pushing the violations found in `violations`.
+  /// With `OnViolation::SetNull`, the faulty values are replaced by `NULL`.
+  /// Returns `false` if the row has to be dropped, i.e. if a violation has been found with
+  /// `OnViolation::DropRow`.
+  pub fn check_row(&self, row: &mut [VOTableValue], violations: &mut Vec<Violation>) -> bool {
+    let mut keep = true;
+    for (col, (constraints, value)) in self.columns.iter().zip(row.iter_mut()).enumerate() {
+      let violation = constraints
+        .as_ref()
+        .and_then(|constraints| constraints.check(value).map(|kind| (constraints, kind)));
+      if let Some((constraints, kind)) = violation {
+        violations.push(Violation {
+          col,
+          name: constraints.name.clone(),
+          value: value.clone(),
+          kind,
+        });
+        match self.on_violation {
+          OnViolation::Report => {}
+          OnViolation::SetNull => *value = VOTableValue::Null,
+          OnViolation::DropRow => keep = false,
+        }
+      }
+    }
+    keep
+  }
+
+  /// Returns the name of the `OPTION` corresponding to the given value of the given column,
+  /// if any.
+  pub fn option_name(&self, col: usize, value: &VOTableValue) -> Option<&str> {
+    self
+      .columns
+      .get(col)
+      .and_then(Option::as_ref)
+      .and_then(|constraints| {
+        constraints
+          .enumeration
+          .iter()
+          .find(|(v, _)| equals(v, value))
+          .and_then(|(_, name)| name.as_deref())
+      })
+  }
+
+  /// Returns the value of the `OPTION` of the given name of the given column, if any.
+  pub fn option_value(&self, col: usize, name: &str) -> Option<&VOTableValue> {
+    self
+      .columns
+      .get(col)
+      .and_then(Option::as_ref)
+      .and_then(|constraints| {
+        constraints
+          .enumeration
+          .iter()
+          .find(|(_, n)| n.as_deref() == Some(name))
+          .map(|(value, _)| value)
+      })
+  }
+}
+
+impl ColumnConstraints {
+  fn new(schema: &Schema, name: &str, values: &Values) -> Result<Self, VOTableError> {
+    let parse = |s: &str| {
+      schema.value_from_str(s).map_err(|e| {
+        VOTableError::Custom(format!(
+          "Unable to parse VALUES '{}' of column '{}': {}",
+          s, name, e
+        ))
+      })
+    };
+    let min = values
+      .min
+      .as_ref()
+      .map(|min| {
+        parse(&min.value).map(|value| Bound {
+          raw: min.value.clone(),
+          value,
+          inclusive: min.inclusive,
+        })
+      })
+      .transpose()?;
+    let max = values
+      .max
+      .as_ref()
+      .map(|max| {
+        parse(&max.value).map(|value| Bound {
+          raw: max.value.clone(),
+          value,
+          inclusive: max.inclusive,
+        })
+      })
+      .transpose()?;
+    let enumeration = values
+      .enumeration()
+      .into_iter()
+      .map(|(value, name)| parse(value).map(|value| (value, name.map(String::from))))
+      .collect::<Result<Vec<_>, VOTableError>>()?;
+    let legal = values.is_legal() && !enumeration.is_empty();
+    Ok(Self {
+      name: name.to_string(),
+      min,
+      max,
+      legal,
+      enumeration,
+    })
+  }
+
+  fn check(&self, value: &VOTableValue) -> Option<ViolationKind> {
+    if matches!(value, VOTableValue::Null) {
+      return None;
+    }
+    if let Some(min) = &self.min {
+      match compare(value, &min.value) {
+        Some(Ordering::Less) => {
+          return Some(ViolationKind::BelowMin {
+            min: min.raw.clone(),
+            inclusive: min.inclusive,
+          });
+        }
+        Some(Ordering::Equal) if !min.inclusive => {
+          return Some(ViolationKind::BelowMin {
+            min: min.raw.clone(),
+            inclusive: min.inclusive,
+          });
+        }
+        _ => {}
+      }
+    }
+    if let Some(max) = &self.max {
+      match compare(value, &max.value) {
+        Some(Ordering::Greater) => {
+          return Some(ViolationKind::AboveMax {
+            max: max.raw.clone(),
+            inclusive: max.inclusive,
+          });
+        }
+        Some(Ordering::Equal) if !max.inclusive => {
+          return Some(ViolationKind::AboveMax {
+            max: max.raw.clone(),
+            inclusive: max.inclusive,
+          });
+        }
+        _ => {}
+      }
+    }
+    if self.legal && !self.enumeration.iter().any(|(v, _)| equals(v, value)) {
+      Some(ViolationKind::NotLegal)
+    } else {
+      None
+    }
+  }
+}
+
+/// Compare numeric scalar values or strings, returns `None` for other values (or NaN).
+fn compare(a: &VOTableValue, b: &VOTableValue) -> Option<Ordering> {
+  match (a.as_f64(), b.as_f64()) {
+    (Some(a), Some(b)) => a.partial_cmp(&b),
+    _ => match (a, b) {
+      (
+        VOTableValue::String(_) | VOTableValue::CharASCII(_) | VOTableValue::CharUnicode(_),
+        VOTableValue::String(_) | VOTableValue::CharASCII(_) | VOTableValue::CharUnicode(_),
+      ) => Some(a.to_string().cmp(&b.to_string())),
+      _ => None,
+    },
+  }
+}
+
+fn equals(a: &VOTableValue, b: &VOTableValue) -> bool {
+  match (a.as_f64(), b.as_f64()) {
+    (Some(a), Some(b)) => a == b,
+    _ => a == b || a.to_string() == b.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    datatype::Datatype,
+    field::{ArraySize, Field},
+    values::{Max, Min, Opt},
+  };
+
+  #[test]
+  fn test_values_checker() {
+    let elems = vec![
+      TableElem::Field(
+        Field::new("ra", Datatype::Double).set_values(
+          Values::new()
+            .set_min(Min::new("0"))
+            .set_max(Max::new("360").set_inclusive(false)),
+        ),
+      ),
+      TableElem::Field(
+        Field::new("flag", Datatype::ShortInt).set_values(
+          Values::new()
+            .set_type("legal")
+            .push_opt(Opt::new("0").set_name("ok"))
+            .push_opt(
+              Opt::new("1")
+                .set_name("bad")
+                .push_opt(Opt::new("2").set_name("worse")),
+            ),
+        ),
+      ),
+      TableElem::Field(
+        Field::new("band", Datatype::CharASCII)
+          .set_arraysize(ArraySize::new_variable_1d())
+          .set_values(Values::new().set_min(Min::new("B")).set_max(Max::new("V"))),
+      ),
+    ];
+    let row = || {
+      vec![
+        VOTableValue::Double(360.0),
+        VOTableValue::Short(2),
+        VOTableValue::String(String::from("A")),
+      ]
+    };
+    // Report
+    let checker = ValuesChecker::from_table_elems(&elems, OnViolation::Report).unwrap();
+    let mut violations = Vec::new();
+    let mut r = row();
+    assert!(checker.check_row(&mut r, &mut violations));
+    assert_eq!(r, row());
+    assert_eq!(violations.len(), 2);
+    assert_eq!(
+      violations[0].kind,
+      ViolationKind::AboveMax {
+        max: String::from("360"),
+        inclusive: false
+      }
+    );
+    assert_eq!(violations[1].name, "band");
+    assert_eq!(
+      violations[1].to_string(),
+      "Column 'band': value 'A' < MIN 'B'"
+    );
+    // Legal values
+    violations.clear();
+    let mut r = vec![
+      VOTableValue::Double(0.0),
+      VOTableValue::Short(3),
+      VOTableValue::Null,
+    ];
+    assert!(checker.check_row(&mut r, &mut violations));
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].kind, ViolationKind::NotLegal);
+    // Set NULL and drop
+    let checker = ValuesChecker::from_table_elems(&elems, OnViolation::SetNull).unwrap();
+    let mut r = row();
+    assert!(checker.check_row(&mut r, &mut violations));
+    assert_eq!(r[0], VOTableValue::Null);
+    assert_eq!(r[1], VOTableValue::Short(2));
+    let checker = ValuesChecker::from_table_elems(&elems, OnViolation::DropRow).unwrap();
+    assert!(!checker.check_row(&mut row(), &mut violations));
+    // Enumeration
+    assert_eq!(
+      checker.option_name(1, &VOTableValue::Short(2)),
+      Some("worse")
+    );
+    assert_eq!(checker.option_name(0, &VOTableValue::Double(0.0)), None);
+    assert_eq!(
+      checker.option_value(1, "bad"),
+      Some(&VOTableValue::Short(1))
+    );
+  }
+}
@@ -16,6 +16,9 @@ use super::{
   VOTableVisitor,
 };
 
+pub mod check;
+pub mod resolve;
+
 /// Struct corresponding to the `MIN` XML tag.
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Min {
@@ -338,6 +341,41 @@ impl Values {
 
   impl_builder_push!(Opt);
 
+  /// Tells whether the `OPTION`s are the only legal values (`type="legal"`), and not only the
+  /// values actually present in the column.
+  pub fn is_legal(&self) -> bool {
+    self.type_.as_deref() == Some("legal")
+  }
+
+  /// Returns the `(value, name)` pairs of all `OPTION`s, including nested ones (depth-first).
+  pub fn enumeration(&self) -> Vec<(&str, Option<&str>)> {
+    fn push_opts<'a>(opts: &'a [Opt], res: &mut Vec<(&'a str, Option<&'a str>)>) {
+      for opt in opts {
+        res.push((opt.value.as_str(), opt.name.as_deref()));
+        push_opts(&opt.opts, res);
+      }
+    }
+    let mut res = Vec::new();
+    push_opts(&self.opts, &mut res);
+    res
+  }
+
+  /// Returns the name of the `OPTION` having the given value, if any.
+  pub fn name_of(&self, value: &str) -> Option<&str> {
+    self
+      .enumeration()
+      .into_iter()
+      .find_map(|(v, name)| if v == value { name } else { None })
+  }
+
+  /// Returns the value of the `OPTION` having the given name, if any.
+  pub fn value_of(&self, name: &str) -> Option<&str> {
+    self
+      .enumeration()
+      .into_iter()
+      .find_map(|(value, n)| if n == Some(name) { Some(value) } else { None })
+  }
+
   pub fn visit<C, V>(&mut self, visitor: &mut V) -> Result<(), V::E>
   where
     C: TableDataContent,
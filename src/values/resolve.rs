@@ -0,0 +1,383 @@
+//! Resolution of the `ref` attribute of `VALUES`, i.e. of `VALUES` re-using the content of
+//! another `VALUES` (identified by its `ID`).
+//!
+//! The attributes and sub-elements explicitly set in a referencing `VALUES` take precedence over
+//! the ones of the referenced `VALUES`.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use super::Values;
+use crate::{
+  error::VOTableError,
+  field::Field,
+  table::TableElem,
+  votable::VOTable,
+  walk::{for_each_field, walk_fields_and_params, walk_votable, FieldVisitor, Meta},
+  TableDataContent,
+};
+
+/// Maximum number of successive references followed before reporting a cycle.
+const MAX_REF_DEPTH: usize = 32;
+
+/// The `VALUES` having an `ID`, indexed by `ID`.
+#[derive(Default, Debug)]
+pub struct ValuesRegistry {
+  by_id: HashMap<String, Values>,
+}
+
+impl ValuesRegistry {
+  /// Registers the `VALUES` of the `FIELD`s and `PARAM`s of the given table elements.
+  pub fn from_table_elems(elems: &[TableElem]) -> Self {
+    let mut registry = Self::default();
+    walk_fields_and_params(elems, &mut |field| registry.push_field(field));
+    registry
+  }
+
+  /// Registers the `VALUES` of all the `FIELD`s and `PARAM`s of the given VOTable.
+  pub fn from_votable<C: TableDataContent>(votable: &VOTable<C>) -> Self {
+    let mut registry = Self::default();
+    for_each_field(votable, &mut |field| registry.push_field(field));
+    registry
+  }
+
+  fn push_field(&mut self, field: &Field) {
+    if let Some((id, values)) = field
+      .values
+      .as_ref()
+      .and_then(|values| values.id.as_ref().map(|id| (id, values)))
+    {
+      self.by_id.insert(id.clone(), values.clone());
+    }
+  }
+
+  /// Returns the `VALUES` having the given `ID`, if any.
+  pub fn get(&self, id: &str) -> Option<&Values> {
+    self.by_id.get(id)
+  }
+
+  /// Returns the given `VALUES` completed by the (possibly chained) `VALUES` it references.
+  /// The returned `VALUES` has no more `ref`.
+  /// # Errors
+  /// If a referenced `VALUES` is not found, or in case of a reference cycle.
+  pub fn resolve<'a>(&self, values: &'a Values) -> Result<Cow<'a, Values>, VOTableError> {
+    if values.ref_.is_none() {
+      return Ok(Cow::Borrowed(values));
+    }
+    let mut resolved = values.clone();
+    let mut depth = 0;
+    while let Some(id) = resolved.ref_.take() {
+      depth += 1;
+      if depth > MAX_REF_DEPTH {
+        return Err(VOTableError::Custom(format!(
+          "Too many successive VALUES references (cycle?) starting from '{}'",
+          values.ref_.as_deref().unwrap_or_default()
+        )));
+      }
+      let referenced = self
+        .get(&id)
+        .ok_or_else(|| VOTableError::Custom(format!("Referenced VALUES '{}' not found", id)))?;
+      resolved.inherit_from(referenced);
+    }
+    Ok(Cow::Owned(resolved))
+  }
+
+  /// Returns the given field with its `VALUES` reference resolved (borrowed if the field has no
+  /// `VALUES` or if its `VALUES` has no `ref`).
+  pub fn resolve_field<'a>(&self, field: &'a Field) -> Result<Cow<'a, Field>, VOTableError> {
+    match &field.values {
+      Some(values) if values.ref_.is_some() => {
+        let resolved = self.resolve(values)?.into_owned();
+        let mut field = field.clone();
+        field.values = Some(resolved);
+        Ok(Cow::Owned(field))
+      }
+      _ => Ok(Cow::Borrowed(field)),
+    }
+  }
+}
+
+impl Values {
+  /// Set the attributes and sub-elements not set in this `VALUES` from the given referenced
+  /// `VALUES`, and replace the `ref` by the one of the referenced `VALUES` (if any).
+  fn inherit_from(&mut self, referenced: &Values) {
+    if self.type_.is_none() {
+      self.type_.clone_from(&referenced.type_);
+    }
+    if self.null.is_none() {
+      self.null.clone_from(&referenced.null);
+    }
+    if self.min.is_none() {
+      self.min.clone_from(&referenced.min);
+    }
+    if self.max.is_none() {
+      self.max.clone_from(&referenced.max);
+    }
+    if self.opts.is_empty() {
+      self.opts.clone_from(&referenced.opts);
+    }
+    self.ref_.clone_from(&referenced.ref_);
+  }
+
+  /// Tells whether this `VALUES` has the same content as the given one, ignoring the `ID`.
+  fn same_content(&self, other: &Values) -> bool {
+    self.type_ == other.type_
+      && self.null == other.null
+      && self.ref_ == other.ref_
+      && self.min == other.min
+      && self.max == other.max
+      && self.opts == other.opts
+  }
+}
+
+/// Replace, in the whole VOTable, the `VALUES` having a `ref` by the resolved `VALUES`
+/// (the `ID` of the referencing `VALUES` are preserved).
+/// # Errors
+/// If a referenced `VALUES` is not found, or in case of a reference cycle.
+pub fn inline_values_refs<C: TableDataContent>(
+  votable: &mut VOTable<C>,
+) -> Result<(), VOTableError> {
+  let registry = ValuesRegistry::from_votable(votable);
+  votable.visit(&mut FieldVisitor::fields_and_params(|field: &mut Field| {
+    if let Some(values) = &mut field.values
+      && let Cow::Owned(resolved) = registry.resolve(values)?
+    {
+      *values = resolved;
+    }
+    Ok(())
+  }))
+}
+
+/// Replace, in each `TABLE`, the `VALUES` of a `FIELD` identical to the `VALUES` of a previous
+/// `FIELD` of the same `TABLE` by a reference to this previous `VALUES` (an `ID` is generated if
+/// the previous `VALUES` has none).
+/// References are not shared across tables (nor with `PARAM`s) since they are resolved
+/// table by table when building the schema of a table (see [TableSchema](crate::impls::TableSchema)).
+/// `VALUES` having an `ID` (possibly referenced) and empty `VALUES` are left unchanged.
+/// Returns the number of replaced `VALUES`.
+pub fn dedup_values<C: TableDataContent>(votable: &mut VOTable<C>) -> usize {
+  let mut ids = Vec::new();
+  for_each_field(votable, &mut |field| {
+    if let Some(id) = field.values.as_ref().and_then(|values| values.id.clone()) {
+      ids.push(id);
+    }
+  });
+  // First pass: group the FIELDs (by index in document order) having the same VALUES in a table
+  let mut groups: Vec<Vec<usize>> = Vec::new();
+  let mut n_fields = 0;
+  {
+    // Distinct VALUES of the current table, with the index of their group
+    let mut distinct: Vec<(&Values, usize)> = Vec::new();
+    walk_votable(votable, &mut |meta| match meta {
+      Meta::TableStart(_) => distinct.clear(),
+      Meta::Field(field) => {
+        let values = field
+          .values
+          .as_ref()
+          .filter(|values| values.id.is_none() && **values != Values::default());
+        if let Some(values) = values {
+          match distinct.iter().find(|(v, _)| v.same_content(values)) {
+            Some((_, i_group)) => groups[*i_group].push(n_fields),
+            None => {
+              distinct.push((values, groups.len()));
+              groups.push(vec![n_fields]);
+            }
+          }
+        }
+        n_fields += 1;
+      }
+      _ => (),
+    });
+  }
+  // Generate the IDs of the duplicated VALUES: the first FIELD of a group gets the ID,
+  // the other ones a reference to it
+  let new_id = |i: usize| format!("_values_{}", i);
+  let mut i_id = 0;
+  let mut n_dup = 0;
+  let mut changes: Vec<Option<(String, bool)>> = vec![None; n_fields];
+  for group in groups.into_iter().filter(|group| group.len() > 1) {
+    let mut id = new_id(i_id);
+    while ids.contains(&id) {
+      i_id += 1;
+      id = new_id(i_id);
+    }
+    i_id += 1;
+    n_dup += group.len() - 1;
+    for (i, i_field) in group.into_iter().enumerate() {
+      changes[i_field] = Some((id.clone(), i == 0));
+    }
+  }
+  if n_dup == 0 {
+    return 0;
+  }
+  // Second pass: set the IDs and the references
+  let mut i_field = 0;
+  let mut set_refs = FieldVisitor::fields(|field: &mut Field| {
+    if let (Some(values), Some((id, is_first))) = (&mut field.values, changes[i_field].take()) {
+      if is_first {
+        values.id = Some(id);
+      } else {
+        *values = Values::new().set_ref(id);
+      }
+    }
+    i_field += 1;
+    Ok(())
+  });
+  // The closure never fails
+  let _ = votable.visit(&mut set_refs);
+  n_dup
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    data::DataElem,
+    datatype::Datatype,
+    impls::{mem::InMemTableDataRows, Schema, TableSchema, VOTableValue},
+    resource::{Resource, ResourceOrTable},
+    table::Table,
+    values::Max,
+    votable::{VOTableWrapper, Version},
+    VoidTableDataContent,
+  };
+
+  fn table_elems() -> Vec<TableElem> {
+    vec![
+      TableElem::Field(
+        Field::new("a", Datatype::Int).set_values(Values::new().set_id("flag").set_null("-1")),
+      ),
+      TableElem::Field(Field::new("b", Datatype::Int).set_values(Values::new().set_ref("flag"))),
+      TableElem::Field(
+        Field::new("c", Datatype::Int).set_values(
+          Values::new()
+            .set_ref("flag")
+            .set_null("-9")
+            .set_max(Max::new("100")),
+        ),
+      ),
+    ]
+  }
+
+  #[test]
+  fn test_values_ref_schema() {
+    let elems = table_elems();
+    let schema = TableSchema::try_from_table_elems(&elems).unwrap();
+    assert!(matches!(
+      schema.as_slice()[1],
+      Schema::Int { null: Some(-1) }
+    ));
+    assert!(matches!(
+      schema.as_slice()[2],
+      Schema::Int { null: Some(-9) }
+    ));
+    let schema = TableSchema::from(elems.as_slice());
+    assert!(matches!(
+      schema.as_slice()[1],
+      Schema::Int { null: Some(-1) }
+    ));
+
+    let mut elems = table_elems();
+    elems.remove(0);
+    assert!(TableSchema::try_from_table_elems(&elems).is_err());
+  }
+
+  #[test]
+  fn test_inline_and_dedup() {
+    let mut table = Table::<VoidTableDataContent>::new();
+    for elem in table_elems() {
+      match elem {
+        TableElem::Field(field) => table = table.push_field(field),
+        _ => unreachable!(),
+      }
+    }
+    let mut votable = VOTable::new(Version::V1_4, Resource::default().push_table(table));
+    inline_values_refs(&mut votable).unwrap();
+    let fields: Vec<Field> = votable
+      .get_first_table()
+      .unwrap()
+      .elems
+      .iter()
+      .filter_map(|e| match e {
+        TableElem::Field(f) => Some(f.clone()),
+        _ => None,
+      })
+      .collect();
+    assert_eq!(fields[1].null_value().map(|s| s.as_str()), Some("-1"));
+    assert_eq!(fields[2].null_value().map(|s| s.as_str()), Some("-9"));
+    assert!(fields[1].values.as_ref().unwrap().ref_.is_none());
+
+    let mut table = Table::<VoidTableDataContent>::new();
+    for name in ["x", "y", "z"] {
+      table =
+        table.push_field(Field::new(name, Datatype::Int).set_values(Values::new().set_null("-99")));
+    }
+    let mut votable = VOTable::new(Version::V1_4, Resource::default().push_table(table));
+    assert_eq!(dedup_values(&mut votable), 2);
+    let schema =
+      TableSchema::try_from_table_elems(&votable.get_first_table().unwrap().elems).unwrap();
+    for s in schema.iter() {
+      assert!(matches!(s, Schema::Int { null: Some(-99) }));
+    }
+  }
+
+  #[test]
+  fn test_dedup_round_trip() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VOTABLE version="1.4">
+  <RESOURCE>
+    <PARAM name="p" datatype="int" value="-99"><VALUES null="-99"/></PARAM>
+    <TABLE name="t1">
+      <FIELD name="a" datatype="int"><VALUES null="-99"><MAX value="10"/></VALUES></FIELD>
+      <FIELD name="b" datatype="int"><VALUES null="-99"><MAX value="10"/></VALUES></FIELD>
+      <FIELD name="c" datatype="int"><VALUES null="-99"/></FIELD>
+      <DATA><TABLEDATA>
+        <TR><TD>-99</TD><TD>1</TD><TD>-99</TD></TR>
+      </TABLEDATA></DATA>
+    </TABLE>
+    <TABLE name="t2">
+      <FIELD name="d" datatype="int"><VALUES null="-99"/></FIELD>
+      <FIELD name="e" datatype="int"><VALUES null="-99"/></FIELD>
+      <DATA><TABLEDATA>
+        <TR><TD>2</TD><TD>-99</TD></TR>
+      </TABLEDATA></DATA>
+    </TABLE>
+  </RESOURCE>
+</VOTABLE>"#;
+    fn tables_rows(votable: &VOTable<InMemTableDataRows>) -> Vec<Vec<Vec<VOTableValue>>> {
+      votable.resources[0]
+        .sub_elems
+        .iter()
+        .filter_map(|sub_elem| match &sub_elem.resource_or_table {
+          ResourceOrTable::Table(table) => match table.data.as_ref().map(|data| &data.data) {
+            Some(DataElem::TableData(data)) => Some(data.content.rows.clone()),
+            _ => None,
+          },
+          _ => None,
+        })
+        .collect()
+    }
+    let mut votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(xml)
+      .unwrap()
+      .unwrap();
+    let rows = tables_rows(&votable);
+    assert_eq!(
+      rows,
+      vec![
+        vec![vec![
+          VOTableValue::Null,
+          VOTableValue::Int(1),
+          VOTableValue::Null
+        ]],
+        vec![vec![VOTableValue::Int(2), VOTableValue::Null]],
+      ]
+    );
+    // a/b in t1 and d/e in t2 (the PARAM and c have no duplicate in their own scope)
+    assert_eq!(dedup_values(&mut votable), 2);
+    let xml = votable.wrap().to_ivoa_xml_string().unwrap();
+    assert_eq!(xml.matches("ref=\"").count(), 2);
+    let votable = VOTableWrapper::<InMemTableDataRows>::from_ivoa_xml_str(&xml)
+      .unwrap()
+      .unwrap();
+    assert_eq!(tables_rows(&votable), rows);
+  }
+}
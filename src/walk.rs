@@ -0,0 +1,356 @@
+//! Walk through the metadata of a VOTable (its `RESOURCE`s, `TABLE`s, `FIELD`s and `PARAM`s).
+//!
+//! [walk_votable] is the read-only counterpart of [VOTableVisitor] (which needs a mutable
+//! VOTable): the code looking for the `FIELD`s, `PARAM`s, `TABLE`s or `RESOURCE`s of a VOTable
+//! shares it instead of re-implementing its own traversal.
+//! To modify the `FIELD`s or `PARAM`s, use a [FieldVisitor].
+
+use crate::{
+  coosys::CooSys,
+  data::{fits::Fits, stream::Stream, tabledata::TableData, Data},
+  definitions::{Definitions, DefinitionsElem},
+  desc::Description,
+  error::VOTableError,
+  field::Field,
+  fieldref::FieldRef,
+  group::{Group, GroupElem, TableGroup, TableGroupElem},
+  info::Info,
+  link::Link,
+  param::Param,
+  paramref::ParamRef,
+  resource::{Resource, ResourceElem, ResourceOrTable},
+  table::{Table, TableElem},
+  timesys::TimeSys,
+  values::{Max, Min, Opt, Values},
+  votable::{VOTable, VOTableElem},
+  TableDataContent, VOTableVisitor, VoidTableDataContent,
+};
+
+#[cfg(feature = "mivot")]
+use crate::mivot::visitors::donothing::DoNothing;
+
+/// Element met while walking through the metadata of a VOTable, in document order.
+pub(crate) enum Meta<'a, C: TableDataContent> {
+  /// Followed by the `PARAM`s of the `RESOURCE` (possibly in `GROUP`s), by its sub-`RESOURCE`s
+  /// and `TABLE`s and then by [Meta::ResourceEnded].
+  #[cfg_attr(not(feature = "mivot"), allow(dead_code))]
+  ResourceStart(&'a Resource<C>),
+  ResourceEnded,
+  /// Followed by the `FIELD`s and `PARAM`s (possibly in `GROUP`s) of the `TABLE` and then by
+  /// [Meta::TableEnded].
+  #[cfg_attr(not(feature = "mivot"), allow(dead_code))]
+  TableStart(&'a Table<C>),
+  TableEnded,
+  Field(&'a Field),
+  Param(&'a Param),
+}
+
+/// Calls `f` on each `RESOURCE`, `TABLE`, `FIELD` and `PARAM` of the given VOTable, including the
+/// `PARAM`s of the (deprecated) `DEFINITIONS`.
+pub(crate) fn walk_votable<'a, C, F>(votable: &'a VOTable<C>, f: &mut F)
+where
+  C: TableDataContent,
+  F: FnMut(Meta<'a, C>),
+{
+  if let Some(definitions) = &votable.definitions {
+    for elem in definitions.elems.iter() {
+      if let DefinitionsElem::Param(param) = elem {
+        f(Meta::Param(param));
+      }
+    }
+  }
+  for elem in votable.elems.iter() {
+    match elem {
+      VOTableElem::Param(param) => f(Meta::Param(param)),
+      VOTableElem::Group(group) => walk_group(group, f),
+      _ => (),
+    }
+  }
+  for resource in votable.resources.iter() {
+    walk_resource(resource, f);
+  }
+}
+
+/// Calls `f` on the given `RESOURCE` and on each of its `RESOURCE`, `TABLE`, `FIELD` and `PARAM`.
+pub(crate) fn walk_resource<'a, C, F>(resource: &'a Resource<C>, f: &mut F)
+where
+  C: TableDataContent,
+  F: FnMut(Meta<'a, C>),
+{
+  f(Meta::ResourceStart(resource));
+  for elem in resource.elems.iter() {
+    match elem {
+      ResourceElem::Param(param) => f(Meta::Param(param)),
+      ResourceElem::Group(group) => walk_group(group, f),
+      _ => (),
+    }
+  }
+  for sub_elem in resource.sub_elems.iter() {
+    match &sub_elem.resource_or_table {
+      ResourceOrTable::Resource(resource) => walk_resource(resource, f),
+      ResourceOrTable::Table(table) => {
+        f(Meta::TableStart(table));
+        walk_table_elems(&table.elems, f);
+        f(Meta::TableEnded);
+      }
+    }
+  }
+  f(Meta::ResourceEnded);
+}
+
+/// Calls `f` on each `FIELD` and `PARAM` of the given table elements.
+pub(crate) fn walk_table_elems<'a, C, F>(elems: &'a [TableElem], f: &mut F)
+where
+  C: TableDataContent + 'a,
+  F: FnMut(Meta<'a, C>),
+{
+  fn walk_table_group<'a, C, F>(group: &'a TableGroup, f: &mut F)
+  where
+    C: TableDataContent + 'a,
+    F: FnMut(Meta<'a, C>),
+  {
+    for elem in group.elems.iter() {
+      match elem {
+        TableGroupElem::Param(param) => f(Meta::Param(param)),
+        TableGroupElem::TableGroup(group) => walk_table_group(group, f),
+        _ => (),
+      }
+    }
+  }
+  for elem in elems {
+    match elem {
+      TableElem::Field(field) => f(Meta::Field(field)),
+      TableElem::Param(param) => f(Meta::Param(param)),
+      TableElem::TableGroup(group) => walk_table_group(group, f),
+    }
+  }
+}
+
+fn walk_group<'a, C, F>(group: &'a Group, f: &mut F)
+where
+  C: TableDataContent + 'a,
+  F: FnMut(Meta<'a, C>),
+{
+  for elem in group.elems.iter() {
+    match elem {
+      GroupElem::Param(param) => f(Meta::Param(param)),
+      GroupElem::Group(group) => walk_group(group, f),
+      GroupElem::ParamRef(_) => (),
+    }
+  }
+}
+
+/// Calls `f` on all the `FIELD`s and on the field part of all the `PARAM`s of the given VOTable.
+pub(crate) fn for_each_field<'a, C, F>(votable: &'a VOTable<C>, f: &mut F)
+where
+  C: TableDataContent,
+  F: FnMut(&'a Field),
+{
+  walk_votable(votable, &mut |meta| match meta {
+    Meta::Field(field) => f(field),
+    Meta::Param(param) => f(&param.field),
+    _ => (),
+  });
+}
+
+/// Calls `f` on all the `FIELD`s and on the field part of all the `PARAM`s of the given table
+/// elements.
+pub(crate) fn walk_fields_and_params<'a, F>(elems: &'a [TableElem], f: &mut F)
+where
+  F: FnMut(&'a Field),
+{
+  walk_table_elems::<VoidTableDataContent, _>(elems, &mut |meta| match meta {
+    Meta::Field(field) => f(field),
+    Meta::Param(param) => f(&param.field),
+    _ => (),
+  });
+}
+
+/// [VOTableVisitor] calling a function on each `FIELD` (and possibly on the field part of each
+/// `PARAM`), in document order.
+pub(crate) struct FieldVisitor<F> {
+  f: F,
+  with_params: bool,
+}
+
+impl<F> FieldVisitor<F>
+where
+  F: FnMut(&mut Field) -> Result<(), VOTableError>,
+{
+  /// Calls `f` on each `FIELD` only.
+  pub(crate) fn fields(f: F) -> Self {
+    Self {
+      f,
+      with_params: false,
+    }
+  }
+
+  /// Calls `f` on each `FIELD` and on the field part of each `PARAM`.
+  pub(crate) fn fields_and_params(f: F) -> Self {
+    Self {
+      f,
+      with_params: true,
+    }
+  }
+}
+
+impl<C, F> VOTableVisitor<C> for FieldVisitor<F>
+where
+  C: TableDataContent,
+  F: FnMut(&mut Field) -> Result<(), VOTableError>,
+{
+  type E = VOTableError;
+
+  #[cfg(feature = "mivot")]
+  type M = DoNothing<VOTableError>;
+
+  fn visit_votable_start(&mut self, _votable: &mut VOTable<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_votable_ended(&mut self, _votable: &mut VOTable<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_description(&mut self, _description: &mut Description) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_coosys_start(&mut self, _coosys: &mut CooSys) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_coosys_ended(&mut self, _coosys: &mut CooSys) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_timesys(&mut self, _timesys: &mut TimeSys) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_group_start(&mut self, _group: &mut Group) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_group_ended(&mut self, _group: &mut Group) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  #[cfg(feature = "mivot")]
+  fn get_mivot_visitor(&mut self) -> Self::M {
+    DoNothing::new()
+  }
+
+  fn visit_table_group_start(&mut self, _group: &mut TableGroup) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_table_group_ended(&mut self, _group: &mut TableGroup) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_paramref(&mut self, _paramref: &mut ParamRef) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_fieldref(&mut self, _fieldref: &mut FieldRef) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_param_start(&mut self, param: &mut Param) -> Result<(), Self::E> {
+    if self.with_params {
+      (self.f)(&mut param.field)
+    } else {
+      Ok(())
+    }
+  }
+  fn visit_param_ended(&mut self, _param: &mut Param) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_field_start(&mut self, field: &mut Field) -> Result<(), Self::E> {
+    (self.f)(field)
+  }
+  fn visit_field_ended(&mut self, _field: &mut Field) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_info(&mut self, _info: &mut Info) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_definitions_start(&mut self, _definitions: &mut Definitions) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_definitions_ended(&mut self, _definitions: &mut Definitions) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_resource_start(&mut self, _resource: &mut Resource<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_resource_ended(&mut self, _resource: &mut Resource<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_post_info(&mut self, _info: &mut Info) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_resource_sub_elem_start(&mut self) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_resource_sub_elem_ended(&mut self) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_link(&mut self, _link: &mut Link) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_table_start(&mut self, _table: &mut Table<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_table_ended(&mut self, _table: &mut Table<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_data_start(&mut self, _data: &mut Data<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_data_ended(&mut self, _data: &mut Data<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_tabledata(&mut self, _table: &mut TableData<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_binary_stream(&mut self, _stream: &mut Stream<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_binary2_stream(&mut self, _stream: &mut Stream<C>) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_fits_start(&mut self, _fits: &mut Fits) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_fits_stream(
+    &mut self,
+    _stream: &mut Stream<VoidTableDataContent>,
+  ) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_fits_ended(&mut self, _fits: &mut Fits) -> Result<(), Self::E> {
+    Ok(())
+  }
+
+  fn visit_values_start(&mut self, _values: &mut Values) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_values_min(&mut self, _min: &mut Min) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_values_max(&mut self, _max: &mut Max) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_values_opt_start(&mut self, _opt: &mut Opt) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_values_opt_ended(&mut self, _opt: &mut Opt) -> Result<(), Self::E> {
+    Ok(())
+  }
+  fn visit_values_ended(&mut self, _values: &mut Values) -> Result<(), Self::E> {
+    Ok(())
+  }
+}